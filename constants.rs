@@ -6,8 +6,31 @@ pub const SEED_GLOBAL_CONFIG: &[u8] = b"global_config";
 #[constant]
 pub const SEED_MINT_REQUEST: &[u8] = b"mint_request";
 
+/// 平台手续费金库 PDA Seed (与奖金 Vault 分离，仅可通过 withdraw_fees_* 提取)
+#[constant]
+pub const SEED_FEE_VAULT: &[u8] = b"fee_vault";
+
+/// 中奖 SOL 托管 PDA Seed: [SEED_ESCROW, mint_request] (揭示时由 Vault 划入，领取/过期时释放)
+#[constant]
+pub const SEED_ESCROW: &[u8] = b"escrow";
+
+/// WSOL 支付临时账户 PDA Seed: [SEED_WSOL_PAYMENT, mint_request] (request_mint 内创建并解包关闭)
+#[constant]
+pub const SEED_WSOL_PAYMENT: &[u8] = b"wsol_payment";
+
 // ==================== Pyth Network Price Feeds ====================
 
+/// 默认价格最大有效期（秒）- 超过此时间的价格视为陈旧
+pub const DEFAULT_MAX_PRICE_AGE_SECONDS: u64 = 60;
+
+/// 价格有效期允许范围（秒）
+/// NOTE: Devnet 上 Pyth 更新频率较低，可由管理员调到上限 (1 小时)
+pub const MIN_PRICE_AGE_SECONDS: u64 = 10;
+pub const MAX_PRICE_AGE_SECONDS: u64 = 3600;
+
+/// 价格置信区间上限 (bps)：conf / price 超过 2% 视为不可靠报价
+pub const MAX_PRICE_CONF_BPS: u64 = 200;
+
 /// Pyth SOL/USD Price Feed ID
 pub const PYTH_SOL_USD_FEED_ID: [u8; 32] =
     hex_literal::hex!("ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d");
@@ -35,6 +58,17 @@ pub const VRF_PROGRAM_IDENTITY: Pubkey = pubkey!("9irBy75QS2BN81FUgXuHcjqceJJRuc
 /// 参考: ephemeral-vrf-sdk/src/consts.rs
 pub const ORACLE_QUEUE_DEVNET: Pubkey = pubkey!("Cuj97ggrhhidhbu39TijNVqE74xvKJ69gDervRUXAxGh");
 
+/// VRF Oracle Queue 白名单最大数量 (主 + 备用)
+pub const MAX_ORACLE_QUEUES: usize = 4;
+
+// ==================== 提取白名单 ====================
+
+/// 管理员提取接收地址白名单最大数量
+pub const MAX_WITHDRAW_RECIPIENTS: usize = 4;
+
+/// 新增提取接收地址的生效延迟 (48 小时)，管理员私钥泄露时留出响应窗口
+pub const WITHDRAW_RECIPIENT_DELAY_SECONDS: i64 = 48 * 60 * 60;
+
 // ==================== USDT Token Constants ====================
 
 /// USDT Mint Address on Devnet (使用官方 Mock USDT)
@@ -44,9 +78,12 @@ pub const USDT_MINT_DEVNET: Pubkey = pubkey!("CHLJch4C3fnh2jdE3DhNJRHmqpjaWzDzBK
 /// USDT Decimals (6 位精度)
 pub const USDT_DECIMALS: u32 = 6;
 
+/// 稳定币支付 Mint 白名单上限 (USDT / USDC / PYUSD ...)
+pub const MAX_PAYMENT_MINTS: usize = 4;
+
 // ==================== Business Logic Constants ====================
 
-/// Target USD amount for one mint (10 USD)
+/// Target USD amount for one card (10 USD)，唯一的票价基准，修改时须同步审计分层概率配置
 pub const TARGET_USD_AMOUNT: u64 = 10;
 
 /// SOL Decimals
@@ -55,16 +92,106 @@ pub const SOL_DECIMALS: u32 = 9;
 /// USD Precision (10^6)
 pub const USD_PRECISION: u64 = 1_000_000;
 
+/// 单张卡票价 (micro-USD)，支付换算 (稳定币按 Mint 精度，SOL / WSOL 按 Pyth 价格) 与
+/// utils/economics.rs 的编译期期望值断言共用此常量
+pub const TICKET_PRICE_MICRO_USD: u64 = TARGET_USD_AMOUNT * USD_PRECISION;
+
 // ==================== Claim Timeout Constants ====================
 
 /// Claim timeout in seconds (24 hours)
 pub const CLAIM_TIMEOUT_SECONDS: i64 = 24 * 60 * 60;
 
+/// 登记的 swap 失败次数达到该值后，SOL 领取按 Token 发放比例计算 (不低于 SOL 比例)
+pub const SWAP_FAILURE_RELIEF_ATTEMPTS: u8 = 3;
+
+/// 两次 report_swap_failure 登记的最小间隔 (秒)
+pub const SWAP_FAILURE_REPORT_INTERVAL_SECONDS: i64 = 60;
+
+// ==================== 揭示失败原因 (MintRequest.failure_reason) ====================
+
+/// 未失败
+pub const FAILURE_NONE: u8 = 0;
+
+/// 逐卡奖金累加溢出
+pub const FAILURE_REVEAL_OVERFLOW: u8 = 1;
+
+/// 逐卡计算结果不满足不变量 (总额非 REWARD_STEP 整数倍等)
+pub const FAILURE_REVEAL_INVARIANT: u8 = 2;
+
+/// RTP 调节后奖金溢出
+pub const FAILURE_MODULATION_OVERFLOW: u8 = 3;
+
+/// Vault 可用余额不足以托管中奖金额
+pub const FAILURE_INSUFFICIENT_VAULT: u8 = 4;
+
+/// 请求所用 Oracle Queue 已被移出白名单 (随机数来源不再可信)
+pub const FAILURE_QUEUE_REVOKED: u8 = 5;
+
+/// extend_claim_deadline 单笔交易最多顺延的请求数
+pub const MAX_DEADLINE_EXTENSIONS_PER_TX: usize = 20;
+
+/// 全局发放熔断统计窗口 (24 小时)
+pub const PAYOUT_WINDOW_SECONDS: i64 = 24 * 60 * 60;
+
 /// Request timeout for refund in seconds (45 seconds for testing)
 /// 用户在 Pending 状态超过此时间后可申请退款
 /// NOTE: 生产环境应改回 10 * 60 (10 分钟)
 pub const REQUEST_TIMEOUT_SECONDS: i64 = 45;
 
+/// 退款超时上限 (不超过领取期限 24 小时)
+pub const MAX_REQUEST_TIMEOUT_SECONDS: i64 = CLAIM_TIMEOUT_SECONDS;
+
+/// Pending 请求过期时间 (30 天)
+/// 超过此时间后任何人都可调用 crank_expire_pending 代为退款并关闭 PDA
+pub const PENDING_EXPIRY_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+/// 默认单笔请求卡数下限
+pub const DEFAULT_MIN_CARDS: u32 = 1;
+
+/// 默认单笔请求卡数上限
+pub const DEFAULT_MAX_CARDS: u32 = 100;
+
+/// 管理员可设置的单笔请求卡数上限的最大值
+pub const MAX_CARDS_LIMIT: u32 = 1_000;
+
+/// 单次 VRF 回调 / continue_reveal 最多处理的卡数 (回调计算预算由 MagicBlock 固定)
+pub const REVEAL_CARDS_PER_IX: u32 = 50;
+
+/// sweep_expired 单笔交易最多处理的请求数
+pub const MAX_SWEEP_REQUESTS: usize = 12;
+
+/// 默认 crank 小费 (lamports)，由 Vault 支付给调用者
+pub const DEFAULT_CRANK_TIP_LAMPORTS: u64 = 10_000;
+
+// ==================== Payout Ratio Constants ====================
+
+/// Basis points 基数 (10000 = 100%)
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// SOL 模式默认发放比例 (95%)
+pub const DEFAULT_SOL_PAYOUT_BPS: u16 = 9_500;
+
+/// Token 模式默认发放比例 (100%)
+pub const DEFAULT_TOKEN_PAYOUT_BPS: u16 = 10_000;
+
+/// 发放比例允许范围 [50%, 100%]
+pub const MIN_PAYOUT_BPS: u16 = 5_000;
+pub const MAX_PAYOUT_BPS: u16 = 10_000;
+
+/// 推荐返佣比例上限 (10%)
+pub const MAX_REFERRAL_BPS: u16 = 1_000;
+
+/// 默认奖励最小领取额: 1 USD (micro-USD)
+pub const DEFAULT_MIN_REWARDS_CLAIM_USD: u64 = 1_000_000;
+
+/// RTP 动态调节幅度上限 (±20%)
+pub const MAX_RTP_MODULATION_BPS: u16 = 2_000;
+
+/// 平台手续费收取时机: 铸造时 (票款整体进入 Vault，平台收益计入票款，领取不扣费)
+pub const FEE_POLICY_ON_MINT: u8 = 0;
+/// 平台手续费收取时机: 领取时 (按 platform_fee_bps 从中奖金额中扣除，转入手续费金库)
+pub const FEE_POLICY_ON_CLAIM: u8 = 1;
+
 // ==================== WSOL (Wrapped SOL) Constants ====================
 
 /// Native SOL Mint Address (WSOL)
@@ -76,22 +203,58 @@ pub const NATIVE_SOL_MINT: Pubkey = pubkey!("So111111111111111111111111111111111
 /// Jupiter V6 Program ID (Mainnet & Devnet)
 pub const JUPITER_PROGRAM_ID: Pubkey = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
 
+/// Jupiter 路由指令 discriminator 白名单最大数量
+pub const MAX_ROUTE_DISCRIMINATORS: usize = 6;
+
+/// Jupiter 路由 remaining_accounts 数量上限 (含 Jupiter Program)，超出时在分配任何缓冲区前拒绝
+pub const MAX_JUPITER_ROUTE_ACCOUNTS: usize = 64;
+
+/// Jupiter swap_data 长度上限 (bytes)
+pub const MAX_SWAP_DATA_LEN: usize = 1024;
+
 /// 默认滑点保护：3% (300 basis points)
 pub const DEFAULT_SLIPPAGE_BPS: u64 = 300;
 
+/// 用户预授权领取时允许设置的最大滑点 (10%)
+pub const MAX_CLAIM_SLIPPAGE_BPS: u16 = 1_000;
+
 // ==================== Raydium CPMM Constants (Task 1.20) ====================
 
 /// Raydium CPMM Swap Program ID (Mainnet)
-/// 用于校验 remaining_accounts 中传入的程序地址
 pub const RAYDIUM_CP_SWAP_PROGRAM: Pubkey = pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
 
 /// Raydium CPMM Swap Program ID (Devnet)
 pub const RAYDIUM_CP_SWAP_PROGRAM_DEVNET: Pubkey =
     pubkey!("DRaycpLY18LhpbydsBWbVJtxpNv9oXPgjRSfpF2bWpYb");
 
+/// initialize/migrate 写入 config.raydium_program 的默认值 (按构建环境选择，之后可通过 update_raydium_program 修改)
+#[cfg(feature = "mainnet")]
+pub const DEFAULT_RAYDIUM_CP_SWAP_PROGRAM: Pubkey = RAYDIUM_CP_SWAP_PROGRAM;
+#[cfg(not(feature = "mainnet"))]
+pub const DEFAULT_RAYDIUM_CP_SWAP_PROGRAM: Pubkey = RAYDIUM_CP_SWAP_PROGRAM_DEVNET;
+
 /// Raydium CPMM remaining_accounts 固定数量 (13 个账户)
 pub const RAYDIUM_SWAP_ACCOUNTS_COUNT: usize = 13;
 
+// ==================== Card NFT (Metaplex Core) ====================
+
+/// Metaplex Core Program ID (Mainnet & Devnet)
+pub const MPL_CORE_PROGRAM_ID: Pubkey = pubkey!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+
+/// 卡牌 Collection 更新权限 PDA Seed (铸造卡牌 NFT 时签名)
+#[constant]
+pub const SEED_COLLECTION_AUTHORITY: &[u8] = b"collection_authority";
+
+/// 卡牌 NFT 资产 PDA Seed: [SEED_CARD_ASSET, mint_request, card_index (u32 LE)]
+#[constant]
+pub const SEED_CARD_ASSET: &[u8] = b"card_asset";
+
+/// 单笔交易最多铸造的卡牌 NFT 数量
+pub const MAX_CARD_NFTS_PER_TX: u8 = 10;
+
+/// 卡牌 NFT 元数据 URI 前缀最大长度
+pub const MAX_CARD_URI_PREFIX_LEN: usize = 64;
+
 // ==================== Prize Pool Constants (Task 1.23) ====================
 
 /// 默认奖品池数量 (5 个 Raydium CPMM 池子)
@@ -106,6 +269,119 @@ pub const SEED_PRIZE_POOL: &[u8] = b"prize_pool";
 /// 奖品池最大数量
 pub const MAX_PRIZE_POOLS: usize = 50;
 
+/// 奖品池 Token 符号最大长度 (字节)
+pub const MAX_POOL_SYMBOL_LEN: usize = 10;
+
+/// 奖品池元数据 (logo 等) URI 最大长度 (字节)
+pub const MAX_POOL_METADATA_URI_LEN: usize = 100;
+
+/// 揭示时没有可选奖品池的哨兵索引 (此类请求仅可 SOL 领取)
+pub const NO_PRIZE_POOL: u8 = 255;
+
+/// 奖品池 swap_pool 变更的默认生效延迟 (24 小时)
+pub const DEFAULT_POOL_CHANGE_DELAY_SECONDS: u64 = 24 * 60 * 60;
+
+// ==================== Pause Flags ====================
+
+/// 暂停 request_mint
+pub const PAUSE_MINT: u8 = 1 << 0;
+/// 暂停 claim
+pub const PAUSE_CLAIM: u8 = 1 << 1;
+/// 暂停 refund
+pub const PAUSE_REFUND: u8 = 1 << 2;
+/// 暂停管理员提取 (withdraw_sol / withdraw_token)
+pub const PAUSE_ADMIN_WITHDRAW: u8 = 1 << 3;
+/// 所有已定义的暂停位
+pub const PAUSE_ALL: u8 = PAUSE_MINT | PAUSE_CLAIM | PAUSE_REFUND | PAUSE_ADMIN_WITHDRAW;
+
+// ==================== Health Check Violations ====================
+
+/// Vault 可用余额低于未领取中奖总额
+pub const HEALTH_VAULT_BELOW_LIABILITIES: u16 = 1 << 0;
+/// Pyth 价格超过 max_price_age_seconds
+pub const HEALTH_ORACLE_STALE: u16 = 1 << 1;
+/// Pyth 价格无效 (非正数或置信区间过宽)，无法换算负债
+pub const HEALTH_ORACLE_INVALID: u16 = 1 << 2;
+/// 没有活跃奖品池
+pub const HEALTH_NO_ACTIVE_POOLS: u16 = 1 << 3;
+/// 全局配置不满足 validate() 不变量
+pub const HEALTH_CONFIG_INVALID: u16 = 1 << 4;
+
+// ==================== Claim Receipt ====================
+
+/// 领取凭证 PDA Seed
+#[constant]
+pub const SEED_CLAIM_RECEIPT: &[u8] = b"receipt";
+
+// ==================== Claim Delegation ====================
+
+/// 领取委托 PDA Seed
+#[constant]
+pub const SEED_DELEGATE: &[u8] = b"delegate";
+
+/// 委托最长有效期 (7 天)
+pub const MAX_DELEGATION_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+// ==================== Co-Admin Approval ====================
+
+/// 待确认经济参数变更 PDA Seed (全局唯一，同一时间只允许一项待确认变更)
+#[constant]
+pub const SEED_PENDING_CHANGE: &[u8] = b"pending_change";
+
+/// 待确认变更的有效期 (72 小时)，超时后 co-admin 无法确认，只能取消
+pub const PENDING_CHANGE_TTL_SECONDS: i64 = 72 * 60 * 60;
+
+// ==================== Config Layout ====================
+
+/// 当前 IPFlowState 布局版本
+/// v1: 新增 sol_payments_enabled / usdt_payments_enabled (默认 true)，crank_tip_lamports 写入默认值
+/// v2: 新增 vault_pays_vrf_fee (默认 true)
+/// v3: 新增 claims_allowed_while_paused (默认 true)
+/// v4: 新增 min_swap_lamports / dust_swap_fallback_to_sol (默认 true)
+/// v5: 新增 pool_change_delay_seconds (默认 DEFAULT_POOL_CHANGE_DELAY_SECONDS)
+/// v6: pool_pending_claims 由按活跃位置改为按奖品池 index 存储
+pub const CONFIG_VERSION: u8 = 6;
+
+/// get_config 返回的 ConfigView 版本 (只追加字段，追加时递增)
+pub const CONFIG_VIEW_VERSION: u8 = 1;
+
+// ==================== User Stats ====================
+
+/// 用户统计 PDA Seed
+#[constant]
+pub const SEED_USER_STATS: &[u8] = b"user_stats";
+
+/// 用户奖励余额 PDA Seed
+#[constant]
+pub const SEED_REWARDS: &[u8] = b"rewards";
+
+// ==================== Epoch Stats ====================
+
+/// Epoch 统计 PDA Seed
+#[constant]
+pub const SEED_EPOCH: &[u8] = b"epoch";
+
+/// 默认 epoch 长度 (7 天)
+pub const DEFAULT_EPOCH_LENGTH_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+/// epoch 长度下限 (1 小时)
+pub const MIN_EPOCH_LENGTH_SECONDS: u64 = 60 * 60;
+
+/// emit_stats 最小调用间隔 (1 小时)
+pub const STATS_EMIT_INTERVAL_SECONDS: i64 = 60 * 60;
+
+// ==================== Leaderboard ====================
+
+/// 单笔中奖排行榜 PDA Seed
+#[constant]
+pub const SEED_LEADERBOARD: &[u8] = b"leaderboard";
+
+// ==================== VRF Queue Stats ====================
+
+/// VRF Oracle Queue 回调延迟统计 PDA Seed: [SEED_QUEUE_STATS, queue]
+#[constant]
+pub const SEED_QUEUE_STATS: &[u8] = b"queue_stats";
+
 // ==================== 分层概率配置 ====================
 // 目标分布 (单抽 10U):
 // - Tier 1 (15%): 5.0 - 7.0 USDC,   期望 6.0,  贡献 0.9
@@ -140,6 +416,14 @@ pub const TIER3_STEPS: u64 = 360; // 14.0, 14.1, ..., 49.9
 pub const TIER4_MIN_USD: u64 = 50_000_000; // 50.0 USDC (micro)
 pub const TIER4_STEPS: u64 = 500; // 50.0, 50.1, ..., 99.9
 
+/// Tier 覆盖表允许的理论 RTP 上限 (bps，单卡期望奖金 / 票价，25000 即 2.5 倍票价)
+pub const MAX_TIER_RTP_BPS: u64 = 25_000;
+
+/// 默认 Tier 累积阈值表 [Tier 1, Tier 2, Tier 3, Tier 4]，Tier k 的概率区间为 [t[k-2], t[k-1])
+/// 奖品池可通过 tier_override 替换为自定义阈值表 (各 Tier 奖金区间不变)
+pub const DEFAULT_TIER_THRESHOLDS: [u64; 4] =
+    [TIER1_THRESHOLD, TIER2_THRESHOLD, TIER3_THRESHOLD, PROB_PRECISION];
+
 // 保留旧常量用于测试兼容 (将被废弃)
 pub const TIER1_MAX_USD: u64 = 7_000_000; // 7.0 USDC
 pub const TIER2_MAX_USD: u64 = 14_000_000; // 14.0 USDC
@@ -80,4 +80,287 @@ pub enum IPFlowError {
     InvalidOracleQueue,
     #[msg("Jupiter swap input exceeded maximum allowed amount")]
     ExcessiveSwapInput,
+    #[msg("Prize pool still has pending claims or unrevealed requests that snapshotted it")]
+    PrizePoolHasPendingClaims,
+    // ==================== 请求状态细分错误码 ====================
+    #[msg("Request has not been revealed yet")]
+    RequestNotRevealed,
+    #[msg("Request has already been claimed")]
+    RequestAlreadyClaimed,
+    #[msg("Request is still pending VRF reveal")]
+    RequestStillPending,
+    #[msg("Request has failed and can only be refunded")]
+    RequestFailed,
+    #[msg("Request has already been revealed")]
+    RequestAlreadyRevealed,
+    #[msg("User stats account is required when dust accumulation is enabled")]
+    MissingUserStats,
+    // ==================== 细粒度暂停错误码 ====================
+    #[msg("Claims are paused")]
+    ClaimsPaused,
+    #[msg("Refunds are paused")]
+    RefundsPaused,
+    #[msg("Admin withdrawals are paused")]
+    WithdrawalsPaused,
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+    #[msg("Pending request has not reached the crank expiry window")]
+    RequestNotExpired,
+    #[msg("Vault account must be owned by the System Program")]
+    InvalidVaultOwner,
+    #[msg("Payment mode is currently disabled")]
+    PaymentModeDisabled,
+    #[msg("Payout bps out of allowed range")]
+    InvalidPayoutBps,
+    #[msg("Claim receipt account does not match create_receipt flag")]
+    ClaimReceiptMismatch,
+    // ==================== 领取委托错误码 ====================
+    #[msg("Delegation expiry must be in the future and within the maximum duration")]
+    InvalidDelegationExpiry,
+    #[msg("Delegate must differ from the user")]
+    InvalidDelegate,
+    #[msg("Signer is neither the user nor an active delegate")]
+    UnauthorizedClaimer,
+    #[msg("Delegation has expired")]
+    DelegationExpired,
+    #[msg("Max price age out of allowed range")]
+    InvalidPriceAge,
+    // ==================== 发放熔断错误码 ====================
+    #[msg("Daily payout cap reached, retry in the next window")]
+    DailyPayoutCapReached,
+    #[msg("Payout is not currently blocked by the daily cap")]
+    PayoutNotBlocked,
+    // ==================== 大额中奖审核错误码 ====================
+    #[msg("Large win is pending admin review")]
+    PendingReview,
+    #[msg("Request is not pending review")]
+    NotPendingReview,
+    // ==================== Oracle Queue 白名单错误码 ====================
+    #[msg("Oracle queue whitelist is full")]
+    OracleQueueListFull,
+    #[msg("Oracle queue is already whitelisted")]
+    OracleQueueAlreadyWhitelisted,
+    #[msg("Cannot remove the last whitelisted oracle queue")]
+    CannotRemoveLastOracleQueue,
+    // ==================== Pyth 价格账户校验错误码 ====================
+    #[msg("Pyth price account is not owned by the Pyth receiver program")]
+    InvalidPythAccountOwner,
+    #[msg("Pyth price feed id does not match SOL/USD")]
+    PythFeedIdMismatch,
+    #[msg("Pyth price confidence interval is too wide")]
+    PythPriceConfidenceTooWide,
+    // ==================== 支付 Mint 白名单错误码 ====================
+    #[msg("Payment mint whitelist is full")]
+    PaymentMintListFull,
+    #[msg("Payment mint is already whitelisted")]
+    PaymentMintAlreadyWhitelisted,
+    #[msg("Payment mint is not whitelisted")]
+    PaymentMintNotWhitelisted,
+    #[msg("Payment mint is disabled")]
+    PaymentMintDisabled,
+    // ==================== Vault Token 账户清理错误码 ====================
+    #[msg("Vault token account still holds tokens")]
+    VaultTokenAccountNotEmpty,
+    #[msg("Cannot close the vault account of a whitelisted payment mint or the vault WSOL account")]
+    CannotClosePaymentVaultAccount,
+    #[msg("Prize pool schedule is invalid (active_until must be after active_from)")]
+    InvalidPoolSchedule,
+    // ==================== Epoch 统计错误码 ====================
+    #[msg("Epoch statistics have not been started")]
+    EpochNotStarted,
+    #[msg("Epoch statistics have already been started")]
+    EpochAlreadyStarted,
+    #[msg("Current epoch has not elapsed yet")]
+    EpochNotElapsed,
+    #[msg("Current epoch stats account is required")]
+    EpochStatsRequired,
+    #[msg("Epoch length out of allowed range")]
+    InvalidEpochLength,
+    #[msg("Invalid RTP modulation settings")]
+    InvalidRtpModulation,
+    // ==================== Jupiter 配置错误码 ====================
+    #[msg("Route discriminator list must be non-empty, unique and within capacity")]
+    InvalidRouteDiscriminators,
+    // ==================== 配置校验错误码 ====================
+    #[msg("Platform fee must not exceed 10000 bps")]
+    InvalidPlatformFee,
+    #[msg("Admin must not be the default public key")]
+    InvalidAdmin,
+    #[msg("Global config violates an invariant")]
+    InvalidConfigState,
+    // ==================== 奖励余额错误码 ====================
+    #[msg("Referral bps exceeds the allowed maximum")]
+    InvalidReferralBps,
+    #[msg("Referrer must not be the requesting user")]
+    SelfReferral,
+    #[msg("Accrued rewards below the minimum claim size")]
+    RewardsBelowMinimum,
+    // ==================== 领取重入保护错误码 ====================
+    #[msg("Claim already in progress for this request")]
+    ClaimInProgress,
+    // ==================== 健康检查错误码 ====================
+    #[msg("Health check found violated invariants")]
+    HealthCheckFailed,
+    #[msg("Invalid vault token account passed to health check")]
+    InvalidHealthCheckAccount,
+    // ==================== 卡牌 NFT 错误码 ====================
+    #[msg("Invalid Metaplex Core program")]
+    InvalidCoreProgram,
+    #[msg("Card NFT minting is disabled or collection not created")]
+    CardNftsDisabled,
+    #[msg("Card NFT collection already created")]
+    CardCollectionExists,
+    #[msg("Card NFT metadata URI prefix too long")]
+    CardUriTooLong,
+    #[msg("Invalid card NFT asset account")]
+    InvalidCardAsset,
+    #[msg("All card NFTs already minted for this request")]
+    CardNftsComplete,
+    // ==================== 拆分领取错误码 ====================
+    #[msg("SOL portion must not exceed 10000 bps")]
+    InvalidSolPortion,
+    // ==================== 支付余额预检错误码 ====================
+    #[msg("Insufficient token balance for payment")]
+    InsufficientPaymentBalance,
+    #[msg("Insufficient SOL for payment and fees")]
+    InsufficientSolForPayment,
+    // ==================== 请求防重放错误码 ====================
+    #[msg("A mint request for this user already exists in this slot")]
+    DuplicateRequestInSlot,
+    // ==================== 批量清理错误码 ====================
+    #[msg("Sweep expects [mint_request, payer] pairs within the batch limit")]
+    InvalidSweepAccounts,
+    // ==================== 奖品池可用性错误码 ====================
+    #[msg("Selected prize pool is no longer available; claim with SOL payout")]
+    PrizePoolUnavailable,
+    // ==================== 卡数上下限错误码 ====================
+    #[msg("Card limits must satisfy 1 <= min_cards <= max_cards <= 1000")]
+    InvalidCardLimits,
+    // ==================== 分批揭示错误码 ====================
+    #[msg("Reveal is still in progress; call continue_reveal")]
+    RevealInProgress,
+    #[msg("Request is not being revealed")]
+    RevealNotInProgress,
+    // ==================== Swap 失败细分错误码 ====================
+    #[msg("Swap route is stale or no longer matches the provided accounts; refresh the quote")]
+    RouteExpired,
+    #[msg("Prize token output account or mint is missing")]
+    OutputAccountMissing,
+    #[msg("Swap output mint does not match the prize token mint")]
+    OutputMintMismatch,
+    #[msg("Vault WSOL input account is missing from the swap accounts")]
+    VaultWsolAccountMissing,
+    #[msg("Swap returned less than the minimum output")]
+    SwapReturnedTooLittle,
+    // ==================== 手续费金库错误码 ====================
+    #[msg("Recipient must be the configured fee recipient")]
+    InvalidFeeRecipient,
+    // ==================== 提取白名单错误码 ====================
+    #[msg("Recipient is not an activated withdrawal whitelist entry")]
+    WithdrawRecipientNotAllowed,
+    #[msg("Recipient is already in the withdrawal whitelist")]
+    WithdrawRecipientAlreadyWhitelisted,
+    #[msg("Withdrawal whitelist is full")]
+    WithdrawWhitelistFull,
+    // ==================== 领取期限顺延错误码 ====================
+    #[msg("New claim deadline must be later than the current deadline")]
+    ClaimDeadlineNotExtended,
+    #[msg("Too many requests in one claim deadline extension")]
+    TooManyDeadlineExtensions,
+    #[msg("Claim deadline extension accounts must be writable mint requests")]
+    InvalidDeadlineExtensionAccount,
+    // ==================== 中奖托管错误码 ====================
+    #[msg("Escrow account does not match the mint request escrow PDA")]
+    InvalidEscrowAccount,
+    // ==================== 配置视图错误码 ====================
+    #[msg("Config view data is truncated or malformed")]
+    InvalidConfigView,
+    // ==================== 奖品池展示信息错误码 ====================
+    #[msg("Prize pool symbol or metadata URI exceeds the maximum length")]
+    InvalidPoolMetadata,
+    // ==================== 奖品池 Tier 覆盖错误码 ====================
+    #[msg("Tier override must be non-decreasing and end at PROB_PRECISION")]
+    InvalidTierOverride,
+    // ==================== 配置迁移错误码 ====================
+    #[msg("Prize pool count is inconsistent with the on-chain prize pool accounts")]
+    InconsistentPoolCount,
+    // ==================== Raydium 路由账户错误码 ====================
+    #[msg("Vault WSOL input account must be writable")]
+    VaultWsolAccountNotWritable,
+    #[msg("Swap input token program must be the SPL Token program")]
+    InvalidSwapTokenProgram,
+    // ==================== 平台手续费策略错误码 ====================
+    #[msg("Fee policy must be FEE_POLICY_ON_MINT or FEE_POLICY_ON_CLAIM")]
+    InvalidFeePolicy,
+    #[msg("Fee vault account is required for claim-time platform fees")]
+    MissingFeeVault,
+    // ==================== WSOL 支付错误码 ====================
+    #[msg("Missing required token accounts for WSOL payment")]
+    MissingWsolAccounts,
+    #[msg("WSOL payment must use the native SOL mint")]
+    InvalidWsolMint,
+    #[msg("Missing token accounts for wrapped SOL payout")]
+    WrapPayoutAccountsMissing,
+    // ==================== RTP 错误码 ====================
+    #[msg("Tier override expected value exceeds the maximum allowed RTP")]
+    TierOverrideRtpTooHigh,
+    // ==================== 输出账户清理错误码 ====================
+    #[msg("Output token account still holds a balance")]
+    OutputAccountNotEmpty,
+    #[msg("Output token account was not created by this claim")]
+    OutputAccountNotCreatedByClaim,
+    // ==================== Swap 失败登记错误码 ====================
+    #[msg("Swap failure was reported too recently")]
+    SwapFailureReportTooSoon,
+    // ==================== 偿付能力核对错误码 ====================
+    #[msg("Solvency violated: vault balance is below its rent-exempt floor")]
+    SolvencyRentFloorBreached,
+    #[msg("Solvency violated: escrowed lamports recorded without outstanding liabilities")]
+    SolvencyEscrowWithoutLiabilities,
+    #[msg("Solvency violated: vault and escrow balances do not cover outstanding liabilities")]
+    SolvencyLiabilitiesUncovered,
+    // ==================== 退款来源错误码 ====================
+    #[msg("Refund must be sourced from the vault token account that received the payment")]
+    RefundSourceMismatch,
+    #[msg("Original vault token account can still cover the refund")]
+    OriginalRefundSourceFunded,
+    // ==================== Swap 下限错误码 ====================
+    #[msg("Payout is too small to swap; claim in SOL instead")]
+    PayoutTooSmallForSwap,
+    // ==================== 奖品池变更延迟错误码 ====================
+    #[msg("Prize pool has no pending swap pool change")]
+    NoPendingPoolUpdate,
+    #[msg("Pending prize pool change is not yet effective")]
+    PoolUpdateNotYetEffective,
+    // ==================== Vault 储备错误码 ====================
+    #[msg("Vault balance is below the minimum reserve; new mints are paused")]
+    VaultBelowReserve,
+    // ==================== 漏斗统计错误码 ====================
+    #[msg("Stats snapshot was emitted too recently")]
+    StatsEmitTooSoon,
+    // ==================== VRF 程序配置错误码 ====================
+    #[msg("VRF program does not match the configured VRF program")]
+    InvalidVrfProgram,
+    // ==================== Swap 负载错误码 ====================
+    #[msg("Swap route has too many accounts or too much instruction data")]
+    SwapPayloadTooLarge,
+    // ==================== 账户关闭错误码 ====================
+    #[msg("Account is already closed or not owned by this program")]
+    AccountAlreadyClosed,
+    // ==================== Co-Admin 审批错误码 ====================
+    #[msg("Economics change requires co-admin approval; stage it with stage_economics_change")]
+    CoAdminApprovalRequired,
+    #[msg("Co-admin is not configured")]
+    CoAdminNotConfigured,
+    #[msg("Co-admin must differ from the admin")]
+    InvalidCoAdmin,
+    #[msg("Pending economics change has expired")]
+    PendingChangeExpired,
+    #[msg("Prize pool does not match the pending economics change")]
+    PendingChangePoolMismatch,
+    // ==================== 领取预授权错误码 ====================
+    #[msg("Claim does not match the user's pre-authorized router or payout mode")]
+    ClaimPreauthorizationMismatch,
+    #[msg("Pre-authorized claim terms are invalid (zero expected output or slippage too high)")]
+    InvalidClaimPreauthorization,
 }
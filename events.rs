@@ -3,7 +3,10 @@
 // 事件用于链下索引和历史追溯
 // 由于 MintRequest PDA 在 claim 后关闭，事件日志成为唯一的历史记录来源
 
-use crate::state::{PaymentMode, PayoutMode, PoolType, SwapRouter};
+use crate::state::{
+    EconomicsChange, IPFlowState, PaymentMode, PayoutMode, PoolType, RefundTrigger, RewardSource,
+    SwapRouter,
+};
 use anchor_lang::prelude::*;
 
 /// Claim 完成事件
@@ -11,23 +14,154 @@ use anchor_lang::prelude::*;
 /// 在 MintRequest PDA 关闭前 emit，记录完整的领取信息供链下索引
 #[event]
 pub struct ClaimCompleted {
-    /// 用户地址
+    /// 用户地址 (受益人)
     pub user: Pubkey,
+    /// 支付本金的钱包 (代付时与 user 不同)
+    pub payer: Pubkey,
+    /// 实际签名领取的地址 (user 本人或会话密钥)
+    pub claimed_by: Pubkey,
     /// 中奖总额 (micro-USD, 精度 10^6)
     pub total_won_usd: u64,
-    /// 领取方式 (SOL 或 Token)
+    /// 领取方式 (SOL、Token 或 Split)
     pub payout_mode: PayoutMode,
     /// 支付方式 (SOL 或 USDT)
     pub payment_mode: PaymentMode,
     /// Swap 路由 (Token 模式时使用，SOL 模式为 None)
     /// Task 1.20: 新增字段，记录使用的 DEX 路由
     pub swap_router: Option<SwapRouter>,
-    /// 实际支付金额 (lamports 或 token amount)
+    /// 实际支付金额 (SOL 模式为发放 lamports，Token 模式为 swap 输入 lamports)
     pub paid_amount: u64,
+    /// Token 模式用户实际到账的奖品 Token 数量 (swap 前后余额差)，SOL 模式为 None
+    pub token_output_amount: Option<u64>,
+    /// Token 模式的奖品 Token Mint，SOL 模式为 None
+    pub output_mint: Option<Pubkey>,
     /// 购买的周卡数量
     pub amount_of_cards: u32,
     /// 领取时间戳
     pub timestamp: i64,
+    /// Token 模式 swap 前置校验失败后是否降级为 SOL 发放
+    pub fallback_used: bool,
+    /// 实际采用的发放比例 (bps)
+    pub payout_bps: u16,
+    /// 换算所用 Pyth 价格的 publish_time
+    pub price_publish_time: i64,
+    /// SOL 部分发放的 lamports (Token 模式为 0)
+    pub sol_paid_lamports: u64,
+    /// Token 部分 swap 输入的 lamports (SOL 模式为 0)
+    pub token_paid_amount: u64,
+    /// 领取时的暂停位 (审计暂停期间的领取)
+    pub pause_flags: u8,
+    /// 领取时的 claims_allowed_while_paused 策略
+    pub claims_allowed_while_paused: bool,
+    /// 请求创建时锁定的平台手续费收取时机 (FEE_POLICY_ON_MINT / FEE_POLICY_ON_CLAIM)
+    pub fee_policy: u8,
+    /// 本次领取扣除的平台手续费 (micro-USD，铸造时收费的请求为 0)
+    pub platform_fee_usd: u64,
+    /// 实际转入手续费金库的 lamports (金库不足免租时为 0，手续费暂留 Vault)
+    pub platform_fee_lamports: u64,
+    /// SOL 发放是否已包装为 WSOL 转入用户的 WSOL ATA
+    pub payout_wrapped: bool,
+    /// 创建请求时传入的合作方标签
+    pub memo: [u8; 32],
+    /// 奖品 Token ATA 是否由本次领取新建 (可通过 cleanup_output_account 回收)
+    pub token_account_created: bool,
+    /// WSOL ATA 是否由本次领取新建 (可通过 cleanup_output_account 回收)
+    pub wsol_account_created: bool,
+    /// SOL 部分是否因登记的 swap 失败次数达到阈值而按 Token 发放比例计算
+    pub swap_failure_relief: bool,
+    /// 是否按用户预授权的条款 (路由、预期输出、滑点) 执行
+    pub preauthorized: bool,
+}
+
+/// 抽奖请求创建事件
+///
+/// request_mint 完成支付并发起 VRF 请求后 emit
+#[event]
+pub struct MintRequested {
+    /// 用户地址 (受益人)
+    pub user: Pubkey,
+    /// 支付本金的钱包 (代付时与 user 不同)
+    pub payer: Pubkey,
+    /// MintRequest PDA 地址
+    pub mint_request: Pubkey,
+    /// 购买的卡片数量
+    pub amount_of_cards: u32,
+    /// 支付方式 (SOL 或 USDT)
+    pub payment_mode: PaymentMode,
+    /// 实际支付金额 (lamports 或 token amount)
+    pub paid_amount: u64,
+    /// VRF 请求 slot (PDA 种子)
+    pub vrf_request_slot: u64,
+    /// 使用的 VRF Oracle Queue
+    pub oracle_queue: Pubkey,
+    /// VRF 请求的 caller_seed (= hash(client_seed))
+    pub caller_seed_hash: [u8; 32],
+    /// 可证明公平承诺 = hash(client_seed || vrf_request_slot || mint_request)
+    pub commitment: [u8; 32],
+    /// 换算所用 Pyth 价格的 publish_time (USDT 支付时仅供参考)
+    pub price_publish_time: i64,
+    /// 创建时间戳
+    pub created_at: i64,
+    /// 支付本金对应的美元价值 (micro-USD)，用于统计实际收入
+    pub paid_usd: u64,
+    /// SOL 支付换算所用的 Pyth 价格 (稳定币支付为 0)
+    pub payment_price: i64,
+    /// payment_price 的指数
+    pub payment_price_expo: i32,
+    /// 合作方标签 (如活动 ID)，程序不解析，未传入时全 0
+    pub memo: [u8; 32],
+}
+
+/// Vault 初始化事件
+#[event]
+pub struct VaultInitialized {
+    pub vault: Pubkey,
+    pub bump: u8,
+    /// 初始化后 Vault 余额 (lamports)
+    pub lamports: u64,
+}
+
+/// 全局配置初始化事件 (完整初始配置快照)
+#[event]
+pub struct Initialized {
+    pub admin: Pubkey,
+    pub config: IPFlowState,
+}
+
+/// 退款完成事件 (用户超时退款或 crank 过期清理)
+#[event]
+pub struct RefundCompleted {
+    /// 奖金受益人
+    pub user: Pubkey,
+    /// 支付本金的钱包 (退款接收方)
+    pub payer: Pubkey,
+    pub mint_request: Pubkey,
+    pub payment_mode: PaymentMode,
+    /// 退款触发来源 (决定是否扣除 VRF 费用)
+    pub trigger: RefundTrigger,
+    /// 退还本金 (lamports 或 token amount)，已扣除 vrf_fee_deducted
+    pub refund_amount: u64,
+    /// 从 SOL 本金中扣除的 VRF 费用 (lamports)，失败与 crank 过期退款不扣除
+    pub vrf_fee_deducted: u64,
+    /// crank 调用者，None 表示用户自行退款
+    pub cranker: Option<Pubkey>,
+    /// 支付给 crank 调用者的小费 (lamports)
+    pub cranker_tip_lamports: u64,
+    pub timestamp: i64,
+    /// 创建请求时传入的合作方标签
+    pub memo: [u8; 32],
+}
+
+/// 管理员协助从备用 Vault Token 账户退款事件 (原收款账户已轮换或余额不足)
+#[event]
+pub struct RefundSourcedFromAlternate {
+    pub admin: Pubkey,
+    pub mint_request: Pubkey,
+    /// 支付时收款的 Vault Token 账户
+    pub original_vault_token_account: Pubkey,
+    /// 实际转出退款的 Vault Token 账户
+    pub alternate_vault_token_account: Pubkey,
+    pub refund_amount: u64,
 }
 
 // ==================== Prize Pool 事件 (Task 3.3) ====================
@@ -40,6 +174,12 @@ pub struct PrizePoolAdded {
     pub swap_pool: Pubkey,
     pub pool_type: PoolType,
     pub name: String,
+    /// 生效起始时间戳 (0 表示立即生效)
+    pub active_from: i64,
+    /// 失效时间戳 (0 表示长期有效)
+    pub active_until: i64,
+    pub symbol: Option<String>,
+    pub metadata_uri: Option<String>,
 }
 
 /// 奖品池移除事件（硬删除）
@@ -50,6 +190,47 @@ pub struct PrizePoolRemoved {
     pub swap_pool: Pubkey,
 }
 
+/// 奖品池强制移除事件（仍有待领取请求时由管理员强制删除）
+#[event]
+pub struct PrizePoolForceRemoved {
+    pub admin: Pubkey,
+    pub index: u8,
+    /// 删除时仍未领取的 Revealed 请求数量
+    pub pending_claims: u32,
+    /// 删除时快照了该池、尚未揭示完成的请求数量
+    pub pending_reveals: u32,
+}
+
+/// 奖品池 swap 统计更新事件 (Token 模式 claim 后)
+#[event]
+pub struct PoolVolumeUpdated {
+    pub index: u8,
+    /// 本次 swap 输入 lamports
+    pub swapped_lamports: u64,
+    pub total_swapped_lamports: u64,
+    pub total_claims: u64,
+    pub last_claim_ts: i64,
+}
+
+/// 活跃奖品池索引重建事件 (rebuild_active_pools)
+#[event]
+pub struct ActivePoolsRebuilt {
+    pub admin: Pubkey,
+    /// 新加入活跃列表的索引
+    pub added: Vec<u8>,
+    /// 从活跃列表移除的索引 (PDA 不存在或未传入)
+    pub removed: Vec<u8>,
+    pub active_pool_count: u8,
+}
+
+/// 奖品池分配统计同步事件 (flush_pool_stats)
+#[event]
+pub struct PoolStatsFlushed {
+    pub admin: Pubkey,
+    /// 已同步的奖品池索引
+    pub indices: Vec<u8>,
+}
+
 /// 奖品池更新事件
 #[event]
 pub struct PrizePoolUpdated {
@@ -57,4 +238,438 @@ pub struct PrizePoolUpdated {
     pub index: u8,
     pub old_swap_pool: Pubkey,
     pub new_swap_pool: Pubkey,
+    /// 更新后的 Token 符号与元数据 URI
+    pub symbol: Option<String>,
+    pub metadata_uri: Option<String>,
+}
+
+/// 奖品池 swap_pool 变更已暂存事件 (effective_at 之后才能由 apply_prize_pool_update 生效)
+#[event]
+pub struct PrizePoolSwapPoolStaged {
+    pub admin: Pubkey,
+    pub index: u8,
+    /// 生效前仍在使用的地址
+    pub current_swap_pool: Pubkey,
+    pub pending_swap_pool: Pubkey,
+    pub effective_at: i64,
+}
+
+/// 奖品池 swap_pool 暂存变更生效事件
+#[event]
+pub struct PrizePoolSwapPoolApplied {
+    pub admin: Pubkey,
+    pub index: u8,
+    pub old_swap_pool: Pubkey,
+    pub new_swap_pool: Pubkey,
+}
+
+/// 奖品池 Tier 覆盖表调整事件 (None 表示默认阈值表)
+///
+/// 期望值为单卡期望奖金 (micro-USD，不含保底与 RTP 调节)，便于链下评估活动成本
+#[event]
+pub struct PrizePoolTierOverrideUpdated {
+    pub admin: Pubkey,
+    pub index: u8,
+    pub old_tier_override: Option<[u64; 4]>,
+    pub new_tier_override: Option<[u64; 4]>,
+    pub old_expected_card_usd: u64,
+    pub new_expected_card_usd: u64,
+}
+
+/// 24 小时发放上限调整事件
+#[event]
+pub struct DailyPayoutCapUpdated {
+    pub admin: Pubkey,
+    pub old_cap_usd: u64,
+    /// 0 表示取消上限
+    pub new_cap_usd: u64,
+}
+
+/// 平台手续费收取时机调整事件
+#[event]
+pub struct FeePolicyUpdated {
+    pub admin: Pubkey,
+    pub old_fee_policy: u8,
+    /// FEE_POLICY_ON_MINT / FEE_POLICY_ON_CLAIM，仅影响新创建的请求
+    pub new_fee_policy: u8,
+}
+
+/// 领取因发放熔断被阻塞、期限顺延事件
+#[event]
+pub struct ClaimDeadlineExtended {
+    pub user: Pubkey,
+    pub mint_request: Pubkey,
+    /// 当前窗口结束时间戳 (可重试时间)
+    pub retry_after: i64,
+    pub claim_deadline: i64,
+}
+
+/// Token 领取 swap 失败登记事件 (relief_active 时 SOL 领取按 Token 发放比例计算)
+#[event]
+pub struct SwapFailureReported {
+    pub user: Pubkey,
+    pub mint_request: Pubkey,
+    pub failed_swap_attempts: u8,
+    pub relief_active: bool,
+    pub timestamp: i64,
+}
+
+// ==================== 大额中奖审核事件 ====================
+
+/// 大额中奖待审核事件 (VRF 回调时 emit)
+#[event]
+pub struct LargeWinFlagged {
+    pub user: Pubkey,
+    pub mint_request: Pubkey,
+    pub total_won_usd: u64,
+    pub threshold_usd: u64,
+}
+
+/// 大额中奖审核通过事件
+#[event]
+pub struct LargeWinApproved {
+    pub admin: Pubkey,
+    pub user: Pubkey,
+    pub mint_request: Pubkey,
+    pub total_won_usd: u64,
+}
+
+/// 大额中奖审核拒绝事件 (本金已退还支付者)
+#[event]
+pub struct LargeWinRejected {
+    pub admin: Pubkey,
+    pub user: Pubkey,
+    pub payer: Pubkey,
+    pub mint_request: Pubkey,
+    pub total_won_usd: u64,
+    pub refund_amount: u64,
+}
+
+// ==================== Epoch 统计事件 ====================
+
+/// epoch 结算事件 (roll_epoch 时 emit，包含结束 epoch 的汇总)
+#[event]
+pub struct EpochRolled {
+    pub epoch: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub collected_usd: u64,
+    pub paid_out_usd: u64,
+    pub refunded_usd: u64,
+    pub cards_sold: u64,
+    /// 发放 / 净收入 (bps)
+    pub rtp_bps: u64,
+}
+
+/// 转化漏斗计数快照 (emit_stats 定期发出，计数为累计值)
+#[event]
+pub struct StatsSnapshot {
+    pub count_requested: u64,
+    pub count_revealed: u64,
+    pub count_claimed_sol: u64,
+    pub count_claimed_token: u64,
+    pub count_refunded: u64,
+    pub count_expired: u64,
+    /// 上一次快照时间戳 (首次为 0)
+    pub previous_stats_ts: i64,
+    pub timestamp: i64,
+}
+
+// ==================== 排行榜事件 ====================
+
+/// 排行榜条目变更事件 (VRF 回调中新结果上榜时 emit)
+#[event]
+pub struct LeaderboardUpdated {
+    pub season: u64,
+    /// 名次 (0 为第一名)
+    pub rank: u8,
+    pub user: Pubkey,
+    pub total_won_usd: u64,
+    pub request_id: u64,
+    pub revealed_at: i64,
+}
+
+/// 排行榜重置事件 (新赛季开始)
+#[event]
+pub struct LeaderboardReset {
+    pub admin: Pubkey,
+    pub season: u64,
+}
+
+// ==================== VRF Queue 统计事件 ====================
+
+/// Oracle Queue 回调延迟统计清零事件 (reset_queue_stats)
+#[event]
+pub struct QueueStatsReset {
+    pub admin: Pubkey,
+    pub queue: Pubkey,
+    /// 清零前的回调次数
+    pub count: u64,
+    /// 清零前的累计延迟 (秒)
+    pub total_latency_seconds: u64,
+    /// 清零前的最大延迟 (秒)
+    pub max_latency_seconds: u64,
+}
+
+// ==================== Vault Token 账户清理事件 ====================
+
+/// Vault 空 Token 账户关闭事件 (租金退还给管理员指定地址)
+#[event]
+pub struct VaultTokenAccountClosed {
+    pub admin: Pubkey,
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    /// 回收的租金 (lamports)
+    pub reclaimed_lamports: u64,
+}
+
+// ==================== 用户输出账户清理事件 ====================
+
+/// 领取时新建的用户空 Token 账户关闭事件 (租金退还给用户)
+#[event]
+pub struct OutputAccountClosed {
+    pub user: Pubkey,
+    pub request_id: u64,
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    /// 回收的租金 (lamports)
+    pub reclaimed_lamports: u64,
+}
+
+// ==================== Jupiter 配置事件 ====================
+
+/// Jupiter Program ID 更新事件
+#[event]
+pub struct JupiterProgramUpdated {
+    pub admin: Pubkey,
+    pub old_program: Pubkey,
+    pub new_program: Pubkey,
+}
+
+/// MagicBlock VRF 程序 / 回调身份更新事件
+#[event]
+pub struct VrfProgramUpdated {
+    pub admin: Pubkey,
+    pub old_program: Pubkey,
+    pub new_program: Pubkey,
+    pub old_identity: Pubkey,
+    pub new_identity: Pubkey,
+}
+
+/// Jupiter 路由指令 discriminator 白名单更新事件
+#[event]
+pub struct RouteDiscriminatorsUpdated {
+    pub admin: Pubkey,
+    pub discriminators: Vec<[u8; 8]>,
+}
+
+// ==================== 奖励余额事件 ====================
+
+/// 奖励累积事件
+#[event]
+pub struct RewardsAccrued {
+    pub user: Pubkey,
+    pub source: RewardSource,
+    /// 本次累积金额 (micro-USD)
+    pub amount_usd: u64,
+    /// 累积后的待领取余额 (micro-USD)
+    pub accrued_usd: u64,
+}
+
+/// 奖励领取事件
+#[event]
+pub struct RewardsClaimed {
+    pub user: Pubkey,
+    /// 领取金额 (micro-USD)
+    pub amount_usd: u64,
+    /// 实际发放 lamports
+    pub lamports: u64,
+}
+
+/// 卡牌 Collection 创建事件
+#[event]
+pub struct CardCollectionCreated {
+    pub admin: Pubkey,
+    pub collection: Pubkey,
+}
+
+/// 卡牌 NFT 批量铸造事件
+#[event]
+pub struct CardNftsMinted {
+    pub user: Pubkey,
+    pub mint_request: Pubkey,
+    pub request_id: u64,
+    /// 本批第一张卡的索引
+    pub first_card_index: u32,
+    /// 本批铸造数量
+    pub count: u32,
+    /// 累计已铸造数量
+    pub cards_minted: u32,
+}
+
+/// 已揭示请求超过领取期限被清理 (奖金作废)
+#[event]
+pub struct RevealedRequestExpired {
+    pub user: Pubkey,
+    /// 支付本金的钱包 (PDA 租金接收方)
+    pub payer: Pubkey,
+    pub mint_request: Pubkey,
+    /// 作废的中奖金额 (micro-USD)
+    pub total_won_usd: u64,
+    pub claim_deadline: i64,
+    pub cranker: Pubkey,
+    pub timestamp: i64,
+}
+
+/// 分批揭示进度事件 (keeper 据此继续调用 continue_reveal)
+#[event]
+pub struct RevealAdvanced {
+    pub mint_request: Pubkey,
+    /// 已处理卡数
+    pub cards_processed: u32,
+    pub amount_of_cards: u32,
+}
+
+/// 手续费金库提取事件
+#[event]
+pub struct FeesWithdrawn {
+    pub admin: Pubkey,
+    /// SOL 提取为 fee_recipient，Token 提取为其 Token 账户
+    pub recipient: Pubkey,
+    /// 提取的 Token Mint，SOL 为 None
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    /// 提取后手续费金库剩余可提取余额
+    pub remaining: u64,
+    pub timestamp: i64,
+}
+
+/// 提取白名单新增地址事件
+#[event]
+pub struct WithdrawRecipientAdded {
+    pub admin: Pubkey,
+    pub recipient: Pubkey,
+    /// 生效时间 (此前提取到该地址会被拒绝)
+    pub active_after: i64,
+}
+
+/// 提取白名单移除地址事件
+#[event]
+pub struct WithdrawRecipientRemoved {
+    pub admin: Pubkey,
+    pub recipient: Pubkey,
+}
+
+/// 提取被拒绝事件 (接收方不在白名单或尚未生效)
+///
+/// 交易随后失败，事件仅保留在失败交易的日志中，供监控告警
+#[event]
+pub struct WithdrawalRejected {
+    pub admin: Pubkey,
+    /// SOL 提取为接收地址，Token 提取为接收账户的 owner
+    pub recipient: Pubkey,
+    /// 提取的 Token Mint，SOL 为 None
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// 新请求被拒绝事件 (Vault 可用余额低于储备下限)
+///
+/// 交易随后失败，事件仅保留在失败交易的日志中，供看板统计被拒绝的需求
+#[event]
+pub struct MintRejected {
+    pub user: Pubkey,
+    pub amount_of_cards: u32,
+    /// Vault 可用余额 (lamports，扣除租金)
+    pub vault_lamports: u64,
+    pub min_vault_reserve_lamports: u64,
+    pub timestamp: i64,
+}
+
+/// 管理员顺延领取期限事件
+#[event]
+pub struct ClaimDeadlineExtendedByAdmin {
+    pub admin: Pubkey,
+    pub user: Pubkey,
+    pub mint_request: Pubkey,
+    pub old_deadline: i64,
+    pub new_deadline: i64,
+}
+
+/// VRF 程序身份 PDA 初始化事件
+#[event]
+pub struct ProgramIdentityInitialized {
+    pub identity: Pubkey,
+    pub bump: u8,
+    /// 初始化后余额 (lamports)
+    pub lamports: u64,
+}
+
+/// co-admin 变更事件 (new_co_admin 为零值表示取消两步审批)
+#[event]
+pub struct CoAdminUpdated {
+    pub admin: Pubkey,
+    pub old_co_admin: Pubkey,
+    pub new_co_admin: Pubkey,
+}
+
+/// 经济参数变更暂存事件
+#[event]
+pub struct EconomicsChangeStaged {
+    pub admin: Pubkey,
+    pub co_admin: Pubkey,
+    pub change: EconomicsChange,
+    pub staged_at: i64,
+    pub expires_at: i64,
+}
+
+/// 经济参数变更经 co-admin 确认并生效事件
+#[event]
+pub struct EconomicsChangeConfirmed {
+    /// 暂存变更的管理员
+    pub admin: Pubkey,
+    pub co_admin: Pubkey,
+    pub change: EconomicsChange,
+    pub staged_at: i64,
+    pub confirmed_at: i64,
+}
+
+/// 经济参数变更在有效期内被取消事件
+#[event]
+pub struct EconomicsChangeCancelled {
+    /// 取消人 (管理员或 co-admin)
+    pub cancelled_by: Pubkey,
+    pub change: EconomicsChange,
+    pub staged_at: i64,
+}
+
+/// 经济参数变更过期未确认事件 (关闭过期的 PendingChange 时发出)
+#[event]
+pub struct EconomicsChangeExpired {
+    pub staged_by: Pubkey,
+    pub change: EconomicsChange,
+    pub expires_at: i64,
+    /// 关闭账户的调用人
+    pub closed_by: Pubkey,
+}
+
+/// 用户预授权 Token 领取条款事件 (覆盖时再次发出)
+#[event]
+pub struct ClaimPreauthorized {
+    pub user: Pubkey,
+    pub mint_request: Pubkey,
+    pub router: SwapRouter,
+    pub expected_token_output: u64,
+    pub max_slippage_bps: u16,
+    pub timestamp: i64,
+}
+
+/// 用户清除预授权事件
+#[event]
+pub struct ClaimPreauthorizationCleared {
+    pub user: Pubkey,
+    pub mint_request: Pubkey,
+    pub timestamp: i64,
 }
@@ -0,0 +1,44 @@
+// ==================== 卡牌 NFT Collection 管理 ====================
+//
+// 管理员创建 Metaplex Core Collection，更新权限为程序 PDA (SEED_COLLECTION_AUTHORITY)，
+// 之后 mint_card_nfts 由该 PDA 签名把卡牌资产铸造进 Collection。
+
+use anchor_lang::prelude::*;
+
+use crate::errors::IPFlowError;
+use crate::events::CardCollectionCreated;
+use crate::utils::core_cpi;
+use crate::CreateCardCollection;
+
+/// 创建卡牌 Collection (每个部署仅一次)，创建后默认不启用铸造
+pub fn create_card_collection(
+    ctx: Context<CreateCardCollection>,
+    name: String,
+    uri: String,
+) -> Result<()> {
+    require!(
+        ctx.accounts.config.card_collection.collection == Pubkey::default(),
+        IPFlowError::CardCollectionExists
+    );
+
+    core_cpi::create_collection(
+        ctx.accounts.mpl_core_program.to_account_info(),
+        ctx.accounts.collection.to_account_info(),
+        ctx.accounts.collection_authority.to_account_info(),
+        ctx.accounts.admin.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        &name,
+        &uri,
+    )?;
+
+    let collection = ctx.accounts.collection.key();
+    ctx.accounts.config.card_collection.collection = collection;
+
+    emit!(CardCollectionCreated {
+        admin: ctx.accounts.admin.key(),
+        collection,
+    });
+
+    msg!("Card collection created: {}", collection);
+    Ok(())
+}
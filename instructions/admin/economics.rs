@@ -0,0 +1,425 @@
+// ==================== 经济参数两步审批 ====================
+//
+// 配置 co_admin 后，影响偿付能力的参数 (claim 发放比例、奖品池 Tier 覆盖与奖金上限、RTP 调节、保底阈值、
+// 推荐返佣比例、手续费收取时机) 不再由单个密钥修改:
+// 1. 管理员 stage_economics_change 将变更暂存到 PendingChange PDA
+// 2. co-admin 在 PENDING_CHANGE_TTL_SECONDS 内 confirm_economics_change，变更原子生效并关闭 PDA
+// 3. 有效期内管理员或 co-admin 可 cancel_economics_change 撤回；过期后任何人可关闭并发出过期事件
+//
+// 暂停、Oracle 队列等运维参数仍为单密钥操作。
+
+use anchor_lang::prelude::*;
+
+use crate::errors::IPFlowError;
+use crate::events::{
+    CoAdminUpdated, EconomicsChangeCancelled, EconomicsChangeConfirmed, EconomicsChangeExpired,
+    EconomicsChangeStaged,
+};
+use crate::instructions::admin::prize_pool::{
+    apply_max_win_usd, apply_tier_override, normalize_tier_override,
+};
+use crate::instructions::admin::settings::{
+    apply_fee_policy, apply_payout_bps, apply_pity_threshold, apply_referral_bps,
+    apply_rtp_modulation, check_fee_policy, check_payout_bps, check_referral_bps,
+    check_rtp_modulation,
+};
+use crate::state::{EconomicsChange, IPFlowState, PendingChange, PrizePoolAccount};
+use crate::{CancelEconomicsChange, ConfirmEconomicsChange, SetCoAdmin, StageEconomicsChange};
+
+/// 设置 co-admin
+///
+/// - 首次配置只需管理员签名；已配置 co-admin 时须由当前 co-admin 共同签名 (防止管理员单方面撤销审批)
+/// - new_co_admin 为零值表示取消两步审批，不能与管理员相同
+pub fn set_co_admin(ctx: Context<SetCoAdmin>, new_co_admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    check_co_admin_rotation(
+        config,
+        ctx.accounts.co_admin.as_ref().map(|signer| signer.key()),
+        new_co_admin,
+    )?;
+
+    let old_co_admin = config.co_admin;
+    config.co_admin = new_co_admin;
+
+    emit!(CoAdminUpdated {
+        admin: ctx.accounts.admin.key(),
+        old_co_admin,
+        new_co_admin,
+    });
+    msg!("Co-admin updated: {} -> {}", old_co_admin, new_co_admin);
+    Ok(())
+}
+
+/// 暂存经济参数变更 (仅管理员，须已配置 co-admin)
+///
+/// 暂存时即按生效规则校验参数，确认时再次校验 (期间其他配置可能已变化)
+pub fn stage_economics_change(
+    ctx: Context<StageEconomicsChange>,
+    change: EconomicsChange,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(
+        config.economics_requires_co_admin(),
+        IPFlowError::CoAdminNotConfigured
+    );
+    check_economics_change(config, &change)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let pending = &mut ctx.accounts.pending_change;
+    pending.staged_by = ctx.accounts.admin.key();
+    pending.change = change;
+    pending.staged_at = now;
+    pending.expires_at = PendingChange::expiry_for(now);
+    pending.bump = ctx.bumps.pending_change;
+
+    emit!(EconomicsChangeStaged {
+        admin: pending.staged_by,
+        co_admin: config.co_admin,
+        change,
+        staged_at: now,
+        expires_at: pending.expires_at,
+    });
+    msg!(
+        "Economics change staged: {:?}, expires_at={}",
+        change,
+        pending.expires_at
+    );
+    Ok(())
+}
+
+/// co-admin 确认暂存的经济参数变更并原子生效
+///
+/// Tier 覆盖与奖金上限变更须传入对应的奖品池账户
+pub fn confirm_economics_change(ctx: Context<ConfirmEconomicsChange>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let pending = &ctx.accounts.pending_change;
+    require!(!pending.is_expired(now), IPFlowError::PendingChangeExpired);
+
+    let change = pending.change;
+    let staged_by = pending.staged_by;
+    let staged_at = pending.staged_at;
+    let accounts = &mut *ctx.accounts;
+    let config = &mut accounts.config;
+
+    match change {
+        EconomicsChange::PayoutBps {
+            sol_payout_bps,
+            token_payout_bps,
+        } => apply_payout_bps(config, sol_payout_bps, token_payout_bps)?,
+        EconomicsChange::TierOverride {
+            pool_index,
+            tier_override,
+        } => {
+            let prize_pool = change_prize_pool(&mut accounts.prize_pool, pool_index)?;
+            apply_tier_override(config, prize_pool, tier_override, staged_by)?;
+        }
+        EconomicsChange::RtpModulation {
+            rtp_modulation_enabled,
+            vault_target_lamports,
+            max_modulation_bps,
+        } => apply_rtp_modulation(
+            config,
+            rtp_modulation_enabled,
+            vault_target_lamports,
+            max_modulation_bps,
+        )?,
+        EconomicsChange::PityThreshold { pity_threshold } => {
+            apply_pity_threshold(config, pity_threshold)
+        }
+        EconomicsChange::ReferralBps { referral_bps } => apply_referral_bps(config, referral_bps)?,
+        EconomicsChange::FeePolicy { fee_policy } => apply_fee_policy(config, fee_policy, staged_by)?,
+        EconomicsChange::MaxWinUsd {
+            pool_index,
+            max_win_usd,
+        } => {
+            let prize_pool = change_prize_pool(&mut accounts.prize_pool, pool_index)?;
+            apply_max_win_usd(config, prize_pool, max_win_usd);
+        }
+    }
+
+    emit!(EconomicsChangeConfirmed {
+        admin: staged_by,
+        co_admin: ctx.accounts.co_admin.key(),
+        change,
+        staged_at,
+        confirmed_at: now,
+    });
+    msg!("Economics change confirmed: {:?}", change);
+    Ok(())
+}
+
+/// 取消暂存的经济参数变更 (关闭 PendingChange，租金退还暂存人)
+///
+/// - 有效期内: 仅管理员或 co-admin，发出 EconomicsChangeCancelled
+/// - 已过期: 任何人可关闭，发出 EconomicsChangeExpired
+pub fn cancel_economics_change(ctx: Context<CancelEconomicsChange>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let pending = &ctx.accounts.pending_change;
+    let authority = ctx.accounts.authority.key();
+
+    if pending.is_expired(now) {
+        emit!(EconomicsChangeExpired {
+            staged_by: pending.staged_by,
+            change: pending.change,
+            expires_at: pending.expires_at,
+            closed_by: authority,
+        });
+        msg!("Economics change expired: {:?}", pending.change);
+        return Ok(());
+    }
+
+    require!(
+        can_cancel_change(&ctx.accounts.config, authority),
+        IPFlowError::Unauthorized
+    );
+    emit!(EconomicsChangeCancelled {
+        cancelled_by: authority,
+        change: pending.change,
+        staged_at: pending.staged_at,
+    });
+    msg!("Economics change cancelled: {:?}", pending.change);
+    Ok(())
+}
+
+/// 按生效规则校验待暂存的变更
+fn check_economics_change(config: &IPFlowState, change: &EconomicsChange) -> Result<()> {
+    match *change {
+        EconomicsChange::PayoutBps {
+            sol_payout_bps,
+            token_payout_bps,
+        } => check_payout_bps(sol_payout_bps, token_payout_bps),
+        EconomicsChange::TierOverride {
+            pool_index,
+            tier_override,
+        } => {
+            require!(
+                pool_index < config.prize_pool_count,
+                IPFlowError::InvalidPrizePoolIndex
+            );
+            normalize_tier_override(tier_override)?;
+            Ok(())
+        }
+        EconomicsChange::RtpModulation {
+            rtp_modulation_enabled,
+            vault_target_lamports,
+            max_modulation_bps,
+        } => check_rtp_modulation(rtp_modulation_enabled, vault_target_lamports, max_modulation_bps),
+        EconomicsChange::PityThreshold { .. } => Ok(()),
+        EconomicsChange::ReferralBps { referral_bps } => check_referral_bps(referral_bps),
+        EconomicsChange::FeePolicy { fee_policy } => check_fee_policy(fee_policy),
+        EconomicsChange::MaxWinUsd { pool_index, .. } => {
+            require!(
+                pool_index < config.prize_pool_count,
+                IPFlowError::InvalidPrizePoolIndex
+            );
+            Ok(())
+        }
+    }
+}
+
+/// 取出变更对应的奖品池账户 (须与暂存的 pool_index 一致)
+fn change_prize_pool<'a, 'info>(
+    prize_pool: &'a mut Option<Account<'info, PrizePoolAccount>>,
+    pool_index: u8,
+) -> Result<&'a mut Account<'info, PrizePoolAccount>> {
+    let prize_pool = prize_pool
+        .as_mut()
+        .ok_or(IPFlowError::PendingChangePoolMismatch)?;
+    require!(
+        prize_pool.index == pool_index,
+        IPFlowError::PendingChangePoolMismatch
+    );
+    Ok(prize_pool)
+}
+
+/// co-admin 轮换规则: 已配置时须由当前 co-admin 共同签名，新 co-admin 不能是管理员
+fn check_co_admin_rotation(
+    config: &IPFlowState,
+    co_admin_signer: Option<Pubkey>,
+    new_co_admin: Pubkey,
+) -> Result<()> {
+    if config.economics_requires_co_admin() {
+        require!(
+            co_admin_signer == Some(config.co_admin),
+            IPFlowError::Unauthorized
+        );
+    }
+    require!(new_co_admin != config.admin, IPFlowError::InvalidCoAdmin);
+    Ok(())
+}
+
+/// 有效期内可取消变更的签名人: 管理员或已配置的 co-admin
+fn can_cancel_change(config: &IPFlowState, authority: Pubkey) -> bool {
+    authority == config.admin
+        || (config.economics_requires_co_admin() && authority == config.co_admin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{
+        FEE_POLICY_ON_CLAIM, MAX_REFERRAL_BPS, MAX_RTP_MODULATION_BPS, PROB_PRECISION,
+    };
+
+    fn config_with_admins(admin: Pubkey, co_admin: Pubkey) -> IPFlowState {
+        let mut config = IPFlowState::zeroed();
+        config.admin = admin;
+        config.co_admin = co_admin;
+        config.prize_pool_count = 2;
+        config
+    }
+
+    #[test]
+    fn test_co_admin_rotation_requires_current_co_admin() {
+        let (admin, co_admin, next) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        // 首次配置只需管理员
+        let unset = config_with_admins(admin, Pubkey::default());
+        assert!(check_co_admin_rotation(&unset, None, co_admin).is_ok());
+
+        let config = config_with_admins(admin, co_admin);
+        assert_eq!(
+            check_co_admin_rotation(&config, None, Pubkey::default()).unwrap_err(),
+            error!(IPFlowError::Unauthorized)
+        );
+        assert_eq!(
+            check_co_admin_rotation(&config, Some(next), next).unwrap_err(),
+            error!(IPFlowError::Unauthorized)
+        );
+        assert!(check_co_admin_rotation(&config, Some(co_admin), next).is_ok());
+        assert_eq!(
+            check_co_admin_rotation(&config, Some(co_admin), admin).unwrap_err(),
+            error!(IPFlowError::InvalidCoAdmin)
+        );
+    }
+
+    #[test]
+    fn test_cancel_limited_to_admin_or_co_admin() {
+        let (admin, co_admin) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let config = config_with_admins(admin, co_admin);
+        assert!(can_cancel_change(&config, admin));
+        assert!(can_cancel_change(&config, co_admin));
+        assert!(!can_cancel_change(&config, Pubkey::new_unique()));
+
+        // 未配置 co-admin 时零地址不能匹配
+        let unset = config_with_admins(admin, Pubkey::default());
+        assert!(!can_cancel_change(&unset, Pubkey::default()));
+    }
+
+    #[test]
+    fn test_staged_change_validated_up_front() {
+        let config = config_with_admins(Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_eq!(
+            check_economics_change(
+                &config,
+                &EconomicsChange::PayoutBps {
+                    sol_payout_bps: 0,
+                    token_payout_bps: 9_500,
+                },
+            )
+            .unwrap_err(),
+            error!(IPFlowError::InvalidPayoutBps)
+        );
+        assert_eq!(
+            check_economics_change(
+                &config,
+                &EconomicsChange::TierOverride {
+                    pool_index: 2,
+                    tier_override: [0; 4],
+                },
+            )
+            .unwrap_err(),
+            error!(IPFlowError::InvalidPrizePoolIndex)
+        );
+        assert_eq!(
+            check_economics_change(
+                &config,
+                &EconomicsChange::TierOverride {
+                    pool_index: 1,
+                    tier_override: [0, 0, 0, PROB_PRECISION],
+                },
+            )
+            .unwrap_err(),
+            error!(IPFlowError::TierOverrideRtpTooHigh)
+        );
+        assert!(check_economics_change(
+            &config,
+            &EconomicsChange::TierOverride {
+                pool_index: 1,
+                tier_override: [0; 4],
+            },
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_config_changes_validated_up_front() {
+        let config = config_with_admins(Pubkey::new_unique(), Pubkey::new_unique());
+        let check = |change: EconomicsChange| check_economics_change(&config, &change);
+
+        assert_eq!(
+            check(EconomicsChange::RtpModulation {
+                rtp_modulation_enabled: true,
+                vault_target_lamports: 0,
+                max_modulation_bps: 1_000,
+            })
+            .unwrap_err(),
+            error!(IPFlowError::InvalidRtpModulation)
+        );
+        assert_eq!(
+            check(EconomicsChange::RtpModulation {
+                rtp_modulation_enabled: false,
+                vault_target_lamports: 0,
+                max_modulation_bps: MAX_RTP_MODULATION_BPS + 1,
+            })
+            .unwrap_err(),
+            error!(IPFlowError::InvalidRtpModulation)
+        );
+        assert!(check(EconomicsChange::RtpModulation {
+            rtp_modulation_enabled: true,
+            vault_target_lamports: 1_000_000_000,
+            max_modulation_bps: MAX_RTP_MODULATION_BPS,
+        })
+        .is_ok());
+
+        assert!(check(EconomicsChange::PityThreshold { pity_threshold: 0 }).is_ok());
+        assert_eq!(
+            check(EconomicsChange::ReferralBps {
+                referral_bps: MAX_REFERRAL_BPS + 1,
+            })
+            .unwrap_err(),
+            error!(IPFlowError::InvalidReferralBps)
+        );
+        assert!(check(EconomicsChange::ReferralBps {
+            referral_bps: MAX_REFERRAL_BPS,
+        })
+        .is_ok());
+        assert_eq!(
+            check(EconomicsChange::FeePolicy { fee_policy: 2 }).unwrap_err(),
+            error!(IPFlowError::InvalidFeePolicy)
+        );
+        assert!(check(EconomicsChange::FeePolicy {
+            fee_policy: FEE_POLICY_ON_CLAIM,
+        })
+        .is_ok());
+
+        assert_eq!(
+            check(EconomicsChange::MaxWinUsd {
+                pool_index: 2,
+                max_win_usd: 0,
+            })
+            .unwrap_err(),
+            error!(IPFlowError::InvalidPrizePoolIndex)
+        );
+        assert!(check(EconomicsChange::MaxWinUsd {
+            pool_index: 1,
+            max_win_usd: 100_000_000,
+        })
+        .is_ok());
+    }
+}
@@ -1,38 +1,158 @@
 use crate::Initialize;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{program::invoke, system_instruction};
-use anchor_lang::{AccountDeserialize, AccountSerialize};
+use anchor_lang::{AccountDeserialize, AccountSerialize, Discriminator};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use ephemeral_vrf_sdk::consts::{IDENTITY, VRF_PROGRAM_ID, VRF_PROGRAM_IDENTITY};
 
-use crate::constants::{ORACLE_QUEUE_DEVNET, REQUEST_TIMEOUT_SECONDS};
+use crate::constants::{
+    BPS_DENOMINATOR, CONFIG_VERSION, DEFAULT_CRANK_TIP_LAMPORTS, DEFAULT_MAX_CARDS, DEFAULT_POOL_CHANGE_DELAY_SECONDS, DEFAULT_MIN_CARDS, DEFAULT_EPOCH_LENGTH_SECONDS, DEFAULT_MAX_PRICE_AGE_SECONDS, DEFAULT_MIN_REWARDS_CLAIM_USD, DEFAULT_RAYDIUM_CP_SWAP_PROGRAM, FEE_POLICY_ON_MINT,
+    DEFAULT_SOL_PAYOUT_BPS, DEFAULT_TOKEN_PAYOUT_BPS, JUPITER_PROGRAM_ID, MAX_ORACLE_QUEUES, MAX_PAYMENT_MINTS, MAX_ROUTE_DISCRIMINATORS, ORACLE_QUEUE_DEVNET, PAUSE_MINT, REQUEST_TIMEOUT_SECONDS, SEED_FEE_VAULT, SEED_PRIZE_POOL, USDT_DECIMALS, USDT_MINT_DEVNET,
+};
 use crate::errors::IPFlowError;
+use crate::events::{Initialized, ProgramIdentityInitialized, VaultInitialized};
 use crate::state::global_config::MAX_PRIZE_POOLS;
-use crate::state::IPFlowState;
-use crate::MigrateConfig;
+use crate::state::global_config::{CollectionConfig, PaymentMintInfo};
+use crate::state::{IPFlowState, PrizePoolAccount};
+use crate::utils::DEFAULT_ROUTE_DISCRIMINATORS;
+use crate::instructions::user::epoch::init_epoch_stats;
+use crate::{MigrateConfig, StartEpoch};
 use crate::CloseConfig;
+use crate::InitVaultTokenAccount;
+use crate::InitVaultWsol;
+use crate::InitProgramIdentity;
 
 pub fn handler(ctx: Context<Initialize>, platform_fee_bps: u16) -> Result<()> {
+    require!(
+        platform_fee_bps as u64 <= BPS_DENOMINATOR,
+        IPFlowError::InvalidPlatformFee
+    );
+    require!(
+        ctx.accounts.admin.key() != Pubkey::default(),
+        IPFlowError::InvalidAdmin
+    );
+
     let config = &mut ctx.accounts.config;
     config.admin = ctx.accounts.admin.key(); // 管理员的公钥
     config.platform_fee_bps = platform_fee_bps; // 平台手续费，单位为 basis points (bps)
     config.is_paused = false; // 初始化时不暂停
+    config.pause_flags = 0; // 初始化时所有操作均不暂停
     config.pool_count = 0; // 初始池数量为 0
     config.prize_pool_count = 0; // Task 3.3: 初始为 0，表示下一个可用索引
     config.active_pool_count = 0; // Task 3.3: 初始无活跃池
     config.active_pool_indices = [255u8; MAX_PRIZE_POOLS]; // Task 3.3: 255 表示空位
+    config.pool_pending_claims = [0u32; MAX_PRIZE_POOLS]; // 初始无待领取请求
+    config.pool_pending_reveals = [0u32; MAX_PRIZE_POOLS]; // 初始无待揭示请求
+    config.active_pool_max_win_usd = [0u64; MAX_PRIZE_POOLS]; // 初始无奖金上限
+    config.active_pool_tier_overrides = [[0u64; 4]; MAX_PRIZE_POOLS]; // 初始无 Tier 覆盖
     config.total_collected = 0; // 初始总收集金额为 0
-    config.oracle_queue = ORACLE_QUEUE_DEVNET; // 默认 VRF Queue 白名单
+    config.oracle_queue = ORACLE_QUEUE_DEVNET; // 默认主 VRF Queue
+    config.oracle_queues = [Pubkey::default(); MAX_ORACLE_QUEUES];
+    config.oracle_queues[0] = ORACLE_QUEUE_DEVNET; // 默认 VRF Queue 白名单
+    config.oracle_queue_count = 1;
     config.request_timeout_seconds = REQUEST_TIMEOUT_SECONDS; // 默认退款超时
+    config.min_payout_lamports = 0; // 默认不限制最小发放额
+    config.dust_accumulation_enabled = false; // 默认向上取整 (house 承担零头)
+    config.crank_tip_lamports = DEFAULT_CRANK_TIP_LAMPORTS; // 默认 crank 小费
+    config.vault_usdt_account = Pubkey::default(); // 由 init_vault_token_account 写入
+    config.sol_payments_enabled = true; // 默认接受 SOL 支付
+    config.usdt_payments_enabled = true; // 默认接受 USDT 支付
+    config.config_version = CONFIG_VERSION;
+    config.sol_payout_bps = DEFAULT_SOL_PAYOUT_BPS; // 默认 SOL 发放 95%
+    config.token_payout_bps = DEFAULT_TOKEN_PAYOUT_BPS; // 默认 Token 发放 100%
+    config.vault_pays_vrf_fee = true; // 默认由 Vault 承担 VRF 费用
+    config.total_vrf_fees_lamports = 0;
+    config.max_price_age_seconds = DEFAULT_MAX_PRICE_AGE_SECONDS; // 默认 60 秒
+    config.vrf_fee_lamports = 0; // 默认退款不扣除 VRF 费用
+    config.payment_mints = [PaymentMintInfo::default(); MAX_PAYMENT_MINTS];
+    config.payment_mints[0] = default_payment_mint(); // 默认仅接受 USDT
+    config.payment_mint_count = 1;
+    config.current_epoch = 0;
+    config.epoch_start_ts = 0; // 由 start_epoch 启用统计
+    config.epoch_length_seconds = DEFAULT_EPOCH_LENGTH_SECONDS; // 默认 7 天
+    config.pity_threshold = 0; // 默认关闭保底
+    config.rtp_modulation_enabled = false; // 默认关闭 RTP 动态调节
+    config.vault_target_lamports = 0;
+    config.max_modulation_bps = 0;
+    seed_default_jupiter_routes(config); // Jupiter V6 Program 与路由指令
+    config.raydium_program = DEFAULT_RAYDIUM_CP_SWAP_PROGRAM; // 按构建环境 (mainnet feature) 选择
+    config.referral_bps = 0; // 默认关闭推荐返佣
+    config.min_rewards_claim_usd = DEFAULT_MIN_REWARDS_CLAIM_USD; // 默认 1 USD
+    config.outstanding_liabilities_usd = 0;
+    config.card_collection = CollectionConfig::default(); // 由 create_card_collection 创建
+    config.min_cards = DEFAULT_MIN_CARDS; // 默认 1-100 张
+    config.max_cards = DEFAULT_MAX_CARDS;
+    // 手续费金库: 0 字节 System 账户，首笔手续费转入时创建
+    let (_, fee_vault_bump) = Pubkey::find_program_address(&[SEED_FEE_VAULT], ctx.program_id);
+    config.fee_vault_bump = fee_vault_bump;
+    config.fee_recipient = ctx.accounts.admin.key(); // 默认手续费转给管理员，可通过 update_fee_recipient 修改
+    // 程序身份 PDA 的 bump (request_mint 直接使用，省去每次 find_program_address)
+    let (_, identity_bump) = Pubkey::find_program_address(&[IDENTITY], ctx.program_id);
+    config.identity_bump = identity_bump;
+    config.claims_allowed_while_paused = true; // 暂停期间默认仍允许领取已揭示的奖金
+    config.fee_policy = FEE_POLICY_ON_MINT; // 默认铸造时收取，领取不扣费
+    config.total_paid_out_usd = 0;
+    config.pool_assigned_usd = [0u64; MAX_PRIZE_POOLS]; // 初始无分配统计
+    config.pool_times_selected = [0u64; MAX_PRIZE_POOLS];
+    config.total_escrowed_lamports = 0;
+    config.min_swap_lamports = 0; // 默认不限制 swap 输入下限
+    config.dust_swap_fallback_to_sol = true; // 过小的 swap 默认降级为 SOL 发放
+    config.pool_change_delay_seconds = DEFAULT_POOL_CHANGE_DELAY_SECONDS; // swap_pool 变更默认延迟 24 小时生效
+    config.min_vault_reserve_lamports = 0; // 默认不限制 Vault 储备下限
+    config.vrf_program = VRF_PROGRAM_ID; // 默认 SDK 常量，可通过 update_vrf_program 修改
+    config.vrf_identity = VRF_PROGRAM_IDENTITY;
+    config.vault_wsol_account = Pubkey::default(); // 由 init_vault_wsol 写入
+    config.co_admin = Pubkey::default(); // 未配置 co-admin 时经济参数由管理员直接修改
 
     // 获取 vault 的 bump
-    let (_, vault_bump) = Pubkey::find_program_address(&[b"vault"], ctx.program_id);
+    let vault_bump = ctx.bumps.vault;
     config.vault_bump = vault_bump; // 设置 vault 的 bump, 用于 PDA 生成,vlault 是资金归集账户
 
+    // 创建 Vault: 0 字节 System 账户，由 admin 注资至免租金额
+    // 已有余额 (例如重新初始化) 时只补足差额
+    let vault_info = ctx.accounts.vault.to_account_info();
+    let required_lamports = Rent::get()?.minimum_balance(0);
+    let current_lamports = vault_info.lamports();
+    if current_lamports < required_lamports {
+        invoke(
+            &system_instruction::transfer(
+                ctx.accounts.admin.key,
+                vault_info.key,
+                required_lamports - current_lamports,
+            ),
+            &[
+                ctx.accounts.admin.to_account_info(),
+                vault_info.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+
+    emit!(VaultInitialized {
+        vault: vault_info.key(),
+        bump: vault_bump,
+        lamports: vault_info.lamports(),
+    });
+
+    let config = &ctx.accounts.config;
+    config.validate()?;
+    emit!(Initialized {
+        admin: config.admin,
+        config: IPFlowState::clone(config),
+    });
+
     Ok(())
 }
 
 /// 迁移/扩容全局配置账户
 /// Task 3.3: 增加 active_pool_count 和 active_pool_indices 字段的初始化
 /// CRITICAL FIX: 保留现有活跃池状态，避免迁移时丢失数据
+///
+/// prize_pool_count 须与链上奖品池一致，remaining_accounts:
+/// - [0]: 索引 prize_pool_count 的奖品池 PDA (须尚未创建)
+/// - [1..]: 现存奖品池 PDA (可选，任意顺序，兼容旧布局)，索引均须小于 prize_pool_count
+///
+/// 活跃列表中的索引同样须小于 prize_pool_count；删除产生的空洞不影响校验
 pub fn migrate_config(ctx: Context<MigrateConfig>, prize_pool_count: u8) -> Result<()> {
     let config_info = ctx.accounts.config.to_account_info();
     let data = config_info.try_borrow_data()?;
@@ -52,6 +172,20 @@ pub fn migrate_config(ctx: Context<MigrateConfig>, prize_pool_count: u8) -> Resu
 
     drop(data);
 
+    // 读取传入的奖品池 PDA (在扩容前完成，失败时不改变配置账户)
+    let (next_pool, existing_pools) = ctx
+        .remaining_accounts
+        .split_first()
+        .ok_or(IPFlowError::InconsistentPoolCount)?;
+    let (next_pool_key, _) =
+        Pubkey::find_program_address(&[SEED_PRIZE_POOL, &[prize_pool_count]], ctx.program_id);
+    require_keys_eq!(next_pool.key(), next_pool_key, IPFlowError::InconsistentPoolCount);
+    let next_pool_exists = next_pool.owner == ctx.program_id && !next_pool.data_is_empty();
+    let mut existing_indices = existing_pools
+        .iter()
+        .map(|info| existing_pool_index(info, ctx.program_id))
+        .collect::<Result<Vec<u8>>>()?;
+
     let new_space = 8 + IPFlowState::INIT_SPACE;
     let rent = Rent::get()?;
     let required_lamports = rent.minimum_balance(new_space);
@@ -99,19 +233,120 @@ pub fn migrate_config(ctx: Context<MigrateConfig>, prize_pool_count: u8) -> Resu
         // 首次迁移或无活跃池，初始化为默认值
         config_state.active_pool_count = 0;
         config_state.active_pool_indices = [255u8; MAX_PRIZE_POOLS];
+        config_state.pool_pending_claims = [0u32; MAX_PRIZE_POOLS];
+        config_state.active_pool_max_win_usd = [0u64; MAX_PRIZE_POOLS];
         msg!(
             "Migrate config: prize_pool_count={}, initialized active_pool_count=0",
             prize_pool_count
         );
     }
 
+    // 校验 prize_pool_count (活跃池 index 取上面保留后的列表)
+    let active_count = config_state.active_pool_count as usize;
+    existing_indices.extend_from_slice(&config_state.active_pool_indices[..active_count]);
+    check_prize_pool_count(prize_pool_count, next_pool_exists, &existing_indices)?;
+
     // 初始化新增配置字段（仅当为空时设置默认值）
     if config_state.oracle_queue == Pubkey::default() {
         config_state.oracle_queue = ORACLE_QUEUE_DEVNET;
     }
+    // 旧版单一 oracle_queue 并入白名单首位
+    if config_state.oracle_queue_count == 0 {
+        config_state.oracle_queues = [Pubkey::default(); MAX_ORACLE_QUEUES];
+        config_state.oracle_queues[0] = config_state.oracle_queue;
+        config_state.oracle_queue_count = 1;
+    }
+    // 旧版硬编码的 USDT 并入支付 Mint 白名单首位
+    if config_state.payment_mint_count == 0 {
+        config_state.payment_mints = [PaymentMintInfo::default(); MAX_PAYMENT_MINTS];
+        config_state.payment_mints[0] = default_payment_mint();
+        config_state.payment_mint_count = 1;
+    }
+    if config_state.epoch_length_seconds == 0 {
+        config_state.epoch_length_seconds = DEFAULT_EPOCH_LENGTH_SECONDS;
+    }
+    // 旧版硬编码的 Jupiter Program 与路由 discriminator 写入配置
+    if config_state.jupiter_program == Pubkey::default() {
+        seed_default_jupiter_routes(&mut config_state);
+    }
+    if config_state.raydium_program == Pubkey::default() {
+        config_state.raydium_program = DEFAULT_RAYDIUM_CP_SWAP_PROGRAM;
+    }
+    if config_state.min_rewards_claim_usd == 0 {
+        config_state.min_rewards_claim_usd = DEFAULT_MIN_REWARDS_CLAIM_USD;
+    }
+    if config_state.max_cards == 0 {
+        config_state.min_cards = DEFAULT_MIN_CARDS;
+        config_state.max_cards = DEFAULT_MAX_CARDS;
+    }
+    if config_state.fee_recipient == Pubkey::default() {
+        let (_, fee_vault_bump) = Pubkey::find_program_address(&[SEED_FEE_VAULT], ctx.program_id);
+        config_state.fee_vault_bump = fee_vault_bump;
+        config_state.fee_recipient = admin_key;
+    }
+    if config_state.identity_bump == 0 {
+        let (_, identity_bump) = Pubkey::find_program_address(&[IDENTITY], ctx.program_id);
+        config_state.identity_bump = identity_bump;
+    }
+    if config_state.vrf_program == Pubkey::default() {
+        config_state.vrf_program = VRF_PROGRAM_ID;
+    }
+    if config_state.vrf_identity == Pubkey::default() {
+        config_state.vrf_identity = VRF_PROGRAM_IDENTITY;
+    }
     if config_state.request_timeout_seconds == 0 {
         config_state.request_timeout_seconds = REQUEST_TIMEOUT_SECONDS;
     }
+    // 旧部署的 Vault USDT ATA 地址可直接派生
+    if config_state.vault_usdt_account == Pubkey::default() {
+        let (vault, _) = Pubkey::find_program_address(&[b"vault"], ctx.program_id);
+        config_state.vault_usdt_account = get_associated_token_address(&vault, &USDT_MINT_DEVNET);
+    }
+    if config_state.max_price_age_seconds == 0 {
+        config_state.max_price_age_seconds = DEFAULT_MAX_PRICE_AGE_SECONDS;
+    }
+    if config_state.sol_payout_bps == 0 {
+        config_state.sol_payout_bps = DEFAULT_SOL_PAYOUT_BPS;
+    }
+    if config_state.token_payout_bps == 0 {
+        config_state.token_payout_bps = DEFAULT_TOKEN_PAYOUT_BPS;
+    }
+    // v1: 支付方式开关默认开启 (布尔字段无法用零值判断，按版本号初始化)
+    //     crank 小费同样在此写入默认值，之后 0 表示管理员关闭小费，重复迁移不再覆盖
+    if config_state.config_version < 1 {
+        config_state.sol_payments_enabled = true;
+        config_state.usdt_payments_enabled = true;
+        config_state.crank_tip_lamports = DEFAULT_CRANK_TIP_LAMPORTS;
+    }
+    // v2: VRF 费用默认由 Vault 支付
+    if config_state.config_version < 2 {
+        config_state.vault_pays_vrf_fee = true;
+    }
+    // v3: 程序暂停期间默认仍允许领取已揭示的奖金
+    if config_state.config_version < 3 {
+        config_state.claims_allowed_while_paused = true;
+    }
+    // v4: 过小的 Token swap 默认降级为 SOL 发放
+    if config_state.config_version < 4 {
+        config_state.dust_swap_fallback_to_sol = true;
+    }
+    // v5: 奖品池 swap_pool 变更默认延迟生效
+    if config_state.config_version < 5 {
+        config_state.pool_change_delay_seconds = DEFAULT_POOL_CHANGE_DELAY_SECONDS;
+    }
+    // v6: 待领取计数由按活跃位置存储改为按奖品池 index 存储
+    if config_state.config_version < 6 {
+        config_state.pool_pending_claims = pending_claims_by_index(
+            &config_state.active_pool_indices[..active_count],
+            &config_state.pool_pending_claims,
+        );
+    }
+    config_state.config_version = CONFIG_VERSION;
+    // 旧版 is_paused 映射为 pause_flags 的 bit 0
+    if config_state.is_paused {
+        config_state.pause_flags |= PAUSE_MINT;
+    }
+    config_state.validate()?;
 
     let mut dst: &mut [u8] = &mut data_mut;
     config_state.try_serialize(&mut dst)?;
@@ -119,8 +354,153 @@ pub fn migrate_config(ctx: Context<MigrateConfig>, prize_pool_count: u8) -> Resu
     Ok(())
 }
 
+/// 启用 epoch 统计：创建当前 epoch 的 EpochStats 并记录开始时间 (仅可调用一次)
+///
+/// 启用后 request_mint / claim / refund 必须传入当前 EpochStats
+pub fn start_epoch(ctx: Context<StartEpoch>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let config = &mut ctx.accounts.config;
+    require!(!config.epochs_started(), IPFlowError::EpochAlreadyStarted);
+
+    init_epoch_stats(
+        &mut ctx.accounts.epoch_stats,
+        config.current_epoch,
+        now,
+        ctx.bumps.epoch_stats,
+    );
+    config.epoch_start_ts = now;
+
+    msg!("Epoch statistics started: epoch={}", config.current_epoch);
+    Ok(())
+}
+
+/// 默认支付 Mint: Devnet USDT
+fn default_payment_mint() -> PaymentMintInfo {
+    PaymentMintInfo {
+        mint: USDT_MINT_DEVNET,
+        decimals: USDT_DECIMALS as u8,
+        enabled: true,
+    }
+}
+
+/// 默认 Jupiter 配置: V6 Program 及 route/sharedAccountsRoute/exactOutRoute
+fn seed_default_jupiter_routes(config: &mut IPFlowState) {
+    config.jupiter_program = JUPITER_PROGRAM_ID;
+    config.allowed_route_discriminators = [[0u8; 8]; MAX_ROUTE_DISCRIMINATORS];
+    config.allowed_route_discriminators[..DEFAULT_ROUTE_DISCRIMINATORS.len()]
+        .copy_from_slice(&DEFAULT_ROUTE_DISCRIMINATORS);
+    config.route_discriminator_count = DEFAULT_ROUTE_DISCRIMINATORS.len() as u8;
+}
+
+/// 创建 Vault 对白名单支付 Mint 的 ATA (幂等，已存在则跳过)
+/// USDT 的 ATA 同时写入 config.vault_usdt_account 供旧客户端读取
+pub fn init_vault_token_account(ctx: Context<InitVaultTokenAccount>) -> Result<()> {
+    associated_token::create_idempotent(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer: ctx.accounts.admin.to_account_info(),
+            associated_token: ctx.accounts.vault_token_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+            mint: ctx.accounts.usdt_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+    ))?;
+
+    let vault_token_account = ctx.accounts.vault_token_account.key();
+    if ctx.accounts.usdt_mint.key() == USDT_MINT_DEVNET {
+        ctx.accounts.config.vault_usdt_account = vault_token_account;
+    }
+
+    msg!(
+        "Vault token account initialized: mint={}, account={}",
+        ctx.accounts.usdt_mint.key(),
+        vault_token_account
+    );
+    Ok(())
+}
+
+/// 创建 Vault 的 WSOL ATA 并登记到 config.vault_wsol_account (幂等，已存在则跳过)
+///
+/// 登记后 Jupiter / Raydium swap 路径只接受该账户作为 Vault 输入，不再扫描 remaining_accounts
+pub fn init_vault_wsol(ctx: Context<InitVaultWsol>) -> Result<()> {
+    associated_token::create_idempotent(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer: ctx.accounts.admin.to_account_info(),
+            associated_token: ctx.accounts.vault_wsol_account.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+            mint: ctx.accounts.wsol_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+    ))?;
+
+    let vault_wsol_account = ctx.accounts.vault_wsol_account.key();
+    ctx.accounts.config.vault_wsol_account = vault_wsol_account;
+
+    msg!("Vault WSOL account registered: {}", vault_wsol_account);
+    Ok(())
+}
+
+/// 初始化 VRF 程序身份 PDA (仅管理员，幂等)
+///
+/// 身份 PDA 仅作为 VRF 请求的签名者，当前无需持有余额；预先注资至免租金额并记录 bump，
+/// 以防 VRF SDK 日后要求签名账户已存在。旧部署可据此重新写入 bump。
+pub fn init_program_identity(ctx: Context<InitProgramIdentity>) -> Result<()> {
+    let identity_info = ctx.accounts.program_identity.to_account_info();
+    let required_lamports = Rent::get()?.minimum_balance(0);
+    let current_lamports = identity_info.lamports();
+    if current_lamports < required_lamports {
+        invoke(
+            &system_instruction::transfer(
+                ctx.accounts.admin.key,
+                identity_info.key,
+                required_lamports - current_lamports,
+            ),
+            &[
+                ctx.accounts.admin.to_account_info(),
+                identity_info.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+
+    let bump = ctx.bumps.program_identity;
+    ctx.accounts.config.identity_bump = bump;
+
+    emit!(ProgramIdentityInitialized {
+        identity: identity_info.key(),
+        bump,
+        lamports: identity_info.lamports(),
+    });
+
+    msg!(
+        "Program identity initialized: {} (bump={})",
+        identity_info.key(),
+        bump
+    );
+    Ok(())
+}
+
+/// co_admin 在 IPFlowState 原始数据中的偏移 (含 discriminator；其后仅追加了 pool_pending_reveals)
+const CO_ADMIN_OFFSET: usize = 8 + IPFlowState::INIT_SPACE - 4 * MAX_PRIZE_POOLS - 32;
+
+/// 从原始数据读取已配置的 co-admin (co_admin 字段之前的旧布局或零值时为 None)
+fn raw_co_admin(data: &[u8]) -> Option<Pubkey> {
+    let bytes: [u8; 32] = data
+        .get(CO_ADMIN_OFFSET..CO_ADMIN_OFFSET + 32)?
+        .try_into()
+        .ok()?;
+    let co_admin = Pubkey::new_from_array(bytes);
+    (co_admin != Pubkey::default()).then_some(co_admin)
+}
+
 /// 关闭全局配置账户（用于重新初始化）
 /// 将账户 lamports 转回 admin，并清零数据
+///
+/// 已配置 co-admin 时须由其共同签名：重新初始化会清空 co_admin，
+/// 否则管理员可借关闭配置单方面撤销经济参数的两步审批
 pub fn close_config(ctx: Context<CloseConfig>) -> Result<()> {
     let config_info = ctx.accounts.config.to_account_info();
     let data = config_info.try_borrow_data()?;
@@ -138,6 +518,12 @@ pub fn close_config(ctx: Context<CloseConfig>) -> Result<()> {
         admin_key == ctx.accounts.admin.key(),
         IPFlowError::Unauthorized
     );
+    if let Some(co_admin) = raw_co_admin(&data) {
+        require!(
+            ctx.accounts.co_admin.as_ref().map(|signer| signer.key()) == Some(co_admin),
+            IPFlowError::Unauthorized
+        );
+    }
 
     drop(data);
 
@@ -155,3 +541,124 @@ pub fn close_config(ctx: Context<CloseConfig>) -> Result<()> {
     msg!("Config account closed, lamports returned to admin");
     Ok(())
 }
+
+/// 校验迁移传入的 prize_pool_count:
+/// - 索引 prize_pool_count 处的 PDA 尚未创建 (否则 add_prize_pool 会与已有池冲突)
+/// - 已知存在的奖品池索引 (活跃列表及传入的 PDA) 均小于 prize_pool_count
+pub(crate) fn check_prize_pool_count(
+    prize_pool_count: u8,
+    next_pool_exists: bool,
+    existing_indices: &[u8],
+) -> Result<()> {
+    require!(!next_pool_exists, IPFlowError::InconsistentPoolCount);
+    require!(
+        existing_indices.iter().all(|&index| index < prize_pool_count),
+        IPFlowError::InconsistentPoolCount
+    );
+    Ok(())
+}
+
+/// 现存奖品池 PDA 的索引: 只读取 discriminator 之后的 index 字节并按种子校验地址，
+/// 不反序列化整个账户 (迁移前的旧布局同样适用)
+/// v6 迁移: 将按活跃位置存储的待领取计数改写为按奖品池 index 存储
+fn pending_claims_by_index(
+    active_indices: &[u8],
+    by_position: &[u32; MAX_PRIZE_POOLS],
+) -> [u32; MAX_PRIZE_POOLS] {
+    let mut by_index = [0u32; MAX_PRIZE_POOLS];
+    for (pos, &index) in active_indices.iter().enumerate() {
+        if let Some(count) = by_index.get_mut(index as usize) {
+            *count = by_position[pos];
+        }
+    }
+    by_index
+}
+
+fn existing_pool_index(info: &AccountInfo, program_id: &Pubkey) -> Result<u8> {
+    require_keys_eq!(*info.owner, *program_id, IPFlowError::InconsistentPoolCount);
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() > 8 && data[..8] == PrizePoolAccount::DISCRIMINATOR[..],
+        IPFlowError::InconsistentPoolCount
+    );
+    let index = data[8];
+    let (expected, _) = Pubkey::find_program_address(&[SEED_PRIZE_POOL, &[index]], program_id);
+    require_keys_eq!(info.key(), expected, IPFlowError::InconsistentPoolCount);
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn close_config_reads_co_admin_from_raw_layout() {
+        let mut config = IPFlowState::zeroed();
+        config.admin = Pubkey::new_unique();
+        let mut data = Vec::new();
+        config.try_serialize(&mut data).unwrap();
+        assert_eq!(raw_co_admin(&data), None);
+
+        let co_admin = Pubkey::new_unique();
+        config.co_admin = co_admin;
+        data.clear();
+        config.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + IPFlowState::INIT_SPACE);
+        assert_eq!(raw_co_admin(&data), Some(co_admin));
+
+        // 新增 pool_pending_reveals 前迁移的布局同样能读到 co_admin
+        let pre_pending_reveals = &data[..data.len() - 4 * MAX_PRIZE_POOLS];
+        assert_eq!(raw_co_admin(pre_pending_reveals), Some(co_admin));
+        // co_admin 字段之前的旧布局没有 co-admin
+        assert_eq!(raw_co_admin(&data[..CO_ADMIN_OFFSET]), None);
+    }
+
+    #[test]
+    fn pool_count_accepts_gaps_from_removals() {
+        // 已创建 0..=5，其中 1、3、4 已删除
+        assert!(check_prize_pool_count(6, false, &[0, 2, 5]).is_ok());
+        // 最后创建的池已删除时只能通过下一个 PDA 未创建来确认
+        assert!(check_prize_pool_count(6, false, &[0, 2]).is_ok());
+        assert!(check_prize_pool_count(0, false, &[]).is_ok());
+    }
+
+    #[test]
+    fn pool_count_rejects_inconsistent_input() {
+        // 过小: 现存池 5 的索引会被 add_prize_pool 复用
+        assert_eq!(
+            check_prize_pool_count(5, false, &[0, 2, 5]).unwrap_err(),
+            error!(IPFlowError::InconsistentPoolCount)
+        );
+        // 下一个索引的 PDA 已存在 (计数落后于实际创建数量)
+        assert_eq!(
+            check_prize_pool_count(6, true, &[0, 2, 5]).unwrap_err(),
+            error!(IPFlowError::InconsistentPoolCount)
+        );
+        assert!(check_prize_pool_count(0, false, &[0]).is_err());
+    }
+
+    #[test]
+    fn pending_claims_remapped_from_position_to_index() {
+        let mut by_position = [0u32; MAX_PRIZE_POOLS];
+        by_position[..3].copy_from_slice(&[4, 0, 7]);
+        let by_index = pending_claims_by_index(&[2, 5, 9], &by_position);
+        assert_eq!(by_index[2], 4);
+        assert_eq!(by_index[5], 0);
+        assert_eq!(by_index[9], 7);
+        assert_eq!(by_index.iter().sum::<u32>(), 11);
+    }
+
+    #[test]
+    fn identity_pda_rejects_incorrect_bump() {
+        let (identity, bump) = Pubkey::find_program_address(&[IDENTITY], &crate::ID);
+        assert_eq!(
+            Pubkey::create_program_address(&[IDENTITY, &[bump]], &crate::ID).unwrap(),
+            identity
+        );
+        // seeds 约束以 config.identity_bump 重新派生，错误的 bump 无法得到同一地址
+        for wrong_bump in [bump.wrapping_sub(1), bump.wrapping_add(1)] {
+            let derived = Pubkey::create_program_address(&[IDENTITY, &[wrong_bump]], &crate::ID);
+            assert!(derived.map_or(true, |key| key != identity));
+        }
+    }
+}
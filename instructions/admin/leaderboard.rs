@@ -0,0 +1,32 @@
+// ==================== 排行榜管理指令 ====================
+
+use anchor_lang::prelude::*;
+
+use crate::events::LeaderboardReset;
+use crate::state::{LeaderboardEntry, LEADERBOARD_SIZE};
+use crate::{InitLeaderboard, ResetLeaderboard};
+
+/// 创建排行榜 PDA (创建前 VRF 回调跳过排行榜更新)
+pub fn init_leaderboard(ctx: Context<InitLeaderboard>) -> Result<()> {
+    let leaderboard = &mut ctx.accounts.leaderboard;
+    leaderboard.season = 0;
+    leaderboard.entries = [LeaderboardEntry::default(); LEADERBOARD_SIZE];
+    leaderboard.bump = ctx.bumps.leaderboard;
+
+    msg!("Leaderboard initialized");
+    Ok(())
+}
+
+/// 清空排行榜并开启新赛季
+pub fn reset_leaderboard(ctx: Context<ResetLeaderboard>) -> Result<()> {
+    let leaderboard = &mut ctx.accounts.leaderboard;
+    leaderboard.reset();
+
+    emit!(LeaderboardReset {
+        admin: ctx.accounts.admin.key(),
+        season: leaderboard.season,
+    });
+
+    msg!("Leaderboard reset: season={}", leaderboard.season);
+    Ok(())
+}
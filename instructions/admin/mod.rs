@@ -1,7 +1,19 @@
+pub mod card_collection;
+pub mod economics;
 pub mod initialize;
+pub mod leaderboard;
 pub mod prize_pool;
+pub mod queue_stats;
+pub mod review;
+pub mod settings;
 pub mod withdraw;
 
+pub use card_collection::*;
+pub use economics::*;
 pub use initialize::*;
+pub use leaderboard::*;
 pub use prize_pool::*;
+pub use queue_stats::*;
+pub use review::*;
+pub use settings::*;
 pub use withdraw::*;
@@ -7,23 +7,49 @@
 //   读取 PDA[actual_index] → swap_pool
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke, system_instruction};
+use anchor_lang::{AccountDeserialize, AccountSerialize};
 
+use crate::constants::{
+    MAX_POOL_METADATA_URI_LEN, MAX_POOL_SYMBOL_LEN, MAX_TIER_RTP_BPS, SEED_PRIZE_POOL,
+    TICKET_PRICE_MICRO_USD,
+};
 use crate::errors::IPFlowError;
-use crate::events::{PrizePoolAdded, PrizePoolRemoved, PrizePoolUpdated};
+use crate::events::{
+    ActivePoolsRebuilt, PoolStatsFlushed, PrizePoolAdded, PrizePoolForceRemoved, PrizePoolRemoved,
+    PrizePoolSwapPoolApplied, PrizePoolSwapPoolStaged, PrizePoolTierOverrideUpdated,
+    PrizePoolUpdated,
+};
 use crate::state::global_config::MAX_PRIZE_POOLS;
-use crate::state::PoolType;
+use crate::state::{IPFlowState, PoolType, PrizePoolAccount};
+use crate::utils::{
+    compute_expected_value, is_valid_tier_thresholds, resolve_tier_thresholds, rtp_bps,
+    NO_TIER_OVERRIDE,
+};
 
 /// 添加奖品池
 ///
 /// 1. 创建 PrizePoolAccount PDA
-/// 2. 将新索引追加到 active_pool_indices
+/// 2. 将新索引追加到 active_pool_indices (同步写入奖金上限与排期镜像)
 /// 3. 更新 active_pool_count 和 prize_pool_count
+///
+/// symbol / metadata_uri 为可选展示信息，None 或空字符串表示不设置
+#[allow(clippy::too_many_arguments)]
 pub fn add_prize_pool(
     ctx: Context<crate::AddPrizePool>,
     swap_pool: Pubkey,
     pool_type: PoolType,
     name: String,
+    max_win_usd: u64,
+    active_from: i64,
+    active_until: i64,
+    symbol: Option<String>,
+    metadata_uri: Option<String>,
 ) -> Result<()> {
+    validate_schedule(active_from, active_until)?;
+    let symbol = normalize_display_field(symbol, MAX_POOL_SYMBOL_LEN)?;
+    let metadata_uri = normalize_display_field(metadata_uri, MAX_POOL_METADATA_URI_LEN)?;
+
     let config = &mut ctx.accounts.config;
     let prize_pool = &mut ctx.accounts.prize_pool;
 
@@ -31,10 +57,7 @@ pub fn add_prize_pool(
     let index = config.prize_pool_count;
 
     // 检查是否达到上限
-    require!(
-        (config.active_pool_count as usize) < MAX_PRIZE_POOLS,
-        IPFlowError::MaxPrizePoolsReached
-    );
+    check_pool_capacity(config.active_pool_count, config.prize_pool_count)?;
 
     // 初始化 PrizePoolAccount
     prize_pool.index = index;
@@ -42,10 +65,28 @@ pub fn add_prize_pool(
     prize_pool.pool_type = pool_type;
     prize_pool.name = name.clone();
     prize_pool.bump = ctx.bumps.prize_pool;
+    prize_pool.total_swapped_lamports = 0;
+    prize_pool.total_claims = 0;
+    prize_pool.last_claim_ts = 0;
+    prize_pool.max_win_usd = max_win_usd;
+    prize_pool.active_from = active_from;
+    prize_pool.active_until = active_until;
+    prize_pool.symbol = symbol.clone();
+    prize_pool.metadata_uri = metadata_uri.clone();
+    prize_pool.tier_override = None;
+    prize_pool.total_assigned_usd = 0;
+    prize_pool.times_selected = 0;
+    prize_pool.pending_swap_pool = None;
+    prize_pool.pending_swap_pool_effective_at = 0;
+    prize_pool.pending_claims = 0;
 
-    // 更新 Config: 添加到活跃索引列表末尾
+    // 更新 Config: 添加到活跃索引列表末尾 (index 永不复用，待领取计数必为 0)
     let active_pos = config.active_pool_count as usize;
     config.active_pool_indices[active_pos] = index;
+    config.active_pool_max_win_usd[active_pos] = max_win_usd;
+    config.active_pool_active_from[active_pos] = active_from;
+    config.active_pool_active_until[active_pos] = active_until;
+    config.active_pool_tier_overrides[active_pos] = NO_TIER_OVERRIDE;
     config.active_pool_count += 1;
     config.prize_pool_count += 1;
 
@@ -55,6 +96,10 @@ pub fn add_prize_pool(
         swap_pool,
         pool_type,
         name,
+        active_from,
+        active_until,
+        symbol,
+        metadata_uri,
     });
 
     msg!(
@@ -67,15 +112,52 @@ pub fn add_prize_pool(
     Ok(())
 }
 
+/// 新增奖品池的容量校验
+///
+/// 活跃池数量受 active_pool_indices 长度限制；index 永不复用，且按 index 存储的待领取 / 分配统计
+/// 只覆盖 0..MAX_PRIZE_POOLS，故累计创建的池数量同样不得超过 MAX_PRIZE_POOLS
+fn check_pool_capacity(active_pool_count: u8, prize_pool_count: u8) -> Result<()> {
+    require!(
+        (active_pool_count as usize) < MAX_PRIZE_POOLS
+            && (prize_pool_count as usize) < MAX_PRIZE_POOLS,
+        IPFlowError::MaxPrizePoolsReached
+    );
+    Ok(())
+}
+
+/// 删除奖品池前的 (待领取计数, 待揭示计数)，任一非零时须 force
+///
+/// 待揭示计数覆盖创建时快照了该池、尚未揭示完成的请求 (揭示时仍可能选中该池)。
+/// 上限校验之前创建的 index >= MAX_PRIZE_POOLS 的池没有计数，无法证明没有引用，
+/// 按 u32::MAX 处理 (同样须 force)
+fn removal_references(config: &IPFlowState, index: u8, force: bool) -> Result<(u32, u32)> {
+    let pending_claims = config
+        .pool_pending_claims
+        .get(index as usize)
+        .copied()
+        .unwrap_or(u32::MAX);
+    let pending_reveals = config
+        .pool_pending_reveals
+        .get(index as usize)
+        .copied()
+        .unwrap_or(u32::MAX);
+    require!(
+        (pending_claims == 0 && pending_reveals == 0) || force,
+        IPFlowError::PrizePoolHasPendingClaims
+    );
+    Ok((pending_claims, pending_reveals))
+}
+
 /// 硬删除奖品池
 ///
 /// 1. 找到 index 在 active_pool_indices 中的位置
-/// 2. 将后续元素前移一位（填补空洞）
-/// 3. 更新 active_pool_count
-/// 4. PDA 通过 close = admin 自动关闭，租金退给 admin
+/// 2. 校验该池没有待领取的 Revealed 请求及快照了该池的待揭示请求 (force = true 时跳过并发出警告事件)
+/// 3. 将后续元素前移一位（填补空洞）
+/// 4. 更新 active_pool_count
+/// 5. PDA 通过 close = admin 自动关闭，租金退给 admin
 ///
 /// 注意: prize_pool_count 不变（只增不减），用于分配新索引
-pub fn remove_prize_pool(ctx: Context<crate::RemovePrizePool>) -> Result<()> {
+pub fn remove_prize_pool(ctx: Context<crate::RemovePrizePool>, force: bool) -> Result<()> {
     let config = &mut ctx.accounts.config;
     let prize_pool = &ctx.accounts.prize_pool;
     let index = prize_pool.index;
@@ -96,14 +178,40 @@ pub fn remove_prize_pool(ctx: Context<crate::RemovePrizePool>) -> Result<()> {
     }
     let pos = found_pos.ok_or(IPFlowError::InvalidPrizePoolIndex)?;
 
-    // 2. 将 pos 之后的元素前移一位
+    // 2. 仍有用户持有选中该池的 Revealed 请求，或有快照了该池的请求尚未揭示时禁止删除，除非管理员强制
+    let (pending_claims, pending_reveals) = removal_references(config, index, force)?;
+    if pending_claims > 0 || pending_reveals > 0 {
+        emit!(PrizePoolForceRemoved {
+            admin: ctx.accounts.admin.key(),
+            index,
+            pending_claims,
+            pending_reveals,
+        });
+
+        msg!(
+            "WARNING: force removing prize pool index={} with {} pending claims, {} pending reveals",
+            index,
+            pending_claims,
+            pending_reveals
+        );
+    }
+
+    // 3. 将 pos 之后的元素前移一位 (待领取计数按 index 存储，强制删除后仍随领取 / 过期扣减)
     let last_active = (config.active_pool_count - 1) as usize;
     for i in pos..last_active {
         config.active_pool_indices[i] = config.active_pool_indices[i + 1];
+        config.active_pool_max_win_usd[i] = config.active_pool_max_win_usd[i + 1];
+        config.active_pool_active_from[i] = config.active_pool_active_from[i + 1];
+        config.active_pool_active_until[i] = config.active_pool_active_until[i + 1];
+        config.active_pool_tier_overrides[i] = config.active_pool_tier_overrides[i + 1];
     }
 
-    // 3. 清空最后一个位置，更新计数
+    // 4. 清空最后一个位置，更新计数
     config.active_pool_indices[last_active] = 255; // 255 表示空位
+    config.active_pool_max_win_usd[last_active] = 0;
+    config.active_pool_active_from[last_active] = 0;
+    config.active_pool_active_until[last_active] = 0;
+    config.active_pool_tier_overrides[last_active] = NO_TIER_OVERRIDE;
     config.active_pool_count -= 1;
     // prize_pool_count 不变！只增不减
 
@@ -125,18 +233,53 @@ pub fn remove_prize_pool(ctx: Context<crate::RemovePrizePool>) -> Result<()> {
 
 /// 更新奖品池
 ///
-/// 可选更新: swap_pool, pool_type, name
+/// 可选更新: swap_pool, pool_type, name, max_win_usd, active_from, active_until, symbol, metadata_uri,
+/// tier_override (symbol / metadata_uri 传入空字符串表示清除，tier_override 传入全 0 表示恢复默认概率)
+///
+/// tier_override 须单调不减且末项为 PROB_PRECISION，变更时发出 PrizePoolTierOverrideUpdated
+/// (含前后单卡期望奖金)；已进入揭示的请求沿用回调时锁定的阈值表。已配置 co_admin 时
+/// tier_override 须改用 stage_economics_change / confirm_economics_change
+///
+/// 旧版布局的奖品池须先调用 migrate_prize_pool 扩容
+#[allow(clippy::too_many_arguments)]
 pub fn update_prize_pool(
     ctx: Context<crate::UpdatePrizePool>,
     swap_pool: Option<Pubkey>,
     pool_type: Option<PoolType>,
     name: Option<String>,
+    max_win_usd: Option<u64>,
+    active_from: Option<i64>,
+    active_until: Option<i64>,
+    symbol: Option<String>,
+    metadata_uri: Option<String>,
+    tier_override: Option<[u64; 4]>,
 ) -> Result<()> {
+    let config = &mut ctx.accounts.config;
     let prize_pool = &mut ctx.accounts.prize_pool;
     let old_swap_pool = prize_pool.swap_pool;
 
+    // swap_pool 变更需等待 pool_change_delay_seconds (0 表示立即生效)，
+    // 生效前仍按旧地址校验，防止在大额领取前被替换为恶意池
     if let Some(sp) = swap_pool {
-        prize_pool.swap_pool = sp;
+        let now = Clock::get()?.unix_timestamp;
+        match stage_swap_pool(prize_pool, sp, config.pool_change_delay_seconds, now) {
+            Some(effective_at) => {
+                emit!(PrizePoolSwapPoolStaged {
+                    admin: ctx.accounts.admin.key(),
+                    index: prize_pool.index,
+                    current_swap_pool: old_swap_pool,
+                    pending_swap_pool: sp,
+                    effective_at,
+                });
+                msg!(
+                    "Prize pool swap_pool change staged: index={}, pending={}, effective_at={}",
+                    prize_pool.index,
+                    sp,
+                    effective_at
+                );
+            }
+            None => prize_pool.swap_pool = sp,
+        }
     }
     if let Some(pt) = pool_type {
         prize_pool.pool_type = pt;
@@ -144,12 +287,48 @@ pub fn update_prize_pool(
     if let Some(n) = name {
         prize_pool.name = n;
     }
+    if let Some(s) = symbol {
+        prize_pool.symbol = normalize_display_field(Some(s), MAX_POOL_SYMBOL_LEN)?;
+    }
+    if let Some(uri) = metadata_uri {
+        prize_pool.metadata_uri = normalize_display_field(Some(uri), MAX_POOL_METADATA_URI_LEN)?;
+    }
+    if let Some(cap) = max_win_usd {
+        // 已配置 co_admin 时奖金上限须经 stage_economics_change / confirm_economics_change
+        require!(
+            !config.economics_requires_co_admin(),
+            IPFlowError::CoAdminApprovalRequired
+        );
+        apply_max_win_usd(config, prize_pool, cap);
+    }
+    if active_from.is_some() || active_until.is_some() {
+        let from = active_from.unwrap_or(prize_pool.active_from);
+        let until = active_until.unwrap_or(prize_pool.active_until);
+        validate_schedule(from, until)?;
+        prize_pool.active_from = from;
+        prize_pool.active_until = until;
+        // 同步活跃池排期镜像
+        if let Some(pos) = config.active_pool_position(prize_pool.index) {
+            config.active_pool_active_from[pos] = from;
+            config.active_pool_active_until[pos] = until;
+        }
+    }
+    if let Some(thresholds) = tier_override {
+        // 已配置 co_admin 时 Tier 覆盖须经 stage_economics_change / confirm_economics_change
+        require!(
+            !config.economics_requires_co_admin(),
+            IPFlowError::CoAdminApprovalRequired
+        );
+        apply_tier_override(config, prize_pool, thresholds, ctx.accounts.admin.key())?;
+    }
 
     emit!(PrizePoolUpdated {
         admin: ctx.accounts.admin.key(),
         index: prize_pool.index,
         old_swap_pool,
         new_swap_pool: prize_pool.swap_pool,
+        symbol: prize_pool.symbol.clone(),
+        metadata_uri: prize_pool.metadata_uri.clone(),
     });
 
     msg!(
@@ -160,3 +339,552 @@ pub fn update_prize_pool(
 
     Ok(())
 }
+
+/// 生效已暂存的 swap_pool 变更 (须已到 effective_at)
+pub fn apply_prize_pool_update(ctx: Context<crate::ApplyPrizePoolUpdate>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let prize_pool = &mut ctx.accounts.prize_pool;
+    let old_swap_pool = prize_pool.swap_pool;
+
+    let new_swap_pool = take_pending_swap_pool(prize_pool, now)?;
+    prize_pool.swap_pool = new_swap_pool;
+
+    emit!(PrizePoolSwapPoolApplied {
+        admin: ctx.accounts.admin.key(),
+        index: prize_pool.index,
+        old_swap_pool,
+        new_swap_pool,
+    });
+
+    msg!(
+        "Prize pool swap_pool change applied: index={}, {} -> {}",
+        prize_pool.index,
+        old_swap_pool,
+        new_swap_pool
+    );
+    Ok(())
+}
+
+/// 暂存 swap_pool 变更，返回生效时间；无需延迟 (delay 为 0) 时返回 None，由调用方立即写入
+///
+/// 新地址与当前地址相同时视为撤销暂存的变更
+fn stage_swap_pool(
+    pool: &mut PrizePoolAccount,
+    swap_pool: Pubkey,
+    delay_seconds: u64,
+    now: i64,
+) -> Option<i64> {
+    if delay_seconds == 0 || swap_pool == pool.swap_pool {
+        pool.pending_swap_pool = None;
+        pool.pending_swap_pool_effective_at = 0;
+        return None;
+    }
+    let effective_at = now.saturating_add(delay_seconds.min(i64::MAX as u64) as i64);
+    pool.pending_swap_pool = Some(swap_pool);
+    pool.pending_swap_pool_effective_at = effective_at;
+    Some(effective_at)
+}
+
+/// 取出已到期的暂存 swap_pool 并清空暂存字段
+fn take_pending_swap_pool(pool: &mut PrizePoolAccount, now: i64) -> Result<Pubkey> {
+    let pending = pool
+        .pending_swap_pool
+        .ok_or(IPFlowError::NoPendingPoolUpdate)?;
+    require!(
+        now >= pool.pending_swap_pool_effective_at,
+        IPFlowError::PoolUpdateNotYetEffective
+    );
+    pool.pending_swap_pool = None;
+    pool.pending_swap_pool_effective_at = 0;
+    Ok(pending)
+}
+
+/// 迁移/扩容奖品池账户
+///
+/// 旧版 PrizePoolAccount 不含新增字段，直接以 Account 加载会反序列化失败。
+/// 此指令将 PDA 扩容到当前 INIT_SPACE (admin 补足租金差额)，新增字段初始化为 0
+/// (symbol / metadata_uri / tier_override 为 None，分配统计在下次 flush_pool_stats 时同步)。
+/// 对已是最新布局的账户重复调用是安全的 (no-op)。
+pub fn migrate_prize_pool(ctx: Context<crate::MigratePrizePool>, index: u8) -> Result<()> {
+    let pool_info = ctx.accounts.prize_pool.to_account_info();
+    require!(
+        pool_info.owner == ctx.program_id,
+        IPFlowError::InvalidPrizePoolIndex
+    );
+
+    let new_space = 8 + PrizePoolAccount::INIT_SPACE;
+    if pool_info.data_len() < new_space {
+        let required_lamports = Rent::get()?.minimum_balance(new_space);
+        let current_lamports = pool_info.lamports();
+
+        if current_lamports < required_lamports {
+            let diff = required_lamports - current_lamports;
+            invoke(
+                &system_instruction::transfer(ctx.accounts.admin.key, pool_info.key, diff),
+                &[
+                    ctx.accounts.admin.to_account_info(),
+                    pool_info.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+
+        #[allow(deprecated)] // realloc 是当前唯一的账户扩容方式
+        pool_info.realloc(new_space, true)?;
+    }
+
+    // 反序列化 → 重新序列化，规范化新增字段
+    let mut data_mut = pool_info.try_borrow_mut_data()?;
+    let mut cursor: &[u8] = &data_mut;
+    let pool_state = PrizePoolAccount::try_deserialize(&mut cursor)?;
+    require!(pool_state.index == index, IPFlowError::InvalidPrizePoolIndex);
+
+    let mut dst: &mut [u8] = &mut data_mut;
+    pool_state.try_serialize(&mut dst)?;
+
+    msg!(
+        "Prize pool migrated: index={}, space={}",
+        index,
+        new_space
+    );
+
+    Ok(())
+}
+
+/// 重建活跃奖品池索引 (完整性修复)
+///
+/// remaining_accounts 传入所有现存奖品池 PDA (任意顺序)。逐个校验 PDA 种子与 index 后，
+/// 按 index 升序重写 active_pool_indices / active_pool_count 及平行数组:
+/// 奖金上限、排期与 Tier 覆盖镜像从 PDA 重新读取 (待领取计数按 index 存储，不受影响)
+///
+/// 未传入的池会被移出活跃列表，调用方需保证传入完整列表。已一致时为 no-op。
+pub fn rebuild_active_pools<'info>(
+    ctx: Context<'_, '_, 'info, 'info, crate::RebuildActivePools<'info>>,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(
+        ctx.remaining_accounts.len() <= MAX_PRIZE_POOLS,
+        IPFlowError::MaxPrizePoolsReached
+    );
+
+    // 1. 校验并收集现存奖品池 (index, max_win_usd, active_from, active_until, tier_override)
+    let mut pools: Vec<(u8, u64, i64, i64, [u64; 4])> =
+        Vec::with_capacity(ctx.remaining_accounts.len());
+    for info in ctx.remaining_accounts.iter() {
+        let pool = load_prize_pool(info, ctx.program_id)?;
+        require!(
+            pool.index < config.prize_pool_count,
+            IPFlowError::InvalidPrizePoolIndex
+        );
+        pools.push((
+            pool.index,
+            pool.max_win_usd,
+            pool.active_from,
+            pool.active_until,
+            pool.tier_override.unwrap_or(NO_TIER_OVERRIDE),
+        ));
+    }
+    pools.sort_unstable_by_key(|&(index, ..)| index);
+    require!(
+        pools.windows(2).all(|w| w[0].0 != w[1].0),
+        IPFlowError::InvalidPrizePoolIndex
+    );
+
+    // 2. 按升序重建活跃列表及平行数组
+    let old_count = config.active_pool_count as usize;
+    let old_indices: Vec<u8> = config.active_pool_indices[..old_count].to_vec();
+
+    let mut indices = [255u8; MAX_PRIZE_POOLS];
+    let mut max_win_usd = [0u64; MAX_PRIZE_POOLS];
+    let mut active_from = [0i64; MAX_PRIZE_POOLS];
+    let mut active_until = [0i64; MAX_PRIZE_POOLS];
+    let mut tier_overrides = [NO_TIER_OVERRIDE; MAX_PRIZE_POOLS];
+    for (pos, &(index, cap, from, until, tier_override)) in pools.iter().enumerate() {
+        indices[pos] = index;
+        max_win_usd[pos] = cap;
+        active_from[pos] = from;
+        active_until[pos] = until;
+        tier_overrides[pos] = tier_override;
+    }
+
+    config.active_pool_indices = indices;
+    config.active_pool_max_win_usd = max_win_usd;
+    config.active_pool_active_from = active_from;
+    config.active_pool_active_until = active_until;
+    config.active_pool_tier_overrides = tier_overrides;
+    config.active_pool_count = pools.len() as u8;
+
+    // 3. 输出差异
+    let new_indices: Vec<u8> = pools.iter().map(|&(index, ..)| index).collect();
+    let (added, removed) = diff_indices(&old_indices, &new_indices);
+
+    msg!(
+        "Active pools rebuilt: count {} -> {}, added={:?}, removed={:?}",
+        old_count,
+        config.active_pool_count,
+        added,
+        removed
+    );
+
+    emit!(ActivePoolsRebuilt {
+        admin: ctx.accounts.admin.key(),
+        added,
+        removed,
+        active_pool_count: config.active_pool_count,
+    });
+
+    Ok(())
+}
+
+/// 校验排期窗口：active_until 非 0 时必须晚于 active_from
+fn validate_schedule(active_from: i64, active_until: i64) -> Result<()> {
+    require!(
+        active_from >= 0 && active_until >= 0,
+        IPFlowError::InvalidPoolSchedule
+    );
+    require!(
+        active_until == 0 || active_until > active_from,
+        IPFlowError::InvalidPoolSchedule
+    );
+    Ok(())
+}
+
+/// 规范化可选展示字段: None 或空字符串视为未设置，超过 max_len 字节报错
+fn normalize_display_field(value: Option<String>, max_len: usize) -> Result<Option<String>> {
+    match value {
+        Some(v) if !v.is_empty() => {
+            require!(v.len() <= max_len, IPFlowError::InvalidPoolMetadata);
+            Ok(Some(v))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// 写入奖品池 Tier 覆盖表并同步活跃池镜像 (update_prize_pool 与 confirm_economics_change 共用)
+///
+/// authority 为发起变更的管理员，记录在 PrizePoolTierOverrideUpdated 中
+pub(crate) fn apply_tier_override(
+    config: &mut IPFlowState,
+    prize_pool: &mut PrizePoolAccount,
+    thresholds: [u64; 4],
+    authority: Pubkey,
+) -> Result<()> {
+    let new_override = normalize_tier_override(thresholds)?;
+    let old_override = prize_pool.tier_override;
+    prize_pool.tier_override = new_override;
+    // 同步活跃池 Tier 覆盖镜像 (VRF 回调从镜像读取)
+    if let Some(pos) = config.active_pool_position(prize_pool.index) {
+        config.active_pool_tier_overrides[pos] = new_override.unwrap_or(NO_TIER_OVERRIDE);
+    }
+
+    if new_override != old_override {
+        let old_expected = override_expected_card_usd(old_override);
+        let new_expected = override_expected_card_usd(new_override);
+        emit!(PrizePoolTierOverrideUpdated {
+            admin: authority,
+            index: prize_pool.index,
+            old_tier_override: old_override,
+            new_tier_override: new_override,
+            old_expected_card_usd: old_expected,
+            new_expected_card_usd: new_expected,
+        });
+        msg!(
+            "Prize pool tier override updated: index={}, expected card usd {} -> {}",
+            prize_pool.index,
+            old_expected,
+            new_expected
+        );
+    }
+    Ok(())
+}
+
+/// 写入奖品池 Token 模式奖金上限 (update_prize_pool 与 confirm_economics_change 共用)
+pub(crate) fn apply_max_win_usd(
+    config: &mut IPFlowState,
+    prize_pool: &mut PrizePoolAccount,
+    max_win_usd: u64,
+) {
+    prize_pool.max_win_usd = max_win_usd;
+    // 同步活跃池奖金上限镜像 (已删除的池不在活跃列表中)
+    if let Some(pos) = config.active_pool_position(prize_pool.index) {
+        config.active_pool_max_win_usd[pos] = max_win_usd;
+    }
+
+    msg!(
+        "Prize pool max win updated: index={}, max_win_usd={}",
+        prize_pool.index,
+        max_win_usd
+    );
+}
+
+/// 规范化 Tier 覆盖表: 全 0 视为清除 (None)，否则须通过阈值表校验
+pub(crate) fn normalize_tier_override(thresholds: [u64; 4]) -> Result<Option<[u64; 4]>> {
+    if thresholds == NO_TIER_OVERRIDE {
+        return Ok(None);
+    }
+    require!(
+        is_valid_tier_thresholds(&thresholds),
+        IPFlowError::InvalidTierOverride
+    );
+    require!(
+        rtp_bps(compute_expected_value(&thresholds), TICKET_PRICE_MICRO_USD) <= MAX_TIER_RTP_BPS,
+        IPFlowError::TierOverrideRtpTooHigh
+    );
+    Ok(Some(thresholds))
+}
+
+/// Tier 覆盖表 (None 为默认表) 下的单卡期望奖金
+fn override_expected_card_usd(tier_override: Option<[u64; 4]>) -> u64 {
+    compute_expected_value(&resolve_tier_thresholds(
+        &tier_override.unwrap_or(NO_TIER_OVERRIDE),
+    ))
+}
+
+/// 将配置中按 index 累计的分配统计与待领取计数同步到奖品池 PDA
+///
+/// remaining_accounts 传入需要同步的奖品池 PDA (可写，任意顺序)。配置中的统计为累计值，
+/// 直接覆盖 PDA 字段，重复调用是幂等的。
+pub fn flush_pool_stats<'info>(
+    ctx: Context<'_, '_, 'info, 'info, crate::FlushPoolStats<'info>>,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(
+        ctx.remaining_accounts.len() <= MAX_PRIZE_POOLS,
+        IPFlowError::MaxPrizePoolsReached
+    );
+
+    let mut flushed = Vec::with_capacity(ctx.remaining_accounts.len());
+    for info in ctx.remaining_accounts.iter() {
+        let mut pool = load_prize_pool(info, ctx.program_id)?;
+        let index = pool.index as usize;
+        require!(index < MAX_PRIZE_POOLS, IPFlowError::InvalidPrizePoolIndex);
+        pool.total_assigned_usd = config.pool_assigned_usd[index];
+        pool.times_selected = config.pool_times_selected[index];
+        pool.pending_claims = config.pool_pending_claims[index];
+        pool.exit(ctx.program_id)?;
+        flushed.push(pool.index);
+    }
+
+    msg!("Pool stats flushed: indices={:?}", flushed);
+
+    emit!(PoolStatsFlushed {
+        admin: ctx.accounts.admin.key(),
+        indices: flushed,
+    });
+
+    Ok(())
+}
+
+/// 加载并校验奖品池 PDA (owner/discriminator + 种子 [b"prize_pool", index])
+pub(crate) fn load_prize_pool<'info>(
+    info: &'info AccountInfo<'info>,
+    program_id: &Pubkey,
+) -> Result<Account<'info, PrizePoolAccount>> {
+    let pool = Account::<PrizePoolAccount>::try_from(info)?;
+    let expected =
+        Pubkey::create_program_address(&[SEED_PRIZE_POOL, &[pool.index], &[pool.bump]], program_id)
+            .map_err(|_| error!(IPFlowError::InvalidPrizePoolIndex))?;
+    require_keys_eq!(info.key(), expected, IPFlowError::InvalidPrizePoolIndex);
+    Ok(pool)
+}
+
+/// 新旧活跃索引差异: (新增, 移除)
+pub(crate) fn diff_indices(old: &[u8], new: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let added = new.iter().filter(|i| !old.contains(i)).copied().collect();
+    let removed = old.iter().filter(|i| !new.contains(i)).copied().collect();
+    (added, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(swap_pool: Pubkey) -> PrizePoolAccount {
+        PrizePoolAccount {
+            index: 0,
+            swap_pool,
+            pool_type: PoolType::RaydiumCPMM,
+            name: String::new(),
+            bump: 255,
+            total_swapped_lamports: 0,
+            total_claims: 0,
+            last_claim_ts: 0,
+            max_win_usd: 0,
+            active_from: 0,
+            active_until: 0,
+            symbol: None,
+            metadata_uri: None,
+            tier_override: None,
+            total_assigned_usd: 0,
+            times_selected: 0,
+            pending_swap_pool: None,
+            pending_swap_pool_effective_at: 0,
+            pending_claims: 0,
+        }
+    }
+
+    #[test]
+    fn swap_pool_change_waits_for_delay() {
+        let old = Pubkey::new_unique();
+        let new = Pubkey::new_unique();
+        let mut p = pool(old);
+
+        assert_eq!(stage_swap_pool(&mut p, new, 86_400, 1_000), Some(87_400));
+        // 生效前仍使用旧地址
+        assert_eq!(p.swap_pool, old);
+        assert_eq!(
+            take_pending_swap_pool(&mut p, 87_399).unwrap_err(),
+            error!(IPFlowError::PoolUpdateNotYetEffective)
+        );
+        assert_eq!(take_pending_swap_pool(&mut p, 87_400).unwrap(), new);
+        assert_eq!(p.pending_swap_pool, None);
+        assert_eq!(
+            take_pending_swap_pool(&mut p, 87_400).unwrap_err(),
+            error!(IPFlowError::NoPendingPoolUpdate)
+        );
+    }
+
+    #[test]
+    fn swap_pool_change_immediate_without_delay_or_when_reverted() {
+        let old = Pubkey::new_unique();
+        let mut p = pool(old);
+        assert_eq!(stage_swap_pool(&mut p, Pubkey::new_unique(), 0, 1_000), None);
+
+        // 暂存后改回当前地址即撤销
+        stage_swap_pool(&mut p, Pubkey::new_unique(), 60, 1_000);
+        assert_eq!(stage_swap_pool(&mut p, old, 60, 1_010), None);
+        assert_eq!(p.pending_swap_pool, None);
+        assert_eq!(p.pending_swap_pool_effective_at, 0);
+    }
+
+    #[test]
+    fn schedule_validation() {
+        assert!(validate_schedule(0, 0).is_ok());
+        assert!(validate_schedule(1_000, 0).is_ok());
+        assert!(validate_schedule(0, 1_000).is_ok());
+        assert!(validate_schedule(1_000, 2_000).is_ok());
+        assert!(validate_schedule(2_000, 2_000).is_err());
+        assert!(validate_schedule(-1, 0).is_err());
+    }
+
+    #[test]
+    fn display_fields_normalized_and_bounded() {
+        assert_eq!(normalize_display_field(None, MAX_POOL_SYMBOL_LEN).unwrap(), None);
+        assert_eq!(
+            normalize_display_field(Some(String::new()), MAX_POOL_SYMBOL_LEN).unwrap(),
+            None
+        );
+        assert_eq!(
+            normalize_display_field(Some("BONK".to_string()), MAX_POOL_SYMBOL_LEN).unwrap(),
+            Some("BONK".to_string())
+        );
+        assert_eq!(
+            normalize_display_field(Some("A".repeat(11)), MAX_POOL_SYMBOL_LEN).unwrap_err(),
+            error!(IPFlowError::InvalidPoolMetadata)
+        );
+        let uri = format!("https://{}", "a".repeat(MAX_POOL_METADATA_URI_LEN - 8));
+        assert!(normalize_display_field(Some(uri.clone()), MAX_POOL_METADATA_URI_LEN).is_ok());
+        assert!(normalize_display_field(Some(uri + "x"), MAX_POOL_METADATA_URI_LEN).is_err());
+    }
+
+    #[test]
+    fn tier_override_normalized_and_validated() {
+        use crate::constants::{DEFAULT_TIER_THRESHOLDS, PROB_PRECISION};
+
+        assert_eq!(normalize_tier_override(NO_TIER_OVERRIDE).unwrap(), None);
+        let boosted = [150_000, 650_000, 900_000, PROB_PRECISION];
+        assert_eq!(normalize_tier_override(boosted).unwrap(), Some(boosted));
+        for invalid in [
+            [650_000, 150_000, 950_000, PROB_PRECISION],
+            [150_000, 650_000, 950_000, PROB_PRECISION + 1],
+            [150_000, 650_000, 950_000, 999_999],
+        ] {
+            assert_eq!(
+                normalize_tier_override(invalid).unwrap_err(),
+                error!(IPFlowError::InvalidTierOverride)
+            );
+        }
+        // 理论 RTP 超过 MAX_TIER_RTP_BPS: 全部开出 Tier 4 (期望 74.95 USDC)
+        assert_eq!(
+            normalize_tier_override([0, 0, 0, PROB_PRECISION]).unwrap_err(),
+            error!(IPFlowError::TierOverrideRtpTooHigh)
+        );
+        // 期望值变化: 默认 19.4825 USDC，Tier 4 提升到 10% 后 21.6325 USDC
+        assert_eq!(override_expected_card_usd(None), 19_482_500);
+        assert_eq!(
+            override_expected_card_usd(Some(DEFAULT_TIER_THRESHOLDS)),
+            override_expected_card_usd(None)
+        );
+        assert_eq!(override_expected_card_usd(Some(boosted)), 21_632_500);
+    }
+
+    #[test]
+    fn diff_is_empty_when_consistent() {
+        let (added, removed) = diff_indices(&[0, 2, 5], &[0, 2, 5]);
+        assert!(added.is_empty());
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn diff_reports_dangling_and_missing_indices() {
+        // 旧列表含已删除的 3，缺少现存的 4
+        let (added, removed) = diff_indices(&[0, 3, 2], &[0, 2, 4]);
+        assert_eq!(added, vec![4]);
+        assert_eq!(removed, vec![3]);
+    }
+
+    #[test]
+    fn pool_capacity_caps_lifetime_index() {
+        assert!(check_pool_capacity(0, (MAX_PRIZE_POOLS - 1) as u8).is_ok());
+        // 活跃池已删除到很少，但 index 已用尽
+        assert_eq!(
+            check_pool_capacity(1, MAX_PRIZE_POOLS as u8).unwrap_err(),
+            error!(IPFlowError::MaxPrizePoolsReached)
+        );
+        assert_eq!(
+            check_pool_capacity(MAX_PRIZE_POOLS as u8, 0).unwrap_err(),
+            error!(IPFlowError::MaxPrizePoolsReached)
+        );
+    }
+
+    #[test]
+    fn removing_untracked_pool_index_requires_force() {
+        let mut config = IPFlowState::zeroed();
+        config.pool_pending_claims[3] = 2;
+
+        assert_eq!(removal_references(&config, 4, false).unwrap(), (0, 0));
+        assert_eq!(
+            removal_references(&config, 3, false).unwrap_err(),
+            error!(IPFlowError::PrizePoolHasPendingClaims)
+        );
+        assert_eq!(removal_references(&config, 3, true).unwrap(), (2, 0));
+
+        // 上限校验前创建的 index >= MAX_PRIZE_POOLS: 不越界，且无计数时不能静默放行
+        let legacy = MAX_PRIZE_POOLS as u8 + 10;
+        assert_eq!(
+            removal_references(&config, legacy, false).unwrap_err(),
+            error!(IPFlowError::PrizePoolHasPendingClaims)
+        );
+        assert_eq!(
+            removal_references(&config, legacy, true).unwrap(),
+            (u32::MAX, u32::MAX)
+        );
+    }
+
+    #[test]
+    fn removing_pool_snapshotted_by_pending_reveal_requires_force() {
+        let mut config = IPFlowState::zeroed();
+        // 请求创建时快照了池 [1, 4]，尚未揭示
+        config.add_pool_pending_reveals(&[1, 4]).unwrap();
+
+        assert_eq!(
+            removal_references(&config, 4, false).unwrap_err(),
+            error!(IPFlowError::PrizePoolHasPendingClaims)
+        );
+        assert_eq!(removal_references(&config, 4, true).unwrap(), (0, 1));
+
+        // 揭示完成 (或退款) 后释放快照引用，可正常删除
+        config.release_pool_pending_reveals(&[1, 4]);
+        assert_eq!(removal_references(&config, 4, false).unwrap(), (0, 0));
+    }
+}
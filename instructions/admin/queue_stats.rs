@@ -0,0 +1,27 @@
+// ==================== VRF Queue 统计管理指令 ====================
+
+use anchor_lang::prelude::*;
+
+use crate::events::QueueStatsReset;
+use crate::ResetQueueStats;
+
+/// 创建或清零 Oracle Queue 回调延迟统计 PDA (创建前 VRF 回调跳过该 queue 的统计)
+pub fn reset_queue_stats(ctx: Context<ResetQueueStats>, queue: Pubkey) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let stats = &mut ctx.accounts.queue_stats;
+
+    emit!(QueueStatsReset {
+        admin: ctx.accounts.admin.key(),
+        queue,
+        count: stats.count,
+        total_latency_seconds: stats.total_latency_seconds,
+        max_latency_seconds: stats.max_latency_seconds,
+    });
+
+    stats.queue = queue;
+    stats.bump = ctx.bumps.queue_stats;
+    stats.reset(now);
+
+    msg!("Queue stats reset: queue={}", queue);
+    Ok(())
+}
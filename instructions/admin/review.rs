@@ -0,0 +1,192 @@
+// ==================== 大额中奖人工审核 ====================
+//
+// VRF 回调时 total_won_usd 超过 config.large_win_threshold_usd 的请求被标记 needs_review，
+// claim 在审核完成前被拒绝 (PendingReview)。管理员:
+// - approve_large_win: 清除标记，并从审核通过时起重新给予完整的领取期限
+// - reject_large_win: 请求置为 Failed，托管资金退回 Vault，本金退还支付者，关闭 PDA
+//
+// 另提供 extend_claim_deadline: 领取 UI 故障等事故后，批量顺延已揭示请求的领取期限
+
+use anchor_lang::prelude::*;
+
+use crate::constants::{CLAIM_TIMEOUT_SECONDS, MAX_DEADLINE_EXTENSIONS_PER_TX};
+use crate::errors::IPFlowError;
+use crate::events::{ClaimDeadlineExtendedByAdmin, LargeWinApproved, LargeWinRejected};
+use crate::instructions::user::refund_core::{do_refund, emit_refund_completed, RefundAccounts};
+use crate::state::{MintRequest, RefundTrigger, RequestStatus};
+use crate::utils::release_request_escrow;
+use crate::{ApproveLargeWin, ExtendClaimDeadline, RejectLargeWin};
+
+/// 审核通过大额中奖
+pub fn approve_large_win(ctx: Context<ApproveLargeWin>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let request = &mut ctx.accounts.mint_request;
+
+    request.status.check_claimable()?;
+    require!(request.needs_review, IPFlowError::NotPendingReview);
+
+    request.needs_review = false;
+
+    // 审核耗时不占用用户领取期限：截止时间至少为 now + CLAIM_TIMEOUT_SECONDS
+    request.extend_claim_deadline_to(now.saturating_add(CLAIM_TIMEOUT_SECONDS));
+
+    emit!(LargeWinApproved {
+        admin: ctx.accounts.admin.key(),
+        user: request.user,
+        mint_request: request.key(),
+        total_won_usd: request.total_won_usd,
+    });
+
+    msg!(
+        "Large win approved: user={}, total_won_usd={}, claim_deadline={} ({}s window)",
+        request.user,
+        request.total_won_usd,
+        request.claim_deadline,
+        CLAIM_TIMEOUT_SECONDS
+    );
+    Ok(())
+}
+
+/// 拒绝大额中奖：置为 Failed，退还本金给支付者 (PDA 通过 close = payer 关闭)
+pub fn reject_large_win(ctx: Context<RejectLargeWin>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let request = &mut ctx.accounts.mint_request;
+
+    request.status.check_claimable()?;
+    require!(request.needs_review, IPFlowError::NotPendingReview);
+
+    request.status = RequestStatus::Failed;
+    request.needs_review = false;
+
+    // 托管资金退回 Vault
+    release_request_escrow(
+        &mut ctx.accounts.config,
+        request,
+        &request.key(),
+        &ctx.accounts.escrow.to_account_info(),
+        &ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    // 退还本金 (审核拒绝非用户原因，按 Failed 全额退还)
+    let outcome = do_refund(
+        request,
+        RefundTrigger::Failed,
+        RefundAccounts {
+            vault: &ctx.accounts.vault,
+            vault_bump: ctx.accounts.config.vault_bump,
+            recipient: &ctx.accounts.payer.to_account_info(),
+            system_program: &ctx.accounts.system_program,
+            token_program: ctx.accounts.token_program.as_ref(),
+            mint: ctx.accounts.payment_mint.as_ref(),
+            vault_token_account: ctx.accounts.vault_token_account.as_ref(),
+            recipient_token_account: ctx.accounts.user_token_account.as_ref(),
+            source_override: None,
+        },
+    )?;
+    let refund_amount = outcome.refund_amount;
+
+    // 扣减选中池的待领取计数
+    let config = &mut ctx.accounts.config;
+    config.release_pool_pending_claim(request.selected_pool_index);
+    config.outstanding_liabilities_usd = config
+        .outstanding_liabilities_usd
+        .saturating_sub(request.total_won_usd);
+    config.total_collected = config.total_collected.saturating_sub(request.paid_usd);
+    config.count_refunded = config.count_refunded.saturating_add(1);
+
+    emit!(LargeWinRejected {
+        admin: ctx.accounts.admin.key(),
+        user: request.user,
+        payer: request.payer,
+        mint_request: request.key(),
+        total_won_usd: request.total_won_usd,
+        refund_amount,
+    });
+    emit_refund_completed(request, RefundTrigger::Failed, &outcome, None, 0, now);
+
+    msg!(
+        "Large win rejected: user={}, refund={} to payer={}",
+        request.user,
+        refund_amount,
+        request.payer
+    );
+    Ok(())
+}
+
+// ==================== 领取期限顺延 ====================
+
+/// 管理员顺延领取期限 (只能向后推)
+///
+/// remaining_accounts: 可选，需一并顺延的其他 MintRequest (可写)，
+/// 连同主账户最多 MAX_DEADLINE_EXTENSIONS_PER_TX 个
+pub fn extend_claim_deadline<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExtendClaimDeadline<'info>>,
+    new_deadline: i64,
+) -> Result<()> {
+    require!(
+        ctx.remaining_accounts.len() < MAX_DEADLINE_EXTENSIONS_PER_TX,
+        IPFlowError::TooManyDeadlineExtensions
+    );
+    let admin = ctx.accounts.admin.key();
+
+    apply_deadline_extension(&mut ctx.accounts.mint_request, admin, new_deadline)?;
+    for info in ctx.remaining_accounts.iter() {
+        require!(info.is_writable, IPFlowError::InvalidDeadlineExtensionAccount);
+        let mut request = Account::<MintRequest>::try_from(info)?;
+        apply_deadline_extension(&mut request, admin, new_deadline)?;
+        request.exit(&crate::ID)?;
+    }
+
+    msg!(
+        "Claim deadline extended to {} for {} request(s)",
+        new_deadline,
+        ctx.remaining_accounts.len() + 1
+    );
+    Ok(())
+}
+
+fn apply_deadline_extension(
+    request: &mut Account<MintRequest>,
+    admin: Pubkey,
+    new_deadline: i64,
+) -> Result<()> {
+    request.status.check_claimable()?;
+    let old_deadline = request.effective_claim_deadline();
+    check_deadline_extension(old_deadline, new_deadline)?;
+    request.claim_deadline = new_deadline;
+
+    emit!(ClaimDeadlineExtendedByAdmin {
+        admin,
+        user: request.user,
+        mint_request: request.key(),
+        old_deadline,
+        new_deadline,
+    });
+    Ok(())
+}
+
+/// 新截止时间必须晚于当前截止时间
+pub(crate) fn check_deadline_extension(old_deadline: i64, new_deadline: i64) -> Result<()> {
+    require!(
+        new_deadline > old_deadline,
+        IPFlowError::ClaimDeadlineNotExtended
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadline_can_only_move_forward() {
+        assert!(check_deadline_extension(1_000, 1_001).is_ok());
+        for new_deadline in [999, 1_000] {
+            assert_eq!(
+                check_deadline_extension(1_000, new_deadline).unwrap_err(),
+                error!(IPFlowError::ClaimDeadlineNotExtended)
+            );
+        }
+    }
+}
@@ -0,0 +1,783 @@
+// ==================== 全局配置参数调整指令 ====================
+//
+// 管理员调整 IPFlowState 中的运行参数，共用 UpdateConfig 账户上下文
+// (add_payment_mint 需读取 Mint 精度，使用 AddPaymentMint)
+
+use anchor_lang::prelude::*;
+
+use crate::constants::{
+    FEE_POLICY_ON_CLAIM, FEE_POLICY_ON_MINT, MAX_CARDS_LIMIT, MAX_CARD_URI_PREFIX_LEN, MAX_ORACLE_QUEUES, MAX_PAYMENT_MINTS, MAX_REFERRAL_BPS, MAX_ROUTE_DISCRIMINATORS, MAX_RTP_MODULATION_BPS, MAX_WITHDRAW_RECIPIENTS, MIN_EPOCH_LENGTH_SECONDS, MAX_PAYOUT_BPS, MAX_PRICE_AGE_SECONDS, MIN_PAYOUT_BPS, MIN_PRICE_AGE_SECONDS, PAUSE_ALL,
+    PAUSE_MINT, WITHDRAW_RECIPIENT_DELAY_SECONDS,
+};
+use crate::errors::IPFlowError;
+use crate::events::{
+    DailyPayoutCapUpdated, FeePolicyUpdated, JupiterProgramUpdated, RouteDiscriminatorsUpdated,
+    VrfProgramUpdated, WithdrawRecipientAdded, WithdrawRecipientRemoved,
+};
+use crate::state::global_config::PaymentMintInfo;
+use crate::state::IPFlowState;
+use crate::{AddPaymentMint, UpdateConfig};
+
+/// 设置细粒度暂停位
+///
+/// - flags: bit 0 mint, bit 1 claim, bit 2 refund, bit 3 admin-withdraw
+/// - is_paused 同步为 bit 0，保持旧客户端兼容
+pub fn set_pause_flags(ctx: Context<UpdateConfig>, flags: u8) -> Result<()> {
+    require!(flags & !PAUSE_ALL == 0, IPFlowError::InvalidPauseFlags);
+
+    let config = &mut ctx.accounts.config;
+    config.pause_flags = flags;
+    config.is_paused = flags & PAUSE_MINT != 0;
+
+    msg!("Pause flags updated: {:#06b}", flags);
+    Ok(())
+}
+
+/// 设置 SOL 领取最小发放额及零头处理策略
+///
+/// - min_payout_lamports: 最小发放额 (lamports)，0 表示不限制
+/// - dust_accumulation_enabled: 低于最小值时是否累积到 UserStats (否则向上取整)
+pub fn update_min_payout(
+    ctx: Context<UpdateConfig>,
+    min_payout_lamports: u64,
+    dust_accumulation_enabled: bool,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.min_payout_lamports = min_payout_lamports;
+    config.dust_accumulation_enabled = dust_accumulation_enabled;
+
+    msg!(
+        "Min payout updated: min_payout_lamports={}, dust_accumulation_enabled={}",
+        min_payout_lamports,
+        dust_accumulation_enabled
+    );
+    Ok(())
+}
+
+/// 设置 Token 模式 swap 输入下限及低于下限时的处理策略
+///
+/// - min_swap_lamports: swap 输入下限 (lamports)，0 表示不限制
+/// - dust_swap_fallback_to_sol: 低于下限时是否降级为 SOL 发放 (否则拒绝 Token 领取)
+pub fn update_min_swap(
+    ctx: Context<UpdateConfig>,
+    min_swap_lamports: u64,
+    dust_swap_fallback_to_sol: bool,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.min_swap_lamports = min_swap_lamports;
+    config.dust_swap_fallback_to_sol = dust_swap_fallback_to_sol;
+
+    msg!(
+        "Min swap updated: min_swap_lamports={}, dust_swap_fallback_to_sol={}",
+        min_swap_lamports,
+        dust_swap_fallback_to_sol
+    );
+    Ok(())
+}
+
+/// 设置奖品池 swap_pool 变更的生效延迟
+///
+/// - pool_change_delay_seconds: update_prize_pool 暂存的 swap_pool 变更须等待的秒数，0 表示立即生效
+///   (已暂存的变更按暂存时的 effective_at 生效)
+pub fn update_pool_change_delay(
+    ctx: Context<UpdateConfig>,
+    pool_change_delay_seconds: u64,
+) -> Result<()> {
+    ctx.accounts.config.pool_change_delay_seconds = pool_change_delay_seconds;
+
+    msg!("Pool change delay updated: {}s", pool_change_delay_seconds);
+    Ok(())
+}
+
+/// 设置 Vault 储备下限
+///
+/// - min_vault_reserve_lamports: Vault 可用余额低于该值时拒绝 request_mint，0 表示不限制
+pub fn update_min_vault_reserve(
+    ctx: Context<UpdateConfig>,
+    min_vault_reserve_lamports: u64,
+) -> Result<()> {
+    ctx.accounts.config.min_vault_reserve_lamports = min_vault_reserve_lamports;
+
+    msg!("Min vault reserve updated: {} lamports", min_vault_reserve_lamports);
+    Ok(())
+}
+
+/// 设置 crank_expire_pending 调用者小费
+///
+/// - crank_tip_lamports: 每次成功 crank 由 Vault 支付给调用者的 lamports，0 表示不支付
+pub fn update_crank_tip(ctx: Context<UpdateConfig>, crank_tip_lamports: u64) -> Result<()> {
+    ctx.accounts.config.crank_tip_lamports = crank_tip_lamports;
+
+    msg!("Crank tip updated: {} lamports", crank_tip_lamports);
+    Ok(())
+}
+
+/// 设置单次 VRF 请求费用 (仅影响新创建的请求，已创建请求按创建时记录的值扣除)
+///
+/// - vrf_fee_lamports: 用户超时退款时从 SOL 本金中扣除的金额，0 表示全额退款
+pub fn update_vrf_fee(ctx: Context<UpdateConfig>, vrf_fee_lamports: u64) -> Result<()> {
+    ctx.accounts.config.vrf_fee_lamports = vrf_fee_lamports;
+
+    msg!("VRF fee updated: {} lamports", vrf_fee_lamports);
+    Ok(())
+}
+
+/// 设置保底阈值: 连续 pity_threshold 张 Tier 1/2 卡后强制下一张 Tier 3+，0 表示关闭
+///
+/// 已配置 co_admin 时须改用 stage_economics_change / confirm_economics_change
+pub fn update_pity_threshold(ctx: Context<UpdateConfig>, pity_threshold: u16) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(
+        !config.economics_requires_co_admin(),
+        IPFlowError::CoAdminApprovalRequired
+    );
+    apply_pity_threshold(config, pity_threshold);
+    Ok(())
+}
+
+/// 写入保底阈值 (update_pity_threshold 与 confirm_economics_change 共用)
+pub(crate) fn apply_pity_threshold(config: &mut IPFlowState, pity_threshold: u16) {
+    config.pity_threshold = pity_threshold;
+
+    msg!("Pity threshold updated: {}", pity_threshold);
+}
+
+/// 设置单笔请求卡数上下限 (1 <= min_cards <= max_cards <= MAX_CARDS_LIMIT)
+///
+/// 仅约束新请求，已创建的请求按原卡数揭示
+pub fn update_card_limits(ctx: Context<UpdateConfig>, min_cards: u32, max_cards: u32) -> Result<()> {
+    require!(
+        IPFlowState::card_limits_valid(min_cards, max_cards),
+        IPFlowError::InvalidCardLimits
+    );
+
+    let config = &mut ctx.accounts.config;
+    config.min_cards = min_cards;
+    config.max_cards = max_cards;
+
+    msg!(
+        "Card limits updated: {}..={} (cap {})",
+        min_cards,
+        max_cards,
+        MAX_CARDS_LIMIT
+    );
+    Ok(())
+}
+
+/// 设置程序暂停期间是否仍允许领取已揭示的奖金
+///
+/// 仅作用于程序暂停 (PAUSE_MINT)；PAUSE_CLAIM 位始终拒绝领取，退款不受程序暂停影响
+pub fn set_claims_allowed_while_paused(ctx: Context<UpdateConfig>, allowed: bool) -> Result<()> {
+    ctx.accounts.config.claims_allowed_while_paused = allowed;
+    msg!("Claims allowed while paused: {}", allowed);
+    Ok(())
+}
+
+/// 设置手续费接收地址 (withdraw_fees_* 的唯一合法接收方)
+pub fn update_fee_recipient(ctx: Context<UpdateConfig>, fee_recipient: Pubkey) -> Result<()> {
+    require!(
+        fee_recipient != Pubkey::default(),
+        IPFlowError::InvalidFeeRecipient
+    );
+
+    let config = &mut ctx.accounts.config;
+    let old = config.fee_recipient;
+    config.fee_recipient = fee_recipient;
+
+    msg!("Fee recipient updated: {} -> {}", old, fee_recipient);
+    Ok(())
+}
+
+/// 设置 RTP 动态调节参数
+///
+/// - rtp_modulation_enabled: 是否按 Vault 余额调节开奖金额
+/// - vault_target_lamports: Vault 目标余额，启用时必须大于 0
+/// - max_modulation_bps: 调节幅度上限，不超过 MAX_RTP_MODULATION_BPS
+/// - 已配置 co_admin 时须改用 stage_economics_change / confirm_economics_change
+pub fn update_rtp_modulation(
+    ctx: Context<UpdateConfig>,
+    rtp_modulation_enabled: bool,
+    vault_target_lamports: u64,
+    max_modulation_bps: u16,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(
+        !config.economics_requires_co_admin(),
+        IPFlowError::CoAdminApprovalRequired
+    );
+    apply_rtp_modulation(
+        config,
+        rtp_modulation_enabled,
+        vault_target_lamports,
+        max_modulation_bps,
+    )
+}
+
+/// 校验 RTP 动态调节参数
+pub(crate) fn check_rtp_modulation(
+    rtp_modulation_enabled: bool,
+    vault_target_lamports: u64,
+    max_modulation_bps: u16,
+) -> Result<()> {
+    require!(
+        max_modulation_bps <= MAX_RTP_MODULATION_BPS
+            && (!rtp_modulation_enabled || vault_target_lamports > 0),
+        IPFlowError::InvalidRtpModulation
+    );
+    Ok(())
+}
+
+/// 写入 RTP 动态调节参数 (update_rtp_modulation 与 confirm_economics_change 共用)
+pub(crate) fn apply_rtp_modulation(
+    config: &mut IPFlowState,
+    rtp_modulation_enabled: bool,
+    vault_target_lamports: u64,
+    max_modulation_bps: u16,
+) -> Result<()> {
+    check_rtp_modulation(rtp_modulation_enabled, vault_target_lamports, max_modulation_bps)?;
+
+    config.rtp_modulation_enabled = rtp_modulation_enabled;
+    config.vault_target_lamports = vault_target_lamports;
+    config.max_modulation_bps = max_modulation_bps;
+
+    msg!(
+        "RTP modulation updated: enabled={}, target={} lamports, max={} bps",
+        rtp_modulation_enabled,
+        vault_target_lamports,
+        max_modulation_bps
+    );
+    Ok(())
+}
+
+/// 设置奖励余额参数
+///
+/// - referral_bps: 推荐返佣比例 (不超过 MAX_REFERRAL_BPS，0 表示关闭)
+/// - min_rewards_claim_usd: claim_rewards 的最小领取额 (micro-USD)
+/// - 已配置 co_admin 时 referral_bps 须经 stage_economics_change / confirm_economics_change 修改
+///   (传入当前值即可单独调整 min_rewards_claim_usd)
+pub fn update_rewards_settings(
+    ctx: Context<UpdateConfig>,
+    referral_bps: u16,
+    min_rewards_claim_usd: u64,
+) -> Result<()> {
+    check_referral_bps(referral_bps)?;
+
+    let config = &mut ctx.accounts.config;
+    require!(
+        referral_bps == config.referral_bps || !config.economics_requires_co_admin(),
+        IPFlowError::CoAdminApprovalRequired
+    );
+    config.referral_bps = referral_bps;
+    config.min_rewards_claim_usd = min_rewards_claim_usd;
+
+    msg!(
+        "Rewards settings updated: referral_bps={}, min_claim={} (micro-USD)",
+        referral_bps,
+        min_rewards_claim_usd
+    );
+    Ok(())
+}
+
+/// 校验推荐返佣比例
+pub(crate) fn check_referral_bps(referral_bps: u16) -> Result<()> {
+    require!(referral_bps <= MAX_REFERRAL_BPS, IPFlowError::InvalidReferralBps);
+    Ok(())
+}
+
+/// 写入推荐返佣比例 (confirm_economics_change 使用)
+pub(crate) fn apply_referral_bps(config: &mut IPFlowState, referral_bps: u16) -> Result<()> {
+    check_referral_bps(referral_bps)?;
+
+    config.referral_bps = referral_bps;
+
+    msg!("Referral bps updated: {}", referral_bps);
+    Ok(())
+}
+
+/// 设置卡牌 NFT 铸造开关与元数据 URI 前缀 (启用前须已调用 create_card_collection)
+pub fn update_card_collection(
+    ctx: Context<UpdateConfig>,
+    enabled: bool,
+    uri_prefix: String,
+) -> Result<()> {
+    require!(
+        uri_prefix.len() <= MAX_CARD_URI_PREFIX_LEN,
+        IPFlowError::CardUriTooLong
+    );
+
+    let collection = &mut ctx.accounts.config.card_collection;
+    require!(
+        !enabled || collection.collection != Pubkey::default(),
+        IPFlowError::CardNftsDisabled
+    );
+    collection.enabled = enabled;
+    collection.uri_prefix = [0u8; MAX_CARD_URI_PREFIX_LEN];
+    collection.uri_prefix[..uri_prefix.len()].copy_from_slice(uri_prefix.as_bytes());
+    collection.uri_prefix_len = uri_prefix.len() as u8;
+
+    msg!(
+        "Card collection updated: enabled={}, uri_prefix={}",
+        enabled,
+        uri_prefix
+    );
+    Ok(())
+}
+
+/// 设置 epoch 长度 (秒，不低于 1 小时)，下一次 roll_epoch 起生效
+pub fn update_epoch_length(ctx: Context<UpdateConfig>, epoch_length_seconds: u64) -> Result<()> {
+    require!(
+        epoch_length_seconds >= MIN_EPOCH_LENGTH_SECONDS && epoch_length_seconds <= i64::MAX as u64,
+        IPFlowError::InvalidEpochLength
+    );
+    ctx.accounts.config.epoch_length_seconds = epoch_length_seconds;
+
+    msg!("Epoch length updated: {}s", epoch_length_seconds);
+    Ok(())
+}
+
+/// 启用/停用支付方式 (仅影响新的 request_mint，已创建请求的 refund/claim 不受影响)
+pub fn set_payment_modes(
+    ctx: Context<UpdateConfig>,
+    sol_payments_enabled: bool,
+    usdt_payments_enabled: bool,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.sol_payments_enabled = sol_payments_enabled;
+    config.usdt_payments_enabled = usdt_payments_enabled;
+
+    msg!(
+        "Payment modes updated: sol={}, usdt={}",
+        sol_payments_enabled,
+        usdt_payments_enabled
+    );
+    Ok(())
+}
+
+/// 设置 claim 发放比例
+///
+/// - sol_payout_bps: SOL 模式发放比例 (bps)
+/// - token_payout_bps: Token 模式发放比例 (bps)
+/// - 两者均须位于 [MIN_PAYOUT_BPS, MAX_PAYOUT_BPS]
+/// - 已配置 co_admin 时须改用 stage_economics_change / confirm_economics_change
+pub fn update_payout_bps(
+    ctx: Context<UpdateConfig>,
+    sol_payout_bps: u16,
+    token_payout_bps: u16,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(
+        !config.economics_requires_co_admin(),
+        IPFlowError::CoAdminApprovalRequired
+    );
+    apply_payout_bps(config, sol_payout_bps, token_payout_bps)
+}
+
+/// 校验 claim 发放比例
+pub(crate) fn check_payout_bps(sol_payout_bps: u16, token_payout_bps: u16) -> Result<()> {
+    require!(
+        (MIN_PAYOUT_BPS..=MAX_PAYOUT_BPS).contains(&sol_payout_bps)
+            && (MIN_PAYOUT_BPS..=MAX_PAYOUT_BPS).contains(&token_payout_bps),
+        IPFlowError::InvalidPayoutBps
+    );
+    Ok(())
+}
+
+/// 写入 claim 发放比例 (update_payout_bps 与 confirm_economics_change 共用)
+pub(crate) fn apply_payout_bps(
+    config: &mut IPFlowState,
+    sol_payout_bps: u16,
+    token_payout_bps: u16,
+) -> Result<()> {
+    check_payout_bps(sol_payout_bps, token_payout_bps)?;
+
+    config.sol_payout_bps = sol_payout_bps;
+    config.token_payout_bps = token_payout_bps;
+
+    msg!(
+        "Payout bps updated: sol={}, token={}",
+        sol_payout_bps,
+        token_payout_bps
+    );
+    Ok(())
+}
+
+/// 设置 VRF 请求费用的支付方
+///
+/// - vault_pays_vrf_fee: true 由 Vault 支付 (计入 total_vrf_fees_lamports)，false 由用户支付
+pub fn set_vrf_fee_payer(ctx: Context<UpdateConfig>, vault_pays_vrf_fee: bool) -> Result<()> {
+    ctx.accounts.config.vault_pays_vrf_fee = vault_pays_vrf_fee;
+
+    msg!("VRF fee payer updated: vault_pays_vrf_fee={}", vault_pays_vrf_fee);
+    Ok(())
+}
+
+/// 设置 Pyth 价格最大有效期
+///
+/// - max_price_age_seconds: 须位于 [MIN_PRICE_AGE_SECONDS, MAX_PRICE_AGE_SECONDS]
+pub fn update_max_price_age(ctx: Context<UpdateConfig>, max_price_age_seconds: u64) -> Result<()> {
+    require!(
+        (MIN_PRICE_AGE_SECONDS..=MAX_PRICE_AGE_SECONDS).contains(&max_price_age_seconds),
+        IPFlowError::InvalidPriceAge
+    );
+
+    ctx.accounts.config.max_price_age_seconds = max_price_age_seconds;
+
+    msg!("Max price age updated: {}s", max_price_age_seconds);
+    Ok(())
+}
+
+/// 设置 24 小时发放上限 (熔断)
+///
+/// - daily_payout_cap_usd: 窗口内允许发放的中奖总额 (micro-USD)，0 表示取消上限
+pub fn update_daily_payout_cap(ctx: Context<UpdateConfig>, daily_payout_cap_usd: u64) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let old_cap_usd = config.daily_payout_cap_usd;
+    config.daily_payout_cap_usd = daily_payout_cap_usd;
+
+    emit!(DailyPayoutCapUpdated {
+        admin: ctx.accounts.admin.key(),
+        old_cap_usd,
+        new_cap_usd: daily_payout_cap_usd,
+    });
+
+    msg!(
+        "Daily payout cap updated: {} -> {}",
+        old_cap_usd,
+        daily_payout_cap_usd
+    );
+    Ok(())
+}
+
+/// 设置平台手续费收取时机
+///
+/// - fee_policy: FEE_POLICY_ON_MINT (铸造时，领取不扣费) 或 FEE_POLICY_ON_CLAIM (领取时从中奖金额扣除)
+/// - 请求创建时锁定策略，切换只影响新请求，同一请求不会被重复收费
+/// - 已配置 co_admin 时须改用 stage_economics_change / confirm_economics_change
+pub fn update_fee_policy(ctx: Context<UpdateConfig>, fee_policy: u8) -> Result<()> {
+    let admin = ctx.accounts.admin.key();
+    let config = &mut ctx.accounts.config;
+    require!(
+        !config.economics_requires_co_admin(),
+        IPFlowError::CoAdminApprovalRequired
+    );
+    apply_fee_policy(config, fee_policy, admin)
+}
+
+/// 校验手续费收取时机
+pub(crate) fn check_fee_policy(fee_policy: u8) -> Result<()> {
+    require!(
+        fee_policy == FEE_POLICY_ON_MINT || fee_policy == FEE_POLICY_ON_CLAIM,
+        IPFlowError::InvalidFeePolicy
+    );
+    Ok(())
+}
+
+/// 写入手续费收取时机 (update_fee_policy 与 confirm_economics_change 共用)
+pub(crate) fn apply_fee_policy(
+    config: &mut IPFlowState,
+    fee_policy: u8,
+    authority: Pubkey,
+) -> Result<()> {
+    check_fee_policy(fee_policy)?;
+
+    let old_fee_policy = config.fee_policy;
+    config.fee_policy = fee_policy;
+
+    emit!(FeePolicyUpdated {
+        admin: authority,
+        old_fee_policy,
+        new_fee_policy: fee_policy,
+    });
+
+    msg!("Fee policy updated: {} -> {}", old_fee_policy, fee_policy);
+    Ok(())
+}
+
+/// 设置大额中奖人工审核阈值
+///
+/// - large_win_threshold_usd: 超过该金额 (micro-USD) 的中奖需管理员审核，0 表示关闭审核
+pub fn update_large_win_threshold(
+    ctx: Context<UpdateConfig>,
+    large_win_threshold_usd: u64,
+) -> Result<()> {
+    ctx.accounts.config.large_win_threshold_usd = large_win_threshold_usd;
+
+    msg!("Large win threshold updated: {}", large_win_threshold_usd);
+    Ok(())
+}
+
+/// 更新 Jupiter Program ID (Jupiter 发布新版本时无需重新部署)
+pub fn update_jupiter_program(ctx: Context<UpdateConfig>, jupiter_program: Pubkey) -> Result<()> {
+    require!(
+        jupiter_program != Pubkey::default(),
+        IPFlowError::InvalidJupiterProgram
+    );
+
+    let config = &mut ctx.accounts.config;
+    let old_program = config.jupiter_program;
+    config.jupiter_program = jupiter_program;
+
+    emit!(JupiterProgramUpdated {
+        admin: ctx.accounts.admin.key(),
+        old_program,
+        new_program: jupiter_program,
+    });
+
+    msg!("Jupiter program updated: {} -> {}", old_program, jupiter_program);
+    Ok(())
+}
+
+/// 更新 MagicBlock VRF 程序 ID 与回调签名身份 (VRF 程序升级或身份轮换时无需重新部署)
+///
+/// 仅影响之后的 VRF 请求与回调校验；身份变更前已发出的请求将由新身份回调，旧身份的回调被拒绝
+pub fn update_vrf_program(
+    ctx: Context<UpdateConfig>,
+    vrf_program: Pubkey,
+    vrf_identity: Pubkey,
+) -> Result<()> {
+    require!(
+        vrf_program != Pubkey::default() && vrf_identity != Pubkey::default(),
+        IPFlowError::InvalidVrfProgram
+    );
+
+    let config = &mut ctx.accounts.config;
+    let old_program = config.vrf_program;
+    let old_identity = config.vrf_identity;
+    config.vrf_program = vrf_program;
+    config.vrf_identity = vrf_identity;
+
+    emit!(VrfProgramUpdated {
+        admin: ctx.accounts.admin.key(),
+        old_program,
+        new_program: vrf_program,
+        old_identity,
+        new_identity: vrf_identity,
+    });
+
+    msg!("VRF program updated: {} (identity {})", vrf_program, vrf_identity);
+    Ok(())
+}
+
+/// 更新 Raydium CPMM Program ID (仅接受该地址作为 Raydium 路由的 CPI 目标)
+pub fn update_raydium_program(ctx: Context<UpdateConfig>, raydium_program: Pubkey) -> Result<()> {
+    require!(
+        raydium_program != Pubkey::default(),
+        IPFlowError::InvalidRaydiumProgram
+    );
+    ctx.accounts.config.raydium_program = raydium_program;
+
+    msg!("Raydium program updated: {}", raydium_program);
+    Ok(())
+}
+
+/// 整体替换允许的 Jupiter 路由指令 discriminator (1..=MAX_ROUTE_DISCRIMINATORS 个，不可重复)
+pub fn set_route_discriminators(
+    ctx: Context<UpdateConfig>,
+    discriminators: Vec<[u8; 8]>,
+) -> Result<()> {
+    validate_route_discriminators(&discriminators)?;
+
+    let config = &mut ctx.accounts.config;
+    config.allowed_route_discriminators = [[0u8; 8]; MAX_ROUTE_DISCRIMINATORS];
+    config.allowed_route_discriminators[..discriminators.len()].copy_from_slice(&discriminators);
+    config.route_discriminator_count = discriminators.len() as u8;
+
+    msg!("Route discriminators updated: count={}", discriminators.len());
+
+    emit!(RouteDiscriminatorsUpdated {
+        admin: ctx.accounts.admin.key(),
+        discriminators,
+    });
+    Ok(())
+}
+
+fn validate_route_discriminators(discriminators: &[[u8; 8]]) -> Result<()> {
+    require!(
+        !discriminators.is_empty() && discriminators.len() <= MAX_ROUTE_DISCRIMINATORS,
+        IPFlowError::InvalidRouteDiscriminators
+    );
+    for (i, discriminator) in discriminators.iter().enumerate() {
+        require!(
+            !discriminators[..i].contains(discriminator),
+            IPFlowError::InvalidRouteDiscriminators
+        );
+    }
+    Ok(())
+}
+
+/// 添加 VRF Oracle Queue 到白名单 (最多 MAX_ORACLE_QUEUES 个)
+pub fn add_oracle_queue(ctx: Context<UpdateConfig>, queue: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(
+        queue != Pubkey::default(),
+        IPFlowError::InvalidOracleQueue
+    );
+    require!(
+        !config.is_oracle_queue_whitelisted(&queue),
+        IPFlowError::OracleQueueAlreadyWhitelisted
+    );
+
+    let count = config.oracle_queue_count as usize;
+    require!(count < MAX_ORACLE_QUEUES, IPFlowError::OracleQueueListFull);
+
+    config.oracle_queues[count] = queue;
+    config.oracle_queue_count += 1;
+    if count == 0 {
+        config.oracle_queue = queue;
+    }
+
+    msg!(
+        "Oracle queue added: {} (count={})",
+        queue,
+        config.oracle_queue_count
+    );
+    Ok(())
+}
+
+/// 从白名单移除 VRF Oracle Queue (保持有序无空洞，至少保留一个)
+///
+/// 仍在该 queue 上等待回调的请求将被拒绝揭示 (InvalidOracleQueue)，只能超时退款；
+/// 仅轮换主 queue 时应先 add 新 queue，旧 queue 上的请求完成后再移除
+pub fn remove_oracle_queue(ctx: Context<UpdateConfig>, queue: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let count = config.oracle_queue_count as usize;
+    let pos = config.oracle_queues[..count]
+        .iter()
+        .position(|q| *q == queue)
+        .ok_or(IPFlowError::InvalidOracleQueue)?;
+    require!(count > 1, IPFlowError::CannotRemoveLastOracleQueue);
+
+    for i in pos..count - 1 {
+        config.oracle_queues[i] = config.oracle_queues[i + 1];
+    }
+    config.oracle_queues[count - 1] = Pubkey::default();
+    config.oracle_queue_count -= 1;
+    config.oracle_queue = config.oracle_queues[0];
+
+    msg!(
+        "Oracle queue removed: {} (count={})",
+        queue,
+        config.oracle_queue_count
+    );
+    Ok(())
+}
+
+/// 添加提取接收地址到白名单 (最多 MAX_WITHDRAW_RECIPIENTS 个)
+///
+/// 新地址在 WITHDRAW_RECIPIENT_DELAY_SECONDS (48 小时) 后才可接收 withdraw_sol / withdraw_token
+pub fn add_withdraw_recipient(ctx: Context<UpdateConfig>, recipient: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(
+        recipient != Pubkey::default(),
+        IPFlowError::WithdrawRecipientNotAllowed
+    );
+    require!(
+        config.withdraw_recipient_position(&recipient).is_none(),
+        IPFlowError::WithdrawRecipientAlreadyWhitelisted
+    );
+
+    let count = config.withdraw_whitelist_count as usize;
+    require!(
+        count < MAX_WITHDRAW_RECIPIENTS,
+        IPFlowError::WithdrawWhitelistFull
+    );
+
+    let active_after = Clock::get()?
+        .unix_timestamp
+        .saturating_add(WITHDRAW_RECIPIENT_DELAY_SECONDS);
+    config.withdraw_whitelist[count] = recipient;
+    config.withdraw_whitelist_active_after[count] = active_after;
+    config.withdraw_whitelist_count += 1;
+
+    emit!(WithdrawRecipientAdded {
+        admin: ctx.accounts.admin.key(),
+        recipient,
+        active_after,
+    });
+
+    msg!(
+        "Withdraw recipient added: {} (active_after={}, count={})",
+        recipient,
+        active_after,
+        config.withdraw_whitelist_count
+    );
+    Ok(())
+}
+
+/// 从提取白名单移除接收地址 (立即生效，保持有序无空洞)
+pub fn remove_withdraw_recipient(ctx: Context<UpdateConfig>, recipient: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let count = config.withdraw_whitelist_count as usize;
+    let pos = config
+        .withdraw_recipient_position(&recipient)
+        .ok_or(IPFlowError::WithdrawRecipientNotAllowed)?;
+
+    for i in pos..count - 1 {
+        config.withdraw_whitelist[i] = config.withdraw_whitelist[i + 1];
+        config.withdraw_whitelist_active_after[i] = config.withdraw_whitelist_active_after[i + 1];
+    }
+    config.withdraw_whitelist[count - 1] = Pubkey::default();
+    config.withdraw_whitelist_active_after[count - 1] = 0;
+    config.withdraw_whitelist_count -= 1;
+
+    emit!(WithdrawRecipientRemoved {
+        admin: ctx.accounts.admin.key(),
+        recipient,
+    });
+
+    msg!(
+        "Withdraw recipient removed: {} (count={})",
+        recipient,
+        config.withdraw_whitelist_count
+    );
+    Ok(())
+}
+
+/// 添加稳定币支付 Mint 到白名单 (最多 MAX_PAYMENT_MINTS 个，精度从 Mint 账户读取)
+///
+/// 添加后需调用 init_vault_token_account 创建 Vault 对应的 ATA
+pub fn add_payment_mint(ctx: Context<AddPaymentMint>) -> Result<()> {
+    let mint = ctx.accounts.mint.key();
+    let decimals = ctx.accounts.mint.decimals;
+    let config = &mut ctx.accounts.config;
+
+    require!(
+        config.find_payment_mint(&mint).is_none(),
+        IPFlowError::PaymentMintAlreadyWhitelisted
+    );
+    let count = config.payment_mint_count as usize;
+    require!(count < MAX_PAYMENT_MINTS, IPFlowError::PaymentMintListFull);
+
+    config.payment_mints[count] = PaymentMintInfo {
+        mint,
+        decimals,
+        enabled: true,
+    };
+    config.payment_mint_count += 1;
+
+    msg!(
+        "Payment mint added: {} (decimals={}, count={})",
+        mint,
+        decimals,
+        config.payment_mint_count
+    );
+    Ok(())
+}
+
+/// 启用/停用白名单中的稳定币支付 Mint (仅影响新的 request_mint，已创建请求仍可退款)
+pub fn set_payment_mint_enabled(
+    ctx: Context<UpdateConfig>,
+    mint: Pubkey,
+    enabled: bool,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let count = config.payment_mint_count as usize;
+    let info = config.payment_mints[..count]
+        .iter_mut()
+        .find(|info| info.mint == mint)
+        .ok_or(IPFlowError::PaymentMintNotWhitelisted)?;
+    info.enabled = enabled;
+
+    msg!("Payment mint {} enabled={}", mint, enabled);
+    Ok(())
+}
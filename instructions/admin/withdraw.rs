@@ -1,21 +1,57 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Transfer};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{self, CloseAccount, TransferChecked};
 
+use crate::constants::{NATIVE_SOL_MINT, SEED_FEE_VAULT};
 use crate::errors::IPFlowError;
+use crate::events::{FeesWithdrawn, VaultTokenAccountClosed, WithdrawalRejected};
+use crate::state::IPFlowState;
+use crate::utils::vault_available_lamports;
+use crate::CloseVaultTokenAccount;
 use crate::WithdrawSol;
 use crate::WithdrawToken;
+use crate::{WithdrawFeesSol, WithdrawFeesToken};
+
+/// 校验接收方为已生效的提取白名单地址，否则记录 WithdrawalRejected 后拒绝
+fn check_withdraw_recipient(
+    config: &IPFlowState,
+    admin: Pubkey,
+    recipient: Pubkey,
+    mint: Option<Pubkey>,
+    amount: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    if !config.is_withdraw_recipient_active(&recipient, now) {
+        emit!(WithdrawalRejected {
+            admin,
+            recipient,
+            mint,
+            amount,
+            timestamp: now,
+        });
+        return err!(IPFlowError::WithdrawRecipientNotAllowed);
+    }
+    Ok(())
+}
 
 // ==================== SOL 提取 ====================
 
-/// 提取 SOL 到指定接收地址
+/// 提取 SOL 到提取白名单中已生效的接收地址
 pub fn withdraw_sol(ctx: Context<WithdrawSol>, amount: u64) -> Result<()> {
     let vault = &ctx.accounts.vault;
     let recipient = &ctx.accounts.recipient;
     let config = &ctx.accounts.config;
 
+    check_withdraw_recipient(
+        config,
+        ctx.accounts.admin.key(),
+        recipient.key(),
+        None,
+        amount,
+    )?;
+
     // 保留最小租金，防止账户被关闭
-    let min_rent = Rent::get()?.minimum_balance(0);
-    let available = vault.lamports().saturating_sub(min_rent);
+    let available = vault_available_lamports(vault)?;
     require!(amount <= available, IPFlowError::InsufficientVaultBalance);
 
     // PDA 签名转账
@@ -44,10 +80,18 @@ pub fn withdraw_sol(ctx: Context<WithdrawSol>, amount: u64) -> Result<()> {
 
 // ==================== Token 提取 ====================
 
-/// 提取 Token 到指定接收地址
+/// 提取 Token 到 owner 为提取白名单已生效地址的 Token 账户
 pub fn withdraw_token(ctx: Context<WithdrawToken>, amount: u64) -> Result<()> {
     let config = &ctx.accounts.config;
 
+    check_withdraw_recipient(
+        config,
+        ctx.accounts.admin.key(),
+        ctx.accounts.recipient_token_account.owner,
+        Some(ctx.accounts.mint.key()),
+        amount,
+    )?;
+
     // 检查 Token 余额
     require!(
         ctx.accounts.vault_token_account.amount >= amount,
@@ -58,17 +102,19 @@ pub fn withdraw_token(ctx: Context<WithdrawToken>, amount: u64) -> Result<()> {
     let seeds = &[b"vault".as_ref(), &[config.vault_bump]];
     let signer = &[&seeds[..]];
 
-    token::transfer(
+    token::transfer_checked(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
                 to: ctx.accounts.recipient_token_account.to_account_info(),
                 authority: ctx.accounts.vault.to_account_info(),
             },
             signer,
         ),
         amount,
+        ctx.accounts.mint.decimals,
     )?;
 
     msg!(
@@ -78,3 +124,147 @@ pub fn withdraw_token(ctx: Context<WithdrawToken>, amount: u64) -> Result<()> {
     );
     Ok(())
 }
+
+// ==================== 手续费金库提取 ====================
+//
+// 手续费单独存放在 fee_vault PDA，以下指令只以 fee_vault 签名，无法动用奖金 Vault；
+// 接收方必须是 config.fee_recipient，由 Context 约束校验。
+
+/// 从手续费金库提取 SOL 到 fee_recipient
+pub fn withdraw_fees_sol(ctx: Context<WithdrawFeesSol>, amount: u64) -> Result<()> {
+    let fee_vault = &ctx.accounts.fee_vault;
+    let recipient = &ctx.accounts.recipient;
+
+    // 保留最小租金，防止账户被回收
+    let available = vault_available_lamports(fee_vault)?;
+    require!(amount <= available, IPFlowError::InsufficientVaultBalance);
+
+    let seeds = &[SEED_FEE_VAULT, &[ctx.accounts.config.fee_vault_bump]];
+    let signer = &[&seeds[..]];
+
+    anchor_lang::system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: fee_vault.to_account_info(),
+                to: recipient.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+
+    emit!(FeesWithdrawn {
+        admin: ctx.accounts.admin.key(),
+        recipient: recipient.key(),
+        mint: None,
+        amount,
+        remaining: available - amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "Admin withdrew {} lamports from fee vault to {}",
+        amount,
+        recipient.key()
+    );
+    Ok(())
+}
+
+/// 从手续费金库提取 Token 到 fee_recipient 名下的 Token 账户
+pub fn withdraw_fees_token(ctx: Context<WithdrawFeesToken>, amount: u64) -> Result<()> {
+    let balance = ctx.accounts.fee_vault_token_account.amount;
+    require!(balance >= amount, IPFlowError::InsufficientVaultBalance);
+
+    let seeds = &[SEED_FEE_VAULT, &[ctx.accounts.config.fee_vault_bump]];
+    let signer = &[&seeds[..]];
+
+    token::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.fee_vault_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: ctx.accounts.fee_vault.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    emit!(FeesWithdrawn {
+        admin: ctx.accounts.admin.key(),
+        recipient: ctx.accounts.recipient_token_account.key(),
+        mint: Some(ctx.accounts.mint.key()),
+        amount,
+        remaining: balance - amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "Admin withdrew {} tokens from fee vault to {}",
+        amount,
+        ctx.accounts.recipient_token_account.key()
+    );
+    Ok(())
+}
+
+// ==================== 空 Token 账户清理 ====================
+
+/// 关闭 Vault 名下余额为 0 的杂散 Token 账户 (空投、误转等)，回收租金
+///
+/// 白名单支付 Mint 的 Vault ATA 仍用于收款/退款，Vault 的 WSOL 账户 (已登记的 vault_wsol_account
+/// 及 NATIVE_SOL_MINT ATA) 用作 swap 输入，均不允许关闭
+pub fn close_vault_token_account(ctx: Context<CloseVaultTokenAccount>) -> Result<()> {
+    let token_account = &ctx.accounts.vault_token_account;
+    let vault_key = ctx.accounts.vault.key();
+    let config = &ctx.accounts.config;
+
+    require!(token_account.amount == 0, IPFlowError::VaultTokenAccountNotEmpty);
+
+    let mint = token_account.mint;
+    let is_vault_ata = token_account.key() == get_associated_token_address(&vault_key, &mint);
+    let is_payment_vault_account = token_account.key() == config.vault_usdt_account
+        || (config.find_payment_mint(&mint).is_some() && is_vault_ata);
+    let is_wsol_vault_account = config.pinned_vault_wsol_account() == Some(token_account.key())
+        || (mint == NATIVE_SOL_MINT && is_vault_ata);
+    require!(
+        !is_payment_vault_account && !is_wsol_vault_account,
+        IPFlowError::CannotClosePaymentVaultAccount
+    );
+
+    let reclaimed_lamports = token_account.to_account_info().lamports();
+
+    // PDA 签名
+    let seeds = &[b"vault".as_ref(), &[ctx.accounts.config.vault_bump]];
+    let signer = &[&seeds[..]];
+
+    token::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: token_account.to_account_info(),
+            destination: ctx.accounts.recipient.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        },
+        signer,
+    ))?;
+
+    emit!(VaultTokenAccountClosed {
+        admin: ctx.accounts.admin.key(),
+        token_account: token_account.key(),
+        mint,
+        recipient: ctx.accounts.recipient.key(),
+        reclaimed_lamports,
+    });
+
+    msg!(
+        "Vault token account {} closed (mint={}), {} lamports to {}",
+        token_account.key(),
+        mint,
+        reclaimed_lamports,
+        ctx.accounts.recipient.key()
+    );
+    Ok(())
+}
@@ -1,15 +1,39 @@
 // ==================== MagicBlock VRF 回调指令 ====================
 //
 // 处理 MagicBlock Ephemeral VRF 回调，计算抽奖结果
-// 状态转换：Pending -> Revealed
+// 状态转换：Pending -> Revealing (回调) -> Revealed (continue_reveal)
+// 回调收到随机数后先在请求创建时快照的排期内活跃池中选择奖品池 (字节 8-15)，再按选中池的 Tier 阈值表 (覆盖表或默认表) 逐卡开奖；
+// 候选池、覆盖表、奖金上限、保底阈值与回调身份均取自请求创建时的快照，回调不传入全局配置
+// 回调时记录 VRF 延迟 (created_at 到收到随机数)，并按请求所用 queue 累计到 QueueStats
+// 回调只保存随机数并处理前 REVEAL_CARDS_PER_IX 张卡，剩余卡及揭示结算由 continue_reveal 完成
+// (MagicBlock 回调的计算预算固定，大卡数请求无法在一次回调内完成)
+// 揭示结算时按当前 Pyth 价格将中奖金额从 Vault 托管到请求的 escrow PDA (锁定领取汇率)
 // 用户后续调用 claim 选择发放方式
 
 use anchor_lang::prelude::*;
-
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::{AccountDeserialize, AccountSerialize};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::constants::{
+    BPS_DENOMINATOR, CLAIM_TIMEOUT_SECONDS, FAILURE_INSUFFICIENT_VAULT,
+    FAILURE_MODULATION_OVERFLOW, FAILURE_QUEUE_REVOKED, FAILURE_REVEAL_INVARIANT,
+    FAILURE_REVEAL_OVERFLOW, REVEAL_CARDS_PER_IX,
+};
 use crate::errors::IPFlowError;
-use crate::state::RequestStatus;
-use crate::utils::vrf_helper::process_vrf_result;
-use crate::ConsumeLotteryRandomness;
+use crate::events::{LargeWinFlagged, LeaderboardUpdated, RevealAdvanced};
+use crate::state::global_config::MAX_PRIZE_POOLS;
+use crate::state::{
+    reveal_latency_seconds, IPFlowState, Leaderboard, LeaderboardEntry, MintRequest, QueueStats,
+    RequestStatus, UserStats,
+};
+use crate::utils::escrow::{escrow_funding_lamports, fund_escrow};
+use crate::utils::vrf_helper::{
+    advance_reveal, apply_rtp_modulation, randomness_hash, rtp_modulation_bps,
+    select_active_prize_pool, RevealProgress,
+};
+use crate::utils::{pyth_oracle, vault_available_lamports};
+use crate::{ConsumeLotteryRandomness, ContinueReveal};
 
 /// MagicBlock VRF 回调事件
 #[event]
@@ -24,6 +48,91 @@ pub struct LotteryRevealed {
     pub selected_pool_index: u8,
     /// 揭示时间戳
     pub revealed_at: i64,
+    /// 选中池的 Token 模式奖金上限 (micro-USD，0 表示不限制)
+    pub pool_max_win_usd: u64,
+    /// Token 模式可领取奖金 (按上限截断后，SOL 模式仍按 total_won_usd 发放)
+    pub token_won_usd: u64,
+    /// 请求时记录的 caller_seed (= hash(client_seed))
+    pub caller_seed_hash: [u8; 32],
+    /// hash(randomness)，与 caller_seed_hash 一起供外部验证者重建完整链路
+    pub randomness_hash: [u8; 32],
+    /// 请求创建时写入的可证明公平承诺，与 randomness_hash 关联同一请求
+    pub commitment: [u8; 32],
+    /// 请求时使用的 Oracle Queue，供链下与 VRF 回调交易交叉核对
+    pub oracle_queue: Pubkey,
+    /// 本次开奖时的保底阈值 (0 表示关闭)
+    pub pity_threshold: u16,
+    /// 保底机制强制提升到 Tier 3+ 的卡数 (已计入 total_won_usd)
+    pub pity_upgrades: u32,
+    /// 开奖后的用户保底计数器
+    pub pity_counter: u16,
+    /// 应用的 RTP 调节系数 (bps，10000 表示未调节)
+    pub rtp_modulation_bps: u16,
+    /// 计算调节系数时的 Vault 可用余额 (lamports)
+    pub vault_lamports: u64,
+    /// 调节前的中奖金额 (micro-USD)
+    pub unmodulated_won_usd: u64,
+    /// 领取截止时间戳 (revealed_at + CLAIM_TIMEOUT_SECONDS)
+    pub claim_deadline: i64,
+    /// 托管到 escrow PDA 的中奖金额 (lamports，按揭示时 Pyth 价格换算)
+    pub escrow_lamports: u64,
+    /// 逐卡开奖使用的 Tier 阈值表 (选中池的覆盖表或默认表)
+    pub tier_thresholds: [u64; 4],
+    /// 创建请求时传入的合作方标签
+    pub memo: [u8; 32],
+    /// 本次揭示分配到选中池的奖金 (micro-USD，无活跃池时为 0)
+    pub pool_assigned_usd: u64,
+    /// VRF 回调延迟 (秒，请求创建到收到随机数)
+    pub reveal_latency_seconds: u64,
+}
+
+/// 揭示失败事件 (请求置为 Failed，可立即退款)
+#[event]
+pub struct LotteryFailed {
+    pub user: Pubkey,
+    pub mint_request: Pubkey,
+    /// 失败原因 (FAILURE_*)
+    pub failure_reason: u8,
+    /// 失败时已处理的卡数
+    pub cards_processed: u32,
+    pub timestamp: i64,
+}
+
+/// 已揭示 (或揭示中) 的请求再次收到不同随机数的投递 (不修改状态，仅记录供排查 oracle 行为)
+#[event]
+pub struct ConflictingRandomness {
+    pub mint_request: Pubkey,
+    /// 已采用的 hash(randomness)
+    pub recorded_randomness_hash: [u8; 32],
+    /// 本次被丢弃的 hash(randomness)
+    pub discarded_randomness_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// 同一请求的随机数投递分类
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum RandomnessDelivery {
+    /// 首次投递 (请求仍为 Pending)
+    First,
+    /// 与已采用的随机数相同的重复投递 (幂等返回)
+    Duplicate,
+    /// 与已采用的随机数不同的投递 (记录 ConflictingRandomness 后返回，不覆盖)
+    Conflicting,
+}
+
+/// 按请求状态与已采用的随机数 hash 对本次投递分类
+pub(crate) fn classify_delivery(
+    status: RequestStatus,
+    recorded_hash: [u8; 32],
+    incoming_hash: [u8; 32],
+) -> RandomnessDelivery {
+    if !matches!(status, RequestStatus::Revealing | RequestStatus::Revealed) {
+        RandomnessDelivery::First
+    } else if recorded_hash == incoming_hash {
+        RandomnessDelivery::Duplicate
+    } else {
+        RandomnessDelivery::Conflicting
+    }
 }
 
 /// 处理 MagicBlock VRF 回调 (handler 入口)
@@ -31,65 +140,534 @@ pub struct LotteryRevealed {
 /// # 参数
 /// - `ctx`: 账户上下文 (ConsumeLotteryRandomness，定义于 lib.rs)
 /// - `randomness`: 32 字节 VRF 随机数
+/// - `amount_of_cards` / `request_id`: request_mint 写入 callback_args 的请求参数
 ///
 /// # 状态转换
-/// - MintRequest.status: Pending -> Revealed
+/// - MintRequest.status: Pending -> Revealing (揭示结算由 continue_reveal 完成)
+/// - 开奖计算出错时置为 Failed 并记录 failure_reason (交易仍成功，以便持久化)
 ///
 /// # 安全考虑
-/// - 仅允许 VRF 程序身份 PDA 调用 (由 lib.rs 中 address constraint 保证)
-/// - 防重放：仅处理 Pending 状态的请求
-/// - 幂等性：已 Revealed 的请求直接返回 Ok
-pub fn handler(ctx: Context<ConsumeLotteryRandomness>, randomness: [u8; 32]) -> Result<()> {
-    let mint_request = &mut ctx.accounts.mint_request;
-    let config = &ctx.accounts.config;
+/// - 仅允许 VRF 程序身份 PDA 调用 (与请求创建时记录的 vrf_identity 比对，默认 SDK 常量)
+/// - 防重放：仅处理 Pending 状态的请求，首次处理时记录 hash(randomness)
+/// - 幂等性：已 Revealing / Revealed 的请求直接返回 Ok；随机数与已采用的不同时发出
+///   ConflictingRandomness 且不修改状态
+/// - 被拒绝的投递记录随机数 hash 日志，供排查 oracle 行为
+/// - 回调不传入全局配置，开奖条款只能来自请求创建时的快照；请求所用 Oracle Queue 是否仍在白名单中
+///   由揭示结算时校验 (MagicBlock 回调不携带来源 queue，无法进一步比对)
+pub fn handler(
+    ctx: Context<ConsumeLotteryRandomness>,
+    randomness: [u8; 32],
+    amount_of_cards: u32,
+    request_id: u64,
+) -> Result<()> {
+    let accounts = &mut ctx.accounts;
+    require_keys_eq!(
+        accounts.vrf_program_identity.key(),
+        accounts.mint_request.vrf_identity_key(),
+        IPFlowError::InvalidVrfCallback
+    );
+    consume_randomness(
+        ctx.program_id,
+        &mut accounts.mint_request,
+        &accounts.user_stats,
+        &accounts.queue_stats,
+        randomness,
+        amount_of_cards,
+        request_id,
+    )
+}
+
+/// 回调主体：VRF 回调与 test-hooks 构建的 test_reveal 共用，调用方负责校验随机数来源
+pub(crate) fn consume_randomness<'info>(
+    program_id: &Pubkey,
+    mint_request: &mut Account<'info, MintRequest>,
+    user_stats_info: &UncheckedAccount<'info>,
+    queue_stats: &UncheckedAccount<'info>,
+    randomness: [u8; 32],
+    amount_of_cards: u32,
+    request_id: u64,
+) -> Result<()> {
     let clock = Clock::get()?;
+    let incoming_hash = randomness_hash(&randomness);
+
+    // 1. 幂等性检查：已收到随机数则直接返回成功 (防止网络抖动或 VRF 重试导致的重复调用)
+    //    随机数与已采用的不同时只记录事件，永不覆盖
+    match classify_delivery(
+        mint_request.status,
+        mint_request.recorded_randomness_hash(),
+        incoming_hash,
+    ) {
+        RandomnessDelivery::First => {}
+        RandomnessDelivery::Duplicate => {
+            msg!("Randomness already consumed, returning Ok (idempotent).");
+            return Ok(());
+        }
+        RandomnessDelivery::Conflicting => {
+            let recorded_randomness_hash = mint_request.recorded_randomness_hash();
+            emit!(ConflictingRandomness {
+                mint_request: mint_request.key(),
+                recorded_randomness_hash,
+                discarded_randomness_hash: incoming_hash,
+                timestamp: clock.unix_timestamp,
+            });
+            msg!(
+                "Conflicting randomness discarded: recorded={}, discarded={}",
+                Hash::new_from_array(recorded_randomness_hash),
+                Hash::new_from_array(incoming_hash)
+            );
+            return Ok(());
+        }
+    }
 
-    // 1. 幂等性检查：已揭示则直接返回成功
-    // 防止因网络抖动导致的重复调用
-    if mint_request.status == RequestStatus::Revealed {
-        msg!("Request already revealed, returning Ok (idempotent).");
-        return Ok(());
+    // 2. 投递校验 (状态 / 回调参数)，拒绝时记录随机数 hash
+    check_delivery(mint_request, amount_of_cards, request_id).map_err(|e| {
+        msg!(
+            "Randomness delivery rejected: randomness_hash={}",
+            Hash::new_from_array(incoming_hash)
+        );
+        e
+    })?;
+
+    // 2.2 记录 VRF 回调延迟，累计到请求所用 queue 的统计 (QueueStats 未创建时跳过)
+    let latency = reveal_latency_seconds(mint_request.created_at, clock.unix_timestamp);
+    mint_request.reveal_latency_seconds = latency;
+    record_queue_latency(program_id, queue_stats, latency)?;
+
+    // 3. 读取受益人保底计数器 (UserStats 未创建或未迁移时保底不生效)
+    //    保底阈值为请求创建时锁定的值
+    let user_stats = load_user_stats(program_id, user_stats_info)?;
+    let pity_threshold = if user_stats.is_some() {
+        mint_request.reveal_pity_threshold
+    } else {
+        0
+    };
+    let pity_counter = user_stats.as_ref().map_or(0, |stats| stats.pity_counter);
+
+    // 4. 保存随机数与保底参数，进入分批处理 (逐卡结果可由这些字段复算)
+    mint_request.status = RequestStatus::Revealing;
+    mint_request.randomness = randomness;
+    mint_request.randomness_hash = incoming_hash;
+    mint_request.reveal_pity_threshold = pity_threshold;
+    mint_request.reveal_pity_counter = pity_counter;
+    mint_request.set_reveal_progress(&RevealProgress::start(pity_counter));
+
+    // 4.1 先选择奖品池 (在请求创建时快照的排期内活跃池中等概率选择)，锁定该池快照的 Tier 阈值表
+    //     后续批次与卡牌 NFT 复算均使用锁定的阈值表，不受管理员之后修改覆盖表的影响
+    //     升级前创建的请求没有快照，按默认表开奖，揭示结算时再选池
+    if mint_request.pool_snapshot_taken {
+        let selected_pool_index = select_active_prize_pool(
+            &randomness,
+            mint_request.pool_snapshot_count,
+            &mint_request.pool_snapshot_indices,
+        );
+        mint_request.selected_pool_index = selected_pool_index;
+        mint_request.tier_thresholds = mint_request.snapshot_tier_thresholds(selected_pool_index);
     }
 
-    // 2. 状态校验 (由 Accounts constraint 保证，这里做双重校验)
+    // 5. 处理第一批卡牌 (揭示结算依赖全局配置，由 continue_reveal 完成)
+    advance_reveal_batch(mint_request, &clock)?;
+    Ok(())
+}
+
+/// 续算分批揭示 (任何人可调用，通常由 keeper 反复调用直到揭示完成)
+///
+/// 卡牌全部处理完成后 (含回调内已处理完的请求) 完成揭示结算
+pub fn continue_reveal(ctx: Context<ContinueReveal>) -> Result<()> {
+    let clock = Clock::get()?;
     require!(
-        mint_request.status == RequestStatus::Pending,
-        IPFlowError::InvalidRequestStatus
+        ctx.accounts.mint_request.status == RequestStatus::Revealing,
+        IPFlowError::RevealNotInProgress
     );
 
-    // 3. 处理 VRF 结果，计算奖金和选择奖品池
-    let result = process_vrf_result(
-        &randomness,
+    let accounts = &mut ctx.accounts;
+    settle_reveal(
+        ctx.program_id,
+        &mut accounts.mint_request,
+        &mut accounts.config,
+        &accounts.leaderboard,
+        &accounts.user_stats,
+        &accounts.vault,
+        &accounts.pyth_price_update,
+        &accounts.escrow,
+        ctx.bumps.escrow,
+        &accounts.system_program,
+        &clock,
+    )
+}
+
+/// 处理下一批卡牌 (尚未全部处理时)，全部处理完成时完成揭示结算
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn settle_reveal<'info>(
+    program_id: &Pubkey,
+    mint_request: &mut Account<'info, MintRequest>,
+    config: &mut Account<'info, IPFlowState>,
+    leaderboard: &UncheckedAccount<'info>,
+    user_stats: &UncheckedAccount<'info>,
+    vault: &SystemAccount<'info>,
+    pyth_price_update: &Account<'info, PriceUpdateV2>,
+    escrow: &SystemAccount<'info>,
+    escrow_bump: u8,
+    system_program: &Program<'info, System>,
+    clock: &Clock,
+) -> Result<()> {
+    let complete = mint_request
+        .reveal_progress()
+        .is_complete(mint_request.amount_of_cards);
+    if !complete && !advance_reveal_batch(mint_request, clock)? {
+        return Ok(());
+    }
+
+    finalize_reveal(
+        program_id,
+        mint_request,
+        config,
+        leaderboard,
+        user_stats,
+        vault,
+        pyth_price_update,
+        escrow,
+        escrow_bump,
+        system_program,
+        clock,
+    )
+}
+
+/// 处理至多 REVEAL_CARDS_PER_IX 张卡并写回进度 (不读取全局配置)
+///
+/// 返回卡牌是否已全部处理完成；逐卡计算出错时置为 Failed 并返回 false
+fn advance_reveal_batch(mint_request: &mut Account<MintRequest>, clock: &Clock) -> Result<bool> {
+    let progress = match advance_reveal(
+        &mint_request.randomness,
         mint_request.amount_of_cards,
-        config.active_pool_count,
-        &config.active_pool_indices,
+        mint_request.reveal_pity_threshold,
+        &mint_request.effective_tier_thresholds(),
+        mint_request.reveal_progress(),
+        REVEAL_CARDS_PER_IX,
+    ) {
+        Ok(progress) => progress,
+        Err(err) => {
+            fail_reveal(mint_request, reveal_failure_reason(&err), clock)?;
+            return Ok(false);
+        }
+    };
+    mint_request.set_reveal_progress(&progress);
+
+    emit!(RevealAdvanced {
+        mint_request: mint_request.key(),
+        cards_processed: progress.cards_processed,
+        amount_of_cards: mint_request.amount_of_cards,
+    });
+    msg!(
+        "Reveal in progress: {}/{} cards",
+        progress.cards_processed,
+        mint_request.amount_of_cards
+    );
+    Ok(progress.is_complete(mint_request.amount_of_cards))
+}
+
+/// 完成揭示：RTP 调节、托管中奖金额、回写保底计数器，状态切换为 Revealed
+/// (LotteryRevealed 仅在此发出)
+///
+/// 请求所用 Oracle Queue 已被移出白名单时置为 Failed (可立即退款)；
+/// 托管所需的 Pyth 价格不可用时保持 Revealing 并返回 Ok，由 continue_reveal 以新价格重试
+#[allow(clippy::too_many_arguments)]
+fn finalize_reveal<'info>(
+    program_id: &Pubkey,
+    mint_request: &mut Account<'info, MintRequest>,
+    config: &mut Account<'info, IPFlowState>,
+    leaderboard: &UncheckedAccount<'info>,
+    user_stats: &UncheckedAccount<'info>,
+    vault: &SystemAccount<'info>,
+    pyth_price_update: &Account<'info, PriceUpdateV2>,
+    escrow: &SystemAccount<'info>,
+    escrow_bump: u8,
+    system_program: &Program<'info, System>,
+    clock: &Clock,
+) -> Result<()> {
+    let progress = mint_request.reveal_progress();
+    let randomness = mint_request.randomness;
+    let pity_threshold = mint_request.reveal_pity_threshold;
+
+    // 0. 请求创建时记录的 Oracle Queue 必须仍在白名单中 (已被移除的 queue 产生的随机数不再可信)
+    if check_request_queue(
+        &config.oracle_queues[..config.oracle_queue_count as usize],
+        &mint_request.oracle_queue,
     )
-    .map_err(|_| IPFlowError::MathOverflow)?;
+    .is_err()
+    {
+        return fail_reveal(mint_request, FAILURE_QUEUE_REVOKED, clock);
+    }
+
+    // 1. 奖品池已在回调时选定；升级前进入揭示的请求在此补选 (仅在排期窗口内的活跃池中等概率选择)
+    let selected_pool_index = if mint_request.pool_selected() {
+        mint_request.selected_pool_index
+    } else {
+        let (scheduled_indices, scheduled_count) =
+            mint_request.selection_pools(config, clock.unix_timestamp);
+        select_active_prize_pool(&randomness, scheduled_count, &scheduled_indices)
+    };
+
+    // 2. 按 Vault 健康度调节奖金 (系数与余额写入事件，结果可由公开输入复算)
+    let vault_lamports = vault_available_lamports(&vault.to_account_info())?;
+    let modulation_bps = if config.rtp_modulation_enabled {
+        rtp_modulation_bps(
+            vault_lamports,
+            config.vault_target_lamports,
+            config.max_modulation_bps,
+        )
+    } else {
+        BPS_DENOMINATOR as u16
+    };
+    let unmodulated_won_usd = progress.total_won_usd;
+    let Some(total_won_usd) = apply_rtp_modulation(unmodulated_won_usd, modulation_bps) else {
+        return fail_reveal(mint_request, FAILURE_MODULATION_OVERFLOW, clock);
+    };
+
+    // 3. 按当前价格将中奖金额托管到 escrow PDA，锁定领取汇率并预留兑付资金
+    //    Vault 余额不足时揭示失败 (用户可立即全额退款)，不再留到领取时才暴露
+    let mut escrow_lamports = 0u64;
+    if total_won_usd > 0 {
+        let lamports = match pyth_oracle::get_lamports_floor(
+            pyth_price_update,
+            total_won_usd,
+            config.max_price_age_seconds,
+        ) {
+            Ok(lamports) => lamports,
+            Err(err) => {
+                msg!("Escrow price unavailable, awaiting continue_reveal: {:?}", err);
+                return Ok(());
+            }
+        };
+        let rent = Rent::get()?.minimum_balance(0);
+        let required = escrow_funding_lamports(lamports, escrow.lamports(), rent)
+            .ok_or(IPFlowError::MathOverflow)?;
+        if required > vault_lamports {
+            return fail_reveal(mint_request, FAILURE_INSUFFICIENT_VAULT, clock);
+        }
+        fund_escrow(
+            &vault.to_account_info(),
+            config.vault_bump,
+            &escrow.to_account_info(),
+            &system_program.to_account_info(),
+            lamports,
+        )?;
+        escrow_lamports = lamports;
+    }
+
+    // 3.1 回写保底计数器 (UserStats 未创建或未迁移时不持久化)
+    //     在所有可失败的计算之后执行，揭示失败时不改变用户状态
+    if let Some(mut stats) = load_user_stats(program_id, user_stats)? {
+        stats.pity_counter = progress.pity_counter;
+        stats.try_serialize(&mut &mut user_stats.try_borrow_mut_data()?[..])?;
+    }
 
     // 4. 更新 MintRequest 状态
     mint_request.status = RequestStatus::Revealed;
-    mint_request.total_won_usd = result.total_won_usd;
-    mint_request.selected_pool_index = result.selected_pool_index;
+    mint_request.total_won_usd = total_won_usd;
+    mint_request.rtp_modulation_bps = modulation_bps;
+    mint_request.selected_pool_index = selected_pool_index;
     mint_request.revealed_at = clock.unix_timestamp;
+    mint_request.claim_deadline = clock.unix_timestamp.saturating_add(CLAIM_TIMEOUT_SECONDS);
     mint_request.reveal_slot = clock.slot;
+    mint_request.escrow_lamports = escrow_lamports;
+    mint_request.escrow_bump = escrow_bump;
+    config.outstanding_liabilities_usd = config
+        .outstanding_liabilities_usd
+        .checked_add(total_won_usd)
+        .ok_or(IPFlowError::MathOverflow)?;
+    config.total_collected = config
+        .total_collected
+        .checked_add(mint_request.paid_usd)
+        .ok_or(IPFlowError::MathOverflow)?;
+    config.total_escrowed_lamports = config
+        .total_escrowed_lamports
+        .checked_add(escrow_lamports)
+        .ok_or(IPFlowError::MathOverflow)?;
+    config.count_revealed = config.count_revealed.saturating_add(1);
+
+    // 5. 待揭示引用转为选中池的待领取计数 (阻止管理员删除仍被引用的奖品池)
+    //    并按选中池在请求创建时快照的奖金上限截断 Token 模式可领取金额
+    config.release_pool_pending_reveals(mint_request.snapshot_pool_indices());
+    config.add_pool_pending_claim(selected_pool_index)?;
+    let pool_max_win_usd = mint_request.pool_max_win_usd(config, selected_pool_index);
+    mint_request.token_won_usd = if pool_max_win_usd > 0 {
+        total_won_usd.min(pool_max_win_usd)
+    } else {
+        total_won_usd
+    };
+
+    // 5.0 按池 index 累计分配统计 (选中池 PDA 不在回调账户中，由 flush_pool_stats 同步)
+    let mut pool_assigned_usd = 0u64;
+    if (selected_pool_index as usize) < MAX_PRIZE_POOLS {
+        let index = selected_pool_index as usize;
+        config.pool_assigned_usd[index] = config.pool_assigned_usd[index]
+            .checked_add(total_won_usd)
+            .ok_or(IPFlowError::MathOverflow)?;
+        config.pool_times_selected[index] = config.pool_times_selected[index]
+            .checked_add(1)
+            .ok_or(IPFlowError::MathOverflow)?;
+        pool_assigned_usd = total_won_usd;
+    }
 
-    // 5. 发射事件 (供链下索引)
+    // 5.1 大额中奖标记人工审核
+    if config.requires_review(total_won_usd) {
+        mint_request.needs_review = true;
+        emit!(LargeWinFlagged {
+            user: mint_request.user,
+            mint_request: mint_request.key(),
+            total_won_usd,
+            threshold_usd: config.large_win_threshold_usd,
+        });
+        msg!("Large win flagged for review: {} (micro-USD)", total_won_usd);
+    }
+
+    // 5.2 更新单笔中奖排行榜 (未创建时跳过；未上榜结果仅比较一次)
+    if leaderboard.owner == program_id {
+        let mut data = leaderboard.try_borrow_mut_data()?;
+        let mut board = Leaderboard::try_deserialize(&mut &data[..])?;
+        let entry = LeaderboardEntry {
+            user: mint_request.user,
+            total_won_usd,
+            request_id: mint_request.vrf_request_slot,
+            revealed_at: clock.unix_timestamp,
+        };
+        if let Some(rank) = board.try_insert(entry) {
+            board.try_serialize(&mut &mut data[..])?;
+            emit!(LeaderboardUpdated {
+                season: board.season,
+                rank: rank as u8,
+                user: entry.user,
+                total_won_usd: entry.total_won_usd,
+                request_id: entry.request_id,
+                revealed_at: entry.revealed_at,
+            });
+        }
+    }
+
+    // 6. 发射事件 (供链下索引)
     emit!(LotteryRevealed {
         user: mint_request.user,
         mint_request: mint_request.key(),
-        total_won_usd: result.total_won_usd,
-        selected_pool_index: result.selected_pool_index,
+        total_won_usd,
+        selected_pool_index,
         revealed_at: clock.unix_timestamp,
+        claim_deadline: mint_request.claim_deadline,
+        pool_max_win_usd,
+        token_won_usd: mint_request.token_won_usd,
+        caller_seed_hash: mint_request.caller_seed_hash,
+        randomness_hash: randomness_hash(&randomness),
+        commitment: mint_request.commitment,
+        oracle_queue: mint_request.oracle_queue,
+        pity_threshold,
+        pity_upgrades: progress.pity_upgrades,
+        pity_counter: progress.pity_counter,
+        rtp_modulation_bps: modulation_bps,
+        vault_lamports,
+        unmodulated_won_usd,
+        escrow_lamports,
+        tier_thresholds: mint_request.effective_tier_thresholds(),
+        memo: mint_request.memo,
+        pool_assigned_usd,
+        reveal_latency_seconds: mint_request.reveal_latency_seconds,
     });
 
     msg!(
-        "Lottery Revealed: User={}, Cards={}, Total Won USD={} (micro), Pool Index={}",
+        "Lottery Revealed: User={}, Cards={}, Total Won USD={} (micro), Pool Index={}, Pity Upgrades={}",
         mint_request.user,
         mint_request.amount_of_cards,
-        result.total_won_usd,
-        result.selected_pool_index
+        total_won_usd,
+        selected_pool_index,
+        progress.pity_upgrades
+    );
+
+    Ok(())
+}
+
+/// 揭示计算出错：请求置为 Failed 并记录原因后正常返回，使状态得以持久化，
+/// 用户随即可通过 refund 全额退款 (无需等待超时)
+fn fail_reveal(mint_request: &mut Account<MintRequest>, reason: u8, clock: &Clock) -> Result<()> {
+    mint_request.status = RequestStatus::Failed;
+    mint_request.failure_reason = reason;
+
+    emit!(LotteryFailed {
+        user: mint_request.user,
+        mint_request: mint_request.key(),
+        failure_reason: reason,
+        cards_processed: mint_request.cards_processed,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Lottery failed: mint_request={}, reason={}",
+        mint_request.key(),
+        reason
+    );
+    Ok(())
+}
+
+/// 逐卡计算错误对应的失败原因
+fn reveal_failure_reason(err: &ProgramError) -> u8 {
+    match err {
+        ProgramError::ArithmeticOverflow => FAILURE_REVEAL_OVERFLOW,
+        _ => FAILURE_REVEAL_INVARIANT,
+    }
+}
+
+/// 按 UserStats 反序列化 (仅在 owner 为本程序且为当前结构大小时，否则视为未创建)
+fn load_user_stats(program_id: &Pubkey, info: &AccountInfo) -> Result<Option<UserStats>> {
+    if info.owner == program_id && info.data_len() >= 8 + UserStats::INIT_SPACE {
+        Ok(Some(UserStats::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// 累计 Oracle Queue 回调延迟 (仅在 owner 为本程序时按 QueueStats 反序列化，否则视为未创建)
+fn record_queue_latency(program_id: &Pubkey, info: &AccountInfo, latency: u64) -> Result<()> {
+    if info.owner != program_id {
+        return Ok(());
+    }
+    let mut data = info.try_borrow_mut_data()?;
+    let mut stats = QueueStats::try_deserialize(&mut &data[..])?;
+    stats.record(latency);
+    stats.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+/// 首次投递的前置校验
+///
+/// - 状态：Claimed / Failed 等非 Pending 状态返回各自的错误码
+/// - 回调参数交叉校验：奖金计算基数必须与请求时一致
+fn check_delivery(mint_request: &MintRequest, amount_of_cards: u32, request_id: u64) -> Result<()> {
+    mint_request.status.check_pending()?;
+    check_callback_args(
+        mint_request.amount_of_cards,
+        mint_request.vrf_request_slot,
+        amount_of_cards,
+        request_id,
+    )
+}
+
+/// 校验 VRF 回调参数与 MintRequest 中存储的字段一致
+fn check_callback_args(
+    stored_amount_of_cards: u32,
+    stored_request_id: u64,
+    amount_of_cards: u32,
+    request_id: u64,
+) -> Result<()> {
+    require!(
+        stored_amount_of_cards == amount_of_cards && stored_request_id == request_id,
+        IPFlowError::InvalidVrfCallback
     );
+    Ok(())
+}
 
+/// 校验请求创建时记录的 Oracle Queue 仍在当前白名单中
+fn check_request_queue(whitelist: &[Pubkey], request_queue: &Pubkey) -> Result<()> {
+    require!(
+        whitelist.contains(request_queue),
+        IPFlowError::InvalidOracleQueue
+    );
     Ok(())
 }
 
@@ -98,6 +676,7 @@ pub fn handler(ctx: Context<ConsumeLotteryRandomness>, randomness: [u8; 32]) ->
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::vrf_helper::process_vrf_result;
 
     /// 测试 LotteryRevealed 事件结构
     #[test]
@@ -109,12 +688,49 @@ mod tests {
             total_won_usd: 100_000_000, // 100 USD
             selected_pool_index: 2,
             revealed_at: 1700000000,
+            claim_deadline: 1700000000 + CLAIM_TIMEOUT_SECONDS,
+            pool_max_win_usd: 50_000_000,
+            token_won_usd: 50_000_000,
+            caller_seed_hash: [1u8; 32],
+            randomness_hash: [2u8; 32],
+            commitment: [3u8; 32],
+            oracle_queue: Pubkey::default(),
+            pity_threshold: 10,
+            pity_upgrades: 1,
+            pity_counter: 0,
+            rtp_modulation_bps: 10_000,
+            vault_lamports: 0,
+            unmodulated_won_usd: 100_000_000,
+            escrow_lamports: 1_000_000_000,
+            tier_thresholds: crate::constants::DEFAULT_TIER_THRESHOLDS,
+            memo: [0u8; 32],
+            pool_assigned_usd: 100_000_000,
+            reveal_latency_seconds: 3,
         };
 
         assert_eq!(event.total_won_usd, 100_000_000);
         assert_eq!(event.selected_pool_index, 2);
     }
 
+    #[test]
+    fn test_reveal_failure_reason_codes() {
+        assert_eq!(
+            reveal_failure_reason(&ProgramError::ArithmeticOverflow),
+            FAILURE_REVEAL_OVERFLOW
+        );
+        assert_eq!(
+            reveal_failure_reason(&ProgramError::InvalidArgument),
+            FAILURE_REVEAL_INVARIANT
+        );
+    }
+
+    #[test]
+    fn test_callback_args_must_match_request() {
+        assert!(check_callback_args(5, 100, 5, 100).is_ok());
+        assert!(check_callback_args(5, 100, 10, 100).is_err());
+        assert!(check_callback_args(5, 100, 5, 101).is_err());
+    }
+
     /// 测试随机数处理边界条件 - 全零
     #[test]
     fn test_randomness_boundary_zero() {
@@ -133,14 +749,14 @@ mod tests {
         assert!(result.is_ok());
     }
 
-    /// 测试无活跃池时返回默认索引
+    /// 测试无活跃池时返回哨兵索引
     #[test]
     fn test_no_active_pools() {
         let randomness = [42u8; 32];
         let empty_indices = [255u8; 50];
         let result = process_vrf_result(&randomness, 1, 0, &empty_indices);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap().selected_pool_index, 0);
+        assert_eq!(result.unwrap().selected_pool_index, crate::constants::NO_PRIZE_POOL);
     }
 
     /// 测试多张卡的奖金累加
@@ -206,6 +822,57 @@ mod tests {
         assert_eq!(result_a.selected_pool_index, result_b.selected_pool_index);
     }
 
+    /// 测试请求 queue 校验：轮换主 queue 不影响已发起请求，移除后回调被拒绝
+    #[test]
+    fn test_request_queue_must_stay_whitelisted() {
+        let primary = Pubkey::new_unique();
+        let secondary = Pubkey::new_unique();
+
+        assert!(check_request_queue(&[secondary, primary], &primary).is_ok());
+        assert_eq!(
+            check_request_queue(&[secondary], &primary).unwrap_err(),
+            error!(IPFlowError::InvalidOracleQueue)
+        );
+        assert!(check_request_queue(&[secondary], &Pubkey::default()).is_err());
+    }
+
+    /// 模拟同一请求的重复投递：相同随机数幂等，不同随机数记录冲突且不覆盖
+    #[test]
+    fn test_double_delivery_equal_and_different_payloads() {
+        let first = [7u8; 32];
+        let (first_hash, retry_hash) = (randomness_hash(&first), randomness_hash(&[9u8; 32]));
+        let mut request = MintRequest::zeroed();
+
+        // 首次投递：Pending 状态，记录随机数与 hash
+        assert_eq!(
+            classify_delivery(request.status, request.recorded_randomness_hash(), first_hash),
+            RandomnessDelivery::First
+        );
+        request.status = RequestStatus::Revealing;
+        request.randomness = first;
+        request.randomness_hash = first_hash;
+
+        for status in [RequestStatus::Revealing, RequestStatus::Revealed] {
+            request.status = status;
+            assert_eq!(
+                classify_delivery(status, request.recorded_randomness_hash(), first_hash),
+                RandomnessDelivery::Duplicate
+            );
+            assert_eq!(
+                classify_delivery(status, request.recorded_randomness_hash(), retry_hash),
+                RandomnessDelivery::Conflicting
+            );
+        }
+
+        // 升级前揭示的请求未记录 hash，按已保存的随机数判断
+        request.randomness_hash = [0u8; 32];
+        assert_eq!(request.recorded_randomness_hash(), first_hash);
+        assert_eq!(
+            classify_delivery(request.status, request.recorded_randomness_hash(), first_hash),
+            RandomnessDelivery::Duplicate
+        );
+    }
+
     /// 辅助函数：创建活跃池索引数组
     fn create_active_pool_indices(active: &[u8]) -> [u8; 50] {
         let mut indices = [255u8; 50];
@@ -3,5 +3,9 @@
 // 处理 VRF 回调的指令集
 
 pub mod consume_randomness;
+#[cfg(feature = "test-hooks")]
+pub mod test_hooks;
 
 pub use consume_randomness::*;
+#[cfg(feature = "test-hooks")]
+pub use test_hooks::*;
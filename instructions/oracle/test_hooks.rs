@@ -0,0 +1,48 @@
+// ==================== 测试钩子 (仅 test-hooks 构建) ====================
+//
+// localnet / bankrun 未部署 MagicBlock VRF 程序，request_mint 在 test-hooks 构建中跳过 VRF CPI，
+// 由管理员调用 test_reveal 传入确定性随机数，依次执行与 consume_lottery_randomness 及 continue_reveal
+// 相同的揭示流程 (卡数不超过 REVEAL_CARDS_PER_IX 时一次完成揭示结算)。
+// 该模块仅在 test-hooks feature 下编译，且与 mainnet feature 互斥 (见 lib.rs 编译期断言)。
+
+use anchor_lang::prelude::*;
+
+use crate::instructions::oracle::consume_randomness::{consume_randomness, settle_reveal};
+use crate::state::RequestStatus;
+use crate::TestReveal;
+
+/// 以指定随机数揭示请求 (仅管理员)，回调参数取自 MintRequest 本身
+pub fn test_reveal(ctx: Context<TestReveal>, randomness: [u8; 32]) -> Result<()> {
+    let accounts = &mut ctx.accounts;
+    let amount_of_cards = accounts.mint_request.amount_of_cards;
+    let request_id = accounts.mint_request.vrf_request_slot;
+
+    msg!("test-hooks: test_reveal for request {}", request_id);
+
+    consume_randomness(
+        ctx.program_id,
+        &mut accounts.mint_request,
+        &accounts.user_stats,
+        &accounts.queue_stats,
+        randomness,
+        amount_of_cards,
+        request_id,
+    )?;
+    if accounts.mint_request.status != RequestStatus::Revealing {
+        return Ok(());
+    }
+
+    settle_reveal(
+        ctx.program_id,
+        &mut accounts.mint_request,
+        &mut accounts.config,
+        &accounts.leaderboard,
+        &accounts.user_stats,
+        &accounts.vault,
+        &accounts.pyth_price_update,
+        &accounts.escrow,
+        ctx.bumps.escrow,
+        &accounts.system_program,
+        &Clock::get()?,
+    )
+}
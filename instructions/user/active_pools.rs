@@ -0,0 +1,164 @@
+// ==================== 活跃奖品池查询 (只读) ====================
+//
+// 前端通过 simulateTransaction 调用，一次性读取所有活跃奖品池信息，
+// 替代 config + 最多 50 个 PrizePoolAccount 的 getMultipleAccounts。
+//
+// remaining_accounts: 按 config.active_pool_indices 顺序传入奖品池 PDA，
+// 逐个校验 owner/discriminator、index 与 PDA 种子，兼作索引表一致性检查。
+// 结果以 borsh 写入 return data，超过 1024 字节时截断并置 has_more。
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::MAX_RETURN_DATA;
+
+use crate::errors::IPFlowError;
+use crate::instructions::admin::prize_pool::load_prize_pool;
+use crate::state::PoolType;
+use crate::GetActivePools;
+
+/// 单个活跃奖品池摘要
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ActivePoolInfo {
+    pub index: u8,
+    pub swap_pool: Pubkey,
+    pub pool_type: PoolType,
+    pub name: String,
+    /// 选中权重 (当前 VRF 在活跃池间等概率选择，固定为 1)
+    pub weight: u16,
+    /// Token 符号 (未设置时为 None)
+    pub symbol: Option<String>,
+    /// 元数据 / logo URI (未设置时为 None)
+    pub metadata_uri: Option<String>,
+}
+
+/// get_active_pools 返回值
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ActivePoolList {
+    pub pools: Vec<ActivePoolInfo>,
+    /// return data 超出上限被截断时为 true，前端需改用 getMultipleAccounts 读取剩余池
+    pub has_more: bool,
+}
+
+pub fn get_active_pools<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GetActivePools<'info>>,
+) -> Result<ActivePoolList> {
+    let config = &ctx.accounts.config;
+    let active_count = config.active_pool_count as usize;
+
+    require!(
+        ctx.remaining_accounts.len() == active_count,
+        IPFlowError::InvalidPrizePoolIndex
+    );
+
+    let mut pools = Vec::with_capacity(active_count);
+    for (info, &index) in ctx
+        .remaining_accounts
+        .iter()
+        .zip(config.active_pool_indices[..active_count].iter())
+    {
+        // owner + discriminator + PDA 种子校验
+        let pool = load_prize_pool(info, ctx.program_id)?;
+        require!(pool.index == index, IPFlowError::InvalidPrizePoolIndex);
+
+        pools.push(ActivePoolInfo {
+            index,
+            swap_pool: pool.swap_pool,
+            pool_type: pool.pool_type,
+            name: pool.name.clone(),
+            weight: 1,
+            symbol: pool.symbol.clone(),
+            metadata_uri: pool.metadata_uri.clone(),
+        });
+    }
+
+    let list = fit_return_data(pools);
+    msg!(
+        "Active pools returned: {} (has_more={})",
+        list.pools.len(),
+        list.has_more
+    );
+    Ok(list)
+}
+
+/// 按顺序保留能放入 return data (MAX_RETURN_DATA 字节) 的池，其余截断
+pub(crate) fn fit_return_data(pools: Vec<ActivePoolInfo>) -> ActivePoolList {
+    // 4 (Vec 长度前缀) + 1 (has_more)
+    let mut size = 4 + 1;
+    let total = pools.len();
+    let mut kept = Vec::with_capacity(total);
+
+    for pool in pools {
+        // 1 (index) + 32 (swap_pool) + 1 (pool_type) + 4 + name (String) + 2 (weight)
+        // + symbol / metadata_uri (Option<String>: 1，Some 时另加 4 + 长度)
+        let display_size =
+            optional_string_size(&pool.symbol) + optional_string_size(&pool.metadata_uri);
+        let entry_size = 1 + 32 + 1 + 4 + pool.name.len() + 2 + display_size;
+        if size + entry_size > MAX_RETURN_DATA {
+            break;
+        }
+        size += entry_size;
+        kept.push(pool);
+    }
+
+    ActivePoolList {
+        has_more: kept.len() < total,
+        pools: kept,
+    }
+}
+
+/// Option<String> 的 borsh 编码长度
+fn optional_string_size(value: &Option<String>) -> usize {
+    1 + value.as_ref().map_or(0, |s| 4 + s.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(index: u8, name: &str) -> ActivePoolInfo {
+        ActivePoolInfo {
+            index,
+            swap_pool: Pubkey::new_unique(),
+            pool_type: PoolType::RaydiumCPMM,
+            name: name.to_string(),
+            weight: 1,
+            symbol: None,
+            metadata_uri: None,
+        }
+    }
+
+    #[test]
+    fn fits_small_list_without_truncation() {
+        let list = fit_return_data((0..5).map(|i| pool(i, "BONK")).collect());
+        assert_eq!(list.pools.len(), 5);
+        assert!(!list.has_more);
+        assert!(list.try_to_vec().unwrap().len() <= MAX_RETURN_DATA);
+    }
+
+    #[test]
+    fn truncates_full_list_to_return_data_limit() {
+        // 50 个池 * 58 字节 (16 字节名称，未设置 symbol / metadata_uri) 远超 1024 字节
+        let list = fit_return_data((0..50).map(|i| pool(i, "ABCDEFGHIJKLMNOP")).collect());
+        assert!(list.has_more);
+        assert_eq!(list.pools.len(), (MAX_RETURN_DATA - 5) / 58);
+        assert_eq!(list.pools.last().unwrap().index as usize, list.pools.len() - 1);
+
+        let encoded = list.try_to_vec().unwrap();
+        assert!(encoded.len() <= MAX_RETURN_DATA);
+    }
+
+    #[test]
+    fn size_estimate_includes_display_fields() {
+        let mut with_metadata = pool(0, "BONK");
+        with_metadata.symbol = Some("BONK".to_string());
+        with_metadata.metadata_uri = Some("https://example.com/bonk.json".to_string());
+        let pools: Vec<_> = (0..50).map(|_| with_metadata.clone()).collect();
+
+        let list = fit_return_data(pools);
+        assert!(list.has_more);
+        let encoded = list.try_to_vec().unwrap();
+        assert!(encoded.len() <= MAX_RETURN_DATA);
+        // 估算与实际编码一致：再多一个池就会超限
+        let entry = with_metadata.try_to_vec().unwrap().len();
+        assert!(encoded.len() + entry > MAX_RETURN_DATA);
+    }
+}
@@ -0,0 +1,191 @@
+// ==================== 卡牌 NFT 铸造 ====================
+//
+// 每张卡铸造一个 Metaplex Core 资产，元数据 URI 编码 Tier、该卡奖金、request_id 与卡牌索引。
+// 逐卡结果由揭示时保存的随机数与保底参数复算，与 LotteryRevealed 的总额一致 (RTP 调节前)。
+//
+// remaining_accounts: 从 mint_request.cards_minted 起依次传入卡牌资产 PDA
+//   [SEED_CARD_ASSET, mint_request, card_index (u32 LE)]，单笔最多 MAX_CARD_NFTS_PER_TX 个。
+// 可在 claim 前后分批调用；已开始铸造的请求在 claim 后保留 PDA，铸造完最后一批时关闭。
+
+use anchor_lang::prelude::*;
+
+use crate::constants::*;
+use crate::errors::IPFlowError;
+use crate::events::CardNftsMinted;
+use crate::state::*;
+use crate::utils::{card_outcomes, close_account_to, core_cpi, CardOutcome};
+use crate::MintCardNfts;
+
+pub fn mint_card_nfts<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MintCardNfts<'info>>,
+    vrf_request_slot: u64,
+    max_cards: u8,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.card_collection.is_active(), IPFlowError::CardNftsDisabled);
+
+    let request = &ctx.accounts.mint_request;
+    check_mintable_status(request.status)?;
+
+    let start = request.cards_minted;
+    let count = card_batch_size(
+        request.amount_of_cards,
+        start,
+        max_cards,
+        ctx.remaining_accounts.len(),
+    );
+    require!(count > 0, IPFlowError::CardNftsComplete);
+
+    let outcomes = card_outcomes(
+        &request.randomness,
+        request.amount_of_cards,
+        request.reveal_pity_threshold,
+        request.reveal_pity_counter,
+        &request.effective_tier_thresholds(),
+        start,
+        count,
+    );
+
+    let request_key = request.key();
+    let uri_prefix = config.card_collection.uri_prefix();
+    let authority_bump = ctx.bumps.collection_authority;
+    let authority_seeds: &[&[u8]] = &[SEED_COLLECTION_AUTHORITY, &[authority_bump]];
+
+    for (offset, (asset, outcome)) in ctx
+        .remaining_accounts
+        .iter()
+        .zip(outcomes.iter())
+        .enumerate()
+    {
+        let card_index = start + offset as u32;
+        let index_bytes = card_index.to_le_bytes();
+        let (expected, asset_bump) = Pubkey::find_program_address(
+            &[SEED_CARD_ASSET, request_key.as_ref(), &index_bytes],
+            ctx.program_id,
+        );
+        require_keys_eq!(asset.key(), expected, IPFlowError::InvalidCardAsset);
+
+        let asset_seeds: &[&[u8]] = &[
+            SEED_CARD_ASSET,
+            request_key.as_ref(),
+            &index_bytes,
+            &[asset_bump],
+        ];
+        core_cpi::create_asset(
+            ctx.accounts.mpl_core_program.to_account_info(),
+            asset.clone(),
+            ctx.accounts.collection.to_account_info(),
+            ctx.accounts.collection_authority.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            &card_name(card_index, outcome),
+            &card_uri(uri_prefix, vrf_request_slot, card_index, outcome),
+            &[asset_seeds, authority_seeds],
+        )?;
+    }
+
+    let request = &mut ctx.accounts.mint_request;
+    request.cards_minted = start + count;
+
+    emit!(CardNftsMinted {
+        user: request.user,
+        mint_request: request_key,
+        request_id: vrf_request_slot,
+        first_card_index: start,
+        count,
+        cards_minted: request.cards_minted,
+    });
+
+    msg!(
+        "Card NFTs minted: {}..{} of {}",
+        start,
+        request.cards_minted,
+        request.amount_of_cards
+    );
+
+    // 已领取且全部铸造完成：关闭 MintRequest PDA，租金退还给用户
+    if request.closes_after_claim() {
+        close_account_to(
+            &ctx.accounts.mint_request.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// 仅已揭示 (Revealed) 或已领取但保留 PDA (Claimed) 的请求可铸造
+fn check_mintable_status(status: RequestStatus) -> Result<()> {
+    match status {
+        RequestStatus::Revealed | RequestStatus::Claimed => Ok(()),
+        RequestStatus::Pending => err!(IPFlowError::RequestStillPending),
+        RequestStatus::Failed => err!(IPFlowError::RequestFailed),
+        RequestStatus::Claiming => err!(IPFlowError::ClaimInProgress),
+        RequestStatus::Revealing => err!(IPFlowError::RevealInProgress),
+    }
+}
+
+/// 本批铸造数量: 受剩余卡数、调用方上限、MAX_CARD_NFTS_PER_TX 与传入资产账户数共同限制
+pub(crate) fn card_batch_size(
+    amount_of_cards: u32,
+    cards_minted: u32,
+    max_cards: u8,
+    asset_accounts: usize,
+) -> u32 {
+    amount_of_cards
+        .saturating_sub(cards_minted)
+        .min(max_cards.min(MAX_CARD_NFTS_PER_TX) as u32)
+        .min(asset_accounts.min(u32::MAX as usize) as u32)
+}
+
+/// 卡牌 NFT 名称，例如 "Card #3 T4"
+pub(crate) fn card_name(card_index: u32, outcome: &CardOutcome) -> String {
+    format!("Card #{} T{}", card_index + 1, outcome.tier)
+}
+
+/// 卡牌 NFT 元数据 URI: {prefix}?tier=&won=&request=&card=
+pub(crate) fn card_uri(prefix: &str, request_id: u64, card_index: u32, outcome: &CardOutcome) -> String {
+    format!(
+        "{}?tier={}&won={}&request={}&card={}",
+        prefix, outcome.tier, outcome.won_usd, request_id, card_index
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_size_respects_all_limits() {
+        assert_eq!(card_batch_size(25, 0, 255, 40), MAX_CARD_NFTS_PER_TX as u32);
+        assert_eq!(card_batch_size(25, 20, 10, 10), 5);
+        assert_eq!(card_batch_size(25, 0, 3, 10), 3);
+        assert_eq!(card_batch_size(25, 0, 10, 2), 2);
+        assert_eq!(card_batch_size(25, 25, 10, 10), 0);
+    }
+
+    #[test]
+    fn metadata_encodes_tier_win_request_and_index() {
+        let outcome = CardOutcome { tier: 3, won_usd: 21_500_000 };
+        assert_eq!(card_name(0, &outcome), "Card #1 T3");
+        assert_eq!(
+            card_uri("https://cards.example/meta", 4242, 0, &outcome),
+            "https://cards.example/meta?tier=3&won=21500000&request=4242&card=0"
+        );
+    }
+
+    #[test]
+    fn only_revealed_or_claimed_requests_mint() {
+        assert!(check_mintable_status(RequestStatus::Revealed).is_ok());
+        assert!(check_mintable_status(RequestStatus::Claimed).is_ok());
+        assert_eq!(
+            check_mintable_status(RequestStatus::Claiming).unwrap_err(),
+            error!(IPFlowError::ClaimInProgress)
+        );
+        assert_eq!(
+            check_mintable_status(RequestStatus::Pending).unwrap_err(),
+            error!(IPFlowError::RequestStillPending)
+        );
+    }
+}
@@ -1,16 +1,30 @@
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::{self, get_associated_token_address};
 
 use crate::constants::*;
 use crate::errors::IPFlowError;
-use crate::events::ClaimCompleted;
+use crate::events::{
+    ClaimCompleted, ClaimDeadlineExtended, ClaimPreauthorizationCleared, ClaimPreauthorized,
+    PoolVolumeUpdated, SwapFailureReported,
+};
+use crate::instructions::user::epoch::require_epoch_stats;
+use crate::instructions::user::rewards::accrue_reward;
 use crate::state::*;
-use crate::utils::{jupiter_cpi, pyth_oracle, raydium_cpi, wsol_helper};
-use crate::Claim;
+use crate::utils::{
+    close_account_to, jupiter_cpi, locked_lamports_for_usd, pyth_oracle, raydium_cpi,
+    release_request_escrow, vault_available_lamports, wsol_helper,
+};
+use crate::{Claim, PreAuthorizeClaim, RecordPayoutBlock, ReportSwapFailure};
 
 // ==================== Token Claim 账户说明 ====================
 //
 // Task 1.20: 双路由架构 (Jupiter / Raydium)
 //
+// **用户输出账户** (两种路由共用):
+//   - 通过命名账户 token_program / associated_token_program / token_mint / user_token_account 传入
+//   - user_token_account 必须是 (user, token_mint) 的 ATA，不存在时合约以签名者 (authority) 为 payer 幂等创建
+//   - swap 前后余额比对始终基于该命名账户，不再依赖 remaining_accounts 中的位置
+//
 // **Jupiter 路由** (推荐):
 //   - remaining_accounts 由前端从 Jupiter /v6/swap-instructions API 获取
 //   - 第一个账户必须是 Jupiter Program (JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4)
@@ -24,13 +38,13 @@ use crate::Claim;
 //     [2] amm_config - AMM 配置
 //     [3] pool_state - 池子状态
 //     [4] input_token_account - Vault 的 WSOL ATA (输入，合约自动 wrap SOL)
-//     [5] output_token_account - 用户的目标 Token Account (输出)
+//     [5] output_token_account - 用户的目标 Token ATA (输出，须与 user_token_account 一致)
 //     [6] input_vault - Pool WSOL Vault
 //     [7] output_vault - Pool Token Vault
 //     [8] input_token_program (SPL Token，同时用于 wrap_sol)
 //     [9] output_token_program
 //     [10] input_token_mint (WSOL)
-//     [11] output_token_mint
+//     [11] output_token_mint (须与 token_mint 一致)
 //     [12] observation_state
 //   - swap_data 不使用 (Raydium 参数通过 expected_token_output 传入)
 //   - **自动 WSOL 包装**: 合约在 swap 前自动将 Vault SOL 包装到 WSOL ATA
@@ -40,267 +54,1722 @@ use crate::Claim;
 //   Raydium: getSwapQuote → claim(Raydium, ...)
 
 /// 用户领取奖励
-/// - SOL 模式：直接从 Vault 转账 (95% 发放)
-/// - Token 模式：通过 Jupiter/Raydium CPI Swap (100% 发放，用户承担滑点)
+/// - SOL 模式：直接从 Vault 转账 (按 config.sol_payout_bps 发放，默认 95%)
+/// - Token 模式：通过 Jupiter/Raydium CPI Swap (按 config.token_payout_bps 发放，默认 100%，用户承担滑点)
+/// - Split 模式：sol_portion_bps 部分按 SOL 模式发放，其余按 Token 模式 swap (两组账户均须传入)
 ///
 /// # 参数
-/// - `payout_mode`: SOL 或 Token 发放方式
+/// - `payout_mode`: SOL、Token 或 Split 发放方式
 /// - `swap_router`: Token 模式时选择 DEX 路由 (Jupiter/Raydium)，SOL 模式传 None
 /// - `expected_token_output`: Token 模式必填，前端从 DEX quote 获取的预期输出量
 /// - `swap_data`: Token 模式 Jupiter 路由必填；Raydium 路由不需要
+/// - `vrf_request_slot`: 请求 ID，写入 ClaimReceipt
+/// - `fallback_to_sol`: Token 模式 swap 前置校验因路由 / discriminator / 奖品池原因失败时，是否自动降级为 SOL 发放
+/// - `create_receipt`: 是否创建 ClaimReceipt PDA (须同时传入 receipt 账户)
+/// - `sol_portion_bps`: Split 模式 SOL 部分占比 (0 等同 Token，10000 等同 SOL)，其他模式忽略
+/// - `wrap_payout`: SOL 发放 (含 Split 的 SOL 部分与降级发放) 是否包装为 WSOL 转入用户的 WSOL ATA
+///   (须传入 user_wsol_account / wsol_mint / token_program / associated_token_program)
+///
+/// # 预授权说明
+/// 用户通过 pre_authorize_claim 锁定条款后，只能以 Token 模式领取: swap_router 须为 None 或与预授权一致，
+/// expected_token_output 与滑点取自预授权 (忽略指令参数)，且不接受调用方的 fallback_to_sol。
+///
+/// # SOL 降级说明
+/// 降级只发生在 swap 前置校验阶段，且仅限 SOL_FALLBACK_ERRORS 中的路由、discriminator、奖品池错误
+/// (参数缺失、输出 ATA、Vault WSOL 账户等调用方错误照常报错)，此时尚未移动任何资金。
+/// DEX CPI 本身失败会导致整笔交易回滚 (Solana 运行时不允许捕获 CPI 错误)，
+/// 因此 Vault 不可能同时完成 swap 与 SOL 转账，不存在重复支付。
+#[allow(clippy::too_many_arguments)]
 pub fn handler<'info>(
     ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
     payout_mode: PayoutMode,
     swap_router: Option<SwapRouter>,
     expected_token_output: Option<u64>,
     swap_data: Option<Vec<u8>>,
+    vrf_request_slot: u64,
+    fallback_to_sol: bool,
+    create_receipt: bool,
+    sol_portion_bps: u16,
+    wrap_payout: bool,
 ) -> Result<()> {
     let clock = Clock::get()?;
-    let request = &mut ctx.accounts.mint_request;
+    let payout_mode = resolve_payout_mode(payout_mode, sol_portion_bps)?;
+    let terms = resolve_claim_terms(
+        &ctx.accounts.mint_request,
+        payout_mode,
+        swap_router,
+        expected_token_output,
+        fallback_to_sol,
+    )?;
+
+    // 签名者校验：user 本人，或持有未过期委托的会话密钥
+    authorize_claimer(ctx.accounts, clock.unix_timestamp)?;
 
-    // 1. 校验领取超时 (24 小时)
-    let claim_timeout = CLAIM_TIMEOUT_SECONDS;
+    // receipt 账户必须与 create_receipt 参数一致 (避免意外扣取租金)
     require!(
-        clock.unix_timestamp - request.revealed_at < claim_timeout,
-        IPFlowError::ClaimExpired
+        create_receipt == ctx.accounts.receipt.is_some(),
+        IPFlowError::ClaimReceiptMismatch
     );
 
-    // 2. 根据 payout_mode 执行发放
-    let final_paid_amount: u64;
-    let used_router: Option<SwapRouter>;
+    // 0. 状态校验：仅 Revealed 可领取 (Pending/Claimed/Failed/Claiming 返回各自的错误码)
+    // 注意: 成功领取后 PDA 即被关闭，重复提交的交易会由 Anchor 报 AccountNotInitialized
+    check_claim_status(&ctx.accounts.mint_request)?;
 
-    match payout_mode {
-        PayoutMode::SOL => {
-            // ==================== SOL 发放路径 ====================
-            // 计算 95% 发放金额
-            let payout_usd = request
-                .total_won_usd
-                .checked_mul(95)
-                .ok_or(IPFlowError::MathOverflow)?
-                / 100;
-
-            let total_lamports = pyth_oracle::get_lamports_for_micro_usd(
-                &ctx.accounts.pyth_price_update,
-                payout_usd,
-            )?;
+    // 1. 校验领取超时 (揭示时写入的截止时间，含熔断/审核/管理员顺延)
+    check_claim_deadline(&ctx.accounts.mint_request, clock.unix_timestamp)?;
 
-            // Vault 余额校验：保留最小租金，确保可用余额足够
-            let min_rent = Rent::get()?.minimum_balance(0);
-            let available = ctx.accounts.vault.lamports().saturating_sub(min_rent);
-            require!(
-                total_lamports <= available,
-                IPFlowError::InsufficientVaultBalance
-            );
+    // 1.1 全局 24 小时发放熔断：超出上限时拒绝，用户可在下个窗口重试
+    // (可先调用 record_payout_block 顺延领取期限)
+    let total_won_usd = ctx.accounts.mint_request.total_won_usd;
+    check_payout_cap(&ctx.accounts.config, total_won_usd, clock.unix_timestamp)?;
+    let config = &mut ctx.accounts.config;
+    config.roll_payout_window(clock.unix_timestamp);
+    config.payout_in_window_usd = config
+        .payout_in_window_usd
+        .checked_add(total_won_usd)
+        .ok_or(IPFlowError::MathOverflow)?;
 
-            // PDA 签名转账
-            let seeds = &[b"vault".as_ref(), &[ctx.accounts.config.vault_bump]];
-            let signer = &[&seeds[..]];
-
-            // ==================== 重入保护: 先更新状态 (Effects before Interactions) ====================
-            // 遵循 Checks-Effects-Interactions 模式，在 CPI 调用前先标记状态为 Claimed
-            request.status = RequestStatus::Claimed;
-
-            anchor_lang::solana_program::program::invoke_signed(
-                &anchor_lang::solana_program::system_instruction::transfer(
-                    ctx.accounts.vault.key,
-                    ctx.accounts.user.key,
-                    total_lamports,
-                ),
-                &[
-                    ctx.accounts.vault.to_account_info(),
-                    ctx.accounts.user.to_account_info(),
-                    ctx.accounts.system_program.to_account_info(),
-                ],
-                signer,
-            )?;
+    // ==================== 重入保护: CPI 前标记 Claiming 并写回账户数据 ====================
+    // Anchor 仅在指令结束时序列化账户，此处显式 exit 使 CPI 期间的重入调用读到 Claiming
+    // 并在 check_claimable 处被拒绝；转账全部成功后才切换为 Claimed
+    ctx.accounts.mint_request.status = RequestStatus::Claiming;
+    ctx.accounts.mint_request.exit(&crate::ID)?;
+
+    // 1.2 揭示时托管的资金全部退回 Vault，随后按锁定汇率从 Vault 发放 (Token 模式的 swap 输入同样出自 Vault)
+    let accounts = &mut *ctx.accounts;
+    release_request_escrow(
+        &mut accounts.config,
+        &accounts.mint_request,
+        &accounts.mint_request.key(),
+        &accounts.escrow.to_account_info(),
+        &accounts.vault.to_account_info(),
+        &accounts.system_program.to_account_info(),
+    )?;
+
+    // 2. 根据 payout_mode 执行发放
+    let mut sol_paid_lamports: u64 = 0;
+    let mut token_paid_amount: u64 = 0;
+    let mut used_router: Option<SwapRouter> = None;
+    let mut applied_payout_mode = payout_mode;
+    let mut fallback_used = false;
+    let mut token_output_amount: Option<u64> = None;
+    let mut output_mint: Option<Pubkey> = None;
+
+    // 记录发放前尚未创建的输出账户，发放后据此判断哪些 ATA 由本次领取新建
+    let token_account_missing = account_missing(ctx.accounts.user_token_account.as_ref());
+    let wsol_account_missing = account_missing(ctx.accounts.user_wsol_account.as_ref());
 
-            final_paid_amount = total_lamports;
-            used_router = None;
-            msg!("SOL Claim: {} lamports to user", total_lamports);
+    match payout_mode {
+        PayoutMode::SOL => {
+            sol_paid_lamports = payout_sol(ctx.accounts, FULL_PORTION_BPS, wrap_payout)?;
         }
-        PayoutMode::Token => {
+        PayoutMode::Token | PayoutMode::Split => {
             // ==================== Token 发放路径 (双路由调度) ====================
             //
             // Task 1.20: 根据 swap_router 参数选择 Jupiter 或 Raydium 路由
+            // Split: 先发放 SOL 部分，剩余部分走同一 swap 路径
+            let sol_portion = sol_portion_for(payout_mode, sol_portion_bps);
 
-            // Step 1: 校验必需参数
-            let expected_output =
-                expected_token_output.ok_or(IPFlowError::MissingExpectedOutput)?;
-            let router = swap_router.ok_or(IPFlowError::InvalidChoice)?;
-
-            // 校验 remaining_accounts 数量
-            require!(
-                !ctx.remaining_accounts.is_empty(),
-                IPFlowError::MissingSwapAccounts
+            // Step 1: 前置校验 (不移动资金)，失败时按需降级为 SOL 发放
+            let preflight = preflight_token_claim(
+                ctx.accounts,
+                ctx.remaining_accounts,
+                terms.swap_router,
+                terms.expected_token_output,
+                swap_data.as_deref(),
+                FULL_PORTION_BPS - sol_portion,
             );
+            // swap 金额过小时按配置降级为 SOL 发放 (不依赖调用方的 fallback_to_sol)
+            let dust_swap_fallback = ctx.accounts.config.dust_swap_fallback_to_sol;
 
-            // Step 2: 计算发放金额和滑点保护
-            // Token 模式：100% 发放 (用户承担滑点风险)
-            let payout_usd = request.total_won_usd;
-
-            let amount_in = pyth_oracle::get_lamports_for_micro_usd(
-                &ctx.accounts.pyth_price_update,
-                payout_usd,
-            )?;
-
-            // 计算最小输出 (3% 滑点保护)
-            let minimum_amount_out =
-                jupiter_cpi::calculate_min_output(expected_output, DEFAULT_SLIPPAGE_BPS)?;
+            match preflight {
+                Ok((router, expected_output)) => {
+                    if sol_portion > 0 {
+                        // Vault 余额须同时覆盖 SOL 与 swap 两部分
+                        check_split_vault_balance(ctx.accounts, sol_portion)?;
+                        sol_paid_lamports = payout_sol(ctx.accounts, sol_portion, wrap_payout)?;
+                    }
+                    let (amount_in, actual_output) = payout_token(
+                        ctx.accounts,
+                        ctx.remaining_accounts,
+                        router,
+                        expected_output,
+                        terms.slippage_bps,
+                        swap_data.as_deref(),
+                        FULL_PORTION_BPS - sol_portion,
+                    )?;
+                    token_paid_amount = amount_in;
+                    used_router = Some(router);
+                    token_output_amount = Some(actual_output);
+                    output_mint = ctx.accounts.token_mint.as_ref().map(|mint| mint.key());
 
-            msg!(
-                "Token Claim: amount_in={} lamports, expected_out={}, min_out={} ({}bps slippage), router={:?}",
-                amount_in,
-                expected_output,
-                minimum_amount_out,
-                DEFAULT_SLIPPAGE_BPS,
-                router
-            );
+                    // 累计选中奖品池的 swap 统计 (传入 prize_pool 时)
+                    if let Some(prize_pool) = ctx.accounts.prize_pool.as_mut() {
+                        prize_pool.total_swapped_lamports = prize_pool
+                            .total_swapped_lamports
+                            .checked_add(token_paid_amount)
+                            .ok_or(IPFlowError::MathOverflow)?;
+                        prize_pool.total_claims = prize_pool
+                            .total_claims
+                            .checked_add(1)
+                            .ok_or(IPFlowError::MathOverflow)?;
+                        prize_pool.last_claim_ts = clock.unix_timestamp;
 
-            // Step 3: 根据路由执行 Swap
-            let vault_bump = ctx.accounts.config.vault_bump;
-            let remaining = &ctx.remaining_accounts;
-
-            // ==================== 重入保护: 先更新状态 (Effects before Interactions) ====================
-            // 遵循 Checks-Effects-Interactions 模式，在 CPI 调用前先标记状态为 Claimed
-            // 防止恶意合约在 CPI 回调中重入 claim 指令
-            request.status = RequestStatus::Claimed;
-
-            match router {
-                SwapRouter::Jupiter => {
-                    // ==================== Jupiter 路由 ====================
-                    let swap_instruction_data =
-                        swap_data.ok_or(IPFlowError::MissingExpectedOutput)?;
-
-                    // 从 remaining_accounts 获取用户输出 token 账户
-                    // Jupiter swap-instructions 返回的账户列表中，用户输出账户通常在固定位置
-                    // 前端需要确保 remaining_accounts[2] 是用户的输出 token 账户
-                    // 账户布局: [0]=Jupiter Program, [1]=..., [2]=user_output_token_account, ...
-                    require!(
-                        remaining.len() >= 3,
-                        IPFlowError::MissingSwapAccounts
-                    );
-                    let user_output_token_account = &remaining[2];
-
-                    // 执行 Jupiter swap 并验证滑点保护
-                    jupiter_cpi::swap_via_jupiter(
-                        remaining,
-                        swap_instruction_data,
-                        &ctx.accounts.vault.to_account_info(),
-                        vault_bump,
-                        user_output_token_account,
-                        minimum_amount_out,
-                        amount_in,
-                    )
-                    .map_err(|e| {
-                        msg!("Jupiter swap failed: {:?}", e);
-                        error!(IPFlowError::JupiterSwapFailed)
-                    })?;
-
-                    msg!("Jupiter Swap executed successfully with slippage protection");
+                        emit!(PoolVolumeUpdated {
+                            index: prize_pool.index,
+                            swapped_lamports: token_paid_amount,
+                            total_swapped_lamports: prize_pool.total_swapped_lamports,
+                            total_claims: prize_pool.total_claims,
+                            last_claim_ts: prize_pool.last_claim_ts,
+                        });
+                    }
                 }
-                SwapRouter::Raydium => {
-                    // ==================== Raydium 路由 ====================
-                    // 校验账户数量
-                    require!(
-                        remaining.len() >= RAYDIUM_SWAP_ACCOUNTS_COUNT,
-                        IPFlowError::MissingSwapAccounts
-                    );
-
-                    // 校验 Raydium Program ID
-                    let cp_swap_program = remaining[0].key();
-                    require!(
-                        cp_swap_program == RAYDIUM_CP_SWAP_PROGRAM
-                            || cp_swap_program == RAYDIUM_CP_SWAP_PROGRAM_DEVNET,
-                        IPFlowError::InvalidRaydiumProgram
-                    );
-
-                    // 构建 Vault PDA 签名
-                    let seeds: &[&[u8]] = &[b"vault".as_ref(), &[vault_bump]];
-                    let signer_seeds = &[seeds];
-
-                    // ==================== Step 3.1: 包装 SOL -> WSOL ====================
-                    // 从 Vault SOL 余额包装到 Vault WSOL ATA
-                    // remaining[4] = input_token_account (Vault WSOL ATA)
-                    // remaining[8] = input_token_program (SPL Token)
-                    wsol_helper::wrap_sol(
-                        &ctx.accounts.vault.to_account_info(),
-                        &remaining[4], // wsol_token_account (Vault WSOL ATA)
-                        &ctx.accounts.system_program.to_account_info(),
-                        &remaining[8], // token_program
-                        amount_in,
-                        signer_seeds,
-                    )
-                    .map_err(|e| {
-                        msg!("WSOL wrap failed: {:?}", e);
-                        error!(IPFlowError::WsolWrapFailed)
-                    })?;
-
-                    msg!("WSOL Wrap: {} lamports wrapped to WSOL", amount_in);
-
-                    // ==================== Step 3.2: 执行 Raydium CPMM Swap ====================
-                    raydium_cpi::swap_base_input(
-                        remaining[0].clone(),                 // cp_swap_program
-                        ctx.accounts.vault.to_account_info(), // payer (Vault PDA)
-                        remaining[1].clone(),                 // authority
-                        remaining[2].clone(),                 // amm_config
-                        remaining[3].clone(),                 // pool_state
-                        remaining[4].clone(), // input_token_account (Vault WSOL ATA)
-                        remaining[5].clone(), // output_token_account (User Token ATA)
-                        remaining[6].clone(), // input_vault
-                        remaining[7].clone(), // output_vault
-                        remaining[8].clone(), // input_token_program
-                        remaining[9].clone(), // output_token_program
-                        remaining[10].clone(), // input_token_mint
-                        remaining[11].clone(), // output_token_mint
-                        remaining[12].clone(), // observation_state
-                        amount_in,
-                        minimum_amount_out,
-                        signer_seeds,
-                    )
-                    .map_err(|e| {
-                        msg!("Raydium swap failed: {:?}", e);
-                        error!(IPFlowError::RaydiumSwapFailed)
-                    })?;
-
-                    msg!("Raydium Swap executed successfully");
+                Err(e) if falls_back_to_sol(&e, terms.fallback_to_sol, dust_swap_fallback) => {
+                    msg!("Token claim preflight failed ({:?}), falling back to SOL payout", e);
+                    sol_paid_lamports = payout_sol(ctx.accounts, FULL_PORTION_BPS, wrap_payout)?;
+                    applied_payout_mode = PayoutMode::SOL;
+                    fallback_used = true;
                 }
+                Err(e) => return Err(e),
             }
-
-            final_paid_amount = amount_in;
-            used_router = Some(router);
-            msg!(
-                "Token Claim: Swapped {} lamports via {:?}",
-                amount_in,
-                router
-            );
         }
     }
 
-    // 3. 更新支付金额 (状态已在各分支的 CPI 前更新，此处仅更新金额)
+    let created_token_account =
+        created_account(ctx.accounts.user_token_account.as_ref(), token_account_missing);
+    let created_wsol_account =
+        created_account(ctx.accounts.user_wsol_account.as_ref(), wsol_account_missing);
+
+    let final_paid_amount = sol_paid_lamports
+        .checked_add(token_paid_amount)
+        .ok_or(IPFlowError::MathOverflow)?;
+    let applied_sol_portion = sol_portion_for(applied_payout_mode, sol_portion_bps);
+
+    // 2.1 领取时收费的请求：将已扣除部分按锁定汇率从 Vault 转入手续费金库 (铸造时收费的请求为 0)
+    let platform_fee_usd = claim_fee_usd(ctx.accounts, applied_sol_portion)?;
+    let platform_fee_lamports = collect_claim_fee(ctx.accounts, platform_fee_usd)?;
+    let (net_total_usd, net_token_usd) = net_won_usd(ctx.accounts)?;
+
+    let request = &mut ctx.accounts.mint_request;
+
+    // 3. 所有转账已成功：更新支付金额并完成状态切换 Claiming -> Claimed
     request.paid_amount = final_paid_amount;
+    request.status = RequestStatus::Claimed;
+
+    // 3.1 扣减选中池的待领取计数 (按 index 记录，池已被强制删除时同样扣减)
+    let config = &mut ctx.accounts.config;
+    config.release_pool_pending_claim(request.selected_pool_index);
+    config.outstanding_liabilities_usd = config
+        .outstanding_liabilities_usd
+        .saturating_sub(request.total_won_usd);
+
+    // 3.2 按需写入领取凭证
+    if let Some(receipt) = ctx.accounts.receipt.as_mut() {
+        receipt.user = ctx.accounts.user.key();
+        receipt.request_id = vrf_request_slot;
+        receipt.total_won_usd = request.total_won_usd;
+        receipt.paid_amount = final_paid_amount;
+        receipt.payout_mode = applied_payout_mode;
+        receipt.selected_pool_index = request.selected_pool_index;
+        receipt.timestamp = clock.unix_timestamp;
+        receipt.bump = ctx.bumps.receipt.unwrap_or_default();
+        receipt.created_token_account = created_token_account;
+        receipt.created_wsol_account = created_wsol_account;
+    }
+
+    let sol_payout_bps = request.sol_payout_bps(config.sol_payout_bps, config.token_payout_bps);
+    let payout_bps = blended_payout_bps(
+        sol_payout_bps,
+        config.token_payout_bps,
+        applied_sol_portion,
+    );
+
+    // 3.3 累计当前 epoch 发放 (按发放比例折算后的美元价值，Split 为两部分之和)
+    let sol_leg_usd = leg_payout_usd(net_total_usd, applied_sol_portion, sol_payout_bps)?;
+    let token_leg_usd = leg_payout_usd(
+        net_token_usd,
+        FULL_PORTION_BPS - applied_sol_portion,
+        config.token_payout_bps,
+    )?;
+    let payout_usd = sol_leg_usd
+        .checked_add(token_leg_usd)
+        .ok_or(IPFlowError::MathOverflow)?;
+    config.total_paid_out_usd = config
+        .total_paid_out_usd
+        .checked_add(payout_usd)
+        .ok_or(IPFlowError::MathOverflow)?;
+    // 漏斗计数按实际发放方式 (降级为 SOL 的 Token 领取计入 SOL)
+    if applied_payout_mode == PayoutMode::SOL {
+        config.count_claimed_sol = config.count_claimed_sol.saturating_add(1);
+    } else {
+        config.count_claimed_token = config.count_claimed_token.saturating_add(1);
+    }
+    if let Some(stats) = require_epoch_stats(config, ctx.accounts.epoch_stats.as_mut())? {
+        stats.record_payout(payout_usd)?;
+    }
 
     // 4. Emit 事件 (Task 1.14: PDA 关闭前记录完整信息供链下索引)
     emit!(ClaimCompleted {
         user: ctx.accounts.user.key(),
+        payer: request.payer,
+        claimed_by: ctx.accounts.authority.key(),
         total_won_usd: request.total_won_usd,
-        payout_mode,
+        payout_mode: applied_payout_mode,
         payment_mode: request.payment_mode,
         swap_router: used_router,
         paid_amount: final_paid_amount,
+        token_output_amount,
+        output_mint,
         amount_of_cards: request.amount_of_cards,
         timestamp: clock.unix_timestamp,
+        fallback_used,
+        payout_bps,
+        price_publish_time: ctx.accounts.pyth_price_update.price_message.publish_time,
+        sol_paid_lamports,
+        token_paid_amount,
+        pause_flags: ctx.accounts.config.pause_flags,
+        claims_allowed_while_paused: ctx.accounts.config.claims_allowed_while_paused,
+        fee_policy: request.fee_policy,
+        platform_fee_usd,
+        platform_fee_lamports,
+        payout_wrapped: wrap_payout && sol_paid_lamports > 0,
+        memo: request.memo,
+        token_account_created: created_token_account != Pubkey::default(),
+        wsol_account_created: created_wsol_account != Pubkey::default(),
+        swap_failure_relief: applied_sol_portion > 0 && sol_payout_bps > config.sol_payout_bps,
+        preauthorized: terms.preauthorized,
     });
 
     msg!(
-        "Claim Success: User={}, Mode={:?}, Router={:?}, Paid={}, PDA will be closed",
+        "Claim Success: User={}, Mode={:?}, Router={:?}, Paid={}, Fallback={}, PDA will be closed",
         ctx.accounts.user.key(),
-        payout_mode,
+        applied_payout_mode,
         used_router,
-        final_paid_amount
+        final_paid_amount,
+        fallback_used
     );
 
-    // 注意: MintRequest PDA 将在指令结束时自动关闭 (close = user)
-    // 租金将退还给用户
+    // 5. 仅在最终状态为 Claimed 时关闭 MintRequest PDA，租金 (随账户大小增长) 退还给用户
+    //    卡牌 NFT 铸造未完成时保留，由 mint_card_nfts 最后一批关闭
+    if ctx.accounts.mint_request.closes_after_claim() {
+        close_account_to(
+            &ctx.accounts.mint_request.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+        )?;
+    }
 
     Ok(())
 }
+
+/// 登记发放熔断导致的领取期限顺延
+///
+/// claim 因 DailyPayoutCapReached 失败时整笔回滚，无法写入状态，
+/// 因此由用户单独调用本指令：确认当前确实被熔断阻塞后，在当前生效的领取期限 (含审核通过与
+/// 管理员顺延) 基础上顺延 (窗口结束 - now)。每个熔断窗口只顺延一次，重复调用不会叠加。
+pub fn record_payout_block(ctx: Context<RecordPayoutBlock>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let config = &ctx.accounts.config;
+    let request = &mut ctx.accounts.mint_request;
+
+    request.status.check_claimable()?;
+    require!(now < request.effective_claim_deadline(), IPFlowError::ClaimExpired);
+    require!(
+        config.exceeds_payout_cap(request.total_won_usd, now),
+        IPFlowError::PayoutNotBlocked
+    );
+
+    // 顺延阻塞时长 (窗口结束 - now)，按窗口起点记录已顺延的窗口
+    let retry_after = config.payout_window_end();
+    if !request.credit_payout_block(config.payout_window_start, retry_after, now) {
+        msg!(
+            "Payout block already credited for window starting at {}",
+            config.payout_window_start
+        );
+        return Ok(());
+    }
+
+    emit!(ClaimDeadlineExtended {
+        user: request.user,
+        mint_request: request.key(),
+        retry_after,
+        claim_deadline: request.claim_deadline,
+    });
+
+    msg!(
+        "Claim deadline extended: retry_after={}, claim_deadline={}",
+        retry_after,
+        request.claim_deadline
+    );
+    Ok(())
+}
+
+/// 登记 Token 领取的 swap 失败 (滑点超限等)
+///
+/// swap 失败时 claim 整笔回滚，无法在失败交易中写入状态，因此由用户单独登记。
+/// 仅可 Token 发放 (选中池仍在活跃列表中) 的已揭示请求可登记，两次登记间隔不少于
+/// SWAP_FAILURE_REPORT_INTERVAL_SECONDS；累计 SWAP_FAILURE_RELIEF_ATTEMPTS 次后，
+/// SOL 领取按 Token 发放比例计算。从未登记的请求行为不变。
+pub fn report_swap_failure(ctx: Context<ReportSwapFailure>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let config = &ctx.accounts.config;
+    let request = &mut ctx.accounts.mint_request;
+
+    request.status.check_claimable()?;
+    require!(now < request.effective_claim_deadline(), IPFlowError::ClaimExpired);
+    check_prize_pool_available(
+        &config.active_pool_indices[..config.active_pool_count as usize],
+        request.selected_pool_index,
+    )?;
+    request.record_swap_failure(now)?;
+
+    emit!(SwapFailureReported {
+        user: request.user,
+        mint_request: request.key(),
+        failed_swap_attempts: request.failed_swap_attempts,
+        relief_active: request.swap_failure_relief(),
+        timestamp: now,
+    });
+
+    msg!(
+        "Swap failure reported: attempts={}, relief={}",
+        request.failed_swap_attempts,
+        request.swap_failure_relief()
+    );
+    Ok(())
+}
+
+/// 预授权 Token 领取条款 (仅用户本人签名，重复调用覆盖)
+///
+/// 预授权后 claim 可由会话密钥或中继提交，但只能按锁定的路由执行 Token 发放，
+/// 最小输出按锁定的 expected_token_output 与 max_slippage_bps 计算，调用方无法放宽。
+pub fn pre_authorize_claim(
+    ctx: Context<PreAuthorizeClaim>,
+    router: SwapRouter,
+    expected_token_output: u64,
+    max_slippage_bps: u16,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let request = &mut ctx.accounts.mint_request;
+
+    check_claim_status(request)?;
+    check_claim_deadline(request, now)?;
+    let preauthorization = ClaimPreauthorization {
+        router,
+        expected_token_output,
+        max_slippage_bps,
+    };
+    check_claim_preauthorization(&preauthorization)?;
+    request.set_claim_preauthorization(Some(preauthorization));
+
+    emit!(ClaimPreauthorized {
+        user: request.user,
+        mint_request: request.key(),
+        router,
+        expected_token_output,
+        max_slippage_bps,
+        timestamp: now,
+    });
+
+    msg!(
+        "Claim pre-authorized: router={:?}, expected_out={}, max_slippage={}bps",
+        router,
+        expected_token_output,
+        max_slippage_bps
+    );
+    Ok(())
+}
+
+/// 清除 Token 领取预授权，claim 恢复按指令参数执行
+pub fn clear_claim_preauthorization(ctx: Context<PreAuthorizeClaim>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let request = &mut ctx.accounts.mint_request;
+
+    check_claim_status(request)?;
+    request.set_claim_preauthorization(None);
+
+    emit!(ClaimPreauthorizationCleared {
+        user: request.user,
+        mint_request: request.key(),
+        timestamp: now,
+    });
+
+    msg!("Claim pre-authorization cleared");
+    Ok(())
+}
+
+/// 预授权条款校验: 预期输出非零，滑点不超过 MAX_CLAIM_SLIPPAGE_BPS
+fn check_claim_preauthorization(preauthorization: &ClaimPreauthorization) -> Result<()> {
+    require!(
+        preauthorization.expected_token_output > 0
+            && preauthorization.max_slippage_bps <= MAX_CLAIM_SLIPPAGE_BPS,
+        IPFlowError::InvalidClaimPreauthorization
+    );
+    Ok(())
+}
+
+/// 本次领取实际采用的 Token 发放条款
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) struct ClaimTerms {
+    pub swap_router: Option<SwapRouter>,
+    pub expected_token_output: Option<u64>,
+    /// 最小输出的滑点保护 (bps)
+    pub slippage_bps: u64,
+    pub fallback_to_sol: bool,
+    /// 是否取自用户预授权
+    pub preauthorized: bool,
+}
+
+/// 合并指令参数与预授权，得到本次领取的 Token 发放条款
+///
+/// - 未预授权: 使用指令参数，滑点为 DEFAULT_SLIPPAGE_BPS
+/// - 已预授权: 仅可 Token 模式，swap_router 须为 None 或与预授权一致 (`ClaimPreauthorizationMismatch`)；
+///   预期输出与滑点取自预授权，忽略调用方的 fallback_to_sol (过小 swap 的配置降级仍适用)
+pub(super) fn resolve_claim_terms(
+    request: &MintRequest,
+    payout_mode: PayoutMode,
+    swap_router: Option<SwapRouter>,
+    expected_token_output: Option<u64>,
+    fallback_to_sol: bool,
+) -> Result<ClaimTerms> {
+    let Some(preauthorization) = request.claim_preauthorization() else {
+        return Ok(ClaimTerms {
+            swap_router,
+            expected_token_output,
+            slippage_bps: DEFAULT_SLIPPAGE_BPS,
+            fallback_to_sol,
+            preauthorized: false,
+        });
+    };
+
+    require!(
+        payout_mode == PayoutMode::Token
+            && (swap_router.is_none() || swap_router == Some(preauthorization.router)),
+        IPFlowError::ClaimPreauthorizationMismatch
+    );
+    Ok(ClaimTerms {
+        swap_router: Some(preauthorization.router),
+        expected_token_output: Some(preauthorization.expected_token_output),
+        slippage_bps: preauthorization.max_slippage_bps as u64,
+        fallback_to_sol: false,
+        preauthorized: true,
+    })
+}
+
+/// 领取状态校验：仅 Revealed 且无待审核标记的请求可领取
+pub(super) fn check_claim_status(request: &MintRequest) -> Result<()> {
+    request.status.check_claimable()?;
+    require!(!request.needs_review, IPFlowError::PendingReview);
+    Ok(())
+}
+
+/// 领取期限校验 (揭示时写入的截止时间，含熔断/审核/管理员顺延)
+pub(super) fn check_claim_deadline(request: &MintRequest, now: i64) -> Result<()> {
+    require!(
+        now < request.effective_claim_deadline(),
+        IPFlowError::ClaimExpired
+    );
+    Ok(())
+}
+
+/// 24 小时发放熔断校验 (只读，窗口滚动与累计由 handler 完成)
+pub(super) fn check_payout_cap(config: &IPFlowState, total_won_usd: u64, now: i64) -> Result<()> {
+    require!(
+        !config.exceeds_payout_cap(total_won_usd, now),
+        IPFlowError::DailyPayoutCapReached
+    );
+    Ok(())
+}
+
+/// fallback_to_sol 可降级的 Token 前置校验错误 (路由程序、swap 指令 data / discriminator、奖品池)
+const SOL_FALLBACK_ERRORS: [IPFlowError; 6] = [
+    IPFlowError::PrizePoolUnavailable,
+    IPFlowError::OutputMintMismatch,
+    IPFlowError::InvalidSwapData,
+    IPFlowError::SwapPayloadTooLarge,
+    IPFlowError::InvalidJupiterProgram,
+    IPFlowError::InvalidRaydiumProgram,
+];
+
+/// Token 前置校验失败时是否降级为 SOL 发放
+///
+/// - swap 过小: 调用方要求降级或开启了过小降级
+/// - 路由 / discriminator / 奖品池错误: 调用方要求降级
+/// - 其余 (参数缺失、输出 ATA、Vault WSOL 账户等): 不降级
+pub(super) fn falls_back_to_sol(err: &Error, fallback_to_sol: bool, dust_swap_fallback: bool) -> bool {
+    if *err == error!(IPFlowError::PayoutTooSmallForSwap) {
+        return fallback_to_sol || dust_swap_fallback;
+    }
+    fallback_to_sol && SOL_FALLBACK_ERRORS.iter().any(|&code| *err == error!(code))
+}
+
+/// 校验 claim 签名者：user 本人直接通过；否则须传入 delegation 且 delegate 与签名者一致、未过期
+pub(super) fn authorize_claimer(accounts: &Claim, now: i64) -> Result<()> {
+    let authority = accounts.authority.key();
+    if authority == accounts.user.key() {
+        return Ok(());
+    }
+
+    let delegation = accounts
+        .delegation
+        .as_ref()
+        .ok_or(IPFlowError::UnauthorizedClaimer)?;
+    require!(
+        delegation.delegate == authority,
+        IPFlowError::UnauthorizedClaimer
+    );
+    require!(delegation.is_active(now), IPFlowError::DelegationExpired);
+
+    msg!("Claim signed by delegate {}", authority);
+    Ok(())
+}
+
+/// SOL 发放路径：按 Pyth 价格换算 sol_portion_bps 部分奖金的 sol_payout_bps 比例，从 Vault 转账给用户
+/// (wrap_payout 时包装为 WSOL 转入用户的 WSOL ATA)
+///
+/// 返回实际发放的 lamports
+fn payout_sol<'info>(
+    accounts: &mut Claim<'info>,
+    sol_portion_bps: u16,
+    wrap_payout: bool,
+) -> Result<u64> {
+    // 按 sol_payout_bps 计算发放金额 (领取时收费的请求先扣除平台手续费)
+    let (net_total_usd, _) = net_won_usd(accounts)?;
+    let payout_usd = leg_payout_usd(
+        net_total_usd,
+        sol_portion_bps,
+        claim_sol_payout_bps(accounts),
+    )?;
+
+    let computed_lamports = payout_lamports(accounts, payout_usd)?;
+
+    // 零头优先按美元累积到奖励余额 (传入 RewardsAccount 且开启零头累积时)
+    if accounts.config.dust_accumulation_enabled
+        && computed_lamports < accounts.config.min_payout_lamports
+    {
+        if let Some(rewards) = accounts.rewards_account.as_mut() {
+            accrue_reward(rewards, RewardSource::Dust, payout_usd)?;
+            msg!("Payout {} micro-USD below minimum, accrued to rewards", payout_usd);
+            return Ok(0);
+        }
+    }
+
+    // 最小发放额处理：向上取整 (house 承担) 或累积零头到 UserStats
+    let total_lamports = match apply_min_payout(accounts, computed_lamports)? {
+        Some(lamports) => lamports,
+        None => {
+            // 零头已累积，本次不转账
+            return Ok(0);
+        }
+    };
+
+    // Vault 余额校验：按实际账户大小保留租金，确保可用余额足够
+    let available = vault_available_lamports(&accounts.vault)?;
+    require!(
+        total_lamports <= available,
+        IPFlowError::InsufficientVaultBalance
+    );
+
+    // PDA 签名转账
+    let seeds = &[b"vault".as_ref(), &[accounts.config.vault_bump]];
+    let signer = &[&seeds[..]];
+
+    // 重入保护: 状态已在 handler 中持久化为 Claiming，转账成功后由 handler 切换为 Claimed
+    if wrap_payout {
+        let user_wsol_account = create_user_wsol_ata(accounts)?;
+        let token_program = accounts
+            .token_program
+            .as_ref()
+            .ok_or(IPFlowError::WrapPayoutAccountsMissing)?;
+        wsol_helper::wrap_sol(
+            &accounts.vault.to_account_info(),
+            &user_wsol_account,
+            &accounts.system_program.to_account_info(),
+            &token_program.to_account_info(),
+            total_lamports,
+            signer,
+        )?;
+
+        msg!("SOL Claim: {} lamports wrapped to user WSOL ATA", total_lamports);
+        return Ok(total_lamports);
+    }
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &anchor_lang::solana_program::system_instruction::transfer(
+            accounts.vault.key,
+            accounts.user.key,
+            total_lamports,
+        ),
+        &[
+            accounts.vault.to_account_info(),
+            accounts.user.to_account_info(),
+            accounts.system_program.to_account_info(),
+        ],
+        signer,
+    )?;
+
+    msg!("SOL Claim: {} lamports to user", total_lamports);
+    Ok(total_lamports)
+}
+
+/// 将发放金额 (micro-USD) 换算为 lamports: 优先使用揭示时托管锁定的汇率，
+/// 未托管的旧请求回退到实时 Pyth 价格
+pub(super) fn payout_lamports(accounts: &Claim, payout_usd: u64) -> Result<u64> {
+    let request = &accounts.mint_request;
+    if let Some(lamports) =
+        locked_lamports_for_usd(request.escrow_lamports, request.total_won_usd, payout_usd)
+    {
+        return Ok(lamports);
+    }
+    pyth_oracle::get_lamports_floor(
+        &accounts.pyth_price_update,
+        payout_usd,
+        accounts.config.max_price_age_seconds,
+    )
+}
+
+/// 按 bps 比例计算发放金额 (micro-USD)
+fn apply_payout_bps(amount_usd: u64, payout_bps: u16) -> Result<u64> {
+    let scaled = amount_usd
+        .checked_mul(payout_bps as u64)
+        .ok_or(IPFlowError::MathOverflow)?;
+    Ok(scaled / BPS_DENOMINATOR)
+}
+
+/// 全额占比 (10000 bps)
+pub(super) const FULL_PORTION_BPS: u16 = BPS_DENOMINATOR as u16;
+
+/// 单部分发放金额: 先按占比切分奖金，再按该模式的发放比例折算 (占比 10000 时与整笔发放一致)
+fn leg_payout_usd(amount_usd: u64, portion_bps: u16, payout_bps: u16) -> Result<u64> {
+    apply_payout_bps(apply_payout_bps(amount_usd, portion_bps)?, payout_bps)
+}
+
+/// 按 bps 拆分平台手续费，返回 (扣费后金额, 手续费)
+fn split_platform_fee(amount_usd: u64, fee_bps: u16) -> Result<(u64, u64)> {
+    let fee = apply_payout_bps(amount_usd, fee_bps)?;
+    Ok((amount_usd - fee, fee))
+}
+
+/// 扣除领取时平台手续费后的 (total_won_usd, token_won_usd)，铸造时收费的请求原样返回
+pub(super) fn net_won_usd(accounts: &Claim) -> Result<(u64, u64)> {
+    let request = &accounts.mint_request;
+    let fee_bps = request.claim_fee_bps(accounts.config.platform_fee_bps);
+    let (net_total_usd, _) = split_platform_fee(request.total_won_usd, fee_bps)?;
+    let (net_token_usd, _) = split_platform_fee(request.token_won_usd, fee_bps)?;
+    Ok((net_total_usd, net_token_usd))
+}
+
+/// 本次领取扣除的平台手续费 (micro-USD)：SOL / Token 两部分分别按各自的奖金基数计费
+pub(super) fn claim_fee_usd(accounts: &Claim, sol_portion_bps: u16) -> Result<u64> {
+    let request = &accounts.mint_request;
+    let fee_bps = request.claim_fee_bps(accounts.config.platform_fee_bps);
+    let (_, total_fee_usd) = split_platform_fee(request.total_won_usd, fee_bps)?;
+    let (_, token_fee_usd) = split_platform_fee(request.token_won_usd, fee_bps)?;
+    let fee_usd = apply_payout_bps(total_fee_usd, sol_portion_bps)?
+        .checked_add(apply_payout_bps(token_fee_usd, FULL_PORTION_BPS - sol_portion_bps)?)
+        .ok_or(IPFlowError::MathOverflow)?;
+    Ok(fee_usd)
+}
+
+/// 领取时收费：按锁定汇率将手续费对应的 lamports 从 Vault 转入手续费金库
+///
+/// 返回实际转入的 lamports。手续费金库尚未创建且本次金额不足免租时暂留 Vault (用户侧照常扣除)
+fn collect_claim_fee(accounts: &Claim, fee_usd: u64) -> Result<u64> {
+    if fee_usd == 0 {
+        return Ok(0);
+    }
+    let fee_vault = accounts
+        .fee_vault
+        .as_ref()
+        .ok_or(IPFlowError::MissingFeeVault)?;
+
+    let fee_lamports = payout_lamports(accounts, fee_usd)?;
+    let rent_exempt = Rent::get()?.minimum_balance(0);
+    if fee_lamports == 0 || fee_vault.lamports().saturating_add(fee_lamports) < rent_exempt {
+        msg!("Platform fee {} lamports below fee vault rent, kept in vault", fee_lamports);
+        return Ok(0);
+    }
+    require!(
+        fee_lamports <= vault_available_lamports(&accounts.vault)?,
+        IPFlowError::InsufficientVaultBalance
+    );
+
+    let seeds = &[b"vault".as_ref(), &[accounts.config.vault_bump]];
+    anchor_lang::solana_program::program::invoke_signed(
+        &anchor_lang::solana_program::system_instruction::transfer(
+            accounts.vault.key,
+            fee_vault.key,
+            fee_lamports,
+        ),
+        &[
+            accounts.vault.to_account_info(),
+            fee_vault.to_account_info(),
+            accounts.system_program.to_account_info(),
+        ],
+        &[&seeds[..]],
+    )?;
+
+    msg!("Platform fee: {} micro-USD ({} lamports) to fee vault", fee_usd, fee_lamports);
+    Ok(fee_lamports)
+}
+
+/// 规范化发放方式: Split 的 0 / 10000 分别等同 Token / SOL，超过 10000 报错
+pub(super) fn resolve_payout_mode(payout_mode: PayoutMode, sol_portion_bps: u16) -> Result<PayoutMode> {
+    if payout_mode != PayoutMode::Split {
+        return Ok(payout_mode);
+    }
+    require!(
+        sol_portion_bps <= FULL_PORTION_BPS,
+        IPFlowError::InvalidSolPortion
+    );
+    Ok(match sol_portion_bps {
+        0 => PayoutMode::Token,
+        FULL_PORTION_BPS => PayoutMode::SOL,
+        _ => PayoutMode::Split,
+    })
+}
+
+/// 发放方式对应的 SOL 部分占比 (bps)
+pub(super) fn sol_portion_for(payout_mode: PayoutMode, sol_portion_bps: u16) -> u16 {
+    match payout_mode {
+        PayoutMode::SOL => FULL_PORTION_BPS,
+        PayoutMode::Token => 0,
+        PayoutMode::Split => sol_portion_bps,
+    }
+}
+
+/// 本次领取 SOL 部分适用的发放比例 (bps，登记的 swap 失败达到阈值后按 Token 比例放宽)
+fn claim_sol_payout_bps(accounts: &Claim) -> u16 {
+    accounts
+        .mint_request
+        .sol_payout_bps(accounts.config.sol_payout_bps, accounts.config.token_payout_bps)
+}
+
+/// 按 SOL 占比加权的综合发放比例 (bps)
+fn blended_payout_bps(sol_payout_bps: u16, token_payout_bps: u16, sol_portion_bps: u16) -> u16 {
+    let sol = sol_payout_bps as u64 * sol_portion_bps as u64;
+    let token = token_payout_bps as u64 * (FULL_PORTION_BPS - sol_portion_bps) as u64;
+    ((sol + token) / BPS_DENOMINATOR) as u16
+}
+
+/// Split 模式: Vault 可用余额须覆盖 SOL 部分与 swap 输入之和 (任一部分转出前校验)
+fn check_split_vault_balance(accounts: &Claim, sol_portion_bps: u16) -> Result<()> {
+    let config = &accounts.config;
+    let (net_total_usd, net_token_usd) = net_won_usd(accounts)?;
+    let sol_usd = leg_payout_usd(net_total_usd, sol_portion_bps, claim_sol_payout_bps(accounts))?;
+    let token_usd = leg_payout_usd(
+        net_token_usd,
+        FULL_PORTION_BPS - sol_portion_bps,
+        config.token_payout_bps,
+    )?;
+
+    let sol_lamports = payout_lamports(accounts, sol_usd)?.max(config.min_payout_lamports);
+    let token_lamports = payout_lamports(accounts, token_usd)?;
+    let total = sol_lamports
+        .checked_add(token_lamports)
+        .ok_or(IPFlowError::MathOverflow)?;
+
+    require!(
+        total <= vault_available_lamports(&accounts.vault)?,
+        IPFlowError::InsufficientVaultBalance
+    );
+    Ok(())
+}
+
+/// 按 config.min_payout_lamports 处理小额 SOL 发放
+///
+/// - 未设置最小值 (0) 或金额达标：原样发放 (合并 UserStats 中已累积的零头)
+/// - 零头累积关闭：向上取整到最小值，差额由 house 承担
+/// - 零头累积开启且金额达标：原样发放，传入 UserStats 时合并已累积零头 (不要求传入)
+/// - 零头累积开启且低于最小值：须传入 UserStats，与已累积零头合并，仍低于最小值则累积并返回 None (本次不转账)
+fn apply_min_payout(accounts: &mut Claim, computed_lamports: u64) -> Result<Option<u64>> {
+    let min_payout = accounts.config.min_payout_lamports;
+
+    if !accounts.config.dust_accumulation_enabled {
+        if computed_lamports < min_payout {
+            msg!(
+                "Payout {} below minimum, rounded up to {} lamports",
+                computed_lamports,
+                min_payout
+            );
+        }
+        return Ok(Some(computed_lamports.max(min_payout)));
+    }
+
+    if computed_lamports >= min_payout {
+        let pending_dust = accounts
+            .user_stats
+            .as_mut()
+            .map_or(0, |stats| std::mem::take(&mut stats.pending_dust_lamports));
+        let total = computed_lamports
+            .checked_add(pending_dust)
+            .ok_or(IPFlowError::MathOverflow)?;
+        return Ok(Some(total));
+    }
+
+    let user_stats = accounts
+        .user_stats
+        .as_mut()
+        .ok_or(IPFlowError::MissingUserStats)?;
+    let combined = computed_lamports
+        .checked_add(user_stats.pending_dust_lamports)
+        .ok_or(IPFlowError::MathOverflow)?;
+
+    if combined < min_payout {
+        user_stats.pending_dust_lamports = combined;
+        msg!(
+            "Payout {} below minimum {}, accumulated as dust (pending={})",
+            computed_lamports,
+            min_payout,
+            combined
+        );
+        return Ok(None);
+    }
+
+    user_stats.pending_dust_lamports = 0;
+    Ok(Some(combined))
+}
+
+/// 预估 SOL 部分实际转出的 lamports (不修改任何账户，与 payout_sol 的发放决策一致)
+///
+/// 零头累积到奖励余额或 UserStats 时本次不转账，返回 0
+pub(super) fn preview_sol_lamports(accounts: &Claim, sol_portion_bps: u16) -> Result<u64> {
+    let (net_total_usd, _) = net_won_usd(accounts)?;
+    let payout_usd = leg_payout_usd(
+        net_total_usd,
+        sol_portion_bps,
+        claim_sol_payout_bps(accounts),
+    )?;
+    let computed_lamports = payout_lamports(accounts, payout_usd)?;
+
+    let config = &accounts.config;
+    let pending_dust = accounts
+        .user_stats
+        .as_ref()
+        .map(|stats| stats.pending_dust_lamports);
+    sol_transfer_lamports(
+        computed_lamports,
+        config.min_payout_lamports,
+        config.dust_accumulation_enabled,
+        accounts.rewards_account.is_some(),
+        pending_dust,
+    )
+}
+
+/// 最小发放额规则下的实际转出金额 (纯函数)
+///
+/// - 零头累积关闭: 向上取整到 min_payout
+/// - 零头累积开启且达到下限: 原样发放，传入 UserStats 时合并已累积零头
+/// - 零头累积开启且低于下限: 传入奖励账户时累积到奖励余额 (0)；否则与 UserStats 零头合并，
+///   合并后仍低于下限则累积 (0)，未传入 UserStats 时报 MissingUserStats
+fn sol_transfer_lamports(
+    computed_lamports: u64,
+    min_payout: u64,
+    dust_accumulation_enabled: bool,
+    has_rewards_account: bool,
+    pending_dust: Option<u64>,
+) -> Result<u64> {
+    if !dust_accumulation_enabled {
+        return Ok(computed_lamports.max(min_payout));
+    }
+    if computed_lamports >= min_payout {
+        return computed_lamports
+            .checked_add(pending_dust.unwrap_or(0))
+            .ok_or(error!(IPFlowError::MathOverflow));
+    }
+    if has_rewards_account {
+        return Ok(0);
+    }
+    let combined = computed_lamports
+        .checked_add(pending_dust.ok_or(IPFlowError::MissingUserStats)?)
+        .ok_or(IPFlowError::MathOverflow)?;
+    Ok(if combined < min_payout { 0 } else { combined })
+}
+
+/// Token 发放要求选中的奖品池仍在活跃列表中 (NO_PRIZE_POOL 永远不在列表中)
+fn check_prize_pool_available(active_pool_indices: &[u8], selected_pool_index: u8) -> Result<()> {
+    require!(
+        active_pool_indices.contains(&selected_pool_index),
+        IPFlowError::PrizePoolUnavailable
+    );
+    Ok(())
+}
+
+/// Token 发放前置校验：校验参数与路由账户，不执行任何 CPI、不移动资金
+///
+/// 返回 (swap_router, expected_token_output)
+pub(super) fn preflight_token_claim<'info>(
+    accounts: &Claim<'info>,
+    remaining: &[AccountInfo<'info>],
+    swap_router: Option<SwapRouter>,
+    expected_token_output: Option<u64>,
+    swap_data: Option<&[u8]>,
+    token_portion_bps: u16,
+) -> Result<(SwapRouter, u64)> {
+    // 揭示时无活跃池 (哨兵索引) 或选中池已被强制删除：仅可 SOL 领取
+    let config = &accounts.config;
+    check_prize_pool_available(
+        &config.active_pool_indices[..config.active_pool_count as usize],
+        accounts.mint_request.selected_pool_index,
+    )?;
+
+    // swap 输入过小时大部分被手续费消耗，且 DEX 池会拒绝低于下限的 swap
+    let amount_in = swap_amount_in(accounts, token_portion_bps)?;
+    check_swap_size(amount_in, config.min_swap_lamports)?;
+
+    let expected_output = expected_token_output.ok_or(IPFlowError::MissingExpectedOutput)?;
+    let router = swap_router.ok_or(IPFlowError::InvalidChoice)?;
+
+    // 校验 remaining_accounts 数量
+    require!(!remaining.is_empty(), IPFlowError::MissingSwapAccounts);
+
+    // 校验用户输出 ATA (命名账户)
+    let (token_mint, user_output_token_account) = validate_user_output_ata(accounts)?;
+
+    match router {
+        SwapRouter::Jupiter => {
+            let swap_instruction_data = swap_data.ok_or(IPFlowError::MissingExpectedOutput)?;
+
+            jupiter_cpi::validate_swap_route(
+                remaining,
+                swap_instruction_data,
+                &accounts.config.jupiter_program,
+                accounts.config.route_discriminators(),
+                accounts.config.pinned_vault_wsol_account(),
+                &accounts.vault.to_account_info(),
+                &user_output_token_account,
+            )?;
+        }
+        SwapRouter::Raydium => {
+            // 校验账户数量
+            require!(
+                remaining.len() >= RAYDIUM_SWAP_ACCOUNTS_COUNT,
+                IPFlowError::MissingSwapAccounts
+            );
+
+            // 校验 Raydium Program ID (与配置一致)
+            raydium_cpi::check_raydium_program(&remaining[0].key(), &accounts.config.raydium_program)?;
+
+            check_raydium_route_accounts(
+                remaining,
+                &accounts.vault.key(),
+                accounts.config.pinned_vault_wsol_account(),
+                &user_output_token_account.key(),
+                &token_mint,
+            )?;
+        }
+    }
+
+    Ok((router, expected_output))
+}
+
+/// 校验 Raydium 路由账户顺序与内容 (wrap_sol 按位置向 remaining[4] 转入 Vault 资金，须在此之前完成)
+///
+/// - remaining[4] 必须是 Vault 持有的 WSOL 账户 (`VaultWsolAccountMissing`) 且可写
+///   (`VaultWsolAccountNotWritable`)；已登记 vault_wsol_account 时必须是该账户
+/// - remaining[5] 必须是用户输出 ATA (`InvalidTokenAccount`，账户顺序错误)
+/// - remaining[8] 必须是 SPL Token 程序 (`InvalidSwapTokenProgram`)
+/// - remaining[11] 必须是奖品 Token Mint (`OutputMintMismatch`)
+fn check_raydium_route_accounts(
+    remaining: &[AccountInfo],
+    vault: &Pubkey,
+    pinned_vault_wsol: Option<Pubkey>,
+    user_output_token_account: &Pubkey,
+    token_mint: &Pubkey,
+) -> Result<()> {
+    if let Some(pinned) = pinned_vault_wsol {
+        require_keys_eq!(
+            remaining[4].key(),
+            pinned,
+            IPFlowError::VaultWsolAccountMissing
+        );
+    }
+    jupiter_cpi::require_vault_wsol_account(&remaining[4], vault)?;
+    require_keys_eq!(
+        remaining[5].key(),
+        *user_output_token_account,
+        IPFlowError::InvalidTokenAccount
+    );
+    require_keys_eq!(
+        remaining[8].key(),
+        anchor_spl::token::ID,
+        IPFlowError::InvalidSwapTokenProgram
+    );
+    require_keys_eq!(remaining[11].key(), *token_mint, IPFlowError::OutputMintMismatch);
+    Ok(())
+}
+
+/// 校验 Token 模式的命名输出账户
+///
+/// user_token_account 必须是 (user, token_mint) 派生的 ATA。返回 (token_mint, user_token_account)
+pub(super) fn validate_user_output_ata<'info>(accounts: &Claim<'info>) -> Result<(Pubkey, AccountInfo<'info>)> {
+    require!(
+        accounts.token_program.is_some() && accounts.associated_token_program.is_some(),
+        IPFlowError::MissingSwapAccounts
+    );
+    let token_mint = accounts
+        .token_mint
+        .as_ref()
+        .ok_or(IPFlowError::OutputAccountMissing)?;
+    let user_token_account = accounts
+        .user_token_account
+        .as_ref()
+        .ok_or(IPFlowError::OutputAccountMissing)?;
+
+    let expected_ata = get_associated_token_address(&accounts.user.key(), &token_mint.key());
+    require_keys_eq!(
+        user_token_account.key(),
+        expected_ata,
+        IPFlowError::InvalidTokenAccount
+    );
+
+    Ok((token_mint.key(), user_token_account.to_account_info()))
+}
+
+/// 输出账户是否已传入且尚未初始化 (领取前快照)
+fn account_missing(account: Option<&UncheckedAccount<'_>>) -> bool {
+    account.is_some_and(|account| account.data_is_empty())
+}
+
+/// 领取前不存在、发放后已初始化的输出账户地址 (即由本次领取新建)，否则为 Pubkey::default()
+fn created_account(account: Option<&UncheckedAccount<'_>>, missing_before: bool) -> Pubkey {
+    match account {
+        Some(account) if missing_before && !account.data_is_empty() => account.key(),
+        _ => Pubkey::default(),
+    }
+}
+
+/// 幂等创建用户的奖品 Token ATA (payer = authority)，返回该账户
+fn create_user_output_ata<'info>(accounts: &Claim<'info>) -> Result<AccountInfo<'info>> {
+    let (_, user_output_token_account) = validate_user_output_ata(accounts)?;
+    let token_program = accounts
+        .token_program
+        .as_ref()
+        .ok_or(IPFlowError::MissingSwapAccounts)?;
+    let associated_token_program = accounts
+        .associated_token_program
+        .as_ref()
+        .ok_or(IPFlowError::MissingSwapAccounts)?;
+    let token_mint = accounts
+        .token_mint
+        .as_ref()
+        .ok_or(IPFlowError::MissingSwapAccounts)?;
+
+    associated_token::create_idempotent(CpiContext::new(
+        associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer: accounts.authority.to_account_info(),
+            associated_token: user_output_token_account.clone(),
+            authority: accounts.user.to_account_info(),
+            mint: token_mint.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            token_program: token_program.to_account_info(),
+        },
+    ))?;
+
+    Ok(user_output_token_account)
+}
+
+/// 校验 wrap_payout 所需账户: WSOL 包装账户齐全且 user_wsol_account 为 (user, NATIVE_SOL_MINT) 的 ATA
+pub(super) fn validate_user_wsol_ata(accounts: &Claim) -> Result<()> {
+    require!(
+        accounts.token_program.is_some()
+            && accounts.associated_token_program.is_some()
+            && accounts.wsol_mint.is_some(),
+        IPFlowError::WrapPayoutAccountsMissing
+    );
+    let user_wsol_account = accounts
+        .user_wsol_account
+        .as_ref()
+        .ok_or(IPFlowError::WrapPayoutAccountsMissing)?;
+
+    let expected_ata = get_associated_token_address(&accounts.user.key(), &NATIVE_SOL_MINT);
+    require_keys_eq!(
+        user_wsol_account.key(),
+        expected_ata,
+        IPFlowError::InvalidTokenAccount
+    );
+    Ok(())
+}
+
+/// 幂等创建用户的 WSOL ATA (payer = authority)，返回该账户 (wrap_payout 的 SOL 发放目标)
+fn create_user_wsol_ata<'info>(accounts: &Claim<'info>) -> Result<AccountInfo<'info>> {
+    validate_user_wsol_ata(accounts)?;
+    let token_program = accounts
+        .token_program
+        .as_ref()
+        .ok_or(IPFlowError::WrapPayoutAccountsMissing)?;
+    let associated_token_program = accounts
+        .associated_token_program
+        .as_ref()
+        .ok_or(IPFlowError::WrapPayoutAccountsMissing)?;
+    let wsol_mint = accounts
+        .wsol_mint
+        .as_ref()
+        .ok_or(IPFlowError::WrapPayoutAccountsMissing)?;
+    let user_wsol_account = accounts
+        .user_wsol_account
+        .as_ref()
+        .ok_or(IPFlowError::WrapPayoutAccountsMissing)?;
+
+    associated_token::create_idempotent(CpiContext::new(
+        associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer: accounts.authority.to_account_info(),
+            associated_token: user_wsol_account.to_account_info(),
+            authority: accounts.user.to_account_info(),
+            mint: wsol_mint.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            token_program: token_program.to_account_info(),
+        },
+    ))?;
+
+    Ok(user_wsol_account.to_account_info())
+}
+
+/// swap 输入金额 (lamports)：token_portion_bps 部分按 token_payout_bps 发放
+/// (基数为按选中池奖金上限截断后的金额)
+pub(super) fn swap_amount_in(accounts: &Claim, token_portion_bps: u16) -> Result<u64> {
+    let (_, net_token_usd) = net_won_usd(accounts)?;
+    let payout_usd = leg_payout_usd(
+        net_token_usd,
+        token_portion_bps,
+        accounts.config.token_payout_bps,
+    )?;
+    payout_lamports(accounts, payout_usd)
+}
+
+/// swap 输入须不低于 min_swap_lamports (0 表示不限制)
+fn check_swap_size(amount_in: u64, min_swap_lamports: u64) -> Result<()> {
+    require!(
+        amount_in >= min_swap_lamports,
+        IPFlowError::PayoutTooSmallForSwap
+    );
+    Ok(())
+}
+
+/// Token 发放路径：通过 Jupiter/Raydium 将 token_payout_bps 比例的奖金换成奖品 Token 发给用户
+///
+/// 调用前必须已通过 `preflight_token_claim`。返回 (swap 输入的 lamports, 用户实际到账的 Token 数量)
+fn payout_token<'info>(
+    accounts: &mut Claim<'info>,
+    remaining: &[AccountInfo<'info>],
+    router: SwapRouter,
+    expected_output: u64,
+    slippage_bps: u64,
+    swap_data: Option<&[u8]>,
+    token_portion_bps: u16,
+) -> Result<(u64, u64)> {
+    // Step 2: 计算发放金额和滑点保护
+    // Token 模式：按 token_payout_bps 发放 (用户承担滑点风险)，基数为按选中池奖金上限截断后的金额
+    // Split 模式仅 swap token_portion_bps 部分
+    let amount_in = swap_amount_in(accounts, token_portion_bps)?;
+
+    // 计算最小输出 (默认 3% 滑点保护，预授权时按用户锁定的滑点)
+    let minimum_amount_out = jupiter_cpi::calculate_min_output(expected_output, slippage_bps)?;
+
+    msg!(
+        "Token Claim: amount_in={} lamports, expected_out={}, min_out={} ({}bps slippage), router={:?}",
+        amount_in,
+        expected_output,
+        minimum_amount_out,
+        slippage_bps,
+        router
+    );
+
+    // Step 3: 根据路由执行 Swap
+    let vault_bump = accounts.config.vault_bump;
+
+    // 重入保护: 状态已在 handler 中持久化为 Claiming，重入的 claim 会被 check_claimable 拒绝
+    // 用户首次持有该奖品 Token 时创建输出 ATA (已存在则跳过)
+    let user_output_token_account = create_user_output_ata(accounts)?;
+
+    let actual_output = match router {
+        SwapRouter::Jupiter => {
+            // ==================== Jupiter 路由 ====================
+            let swap_instruction_data = swap_data.ok_or(IPFlowError::MissingExpectedOutput)?;
+            let token_program = accounts
+                .token_program
+                .as_ref()
+                .ok_or(IPFlowError::MissingSwapAccounts)?;
+
+            // 从 Vault lamports 包装输入后执行 Jupiter swap 并验证滑点保护 (返回实际到账数量)
+            let actual_output = jupiter_cpi::swap_via_jupiter(
+                remaining,
+                swap_instruction_data,
+                &accounts.config.jupiter_program,
+                accounts.config.route_discriminators(),
+                accounts.config.pinned_vault_wsol_account(),
+                &accounts.vault.to_account_info(),
+                vault_bump,
+                &accounts.system_program.to_account_info(),
+                &token_program.to_account_info(),
+                &user_output_token_account,
+                minimum_amount_out,
+                amount_in,
+            )
+            .map_err(|e| {
+                msg!("Jupiter swap failed: {:?}", e);
+                e
+            })?;
+
+            msg!("Jupiter Swap executed successfully with slippage protection");
+            actual_output
+        }
+        SwapRouter::Raydium => {
+            // ==================== Raydium 路由 ====================
+            // 构建 Vault PDA 签名
+            let seeds: &[&[u8]] = &[b"vault".as_ref(), &[vault_bump]];
+            let signer_seeds = &[seeds];
+
+            // ==================== Step 3.1: 包装 SOL -> WSOL ====================
+            // 从 Vault SOL 余额包装到 Vault WSOL ATA
+            // remaining[4] = input_token_account (Vault WSOL ATA)
+            // remaining[8] = input_token_program (SPL Token)
+            wsol_helper::wrap_sol(
+                &accounts.vault.to_account_info(),
+                &remaining[4], // wsol_token_account (Vault WSOL ATA)
+                &accounts.system_program.to_account_info(),
+                &remaining[8], // token_program
+                amount_in,
+                signer_seeds,
+            )
+            .map_err(|e| {
+                msg!("WSOL wrap failed: {:?}", e);
+                error!(IPFlowError::WsolWrapFailed)
+            })?;
+
+            msg!("WSOL Wrap: {} lamports wrapped to WSOL", amount_in);
+
+            // ==================== Step 3.2: 执行 Raydium CPMM Swap ====================
+            // 记录 swap 前余额 (wrap 之后)，用于校验实际到账数量与 WSOL 支出
+            let output_before = jupiter_cpi::get_token_amount(&user_output_token_account)?;
+            let input_before = jupiter_cpi::get_token_amount(&remaining[4])?;
+
+            raydium_cpi::swap_base_input(
+                &accounts.config.raydium_program,
+                remaining[0].clone(),                 // cp_swap_program
+                accounts.vault.to_account_info(),     // payer (Vault PDA)
+                remaining[1].clone(),                 // authority
+                remaining[2].clone(),                 // amm_config
+                remaining[3].clone(),                 // pool_state
+                remaining[4].clone(), // input_token_account (Vault WSOL ATA)
+                user_output_token_account.clone(), // output_token_account (User Token ATA)
+                remaining[6].clone(), // input_vault
+                remaining[7].clone(), // output_vault
+                remaining[8].clone(), // input_token_program
+                remaining[9].clone(), // output_token_program
+                remaining[10].clone(), // input_token_mint
+                remaining[11].clone(), // output_token_mint
+                remaining[12].clone(), // observation_state
+                amount_in,
+                minimum_amount_out,
+                signer_seeds,
+            )
+            .map_err(|e| {
+                msg!("Raydium swap failed: {:?}", e);
+                e
+            })?;
+
+            // ==================== Step 3.3: swap 后余额校验 ====================
+            // 不依赖 CPMM 程序自身的 minimum_amount_out 检查
+            let actual_output = raydium_cpi::verify_swap_balances(
+                &remaining[4],
+                &user_output_token_account,
+                input_before,
+                output_before,
+                amount_in,
+                minimum_amount_out,
+            )?;
+
+            msg!("Raydium Swap executed successfully with slippage protection verified");
+            actual_output
+        }
+    };
+
+    msg!(
+        "Token Claim: Swapped {} lamports via {:?}, received {} tokens",
+        amount_in,
+        router,
+        actual_output
+    );
+
+    Ok((amount_in, actual_output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::NO_PRIZE_POOL;
+
+    /// 13 个 Raydium 路由账户，下标 4 按 wsol_owner 构造 WSOL Token 账户数据，下标 8 为 SPL Token 程序
+    fn raydium_fixture(wsol_owner: Pubkey) -> (Vec<Pubkey>, Vec<u64>, Vec<Vec<u8>>) {
+        use anchor_lang::solana_program::program_pack::Pack;
+        use anchor_spl::token::spl_token::state::{Account as SplAccount, AccountState};
+
+        let mut keys: Vec<Pubkey> = (0..RAYDIUM_SWAP_ACCOUNTS_COUNT)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        keys[8] = anchor_spl::token::ID;
+        let lamports = vec![0u64; RAYDIUM_SWAP_ACCOUNTS_COUNT];
+        let mut data = vec![vec![0u8; 16]; RAYDIUM_SWAP_ACCOUNTS_COUNT];
+        let mut wsol = vec![0u8; SplAccount::LEN];
+        SplAccount::pack(
+            SplAccount {
+                mint: NATIVE_SOL_MINT,
+                owner: wsol_owner,
+                state: AccountState::Initialized,
+                ..SplAccount::default()
+            },
+            &mut wsol,
+        )
+        .unwrap();
+        data[4] = wsol;
+        (keys, lamports, data)
+    }
+
+    fn route_infos<'a>(
+        keys: &'a [Pubkey],
+        lamports: &'a mut [u64],
+        data: &'a mut [Vec<u8>],
+    ) -> Vec<AccountInfo<'a>> {
+        keys.iter()
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .map(|((key, lamports), data)| {
+                AccountInfo::new(key, false, true, lamports, data, &anchor_spl::token::ID, false, 0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn raydium_route_account_checks_are_specific() {
+        let vault = Pubkey::new_unique();
+        let (keys, mut lamports, mut data) = raydium_fixture(vault);
+        let infos = route_infos(&keys, &mut lamports, &mut data);
+        let (output, mint) = (keys[5], keys[11]);
+
+        assert!(check_raydium_route_accounts(&infos, &vault, None, &output, &mint).is_ok());
+        assert_eq!(
+            check_raydium_route_accounts(&infos, &Pubkey::new_unique(), None, &output, &mint)
+                .unwrap_err(),
+            error!(IPFlowError::VaultWsolAccountMissing)
+        );
+        assert_eq!(
+            check_raydium_route_accounts(&infos, &vault, None, &keys[6], &mint).unwrap_err(),
+            error!(IPFlowError::InvalidTokenAccount)
+        );
+        assert_eq!(
+            check_raydium_route_accounts(&infos, &vault, None, &output, &Pubkey::new_unique())
+                .unwrap_err(),
+            error!(IPFlowError::OutputMintMismatch)
+        );
+    }
+
+    #[test]
+    fn raydium_route_requires_pinned_vault_wsol() {
+        let vault = Pubkey::new_unique();
+        let (keys, mut lamports, mut data) = raydium_fixture(vault);
+        let infos = route_infos(&keys, &mut lamports, &mut data);
+        let (output, mint) = (keys[5], keys[11]);
+
+        assert!(
+            check_raydium_route_accounts(&infos, &vault, Some(keys[4]), &output, &mint).is_ok()
+        );
+        // 另一个 Vault WSOL 账户 (如迁移前的扫描结果) 不再被接受
+        assert_eq!(
+            check_raydium_route_accounts(&infos, &vault, Some(Pubkey::new_unique()), &output, &mint)
+                .unwrap_err(),
+            error!(IPFlowError::VaultWsolAccountMissing)
+        );
+    }
+
+    #[test]
+    fn raydium_route_rejects_malicious_accounts() {
+        let vault = Pubkey::new_unique();
+        let (mut keys, mut lamports, mut data) = raydium_fixture(vault);
+        let (output, mint) = (keys[5], keys[11]);
+        let fake_program = Pubkey::new_unique();
+        // 攻击者持有的 WSOL 账户，以及数据伪造成 Vault WSOL 但不由 SPL Token 程序持有的账户
+        let (_, _, mut attacker_data) = raydium_fixture(Pubkey::new_unique());
+        let mut forged_data = data[4].clone();
+        let (mut attacker_lamports, mut forged_lamports) = (0u64, 0u64);
+
+        let check = |infos: &[AccountInfo]| {
+            check_raydium_route_accounts(infos, &vault, None, &output, &mint).unwrap_err()
+        };
+
+        {
+            let mut infos = route_infos(&keys, &mut lamports, &mut data);
+
+            // Vault WSOL 与用户 ATA 调换位置
+            infos.swap(4, 5);
+            assert_eq!(check(&infos), error!(IPFlowError::VaultWsolAccountMissing));
+            infos.swap(4, 5);
+
+            infos[4].is_writable = false;
+            assert_eq!(check(&infos), error!(IPFlowError::VaultWsolAccountNotWritable));
+
+            infos[4] = AccountInfo::new(
+                &keys[4],
+                false,
+                true,
+                &mut attacker_lamports,
+                &mut attacker_data[4],
+                &anchor_spl::token::ID,
+                false,
+                0,
+            );
+            assert_eq!(check(&infos), error!(IPFlowError::VaultWsolAccountMissing));
+
+            infos[4] = AccountInfo::new(
+                &keys[4],
+                false,
+                true,
+                &mut forged_lamports,
+                &mut forged_data,
+                &fake_program,
+                false,
+                0,
+            );
+            assert_eq!(check(&infos), error!(IPFlowError::VaultWsolAccountMissing));
+        }
+
+        // remaining[8] 替换为伪造的 Token 程序
+        keys[8] = fake_program;
+        let infos = route_infos(&keys, &mut lamports, &mut data);
+        assert_eq!(check(&infos), error!(IPFlowError::InvalidSwapTokenProgram));
+    }
+
+    #[test]
+    fn token_claim_requires_selected_pool_still_active() {
+        // 请求与揭示之间奖品池被全部删除：揭示写入哨兵索引
+        assert_eq!(
+            check_prize_pool_available(&[], NO_PRIZE_POOL).unwrap_err(),
+            error!(IPFlowError::PrizePoolUnavailable)
+        );
+        // 揭示与领取之间选中池被强制删除
+        assert_eq!(
+            check_prize_pool_available(&[0, 2], 1).unwrap_err(),
+            error!(IPFlowError::PrizePoolUnavailable)
+        );
+        assert!(check_prize_pool_available(&[0, 1, 2], 1).is_ok());
+    }
+
+    #[test]
+    fn swap_size_boundary_at_threshold() {
+        assert!(check_swap_size(10_000_000, 10_000_000).is_ok());
+        assert!(check_swap_size(10_000_001, 10_000_000).is_ok());
+        assert_eq!(
+            check_swap_size(9_999_999, 10_000_000).unwrap_err(),
+            error!(IPFlowError::PayoutTooSmallForSwap)
+        );
+        // 0 表示不限制
+        assert!(check_swap_size(0, 0).is_ok());
+    }
+
+    #[test]
+    fn split_edges_resolve_to_pure_modes() {
+        assert_eq!(resolve_payout_mode(PayoutMode::Split, 0).unwrap(), PayoutMode::Token);
+        assert_eq!(
+            resolve_payout_mode(PayoutMode::Split, FULL_PORTION_BPS).unwrap(),
+            PayoutMode::SOL
+        );
+        assert_eq!(resolve_payout_mode(PayoutMode::Split, 5_000).unwrap(), PayoutMode::Split);
+        assert_eq!(
+            resolve_payout_mode(PayoutMode::Split, 10_001).unwrap_err(),
+            error!(IPFlowError::InvalidSolPortion)
+        );
+        // 纯模式忽略 sol_portion_bps
+        assert_eq!(resolve_payout_mode(PayoutMode::SOL, 10_001).unwrap(), PayoutMode::SOL);
+        assert_eq!(resolve_payout_mode(PayoutMode::Token, 0).unwrap(), PayoutMode::Token);
+    }
+
+    #[test]
+    fn full_portion_leg_matches_whole_payout() {
+        for amount in [0u64, 5_000_000, 123_400_000, 99_900_000 * 1_000] {
+            assert_eq!(
+                leg_payout_usd(amount, FULL_PORTION_BPS, 9_500).unwrap(),
+                apply_payout_bps(amount, 9_500).unwrap()
+            );
+            assert_eq!(leg_payout_usd(amount, 0, 10_000).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn split_legs_sum_within_rounding_of_whole() {
+        let total = 123_400_000u64;
+        let sol = leg_payout_usd(total, 3_333, 10_000).unwrap();
+        let token = leg_payout_usd(total, FULL_PORTION_BPS - 3_333, 10_000).unwrap();
+        assert!(total - (sol + token) <= 1);
+        assert_eq!(leg_payout_usd(total, 5_000, 9_500).unwrap(), 58_615_000);
+    }
+
+    #[test]
+    fn blended_bps_weights_by_sol_portion() {
+        assert_eq!(blended_payout_bps(9_500, 10_000, FULL_PORTION_BPS), 9_500);
+        assert_eq!(blended_payout_bps(9_500, 10_000, 0), 10_000);
+        assert_eq!(blended_payout_bps(9_500, 10_000, 5_000), 9_750);
+        assert_eq!(sol_portion_for(PayoutMode::Split, 2_500), 2_500);
+        assert_eq!(sol_portion_for(PayoutMode::Token, 2_500), 0);
+        assert_eq!(sol_portion_for(PayoutMode::SOL, 2_500), FULL_PORTION_BPS);
+    }
+
+    #[test]
+    fn platform_fee_split_conserves_amount() {
+        assert_eq!(split_platform_fee(100_000_000, 0).unwrap(), (100_000_000, 0));
+        assert_eq!(split_platform_fee(100_000_000, 500).unwrap(), (95_000_000, 5_000_000));
+        let (net, fee) = split_platform_fee(123_456_789, 333).unwrap();
+        assert_eq!(net + fee, 123_456_789);
+        // 先扣手续费再按发放比例折算 (SOL 95%)
+        assert_eq!(net, 119_345_678);
+        assert_eq!(leg_payout_usd(net, FULL_PORTION_BPS, 9_500).unwrap(), 113_378_394);
+    }
+
+    #[test]
+    fn sol_transfer_matches_min_payout_rules() {
+        // 零头累积关闭: 向上取整
+        assert_eq!(sol_transfer_lamports(400, 1_000, false, false, None).unwrap(), 1_000);
+        assert_eq!(sol_transfer_lamports(4_000, 1_000, false, false, None).unwrap(), 4_000);
+        // 零头累积开启: 奖励账户优先，其次与 UserStats 零头合并
+        assert_eq!(sol_transfer_lamports(400, 1_000, true, true, None).unwrap(), 0);
+        assert_eq!(sol_transfer_lamports(400, 1_000, true, false, Some(500)).unwrap(), 0);
+        assert_eq!(sol_transfer_lamports(400, 1_000, true, false, Some(600)).unwrap(), 1_000);
+        assert_eq!(sol_transfer_lamports(4_000, 1_000, true, true, Some(50)).unwrap(), 4_050);
+        // 达到下限的发放不要求 UserStats
+        assert_eq!(sol_transfer_lamports(4_000, 1_000, true, false, None).unwrap(), 4_000);
+        assert_eq!(sol_transfer_lamports(1_000, 1_000, true, false, None).unwrap(), 1_000);
+        assert_eq!(
+            sol_transfer_lamports(400, 1_000, true, false, None).unwrap_err(),
+            error!(IPFlowError::MissingUserStats)
+        );
+    }
+
+    #[test]
+    fn fallback_requires_opt_in_or_dust_swap() {
+        let dust = error!(IPFlowError::PayoutTooSmallForSwap);
+        let route = error!(IPFlowError::InvalidSwapData);
+        assert!(falls_back_to_sol(&route, true, false));
+        assert!(!falls_back_to_sol(&route, false, true));
+        assert!(falls_back_to_sol(&dust, false, true));
+        assert!(falls_back_to_sol(&dust, true, false));
+        assert!(!falls_back_to_sol(&dust, false, false));
+    }
+
+    #[test]
+    fn fallback_ignores_caller_argument_errors() {
+        for code in [
+            IPFlowError::MissingExpectedOutput,
+            IPFlowError::InvalidChoice,
+            IPFlowError::MissingSwapAccounts,
+            IPFlowError::OutputAccountMissing,
+            IPFlowError::InvalidTokenAccount,
+            IPFlowError::VaultWsolAccountMissing,
+            IPFlowError::UnauthorizedClaimer,
+        ] {
+            assert!(!falls_back_to_sol(&error!(code), true, true), "{:?}", code);
+        }
+    }
+
+    #[test]
+    fn raydium_preflight_failure_falls_back_only_for_route_errors() {
+        let vault = Pubkey::new_unique();
+        let (keys, mut lamports, mut data) = raydium_fixture(vault);
+        let infos = route_infos(&keys, &mut lamports, &mut data);
+        let (output, mint) = (keys[5], keys[11]);
+
+        // 报价来自另一个 CPMM 程序: 路由错误，按 fallback_to_sol 降级
+        let wrong_program =
+            raydium_cpi::check_raydium_program(&keys[0], &Pubkey::new_unique()).unwrap_err();
+        assert!(falls_back_to_sol(&wrong_program, true, false));
+        assert!(!falls_back_to_sol(&wrong_program, false, false));
+
+        // 奖品 Mint 与池不符: 降级
+        let wrong_mint =
+            check_raydium_route_accounts(&infos, &vault, None, &output, &Pubkey::new_unique())
+                .unwrap_err();
+        assert!(falls_back_to_sol(&wrong_mint, true, false));
+
+        // 输出账户顺序错误是调用方错误: 即使要求降级也直接报错
+        let wrong_output =
+            check_raydium_route_accounts(&infos, &vault, None, &keys[6], &mint).unwrap_err();
+        assert!(!falls_back_to_sol(&wrong_output, true, true));
+
+        // 选中池已被删除: 降级
+        let pool_gone = check_prize_pool_available(&[0, 2], 1).unwrap_err();
+        assert!(falls_back_to_sol(&pool_gone, true, false));
+    }
+
+    fn request_with_preauth(preauthorization: Option<ClaimPreauthorization>) -> MintRequest {
+        let mut request = MintRequest::zeroed();
+        request.set_claim_preauthorization(preauthorization);
+        request
+    }
+
+    #[test]
+    fn claim_terms_default_to_instruction_args() {
+        let request = request_with_preauth(None);
+        let terms = resolve_claim_terms(
+            &request,
+            PayoutMode::Split,
+            Some(SwapRouter::Jupiter),
+            Some(500),
+            true,
+        )
+        .unwrap();
+        assert_eq!(terms.swap_router, Some(SwapRouter::Jupiter));
+        assert_eq!(terms.expected_token_output, Some(500));
+        assert_eq!(terms.slippage_bps, DEFAULT_SLIPPAGE_BPS);
+        assert!(terms.fallback_to_sol);
+        assert!(!terms.preauthorized);
+    }
+
+    #[test]
+    fn preauthorized_terms_override_relayer_args() {
+        let request = request_with_preauth(Some(ClaimPreauthorization {
+            router: SwapRouter::Raydium,
+            expected_token_output: 1_000_000,
+            max_slippage_bps: 50,
+        }));
+
+        // 中继传入更低的预期输出与降级标记均被忽略
+        let terms =
+            resolve_claim_terms(&request, PayoutMode::Token, None, Some(1), true).unwrap();
+        assert_eq!(terms.swap_router, Some(SwapRouter::Raydium));
+        assert_eq!(terms.expected_token_output, Some(1_000_000));
+        assert_eq!(terms.slippage_bps, 50);
+        assert!(!terms.fallback_to_sol);
+        assert!(terms.preauthorized);
+
+        assert_eq!(
+            resolve_claim_terms(&request, PayoutMode::Token, Some(SwapRouter::Jupiter), None, false)
+                .unwrap_err(),
+            error!(IPFlowError::ClaimPreauthorizationMismatch)
+        );
+        for mode in [PayoutMode::SOL, PayoutMode::Split] {
+            assert_eq!(
+                resolve_claim_terms(&request, mode, Some(SwapRouter::Raydium), None, false)
+                    .unwrap_err(),
+                error!(IPFlowError::ClaimPreauthorizationMismatch)
+            );
+        }
+    }
+
+    #[test]
+    fn preauthorization_bounds() {
+        let terms = |expected_token_output, max_slippage_bps| ClaimPreauthorization {
+            router: SwapRouter::Jupiter,
+            expected_token_output,
+            max_slippage_bps,
+        };
+        assert!(check_claim_preauthorization(&terms(1, 0)).is_ok());
+        assert!(check_claim_preauthorization(&terms(1, MAX_CLAIM_SLIPPAGE_BPS)).is_ok());
+        assert_eq!(
+            check_claim_preauthorization(&terms(1, MAX_CLAIM_SLIPPAGE_BPS + 1)).unwrap_err(),
+            error!(IPFlowError::InvalidClaimPreauthorization)
+        );
+        assert_eq!(
+            check_claim_preauthorization(&terms(0, 100)).unwrap_err(),
+            error!(IPFlowError::InvalidClaimPreauthorization)
+        );
+    }
+}
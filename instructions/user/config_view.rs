@@ -0,0 +1,19 @@
+// ==================== 配置视图查询 (只读) ====================
+//
+// 集成方程序通过 CPI (或前端通过 simulateTransaction) 调用，读取 return data 中的 ConfigView，
+// 无需硬编码 IPFlowState 的账户布局。ConfigView 只追加字段，调用方使用 ConfigView::decode 反序列化。
+
+use anchor_lang::prelude::*;
+
+use crate::state::ConfigView;
+use crate::GetConfig;
+
+pub fn get_config(ctx: Context<GetConfig>) -> Result<ConfigView> {
+    let view = ConfigView::from_state(&ctx.accounts.config);
+    msg!(
+        "Config view returned: view_version={}, config_version={}",
+        view.view_version,
+        view.config_version
+    );
+    Ok(view)
+}
@@ -0,0 +1,137 @@
+// ==================== 过期 Pending 请求清理 (crank) ====================
+//
+// VRF 永久未回调且用户未主动退款的请求会一直占用 PDA 租金，
+// 同时本金仍计入 Vault 余额，导致账目无法对齐。
+//
+// 请求创建超过 PENDING_EXPIRY_SECONDS (30 天) 后，任何人都可调用:
+// 1. 按原支付方式 (SOL/USDT) 将本金退还给支付者
+// 2. 关闭 MintRequest PDA，租金退还给支付者
+// 3. 由 Vault 向调用者支付 crank_tip_lamports 小费
+
+use anchor_lang::prelude::*;
+
+use crate::constants::PENDING_EXPIRY_SECONDS;
+use crate::errors::IPFlowError;
+use crate::instructions::user::refund_core::{do_refund, emit_refund_completed, RefundAccounts};
+use crate::state::RefundTrigger;
+use crate::utils::vault_available_lamports;
+use crate::CrankExpirePending;
+
+pub fn crank_expire_pending(ctx: Context<CrankExpirePending>) -> Result<()> {
+    let clock = Clock::get()?;
+    let request = &ctx.accounts.mint_request;
+
+    // ==================== 1. 校验过期条件 ====================
+    request.status.check_pending()?;
+    require!(
+        is_pending_expired(request.created_at, clock.unix_timestamp),
+        IPFlowError::RequestNotExpired
+    );
+
+    // ==================== 2. 退还本金给支付者 ====================
+    // crank 过期退款为全额退款，不扣除 VRF 费用
+    let outcome = do_refund(
+        request,
+        RefundTrigger::Crank,
+        RefundAccounts {
+            vault: &ctx.accounts.vault,
+            vault_bump: ctx.accounts.config.vault_bump,
+            recipient: &ctx.accounts.payer.to_account_info(),
+            system_program: &ctx.accounts.system_program,
+            token_program: ctx.accounts.token_program.as_ref(),
+            mint: ctx.accounts.payment_mint.as_ref(),
+            vault_token_account: ctx.accounts.vault_token_account.as_ref(),
+            recipient_token_account: ctx.accounts.user_token_account.as_ref(),
+            source_override: None,
+        },
+    )?;
+
+    ctx.accounts
+        .config
+        .release_pool_pending_reveals(request.snapshot_pool_indices());
+    ctx.accounts.config.count_expired = ctx.accounts.config.count_expired.saturating_add(1);
+
+    // ==================== 3. 支付调用者小费 ====================
+    // Vault 余额不足时按可用余额支付，不阻塞退款
+    let tip = crank_tip_amount(
+        ctx.accounts.config.crank_tip_lamports,
+        vault_available_lamports(&ctx.accounts.vault)?,
+    );
+
+    if tip > 0 {
+        let seeds = &[b"vault".as_ref(), &[ctx.accounts.config.vault_bump]];
+        let signer = &[&seeds[..]];
+
+        anchor_lang::solana_program::program::invoke_signed(
+            &anchor_lang::solana_program::system_instruction::transfer(
+                ctx.accounts.vault.key,
+                ctx.accounts.cranker.key,
+                tip,
+            ),
+            &[
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.cranker.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            signer,
+        )?;
+    }
+
+    // 4. 关闭 MintRequest PDA (租金退给支付者)
+    // 通过 Anchor 的 close = payer 自动处理
+
+    emit_refund_completed(
+        request,
+        RefundTrigger::Crank,
+        &outcome,
+        Some(ctx.accounts.cranker.key()),
+        tip,
+        clock.unix_timestamp,
+    );
+
+    msg!(
+        "Expired request cranked: created_at={}, refund={}, tip={}",
+        request.created_at,
+        outcome.refund_amount,
+        tip
+    );
+
+    Ok(())
+}
+
+/// 请求是否已超过 crank 过期窗口
+pub(crate) fn is_pending_expired(created_at: i64, now: i64) -> bool {
+    now.saturating_sub(created_at) > PENDING_EXPIRY_SECONDS
+}
+
+/// 实际支付的小费：不超过 Vault 扣除最小租金后的可用余额
+pub(crate) fn crank_tip_amount(configured_tip: u64, vault_available: u64) -> u64 {
+    configured_tip.min(vault_available)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_expired_before_window() {
+        let created_at = 1_700_000_000;
+        assert!(!is_pending_expired(created_at, created_at));
+        assert!(!is_pending_expired(
+            created_at,
+            created_at + PENDING_EXPIRY_SECONDS
+        ));
+        assert!(is_pending_expired(
+            created_at,
+            created_at + PENDING_EXPIRY_SECONDS + 1
+        ));
+    }
+
+    #[test]
+    fn tip_is_capped_by_vault_available_balance() {
+        assert_eq!(crank_tip_amount(10_000, 1_000_000), 10_000);
+        assert_eq!(crank_tip_amount(10_000, 5_000), 5_000);
+        assert_eq!(crank_tip_amount(10_000, 0), 0);
+        assert_eq!(crank_tip_amount(0, 1_000_000), 0);
+    }
+}
@@ -0,0 +1,49 @@
+// ==================== 领取委托 (会话密钥) ====================
+//
+// 用户授权短期会话密钥代为 claim，奖金始终发放给用户本人
+
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_DELEGATION_SECONDS;
+use crate::errors::IPFlowError;
+use crate::{RevokeDelegate, SetDelegate};
+
+/// 创建领取委托 (租金由用户支付)
+///
+/// - delegate: 会话密钥
+/// - expires_at: 过期时间戳，须在 (now, now + MAX_DELEGATION_SECONDS] 内
+pub fn set_delegate(ctx: Context<SetDelegate>, delegate: Pubkey, expires_at: i64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        expires_at > now && expires_at - now <= MAX_DELEGATION_SECONDS,
+        IPFlowError::InvalidDelegationExpiry
+    );
+    require!(
+        delegate != ctx.accounts.user.key(),
+        IPFlowError::InvalidDelegate
+    );
+
+    let delegation = &mut ctx.accounts.delegation;
+    delegation.user = ctx.accounts.user.key();
+    delegation.delegate = delegate;
+    delegation.expires_at = expires_at;
+    delegation.bump = ctx.bumps.delegation;
+
+    msg!(
+        "Delegate set: user={}, delegate={}, expires_at={}",
+        delegation.user,
+        delegate,
+        expires_at
+    );
+    Ok(())
+}
+
+/// 撤销领取委托 (关闭 PDA，租金通过 close = user 退还)
+pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+    msg!(
+        "Delegate revoked: user={}, delegate={}",
+        ctx.accounts.user.key(),
+        ctx.accounts.delegation.delegate
+    );
+    Ok(())
+}
@@ -0,0 +1,80 @@
+// ==================== Epoch 结算 (crank) ====================
+//
+// 当前 epoch 持续超过 epoch_length_seconds 后，任何人都可调用 roll_epoch:
+// 1. 写入当前 EpochStats 的结束时间并发出 EpochRolled 结算事件
+// 2. 创建下一个 epoch 的 EpochStats PDA (租金由调用者支付)
+// 3. 推进 config.current_epoch / epoch_start_ts
+
+use anchor_lang::prelude::*;
+
+use crate::errors::IPFlowError;
+use crate::events::EpochRolled;
+use crate::state::{EpochStats, IPFlowState};
+use crate::RollEpoch;
+
+pub fn roll_epoch(ctx: Context<RollEpoch>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let config = &mut ctx.accounts.config;
+
+    require!(config.epochs_started(), IPFlowError::EpochNotStarted);
+    require!(config.can_roll_epoch(now), IPFlowError::EpochNotElapsed);
+
+    // 1. 结算当前 epoch
+    let closing = &mut ctx.accounts.current_epoch_stats;
+    closing.end_ts = now;
+
+    emit!(EpochRolled {
+        epoch: closing.epoch,
+        start_ts: closing.start_ts,
+        end_ts: now,
+        collected_usd: closing.collected_usd,
+        paid_out_usd: closing.paid_out_usd,
+        refunded_usd: closing.refunded_usd,
+        cards_sold: closing.cards_sold,
+        rtp_bps: closing.rtp_bps(),
+    });
+
+    // 2. 初始化下一个 epoch
+    let next_epoch = config
+        .current_epoch
+        .checked_add(1)
+        .ok_or(IPFlowError::MathOverflow)?;
+    let next = &mut ctx.accounts.next_epoch_stats;
+    init_epoch_stats(next, next_epoch, now, ctx.bumps.next_epoch_stats);
+
+    config.current_epoch = next_epoch;
+    config.epoch_start_ts = now;
+
+    msg!(
+        "Epoch rolled: {} -> {}, collected={}, paid_out={}, refunded={}",
+        next_epoch - 1,
+        next_epoch,
+        closing.collected_usd,
+        closing.paid_out_usd,
+        closing.refunded_usd
+    );
+    Ok(())
+}
+
+/// 初始化 EpochStats 字段 (start_epoch 与 roll_epoch 共用)
+pub(crate) fn init_epoch_stats(stats: &mut EpochStats, epoch: u64, start_ts: i64, bump: u8) {
+    stats.epoch = epoch;
+    stats.start_ts = start_ts;
+    stats.end_ts = 0;
+    stats.collected_usd = 0;
+    stats.paid_out_usd = 0;
+    stats.refunded_usd = 0;
+    stats.cards_sold = 0;
+    stats.bump = bump;
+}
+
+/// epoch 统计已启用时必须传入当前 EpochStats，防止绕过统计
+pub(crate) fn require_epoch_stats(
+    config: &IPFlowState,
+    epoch_stats: Option<&mut Account<EpochStats>>,
+) -> Result<Option<&mut Account<EpochStats>>> {
+    if epoch_stats.is_none() {
+        require!(!config.epochs_started(), IPFlowError::EpochStatsRequired);
+    }
+    Ok(epoch_stats)
+}
@@ -0,0 +1,43 @@
+// ==================== 转化漏斗统计 ====================
+//
+// IPFlowState 中按请求终态累计计数 (创建 / 揭示 / SOL 领取 / Token 领取 / 退款 / 过期)，
+// 在对应指令中饱和累加。emit_stats 为 permissionless crank，每 STATS_EMIT_INTERVAL_SECONDS
+// 最多发出一次 StatsSnapshot，供看板按时间序列计算转化率。
+
+use anchor_lang::prelude::*;
+
+use crate::errors::IPFlowError;
+use crate::events::StatsSnapshot;
+use crate::EmitStats;
+
+/// 发出漏斗计数快照 (任何人可调用，每小时最多一次)
+pub fn emit_stats(ctx: Context<EmitStats>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let config = &mut ctx.accounts.config;
+    require!(config.can_emit_stats(now), IPFlowError::StatsEmitTooSoon);
+
+    let previous_stats_ts = config.last_stats_ts;
+    config.last_stats_ts = now;
+
+    emit!(StatsSnapshot {
+        count_requested: config.count_requested,
+        count_revealed: config.count_revealed,
+        count_claimed_sol: config.count_claimed_sol,
+        count_claimed_token: config.count_claimed_token,
+        count_refunded: config.count_refunded,
+        count_expired: config.count_expired,
+        previous_stats_ts,
+        timestamp: now,
+    });
+
+    msg!(
+        "Stats snapshot: requested={}, revealed={}, claimed_sol={}, claimed_token={}, refunded={}, expired={}",
+        config.count_requested,
+        config.count_revealed,
+        config.count_claimed_sol,
+        config.count_claimed_token,
+        config.count_refunded,
+        config.count_expired
+    );
+    Ok(())
+}
@@ -0,0 +1,372 @@
+// ==================== 健康检查 / 不变量查询 (只读) ====================
+//
+// 监控机器人通过 simulateTransaction 调用，一次性读取 Vault、手续费金库与中奖托管余额、未领取负债、
+// 活跃池数量、暂停位、预言机时效与配置版本，无需自行拉取多个账户并重复换算。
+//
+// remaining_accounts (可选): Vault 持有的稳定币 Token 账户，须属于支付 Mint 白名单。
+// 结果以 borsh 写入 return data，不发出事件。
+// strict = true 时任一不变量被破坏即返回 HealthCheckFailed，可供 keeper 作为链上断言。
+// assert_solvency 单独核对 Vault 账本，失败时以不同错误码指明被破坏的账本项；
+// strict 模式同样执行该核对。
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::constants::*;
+use crate::errors::IPFlowError;
+use crate::utils::{pyth_oracle, vault_available_lamports};
+use crate::{AssertSolvency, HealthCheck};
+
+/// Vault 持有的单个 Token 余额
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct VaultTokenBalance {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+/// health_check 返回值
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct HealthReport {
+    /// Vault 可用余额 (lamports，已扣除租金)
+    pub vault_lamports: u64,
+    /// 手续费金库可提取余额 (lamports，已扣除租金)，不计入奖金偿付能力
+    pub fee_vault_balance: u64,
+    /// 各请求 escrow PDA 中托管的中奖金额合计 (lamports)，与 Vault 余额一起计入偿付能力
+    pub escrowed_lamports: u64,
+    /// 已揭示未领取的中奖总额 (micro-USD)
+    pub liabilities_usd: u64,
+    /// 按当前 Pyth 价格换算的负债 (lamports)，价格无效时为 None
+    pub liabilities_lamports: Option<u64>,
+    pub active_pool_count: u8,
+    pub pause_flags: u8,
+    /// Pyth 价格发布时间戳
+    pub price_publish_time: i64,
+    /// 价格距今秒数
+    pub oracle_staleness_seconds: i64,
+    pub config_version: u8,
+    pub vault_token_balances: Vec<VaultTokenBalance>,
+    /// 被破坏的不变量位图 (HEALTH_*)，0 表示健康
+    pub violations: u16,
+    pub timestamp: i64,
+}
+
+pub fn health_check<'info>(
+    ctx: Context<'_, '_, 'info, 'info, HealthCheck<'info>>,
+    strict: bool,
+) -> Result<HealthReport> {
+    let now = Clock::get()?.unix_timestamp;
+    let config = &ctx.accounts.config;
+
+    let vault_lamports = vault_available_lamports(&ctx.accounts.vault.to_account_info())?;
+    let fee_vault_balance =
+        vault_available_lamports(&ctx.accounts.fee_vault.to_account_info())?;
+
+    // 预言机: feed id 不符视为传错账户直接报错；价格异常或陈旧仅记录在报告中
+    let price_message = &ctx.accounts.pyth_price_update.price_message;
+    require!(
+        price_message.feed_id == PYTH_SOL_USD_FEED_ID,
+        IPFlowError::PythFeedIdMismatch
+    );
+    let oracle_staleness_seconds = now.saturating_sub(price_message.publish_time);
+    let liabilities_lamports =
+        pyth_oracle::check_price_sanity(price_message.price, price_message.conf)
+            .and_then(|_| {
+                pyth_oracle::micro_usd_to_lamports(
+                    price_message.price,
+                    price_message.exponent,
+                    config.outstanding_liabilities_usd,
+                )
+            })
+            .ok();
+
+    require!(
+        ctx.remaining_accounts.len() <= MAX_PAYMENT_MINTS,
+        IPFlowError::InvalidHealthCheckAccount
+    );
+    let mut vault_token_balances = Vec::with_capacity(ctx.remaining_accounts.len());
+    for info in ctx.remaining_accounts.iter() {
+        let token_account = Account::<TokenAccount>::try_from(info)?;
+        require_keys_eq!(
+            token_account.owner,
+            ctx.accounts.vault.key(),
+            IPFlowError::InvalidHealthCheckAccount
+        );
+        require!(
+            config.find_payment_mint(&token_account.mint).is_some(),
+            IPFlowError::InvalidHealthCheckAccount
+        );
+        vault_token_balances.push(VaultTokenBalance {
+            mint: token_account.mint,
+            amount: token_account.amount,
+        });
+    }
+
+    if strict {
+        if let Some(liabilities_lamports) = liabilities_lamports {
+            let vault_info = ctx.accounts.vault.to_account_info();
+            check_solvency(&SolvencyTerms {
+                vault_lamports: vault_info.lamports(),
+                rent_floor: Rent::get()?.minimum_balance(vault_info.data_len()),
+                escrowed_lamports: config.total_escrowed_lamports,
+                liabilities_usd: config.outstanding_liabilities_usd,
+                liabilities_lamports,
+            })?;
+        }
+    }
+
+    let violations = collect_violations(
+        vault_lamports.saturating_add(config.total_escrowed_lamports),
+        liabilities_lamports,
+        oracle_staleness_seconds,
+        config.max_price_age_seconds,
+        config.active_pool_count,
+        config.validate().is_ok(),
+    );
+    require!(!strict || violations == 0, IPFlowError::HealthCheckFailed);
+
+    Ok(HealthReport {
+        vault_lamports,
+        fee_vault_balance,
+        escrowed_lamports: config.total_escrowed_lamports,
+        liabilities_usd: config.outstanding_liabilities_usd,
+        liabilities_lamports,
+        active_pool_count: config.active_pool_count,
+        pause_flags: config.pause_flags,
+        price_publish_time: price_message.publish_time,
+        oracle_staleness_seconds,
+        config_version: config.config_version,
+        vault_token_balances,
+        violations,
+        timestamp: now,
+    })
+}
+
+/// 偿付能力核对 (任何人可调用，供 keeper 定期模拟执行)
+///
+/// Vault 扣除免租保留额后的余额加上各 escrow PDA 的托管合计，须覆盖按当前 Pyth 价格 (向上取整)
+/// 换算的未领取负债。手续费金库归平台所有，其余额只记录在日志中，不计入偿付能力。
+///
+/// # 错误
+/// - `SolvencyRentFloorBreached` / `SolvencyEscrowWithoutLiabilities` / `SolvencyLiabilitiesUncovered`:
+///   对应账本项被破坏 (见 `check_solvency`)
+/// - Pyth 价格无效或陈旧时返回预言机错误
+pub fn assert_solvency(ctx: Context<AssertSolvency>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let vault_info = ctx.accounts.vault.to_account_info();
+    let fee_vault_balance =
+        vault_available_lamports(&ctx.accounts.fee_vault.to_account_info())?;
+
+    let terms = SolvencyTerms {
+        vault_lamports: vault_info.lamports(),
+        rent_floor: Rent::get()?.minimum_balance(vault_info.data_len()),
+        escrowed_lamports: config.total_escrowed_lamports,
+        liabilities_usd: config.outstanding_liabilities_usd,
+        liabilities_lamports: pyth_oracle::get_lamports_ceil(
+            &ctx.accounts.pyth_price_update,
+            config.outstanding_liabilities_usd,
+            config.max_price_age_seconds,
+        )?,
+    };
+
+    msg!(
+        "Solvency: vault={}, rent_floor={}, escrowed={}, liabilities={} lamports ({} micro-USD), fee_vault={} (excluded)",
+        terms.vault_lamports,
+        terms.rent_floor,
+        terms.escrowed_lamports,
+        terms.liabilities_lamports,
+        terms.liabilities_usd,
+        fee_vault_balance
+    );
+
+    check_solvency(&terms)
+}
+
+/// 偿付能力核对使用的账本项
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct SolvencyTerms {
+    /// Vault 总余额 (lamports，含免租保留额)
+    pub vault_lamports: u64,
+    /// Vault 免租保留额 (lamports)
+    pub rent_floor: u64,
+    /// escrow PDA 托管合计 (config.total_escrowed_lamports)
+    pub escrowed_lamports: u64,
+    /// 已揭示未领取的中奖总额 (micro-USD)
+    pub liabilities_usd: u64,
+    /// 按 Pyth 价格换算的未领取负债 (lamports)
+    pub liabilities_lamports: u64,
+}
+
+/// 依次核对各账本项，返回第一个被破坏项对应的错误:
+/// 1. Vault 余额不低于免租保留额 (`SolvencyRentFloorBreached`)
+/// 2. 有托管资金时必有未领取负债 (`SolvencyEscrowWithoutLiabilities`，托管计数漂移)
+/// 3. Vault 可用余额 + 托管合计 >= 负债 (`SolvencyLiabilitiesUncovered`)
+pub(crate) fn check_solvency(terms: &SolvencyTerms) -> Result<()> {
+    require!(
+        terms.vault_lamports >= terms.rent_floor,
+        IPFlowError::SolvencyRentFloorBreached
+    );
+    require!(
+        terms.escrowed_lamports == 0 || terms.liabilities_usd > 0,
+        IPFlowError::SolvencyEscrowWithoutLiabilities
+    );
+    let backing = (terms.vault_lamports - terms.rent_floor)
+        .checked_add(terms.escrowed_lamports)
+        .ok_or(IPFlowError::MathOverflow)?;
+    require!(
+        backing >= terms.liabilities_lamports,
+        IPFlowError::SolvencyLiabilitiesUncovered
+    );
+    Ok(())
+}
+
+/// 汇总被破坏的不变量 (HEALTH_* 位图)
+pub(crate) fn collect_violations(
+    vault_lamports: u64,
+    liabilities_lamports: Option<u64>,
+    oracle_staleness_seconds: i64,
+    max_price_age_seconds: u64,
+    active_pool_count: u8,
+    config_valid: bool,
+) -> u16 {
+    let mut violations = 0u16;
+    match liabilities_lamports {
+        Some(liabilities) if vault_lamports < liabilities => {
+            violations |= HEALTH_VAULT_BELOW_LIABILITIES
+        }
+        Some(_) => {}
+        None => violations |= HEALTH_ORACLE_INVALID,
+    }
+    if oracle_staleness_seconds > max_price_age_seconds.min(i64::MAX as u64) as i64 {
+        violations |= HEALTH_ORACLE_STALE;
+    }
+    if active_pool_count == 0 {
+        violations |= HEALTH_NO_ACTIVE_POOLS;
+    }
+    if !config_valid {
+        violations |= HEALTH_CONFIG_INVALID;
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program::MAX_RETURN_DATA;
+
+    #[test]
+    fn healthy_state_has_no_violations() {
+        assert_eq!(collect_violations(1_000, Some(1_000), 10, 60, 3, true), 0);
+    }
+
+    #[test]
+    fn flags_vault_below_liabilities() {
+        assert_eq!(
+            collect_violations(999, Some(1_000), 0, 60, 1, true),
+            HEALTH_VAULT_BELOW_LIABILITIES
+        );
+    }
+
+    #[test]
+    fn flags_stale_and_invalid_oracle() {
+        assert_eq!(collect_violations(0, Some(0), 61, 60, 1, true), HEALTH_ORACLE_STALE);
+        // 价格无效时无法比较负债，仅标记 ORACLE_INVALID
+        assert_eq!(collect_violations(0, None, 0, 60, 1, true), HEALTH_ORACLE_INVALID);
+    }
+
+    #[test]
+    fn flags_pool_and_config_violations_together() {
+        assert_eq!(
+            collect_violations(0, Some(0), 0, 60, 0, false),
+            HEALTH_NO_ACTIVE_POOLS | HEALTH_CONFIG_INVALID
+        );
+    }
+
+    fn solvent_terms() -> SolvencyTerms {
+        SolvencyTerms {
+            vault_lamports: 10_000_890_880,
+            rent_floor: 890_880,
+            escrowed_lamports: 2_000_000_000,
+            liabilities_usd: 1_000_000_000,
+            liabilities_lamports: 12_000_000_000,
+        }
+    }
+
+    #[test]
+    fn solvency_accepts_exactly_covered_books() {
+        assert!(check_solvency(&solvent_terms()).is_ok());
+
+        // 无负债无托管时只要求免租保留额
+        let empty = SolvencyTerms {
+            vault_lamports: 890_880,
+            escrowed_lamports: 0,
+            liabilities_usd: 0,
+            liabilities_lamports: 0,
+            ..solvent_terms()
+        };
+        assert!(check_solvency(&empty).is_ok());
+    }
+
+    #[test]
+    fn solvency_rejects_vault_below_rent_floor() {
+        let terms = SolvencyTerms {
+            vault_lamports: 890_879,
+            liabilities_lamports: 0,
+            ..solvent_terms()
+        };
+        assert_eq!(
+            check_solvency(&terms).unwrap_err(),
+            error!(IPFlowError::SolvencyRentFloorBreached)
+        );
+    }
+
+    #[test]
+    fn solvency_rejects_escrow_without_liabilities() {
+        let terms = SolvencyTerms {
+            liabilities_usd: 0,
+            liabilities_lamports: 0,
+            ..solvent_terms()
+        };
+        assert_eq!(
+            check_solvency(&terms).unwrap_err(),
+            error!(IPFlowError::SolvencyEscrowWithoutLiabilities)
+        );
+    }
+
+    #[test]
+    fn solvency_rejects_uncovered_liabilities() {
+        let terms = SolvencyTerms {
+            liabilities_lamports: 12_000_000_001,
+            ..solvent_terms()
+        };
+        assert_eq!(
+            check_solvency(&terms).unwrap_err(),
+            error!(IPFlowError::SolvencyLiabilitiesUncovered)
+        );
+    }
+
+    #[test]
+    fn full_report_fits_return_data() {
+        let report = HealthReport {
+            vault_lamports: u64::MAX,
+            fee_vault_balance: u64::MAX,
+            escrowed_lamports: u64::MAX,
+            liabilities_usd: u64::MAX,
+            liabilities_lamports: Some(u64::MAX),
+            active_pool_count: 50,
+            pause_flags: PAUSE_ALL,
+            price_publish_time: i64::MAX,
+            oracle_staleness_seconds: i64::MAX,
+            config_version: u8::MAX,
+            vault_token_balances: vec![
+                VaultTokenBalance {
+                    mint: Pubkey::new_unique(),
+                    amount: u64::MAX,
+                };
+                MAX_PAYMENT_MINTS
+            ],
+            violations: u16::MAX,
+            timestamp: i64::MAX,
+        };
+        assert!(report.try_to_vec().unwrap().len() <= MAX_RETURN_DATA);
+    }
+}
@@ -0,0 +1,77 @@
+// ==================== MintRequest 布局迁移 ====================
+//
+// MintRequest 新增字段均追加在末尾。升级前创建的请求账户长度不足，以 Account<MintRequest> 加载会
+// 反序列化失败，导致无法领取、退款或清理 (资金滞留 Vault)。
+//
+// migrate_mint_request 将账户扩容到当前 INIT_SPACE 并零填充新增字段 (与 migrate_prize_pool 相同)，
+// 随后按 MintRequest::normalize_legacy_fields 补齐零值不能表示旧语义的字段。任何人都可调用
+// (调用者补足租金差额，关闭 PDA 时租金随其余余额退还给请求的 user / payer)；对最新布局的请求为 no-op。
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke, system_instruction};
+use anchor_lang::Discriminator;
+
+use crate::errors::IPFlowError;
+use crate::state::MintRequest;
+use crate::MigrateMintRequest;
+
+/// 迁移/扩容 MintRequest 账户
+pub fn migrate_mint_request(ctx: Context<MigrateMintRequest>) -> Result<()> {
+    let request_info = ctx.accounts.mint_request.to_account_info();
+    require!(
+        request_info.owner == ctx.program_id,
+        IPFlowError::InvalidRequestStatus
+    );
+    {
+        let data = request_info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == MintRequest::DISCRIMINATOR[..],
+            IPFlowError::InvalidRequestStatus
+        );
+    }
+
+    let old_space = request_info.data_len();
+    let new_space = 8 + MintRequest::INIT_SPACE;
+    if old_space < new_space {
+        let required_lamports = Rent::get()?.minimum_balance(new_space);
+        let current_lamports = request_info.lamports();
+
+        if current_lamports < required_lamports {
+            let diff = required_lamports - current_lamports;
+            invoke(
+                &system_instruction::transfer(ctx.accounts.payer.key, request_info.key, diff),
+                &[
+                    ctx.accounts.payer.to_account_info(),
+                    request_info.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+
+        #[allow(deprecated)] // realloc 是当前唯一的账户扩容方式
+        request_info.realloc(new_space, true)?;
+    }
+
+    // 反序列化 → 补齐旧版字段 → 重新序列化
+    let mut data_mut = request_info.try_borrow_mut_data()?;
+    let mut cursor: &[u8] = &data_mut;
+    let mut request = MintRequest::try_deserialize(&mut cursor)?;
+    require_keys_eq!(
+        request.user,
+        ctx.accounts.user.key(),
+        IPFlowError::Unauthorized
+    );
+    request.normalize_legacy_fields(&ctx.accounts.config);
+
+    let mut dst: &mut [u8] = &mut data_mut;
+    request.try_serialize(&mut dst)?;
+
+    msg!(
+        "Mint request migrated: slot={}, space {} -> {}",
+        request.vrf_request_slot,
+        old_space,
+        new_space.max(old_space)
+    );
+
+    Ok(())
+}
@@ -1,3 +1,19 @@
+pub mod active_pools;
+pub mod card_nft;
 pub mod claim;
+pub mod config_view;
+pub mod crank;
+pub mod delegation;
+pub mod epoch;
+pub mod funnel;
+pub mod health;
+pub mod migrate_request;
+pub mod receipt;
 pub mod refund;
+pub mod refund_core;
 pub mod request_mint;
+pub mod rewards;
+pub mod rtp_info;
+pub mod sweep;
+pub mod user_stats;
+pub mod validate_claim;
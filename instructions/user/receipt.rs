@@ -0,0 +1,58 @@
+// ==================== 领取凭证管理 ====================
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount};
+
+use crate::errors::IPFlowError;
+use crate::events::OutputAccountClosed;
+use crate::{CleanupOutputAccount, CloseReceipt};
+
+/// 关闭领取凭证 PDA (租金通过 close = user 退还给用户)
+pub fn close_receipt(ctx: Context<CloseReceipt>) -> Result<()> {
+    msg!(
+        "ClaimReceipt closed: user={}, request_id={}",
+        ctx.accounts.user.key(),
+        ctx.accounts.receipt.request_id
+    );
+    Ok(())
+}
+
+/// 关闭领取时由合约新建的空输出 Token 账户 (租金退还给用户)
+///
+/// 仅接受领取凭证记录的奖品 Token / WSOL ATA，且余额必须为 0；关闭后清除凭证中的记录
+pub fn cleanup_output_account(ctx: Context<CleanupOutputAccount>) -> Result<()> {
+    let token_account = &ctx.accounts.output_token_account;
+    require!(token_account.amount == 0, IPFlowError::OutputAccountNotEmpty);
+
+    let key = token_account.key();
+    let receipt = &mut ctx.accounts.receipt;
+    if key == receipt.created_token_account {
+        receipt.created_token_account = Pubkey::default();
+    } else if key == receipt.created_wsol_account {
+        receipt.created_wsol_account = Pubkey::default();
+    } else {
+        return err!(IPFlowError::OutputAccountNotCreatedByClaim);
+    }
+
+    let mint = token_account.mint;
+    let reclaimed_lamports = token_account.to_account_info().lamports();
+
+    token::close_account(CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: token_account.to_account_info(),
+            destination: ctx.accounts.user.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        },
+    ))?;
+
+    emit!(OutputAccountClosed {
+        user: ctx.accounts.user.key(),
+        request_id: receipt.request_id,
+        token_account: key,
+        mint,
+        reclaimed_lamports,
+    });
+
+    Ok(())
+}
@@ -1,156 +1,264 @@
 // ==================== Task 2.3: 超时退款指令 ====================
 //
-// 当 MintRequest 处于 Pending 状态超过 10 分钟（VRF 未回调）时，
+// 当 MintRequest 处于 Pending 状态超过 request_timeout_seconds（VRF 未回调）时，
 // 允许用户申请退款，防止资金卡死。
+// 揭示计算出错被置为 Failed 的请求可立即全额退款，无需等待超时。
 //
 // 支持两种退款模式:
 // - SOL 退款: Vault → User (System Program transfer)
 // - USDT 退款: VaultTokenAccount → UserTokenAccount (SPL Token transfer)
+// - WSOL 退款: 支付时已解包为 Vault lamports，与 SOL 相同从 Vault 退还；升级前的 WSOL 请求
+//   仍从 Vault WSOL 账户 → 用户 WSOL 账户 (与 USDT 共用 Token 转账路径)
+//
+// Token 退款只能从支付时收款的 Vault Token 账户 (mint_request.vault_token_account) 转出。
+// 该账户轮换后已关闭或余额不足时，由管理员调用 refund_from_alternate，
+// 从同一 Mint 的其他 Vault Token 账户退款。
+// 金额计算与转账由 refund_core::do_refund 统一处理。
 
 use anchor_lang::prelude::*;
-use anchor_spl::token;
+use anchor_spl::token::{Token, TokenAccount};
 
-use crate::constants::USDT_MINT_DEVNET;
 use crate::errors::IPFlowError;
-use crate::state::{PaymentMode, RequestStatus};
-use crate::Refund;
+use crate::events::RefundSourcedFromAlternate;
+use crate::instructions::user::epoch::require_epoch_stats;
+use crate::instructions::user::refund_core::{
+    do_refund, emit_refund_completed, refund_payment_mode, refund_terms, RefundAccounts,
+};
+use crate::state::{PaymentMode, RefundTrigger, RequestStatus};
+use crate::{Refund, RefundFromAlternate};
 
 pub fn handler(ctx: Context<Refund>) -> Result<()> {
     let clock = Clock::get()?;
     let request = &ctx.accounts.mint_request;
 
     // ==================== 1. 校验退款条件 ====================
-    // 条件: Pending 状态且超过 request_timeout_seconds
+    // 条件: Failed 状态，或 Pending 状态 (其余状态返回具体错误码) 且超过 request_timeout_seconds
     let request_timeout_seconds = ctx.accounts.config.request_timeout_seconds;
-    let is_pending_timeout = request.status == RequestStatus::Pending
-        && clock.unix_timestamp - request.created_at > request_timeout_seconds;
-
-    require!(is_pending_timeout, IPFlowError::RefundNotAllowed);
+    check_refundable(
+        request.status,
+        request.created_at,
+        clock.unix_timestamp,
+        request_timeout_seconds,
+    )?;
+    let trigger = user_refund_trigger(request.status);
 
     msg!(
-        "Refund triggered: created_at={}, now={}, timeout={}s",
+        "Refund triggered: created_at={}, now={}, timeout={}s, trigger={:?}",
         request.created_at,
         clock.unix_timestamp,
-        request_timeout_seconds
+        request_timeout_seconds,
+        trigger
     );
 
     // ==================== 2. 根据支付方式执行退款 ====================
-    match request.payment_mode {
-        PaymentMode::SOL => {
-            // SOL 退款: Vault → User
-            let refund_amount = request.paid_amount;
-            let vault = &ctx.accounts.vault;
-            let user = &ctx.accounts.user;
-            let config = &ctx.accounts.config;
-
-            // Vault 余额检查
-            require!(
-                vault.lamports() >= refund_amount,
-                IPFlowError::InsufficientVaultBalance
-            );
-
-            // PDA 签名转账
-            let seeds = &[b"vault".as_ref(), &[config.vault_bump]];
-            let signer = &[&seeds[..]];
-
-            anchor_lang::solana_program::program::invoke_signed(
-                &anchor_lang::solana_program::system_instruction::transfer(
-                    vault.key,
-                    user.key,
-                    refund_amount,
-                ),
-                &[
-                    vault.to_account_info(),
-                    user.to_account_info(),
-                    ctx.accounts.system_program.to_account_info(),
-                ],
-                signer,
-            )?;
-
-            msg!("SOL refund completed: {} lamports", refund_amount);
-        }
-        PaymentMode::USDT => {
-            // USDT 退款: VaultTokenAccount → UserTokenAccount
-            let refund_amount = request.paid_amount;
-
-            // 校验必需的 Token 账户存在
-            let token_program = ctx
-                .accounts
-                .token_program
-                .as_ref()
-                .ok_or(IPFlowError::RefundNotAllowed)?;
-
-            let vault_token_account = ctx
-                .accounts
-                .vault_token_account
-                .as_ref()
-                .ok_or(IPFlowError::RefundNotAllowed)?;
-
-            let user_token_account = ctx
-                .accounts
-                .user_token_account
-                .as_ref()
-                .ok_or(IPFlowError::RefundNotAllowed)?;
-
-            // 校验 Token 账户余额
-            require!(
-                vault_token_account.amount >= refund_amount,
-                IPFlowError::InsufficientVaultBalance
-            );
-
-            // 校验用户 Token 账户 owner
-            require!(
-                user_token_account.owner == ctx.accounts.user.key(),
-                IPFlowError::Unauthorized
-            );
-            require!(
-                user_token_account.mint == USDT_MINT_DEVNET,
-                IPFlowError::InvalidTokenAccount
-            );
-
-            // 校验 Vault Token 账户 mint/owner
-            require!(
-                vault_token_account.mint == USDT_MINT_DEVNET,
-                IPFlowError::InvalidTokenAccount
-            );
-            require!(
-                vault_token_account.owner == ctx.accounts.vault.key(),
-                IPFlowError::InvalidTokenAccount
-            );
-
-            // Vault PDA 签名
-            let config = &ctx.accounts.config;
-            let seeds = &[b"vault".as_ref(), &[config.vault_bump]];
-            let signer = &[&seeds[..]];
-
-            // SPL Token 转账
-            token::transfer(
-                CpiContext::new_with_signer(
-                    token_program.to_account_info(),
-                    token::Transfer {
-                        from: vault_token_account.to_account_info(),
-                        to: user_token_account.to_account_info(),
-                        authority: ctx.accounts.vault.to_account_info(),
-                    },
-                    signer,
-                ),
-                refund_amount,
-            )?;
-
-            msg!(
-                "USDT refund completed: {} (6 decimals)",
-                refund_amount
-            );
-        }
+    // SOL 超时退款扣除 Vault 已代付的 VRF 费用 (不可回收)；揭示失败非用户原因，全额退还
+    let outcome = do_refund(
+        request,
+        trigger,
+        RefundAccounts {
+            vault: &ctx.accounts.vault,
+            vault_bump: ctx.accounts.config.vault_bump,
+            recipient: &ctx.accounts.user.to_account_info(),
+            system_program: &ctx.accounts.system_program,
+            token_program: ctx.accounts.token_program.as_ref(),
+            mint: ctx.accounts.payment_mint.as_ref(),
+            vault_token_account: ctx.accounts.vault_token_account.as_ref(),
+            recipient_token_account: ctx.accounts.user_token_account.as_ref(),
+            source_override: None,
+        },
+    )?;
+
+    // 2.1 释放请求快照的待揭示引用，累计当前 epoch 退款 (按实际退还比例折算美元价值)
+    ctx.accounts
+        .config
+        .release_pool_pending_reveals(request.snapshot_pool_indices());
+    ctx.accounts.config.count_refunded = ctx.accounts.config.count_refunded.saturating_add(1);
+    if let Some(stats) = require_epoch_stats(
+        &ctx.accounts.config,
+        ctx.accounts.epoch_stats.as_mut(),
+    )? {
+        stats.record_refund(outcome.refund_usd)?;
     }
 
     // 3. 关闭 MintRequest PDA (租金退给用户)
     // 通过 Anchor 的 close = user 自动处理
 
+    emit_refund_completed(request, trigger, &outcome, None, 0, clock.unix_timestamp);
+
+    msg!(
+        "Refund completed for request created at {}, vrf_fee_deducted={}",
+        request.created_at,
+        outcome.vrf_fee_deducted
+    );
+
+    Ok(())
+}
+
+/// 管理员协助退款：原收款 Vault Token 账户已关闭或余额不足时，从同 Mint 的备用 Vault Token 账户退款
+///
+/// 退款条件与金额与 refund 一致 (SOL 计价的超时退款扣除 VRF 费用)，PDA 通过 close = payer 关闭
+pub fn refund_from_alternate(ctx: Context<RefundFromAlternate>) -> Result<()> {
+    let clock = Clock::get()?;
+    let request = &ctx.accounts.mint_request;
+
+    check_refundable(
+        request.status,
+        request.created_at,
+        clock.unix_timestamp,
+        ctx.accounts.config.request_timeout_seconds,
+    )?;
+    require!(
+        refund_payment_mode(request, &ctx.accounts.vault.key()) != PaymentMode::SOL,
+        IPFlowError::RefundNotAllowed
+    );
+    let trigger = user_refund_trigger(request.status);
+    let (refund_amount, _) = refund_terms(
+        trigger,
+        request.payment_mode,
+        request.paid_amount,
+        request.vrf_fee_lamports,
+    );
+
+    // 原收款账户仍能承担退款时必须走 refund，避免管理员任意挪用其他账户
+    let original = &ctx.accounts.original_vault_token_account;
+    let original_balance = if original.owner == &Token::id() && !original.data_is_empty() {
+        let data = original.try_borrow_data()?;
+        Some(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+    } else {
+        None
+    };
+    require!(
+        original_source_depleted(original_balance, refund_amount),
+        IPFlowError::OriginalRefundSourceFunded
+    );
+
+    let alternate = &ctx.accounts.alternate_vault_token_account;
+    let outcome = do_refund(
+        request,
+        trigger,
+        RefundAccounts {
+            vault: &ctx.accounts.vault,
+            vault_bump: ctx.accounts.config.vault_bump,
+            recipient: &ctx.accounts.payer.to_account_info(),
+            system_program: &ctx.accounts.system_program,
+            token_program: Some(&ctx.accounts.token_program),
+            mint: Some(&ctx.accounts.payment_mint),
+            vault_token_account: Some(alternate),
+            recipient_token_account: Some(&ctx.accounts.payer_token_account),
+            source_override: Some(alternate.key()),
+        },
+    )?;
+
+    ctx.accounts
+        .config
+        .release_pool_pending_reveals(request.snapshot_pool_indices());
+    ctx.accounts.config.count_refunded = ctx.accounts.config.count_refunded.saturating_add(1);
+    if let Some(stats) = require_epoch_stats(
+        &ctx.accounts.config,
+        ctx.accounts.epoch_stats.as_mut(),
+    )? {
+        stats.record_refund(outcome.refund_usd)?;
+    }
+
+    emit!(RefundSourcedFromAlternate {
+        admin: ctx.accounts.admin.key(),
+        mint_request: request.key(),
+        original_vault_token_account: original.key(),
+        alternate_vault_token_account: alternate.key(),
+        refund_amount: outcome.refund_amount,
+    });
+    emit_refund_completed(request, trigger, &outcome, None, 0, clock.unix_timestamp);
+
     msg!(
-        "Refund completed for request created at {}",
-        request.created_at
+        "Refund sourced from alternate vault account {} (original {}): {}",
+        alternate.key(),
+        original.key(),
+        outcome.refund_amount
     );
+    Ok(())
+}
+
+/// 用户 (或管理员协助) 退款的触发来源: 揭示失败为 Failed，其余为 VRF 超时
+pub(crate) fn user_refund_trigger(status: RequestStatus) -> RefundTrigger {
+    if status == RequestStatus::Failed {
+        RefundTrigger::Failed
+    } else {
+        RefundTrigger::Timeout
+    }
+}
 
+/// 退款条件: Failed 立即可退；Pending 须超过 request_timeout_seconds；其余状态返回具体错误码
+pub(crate) fn check_refundable(
+    status: RequestStatus,
+    created_at: i64,
+    now: i64,
+    request_timeout_seconds: i64,
+) -> Result<()> {
+    if status == RequestStatus::Failed {
+        return Ok(());
+    }
+    status.check_pending()?;
+    require!(
+        now - created_at > request_timeout_seconds,
+        IPFlowError::RefundNotAllowed
+    );
     Ok(())
 }
+
+/// 原收款账户是否已无法承担退款 (已关闭，或余额不足)，此时才允许从备用账户退款
+pub(crate) fn original_source_depleted(original_balance: Option<u64>, refund_amount: u64) -> bool {
+    original_balance.map_or(true, |balance| balance < refund_amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::user::refund_core::check_refund_source;
+
+    #[test]
+    fn test_failed_request_refundable_immediately() {
+        assert!(check_refundable(RequestStatus::Failed, 1_000, 1_000, 45).is_ok());
+    }
+
+    #[test]
+    fn test_pending_request_waits_for_timeout() {
+        assert_eq!(
+            check_refundable(RequestStatus::Pending, 1_000, 1_045, 45).unwrap_err(),
+            error!(IPFlowError::RefundNotAllowed)
+        );
+        assert!(check_refundable(RequestStatus::Pending, 1_000, 1_046, 45).is_ok());
+    }
+
+    #[test]
+    fn test_revealed_request_not_refundable() {
+        assert_eq!(
+            check_refundable(RequestStatus::Revealed, 1_000, i64::MAX, 45).unwrap_err(),
+            error!(IPFlowError::RequestAlreadyRevealed)
+        );
+    }
+
+    #[test]
+    fn test_refund_source_after_vault_account_rotation() {
+        let original = Pubkey::new_unique();
+        let rotated = Pubkey::new_unique();
+
+        // refund 只接受支付时的收款账户，轮换后的新账户被拒绝
+        assert!(check_refund_source(original, original).is_ok());
+        assert_eq!(
+            check_refund_source(original, rotated).unwrap_err(),
+            error!(IPFlowError::RefundSourceMismatch)
+        );
+
+        // 原账户已关闭或余额不足时才允许 refund_from_alternate
+        assert!(original_source_depleted(None, 1_000_000));
+        assert!(original_source_depleted(Some(0), 1_000_000));
+        assert!(original_source_depleted(Some(999_999), 1_000_000));
+        assert!(!original_source_depleted(Some(1_000_000), 1_000_000));
+    }
+
+    #[test]
+    fn test_user_refund_trigger_follows_status() {
+        assert_eq!(user_refund_trigger(RequestStatus::Failed), RefundTrigger::Failed);
+        assert_eq!(user_refund_trigger(RequestStatus::Pending), RefundTrigger::Timeout);
+    }
+}
@@ -0,0 +1,355 @@
+// ==================== 退款公共逻辑 ====================
+//
+// 所有退还本金的入口 (refund / refund_from_alternate / crank_expire_pending / sweep_expired /
+// reject_large_win) 共用 do_refund:
+// - 按 RefundTrigger 统一计算退款金额与 VRF 费用扣除
+// - SOL 与 Token (USDT / WSOL) 退款的分支只在 transfer_refund 中出现；已解包的 WSOL 支付按 SOL 退还
+// - 统一通过 emit_refund_completed 记录 RefundCompleted 事件 (携带 trigger)
+//
+// 各入口自行负责权限与前置条件校验 (状态、超时、过期窗口、审核标记等) 以及计数器更新。
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+use crate::errors::IPFlowError;
+use crate::events::RefundCompleted;
+use crate::state::{MintRequest, PaymentMode, RefundTrigger};
+use crate::utils::vault_available_lamports;
+
+/// 退款转账涉及的账户
+pub(crate) struct RefundAccounts<'a, 'info> {
+    /// Vault PDA (SOL 退款来源，Token 退款的转账 authority)
+    pub vault: &'a AccountInfo<'info>,
+    pub vault_bump: u8,
+    /// 退款接收方 (SOL 直接转入；Token 退款时为接收 Token 账户的 owner)
+    pub recipient: &'a AccountInfo<'info>,
+    pub system_program: &'a Program<'info, System>,
+    pub token_program: Option<&'a Program<'info, Token>>,
+    pub mint: Option<&'a Account<'info, Mint>>,
+    pub vault_token_account: Option<&'a Account<'info, TokenAccount>>,
+    pub recipient_token_account: Option<&'a Account<'info, TokenAccount>>,
+    /// 允许的 Vault Token 来源账户，None 表示请求记录的收款账户 (仅 refund_from_alternate 覆盖)
+    pub source_override: Option<Pubkey>,
+}
+
+/// 一次退款的结果
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct RefundOutcome {
+    /// 实际退还的本金 (lamports 或 token amount)
+    pub refund_amount: u64,
+    /// 从本金中扣除的 VRF 费用 (lamports)
+    pub vrf_fee_deducted: u64,
+    /// 退款对应的美元价值 (按退还比例折算，用于 epoch 统计)
+    pub refund_usd: u64,
+}
+
+/// 退还请求本金：按 trigger 计算金额后从 Vault (或 Vault Token 账户) 转给接收方
+pub(crate) fn do_refund(
+    request: &Account<'_, MintRequest>,
+    trigger: RefundTrigger,
+    accounts: RefundAccounts<'_, '_>,
+) -> Result<RefundOutcome> {
+    let (refund_amount, vrf_fee_deducted) = refund_terms(
+        trigger,
+        request.payment_mode,
+        request.paid_amount,
+        request.vrf_fee_lamports,
+    );
+
+    transfer_refund(
+        refund_payment_mode(request, accounts.vault.key),
+        request.payment_mint,
+        accounts
+            .source_override
+            .unwrap_or(request.vault_token_account),
+        refund_amount,
+        &accounts,
+    )?;
+
+    Ok(RefundOutcome {
+        refund_amount,
+        vrf_fee_deducted,
+        refund_usd: refunded_usd(request.paid_usd, request.paid_amount, refund_amount),
+    })
+}
+
+/// 记录统一的退款完成事件
+pub(crate) fn emit_refund_completed(
+    request: &Account<'_, MintRequest>,
+    trigger: RefundTrigger,
+    outcome: &RefundOutcome,
+    cranker: Option<Pubkey>,
+    cranker_tip_lamports: u64,
+    timestamp: i64,
+) {
+    emit!(RefundCompleted {
+        user: request.user,
+        payer: request.payer,
+        mint_request: request.key(),
+        payment_mode: request.payment_mode,
+        trigger,
+        refund_amount: outcome.refund_amount,
+        vrf_fee_deducted: outcome.vrf_fee_deducted,
+        cranker,
+        cranker_tip_lamports,
+        timestamp,
+        memo: request.memo,
+    });
+}
+
+/// 按触发来源计算 (退款金额, 扣除的 VRF 费用)
+///
+/// 退款按实际支付的 raw amount 返还，不经过美元换算；VRF 费用仅从 SOL 计价的本金中扣除
+pub(crate) fn refund_terms(
+    trigger: RefundTrigger,
+    payment_mode: PaymentMode,
+    paid_amount: u64,
+    vrf_fee_lamports: u64,
+) -> (u64, u64) {
+    let vrf_fee_deducted = if trigger.deducts_vrf_fee() && payment_mode.is_sol_denominated() {
+        vrf_fee_lamports.min(paid_amount)
+    } else {
+        0
+    };
+    (refundable_amount(paid_amount, vrf_fee_deducted), vrf_fee_deducted)
+}
+
+/// 扣除 VRF 费用后的退款金额 (不低于 0)
+pub(crate) fn refundable_amount(paid_amount: u64, vrf_fee: u64) -> u64 {
+    paid_amount.saturating_sub(vrf_fee)
+}
+
+/// 退款对应的美元价值: paid_usd * refund_amount / paid_amount
+pub(crate) fn refunded_usd(paid_usd: u64, paid_amount: u64, refund_amount: u64) -> u64 {
+    if paid_amount == 0 {
+        return 0;
+    }
+    ((paid_usd as u128) * (refund_amount as u128) / (paid_amount as u128)) as u64
+}
+
+/// 退款实际采用的支付方式
+///
+/// request_mint 将 WSOL 支付解包为 Vault lamports，并记录 Vault 自身为收款账户：此类请求按 SOL
+/// 从 Vault 退还。升级前未解包的 WSOL 请求 (收款账户为 Vault WSOL 账户) 仍按 Token 退款
+pub(crate) fn refund_payment_mode(request: &MintRequest, vault: &Pubkey) -> PaymentMode {
+    if request.payment_mode == PaymentMode::WSOL && request.vault_token_account == *vault {
+        PaymentMode::SOL
+    } else {
+        request.payment_mode
+    }
+}
+
+/// 退款来源须为支付时收款的 Vault Token 账户；请求未记录收款账户 (升级前创建) 时不限制
+pub(crate) fn check_refund_source(recorded: Pubkey, provided: Pubkey) -> Result<()> {
+    require!(
+        recorded == Pubkey::default() || recorded == provided,
+        IPFlowError::RefundSourceMismatch
+    );
+    Ok(())
+}
+
+/// 将本金从 Vault 退还给接收方
+///
+/// - SOL 退款: Vault → 接收方 (System Program transfer)
+/// - USDT / WSOL 退款: VaultTokenAccount → 接收方 Token 账户 (SPL Token transfer)，需传入 Token 账户，
+///   两个 Token 账户及传入的 Mint 账户均须为请求记录的 payment_mint (transfer_checked 需要 Mint 精度)，
+///   Vault Token 账户须为 expected_vault_token_account (默认值表示升级前的请求不校验)
+fn transfer_refund(
+    payment_mode: PaymentMode,
+    payment_mint: Pubkey,
+    expected_vault_token_account: Pubkey,
+    refund_amount: u64,
+    accounts: &RefundAccounts<'_, '_>,
+) -> Result<()> {
+    let vault = accounts.vault;
+    let recipient = accounts.recipient;
+
+    // Vault PDA 签名
+    let seeds = &[b"vault".as_ref(), &[accounts.vault_bump]];
+    let signer = &[&seeds[..]];
+
+    match payment_mode {
+        PaymentMode::SOL => {
+            // SOL 退款: Vault → 接收方
+            // Vault 余额检查：保留最小租金
+            require!(
+                vault_available_lamports(vault)? >= refund_amount,
+                IPFlowError::InsufficientVaultBalance
+            );
+
+            anchor_lang::solana_program::program::invoke_signed(
+                &anchor_lang::solana_program::system_instruction::transfer(
+                    vault.key,
+                    recipient.key,
+                    refund_amount,
+                ),
+                &[
+                    vault.to_account_info(),
+                    recipient.to_account_info(),
+                    accounts.system_program.to_account_info(),
+                ],
+                signer,
+            )?;
+
+            msg!("SOL refund completed: {} lamports", refund_amount);
+        }
+        PaymentMode::USDT | PaymentMode::WSOL => {
+            // USDT / WSOL 退款: VaultTokenAccount → 接收方 Token 账户 (WSOL 的 Mint 为 NATIVE_SOL_MINT)
+            // 校验必需的 Token 账户存在
+            let token_program = accounts.token_program.ok_or(IPFlowError::RefundNotAllowed)?;
+            let mint = accounts.mint.ok_or(IPFlowError::RefundNotAllowed)?;
+            let vault_token_account = accounts
+                .vault_token_account
+                .ok_or(IPFlowError::RefundNotAllowed)?;
+            let recipient_token_account = accounts
+                .recipient_token_account
+                .ok_or(IPFlowError::RefundNotAllowed)?;
+
+            // 校验 Token 账户余额
+            require!(
+                vault_token_account.amount >= refund_amount,
+                IPFlowError::InsufficientVaultBalance
+            );
+
+            // 校验接收方 Token 账户 owner
+            require!(
+                recipient_token_account.owner == recipient.key(),
+                IPFlowError::Unauthorized
+            );
+            require!(
+                recipient_token_account.mint == payment_mint,
+                IPFlowError::InvalidTokenAccount
+            );
+
+            // Vault Token 账户 owner 已由 context 约束，此处校验收款账户与 Mint
+            check_refund_source(expected_vault_token_account, vault_token_account.key())?;
+            require!(
+                vault_token_account.mint == payment_mint,
+                IPFlowError::InvalidTokenAccount
+            );
+            require!(mint.key() == payment_mint, IPFlowError::InvalidUsdtMint);
+
+            // SPL Token 转账 (transfer_checked 校验 Mint 与精度)
+            token::transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    token::TransferChecked {
+                        from: vault_token_account.to_account_info(),
+                        mint: mint.to_account_info(),
+                        to: recipient_token_account.to_account_info(),
+                        authority: vault.to_account_info(),
+                    },
+                    signer,
+                ),
+                refund_amount,
+                mint.decimals,
+            )?;
+
+            msg!(
+                "Token refund completed: {} (raw, mint={})",
+                refund_amount,
+                payment_mint
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_TRIGGERS: [RefundTrigger; 5] = [
+        RefundTrigger::Timeout,
+        RefundTrigger::Cancel,
+        RefundTrigger::Expired,
+        RefundTrigger::Failed,
+        RefundTrigger::Crank,
+    ];
+    const ALL_PAYMENT_MODES: [PaymentMode; 3] =
+        [PaymentMode::SOL, PaymentMode::USDT, PaymentMode::WSOL];
+
+    #[test]
+    fn test_refund_terms_matrix() {
+        let paid_amount = 100_000_000;
+        let vrf_fee = 5_000_000;
+
+        for trigger in ALL_TRIGGERS {
+            for payment_mode in ALL_PAYMENT_MODES {
+                let expected_fee = match (trigger, payment_mode) {
+                    (RefundTrigger::Failed | RefundTrigger::Crank, _) => 0,
+                    (_, PaymentMode::USDT) => 0,
+                    (
+                        RefundTrigger::Timeout | RefundTrigger::Cancel | RefundTrigger::Expired,
+                        PaymentMode::SOL | PaymentMode::WSOL,
+                    ) => vrf_fee,
+                };
+                assert_eq!(
+                    refund_terms(trigger, payment_mode, paid_amount, vrf_fee),
+                    (paid_amount - expected_fee, expected_fee),
+                    "trigger={:?}, payment_mode={:?}",
+                    trigger,
+                    payment_mode
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_refund_terms_fee_capped_by_paid_amount() {
+        assert_eq!(
+            refund_terms(RefundTrigger::Timeout, PaymentMode::SOL, 1_000, 5_000),
+            (0, 1_000)
+        );
+        assert_eq!(
+            refund_terms(RefundTrigger::Crank, PaymentMode::SOL, 1_000, 5_000),
+            (1_000, 0)
+        );
+    }
+
+    #[test]
+    fn test_refundable_amount_deducts_vrf_fee() {
+        assert_eq!(refundable_amount(100_000_000, 0), 100_000_000);
+        assert_eq!(refundable_amount(100_000_000, 5_000_000), 95_000_000);
+    }
+
+    #[test]
+    fn test_refunded_usd_is_proportional() {
+        assert_eq!(refunded_usd(10_000_000, 50_000_000, 50_000_000), 10_000_000);
+        assert_eq!(refunded_usd(10_000_000, 50_000_000, 45_000_000), 9_000_000);
+        assert_eq!(refunded_usd(10_000_000, 0, 0), 0);
+    }
+
+    #[test]
+    fn test_refundable_amount_never_below_zero() {
+        assert_eq!(refundable_amount(1_000, 5_000), 0);
+    }
+
+    #[test]
+    fn test_legacy_request_accepts_any_vault_account() {
+        assert!(check_refund_source(Pubkey::default(), Pubkey::new_unique()).is_ok());
+    }
+
+    #[test]
+    fn test_unwrapped_wsol_payment_refunds_from_vault_lamports() {
+        let vault = Pubkey::new_unique();
+        let mut request = MintRequest::zeroed();
+        request.payment_mode = PaymentMode::WSOL;
+
+        // 解包后的 WSOL 支付: 收款账户记录为 Vault 自身
+        request.vault_token_account = vault;
+        assert_eq!(refund_payment_mode(&request, &vault), PaymentMode::SOL);
+
+        // 升级前留在 Vault WSOL 账户中的支付仍从该账户按 Token 退还
+        request.vault_token_account = Pubkey::new_unique();
+        assert_eq!(refund_payment_mode(&request, &vault), PaymentMode::WSOL);
+        request.vault_token_account = Pubkey::default();
+        assert_eq!(refund_payment_mode(&request, &vault), PaymentMode::WSOL);
+
+        // 其他支付方式不受影响
+        request.payment_mode = PaymentMode::USDT;
+        request.vault_token_account = vault;
+        assert_eq!(refund_payment_mode(&request, &vault), PaymentMode::USDT);
+    }
+}
@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
+#[cfg(not(feature = "test-hooks"))]
 use anchor_lang::solana_program::program::invoke_signed;
 use anchor_lang::system_program::{transfer, Transfer};
-use anchor_spl::token::{transfer as token_transfer, Transfer as TokenTransfer};
+use anchor_spl::token::{transfer_checked, TransferChecked};
+#[cfg(not(feature = "test-hooks"))]
 use ephemeral_vrf_sdk::consts::IDENTITY;
 use ephemeral_vrf_sdk::instructions::{create_request_randomness_ix, RequestRandomnessParams};
 use ephemeral_vrf_sdk::types::SerializableAccountMeta;
@@ -9,8 +11,13 @@ use solana_program::hash::hash;
 
 use crate::constants::*;
 use crate::errors::IPFlowError;
+use crate::events::{MintRejected, MintRequested};
+use crate::instructions::user::epoch::require_epoch_stats;
+use crate::instructions::user::rewards::accrue_reward;
 use crate::state::*;
-use crate::utils::pyth_oracle;
+use crate::utils::{
+    pyth_oracle, request_commitment, vault_available_lamports, wsol_helper, RevealProgress,
+};
 use crate::RequestMint;
 
 /// Request Mint Handler - MagicBlock VRF 版本
@@ -18,7 +25,7 @@ use crate::RequestMint;
 /// 用户发起抽奖请求的处理逻辑:
 /// 1. 验证卡片数量
 /// 2. 验证 request_slot 是否为当前 slot
-/// 3. 处理支付 (SOL 或 USDT)
+/// 3. 处理支付 (SOL、WSOL 或 USDT)
 /// 4. 初始化 MintRequest 状态
 /// 5. VRF 请求由前端单独发起 (简化版实现)
 ///
@@ -29,11 +36,38 @@ pub fn handler(
     payment_mode: PaymentMode,
     client_seed: u8, // VRF 客户端随机种子
     request_slot: u64, // 前端传入的请求 slot
+    beneficiary: Option<Pubkey>, // 代付时的奖金受益人
+    memo: Option<[u8; 32]>, // 合作方标签，原样写入请求与事件
 ) -> Result<()> {
-    // 1. 基础校验
+    // 0. 同一受益人同一 slot 的重复请求: PDA 已存在且已写入 user
+    check_new_request(&ctx.accounts.mint_request)?;
+
+    // 0.1 Vault 储备下限 (暂停校验由 context 约束完成)：低于下限时停止售卡，避免继续扩大缺口
+    let vault_lamports = vault_available_lamports(&ctx.accounts.vault)?;
+    if ctx.accounts.config.is_below_vault_reserve(vault_lamports) {
+        let min_vault_reserve_lamports = ctx.accounts.config.min_vault_reserve_lamports;
+        msg!(
+            "Mint rejected: vault balance {} below reserve {} lamports",
+            vault_lamports,
+            min_vault_reserve_lamports
+        );
+        emit!(MintRejected {
+            user: ctx.accounts.user.key(),
+            amount_of_cards,
+            vault_lamports,
+            min_vault_reserve_lamports,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        return err!(IPFlowError::VaultBelowReserve);
+    }
+
+    // 1. 基础校验 (卡数上下限由管理员配置)
+    ctx.accounts.config.check_card_amount(amount_of_cards)?;
+
+    // 1.1 支付方式开关
     require!(
-        amount_of_cards > 0 && amount_of_cards <= 100,
-        IPFlowError::InvalidCardAmount
+        ctx.accounts.config.is_payment_mode_enabled(payment_mode),
+        IPFlowError::PaymentModeDisabled
     );
 
     // 2. 验证 request_slot 是否在当前 slot 的合理范围内 (允许 10 slot 的容差)
@@ -44,25 +78,47 @@ pub fn handler(
         IPFlowError::InvalidSlot
     );
 
-    // 2.1 验证 Oracle Queue 是否为白名单
+    // 2.1 验证 Oracle Queue 是否在白名单中 (主/备用任一)
     require!(
-        ctx.accounts.oracle_queue.key() == ctx.accounts.config.oracle_queue,
+        ctx.accounts
+            .config
+            .is_oracle_queue_whitelisted(&ctx.accounts.oracle_queue.key()),
         IPFlowError::InvalidOracleQueue
     );
 
     // 3. 根据支付方式执行不同的支付逻辑
+    let total_micro_usd = (amount_of_cards as u64)
+        .checked_mul(TICKET_PRICE_MICRO_USD)
+        .ok_or(IPFlowError::MathOverflow)?;
     let paid_amount: u64;
+    let mut payment_mint = Pubkey::default();
+    // 稳定币支付的收款账户，退款时只能从该账户转出 (WSOL 支付解包后记录 Vault 自身)
+    let mut paid_to_token_account = Pubkey::default();
+    // SOL 支付换算所用的 Pyth 价格快照 (稳定币支付不使用价格，记为 0)
+    let mut payment_price: i64 = 0;
+    let mut payment_price_expo: i32 = 0;
 
     match payment_mode {
         PaymentMode::SOL => {
             // ==================== SOL 支付路径 ====================
-            // 1. 价格校验与换算 (10U/张)
-            let total_usd = (amount_of_cards as u64)
-                .checked_mul(TARGET_USD_AMOUNT)
-                .ok_or(IPFlowError::MathOverflow)?;
+            // 1. 价格校验与换算 (10U/张)，支付侧向上取整，用户不会少付
+            let total_lamports = pyth_oracle::get_lamports_ceil(
+                &ctx.accounts.pyth_price_update,
+                total_micro_usd,
+                ctx.accounts.config.max_price_age_seconds,
+            )?;
 
-            let total_lamports =
-                pyth_oracle::get_lamports_for_usd(&ctx.accounts.pyth_price_update, total_usd)?;
+            // 1.1 余额预检: 支付后须保留免租金额，用户自付 VRF 费用时一并预留
+            //     (否则 System 转账失败只返回难以理解的原始错误码)
+            let vrf_fee_buffer = if ctx.accounts.config.vault_pays_vrf_fee {
+                0
+            } else {
+                ctx.accounts.config.vrf_fee_lamports
+            };
+            let fee_buffer = Rent::get()?
+                .minimum_balance(0)
+                .saturating_add(vrf_fee_buffer);
+            check_sol_for_payment(ctx.accounts.user.lamports(), total_lamports, fee_buffer)?;
 
             // 2. 执行支付 (User -> Vault)
             transfer(
@@ -76,8 +132,10 @@ pub fn handler(
                 total_lamports,
             )?;
 
-            // 记录支付金额 (lamports)
+            // 记录支付金额 (lamports) 与换算价格 (已通过 get_lamports_ceil 校验)
             paid_amount = total_lamports;
+            payment_price = ctx.accounts.pyth_price_update.price_message.price;
+            payment_price_expo = ctx.accounts.pyth_price_update.price_message.exponent;
 
             msg!(
                 "SOL Payment: {} lamports for {} cards",
@@ -109,11 +167,14 @@ pub fn handler(
                 .as_ref()
                 .ok_or(IPFlowError::MissingUsdtAccounts)?;
 
-            // 2. 运行时校验 USDT Mint 地址
-            require!(
-                usdt_mint.key() == USDT_MINT_DEVNET,
-                IPFlowError::InvalidUsdtMint
-            );
+            // 2. 在支付 Mint 白名单中查找传入的 Mint
+            let mint_info = *ctx
+                .accounts
+                .config
+                .find_payment_mint(&usdt_mint.key())
+                .ok_or(IPFlowError::PaymentMintNotWhitelisted)?;
+            require!(mint_info.enabled, IPFlowError::PaymentMintDisabled);
+            payment_mint = mint_info.mint;
 
             // 3. 运行时校验用户 Token 账户
             require!(
@@ -121,17 +182,13 @@ pub fn handler(
                 IPFlowError::InvalidTokenAccount
             );
             require!(
-                user_token_account.mint == USDT_MINT_DEVNET,
+                user_token_account.mint == payment_mint,
                 IPFlowError::InvalidTokenAccount
             );
 
-            // 4. 运行时校验 Vault Token 账户
-            require!(
-                vault_token_account.mint == USDT_MINT_DEVNET,
-                IPFlowError::InvalidTokenAccount
-            );
+            // 4. Vault Token 账户 owner 已由 context 约束，此处校验 Mint
             require!(
-                vault_token_account.owner == ctx.accounts.vault.key(),
+                vault_token_account.mint == payment_mint,
                 IPFlowError::InvalidTokenAccount
             );
             require!(
@@ -139,43 +196,170 @@ pub fn handler(
                 IPFlowError::InvalidTokenAccount
             );
 
-            // 5. 计算 USDT 金额 (10U/张, USDT 精度 6 位)
-            let total_usdt = (amount_of_cards as u64)
-                .checked_mul(TARGET_USD_AMOUNT)
-                .ok_or(IPFlowError::MathOverflow)?
-                .checked_mul(10u64.pow(USDT_DECIMALS))
-                .ok_or(IPFlowError::MathOverflow)?;
+            // 5. 按 Mint 账户精度将 micro-USD 换算为 raw amount
+            let total_usdt = micro_usd_to_token_amount(total_micro_usd, usdt_mint.decimals)?;
 
-            // 6. 执行 USDT 转账 (User -> Vault)
-            token_transfer(
+            // 5.1 余额预检: 余额不足时返回明确错误，而非 SPL Token 的原始 0x1
+            check_token_for_payment(user_token_account.amount, total_usdt)?;
+
+            // 6. 执行稳定币转账 (User -> Vault)，transfer_checked 校验 Mint 与精度
+            let vault_balance_before = vault_token_account.amount;
+            transfer_checked(
                 CpiContext::new(
                     token_program.to_account_info(),
-                    TokenTransfer {
+                    TransferChecked {
                         from: user_token_account.to_account_info(),
+                        mint: usdt_mint.to_account_info(),
                         to: vault_token_account.to_account_info(),
                         authority: ctx.accounts.user.to_account_info(),
                     },
                 ),
                 total_usdt,
+                usdt_mint.decimals,
             )?;
 
-            // 记录支付金额 (USDT raw amount, 6 decimals)
-            paid_amount = total_usdt;
+            // 记录 Vault 实际到账金额 (raw amount，含转账手续费的 Mint 会少于 total_usdt)
+            paid_to_token_account = vault_token_account.key();
+            let vault_token_account = ctx
+                .accounts
+                .vault_token_account
+                .as_mut()
+                .ok_or(IPFlowError::MissingUsdtAccounts)?;
+            vault_token_account.reload()?;
+            paid_amount = token_amount_received(vault_balance_before, vault_token_account.amount)?;
 
             msg!(
-                "USDT Payment: {} USDT (raw) for {} cards",
+                "Stable Payment: {} (raw, mint={}, received={}) for {} cards",
                 total_usdt,
+                payment_mint,
+                paid_amount,
+                amount_of_cards
+            );
+        }
+        PaymentMode::WSOL => {
+            // ==================== WSOL 支付路径 ====================
+            // 复用稳定币支付的可选 Token 账户: usdt_mint 传 WSOL Mint，user_token_account 为用户的 WSOL 账户
+            // 收到的 WSOL 经临时账户立即解包为 Vault lamports: 留在 Vault WSOL 账户中会被 swap 路由消耗，
+            // 且不计入健康度 / 偿付 / 储备校验
+            let token_program = ctx
+                .accounts
+                .token_program
+                .as_ref()
+                .ok_or(IPFlowError::MissingWsolAccounts)?;
+            let wsol_mint = ctx
+                .accounts
+                .usdt_mint
+                .as_ref()
+                .ok_or(IPFlowError::MissingWsolAccounts)?;
+            let user_token_account = ctx
+                .accounts
+                .user_token_account
+                .as_ref()
+                .ok_or(IPFlowError::MissingWsolAccounts)?;
+            let wsol_payment_account = ctx
+                .accounts
+                .wsol_payment_account
+                .as_ref()
+                .ok_or(IPFlowError::MissingWsolAccounts)?;
+            let wsol_payment_bump = ctx
+                .bumps
+                .wsol_payment_account
+                .ok_or(IPFlowError::MissingWsolAccounts)?;
+
+            // 1. Mint 与 Token 账户校验 (临时账户地址已由 context 种子约束)
+            require!(
+                wsol_mint.key() == NATIVE_SOL_MINT,
+                IPFlowError::InvalidWsolMint
+            );
+            require!(
+                user_token_account.owner == ctx.accounts.user.key()
+                    && user_token_account.mint == NATIVE_SOL_MINT,
+                IPFlowError::InvalidTokenAccount
+            );
+            require!(
+                wsol_payment_account.data_is_empty(),
+                IPFlowError::InvalidTokenAccount
+            );
+            payment_mint = NATIVE_SOL_MINT;
+
+            // 2. 与 SOL 支付相同的 Pyth 换算 (向上取整，WSOL raw amount 即 lamports)
+            let total_lamports = pyth_oracle::get_lamports_ceil(
+                &ctx.accounts.pyth_price_update,
+                total_micro_usd,
+                ctx.accounts.config.max_price_age_seconds,
+            )?;
+            check_token_for_payment(user_token_account.amount, total_lamports)?;
+
+            // 3. 用户 WSOL → 临时账户 → 解包到 Vault (Vault lamports 净增加 total_lamports)
+            let mint_request_key = ctx.accounts.mint_request.key();
+            let temp_seeds: &[&[u8]] = &[
+                SEED_WSOL_PAYMENT,
+                mint_request_key.as_ref(),
+                &[wsol_payment_bump],
+            ];
+            let vault_seeds: &[&[u8]] = &[b"vault".as_ref(), &[ctx.accounts.config.vault_bump]];
+            wsol_helper::unwrap_wsol_payment(
+                &ctx.accounts.user.to_account_info(),
+                &user_token_account.to_account_info(),
+                &wsol_payment_account.to_account_info(),
+                &wsol_mint.to_account_info(),
+                &ctx.accounts.vault.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &token_program.to_account_info(),
+                total_lamports,
+                temp_seeds,
+                vault_seeds,
+            )?;
+
+            paid_amount = total_lamports;
+            // 本金已是 Vault lamports: 记录 Vault 自身为收款账户，退款按 SOL 从 Vault 转出
+            paid_to_token_account = ctx.accounts.vault.key();
+            payment_price = ctx.accounts.pyth_price_update.price_message.price;
+            payment_price_expo = ctx.accounts.pyth_price_update.price_message.exponent;
+
+            msg!(
+                "WSOL Payment: {} lamports (unwrapped) for {} cards",
+                total_lamports,
                 amount_of_cards
             );
         }
     }
 
+    // 3.1 累计当前 epoch 收入与售卡数
+    if let Some(stats) = require_epoch_stats(
+        &ctx.accounts.config,
+        ctx.accounts.epoch_stats.as_mut(),
+    )? {
+        stats.record_mint(total_micro_usd, amount_of_cards)?;
+    }
+
+    // 3.2 推荐返佣: 按 paid_usd 比例累积到推荐人奖励余额 (不可自荐)
+    let referral_bps = ctx.accounts.config.referral_bps;
+    if let Some(referrer_rewards) = ctx.accounts.referrer_rewards.as_mut() {
+        let requester = beneficiary.unwrap_or(ctx.accounts.user.key());
+        require!(
+            referrer_rewards.user != ctx.accounts.user.key() && referrer_rewards.user != requester,
+            IPFlowError::SelfReferral
+        );
+        accrue_reward(
+            referrer_rewards,
+            RewardSource::Referral,
+            referral_reward_usd(total_micro_usd, referral_bps),
+        )?;
+    }
+
     // 4. 获取 mint_request PDA key (在可变借用之前)
     let mint_request_key = ctx.accounts.mint_request.key();
 
+    // VRF caller_seed: 记录在 MintRequest 上，供审计重建 种子 → VRF 请求 → 随机数 → 奖金 链路
+    let caller_seed_hash = hash(&[client_seed]).to_bytes();
+    // 可证明公平承诺: 绑定种子、请求 slot 与请求 PDA，揭示时与 hash(randomness) 一同发出
+    let commitment = request_commitment(client_seed, request_slot, &mint_request_key);
+
     // 5. 初始化 MintRequest 状态
     let mint_request = &mut ctx.accounts.mint_request;
-    mint_request.user = ctx.accounts.user.key();
+    mint_request.user = beneficiary.unwrap_or(ctx.accounts.user.key());
+    mint_request.payer = ctx.accounts.user.key();
     mint_request.randomness_account = Pubkey::default(); // MagicBlock VRF 不需要此字段
     mint_request.amount_of_cards = amount_of_cards;
     mint_request.status = RequestStatus::Pending;
@@ -188,11 +372,69 @@ pub fn handler(
     mint_request.commit_slot = request_slot; // 使用 request_slot 作为 commit slot
     mint_request.reveal_slot = 0;
     mint_request.vrf_request_slot = request_slot;
+    mint_request.token_won_usd = 0;
+    mint_request.caller_seed_hash = caller_seed_hash;
+    mint_request.commitment = commitment;
+    // 快照选池输入及候选池的 Tier 覆盖表与奖金上限，揭示结果不受请求与回调之间管理员修改奖品池的影响；
+    // 候选池计入待揭示引用，揭示完成或退款前不能被删除
+    mint_request.snapshot_pools(&ctx.accounts.config, mint_request.created_at);
+    ctx.accounts
+        .config
+        .add_pool_pending_reveals(mint_request.snapshot_pool_indices())?;
+    mint_request.vrf_identity = ctx.accounts.config.vrf_identity_key(); // 回调按创建时的身份校验
+    mint_request.claim_deadline = 0; // 揭示时写入
+    mint_request.failure_reason = FAILURE_NONE;
+    mint_request.needs_review = false;
+    mint_request.oracle_queue = ctx.accounts.oracle_queue.key();
+    mint_request.payment_mint = payment_mint;
+    mint_request.vault_token_account = paid_to_token_account;
+    mint_request.paid_usd = total_micro_usd;
+    mint_request.payment_price = payment_price;
+    mint_request.payment_price_expo = payment_price_expo;
+    mint_request.rtp_modulation_bps = BPS_DENOMINATOR as u16; // 揭示时写入实际系数
+    mint_request.randomness = [0u8; 32];
+    mint_request.reveal_pity_threshold = ctx.accounts.config.pity_threshold; // 创建时锁定，受益人无 UserStats 时回调置 0
+    mint_request.reveal_pity_counter = 0;
+    mint_request.cards_minted = 0;
+    mint_request.set_reveal_progress(&RevealProgress::default());
+    mint_request.fee_policy = ctx.accounts.config.fee_policy; // 锁定手续费策略，后续切换只影响新请求
+    mint_request.memo = memo.unwrap_or_default();
+    mint_request.failed_swap_attempts = 0;
+    mint_request.last_swap_failure_at = 0;
+    mint_request.reveal_latency_seconds = 0; // VRF 回调时写入
+    mint_request.set_claim_preauthorization(None); // 揭示后由用户 pre_authorize_claim 写入
+    // 仅 Vault 代付且 SOL / WSOL 支付时记录，用户自付 VRF 费用或稳定币支付时退款不扣除
+    mint_request.vrf_fee_lamports =
+        if ctx.accounts.config.vault_pays_vrf_fee && payment_mode.is_sol_denominated() {
+            ctx.accounts.config.vrf_fee_lamports
+        } else {
+            0
+        };
+
+    // 6. 事件与日志输出
+    emit!(MintRequested {
+        user: mint_request.user,
+        payer: mint_request.payer,
+        mint_request: mint_request_key,
+        amount_of_cards,
+        payment_mode,
+        paid_amount,
+        vrf_request_slot: request_slot,
+        oracle_queue: ctx.accounts.oracle_queue.key(),
+        caller_seed_hash,
+        commitment,
+        price_publish_time: ctx.accounts.pyth_price_update.price_message.publish_time,
+        created_at: mint_request.created_at,
+        paid_usd: total_micro_usd,
+        payment_price,
+        payment_price_expo,
+        memo: mint_request.memo,
+    });
 
-    // 6. 日志输出
     msg!(
-        "MintRequest created: user={}, cards={}, vrf_request_slot={}, mint_request_pda={}",
+        "MintRequest created: user={}, payer={}, cards={}, vrf_request_slot={}, mint_request_pda={}",
         mint_request.user,
+        mint_request.payer,
         amount_of_cards,
         request_slot,
         mint_request_key
@@ -200,15 +442,24 @@ pub fn handler(
 
     // ==================== VRF CPI 调用 ====================
     // 7. 构建 VRF 请求参数
+    // VRF 费用支付方: 默认 Vault (house 承担)，可由管理员切换为用户
+    let vault_pays_vrf_fee = ctx.accounts.config.vault_pays_vrf_fee;
+    let vrf_payer = if vault_pays_vrf_fee {
+        ctx.accounts.vault.to_account_info()
+    } else {
+        ctx.accounts.user.to_account_info()
+    };
+
     let vrf_params = RequestRandomnessParams {
-        payer: ctx.accounts.user.key(),
+        payer: vrf_payer.key(),
         oracle_queue: ctx.accounts.oracle_queue.key(),
         callback_program_id: crate::ID,
         callback_discriminator: crate::instruction::ConsumeLotteryRandomness::DISCRIMINATOR.to_vec(),
-        caller_seed: hash(&[client_seed]).to_bytes(),
+        caller_seed: caller_seed_hash,
         // Phase 4.3: 回调账户列表
         // 顺序必须与 ConsumeLotteryRandomness Context 一致
         // vrf_program_identity 由 VRF 程序自动添加，无需在此指定
+        // 回调不传入全局配置：开奖条款均取自请求创建时的快照，依赖配置的结算由 continue_reveal 完成
         accounts_metas: Some(vec![
             SerializableAccountMeta {
                 pubkey: mint_request_key, // 需要更新状态 (writable)
@@ -216,36 +467,240 @@ pub fn handler(
                 is_writable: true,
             },
             SerializableAccountMeta {
-                pubkey: ctx.accounts.config.key(), // 读取奖品池信息 (readonly)
+                // 受益人 UserStats，读取保底计数器 (readonly)
+                pubkey: Pubkey::find_program_address(
+                    &[SEED_USER_STATS, mint_request.user.as_ref()],
+                    &crate::ID,
+                )
+                .0,
                 is_signer: false,
                 is_writable: false,
             },
+            SerializableAccountMeta {
+                // 请求所用 Oracle Queue 的延迟统计 PDA (writable，未创建时回调跳过)
+                pubkey: Pubkey::find_program_address(
+                    &[SEED_QUEUE_STATS, ctx.accounts.oracle_queue.key().as_ref()],
+                    &crate::ID,
+                )
+                .0,
+                is_signer: false,
+                is_writable: true,
+            },
         ]),
-        callback_args: None,
+        // 回调参数: (amount_of_cards, request_id) 的 borsh 编码 (小端序)，回调时与 MintRequest 交叉校验
+        callback_args: Some(encode_callback_args(amount_of_cards, request_slot)),
     };
 
     msg!("VRF params prepared: oracle_queue={}", ctx.accounts.oracle_queue.key());
 
-    // 8. 创建 VRF 请求指令
-    let vrf_ix = create_request_randomness_ix(vrf_params);
+    // 8. 创建 VRF 请求指令 (SDK 固定使用其常量程序 ID，按配置覆盖 CPI 目标)
+    let vrf_program_id = ctx.accounts.config.vrf_program_id();
+    require_keys_eq!(
+        ctx.accounts.vrf_program.key(),
+        vrf_program_id,
+        IPFlowError::InvalidVrfProgram
+    );
+    let mut vrf_ix = create_request_randomness_ix(vrf_params);
+    vrf_ix.program_id = vrf_program_id;
 
     // 9. 执行 CPI 调用
-    // 使用 program_identity PDA 作为签名者
-    // 注意：回调账户 (mint_request, config) 已通过 accounts_metas 编码在指令数据中
+    // 使用 program_identity PDA 作为签名者；Vault 支付费用时同时提供 Vault PDA 签名
+    // 注意：回调账户 (mint_request, user_stats, queue_stats) 已通过 accounts_metas 编码在指令数据中
     // VRF 程序会在回调时自动附加这些账户，这里只需要传入 VRF 请求所需的 5 个账户
-    invoke_signed(
-        &vrf_ix,
-        &[
-            ctx.accounts.user.to_account_info(),
-            ctx.accounts.program_identity.to_account_info(),
-            ctx.accounts.oracle_queue.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-            ctx.accounts.slot_hashes.to_account_info(),
-        ],
-        &[&[IDENTITY, &[ctx.bumps.program_identity]]],
-    )?;
+    let vault_lamports_before = ctx.accounts.vault.lamports();
+    #[cfg(not(feature = "test-hooks"))]
+    {
+        let vault_bump = ctx.accounts.config.vault_bump;
+        invoke_signed(
+            &vrf_ix,
+            &[
+                vrf_payer,
+                ctx.accounts.program_identity.to_account_info(),
+                ctx.accounts.oracle_queue.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.slot_hashes.to_account_info(),
+            ],
+            &[
+                &[IDENTITY, &[ctx.accounts.config.identity_bump]],
+                &[b"vault", &[vault_bump]],
+            ],
+        )?;
+    }
+    // test-hooks 构建 (localnet 未部署 VRF 程序) 跳过 CPI，由管理员调用 test_reveal 驱动揭示
+    #[cfg(feature = "test-hooks")]
+    {
+        let _ = (vrf_ix, vrf_payer);
+        msg!("test-hooks: VRF CPI skipped, reveal via test_reveal");
+    }
+
+    // 10. 漏斗计数，并记录 Vault 承担的 VRF 费用
+    let config = &mut ctx.accounts.config;
+    config.count_requested = config.count_requested.saturating_add(1);
+    if vault_pays_vrf_fee {
+        let vrf_fee = vault_lamports_before.saturating_sub(ctx.accounts.vault.lamports());
+        config.total_vrf_fees_lamports = config
+            .total_vrf_fees_lamports
+            .checked_add(vrf_fee)
+            .ok_or(IPFlowError::MathOverflow)?;
+        msg!("VRF fee paid by vault: {} lamports", vrf_fee);
+    }
 
     msg!("VRF request sent successfully");
 
     Ok(())
 }
+
+/// micro-USD 换算为稳定币 raw amount: micro_usd * 10^decimals / 10^6
+///
+/// 精度低于 6 位的 Mint 向上取整，保证实付不低于标价
+pub(crate) fn micro_usd_to_token_amount(micro_usd: u64, decimals: u8) -> Result<u64> {
+    let scale = 10u128
+        .checked_pow(decimals as u32)
+        .ok_or(IPFlowError::MathOverflow)?;
+    let numerator = (micro_usd as u128)
+        .checked_mul(scale)
+        .ok_or(IPFlowError::MathOverflow)?;
+    let amount = numerator.div_ceil(USD_PRECISION as u128);
+    u64::try_from(amount).map_err(|_| error!(IPFlowError::MathOverflow))
+}
+
+/// Vault Token 账户转账前后余额差 (实际到账金额)
+pub(crate) fn token_amount_received(balance_before: u64, balance_after: u64) -> Result<u64> {
+    balance_after
+        .checked_sub(balance_before)
+        .ok_or(error!(IPFlowError::MathOverflow))
+}
+
+/// 重复请求校验: init_if_needed 新建的 MintRequest 全为零值，已存在的请求 user 非默认值
+pub(crate) fn check_new_request(mint_request: &MintRequest) -> Result<()> {
+    require!(
+        mint_request.user == Pubkey::default(),
+        IPFlowError::DuplicateRequestInSlot
+    );
+    Ok(())
+}
+
+/// SOL 支付余额预检: 余额须覆盖支付金额与预留 (免租金额 + 用户自付的 VRF 费用)
+pub(crate) fn check_sol_for_payment(
+    balance: u64,
+    total_lamports: u64,
+    fee_buffer: u64,
+) -> Result<()> {
+    let required = total_lamports
+        .checked_add(fee_buffer)
+        .ok_or(IPFlowError::MathOverflow)?;
+    require!(balance >= required, IPFlowError::InsufficientSolForPayment);
+    Ok(())
+}
+
+/// 稳定币支付余额预检
+pub(crate) fn check_token_for_payment(balance: u64, total_amount: u64) -> Result<()> {
+    require!(
+        balance >= total_amount,
+        IPFlowError::InsufficientPaymentBalance
+    );
+    Ok(())
+}
+
+/// 编码 VRF 回调参数，与 consume_lottery_randomness 的 (amount_of_cards: u32, request_id: u64) 参数顺序一致
+pub(crate) fn encode_callback_args(amount_of_cards: u32, request_id: u64) -> Vec<u8> {
+    let mut args = Vec::with_capacity(12);
+    args.extend_from_slice(&amount_of_cards.to_le_bytes());
+    args.extend_from_slice(&request_id.to_le_bytes());
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_args_match_borsh_encoding() {
+        let args = encode_callback_args(10, 123_456);
+        assert_eq!(args, (10u32, 123_456u64).try_to_vec().unwrap());
+    }
+
+    #[test]
+    fn test_token_amount_six_decimals() {
+        // 10U -> 10_000_000 (USDT/USDC)
+        assert_eq!(
+            micro_usd_to_token_amount(TICKET_PRICE_MICRO_USD, 6).unwrap(),
+            10_000_000
+        );
+        assert_eq!(micro_usd_to_token_amount(1, 6).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_token_amount_eight_and_nine_decimals() {
+        assert_eq!(
+            micro_usd_to_token_amount(TICKET_PRICE_MICRO_USD, 8).unwrap(),
+            1_000_000_000
+        );
+        assert_eq!(
+            micro_usd_to_token_amount(TICKET_PRICE_MICRO_USD, 9).unwrap(),
+            10_000_000_000
+        );
+        // 小数定价: 2.5U
+        assert_eq!(micro_usd_to_token_amount(2_500_000, 9).unwrap(), 2_500_000_000);
+    }
+
+    #[test]
+    fn test_token_amount_rounds_up_for_low_decimals() {
+        // 2 位精度: 0.000001U -> 0.01 (向上取整)
+        assert_eq!(micro_usd_to_token_amount(1, 2).unwrap(), 1);
+        assert_eq!(micro_usd_to_token_amount(10_000, 2).unwrap(), 1);
+        assert_eq!(micro_usd_to_token_amount(10_001, 2).unwrap(), 2);
+        assert_eq!(micro_usd_to_token_amount(0, 2).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_token_amount_received_is_post_fee_delta() {
+        // 无手续费：到账等于转账金额
+        assert_eq!(token_amount_received(5_000, 10_005_000).unwrap(), 10_000_000);
+        // 1% 转账手续费：到账 9_900_000
+        assert_eq!(token_amount_received(5_000, 9_905_000).unwrap(), 9_900_000);
+        assert!(token_amount_received(10, 5).is_err());
+    }
+
+    #[test]
+    fn test_second_mint_in_same_slot_rejected() {
+        // 第一笔: init_if_needed 新建的账户为零值，可继续
+        let mut request = MintRequest::zeroed();
+        assert!(check_new_request(&request).is_ok());
+
+        // 第一笔写入 user 后，同 slot 的第二笔得到确定的 DuplicateRequestInSlot
+        request.user = Pubkey::new_unique();
+        assert_eq!(
+            check_new_request(&request).unwrap_err(),
+            error!(IPFlowError::DuplicateRequestInSlot)
+        );
+    }
+
+    #[test]
+    fn test_sol_balance_precheck() {
+        assert!(check_sol_for_payment(1_000_000, 900_000, 100_000).is_ok());
+        assert_eq!(
+            check_sol_for_payment(999_999, 900_000, 100_000).unwrap_err(),
+            error!(IPFlowError::InsufficientSolForPayment)
+        );
+        assert_eq!(
+            check_sol_for_payment(u64::MAX, u64::MAX, 1).unwrap_err(),
+            error!(IPFlowError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_token_balance_precheck() {
+        let total = micro_usd_to_token_amount(TICKET_PRICE_MICRO_USD, 6).unwrap();
+        assert!(check_token_for_payment(total, total).is_ok());
+        assert_eq!(
+            check_token_for_payment(total - 1, total).unwrap_err(),
+            error!(IPFlowError::InsufficientPaymentBalance)
+        );
+    }
+
+    #[test]
+    fn test_token_amount_overflow() {
+        assert!(micro_usd_to_token_amount(u64::MAX, 18).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+// ==================== 用户奖励余额指令 ====================
+//
+// 推荐返佣、SOL 零头等以 micro-USD 累积到 RewardsAccount，
+// 用户达到最小领取额后通过 claim_rewards 一次性按 Pyth 价格换算为 SOL 从 Vault 领取
+
+use anchor_lang::prelude::*;
+
+use crate::errors::IPFlowError;
+use crate::events::{RewardsAccrued, RewardsClaimed};
+use crate::state::{RewardSource, RewardsAccount};
+use crate::utils::{pyth_oracle, vault_available_lamports};
+use crate::{ClaimRewards, InitRewardsAccount};
+
+/// 初始化用户奖励余额 PDA (租金由用户支付)
+pub fn init_rewards_account(ctx: Context<InitRewardsAccount>) -> Result<()> {
+    let rewards = &mut ctx.accounts.rewards_account;
+    rewards.user = ctx.accounts.user.key();
+    rewards.accrued_usd = 0;
+    rewards.total_claimed_usd = 0;
+    rewards.bump = ctx.bumps.rewards_account;
+
+    msg!("RewardsAccount initialized: user={}", rewards.user);
+    Ok(())
+}
+
+/// 程序内部流程累积奖励 (request_mint 推荐返佣、claim 零头)
+pub(crate) fn accrue_reward(
+    rewards: &mut RewardsAccount,
+    source: RewardSource,
+    amount_usd: u64,
+) -> Result<()> {
+    if amount_usd == 0 {
+        return Ok(());
+    }
+    let accrued_usd = rewards.credit(amount_usd)?;
+
+    emit!(RewardsAccrued {
+        user: rewards.user,
+        source,
+        amount_usd,
+        accrued_usd,
+    });
+    Ok(())
+}
+
+/// 领取全部待领取奖励 (按 Pyth 价格换算为 lamports，Vault 签名转账)
+pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let rewards = &mut ctx.accounts.rewards_account;
+
+    require!(
+        rewards.accrued_usd > 0 && rewards.accrued_usd >= config.min_rewards_claim_usd,
+        IPFlowError::RewardsBelowMinimum
+    );
+
+    let lamports = pyth_oracle::get_lamports_floor(
+        &ctx.accounts.pyth_price_update,
+        rewards.accrued_usd,
+        config.max_price_age_seconds,
+    )?;
+    require!(
+        lamports <= vault_available_lamports(&ctx.accounts.vault)?,
+        IPFlowError::InsufficientVaultBalance
+    );
+
+    let amount_usd = rewards.take_accrued()?;
+
+    // Vault PDA 签名转账
+    let seeds = &[b"vault".as_ref(), &[config.vault_bump]];
+    let signer = &[&seeds[..]];
+    anchor_lang::system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.user.to_account_info(),
+            },
+            signer,
+        ),
+        lamports,
+    )?;
+
+    emit!(RewardsClaimed {
+        user: rewards.user,
+        amount_usd,
+        lamports,
+    });
+
+    msg!(
+        "Rewards claimed: user={}, {} micro-USD -> {} lamports",
+        rewards.user,
+        amount_usd,
+        lamports
+    );
+    Ok(())
+}
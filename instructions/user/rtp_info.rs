@@ -0,0 +1,74 @@
+// ==================== RTP 查询 (只读) ====================
+//
+// 玩家与监管方通过 simulateTransaction 调用，读取理论 RTP (按当前 Tier 阈值表的单卡期望奖金 / 票价)
+// 与实际 RTP (累计发放 / 累计票款)。结果以 borsh 写入 return data，不发出事件。
+//
+// 理论值: 揭示时在活跃池中等概率选池，每个池按自身的 Tier 覆盖表开奖，因此取活跃池期望的平均值；
+// 无活跃池时使用默认表。理论值不含保底、RTP 动态调节与发放比例。
+
+use anchor_lang::prelude::*;
+
+use crate::constants::{BPS_DENOMINATOR, DEFAULT_TIER_THRESHOLDS, TICKET_PRICE_MICRO_USD};
+use crate::state::IPFlowState;
+use crate::utils::{compute_expected_value, resolve_tier_thresholds, rtp_bps};
+use crate::GetRtpInfo;
+
+/// get_rtp_info 返回值
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct RtpInfo {
+    /// 单张卡票价 (micro-USD)
+    pub ticket_price_usd: u64,
+    /// 单卡理论期望奖金 (micro-USD，活跃池平均)
+    pub expected_value_usd: u64,
+    /// 理论 RTP (bps，期望奖金 / 票价)
+    pub theoretical_rtp_bps: u64,
+    /// 按 SOL 发放比例折算后的理论 RTP (bps)
+    pub theoretical_sol_rtp_bps: u64,
+    /// 累计已揭示请求的票款 (micro-USD)
+    pub total_collected_usd: u64,
+    /// 累计领取发放的奖金价值 (micro-USD)
+    pub total_paid_out_usd: u64,
+    /// 实际 RTP (bps，累计发放 / 累计票款，尚无票款时为 0)
+    pub realized_rtp_bps: u64,
+    pub timestamp: i64,
+}
+
+pub fn get_rtp_info(ctx: Context<GetRtpInfo>) -> Result<RtpInfo> {
+    let info = rtp_info(&ctx.accounts.config, Clock::get()?.unix_timestamp);
+    msg!(
+        "RTP info returned: theoretical={}bps, realized={}bps",
+        info.theoretical_rtp_bps,
+        info.realized_rtp_bps
+    );
+    Ok(info)
+}
+
+/// 由配置计算 RTP 信息 (纯函数)
+fn rtp_info(config: &IPFlowState, timestamp: i64) -> RtpInfo {
+    let expected_value_usd = active_expected_value(config);
+    let theoretical_rtp_bps = rtp_bps(expected_value_usd, TICKET_PRICE_MICRO_USD);
+    RtpInfo {
+        ticket_price_usd: TICKET_PRICE_MICRO_USD,
+        expected_value_usd,
+        theoretical_rtp_bps,
+        theoretical_sol_rtp_bps: theoretical_rtp_bps * config.sol_payout_bps as u64
+            / BPS_DENOMINATOR,
+        total_collected_usd: config.total_collected,
+        total_paid_out_usd: config.total_paid_out_usd,
+        realized_rtp_bps: rtp_bps(config.total_paid_out_usd, config.total_collected),
+        timestamp,
+    }
+}
+
+/// 活跃池 Tier 阈值表期望奖金的平均值 (无活跃池时为默认表)
+fn active_expected_value(config: &IPFlowState) -> u64 {
+    let count = config.active_pool_count as usize;
+    if count == 0 {
+        return compute_expected_value(&DEFAULT_TIER_THRESHOLDS);
+    }
+    let total: u128 = config.active_pool_tier_overrides[..count]
+        .iter()
+        .map(|overrides| compute_expected_value(&resolve_tier_thresholds(overrides)) as u128)
+        .sum();
+    (total / count as u128) as u64
+}
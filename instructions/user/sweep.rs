@@ -0,0 +1,266 @@
+// ==================== 过期请求批量清理 (sweep) ====================
+//
+// 单笔交易处理最多 MAX_SWEEP_REQUESTS 个过期请求，任何人都可调用:
+// - Pending 超过 PENDING_EXPIRY_SECONDS (30 天): 全额退还 SOL 本金，关闭 PDA (租金退给支付者)
+// - Revealed 超过领取期限: 奖金作废，托管资金退回 Vault，扣减待领取计数与未兑付负债，关闭 PDA
+// 不满足条件的请求 (未过期、待审核、USDT 支付的 Pending) 直接跳过，不使整笔交易失败；
+// USDT Pending 请求需要 Token 账户，仍通过 crank_expire_pending 单独处理。
+//
+// remaining_accounts: 按 [mint_request, payer, escrow] 三个一组传入，payer 须为 mint_request.payer
+// (可写)，escrow 为 [SEED_ESCROW, mint_request] PDA (可写；仅对已托管的请求校验地址)。
+// 返回实际处理的请求数量 (return data)。
+
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_SWEEP_REQUESTS;
+use crate::errors::IPFlowError;
+use crate::events::RevealedRequestExpired;
+use crate::instructions::user::crank::{crank_tip_amount, is_pending_expired};
+use crate::instructions::user::refund_core::{do_refund, emit_refund_completed, RefundAccounts};
+use crate::state::*;
+use crate::utils::{release_request_escrow, vault_available_lamports};
+use crate::SweepExpired;
+
+/// 单个请求的清理动作
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum SweepAction {
+    /// Pending 过期: 退还本金并关闭
+    RefundPending,
+    /// Revealed 超过领取期限: 作废奖金并关闭
+    ExpireRevealed,
+}
+
+pub fn sweep_expired<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SweepExpired<'info>>,
+) -> Result<u8> {
+    let now = Clock::get()?.unix_timestamp;
+    let requests = sweep_request_count(ctx.remaining_accounts.len())?;
+
+    let mut processed: u8 = 0;
+    let mut refunded: u32 = 0;
+
+    for group in ctx
+        .remaining_accounts
+        .chunks_exact(SWEEP_ACCOUNTS_PER_REQUEST)
+        .take(requests)
+    {
+        let (request_info, payer_info, escrow_info) = (&group[0], &group[1], &group[2]);
+        let request = Account::<MintRequest>::try_from(request_info)?;
+        require_keys_eq!(
+            payer_info.key(),
+            request.payer,
+            IPFlowError::InvalidSweepAccounts
+        );
+
+        let Some(action) = sweep_action(&request, now) else {
+            continue;
+        };
+
+        match action {
+            SweepAction::RefundPending => {
+                // 与 crank 一致：过期退款为全额退款，不扣除 VRF 费用
+                let outcome = do_refund(
+                    &request,
+                    RefundTrigger::Crank,
+                    RefundAccounts {
+                        vault: &ctx.accounts.vault,
+                        vault_bump: ctx.accounts.config.vault_bump,
+                        recipient: payer_info,
+                        system_program: &ctx.accounts.system_program,
+                        token_program: None,
+                        mint: None,
+                        vault_token_account: None,
+                        recipient_token_account: None,
+                        source_override: None,
+                    },
+                )?;
+                ctx.accounts
+                    .config
+                    .release_pool_pending_reveals(request.snapshot_pool_indices());
+                refunded += 1;
+
+                emit_refund_completed(
+                    &request,
+                    RefundTrigger::Crank,
+                    &outcome,
+                    Some(ctx.accounts.cranker.key()),
+                    0,
+                    now,
+                );
+            }
+            SweepAction::ExpireRevealed => {
+                release_request_escrow(
+                    &mut ctx.accounts.config,
+                    &request,
+                    &request.key(),
+                    escrow_info,
+                    &ctx.accounts.vault.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                )?;
+
+                let config = &mut ctx.accounts.config;
+                config.release_pool_pending_claim(request.selected_pool_index);
+                config.outstanding_liabilities_usd = config
+                    .outstanding_liabilities_usd
+                    .saturating_sub(request.total_won_usd);
+
+                emit!(RevealedRequestExpired {
+                    user: request.user,
+                    payer: request.payer,
+                    mint_request: request.key(),
+                    total_won_usd: request.total_won_usd,
+                    claim_deadline: request.effective_claim_deadline(),
+                    cranker: ctx.accounts.cranker.key(),
+                    timestamp: now,
+                });
+            }
+        }
+
+        ctx.accounts.config.count_expired = ctx.accounts.config.count_expired.saturating_add(1);
+
+        // 关闭 MintRequest PDA，租金退还给支付者
+        request.close(payer_info.clone())?;
+        processed += 1;
+    }
+
+    // 仅退款请求计小费 (与 crank_expire_pending 相同)，合并为一笔转账
+    let tip = crank_tip_amount(
+        ctx.accounts
+            .config
+            .crank_tip_lamports
+            .saturating_mul(refunded as u64),
+        vault_available_lamports(&ctx.accounts.vault)?,
+    );
+    if tip > 0 {
+        let seeds = &[b"vault".as_ref(), &[ctx.accounts.config.vault_bump]];
+        let signer = &[&seeds[..]];
+
+        anchor_lang::solana_program::program::invoke_signed(
+            &anchor_lang::solana_program::system_instruction::transfer(
+                ctx.accounts.vault.key,
+                ctx.accounts.cranker.key,
+                tip,
+            ),
+            &[
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.cranker.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            signer,
+        )?;
+    }
+
+    msg!(
+        "Sweep completed: processed={} of {}, refunded={}, tip={}",
+        processed,
+        requests,
+        refunded,
+        tip
+    );
+
+    Ok(processed)
+}
+
+/// 每个请求在 remaining_accounts 中占用的账户数: [mint_request, payer, escrow]
+pub(crate) const SWEEP_ACCOUNTS_PER_REQUEST: usize = 3;
+
+/// 校验 remaining_accounts 为 [mint_request, payer, escrow] 三个一组且不超过批量上限，返回请求数量
+pub(crate) fn sweep_request_count(remaining_accounts: usize) -> Result<usize> {
+    require!(
+        remaining_accounts > 0
+            && remaining_accounts % SWEEP_ACCOUNTS_PER_REQUEST == 0
+            && remaining_accounts / SWEEP_ACCOUNTS_PER_REQUEST <= MAX_SWEEP_REQUESTS,
+        IPFlowError::InvalidSweepAccounts
+    );
+    Ok(remaining_accounts / SWEEP_ACCOUNTS_PER_REQUEST)
+}
+
+/// 判断请求的清理动作，不满足条件时返回 None (跳过)
+pub(crate) fn sweep_action(request: &MintRequest, now: i64) -> Option<SweepAction> {
+    match request.status {
+        RequestStatus::Pending
+            if request.payment_mode == PaymentMode::SOL
+                && is_pending_expired(request.created_at, now) =>
+        {
+            Some(SweepAction::RefundPending)
+        }
+        // 待审核的大额中奖由管理员处理
+        RequestStatus::Revealed
+            if !request.needs_review && now >= request.effective_claim_deadline() =>
+        {
+            Some(SweepAction::ExpireRevealed)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{CLAIM_TIMEOUT_SECONDS, PENDING_EXPIRY_SECONDS};
+
+    const CREATED_AT: i64 = 1_700_000_000;
+
+    fn request(status: RequestStatus, payment_mode: PaymentMode) -> MintRequest {
+        let mut request = MintRequest::zeroed();
+        request.status = status;
+        request.payment_mode = payment_mode;
+        request.created_at = CREATED_AT;
+        request.revealed_at = CREATED_AT;
+        request
+    }
+
+    #[test]
+    fn pending_sol_refunded_only_after_expiry() {
+        let pending = request(RequestStatus::Pending, PaymentMode::SOL);
+        assert_eq!(sweep_action(&pending, CREATED_AT + PENDING_EXPIRY_SECONDS), None);
+        assert_eq!(
+            sweep_action(&pending, CREATED_AT + PENDING_EXPIRY_SECONDS + 1),
+            Some(SweepAction::RefundPending)
+        );
+    }
+
+    #[test]
+    fn pending_usdt_is_skipped() {
+        let pending = request(RequestStatus::Pending, PaymentMode::USDT);
+        assert_eq!(sweep_action(&pending, CREATED_AT + PENDING_EXPIRY_SECONDS + 1), None);
+    }
+
+    #[test]
+    fn revealed_expires_at_claim_deadline_including_extension() {
+        let mut revealed = request(RequestStatus::Revealed, PaymentMode::USDT);
+        let deadline = CREATED_AT + CLAIM_TIMEOUT_SECONDS;
+        assert_eq!(sweep_action(&revealed, deadline - 1), None);
+        assert_eq!(sweep_action(&revealed, deadline), Some(SweepAction::ExpireRevealed));
+
+        revealed.claim_deadline = deadline + 3_600;
+        assert_eq!(sweep_action(&revealed, deadline), None);
+
+        revealed.needs_review = true;
+        assert_eq!(sweep_action(&revealed, deadline + 3_600), None);
+    }
+
+    #[test]
+    fn non_terminal_candidates_are_skipped() {
+        let later = CREATED_AT + PENDING_EXPIRY_SECONDS + 1;
+        for status in [RequestStatus::Claiming, RequestStatus::Claimed, RequestStatus::Failed] {
+            assert_eq!(sweep_action(&request(status, PaymentMode::SOL), later), None);
+        }
+    }
+
+    #[test]
+    fn request_count_bounds() {
+        assert_eq!(sweep_request_count(3).unwrap(), 1);
+        assert_eq!(
+            sweep_request_count(MAX_SWEEP_REQUESTS * 3).unwrap(),
+            MAX_SWEEP_REQUESTS
+        );
+        // 旧的 [mint_request, payer] 成对格式被拒绝
+        for invalid in [0, 2, 4, MAX_SWEEP_REQUESTS * 3 + 3] {
+            assert_eq!(
+                sweep_request_count(invalid).unwrap_err(),
+                error!(IPFlowError::InvalidSweepAccounts)
+            );
+        }
+    }
+}
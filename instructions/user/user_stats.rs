@@ -0,0 +1,55 @@
+// ==================== 用户统计账户初始化 ====================
+
+use anchor_lang::prelude::*;
+
+use crate::errors::IPFlowError;
+use crate::state::UserStats;
+use crate::{InitUserStats, MigrateUserStats};
+
+/// 初始化用户统计 PDA (租金由用户支付)
+pub fn init_user_stats(ctx: Context<InitUserStats>) -> Result<()> {
+    let user_stats = &mut ctx.accounts.user_stats;
+    user_stats.user = ctx.accounts.user.key();
+    user_stats.pending_dust_lamports = 0;
+    user_stats.pity_counter = 0;
+    user_stats.bump = ctx.bumps.user_stats;
+
+    msg!("UserStats initialized: user={}", user_stats.user);
+    Ok(())
+}
+
+/// 将旧版 UserStats 扩容到当前结构大小
+///
+/// 新增字段追加在末尾并零初始化，已是当前大小时直接返回
+pub fn migrate_user_stats(ctx: Context<MigrateUserStats>) -> Result<()> {
+    let user_stats = ctx.accounts.user_stats.to_account_info();
+    require_keys_eq!(*user_stats.owner, crate::ID, IPFlowError::MissingUserStats);
+
+    let new_space = 8 + UserStats::INIT_SPACE;
+    let old_space = user_stats.data_len();
+    if old_space >= new_space {
+        msg!("UserStats already migrated");
+        return Ok(());
+    }
+
+    // 补足扩容后的租金
+    let rent_needed = Rent::get()?
+        .minimum_balance(new_space)
+        .saturating_sub(user_stats.lamports());
+    if rent_needed > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: user_stats.clone(),
+                },
+            ),
+            rent_needed,
+        )?;
+    }
+    user_stats.realloc(new_space, true)?;
+
+    msg!("UserStats migrated: {} -> {} bytes", old_space, new_space);
+    Ok(())
+}
@@ -0,0 +1,733 @@
+// ==================== Claim 预校验 (dry-run) ====================
+//
+// 前端在请求用户签名前通过 simulateTransaction 调用，复用 claim 的账户上下文与参数，
+// 按 claim 的执行顺序完成全部校验 (签名者、状态、期限、熔断、路由账户与 discriminator、奖品池、
+// 输出 ATA、WSOL 包装账户、手续费金库、epoch 统计、Vault 余额)，但不执行任何转账或 CPI。
+//
+// 校验失败不会使指令失败：结果以 ClaimValidation 写入 return data (ok / 失败的检查项 / 错误码)。
+// 只读取账户，不修改 MintRequest、配置或任何统计，可重复调用。
+//
+// 以下情况仍直接返回错误 (与 claim 一致，由 Anchor 账户约束拒绝)：账户不存在或 PDA 不匹配、
+// 领取已暂停 (ClaimsPaused)；传入 receipt 账户 (Anchor 会在 handler 前创建凭证，dry-run 禁止传入)。
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_error::ProgramError;
+
+use crate::errors::IPFlowError;
+use crate::instructions::user::claim::{
+    authorize_claimer, check_claim_deadline, check_claim_status, check_payout_cap,
+    claim_fee_usd, falls_back_to_sol, payout_lamports, preflight_token_claim,
+    preview_sol_lamports, resolve_claim_terms, resolve_payout_mode, sol_portion_for,
+    swap_amount_in, validate_user_wsol_ata, FULL_PORTION_BPS,
+};
+use crate::state::{PayoutMode, SwapRouter};
+use crate::utils::vault_available_lamports;
+use crate::Claim;
+
+/// validate_claim 的检查项 (按 claim 的执行顺序)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ClaimCheck {
+    /// 全部通过
+    None,
+    /// 发放方式与 Split 占比
+    PayoutMode,
+    /// 签名者 (user 本人或有效委托)
+    Claimer,
+    /// 请求状态与审核标记
+    Status,
+    /// 领取期限
+    Deadline,
+    /// 24 小时发放熔断
+    PayoutCap,
+    /// Token 路径前置校验 (奖品池、swap 下限、路由账户、discriminator、输出 ATA)
+    TokenRoute,
+    /// SOL 发放金额 (最小发放额与零头累积账户)
+    SolPayout,
+    /// WSOL 包装发放账户
+    WrapAccounts,
+    /// 领取时收费的手续费金库
+    FeeVault,
+    /// 当前 epoch 统计账户
+    EpochStats,
+    /// Vault 余额 (含领取时退回的托管资金)
+    VaultBalance,
+    /// 用户预授权的领取条款 (发放方式须为 Token，路由须一致)
+    Preauthorization,
+}
+
+/// validate_claim 返回值
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ClaimValidation {
+    /// 全部检查通过
+    pub ok: bool,
+    /// 首个失败的检查项 (通过时为 None)
+    pub failed_check: ClaimCheck,
+    /// 失败的错误码 (IPFlowError 为 6000 + 序号，通过时为 0)
+    pub error_code: u64,
+    /// 实际将采用的发放方式 (Token 前置校验失败降级时为 SOL)
+    pub payout_mode: PayoutMode,
+    /// 是否降级为 SOL 发放
+    pub fallback_used: bool,
+    /// Token 路径使用的路由
+    pub swap_router: Option<SwapRouter>,
+    /// swap 输入 (lamports)
+    pub swap_amount_in: u64,
+    /// SOL 部分转出 (lamports，零头累积时为 0)
+    pub sol_payout_lamports: u64,
+    /// 用户输出 ATA 是否已存在 (不存在时 claim 以 authority 为 payer 创建)
+    pub output_account_exists: bool,
+}
+
+impl ClaimValidation {
+    /// 检查失败的结果
+    fn failed(check: ClaimCheck, err: Error, payout_mode: PayoutMode) -> Self {
+        ClaimValidation {
+            ok: false,
+            failed_check: check,
+            error_code: u64::from(ProgramError::from(err)),
+            payout_mode,
+            fallback_used: false,
+            swap_router: None,
+            swap_amount_in: 0,
+            sol_payout_lamports: 0,
+            output_account_exists: false,
+        }
+    }
+}
+
+/// 校验失败时携带检查项
+type CheckResult<T> = std::result::Result<T, (ClaimCheck, Error)>;
+
+/// 为检查结果标注检查项
+fn at<T>(check: ClaimCheck, result: Result<T>) -> CheckResult<T> {
+    result.map_err(|err| (check, err))
+}
+
+/// 预校验 claim (参数与 claim 相同)，不移动资金、不修改任何账户
+#[allow(clippy::too_many_arguments)]
+pub fn validate_claim<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+    payout_mode: PayoutMode,
+    swap_router: Option<SwapRouter>,
+    expected_token_output: Option<u64>,
+    swap_data: Option<Vec<u8>>,
+    fallback_to_sol: bool,
+    sol_portion_bps: u16,
+    wrap_payout: bool,
+) -> Result<ClaimValidation> {
+    // receipt 为 init 账户，传入即会被创建；dry-run 直接拒绝 (整笔回滚)
+    require!(
+        ctx.accounts.receipt.is_none(),
+        IPFlowError::ClaimReceiptMismatch
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let validation = dry_run_claim(
+        ctx.accounts,
+        ctx.remaining_accounts,
+        ClaimArgs {
+            payout_mode,
+            swap_router,
+            expected_token_output,
+            swap_data: swap_data.as_deref(),
+            fallback_to_sol,
+            sol_portion_bps,
+            wrap_payout,
+        },
+        now,
+    )
+    .unwrap_or_else(|(check, err)| ClaimValidation::failed(check, err, payout_mode));
+
+    msg!(
+        "Claim validation: ok={}, failed_check={:?}, error_code={}",
+        validation.ok,
+        validation.failed_check,
+        validation.error_code
+    );
+    Ok(validation)
+}
+
+/// 预校验使用的 claim 参数
+struct ClaimArgs<'a> {
+    payout_mode: PayoutMode,
+    swap_router: Option<SwapRouter>,
+    expected_token_output: Option<u64>,
+    swap_data: Option<&'a [u8]>,
+    fallback_to_sol: bool,
+    sol_portion_bps: u16,
+    wrap_payout: bool,
+}
+
+/// 按 claim 的执行顺序逐项校验，返回首个失败的检查项
+fn dry_run_claim<'info>(
+    accounts: &Claim<'info>,
+    remaining: &[AccountInfo<'info>],
+    args: ClaimArgs<'_>,
+    now: i64,
+) -> CheckResult<ClaimValidation> {
+    let payout_mode = at(
+        ClaimCheck::PayoutMode,
+        resolve_payout_mode(args.payout_mode, args.sol_portion_bps),
+    )?;
+    let terms = at(
+        ClaimCheck::Preauthorization,
+        resolve_claim_terms(
+            &accounts.mint_request,
+            payout_mode,
+            args.swap_router,
+            args.expected_token_output,
+            args.fallback_to_sol,
+        ),
+    )?;
+    at(ClaimCheck::Claimer, authorize_claimer(accounts, now))?;
+    at(ClaimCheck::Status, check_claim_status(&accounts.mint_request))?;
+    at(
+        ClaimCheck::Deadline,
+        check_claim_deadline(&accounts.mint_request, now),
+    )?;
+    at(
+        ClaimCheck::PayoutCap,
+        check_payout_cap(&accounts.config, accounts.mint_request.total_won_usd, now),
+    )?;
+
+    // Token 路径前置校验 (失败时按 claim 的规则降级为 SOL)
+    let mut applied_payout_mode = payout_mode;
+    let mut fallback_used = false;
+    let mut swap_router = None;
+    let mut token_amount_in = 0;
+    if payout_mode != PayoutMode::SOL {
+        let token_portion = FULL_PORTION_BPS - sol_portion_for(payout_mode, args.sol_portion_bps);
+        match preflight_token_claim(
+            accounts,
+            remaining,
+            terms.swap_router,
+            terms.expected_token_output,
+            args.swap_data,
+            token_portion,
+        ) {
+            Ok((router, _)) => {
+                swap_router = Some(router);
+                token_amount_in = at(
+                    ClaimCheck::TokenRoute,
+                    swap_amount_in(accounts, token_portion),
+                )?;
+            }
+            Err(e)
+                if falls_back_to_sol(
+                    &e,
+                    terms.fallback_to_sol,
+                    accounts.config.dust_swap_fallback_to_sol,
+                ) =>
+            {
+                applied_payout_mode = PayoutMode::SOL;
+                fallback_used = true;
+            }
+            Err(e) => return Err((ClaimCheck::TokenRoute, e)),
+        }
+    }
+
+    let sol_portion = sol_portion_for(applied_payout_mode, args.sol_portion_bps);
+    let sol_payout_lamports = if sol_portion > 0 {
+        at(
+            ClaimCheck::SolPayout,
+            preview_sol_lamports(accounts, sol_portion),
+        )?
+    } else {
+        0
+    };
+    if args.wrap_payout && sol_payout_lamports > 0 {
+        at(ClaimCheck::WrapAccounts, validate_user_wsol_ata(accounts))?;
+    }
+
+    let fee_usd = at(ClaimCheck::FeeVault, claim_fee_usd(accounts, sol_portion))?;
+    let fee_lamports = if fee_usd > 0 {
+        at(
+            ClaimCheck::FeeVault,
+            require_fee_vault(accounts.fee_vault.is_some()),
+        )?;
+        at(ClaimCheck::FeeVault, payout_lamports(accounts, fee_usd))?
+    } else {
+        0
+    };
+
+    at(
+        ClaimCheck::EpochStats,
+        require_epoch_stats_present(
+            accounts.epoch_stats.is_some(),
+            accounts.config.epochs_started(),
+        ),
+    )?;
+
+    // 领取时先将托管资金退回 Vault，再从 Vault 发放 SOL、swap 输入与手续费
+    let escrow_release = if accounts.mint_request.escrow_lamports > 0 {
+        accounts.escrow.lamports()
+    } else {
+        0
+    };
+    let available = at(
+        ClaimCheck::VaultBalance,
+        vault_available_lamports(&accounts.vault),
+    )?
+    .saturating_add(escrow_release);
+    at(
+        ClaimCheck::VaultBalance,
+        check_vault_coverage(
+            &[sol_payout_lamports, token_amount_in, fee_lamports],
+            available,
+        ),
+    )?;
+
+    Ok(ClaimValidation {
+        ok: true,
+        failed_check: ClaimCheck::None,
+        error_code: 0,
+        payout_mode: applied_payout_mode,
+        fallback_used,
+        swap_router,
+        swap_amount_in: token_amount_in,
+        sol_payout_lamports,
+        output_account_exists: swap_router.is_some()
+            && accounts
+                .user_token_account
+                .as_ref()
+                .is_some_and(|account| !account.data_is_empty()),
+    })
+}
+
+/// 领取时收费且手续费大于 0 时须传入手续费金库
+fn require_fee_vault(fee_vault_present: bool) -> Result<()> {
+    require!(fee_vault_present, IPFlowError::MissingFeeVault);
+    Ok(())
+}
+
+/// start_epoch 之后须传入当前 epoch 统计账户 (与 require_epoch_stats 一致)
+fn require_epoch_stats_present(epoch_stats_present: bool, epochs_started: bool) -> Result<()> {
+    require!(
+        epoch_stats_present || !epochs_started,
+        IPFlowError::EpochStatsRequired
+    );
+    Ok(())
+}
+
+/// Vault 可用余额须覆盖本次领取的全部转出
+fn check_vault_coverage(outflows: &[u64], available: u64) -> Result<()> {
+    let total = outflows
+        .iter()
+        .try_fold(0u64, |acc, amount| acc.checked_add(*amount))
+        .ok_or(IPFlowError::MathOverflow)?;
+    require!(total <= available, IPFlowError::InsufficientVaultBalance);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::associated_token::get_associated_token_address;
+    use anchor_spl::token::spl_token::state::Mint as SplMint;
+    use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+    use crate::state::{ClaimPreauthorization, IPFlowState, MintRequest, RequestStatus};
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn revealed_request(claim_deadline: i64) -> MintRequest {
+        let mut request = MintRequest::zeroed();
+        request.status = RequestStatus::Revealed;
+        request.claim_deadline = claim_deadline;
+        request
+    }
+
+    /// 测试账户 (泄漏为 'static，供 Claim<'static> 借用)
+    fn leak_info(
+        key: Pubkey,
+        owner: Pubkey,
+        data: Vec<u8>,
+        is_signer: bool,
+        executable: bool,
+    ) -> &'static AccountInfo<'static> {
+        Box::leak(Box::new(AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(1_000_000_000u64)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            executable,
+            0,
+        )))
+    }
+
+    fn system_info(key: Pubkey, is_signer: bool) -> &'static AccountInfo<'static> {
+        leak_info(key, anchor_lang::system_program::ID, vec![], is_signer, false)
+    }
+
+    fn program_info(key: Pubkey) -> &'static AccountInfo<'static> {
+        leak_info(key, Pubkey::default(), vec![], false, true)
+    }
+
+    fn serialized<T: AccountSerialize>(account: &T) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    /// 10 USD 已托管中奖 (锁定汇率 0.1 SOL / 10 USD)，选中活跃池 0
+    fn claimable_request() -> MintRequest {
+        let mut request = revealed_request(NOW + 86_400);
+        request.user = Pubkey::new_unique();
+        request.total_won_usd = 10_000_000;
+        request.token_won_usd = 10_000_000;
+        request.escrow_lamports = 100_000_000;
+        request.selected_pool_index = 0;
+        request
+    }
+
+    fn claim_config() -> IPFlowState {
+        let mut config = IPFlowState::zeroed();
+        config.sol_payout_bps = 9_500;
+        config.token_payout_bps = 9_500;
+        config.active_pool_count = 1;
+        config.active_pool_indices[0] = 0;
+        config.jupiter_program = Pubkey::new_unique();
+        config.raydium_program = Pubkey::new_unique();
+        config.allowed_route_discriminators[0] = [1u8; 8];
+        config.route_discriminator_count = 1;
+        config
+    }
+
+    /// user 本人签名的 Claim 账户，含 Token 模式的命名输出账户 (其余可选账户不传)
+    fn claim_accounts(request: &MintRequest, config: &IPFlowState) -> Claim<'static> {
+        let user = request.user;
+        let token_mint = Pubkey::new_unique();
+        let mut mint_data = vec![0u8; SplMint::LEN];
+        SplMint::pack(
+            SplMint {
+                decimals: 6,
+                is_initialized: true,
+                ..SplMint::default()
+            },
+            &mut mint_data,
+        )
+        .unwrap();
+
+        Claim {
+            authority: Signer::try_from(system_info(user, true)).unwrap(),
+            user: SystemAccount::try_from(system_info(user, false)).unwrap(),
+            mint_request: Account::try_from(leak_info(
+                Pubkey::new_unique(),
+                crate::ID,
+                serialized(request),
+                false,
+                false,
+            ))
+            .unwrap(),
+            config: Account::try_from(leak_info(
+                Pubkey::new_unique(),
+                crate::ID,
+                serialized(config),
+                false,
+                false,
+            ))
+            .unwrap(),
+            vault: SystemAccount::try_from(system_info(Pubkey::new_unique(), false)).unwrap(),
+            escrow: SystemAccount::try_from(system_info(Pubkey::new_unique(), false)).unwrap(),
+            pyth_price_update: Account::try_from_unchecked(leak_info(
+                Pubkey::new_unique(),
+                PriceUpdateV2::owner(),
+                vec![0u8; PriceUpdateV2::LEN],
+                false,
+                false,
+            ))
+            .unwrap(),
+            system_program: Program::try_from(program_info(anchor_lang::system_program::ID))
+                .unwrap(),
+            token_program: Some(Program::try_from(program_info(anchor_spl::token::ID)).unwrap()),
+            associated_token_program: Some(
+                Program::try_from(program_info(anchor_spl::associated_token::ID)).unwrap(),
+            ),
+            token_mint: Some(
+                Account::try_from(leak_info(
+                    token_mint,
+                    anchor_spl::token::ID,
+                    mint_data,
+                    false,
+                    false,
+                ))
+                .unwrap(),
+            ),
+            user_token_account: Some(UncheckedAccount::try_from(leak_info(
+                get_associated_token_address(&user, &token_mint),
+                anchor_lang::system_program::ID,
+                vec![],
+                false,
+                false,
+            ))),
+            user_stats: None,
+            prize_pool: None,
+            delegation: None,
+            receipt: None,
+            epoch_stats: None,
+            rewards_account: None,
+            fee_vault: None,
+            wsol_mint: None,
+            user_wsol_account: None,
+        }
+    }
+
+    fn sol_args() -> ClaimArgs<'static> {
+        ClaimArgs {
+            payout_mode: PayoutMode::SOL,
+            swap_router: None,
+            expected_token_output: None,
+            swap_data: None,
+            fallback_to_sol: false,
+            sol_portion_bps: 0,
+            wrap_payout: false,
+        }
+    }
+
+    fn token_args(router: SwapRouter, swap_data: Option<&'static [u8]>) -> ClaimArgs<'static> {
+        ClaimArgs {
+            payout_mode: PayoutMode::Token,
+            swap_router: Some(router),
+            expected_token_output: Some(1_000_000),
+            swap_data,
+            ..sol_args()
+        }
+    }
+
+    /// 路由账户: [0] 为 program，其余为不属于 Vault 的空账户
+    fn route(program: Pubkey, len: usize) -> Vec<AccountInfo<'static>> {
+        (0..len)
+            .map(|i| {
+                let key = if i == 0 { program } else { Pubkey::new_unique() };
+                leak_info(key, anchor_spl::token::ID, vec![], false, false).clone()
+            })
+            .collect()
+    }
+
+    /// 执行 dry-run，返回首个失败的 (检查项, 错误)
+    fn first_failure(
+        accounts: &Claim<'static>,
+        remaining: &[AccountInfo<'static>],
+        args: ClaimArgs<'static>,
+    ) -> (ClaimCheck, Error) {
+        dry_run_claim(accounts, remaining, args, NOW).unwrap_err()
+    }
+
+    #[test]
+    fn test_failed_validation_reports_anchor_error_code() {
+        let validation = ClaimValidation::failed(
+            ClaimCheck::TokenRoute,
+            error!(IPFlowError::InvalidSwapData),
+            PayoutMode::Token,
+        );
+        assert_eq!(
+            validation.error_code,
+            6000 + IPFlowError::InvalidSwapData as u64
+        );
+        assert_eq!(validation.swap_router, None);
+    }
+
+    #[test]
+    fn test_dry_run_labels_preauthorization_and_claimer() {
+        let config = claim_config();
+        let mut request = claimable_request();
+        request.set_claim_preauthorization(Some(ClaimPreauthorization {
+            router: SwapRouter::Raydium,
+            expected_token_output: 1_000_000,
+            max_slippage_bps: 100,
+        }));
+        let (check, err) = first_failure(&claim_accounts(&request, &config), &[], sol_args());
+        assert_eq!(check, ClaimCheck::Preauthorization);
+        assert_eq!(err, error!(IPFlowError::ClaimPreauthorizationMismatch));
+
+        // 会话密钥签名但未传入委托
+        let mut accounts = claim_accounts(&claimable_request(), &config);
+        accounts.authority = Signer::try_from(system_info(Pubkey::new_unique(), true)).unwrap();
+        let (check, err) = first_failure(&accounts, &[], sol_args());
+        assert_eq!(check, ClaimCheck::Claimer);
+        assert_eq!(err, error!(IPFlowError::UnauthorizedClaimer));
+
+        // 两者同时失败时先报告预授权 (与 claim 的执行顺序一致)
+        let mut accounts = claim_accounts(&request, &config);
+        accounts.authority = Signer::try_from(system_info(Pubkey::new_unique(), true)).unwrap();
+        assert_eq!(
+            first_failure(&accounts, &[], sol_args()).0,
+            ClaimCheck::Preauthorization
+        );
+    }
+
+    #[test]
+    fn test_dry_run_labels_payout_cap_before_token_route() {
+        let mut config = claim_config();
+        config.daily_payout_cap_usd = 5_000_000;
+        config.payout_window_start = NOW;
+        let request = claimable_request();
+        let (check, err) = first_failure(&claim_accounts(&request, &config), &[], sol_args());
+        assert_eq!(check, ClaimCheck::PayoutCap);
+        assert_eq!(err, error!(IPFlowError::DailyPayoutCapReached));
+
+        // 签名者校验先于熔断
+        let mut accounts = claim_accounts(&request, &config);
+        accounts.authority = Signer::try_from(system_info(Pubkey::new_unique(), true)).unwrap();
+        assert_eq!(first_failure(&accounts, &[], sol_args()).0, ClaimCheck::Claimer);
+
+        // 熔断先于 Token 路径前置校验 (选中池已不可用)
+        let mut request = claimable_request();
+        request.selected_pool_index = 1;
+        assert_eq!(
+            first_failure(
+                &claim_accounts(&request, &config),
+                &[],
+                token_args(SwapRouter::Jupiter, None)
+            )
+            .0,
+            ClaimCheck::PayoutCap
+        );
+    }
+
+    #[test]
+    fn test_dry_run_labels_token_route_failures() {
+        let config = claim_config();
+        let jupiter = route(config.jupiter_program, 3);
+        let raydium = route(config.raydium_program, RAYDIUM_SWAP_ACCOUNTS_COUNT);
+
+        // 奖品池: 选中池已不在活跃列表
+        let mut request = claimable_request();
+        request.selected_pool_index = 1;
+        let (check, err) = first_failure(
+            &claim_accounts(&request, &config),
+            &jupiter,
+            token_args(SwapRouter::Jupiter, Some(&[1u8; 8])),
+        );
+        assert_eq!(check, ClaimCheck::TokenRoute);
+        assert_eq!(err, error!(IPFlowError::PrizePoolUnavailable));
+
+        let request = claimable_request();
+        // 输出 ATA: 不是 (user, token_mint) 派生的地址
+        let mut accounts = claim_accounts(&request, &config);
+        accounts.user_token_account = Some(UncheckedAccount::try_from(system_info(
+            Pubkey::new_unique(),
+            false,
+        )));
+        let (check, err) = first_failure(
+            &accounts,
+            &jupiter,
+            token_args(SwapRouter::Jupiter, Some(&[1u8; 8])),
+        );
+        assert_eq!(check, ClaimCheck::TokenRoute);
+        assert_eq!(err, error!(IPFlowError::InvalidTokenAccount));
+
+        // discriminator: 不在配置的白名单中
+        let accounts = claim_accounts(&request, &config);
+        let (check, err) = first_failure(
+            &accounts,
+            &jupiter,
+            token_args(SwapRouter::Jupiter, Some(&[2u8; 8])),
+        );
+        assert_eq!(check, ClaimCheck::TokenRoute);
+        assert_eq!(err, error!(IPFlowError::InvalidSwapData));
+
+        // 路由账户: Raydium remaining[4] 不是 Vault 的 WSOL 账户 (即使请求降级也不降级)
+        let mut args = token_args(SwapRouter::Raydium, None);
+        args.fallback_to_sol = true;
+        let (check, err) = first_failure(&accounts, &raydium, args);
+        assert_eq!(check, ClaimCheck::TokenRoute);
+        assert_eq!(err, error!(IPFlowError::VaultWsolAccountMissing));
+    }
+
+    #[test]
+    fn test_dry_run_labels_sol_payout_and_wrap_accounts() {
+        let request = claimable_request();
+
+        // 零头累积开启、低于最小发放额且未传入 UserStats / 奖励账户
+        let mut config = claim_config();
+        config.dust_accumulation_enabled = true;
+        config.min_payout_lamports = u64::MAX;
+        let mut args = sol_args();
+        args.wrap_payout = true;
+        let (check, err) = first_failure(&claim_accounts(&request, &config), &[], args);
+        assert_eq!(check, ClaimCheck::SolPayout);
+        assert_eq!(err, error!(IPFlowError::MissingUserStats));
+
+        // wrap_payout 但未传入 WSOL 包装账户
+        let config = claim_config();
+        let mut args = sol_args();
+        args.wrap_payout = true;
+        let (check, err) = first_failure(&claim_accounts(&request, &config), &[], args);
+        assert_eq!(check, ClaimCheck::WrapAccounts);
+        assert_eq!(err, error!(IPFlowError::WrapPayoutAccountsMissing));
+
+        // Token 前置校验按调用方要求降级为 SOL 后继续执行 SOL 路径的检查
+        let mut request = claimable_request();
+        request.selected_pool_index = 1;
+        let mut args = token_args(SwapRouter::Jupiter, Some(&[1u8; 8]));
+        args.fallback_to_sol = true;
+        args.wrap_payout = true;
+        let jupiter = route(config.jupiter_program, 3);
+        let (check, err) = first_failure(&claim_accounts(&request, &config), &jupiter, args);
+        assert_eq!(check, ClaimCheck::WrapAccounts);
+        assert_eq!(err, error!(IPFlowError::WrapPayoutAccountsMissing));
+    }
+
+    #[test]
+    fn test_check_helpers_report_expected_errors() {
+        assert_eq!(
+            resolve_payout_mode(PayoutMode::Split, FULL_PORTION_BPS + 1).unwrap_err(),
+            error!(IPFlowError::InvalidSolPortion)
+        );
+
+        let mut request = revealed_request(1_700_000_000);
+        request.status = RequestStatus::Claiming;
+        assert_eq!(
+            check_claim_status(&request).unwrap_err(),
+            error!(IPFlowError::ClaimInProgress)
+        );
+        request.status = RequestStatus::Revealed;
+        request.needs_review = true;
+        assert_eq!(
+            check_claim_status(&request).unwrap_err(),
+            error!(IPFlowError::PendingReview)
+        );
+
+        let request = revealed_request(1_700_000_000);
+        assert!(check_claim_deadline(&request, 1_699_999_999).is_ok());
+        assert_eq!(
+            check_claim_deadline(&request, 1_700_000_000).unwrap_err(),
+            error!(IPFlowError::ClaimExpired)
+        );
+
+        assert_eq!(
+            require_fee_vault(false).unwrap_err(),
+            error!(IPFlowError::MissingFeeVault)
+        );
+        assert_eq!(
+            require_epoch_stats_present(false, true).unwrap_err(),
+            error!(IPFlowError::EpochStatsRequired)
+        );
+        assert!(require_epoch_stats_present(false, false).is_ok());
+
+        assert!(check_vault_coverage(&[600, 300, 100], 1_000).is_ok());
+        assert_eq!(
+            check_vault_coverage(&[600, 300, 101], 1_000).unwrap_err(),
+            error!(IPFlowError::InsufficientVaultBalance)
+        );
+        assert_eq!(
+            check_vault_coverage(&[u64::MAX, 1], u64::MAX).unwrap_err(),
+            error!(IPFlowError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn test_status_check_does_not_mutate_request() {
+        let request = revealed_request(1_700_000_000);
+        let before = request.try_to_vec().unwrap();
+        for _ in 0..3 {
+            assert!(check_claim_status(&request).is_ok());
+            assert!(check_claim_deadline(&request, 1_600_000_000).is_ok());
+        }
+        assert_eq!(request.try_to_vec().unwrap(), before);
+    }
+}
@@ -7,11 +7,16 @@ pub mod utils;
 
 use crate::state::*;
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 
 declare_id!("ALRWyaQkjVGznjAXsxhqXkyYDaETPUN2xj82W8uyji53");
 
+// test-hooks 跳过 VRF CPI 并开放管理员指定随机数的 test_reveal，绝不能进入主网构建
+#[cfg(all(feature = "test-hooks", feature = "mainnet"))]
+compile_error!("feature \"test-hooks\" must not be enabled together with \"mainnet\"");
+
 #[program]
 pub mod ipflow_v3 {
     use super::*;
@@ -22,25 +27,46 @@ pub mod ipflow_v3 {
     }
 
     /// 迁移/扩容全局配置账户 (仅管理员)
+    /// - prize_pool_count: 已创建的奖品池数量 (下一个可用索引)，按链上奖品池校验
+    /// - remaining_accounts: [索引 prize_pool_count 的奖品池 PDA (未创建), 现存奖品池 PDA...]
     pub fn migrate_config(ctx: Context<MigrateConfig>, prize_pool_count: u8) -> Result<()> {
         instructions::admin::initialize::migrate_config(ctx, prize_pool_count)
     }
 
-    /// 关闭全局配置账户 (仅管理员，用于重新初始化)
+    /// 关闭全局配置账户 (仅管理员，已配置 co-admin 时须其共同签名；用于重新初始化)
     pub fn close_config(ctx: Context<CloseConfig>) -> Result<()> {
         instructions::admin::initialize::close_config(ctx)
     }
 
+    /// 创建 Vault 的 USDT ATA 并记录到全局配置 (仅管理员，幂等)
+    pub fn init_vault_token_account(ctx: Context<InitVaultTokenAccount>) -> Result<()> {
+        instructions::admin::initialize::init_vault_token_account(ctx)
+    }
+
+    /// 创建 Vault 的 WSOL ATA 并登记到全局配置 (仅管理员，幂等)
+    pub fn init_vault_wsol(ctx: Context<InitVaultWsol>) -> Result<()> {
+        instructions::admin::initialize::init_vault_wsol(ctx)
+    }
+
+    /// 初始化 VRF 程序身份 PDA 并记录 bump (仅管理员，幂等)
+    pub fn init_program_identity(ctx: Context<InitProgramIdentity>) -> Result<()> {
+        instructions::admin::initialize::init_program_identity(ctx)
+    }
+
     /// 用户发起抽奖请求 (支付 10U/张 并发起 MagicBlock VRF 请求)
     /// payment_mode: SOL 或 USDT 支付方式
     /// client_seed: 用户提供的随机种子 (用于 VRF 请求)
     /// request_slot: 请求发起时的 slot (用于 PDA 派生)
+    /// beneficiary: 可选，奖金归属地址 (代付赠送)，None 表示支付者本人
+    /// memo: 可选，32 字节合作方标签 (如活动 ID)，随事件输出，None 记为全 0
     pub fn request_mint(
         ctx: Context<RequestMint>,
         amount_of_cards: u32,
         payment_mode: PaymentMode,
         client_seed: u8,
         request_slot: u64,
+        beneficiary: Option<Pubkey>,
+        memo: Option<[u8; 32]>,
     ) -> Result<()> {
         instructions::user::request_mint::handler(
             ctx,
@@ -48,16 +74,35 @@ pub mod ipflow_v3 {
             payment_mode,
             client_seed,
             request_slot,
+            beneficiary,
+            memo,
         )
     }
 
     /// VRF 回调处理 - 由 MagicBlock VRF 程序自动调用
     /// 不应由用户直接调用
+    ///
+    /// amount_of_cards / request_id 来自 request_mint 写入的 callback_args，须与 MintRequest 一致
     pub fn consume_lottery_randomness(
         ctx: Context<ConsumeLotteryRandomness>,
         randomness: [u8; 32],
+        amount_of_cards: u32,
+        request_id: u64,
     ) -> Result<()> {
-        instructions::oracle::consume_randomness::handler(ctx, randomness)
+        instructions::oracle::consume_randomness::handler(ctx, randomness, amount_of_cards, request_id)
+    }
+
+    /// 以指定随机数揭示请求 (仅 test-hooks 构建，仅管理员)
+    /// 依次执行 consume_lottery_randomness 与 continue_reveal 的流程，供 localnet / bankrun 驱动确定性揭示
+    #[cfg(feature = "test-hooks")]
+    pub fn test_reveal(ctx: Context<TestReveal>, randomness: [u8; 32]) -> Result<()> {
+        instructions::oracle::test_hooks::test_reveal(ctx, randomness)
+    }
+
+    /// 续算分批揭示并完成揭示结算 (任何人可调用)
+    /// 回调后反复调用直到状态切换为 Revealed (卡数不超过单次回调处理上限时调用一次即可)
+    pub fn continue_reveal(ctx: Context<ContinueReveal>) -> Result<()> {
+        instructions::oracle::consume_randomness::continue_reveal(ctx)
     }
 
     /// 用户领取奖励 (选择 SOL 或 Token 发放方式)
@@ -66,13 +111,21 @@ pub mod ipflow_v3 {
     /// - expected_token_output: Token 模式必填，前端从 DEX quote 获取的预期输出量
     /// - swap_data: Token 模式必填，从 DEX swap-instructions API 获取的指令数据
     /// - vrf_request_slot: VRF 请求时的 slot (用于 PDA 派生)
+    /// - fallback_to_sol: Token 模式 swap 前置校验因路由、discriminator 或奖品池原因失败时自动降级为 SOL 发放
+    /// - create_receipt: 是否创建 ClaimReceipt PDA 持久化中奖记录 (租金由用户支付)
+    /// - sol_portion_bps: Split 模式 SOL 部分占比 (bps)，其他模式忽略
+    #[allow(clippy::too_many_arguments)]
     pub fn claim<'info>(
         ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
         payout_mode: PayoutMode,
         swap_router: Option<SwapRouter>,
         expected_token_output: Option<u64>,
         swap_data: Option<Vec<u8>>,
-        _vrf_request_slot: u64,
+        vrf_request_slot: u64,
+        fallback_to_sol: bool,
+        create_receipt: bool,
+        sol_portion_bps: u16,
+        wrap_payout: bool,
     ) -> Result<()> {
         instructions::user::claim::handler(
             ctx,
@@ -80,9 +133,115 @@ pub mod ipflow_v3 {
             swap_router,
             expected_token_output,
             swap_data,
+            vrf_request_slot,
+            fallback_to_sol,
+            create_receipt,
+            sol_portion_bps,
+            wrap_payout,
+        )
+    }
+
+    /// 预校验 claim (dry-run，参数与 claim 相同)：执行全部校验但不转账、不执行 CPI、不修改任何账户，
+    /// 结果 (ok / 失败的检查项 / 错误码) 写入 return data，前端通过 simulateTransaction 读取
+    /// - 不得传入 receipt 账户 (create_receipt 仅为与 claim 保持参数一致)
+    #[allow(clippy::too_many_arguments)]
+    pub fn validate_claim<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+        payout_mode: PayoutMode,
+        swap_router: Option<SwapRouter>,
+        expected_token_output: Option<u64>,
+        swap_data: Option<Vec<u8>>,
+        _vrf_request_slot: u64,
+        fallback_to_sol: bool,
+        _create_receipt: bool,
+        sol_portion_bps: u16,
+        wrap_payout: bool,
+    ) -> Result<instructions::user::validate_claim::ClaimValidation> {
+        instructions::user::validate_claim::validate_claim(
+            ctx,
+            payout_mode,
+            swap_router,
+            expected_token_output,
+            swap_data,
+            fallback_to_sol,
+            sol_portion_bps,
+            wrap_payout,
+        )
+    }
+
+    /// 领取被 24 小时发放上限阻塞时登记顺延领取期限
+    /// (claim 失败会整笔回滚，无法在失败交易中写入顺延，故单独登记)
+    /// - vrf_request_slot: VRF 请求时的 slot (用于 PDA 派生)
+    pub fn record_payout_block(ctx: Context<RecordPayoutBlock>, _vrf_request_slot: u64) -> Result<()> {
+        instructions::user::claim::record_payout_block(ctx)
+    }
+
+    /// 登记 Token 领取的 swap 失败 (滑点超限等)，累计 3 次后 SOL 领取按 Token 发放比例计算
+    /// - vrf_request_slot: VRF 请求时的 slot (用于 PDA 派生)
+    pub fn report_swap_failure(
+        ctx: Context<ReportSwapFailure>,
+        _vrf_request_slot: u64,
+    ) -> Result<()> {
+        instructions::user::claim::report_swap_failure(ctx)
+    }
+
+    /// 预授权 Token 领取条款 (仅用户本人签名)，之后的 claim (含中继提交) 须按此路由、预期输出与滑点执行
+    /// 重复调用覆盖已有预授权
+    /// - vrf_request_slot: VRF 请求时的 slot (用于 PDA 派生)
+    /// - router: 锁定的 DEX 路由
+    /// - expected_token_output: 锁定的预期 Token 输出
+    /// - max_slippage_bps: 允许的最大滑点 (不超过 10%)
+    pub fn pre_authorize_claim(
+        ctx: Context<PreAuthorizeClaim>,
+        _vrf_request_slot: u64,
+        router: SwapRouter,
+        expected_token_output: u64,
+        max_slippage_bps: u16,
+    ) -> Result<()> {
+        instructions::user::claim::pre_authorize_claim(
+            ctx,
+            router,
+            expected_token_output,
+            max_slippage_bps,
         )
     }
 
+    /// 清除 Token 领取预授权，claim 恢复使用指令参数
+    /// - vrf_request_slot: VRF 请求时的 slot (用于 PDA 派生)
+    pub fn clear_claim_preauthorization(
+        ctx: Context<PreAuthorizeClaim>,
+        _vrf_request_slot: u64,
+    ) -> Result<()> {
+        instructions::user::claim::clear_claim_preauthorization(ctx)
+    }
+
+    /// 授权会话密钥代为领取
+    /// - delegate: 会话密钥
+    /// - expires_at: 过期时间戳 (最长 7 天)
+    pub fn set_delegate(ctx: Context<SetDelegate>, delegate: Pubkey, expires_at: i64) -> Result<()> {
+        instructions::user::delegation::set_delegate(ctx, delegate, expires_at)
+    }
+
+    /// 撤销会话密钥授权，关闭委托 PDA
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        instructions::user::delegation::revoke_delegate(ctx)
+    }
+
+    /// 关闭领取凭证 PDA，租金退还给用户
+    /// - vrf_request_slot: 对应请求的 slot (用于 PDA 派生)
+    pub fn close_receipt(ctx: Context<CloseReceipt>, _vrf_request_slot: u64) -> Result<()> {
+        instructions::user::receipt::close_receipt(ctx)
+    }
+
+    /// 关闭领取时由合约新建的空输出 Token 账户 (奖品 Token 或 WSOL ATA)，租金退还给用户
+    /// - vrf_request_slot: 对应请求的 slot (用于领取凭证 PDA 派生)
+    pub fn cleanup_output_account(
+        ctx: Context<CleanupOutputAccount>,
+        _vrf_request_slot: u64,
+    ) -> Result<()> {
+        instructions::user::receipt::cleanup_output_account(ctx)
+    }
+
     /// 超时退款 (Task 2.3)
     /// 当 MintRequest 处于 Pending 状态超过 10 分钟时，用户可申请退款
     /// - vrf_request_slot: VRF 请求时的 slot (用于 PDA 派生)
@@ -90,51 +249,578 @@ pub mod ipflow_v3 {
         instructions::user::refund::handler(ctx)
     }
 
+    /// 管理员协助退款 (Vault Token 账户轮换后)
+    /// 原收款 Vault Token 账户已关闭或余额不足时，从同 Mint 的备用 Vault Token 账户退还本金
+    /// - vrf_request_slot: VRF 请求时的 slot (用于 PDA 派生)
+    pub fn refund_from_alternate(
+        ctx: Context<RefundFromAlternate>,
+        _vrf_request_slot: u64,
+    ) -> Result<()> {
+        instructions::user::refund::refund_from_alternate(ctx)
+    }
+
+    /// 过期 Pending 请求清理 (任何人可调用)
+    /// 请求创建超过 30 天仍为 Pending 时，退还本金、关闭 PDA 并向调用者支付小费
+    /// - vrf_request_slot: VRF 请求时的 slot (用于 PDA 派生)
+    pub fn crank_expire_pending(
+        ctx: Context<CrankExpirePending>,
+        _vrf_request_slot: u64,
+    ) -> Result<()> {
+        instructions::user::crank::crank_expire_pending(ctx)
+    }
+
+    /// 过期请求批量清理 (任何人可调用)
+    /// remaining_accounts 按 [mint_request, payer] 成对传入 (最多 12 对)，不满足条件的请求跳过
+    /// 返回实际处理的请求数量
+    pub fn sweep_expired<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepExpired<'info>>,
+    ) -> Result<u8> {
+        instructions::user::sweep::sweep_expired(ctx)
+    }
+
+    /// 迁移/扩容升级前创建的 MintRequest 账户 (任何人可调用，调用者补足租金差额)
+    /// - vrf_request_slot: VRF 请求时的 slot (用于 PDA 派生)
+    pub fn migrate_mint_request(
+        ctx: Context<MigrateMintRequest>,
+        _vrf_request_slot: u64,
+    ) -> Result<()> {
+        instructions::user::migrate_request::migrate_mint_request(ctx)
+    }
+
+    /// 初始化用户统计 PDA
+    pub fn init_user_stats(ctx: Context<InitUserStats>) -> Result<()> {
+        instructions::user::user_stats::init_user_stats(ctx)
+    }
+
+    /// 初始化用户奖励余额 PDA
+    pub fn init_rewards_account(ctx: Context<InitRewardsAccount>) -> Result<()> {
+        instructions::user::rewards::init_rewards_account(ctx)
+    }
+
+    /// 领取奖励余额 (按 Pyth 价格换算为 SOL，需达到最小领取额)
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        instructions::user::rewards::claim_rewards(ctx)
+    }
+
+    /// 为已揭示的请求分批铸造卡牌 NFT (每张卡一个 Metaplex Core 资产)
+    /// - max_cards: 本批上限 (不超过 MAX_CARD_NFTS_PER_TX)
+    /// - remaining_accounts: 从 cards_minted 起依次传入卡牌资产 PDA
+    pub fn mint_card_nfts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MintCardNfts<'info>>,
+        vrf_request_slot: u64,
+        max_cards: u8,
+    ) -> Result<()> {
+        instructions::user::card_nft::mint_card_nfts(ctx, vrf_request_slot, max_cards)
+    }
+
+    /// 将旧版 UserStats 扩容到当前结构 (新增字段零初始化，租金由用户补足)
+    pub fn migrate_user_stats(ctx: Context<MigrateUserStats>) -> Result<()> {
+        instructions::user::user_stats::migrate_user_stats(ctx)
+    }
+
+    /// 结算当前 epoch 并创建下一个 EpochStats (permissionless，满 epoch_length_seconds 后可调用)
+    pub fn roll_epoch(ctx: Context<RollEpoch>) -> Result<()> {
+        instructions::user::epoch::roll_epoch(ctx)
+    }
+
+    /// 发出转化漏斗计数快照 StatsSnapshot (permissionless，每小时最多一次)
+    pub fn emit_stats(ctx: Context<EmitStats>) -> Result<()> {
+        instructions::user::funnel::emit_stats(ctx)
+    }
+
     // ==================== 管理员指令 (Task 3.1) ====================
 
-    /// 管理员提取 SOL
+    /// 管理员提取 SOL (接收方须为已生效的提取白名单地址)
     /// - amount: 提取金额 (lamports)
     pub fn withdraw_sol(ctx: Context<WithdrawSol>, amount: u64) -> Result<()> {
         instructions::admin::withdraw::withdraw_sol(ctx, amount)
     }
 
-    /// 管理员提取 Token
+    /// 管理员提取 Token (接收账户 owner 须为已生效的提取白名单地址)
     /// - amount: 提取金额 (raw token amount)
     pub fn withdraw_token(ctx: Context<WithdrawToken>, amount: u64) -> Result<()> {
         instructions::admin::withdraw::withdraw_token(ctx, amount)
     }
 
+    /// 添加提取接收地址白名单 (48 小时后生效)
+    pub fn add_withdraw_recipient(ctx: Context<UpdateConfig>, recipient: Pubkey) -> Result<()> {
+        instructions::admin::settings::add_withdraw_recipient(ctx, recipient)
+    }
+
+    /// 从提取白名单移除接收地址
+    pub fn remove_withdraw_recipient(ctx: Context<UpdateConfig>, recipient: Pubkey) -> Result<()> {
+        instructions::admin::settings::remove_withdraw_recipient(ctx, recipient)
+    }
+
+    /// 从手续费金库提取 SOL (接收方须为 config.fee_recipient)
+    /// - amount: 提取金额 (lamports)
+    pub fn withdraw_fees_sol(ctx: Context<WithdrawFeesSol>, amount: u64) -> Result<()> {
+        instructions::admin::withdraw::withdraw_fees_sol(ctx, amount)
+    }
+
+    /// 从手续费金库提取 Token (接收账户 owner 须为 config.fee_recipient)
+    /// - amount: 提取金额 (raw token amount)
+    pub fn withdraw_fees_token(ctx: Context<WithdrawFeesToken>, amount: u64) -> Result<()> {
+        instructions::admin::withdraw::withdraw_fees_token(ctx, amount)
+    }
+
+    /// 关闭 Vault 名下余额为 0 的杂散 Token 账户，租金退还给 recipient
+    pub fn close_vault_token_account(ctx: Context<CloseVaultTokenAccount>) -> Result<()> {
+        instructions::admin::withdraw::close_vault_token_account(ctx)
+    }
+
+    // ==================== 配置参数调整 ====================
+
+    /// 设置细粒度暂停位 (bit 0 mint, bit 1 claim, bit 2 refund, bit 3 admin-withdraw)
+    pub fn set_pause_flags(ctx: Context<UpdateConfig>, flags: u8) -> Result<()> {
+        instructions::admin::settings::set_pause_flags(ctx, flags)
+    }
+
+    /// 设置 SOL 领取最小发放额及零头处理策略
+    /// - min_payout_lamports: 最小发放额 (lamports)，0 表示不限制
+    /// - dust_accumulation_enabled: true 时低于最小值的零头累积到 UserStats
+    pub fn update_min_payout(
+        ctx: Context<UpdateConfig>,
+        min_payout_lamports: u64,
+        dust_accumulation_enabled: bool,
+    ) -> Result<()> {
+        instructions::admin::settings::update_min_payout(
+            ctx,
+            min_payout_lamports,
+            dust_accumulation_enabled,
+        )
+    }
+
+    /// 设置 Token 模式 swap 输入下限及低于下限时的处理策略
+    /// - min_swap_lamports: swap 输入下限 (lamports)，0 表示不限制
+    /// - dust_swap_fallback_to_sol: true 时降级为 SOL 发放，false 时拒绝 (PayoutTooSmallForSwap)
+    pub fn update_min_swap(
+        ctx: Context<UpdateConfig>,
+        min_swap_lamports: u64,
+        dust_swap_fallback_to_sol: bool,
+    ) -> Result<()> {
+        instructions::admin::settings::update_min_swap(
+            ctx,
+            min_swap_lamports,
+            dust_swap_fallback_to_sol,
+        )
+    }
+
+    /// 设置奖品池 swap_pool 变更的生效延迟 (秒)，0 表示立即生效
+    pub fn update_pool_change_delay(
+        ctx: Context<UpdateConfig>,
+        pool_change_delay_seconds: u64,
+    ) -> Result<()> {
+        instructions::admin::settings::update_pool_change_delay(ctx, pool_change_delay_seconds)
+    }
+
+    /// 设置 Vault 储备下限 (lamports)，可用余额低于该值时停止接受新请求，0 表示不限制
+    pub fn update_min_vault_reserve(
+        ctx: Context<UpdateConfig>,
+        min_vault_reserve_lamports: u64,
+    ) -> Result<()> {
+        instructions::admin::settings::update_min_vault_reserve(ctx, min_vault_reserve_lamports)
+    }
+
+    /// 设置 crank_expire_pending 调用者小费 (lamports)，0 表示不支付
+    pub fn update_crank_tip(ctx: Context<UpdateConfig>, crank_tip_lamports: u64) -> Result<()> {
+        instructions::admin::settings::update_crank_tip(ctx, crank_tip_lamports)
+    }
+
+    /// 启用 epoch 统计 (创建当前 EpochStats，仅可调用一次)
+    pub fn start_epoch(ctx: Context<StartEpoch>) -> Result<()> {
+        instructions::admin::initialize::start_epoch(ctx)
+    }
+
+    /// 设置 epoch 长度 (秒，不低于 1 小时)
+    pub fn update_epoch_length(ctx: Context<UpdateConfig>, epoch_length_seconds: u64) -> Result<()> {
+        instructions::admin::settings::update_epoch_length(ctx, epoch_length_seconds)
+    }
+
+    /// 设置单次 VRF 请求费用 (lamports)，用户超时退款时扣除，0 表示全额退款
+    pub fn update_vrf_fee(ctx: Context<UpdateConfig>, vrf_fee_lamports: u64) -> Result<()> {
+        instructions::admin::settings::update_vrf_fee(ctx, vrf_fee_lamports)
+    }
+
+    /// 设置保底阈值 (连续 Tier 1/2 卡数，0 表示关闭)
+    pub fn update_pity_threshold(ctx: Context<UpdateConfig>, pity_threshold: u16) -> Result<()> {
+        instructions::admin::settings::update_pity_threshold(ctx, pity_threshold)
+    }
+
+    /// 设置单笔请求卡数上下限 (1 <= min_cards <= max_cards <= 1000)
+    pub fn update_card_limits(
+        ctx: Context<UpdateConfig>,
+        min_cards: u32,
+        max_cards: u32,
+    ) -> Result<()> {
+        instructions::admin::settings::update_card_limits(ctx, min_cards, max_cards)
+    }
+
+    /// 设置手续费接收地址
+    pub fn update_fee_recipient(ctx: Context<UpdateConfig>, fee_recipient: Pubkey) -> Result<()> {
+        instructions::admin::settings::update_fee_recipient(ctx, fee_recipient)
+    }
+
+    /// 设置程序暂停期间是否仍允许领取已揭示的奖金
+    pub fn set_claims_allowed_while_paused(
+        ctx: Context<UpdateConfig>,
+        allowed: bool,
+    ) -> Result<()> {
+        instructions::admin::settings::set_claims_allowed_while_paused(ctx, allowed)
+    }
+
+    /// 设置推荐返佣比例与奖励最小领取额
+    pub fn update_rewards_settings(
+        ctx: Context<UpdateConfig>,
+        referral_bps: u16,
+        min_rewards_claim_usd: u64,
+    ) -> Result<()> {
+        instructions::admin::settings::update_rewards_settings(ctx, referral_bps, min_rewards_claim_usd)
+    }
+
+    /// 创建卡牌 NFT Collection (Metaplex Core，更新权限为程序 PDA)
+    pub fn create_card_collection(
+        ctx: Context<CreateCardCollection>,
+        name: String,
+        uri: String,
+    ) -> Result<()> {
+        instructions::admin::card_collection::create_card_collection(ctx, name, uri)
+    }
+
+    /// 设置卡牌 NFT 铸造开关与元数据 URI 前缀
+    pub fn update_card_collection(
+        ctx: Context<UpdateConfig>,
+        enabled: bool,
+        uri_prefix: String,
+    ) -> Result<()> {
+        instructions::admin::settings::update_card_collection(ctx, enabled, uri_prefix)
+    }
+
+    /// 设置 RTP 动态调节参数 (按 Vault 余额相对目标值缩放开奖金额)
+    pub fn update_rtp_modulation(
+        ctx: Context<UpdateConfig>,
+        rtp_modulation_enabled: bool,
+        vault_target_lamports: u64,
+        max_modulation_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::settings::update_rtp_modulation(
+            ctx,
+            rtp_modulation_enabled,
+            vault_target_lamports,
+            max_modulation_bps,
+        )
+    }
+
+    /// 更新 Jupiter Program ID
+    pub fn update_jupiter_program(ctx: Context<UpdateConfig>, jupiter_program: Pubkey) -> Result<()> {
+        instructions::admin::settings::update_jupiter_program(ctx, jupiter_program)
+    }
+
+    /// 更新 MagicBlock VRF 程序 ID 与回调签名身份
+    pub fn update_vrf_program(
+        ctx: Context<UpdateConfig>,
+        vrf_program: Pubkey,
+        vrf_identity: Pubkey,
+    ) -> Result<()> {
+        instructions::admin::settings::update_vrf_program(ctx, vrf_program, vrf_identity)
+    }
+
+    /// 更新 Raydium CPMM Program ID (按部署环境)
+    pub fn update_raydium_program(ctx: Context<UpdateConfig>, raydium_program: Pubkey) -> Result<()> {
+        instructions::admin::settings::update_raydium_program(ctx, raydium_program)
+    }
+
+    /// 整体替换允许的 Jupiter 路由指令 discriminator
+    pub fn set_route_discriminators(
+        ctx: Context<UpdateConfig>,
+        discriminators: Vec<[u8; 8]>,
+    ) -> Result<()> {
+        instructions::admin::settings::set_route_discriminators(ctx, discriminators)
+    }
+
+    /// 启用/停用 SOL 或 USDT 支付方式 (仅影响 request_mint)
+    pub fn set_payment_modes(
+        ctx: Context<UpdateConfig>,
+        sol_payments_enabled: bool,
+        usdt_payments_enabled: bool,
+    ) -> Result<()> {
+        instructions::admin::settings::set_payment_modes(
+            ctx,
+            sol_payments_enabled,
+            usdt_payments_enabled,
+        )
+    }
+
+    /// 设置 claim 发放比例 (bps，范围 [5000, 10000])
+    /// - sol_payout_bps: SOL 模式发放比例
+    /// - token_payout_bps: Token 模式发放比例
+    pub fn update_payout_bps(
+        ctx: Context<UpdateConfig>,
+        sol_payout_bps: u16,
+        token_payout_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::settings::update_payout_bps(ctx, sol_payout_bps, token_payout_bps)
+    }
+
+    /// 设置经济参数变更的 co-admin (零值表示取消两步审批；已配置时须由当前 co-admin 共同签名)
+    pub fn set_co_admin(ctx: Context<SetCoAdmin>, new_co_admin: Pubkey) -> Result<()> {
+        instructions::admin::economics::set_co_admin(ctx, new_co_admin)
+    }
+
+    /// 暂存经济参数变更 (发放比例 / 奖品池 Tier 覆盖与奖金上限 / RTP 调节 / 保底 / 返佣 / 手续费时机)，
+    /// 等待 co-admin 在 72 小时内确认
+    pub fn stage_economics_change(
+        ctx: Context<StageEconomicsChange>,
+        change: EconomicsChange,
+    ) -> Result<()> {
+        instructions::admin::economics::stage_economics_change(ctx, change)
+    }
+
+    /// co-admin 确认暂存的经济参数变更并生效 (Tier 覆盖与奖金上限须传入对应奖品池)
+    pub fn confirm_economics_change(ctx: Context<ConfirmEconomicsChange>) -> Result<()> {
+        instructions::admin::economics::confirm_economics_change(ctx)
+    }
+
+    /// 取消暂存的经济参数变更 (有效期内仅管理员或 co-admin；过期后任何人可关闭)
+    pub fn cancel_economics_change(ctx: Context<CancelEconomicsChange>) -> Result<()> {
+        instructions::admin::economics::cancel_economics_change(ctx)
+    }
+
+    /// 设置 VRF 请求费用由 Vault (true) 还是用户 (false) 支付
+    pub fn set_vrf_fee_payer(ctx: Context<UpdateConfig>, vault_pays_vrf_fee: bool) -> Result<()> {
+        instructions::admin::settings::set_vrf_fee_payer(ctx, vault_pays_vrf_fee)
+    }
+
+    /// 设置 Pyth 价格最大有效期 (秒，范围 [10, 3600])
+    pub fn update_max_price_age(ctx: Context<UpdateConfig>, max_price_age_seconds: u64) -> Result<()> {
+        instructions::admin::settings::update_max_price_age(ctx, max_price_age_seconds)
+    }
+
+    /// 设置 24 小时发放上限 (micro-USD)，0 表示取消上限
+    pub fn update_daily_payout_cap(
+        ctx: Context<UpdateConfig>,
+        daily_payout_cap_usd: u64,
+    ) -> Result<()> {
+        instructions::admin::settings::update_daily_payout_cap(ctx, daily_payout_cap_usd)
+    }
+
+    /// 设置平台手续费收取时机 (0 铸造时 / 1 领取时)，仅影响新创建的请求
+    pub fn update_fee_policy(ctx: Context<UpdateConfig>, fee_policy: u8) -> Result<()> {
+        instructions::admin::settings::update_fee_policy(ctx, fee_policy)
+    }
+
+    /// 设置大额中奖人工审核阈值 (micro-USD)，0 表示关闭审核
+    pub fn update_large_win_threshold(
+        ctx: Context<UpdateConfig>,
+        large_win_threshold_usd: u64,
+    ) -> Result<()> {
+        instructions::admin::settings::update_large_win_threshold(ctx, large_win_threshold_usd)
+    }
+
+    /// 添加稳定币支付 Mint 到白名单 (精度从 Mint 账户读取，最多 4 个)
+    pub fn add_payment_mint(ctx: Context<AddPaymentMint>) -> Result<()> {
+        instructions::admin::settings::add_payment_mint(ctx)
+    }
+
+    /// 启用/停用白名单中的稳定币支付 Mint
+    pub fn set_payment_mint_enabled(
+        ctx: Context<UpdateConfig>,
+        mint: Pubkey,
+        enabled: bool,
+    ) -> Result<()> {
+        instructions::admin::settings::set_payment_mint_enabled(ctx, mint, enabled)
+    }
+
+    /// 添加 VRF Oracle Queue 到白名单 (最多 4 个，用于前端故障切换)
+    pub fn add_oracle_queue(ctx: Context<UpdateConfig>, queue: Pubkey) -> Result<()> {
+        instructions::admin::settings::add_oracle_queue(ctx, queue)
+    }
+
+    /// 从白名单移除 VRF Oracle Queue (至少保留一个)
+    pub fn remove_oracle_queue(ctx: Context<UpdateConfig>, queue: Pubkey) -> Result<()> {
+        instructions::admin::settings::remove_oracle_queue(ctx, queue)
+    }
+
+    // ==================== 大额中奖审核 ====================
+
+    /// 审核通过大额中奖，用户可正常领取
+    /// - vrf_request_slot: VRF 请求时的 slot (用于 PDA 派生)
+    pub fn approve_large_win(ctx: Context<ApproveLargeWin>, _vrf_request_slot: u64) -> Result<()> {
+        instructions::admin::review::approve_large_win(ctx)
+    }
+
+    /// 拒绝大额中奖：请求置为 Failed，本金退还支付者并关闭 PDA
+    /// - vrf_request_slot: VRF 请求时的 slot (用于 PDA 派生)
+    pub fn reject_large_win(ctx: Context<RejectLargeWin>, _vrf_request_slot: u64) -> Result<()> {
+        instructions::admin::review::reject_large_win(ctx)
+    }
+
+    /// 顺延已揭示请求的领取期限 (只能向后推)
+    /// - request_slot: 主请求的 VRF 请求 slot (用于 PDA 派生)
+    /// - new_deadline: 新的领取截止时间戳
+    /// - remaining_accounts: 可选，一并顺延的其他 MintRequest (合计最多 20 个)
+    pub fn extend_claim_deadline<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExtendClaimDeadline<'info>>,
+        _request_slot: u64,
+        new_deadline: i64,
+    ) -> Result<()> {
+        instructions::admin::review::extend_claim_deadline(ctx, new_deadline)
+    }
+
+    // ==================== 排行榜 ====================
+
+    /// 创建单笔中奖排行榜 PDA (仅管理员)
+    pub fn init_leaderboard(ctx: Context<InitLeaderboard>) -> Result<()> {
+        instructions::admin::leaderboard::init_leaderboard(ctx)
+    }
+
+    /// 清空排行榜并开启新赛季 (仅管理员)
+    pub fn reset_leaderboard(ctx: Context<ResetLeaderboard>) -> Result<()> {
+        instructions::admin::leaderboard::reset_leaderboard(ctx)
+    }
+
+    // ==================== VRF Queue 统计 ====================
+
+    /// 创建或清零 Oracle Queue 回调延迟统计 PDA (仅管理员)
+    /// - queue: 统计的 Oracle Queue
+    pub fn reset_queue_stats(ctx: Context<ResetQueueStats>, queue: Pubkey) -> Result<()> {
+        instructions::admin::queue_stats::reset_queue_stats(ctx, queue)
+    }
+
     // ==================== 奖品池管理 (Task 3.3) ====================
 
     /// 添加奖品池
     /// - swap_pool: 交易对地址 (Raydium Pool / Jupiter Route)
     /// - pool_type: 池子类型
     /// - name: 显示名称 (最长 16 字节)
+    /// - max_win_usd: Token 模式奖金上限 (micro-USD)，0 表示不限制
+    /// - active_from / active_until: 排期窗口时间戳，0 表示不限制
+    /// - symbol / metadata_uri: 可选，Token 符号 (最长 10 字节) 与元数据 URI (最长 100 字节)
+    #[allow(clippy::too_many_arguments)]
     pub fn add_prize_pool(
         ctx: Context<AddPrizePool>,
         swap_pool: Pubkey,
         pool_type: PoolType,
         name: String,
+        max_win_usd: u64,
+        active_from: i64,
+        active_until: i64,
+        symbol: Option<String>,
+        metadata_uri: Option<String>,
     ) -> Result<()> {
-        instructions::admin::prize_pool::add_prize_pool(ctx, swap_pool, pool_type, name)
+        instructions::admin::prize_pool::add_prize_pool(
+            ctx,
+            swap_pool,
+            pool_type,
+            name,
+            max_win_usd,
+            active_from,
+            active_until,
+            symbol,
+            metadata_uri,
+        )
     }
 
     /// 硬删除奖品池（关闭 PDA，退还租金）
-    pub fn remove_prize_pool(ctx: Context<RemovePrizePool>) -> Result<()> {
-        instructions::admin::prize_pool::remove_prize_pool(ctx)
+    /// - force: 存在待领取请求时是否强制删除 (会发出警告事件)
+    pub fn remove_prize_pool(ctx: Context<RemovePrizePool>, force: bool) -> Result<()> {
+        instructions::admin::prize_pool::remove_prize_pool(ctx, force)
+    }
+
+    /// 生效已暂存的奖品池 swap_pool 变更 (仅管理员，须已到 effective_at)
+    pub fn apply_prize_pool_update(ctx: Context<ApplyPrizePoolUpdate>) -> Result<()> {
+        instructions::admin::prize_pool::apply_prize_pool_update(ctx)
+    }
+
+    /// 迁移/扩容奖品池账户 (仅管理员)
+    /// - index: 奖品池索引
+    pub fn migrate_prize_pool(ctx: Context<MigratePrizePool>, index: u8) -> Result<()> {
+        instructions::admin::prize_pool::migrate_prize_pool(ctx, index)
     }
 
     /// 更新奖品池
-    /// - swap_pool: 可选，新的交易对地址
+    /// - swap_pool: 可选，新的交易对地址 (pool_change_delay_seconds 非 0 时暂存，到期后由 apply_prize_pool_update 生效)
     /// - pool_type: 可选，新的池子类型
     /// - name: 可选，新的显示名称
+    /// - max_win_usd: 可选，新的 Token 模式奖金上限 (0 表示不限制)
+    /// - active_from / active_until: 可选，新的排期窗口 (0 表示不限制)
+    /// - symbol / metadata_uri: 可选，新的 Token 符号与元数据 URI (空字符串表示清除)
+    /// - tier_override: 可选，新的 Tier 累积阈值表 (全 0 表示恢复默认概率)
+    #[allow(clippy::too_many_arguments)]
     pub fn update_prize_pool(
         ctx: Context<UpdatePrizePool>,
         swap_pool: Option<Pubkey>,
         pool_type: Option<PoolType>,
         name: Option<String>,
+        max_win_usd: Option<u64>,
+        active_from: Option<i64>,
+        active_until: Option<i64>,
+        symbol: Option<String>,
+        metadata_uri: Option<String>,
+        tier_override: Option<[u64; 4]>,
+    ) -> Result<()> {
+        instructions::admin::prize_pool::update_prize_pool(
+            ctx,
+            swap_pool,
+            pool_type,
+            name,
+            max_win_usd,
+            active_from,
+            active_until,
+            symbol,
+            metadata_uri,
+            tier_override,
+        )
+    }
+
+    /// 重建活跃奖品池索引 (完整性修复，仅管理员)
+    /// - remaining_accounts: 所有现存奖品池 PDA
+    pub fn rebuild_active_pools<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RebuildActivePools<'info>>,
+    ) -> Result<()> {
+        instructions::admin::prize_pool::rebuild_active_pools(ctx)
+    }
+
+    /// 将揭示时累计的奖品池分配统计同步到奖品池 PDA (仅管理员)
+    /// - remaining_accounts: 需要同步的奖品池 PDA (可写)
+    pub fn flush_pool_stats<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FlushPoolStats<'info>>,
     ) -> Result<()> {
-        instructions::admin::prize_pool::update_prize_pool(ctx, swap_pool, pool_type, name)
+        instructions::admin::prize_pool::flush_pool_stats(ctx)
+    }
+
+    /// 查询活跃奖品池列表 (只读，供 simulateTransaction 读取 return data)
+    /// - remaining_accounts: 按 active_pool_indices 顺序传入的奖品池 PDA
+    pub fn get_active_pools<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetActivePools<'info>>,
+    ) -> Result<instructions::user::active_pools::ActivePoolList> {
+        instructions::user::active_pools::get_active_pools(ctx)
+    }
+
+    /// 健康检查 (只读，供 simulateTransaction 读取 return data)
+    /// - strict: 为 true 时任一不变量被破坏即失败，可作为 keeper 的链上断言
+    /// - remaining_accounts: 可选，Vault 持有的稳定币 Token 账户
+    pub fn health_check<'info>(
+        ctx: Context<'_, '_, 'info, 'info, HealthCheck<'info>>,
+        strict: bool,
+    ) -> Result<instructions::user::health::HealthReport> {
+        instructions::user::health::health_check(ctx, strict)
+    }
+
+    /// 偿付能力核对 (只读，任何人可调用，供 keeper 定期 simulateTransaction)
+    /// Vault 可用余额 + 托管合计须覆盖未领取负债，失败时错误码指明被破坏的账本项
+    pub fn assert_solvency(ctx: Context<AssertSolvency>) -> Result<()> {
+        instructions::user::health::assert_solvency(ctx)
+    }
+
+    /// 查询配置视图 (只读，CPI 调用方从 return data 以 ConfigView::decode 读取)
+    pub fn get_config(ctx: Context<GetConfig>) -> Result<ConfigView> {
+        instructions::user::config_view::get_config(ctx)
+    }
+
+    /// 查询理论与实际 RTP (只读，结果写入 return data)
+    pub fn get_rtp_info(ctx: Context<GetRtpInfo>) -> Result<instructions::user::rtp_info::RtpInfo> {
+        instructions::user::rtp_info::get_rtp_info(ctx)
     }
 }
 
@@ -154,6 +840,14 @@ pub struct Initialize<'info> {
     )]
     pub config: Account<'info, IPFlowState>,
 
+    /// 程序金库 PDA (0 字节 System 账户)，初始化时由 admin 注资至免租金额
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -174,192 +868,1590 @@ pub struct MigrateConfig<'info> {
 }
 
 #[derive(Accounts)]
-pub struct CloseConfig<'info> {
+pub struct CloseConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// 当前 co-admin (已配置时必须签名，handler 中与原始数据中的 co_admin 比对)
+    pub co_admin: Option<Signer<'info>>,
+
+    /// CHECK: 关闭过程中需要兼容旧版结构，手动校验 admin
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// AddPaymentMint: 添加稳定币支付 Mint 到白名单 (仅管理员)
+#[derive(Accounts)]
+pub struct AddPaymentMint<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 待添加的 Mint (读取 decimals)
+    pub mint: Account<'info, Mint>,
+}
+
+/// InitVaultTokenAccount: 创建 Vault 对白名单支付 Mint 的 ATA (仅管理员)
+#[derive(Accounts)]
+pub struct InitVaultTokenAccount<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 程序金库 PDA (ATA authority)
+    #[account(
+        seeds = [b"vault"],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// 白名单中的支付 Mint
+    #[account(
+        constraint = config.find_payment_mint(&usdt_mint.key()).is_some()
+            @ errors::IPFlowError::PaymentMintNotWhitelisted
+    )]
+    pub usdt_mint: Account<'info, Mint>,
+
+    /// Vault 对该 Mint 的 ATA (可能尚未创建)
+    /// CHECK: 地址校验为 (vault, usdt_mint) 的 ATA，由 associated token program 创建
+    #[account(
+        mut,
+        address = anchor_spl::associated_token::get_associated_token_address(&vault.key(), &usdt_mint.key())
+            @ errors::IPFlowError::InvalidTokenAccount
+    )]
+    pub vault_token_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// InitVaultWsol: 创建 Vault 的 WSOL ATA 并登记到全局配置 (仅管理员)
+#[derive(Accounts)]
+pub struct InitVaultWsol<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 程序金库 PDA (ATA authority)
+    #[account(
+        seeds = [b"vault"],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// WSOL Mint
+    #[account(address = constants::NATIVE_SOL_MINT @ errors::IPFlowError::InvalidTokenAccount)]
+    pub wsol_mint: Account<'info, Mint>,
+
+    /// Vault 的 WSOL ATA (可能尚未创建)
+    /// CHECK: 地址校验为 (vault, WSOL) 的 ATA，由 associated token program 创建
+    #[account(
+        mut,
+        address = anchor_spl::associated_token::get_associated_token_address(&vault.key(), &wsol_mint.key())
+            @ errors::IPFlowError::InvalidTokenAccount
+    )]
+    pub vault_wsol_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// InitProgramIdentity: 初始化 VRF 程序身份 PDA (仅管理员)
+#[derive(Accounts)]
+pub struct InitProgramIdentity<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 程序身份 PDA (0 字节 System 账户，bump 由 Anchor 规范派生)
+    #[account(mut, seeds = [b"identity"], bump)]
+    pub program_identity: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount_of_cards: u32, payment_mode: PaymentMode, client_seed: u8, request_slot: u64, beneficiary: Option<Pubkey>)]
+pub struct RequestMint<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// 全局配置 (可写：Vault 支付 VRF 费用时累计 total_vrf_fees_lamports)
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = !config.is_mint_paused() @ errors::IPFlowError::ProgramPaused
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 程序金库，用于接收 SOL 及 (可选) 支付 VRF 费用
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// MintRequest PDA - 使用受益人地址与 request_slot 作为种子
+    /// request_slot 由前端传入，合约内验证是否为当前 slot；未指定 beneficiary 时受益人为 user
+    /// init_if_needed: 同一 slot 的重复请求由 handler 返回 DuplicateRequestInSlot，而非 Anchor 的 "already in use"
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + MintRequest::INIT_SPACE,
+        seeds = [constants::SEED_MINT_REQUEST, beneficiary.as_ref().unwrap_or(user.key).as_ref(), &request_slot.to_le_bytes()],
+        bump
+    )]
+    pub mint_request: Account<'info, MintRequest>,
+
+    /// MagicBlock VRF Oracle Queue
+    /// CHECK: 由前端传入正确的 Queue 地址
+    #[account(mut)]
+    pub oracle_queue: AccountInfo<'info>,
+
+    /// 程序身份 PDA - 用于 VRF 请求签名
+    /// CHECK: Seeds 验证 (使用 config 中记录的 bump)
+    #[account(seeds = [b"identity"], bump = config.identity_bump)]
+    pub program_identity: AccountInfo<'info>,
+
+    /// VRF 程序
+    /// CHECK: handler 中与 config.vrf_program 比对 (默认 SDK 常量，可由管理员更新)
+    pub vrf_program: AccountInfo<'info>,
+
+    /// Slot Hashes Sysvar
+    /// CHECK: 地址验证确保是 SlotHashes sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    pub slot_hashes: AccountInfo<'info>,
+
+    /// Pyth 价格数据账户 (SOL 支付时必需)
+    #[account(owner = pyth_solana_receiver_sdk::ID @ errors::IPFlowError::InvalidPythAccountOwner)]
+    pub pyth_price_update: Account<'info, PriceUpdateV2>,
+
+    pub system_program: Program<'info, System>,
+
+    // ==================== USDT / WSOL 支付相关账户 (可选) ====================
+    /// Token Program (USDT / WSOL 支付时必需)
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// 稳定币 Mint 账户 (稳定币支付时必需，须在 config.payment_mints 白名单中；WSOL 支付时传 NATIVE_SOL_MINT)
+    pub usdt_mint: Option<Account<'info, Mint>>,
+
+    /// 用户的 USDT / WSOL Token 账户 (对应支付方式时必需)
+    #[account(mut)]
+    pub user_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// 协议的稳定币 Token 账户 (稳定币支付时必需，mint 在 handler 中校验)
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == vault.key() @ errors::IPFlowError::InvalidTokenAccount
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// WSOL 支付的临时 Token 账户 (WSOL 支付时必需，须为空账户；指令内创建、转入后解包关闭到 Vault)
+    /// CHECK: 种子校验；由 handler 创建并初始化为 Vault 持有的 WSOL 账户
+    #[account(
+        mut,
+        seeds = [constants::SEED_WSOL_PAYMENT, mint_request.key().as_ref()],
+        bump
+    )]
+    pub wsol_payment_account: Option<UncheckedAccount<'info>>,
+
+    /// 当前 epoch 统计 PDA (start_epoch 之后必需)
+    #[account(
+        mut,
+        seeds = [constants::SEED_EPOCH, &config.current_epoch.to_le_bytes()],
+        bump = epoch_stats.bump
+    )]
+    pub epoch_stats: Option<Account<'info, EpochStats>>,
+
+    /// 推荐人奖励余额 PDA (可选，传入且 referral_bps > 0 时累积推荐返佣)
+    #[account(
+        mut,
+        seeds = [constants::SEED_REWARDS, referrer_rewards.user.as_ref()],
+        bump = referrer_rewards.bump
+    )]
+    pub referrer_rewards: Option<Account<'info, RewardsAccount>>,
+}
+
+/// ConsumeLotteryRandomness: VRF 回调处理
+/// 由 MagicBlock VRF 程序自动调用，不应由用户直接调用
+/// 不传入全局配置：开奖条款取自请求创建时的快照，揭示结算由 continue_reveal 完成
+#[derive(Accounts)]
+pub struct ConsumeLotteryRandomness<'info> {
+    /// VRF 程序身份 PDA - 验证调用来源
+    /// 只有 MagicBlock VRF 程序可以调用此指令
+    /// CHECK: handler 中与 mint_request.vrf_identity 比对 (请求创建时的 config.vrf_identity，默认 VRF_PROGRAM_IDENTITY)
+    pub vrf_program_identity: Signer<'info>,
+
+    /// MintRequest 账户 - 通过 callback_accounts_metas 传入
+    /// 状态校验在 handler 中进行 (Revealing / Revealed 幂等返回，其余非 Pending 状态返回具体错误码)
+    #[account(mut)]
+    pub mint_request: Account<'info, MintRequest>,
+
+    /// 受益人 UserStats PDA - 读取保底计数器；未创建或未迁移时按计数 0 处理且保底不生效
+    /// CHECK: 种子校验；handler 中仅在 owner 为本程序且为当前结构大小时按 UserStats 反序列化
+    #[account(seeds = [constants::SEED_USER_STATS, mint_request.user.as_ref()], bump)]
+    pub user_stats: UncheckedAccount<'info>,
+
+    /// 请求所用 Oracle Queue 的延迟统计 PDA - 尚未 reset_queue_stats 创建时跳过统计
+    /// CHECK: 种子校验；handler 中仅在 owner 为本程序时按 QueueStats 反序列化
+    #[account(mut, seeds = [constants::SEED_QUEUE_STATS, mint_request.oracle_queue.as_ref()], bump)]
+    pub queue_stats: UncheckedAccount<'info>,
+}
+
+/// TestReveal: 管理员以指定随机数揭示请求 (仅 test-hooks 构建)
+/// 包含 ConsumeLotteryRandomness 与 ContinueReveal 的全部账户 (回调后直接完成揭示结算)
+#[cfg(feature = "test-hooks")]
+#[derive(Accounts)]
+pub struct TestReveal<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub mint_request: Account<'info, MintRequest>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// CHECK: 种子校验；handler 中仅在 owner 为本程序时按 Leaderboard 反序列化
+    #[account(mut, seeds = [constants::SEED_LEADERBOARD], bump)]
+    pub leaderboard: UncheckedAccount<'info>,
+
+    /// CHECK: 种子校验；handler 中仅在 owner 为本程序且为当前结构大小时按 UserStats 反序列化
+    #[account(mut, seeds = [constants::SEED_USER_STATS, mint_request.user.as_ref()], bump)]
+    pub user_stats: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"vault"], bump = config.vault_bump)]
+    pub vault: SystemAccount<'info>,
+
+    #[account(owner = pyth_solana_receiver_sdk::ID @ errors::IPFlowError::InvalidPythAccountOwner)]
+    pub pyth_price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(mut, seeds = [constants::SEED_ESCROW, mint_request.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
+
+    /// CHECK: 种子校验；handler 中仅在 owner 为本程序时按 QueueStats 反序列化
+    #[account(mut, seeds = [constants::SEED_QUEUE_STATS, mint_request.oracle_queue.as_ref()], bump)]
+    pub queue_stats: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// ContinueReveal: 续算分批揭示 (任何人可调用)
+#[derive(Accounts)]
+pub struct ContinueReveal<'info> {
+    /// 任意调用者 (通常为 keeper)
+    pub cranker: Signer<'info>,
+
+    /// MintRequest 账户 - handler 中校验为 Revealing 状态
+    #[account(mut)]
+    pub mint_request: Account<'info, MintRequest>,
+
+    /// 全局配置 - 揭示结算时校验 Oracle Queue，将待揭示引用转为选中池的待领取计数
+    #[account(mut, seeds = [constants::SEED_GLOBAL_CONFIG], bump)]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 单笔中奖排行榜 PDA - 尚未 init_leaderboard 时跳过更新
+    /// CHECK: 种子校验；handler 中仅在 owner 为本程序时按 Leaderboard 反序列化
+    #[account(mut, seeds = [constants::SEED_LEADERBOARD], bump)]
+    pub leaderboard: UncheckedAccount<'info>,
+
+    /// 受益人 UserStats PDA - 揭示完成时回写保底计数器
+    /// CHECK: 种子校验；handler 中仅在 owner 为本程序且为当前结构大小时按 UserStats 反序列化
+    #[account(mut, seeds = [constants::SEED_USER_STATS, mint_request.user.as_ref()], bump)]
+    pub user_stats: UncheckedAccount<'info>,
+
+    /// Vault PDA - 读取余额计算 RTP 调节系数，揭示完成时划出中奖托管资金
+    #[account(mut, seeds = [b"vault"], bump = config.vault_bump)]
+    pub vault: SystemAccount<'info>,
+
+    /// Pyth 价格数据账户 - 揭示完成时换算托管金额
+    #[account(owner = pyth_solana_receiver_sdk::ID @ errors::IPFlowError::InvalidPythAccountOwner)]
+    pub pyth_price_update: Account<'info, PriceUpdateV2>,
+
+    /// 中奖托管 PDA (0 字节 System 账户，揭示完成时由 Vault 转入)
+    #[account(mut, seeds = [constants::SEED_ESCROW, mint_request.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Claim: 用户领取奖励 (选择 SOL 或 Token)
+/// Task 1.14: claim 完成后自动关闭 MintRequest PDA，退还租金给用户
+#[derive(Accounts)]
+#[instruction(payout_mode: PayoutMode, swap_router: Option<SwapRouter>, expected_token_output: Option<u64>, swap_data: Option<Vec<u8>>, vrf_request_slot: u64, fallback_to_sol: bool, create_receipt: bool, sol_portion_bps: u16)]
+pub struct Claim<'info> {
+    /// 交易签名者：user 本人或持有有效委托的会话密钥
+    /// 同时作为 ATA 创建和 ClaimReceipt 租金的 payer
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// 奖金受益人 (mint_request.user)，SOL 奖金、Token 输出及 PDA 租金均发往该地址
+    #[account(mut)]
+    pub user: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        // 不使用 close = user：handler 在全部转账成功且状态为 Claimed 后手动关闭，租金退还给 user
+        seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        bump,
+        has_one = user @ errors::IPFlowError::Unauthorized,
+        constraint = mint_request.vrf_request_slot == vrf_request_slot @ errors::IPFlowError::InvalidRequestStatus
+    )]
+    pub mint_request: Account<'info, MintRequest>,
+
+    /// 全局配置 (可写：领取后扣减选中池的待领取计数)
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = !config.is_claim_blocked() @ errors::IPFlowError::ClaimsPaused
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 程序金库，用于支付 SOL 奖金
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// 中奖托管 PDA - 领取时全部退回 Vault，再按揭示时锁定的汇率发放
+    #[account(mut, seeds = [constants::SEED_ESCROW, mint_request.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
+
+    /// Pyth 价格数据账户 (揭示时未托管的旧请求按实时价格换算)
+    #[account(owner = pyth_solana_receiver_sdk::ID @ errors::IPFlowError::InvalidPythAccountOwner)]
+    pub pyth_price_update: Account<'info, PriceUpdateV2>,
+
+    pub system_program: Program<'info, System>,
+
+    // ==================== Token 模式输出账户（可选）====================
+    /// Token Program (Token 模式必需，用于创建用户输出 ATA)
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// Associated Token Program (Token 模式必需)
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    /// 奖品 Token Mint (Token 模式必需)
+    pub token_mint: Option<Account<'info, Mint>>,
+
+    /// 用户的奖品 Token ATA (Token 模式必需，不存在时由合约以 authority 为 payer 幂等创建)
+    /// CHECK: handler 中校验地址等于 (user, token_mint) 派生的 ATA
+    #[account(mut)]
+    pub user_token_account: Option<UncheckedAccount<'info>>,
+    /// 用户统计 PDA (可选，开启零头累积时 SOL 领取必需)
+    #[account(
+        mut,
+        seeds = [constants::SEED_USER_STATS, user.key().as_ref()],
+        bump = user_stats.bump
+    )]
+    pub user_stats: Option<Account<'info, UserStats>>,
+
+    /// 选中的奖品池 PDA (可选，传入时累计 swap 统计)
+    #[account(
+        mut,
+        seeds = [constants::SEED_PRIZE_POOL, &[mint_request.selected_pool_index]],
+        bump = prize_pool.bump
+    )]
+    pub prize_pool: Option<Account<'info, PrizePoolAccount>>,
+
+    /// 领取委托 PDA (可选，authority 为会话密钥时必需)
+    #[account(
+        seeds = [constants::SEED_DELEGATE, user.key().as_ref()],
+        bump = delegation.bump,
+        has_one = user @ errors::IPFlowError::Unauthorized
+    )]
+    pub delegation: Option<Account<'info, Delegation>>,
+
+    /// 领取凭证 PDA (可选，create_receipt = true 时必需)
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ClaimReceipt::INIT_SPACE,
+        seeds = [constants::SEED_CLAIM_RECEIPT, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        bump
+    )]
+    pub receipt: Option<Account<'info, ClaimReceipt>>,
+
+    /// 当前 epoch 统计 PDA (start_epoch 之后必需)
+    #[account(
+        mut,
+        seeds = [constants::SEED_EPOCH, &config.current_epoch.to_le_bytes()],
+        bump = epoch_stats.bump
+    )]
+    pub epoch_stats: Option<Account<'info, EpochStats>>,
+
+    /// 用户奖励余额 PDA (可选，开启零头累积时优先将 SOL 零头按美元累积到此账户)
+    #[account(
+        mut,
+        seeds = [constants::SEED_REWARDS, user.key().as_ref()],
+        bump = rewards_account.bump
+    )]
+    pub rewards_account: Option<Account<'info, RewardsAccount>>,
+
+    /// 手续费金库 PDA (可选，FEE_POLICY_ON_CLAIM 的请求且手续费大于 0 时必需)
+    #[account(mut, seeds = [constants::SEED_FEE_VAULT], bump = config.fee_vault_bump)]
+    pub fee_vault: Option<SystemAccount<'info>>,
+
+    // ==================== WSOL 包装发放账户 (可选，wrap_payout = true 时必需) ====================
+    /// Native SOL Mint (创建用户 WSOL ATA 使用)
+    #[account(address = constants::NATIVE_SOL_MINT @ errors::IPFlowError::InvalidWsolMint)]
+    pub wsol_mint: Option<Account<'info, Mint>>,
+
+    /// 用户的 WSOL ATA (不存在时由合约以 authority 为 payer 幂等创建)
+    /// CHECK: handler 中校验地址等于 (user, NATIVE_SOL_MINT) 派生的 ATA
+    #[account(mut)]
+    pub user_wsol_account: Option<UncheckedAccount<'info>>,
+}
+
+/// RecordPayoutBlock: 登记发放熔断导致的领取期限顺延
+#[derive(Accounts)]
+#[instruction(vrf_request_slot: u64)]
+pub struct RecordPayoutBlock<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        bump,
+        has_one = user @ errors::IPFlowError::Unauthorized,
+        constraint = mint_request.vrf_request_slot == vrf_request_slot @ errors::IPFlowError::InvalidRequestStatus
+    )]
+    pub mint_request: Account<'info, MintRequest>,
+
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump
+    )]
+    pub config: Account<'info, IPFlowState>,
+}
+
+/// ReportSwapFailure: 登记 Token 领取的 swap 失败
+#[derive(Accounts)]
+#[instruction(vrf_request_slot: u64)]
+pub struct ReportSwapFailure<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        bump,
+        has_one = user @ errors::IPFlowError::Unauthorized,
+        constraint = mint_request.vrf_request_slot == vrf_request_slot @ errors::IPFlowError::InvalidRequestStatus
+    )]
+    pub mint_request: Account<'info, MintRequest>,
+
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump
+    )]
+    pub config: Account<'info, IPFlowState>,
+}
+
+/// PreAuthorizeClaim: 写入或清除 Token 领取预授权 (仅用户本人)
+#[derive(Accounts)]
+#[instruction(vrf_request_slot: u64)]
+pub struct PreAuthorizeClaim<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        bump,
+        has_one = user @ errors::IPFlowError::Unauthorized,
+        constraint = mint_request.vrf_request_slot == vrf_request_slot @ errors::IPFlowError::InvalidRequestStatus
+    )]
+    pub mint_request: Account<'info, MintRequest>,
+}
+
+/// SetDelegate: 创建领取委托 PDA
+#[derive(Accounts)]
+pub struct SetDelegate<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Delegation::INIT_SPACE,
+        seeds = [constants::SEED_DELEGATE, user.key().as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// RevokeDelegate: 撤销领取委托
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [constants::SEED_DELEGATE, user.key().as_ref()],
+        bump = delegation.bump,
+        has_one = user @ errors::IPFlowError::Unauthorized
+    )]
+    pub delegation: Account<'info, Delegation>,
+}
+
+/// CloseReceipt: 关闭领取凭证 PDA
+#[derive(Accounts)]
+#[instruction(vrf_request_slot: u64)]
+pub struct CloseReceipt<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [constants::SEED_CLAIM_RECEIPT, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        bump = receipt.bump,
+        has_one = user @ errors::IPFlowError::Unauthorized
+    )]
+    pub receipt: Account<'info, ClaimReceipt>,
+}
+
+/// CleanupOutputAccount: 关闭领取凭证记录的、由合约新建的空输出 Token 账户
+#[derive(Accounts)]
+#[instruction(vrf_request_slot: u64)]
+pub struct CleanupOutputAccount<'info> {
+    /// 输出账户所有者，接收回收的租金
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// 领取凭证 (记录本次领取新建的输出账户)
+    #[account(
+        mut,
+        seeds = [constants::SEED_CLAIM_RECEIPT, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        bump = receipt.bump,
+        has_one = user @ errors::IPFlowError::Unauthorized
+    )]
+    pub receipt: Account<'info, ClaimReceipt>,
+
+    /// 待关闭的输出 Token 账户 (须由 user 持有且余额为 0)
+    #[account(
+        mut,
+        constraint = output_token_account.owner == user.key() @ errors::IPFlowError::InvalidTokenAccount
+    )]
+    pub output_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Refund: 超时退款 (Task 2.3)
+/// 当 MintRequest 处于 Pending 状态超过 10 分钟时，用户可申请退款
+/// - SOL 退款: 仅需基础账户
+/// - USDT 退款: 需要额外传入 Token 账户
+#[derive(Accounts)]
+#[instruction(vrf_request_slot: u64)]
+pub struct Refund<'info> {
+    /// 支付本金的钱包 (mint_request.payer)，接收退款及 PDA 租金
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// 奖金受益人 (PDA 种子)，非代付时与 user 相同
+    /// CHECK: 仅用于 PDA 派生，由 mint_request.user 约束
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = user,  // 关闭账户，租金退还给支付者
+        seeds = [constants::SEED_MINT_REQUEST, beneficiary.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        bump,
+        constraint = mint_request.user == beneficiary.key() @ errors::IPFlowError::Unauthorized,
+        constraint = mint_request.payer == user.key() @ errors::IPFlowError::Unauthorized,
+        constraint = mint_request.vrf_request_slot == vrf_request_slot @ errors::IPFlowError::InvalidRequestStatus
+    )]
+    pub mint_request: Account<'info, MintRequest>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = !config.is_refund_paused() @ errors::IPFlowError::RefundsPaused
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 程序金库，用于退还 SOL
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // ==================== USDT 退款专用账户（可选）====================
+
+    /// Token Program (USDT 退款时必需)
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// 请求记录的稳定币 Mint (USDT 退款时必需，transfer_checked 读取精度)
+    #[account(
+        constraint = payment_mint.key() == mint_request.payment_mint @ errors::IPFlowError::InvalidUsdtMint
+    )]
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    /// Vault 的稳定币 Token 账户 (USDT 退款时必需，mint 须为 mint_request.payment_mint)
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == vault.key() @ errors::IPFlowError::InvalidTokenAccount
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// 用户的 USDT Token 账户 (USDT 退款时必需)
+    #[account(mut)]
+    pub user_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// 当前 epoch 统计 PDA (start_epoch 之后必需)
+    #[account(
+        mut,
+        seeds = [constants::SEED_EPOCH, &config.current_epoch.to_le_bytes()],
+        bump = epoch_stats.bump
+    )]
+    pub epoch_stats: Option<Account<'info, EpochStats>>,
+}
+
+/// RefundFromAlternate: 从备用 Vault Token 账户退款 (仅管理员，原收款账户已轮换)
+#[derive(Accounts)]
+#[instruction(vrf_request_slot: u64)]
+pub struct RefundFromAlternate<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized,
+        constraint = !config.is_refund_paused() @ errors::IPFlowError::RefundsPaused
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 奖金受益人 (PDA 种子)
+    /// CHECK: 仅用于 PDA 派生，由 mint_request.user 约束
+    pub user: UncheckedAccount<'info>,
+
+    /// 支付本金的钱包，接收 PDA 租金
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = payer,
+        seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        bump,
+        has_one = user @ errors::IPFlowError::Unauthorized,
+        has_one = payer @ errors::IPFlowError::Unauthorized
+    )]
+    pub mint_request: Account<'info, MintRequest>,
+
+    /// 程序金库 (Token 转账签名)
+    #[account(
+        seeds = [b"vault"],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// 支付时收款的 Vault Token 账户 (可能已关闭)，须已无法承担退款
+    /// CHECK: 地址由 mint_request.vault_token_account 约束，handler 中按 Token 账户解析余额
+    #[account(address = mint_request.vault_token_account @ errors::IPFlowError::RefundSourceMismatch)]
+    pub original_vault_token_account: UncheckedAccount<'info>,
+
+    /// 实际转出退款的 Vault Token 账户 (mint 须为 mint_request.payment_mint)
+    #[account(
+        mut,
+        constraint = alternate_vault_token_account.owner == vault.key() @ errors::IPFlowError::InvalidTokenAccount,
+        constraint = alternate_vault_token_account.mint == mint_request.payment_mint @ errors::IPFlowError::InvalidTokenAccount
+    )]
+    pub alternate_vault_token_account: Account<'info, TokenAccount>,
+
+    /// 请求记录的支付 Mint (transfer_checked 读取精度)
+    #[account(
+        constraint = payment_mint.key() == mint_request.payment_mint @ errors::IPFlowError::InvalidUsdtMint
+    )]
+    pub payment_mint: Account<'info, Mint>,
+
+    /// 支付者的 Token 账户，接收退款
+    #[account(mut)]
+    pub payer_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// 当前 epoch 统计 PDA (start_epoch 之后必需)
+    #[account(
+        mut,
+        seeds = [constants::SEED_EPOCH, &config.current_epoch.to_le_bytes()],
+        bump = epoch_stats.bump
+    )]
+    pub epoch_stats: Option<Account<'info, EpochStats>>,
+}
+
+/// CrankExpirePending: 过期 Pending 请求清理 (permissionless)
+#[derive(Accounts)]
+#[instruction(vrf_request_slot: u64)]
+pub struct CrankExpirePending<'info> {
+    /// 任意调用者，接收小费
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// 奖金受益人 (PDA 种子)
+    pub user: SystemAccount<'info>,
+
+    /// 支付本金的钱包，接收退款及 PDA 租金
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = payer,  // 关闭账户，租金退还给支付者
+        seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        bump,
+        has_one = user @ errors::IPFlowError::Unauthorized,
+        has_one = payer @ errors::IPFlowError::Unauthorized,
+        constraint = mint_request.vrf_request_slot == vrf_request_slot @ errors::IPFlowError::InvalidRequestStatus
+    )]
+    pub mint_request: Account<'info, MintRequest>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = !config.is_refund_paused() @ errors::IPFlowError::RefundsPaused
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 程序金库，用于退还 SOL 及支付小费
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // ==================== USDT 退款专用账户（可选）====================
+
+    /// Token Program (USDT 退款时必需)
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// 请求记录的稳定币 Mint (USDT 退款时必需，transfer_checked 读取精度)
+    #[account(
+        constraint = payment_mint.key() == mint_request.payment_mint @ errors::IPFlowError::InvalidUsdtMint
+    )]
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    /// Vault 的稳定币 Token 账户 (USDT 退款时必需，mint 须为 mint_request.payment_mint)
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == vault.key() @ errors::IPFlowError::InvalidTokenAccount
+    )]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// 用户的 USDT Token 账户 (USDT 退款时必需)
+    #[account(mut)]
+    pub user_token_account: Option<Account<'info, TokenAccount>>,
+}
+
+/// MigrateMintRequest: 迁移/扩容 MintRequest 账户 (任何人可调用)
+#[derive(Accounts)]
+#[instruction(vrf_request_slot: u64)]
+pub struct MigrateMintRequest<'info> {
+    /// 补足扩容租金差额的调用者
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: 请求的受益人，仅用于 PDA 派生 (handler 中与账户数据比对)
+    pub user: UncheckedAccount<'info>,
+
+    /// 全局配置 - 补齐旧版请求的支付 Mint 与奖金上限
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// CHECK: 迁移过程中需要兼容旧版结构，handler 中校验 owner 与 discriminator
+    #[account(
+        mut,
+        seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        bump
+    )]
+    pub mint_request: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// MintRequest、支付者与 escrow PDA 通过 remaining_accounts 按三个一组传入
+#[derive(Accounts)]
+pub struct SweepExpired<'info> {
+    /// 任意调用者，接收退款请求的小费
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// 全局配置 - 扣减过期中奖的待领取计数与未兑付负债
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = !config.is_refund_paused() @ errors::IPFlowError::RefundsPaused
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 程序金库，用于退还 SOL 及支付小费
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// InitLeaderboard: 创建排行榜 PDA (仅管理员)
+#[derive(Accounts)]
+pub struct InitLeaderboard<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Leaderboard::INIT_SPACE,
+        seeds = [constants::SEED_LEADERBOARD],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// ResetLeaderboard: 清空排行榜 (仅管理员)
+#[derive(Accounts)]
+pub struct ResetLeaderboard<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_LEADERBOARD],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+}
+
+/// ResetQueueStats: 创建或清零 Oracle Queue 延迟统计 (仅管理员)
+#[derive(Accounts)]
+#[instruction(queue: Pubkey)]
+pub struct ResetQueueStats<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + QueueStats::INIT_SPACE,
+        seeds = [constants::SEED_QUEUE_STATS, queue.as_ref()],
+        bump
+    )]
+    pub queue_stats: Account<'info, QueueStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// StartEpoch: 启用 epoch 统计 (仅管理员)
+#[derive(Accounts)]
+pub struct StartEpoch<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + EpochStats::INIT_SPACE,
+        seeds = [constants::SEED_EPOCH, &config.current_epoch.to_le_bytes()],
+        bump
+    )]
+    pub epoch_stats: Account<'info, EpochStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// EmitStats: 发出漏斗计数快照 (permissionless)
+#[derive(Accounts)]
+pub struct EmitStats<'info> {
+    /// 任意调用者
+    pub cranker: Signer<'info>,
+
+    /// 全局配置 (可写：记录 last_stats_ts)
+    #[account(mut, seeds = [constants::SEED_GLOBAL_CONFIG], bump)]
+    pub config: Account<'info, IPFlowState>,
+}
+
+/// RollEpoch: 结算当前 epoch 并创建下一个 (permissionless)
+#[derive(Accounts)]
+pub struct RollEpoch<'info> {
+    /// 任意调用者，支付下一个 EpochStats 的租金
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    #[account(mut, seeds = [constants::SEED_GLOBAL_CONFIG], bump)]
+    pub config: Account<'info, IPFlowState>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_EPOCH, &config.current_epoch.to_le_bytes()],
+        bump = current_epoch_stats.bump
+    )]
+    pub current_epoch_stats: Account<'info, EpochStats>,
+
+    #[account(
+        init,
+        payer = cranker,
+        space = 8 + EpochStats::INIT_SPACE,
+        seeds = [constants::SEED_EPOCH, &config.current_epoch.saturating_add(1).to_le_bytes()],
+        bump
+    )]
+    pub next_epoch_stats: Account<'info, EpochStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// InitUserStats: 初始化用户统计 PDA
+#[derive(Accounts)]
+pub struct InitUserStats<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + UserStats::INIT_SPACE,
+        seeds = [constants::SEED_USER_STATS, user.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// InitRewardsAccount: 初始化用户奖励余额 PDA
+#[derive(Accounts)]
+pub struct InitRewardsAccount<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + RewardsAccount::INIT_SPACE,
+        seeds = [constants::SEED_REWARDS, user.key().as_ref()],
+        bump
+    )]
+    pub rewards_account: Account<'info, RewardsAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// ClaimRewards: 领取奖励余额 (Vault → user)
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_REWARDS, user.key().as_ref()],
+        bump = rewards_account.bump,
+        has_one = user @ errors::IPFlowError::Unauthorized
+    )]
+    pub rewards_account: Account<'info, RewardsAccount>,
+
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = !config.is_claim_paused() @ errors::IPFlowError::ClaimsPaused
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(owner = pyth_solana_receiver_sdk::ID @ errors::IPFlowError::InvalidPythAccountOwner)]
+    pub pyth_price_update: Account<'info, PriceUpdateV2>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// MintCardNfts: 分批铸造卡牌 NFT (资产 PDA 通过 remaining_accounts 传入)
+#[derive(Accounts)]
+#[instruction(vrf_request_slot: u64)]
+pub struct MintCardNfts<'info> {
+    /// 受益人本人签名，支付资产租金并持有 NFT
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        bump,
+        has_one = user @ errors::IPFlowError::Unauthorized,
+        constraint = mint_request.vrf_request_slot == vrf_request_slot @ errors::IPFlowError::InvalidRequestStatus
+    )]
+    pub mint_request: Account<'info, MintRequest>,
+
+    #[account(seeds = [constants::SEED_GLOBAL_CONFIG], bump)]
+    pub config: Account<'info, IPFlowState>,
+
+    /// CHECK: 地址须与 config.card_collection.collection 一致，由 Core 程序校验数据
+    #[account(
+        mut,
+        address = config.card_collection.collection @ errors::IPFlowError::CardNftsDisabled
+    )]
+    pub collection: UncheckedAccount<'info>,
+
+    /// CHECK: Collection 更新权限 PDA，仅用于 CPI 签名
+    #[account(seeds = [constants::SEED_COLLECTION_AUTHORITY], bump)]
+    pub collection_authority: UncheckedAccount<'info>,
+
+    /// CHECK: 地址固定为 Metaplex Core Program
+    #[account(address = constants::MPL_CORE_PROGRAM_ID @ errors::IPFlowError::InvalidCoreProgram)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// CreateCardCollection: 创建卡牌 NFT Collection
+#[derive(Accounts)]
+pub struct CreateCardCollection<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 新 Collection 账户 (客户端生成的密钥对，由 Core 程序创建)
+    #[account(mut)]
+    pub collection: Signer<'info>,
+
+    /// CHECK: Collection 更新权限 PDA，仅写入 Collection 数据
+    #[account(seeds = [constants::SEED_COLLECTION_AUTHORITY], bump)]
+    pub collection_authority: UncheckedAccount<'info>,
+
+    /// CHECK: 地址固定为 Metaplex Core Program
+    #[account(address = constants::MPL_CORE_PROGRAM_ID @ errors::IPFlowError::InvalidCoreProgram)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// MigrateUserStats: 旧版 UserStats 扩容
+#[derive(Accounts)]
+pub struct MigrateUserStats<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: 旧版结构无法按当前 UserStats 反序列化，handler 中校验 owner
+    #[account(
+        mut,
+        seeds = [constants::SEED_USER_STATS, user.key().as_ref()],
+        bump
+    )]
+    pub user_stats: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ==================== 管理员指令 Context (Task 3.1) ====================
+
+/// WithdrawSol: 管理员提取 SOL
+#[derive(Accounts)]
+pub struct WithdrawSol<'info> {
+    /// 管理员签名者
+    #[account(
+        mut,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    /// 全局配置
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = !config.is_withdraw_paused() @ errors::IPFlowError::WithdrawalsPaused
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 程序金库 PDA
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// 接收 SOL 的地址
+    /// CHECK: 须为已生效的提取白名单地址 (handler 校验)
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// WithdrawToken: 管理员提取 Token
+#[derive(Accounts)]
+pub struct WithdrawToken<'info> {
+    /// 管理员签名者
+    #[account(
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    /// 全局配置
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = !config.is_withdraw_paused() @ errors::IPFlowError::WithdrawalsPaused
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 程序金库 PDA (作为 Token 转账 authority)
+    #[account(
+        seeds = [b"vault"],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// 待提取 Token 的 Mint (transfer_checked 读取精度)
+    pub mint: Account<'info, Mint>,
+
+    /// Vault 的 Token ATA
+    #[account(
+        mut,
+        constraint = vault_token_account.mint == mint.key() @ errors::IPFlowError::InvalidTokenAccount
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// 接收 Token 的账户，owner 须为已生效的提取白名单地址 (handler 校验)
+    #[account(mut)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// WithdrawFeesSol: 管理员从手续费金库提取 SOL
+#[derive(Accounts)]
+pub struct WithdrawFeesSol<'info> {
+    #[account(
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = !config.is_withdraw_paused() @ errors::IPFlowError::WithdrawalsPaused
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 手续费金库 PDA
+    #[account(
+        mut,
+        seeds = [constants::SEED_FEE_VAULT],
+        bump = config.fee_vault_bump
+    )]
+    pub fee_vault: SystemAccount<'info>,
+
+    /// 接收 SOL 的地址
+    /// CHECK: 须为 config.fee_recipient
+    #[account(
+        mut,
+        constraint = recipient.key() == config.fee_recipient @ errors::IPFlowError::InvalidFeeRecipient
+    )]
+    pub recipient: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// WithdrawFeesToken: 管理员从手续费金库提取 Token
+#[derive(Accounts)]
+pub struct WithdrawFeesToken<'info> {
+    #[account(
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = !config.is_withdraw_paused() @ errors::IPFlowError::WithdrawalsPaused
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 手续费金库 PDA (作为 Token 转账 authority)
+    #[account(
+        seeds = [constants::SEED_FEE_VAULT],
+        bump = config.fee_vault_bump
+    )]
+    pub fee_vault: SystemAccount<'info>,
+
+    /// 待提取 Token 的 Mint (transfer_checked 读取精度)
+    pub mint: Account<'info, Mint>,
+
+    /// 手续费金库的 Token 账户 (不能是奖金 Vault 的账户)
+    #[account(
+        mut,
+        constraint = fee_vault_token_account.mint == mint.key() @ errors::IPFlowError::InvalidTokenAccount,
+        constraint = fee_vault_token_account.owner == fee_vault.key() @ errors::IPFlowError::InvalidTokenAccount
+    )]
+    pub fee_vault_token_account: Account<'info, TokenAccount>,
+
+    /// 接收 Token 的账户，owner 须为 config.fee_recipient
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key() @ errors::IPFlowError::InvalidTokenAccount,
+        constraint = recipient_token_account.owner == config.fee_recipient @ errors::IPFlowError::InvalidFeeRecipient
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// CloseVaultTokenAccount: 关闭 Vault 名下的空 Token 账户 (仅管理员)
+#[derive(Accounts)]
+pub struct CloseVaultTokenAccount<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 程序金库 PDA (Token 账户 close authority)
+    #[account(
+        seeds = [b"vault"],
+        bump = config.vault_bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// 待关闭的 Vault Token 账户
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == vault.key() @ errors::IPFlowError::InvalidTokenAccount
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// 接收租金的地址
+    /// CHECK: 任意地址均可接收
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// UpdateConfig: 管理员调整全局配置参数 (多个参数指令共用)
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+}
+
+/// SetCoAdmin: 设置经济参数变更的 co-admin
+#[derive(Accounts)]
+pub struct SetCoAdmin<'info> {
+    pub admin: Signer<'info>,
+
+    /// 当前 co-admin (已配置时必须签名，handler 中与 config.co_admin 比对)
+    pub co_admin: Option<Signer<'info>>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+}
+
+/// StageEconomicsChange: 管理员暂存经济参数变更
+#[derive(Accounts)]
+pub struct StageEconomicsChange<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
 
-    /// CHECK: 关闭过程中需要兼容旧版结构，手动校验 admin
     #[account(
-        mut,
         seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// 全局唯一，已有待确认变更时须先确认或取消
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PendingChange::INIT_SPACE,
+        seeds = [constants::SEED_PENDING_CHANGE],
         bump
     )]
-    pub config: UncheckedAccount<'info>,
+    pub pending_change: Account<'info, PendingChange>,
 
     pub system_program: Program<'info, System>,
 }
 
+/// ConfirmEconomicsChange: co-admin 确认并生效暂存的经济参数变更
 #[derive(Accounts)]
-#[instruction(amount_of_cards: u32, payment_mode: PaymentMode, client_seed: u8, request_slot: u64)]
-pub struct RequestMint<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
+pub struct ConfirmEconomicsChange<'info> {
+    pub co_admin: Signer<'info>,
 
     #[account(
+        mut,
         seeds = [constants::SEED_GLOBAL_CONFIG],
         bump,
-        constraint = !config.is_paused @ errors::IPFlowError::ProgramPaused
+        constraint = config.economics_requires_co_admin()
+            && config.co_admin == co_admin.key() @ errors::IPFlowError::Unauthorized
     )]
     pub config: Account<'info, IPFlowState>,
 
-    /// 程序金库，用于接收 SOL
-    /// CHECK: PDA used as vault
+    /// 暂存人，接收关闭 PendingChange 的租金
+    #[account(mut, address = pending_change.staged_by @ errors::IPFlowError::Unauthorized)]
+    pub staged_by: SystemAccount<'info>,
+
     #[account(
         mut,
-        seeds = [b"vault"],
-        bump = config.vault_bump
+        close = staged_by,
+        seeds = [constants::SEED_PENDING_CHANGE],
+        bump = pending_change.bump
     )]
-    pub vault: AccountInfo<'info>,
+    pub pending_change: Account<'info, PendingChange>,
 
-    /// MintRequest PDA - 使用 request_slot 作为种子的一部分
-    /// request_slot 由前端传入，合约内验证是否为当前 slot
+    /// Tier 覆盖 / 奖金上限变更的目标奖品池 (其他变更不传)
     #[account(
-        init,
-        payer = user,
-        space = 8 + MintRequest::INIT_SPACE,
-        seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &request_slot.to_le_bytes()],
-        bump
+        mut,
+        seeds = [constants::SEED_PRIZE_POOL, &[prize_pool.index]],
+        bump = prize_pool.bump
     )]
-    pub mint_request: Account<'info, MintRequest>,
-
-    /// MagicBlock VRF Oracle Queue
-    /// CHECK: 由前端传入正确的 Queue 地址
-    #[account(mut)]
-    pub oracle_queue: AccountInfo<'info>,
-
-    /// 程序身份 PDA - 用于 VRF 请求签名
-    /// CHECK: Seeds 验证
-    #[account(seeds = [b"identity"], bump)]
-    pub program_identity: AccountInfo<'info>,
-
-    /// VRF 程序
-    /// CHECK: 地址验证确保是 MagicBlock VRF 程序
-    #[account(address = ephemeral_vrf_sdk::consts::VRF_PROGRAM_ID)]
-    pub vrf_program: AccountInfo<'info>,
-
-    /// Slot Hashes Sysvar
-    /// CHECK: 地址验证确保是 SlotHashes sysvar
-    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
-    pub slot_hashes: AccountInfo<'info>,
-
-    /// Pyth 价格数据账户 (SOL 支付时必需)
-    pub pyth_price_update: Account<'info, PriceUpdateV2>,
-
-    pub system_program: Program<'info, System>,
+    pub prize_pool: Option<Account<'info, PrizePoolAccount>>,
+}
 
-    // ==================== USDT 支付相关账户 (可选) ====================
-    /// Token Program (USDT 支付时必需)
-    pub token_program: Option<Program<'info, Token>>,
+/// CancelEconomicsChange: 取消或关闭过期的经济参数变更
+#[derive(Accounts)]
+pub struct CancelEconomicsChange<'info> {
+    /// 管理员或 co-admin (过期后任何人，handler 中校验)
+    pub authority: Signer<'info>,
 
-    /// USDT Mint 账户 (USDT 支付时必需，用于校验)
-    pub usdt_mint: Option<Account<'info, Mint>>,
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump
+    )]
+    pub config: Account<'info, IPFlowState>,
 
-    /// 用户的 USDT Token 账户 (USDT 支付时必需)
-    #[account(mut)]
-    pub user_token_account: Option<Account<'info, TokenAccount>>,
+    /// 暂存人，接收关闭 PendingChange 的租金
+    #[account(mut, address = pending_change.staged_by @ errors::IPFlowError::Unauthorized)]
+    pub staged_by: SystemAccount<'info>,
 
-    /// 协议的 USDT Token 账户 (USDT 支付时必需)
-    #[account(mut)]
-    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        close = staged_by,
+        seeds = [constants::SEED_PENDING_CHANGE],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
 }
 
-/// ConsumeLotteryRandomness: VRF 回调处理
-/// 由 MagicBlock VRF 程序自动调用，不应由用户直接调用
+// ==================== 奖品池管理 Context (Task 3.3) ====================
+
+/// ApproveLargeWin: 审核通过大额中奖 (仅管理员)
 #[derive(Accounts)]
-pub struct ConsumeLotteryRandomness<'info> {
-    /// VRF 程序身份 PDA - 验证调用来源
-    /// 只有 MagicBlock VRF 程序可以调用此指令
-    /// CHECK: 通过 address constraint 验证是 VRF_PROGRAM_IDENTITY
-    #[account(address = ephemeral_vrf_sdk::consts::VRF_PROGRAM_IDENTITY)]
-    pub vrf_program_identity: Signer<'info>,
+#[instruction(vrf_request_slot: u64)]
+pub struct ApproveLargeWin<'info> {
+    pub admin: Signer<'info>,
 
-    /// MintRequest 账户 - 通过 callback_accounts_metas 传入
     #[account(
-        mut,
-        constraint = mint_request.status == RequestStatus::Pending @ errors::IPFlowError::InvalidRequestStatus
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
     )]
-    pub mint_request: Account<'info, MintRequest>,
-
-    /// 全局配置 - 获取活跃奖品池信息
-    #[account(seeds = [constants::SEED_GLOBAL_CONFIG], bump)]
     pub config: Account<'info, IPFlowState>,
-}
 
-/// Claim: 用户领取奖励 (选择 SOL 或 Token)
-/// Task 1.14: claim 完成后自动关闭 MintRequest PDA，退还租金给用户
-#[derive(Accounts)]
-#[instruction(payout_mode: PayoutMode, swap_router: Option<SwapRouter>, expected_token_output: Option<u64>, swap_data: Option<Vec<u8>>, vrf_request_slot: u64)]
-pub struct Claim<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
+    /// 奖金受益人 (PDA 种子)
+    /// CHECK: 仅用于 PDA 派生，由 mint_request.user 约束
+    pub user: UncheckedAccount<'info>,
 
     #[account(
         mut,
-        close = user,  // Task 1.14: 关闭账户，租金退还给 user
         seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
         bump,
-        has_one = user @ errors::IPFlowError::Unauthorized,
-        constraint = mint_request.status == RequestStatus::Revealed @ errors::IPFlowError::InvalidRequestStatus,
-        constraint = mint_request.vrf_request_slot == vrf_request_slot @ errors::IPFlowError::InvalidRequestStatus
+        has_one = user @ errors::IPFlowError::Unauthorized
     )]
     pub mint_request: Account<'info, MintRequest>,
+}
+
+/// ExtendClaimDeadline: 顺延领取期限 (仅管理员)
+#[derive(Accounts)]
+#[instruction(request_slot: u64)]
+pub struct ExtendClaimDeadline<'info> {
+    pub admin: Signer<'info>,
 
     #[account(
         seeds = [constants::SEED_GLOBAL_CONFIG],
         bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
     )]
     pub config: Account<'info, IPFlowState>,
 
-    /// 程序金库，用于支付 SOL 奖金
-    /// CHECK: PDA
+    /// 奖金受益人 (PDA 种子)
+    /// CHECK: 仅用于 PDA 派生，由 mint_request.user 约束
+    pub user: UncheckedAccount<'info>,
+
     #[account(
         mut,
-        seeds = [b"vault"],
-        bump = config.vault_bump
+        seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &request_slot.to_le_bytes()],
+        bump,
+        has_one = user @ errors::IPFlowError::Unauthorized
     )]
-    pub vault: AccountInfo<'info>,
-
-    /// Pyth 价格数据账户 (SOL 模式需要)
-    pub pyth_price_update: Account<'info, PriceUpdateV2>,
-
-    pub system_program: Program<'info, System>,
-    // TODO: Task 1.10 添加 Raydium CPI 所需的 remaining_accounts
+    pub mint_request: Account<'info, MintRequest>,
 }
 
-/// Refund: 超时退款 (Task 2.3)
-/// 当 MintRequest 处于 Pending 状态超过 10 分钟时，用户可申请退款
-/// - SOL 退款: 仅需基础账户
-/// - USDT 退款: 需要额外传入 Token 账户
+/// RejectLargeWin: 拒绝大额中奖并退还本金 (仅管理员)
 #[derive(Accounts)]
 #[instruction(vrf_request_slot: u64)]
-pub struct Refund<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
+pub struct RejectLargeWin<'info> {
+    pub admin: Signer<'info>,
 
+    /// 全局配置 (可写：扣减选中池的待领取计数)
     #[account(
         mut,
-        close = user,  // 关闭账户，租金退还给 user
-        seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
+        seeds = [constants::SEED_GLOBAL_CONFIG],
         bump,
-        has_one = user @ errors::IPFlowError::Unauthorized,
-        constraint = mint_request.vrf_request_slot == vrf_request_slot @ errors::IPFlowError::InvalidRequestStatus
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
     )]
-    pub mint_request: Account<'info, MintRequest>,
+    pub config: Account<'info, IPFlowState>,
+
+    /// 奖金受益人 (PDA 种子)
+    /// CHECK: 仅用于 PDA 派生，由 mint_request.user 约束
+    pub user: UncheckedAccount<'info>,
+
+    /// 支付本金的钱包，接收退款及 PDA 租金
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
 
     #[account(
-        seeds = [constants::SEED_GLOBAL_CONFIG],
+        mut,
+        close = payer,
+        seeds = [constants::SEED_MINT_REQUEST, user.key().as_ref(), &vrf_request_slot.to_le_bytes()],
         bump,
+        has_one = user @ errors::IPFlowError::Unauthorized,
+        has_one = payer @ errors::IPFlowError::Unauthorized
     )]
-    pub config: Account<'info, IPFlowState>,
+    pub mint_request: Account<'info, MintRequest>,
 
     /// 程序金库，用于退还 SOL
-    /// CHECK: PDA
     #[account(
         mut,
         seeds = [b"vault"],
         bump = config.vault_bump
     )]
-    pub vault: AccountInfo<'info>,
+    pub vault: SystemAccount<'info>,
+
+    /// 中奖托管 PDA - 拒绝后托管资金退回 Vault
+    #[account(mut, seeds = [constants::SEED_ESCROW, mint_request.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
 
     pub system_program: Program<'info, System>,
 
@@ -368,91 +2460,109 @@ pub struct Refund<'info> {
     /// Token Program (USDT 退款时必需)
     pub token_program: Option<Program<'info, Token>>,
 
-    /// Vault 的 USDT Token 账户 (USDT 退款时必需)
-    #[account(mut)]
+    /// 请求记录的稳定币 Mint (USDT 退款时必需，transfer_checked 读取精度)
+    #[account(
+        constraint = payment_mint.key() == mint_request.payment_mint @ errors::IPFlowError::InvalidUsdtMint
+    )]
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    /// Vault 的稳定币 Token 账户 (USDT 退款时必需，mint 须为 mint_request.payment_mint)
+    #[account(
+        mut,
+        constraint = vault_token_account.owner == vault.key() @ errors::IPFlowError::InvalidTokenAccount
+    )]
     pub vault_token_account: Option<Account<'info, TokenAccount>>,
 
-    /// 用户的 USDT Token 账户 (USDT 退款时必需)
+    /// 支付者的 USDT Token 账户 (USDT 退款时必需)
     #[account(mut)]
     pub user_token_account: Option<Account<'info, TokenAccount>>,
 }
 
-// ==================== 管理员指令 Context (Task 3.1) ====================
-
-/// WithdrawSol: 管理员提取 SOL
+/// RebuildActivePools: 按现存奖品池 PDA 重建活跃索引 (remaining_accounts 传入所有池)
 #[derive(Accounts)]
-pub struct WithdrawSol<'info> {
-    /// 管理员签名者
-    #[account(
-        mut,
-        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
-    )]
+pub struct RebuildActivePools<'info> {
     pub admin: Signer<'info>,
 
-    /// 全局配置
     #[account(
+        mut,
         seeds = [constants::SEED_GLOBAL_CONFIG],
         bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
     )]
     pub config: Account<'info, IPFlowState>,
-
-    /// 程序金库 PDA
-    /// CHECK: PDA used as vault
-    #[account(
-        mut,
-        seeds = [b"vault"],
-        bump = config.vault_bump
-    )]
-    pub vault: AccountInfo<'info>,
-
-    /// 接收 SOL 的地址
-    /// CHECK: 任意地址均可接收
-    #[account(mut)]
-    pub recipient: AccountInfo<'info>,
-
-    pub system_program: Program<'info, System>,
 }
 
-/// WithdrawToken: 管理员提取 Token
+/// FlushPoolStats: 同步奖品池分配统计 (remaining_accounts 传入可写的奖品池 PDA)
 #[derive(Accounts)]
-pub struct WithdrawToken<'info> {
-    /// 管理员签名者
-    #[account(
-        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
-    )]
+pub struct FlushPoolStats<'info> {
     pub admin: Signer<'info>,
 
-    /// 全局配置
     #[account(
         seeds = [constants::SEED_GLOBAL_CONFIG],
         bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
     )]
     pub config: Account<'info, IPFlowState>,
+}
 
-    /// 程序金库 PDA (作为 Token 转账 authority)
-    /// CHECK: PDA used as vault
-    #[account(
-        seeds = [b"vault"],
-        bump = config.vault_bump
-    )]
-    pub vault: AccountInfo<'info>,
+/// GetActivePools: 只读查询活跃奖品池 (奖品池 PDA 通过 remaining_accounts 传入)
+#[derive(Accounts)]
+pub struct GetActivePools<'info> {
+    #[account(seeds = [constants::SEED_GLOBAL_CONFIG], bump)]
+    pub config: Account<'info, IPFlowState>,
+}
 
-    /// Vault 的 Token ATA
-    #[account(mut)]
-    pub vault_token_account: Account<'info, TokenAccount>,
+/// GetConfig: 只读配置视图查询
+#[derive(Accounts)]
+pub struct GetConfig<'info> {
+    #[account(seeds = [constants::SEED_GLOBAL_CONFIG], bump)]
+    pub config: Account<'info, IPFlowState>,
+}
 
-    /// 接收 Token 的 ATA
-    #[account(mut)]
-    pub recipient_token_account: Account<'info, TokenAccount>,
+/// GetRtpInfo: 只读 RTP 查询
+#[derive(Accounts)]
+pub struct GetRtpInfo<'info> {
+    #[account(seeds = [constants::SEED_GLOBAL_CONFIG], bump)]
+    pub config: Account<'info, IPFlowState>,
+}
 
-    pub token_program: Program<'info, Token>,
+/// HealthCheck: 只读健康检查 (Vault Token 账户通过 remaining_accounts 传入)
+#[derive(Accounts)]
+pub struct HealthCheck<'info> {
+    #[account(seeds = [constants::SEED_GLOBAL_CONFIG], bump)]
+    pub config: Account<'info, IPFlowState>,
+
+    #[account(seeds = [b"vault"], bump = config.vault_bump)]
+    pub vault: SystemAccount<'info>,
+
+    /// 手续费金库 PDA (仅读取余额)
+    #[account(seeds = [constants::SEED_FEE_VAULT], bump = config.fee_vault_bump)]
+    pub fee_vault: SystemAccount<'info>,
+
+    #[account(owner = pyth_solana_receiver_sdk::ID @ errors::IPFlowError::InvalidPythAccountOwner)]
+    pub pyth_price_update: Account<'info, PriceUpdateV2>,
 }
 
-// ==================== 奖品池管理 Context (Task 3.3) ====================
+/// AssertSolvency: 只读偿付能力核对
+#[derive(Accounts)]
+pub struct AssertSolvency<'info> {
+    #[account(seeds = [constants::SEED_GLOBAL_CONFIG], bump)]
+    pub config: Account<'info, IPFlowState>,
+
+    #[account(seeds = [b"vault"], bump = config.vault_bump)]
+    pub vault: SystemAccount<'info>,
+
+    /// 手续费金库 PDA (仅读取余额，不计入偿付能力)
+    #[account(seeds = [constants::SEED_FEE_VAULT], bump = config.fee_vault_bump)]
+    pub fee_vault: SystemAccount<'info>,
+
+    #[account(owner = pyth_solana_receiver_sdk::ID @ errors::IPFlowError::InvalidPythAccountOwner)]
+    pub pyth_price_update: Account<'info, PriceUpdateV2>,
+}
 
 /// AddPrizePool: 添加奖品池
 #[derive(Accounts)]
-#[instruction(swap_pool: Pubkey, pool_type: PoolType, name: String)]
+#[instruction(swap_pool: Pubkey, pool_type: PoolType, name: String, max_win_usd: u64)]
 pub struct AddPrizePool<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
@@ -505,6 +2615,28 @@ pub struct RemovePrizePool<'info> {
 pub struct UpdatePrizePool<'info> {
     pub admin: Signer<'info>,
 
+    /// 全局配置 (可写：同步活跃池奖金上限镜像)
+    #[account(
+        mut,
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    #[account(
+        mut,
+        seeds = [constants::SEED_PRIZE_POOL, &[prize_pool.index]],
+        bump = prize_pool.bump
+    )]
+    pub prize_pool: Account<'info, PrizePoolAccount>,
+}
+
+/// ApplyPrizePoolUpdate: 生效暂存的奖品池 swap_pool 变更
+#[derive(Accounts)]
+pub struct ApplyPrizePoolUpdate<'info> {
+    pub admin: Signer<'info>,
+
     #[account(
         seeds = [constants::SEED_GLOBAL_CONFIG],
         bump,
@@ -519,3 +2651,28 @@ pub struct UpdatePrizePool<'info> {
     )]
     pub prize_pool: Account<'info, PrizePoolAccount>,
 }
+
+/// MigratePrizePool: 迁移/扩容奖品池账户
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct MigratePrizePool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [constants::SEED_GLOBAL_CONFIG],
+        bump,
+        constraint = config.admin == admin.key() @ errors::IPFlowError::Unauthorized
+    )]
+    pub config: Account<'info, IPFlowState>,
+
+    /// CHECK: 迁移过程中需要兼容旧版结构，handler 中校验 owner 与 index
+    #[account(
+        mut,
+        seeds = [constants::SEED_PRIZE_POOL, &[index]],
+        bump
+    )]
+    pub prize_pool: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
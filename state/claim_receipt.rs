@@ -0,0 +1,35 @@
+// ==================== 领取凭证状态定义 ====================
+
+use anchor_lang::prelude::*;
+
+use crate::state::PayoutMode;
+
+/// 领取凭证 PDA (可选)
+///
+/// Seeds: [b"receipt", user, vrf_request_slot]
+/// claim 时按需创建 (create_receipt = true)，租金由用户支付，
+/// MintRequest 关闭后仍保留中奖记录，用户可通过 close_receipt 回收租金
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimReceipt {
+    /// 所属用户
+    pub user: Pubkey,
+    /// 对应请求的 vrf_request_slot (request_id)
+    pub request_id: u64,
+    /// 中奖总额 (micro-USD)
+    pub total_won_usd: u64,
+    /// 实际发放金额 (lamports 或 token amount)
+    pub paid_amount: u64,
+    /// 实际发放方式 (降级时为 SOL)
+    pub payout_mode: PayoutMode,
+    /// 选中的奖品池索引
+    pub selected_pool_index: u8,
+    /// 领取时间戳
+    pub timestamp: i64,
+    /// PDA bump
+    pub bump: u8,
+    /// 本次领取由合约新建的奖品 Token ATA (未新建或已清理时为 Pubkey::default())
+    pub created_token_account: Pubkey,
+    /// 本次领取由合约新建的 WSOL ATA (未新建或已清理时为 Pubkey::default())
+    pub created_wsol_account: Pubkey,
+}
@@ -0,0 +1,45 @@
+// ==================== 领取委托状态定义 ====================
+
+use anchor_lang::prelude::*;
+
+/// 领取委托 PDA (会话密钥)
+///
+/// Seeds: [b"delegate", user]
+/// 用户通过 set_delegate 授权短期会话密钥代为 claim，revoke_delegate 撤销并回收租金。
+/// 委托只授予签名权限，奖金始终发放给 user。
+#[account]
+#[derive(InitSpace)]
+pub struct Delegation {
+    /// 授权用户
+    pub user: Pubkey,
+    /// 被授权的会话密钥
+    pub delegate: Pubkey,
+    /// 过期时间戳 (不含)
+    pub expires_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl Delegation {
+    /// 委托在 now 时刻是否有效
+    pub fn is_active(&self, now: i64) -> bool {
+        now < self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delegation_expires_at_boundary() {
+        let delegation = Delegation {
+            user: Pubkey::default(),
+            delegate: Pubkey::default(),
+            expires_at: 1_700_000_000,
+            bump: 255,
+        };
+        assert!(delegation.is_active(1_699_999_999));
+        assert!(!delegation.is_active(1_700_000_000));
+    }
+}
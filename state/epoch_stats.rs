@@ -0,0 +1,110 @@
+// ==================== Epoch 统计状态定义 ====================
+
+use anchor_lang::prelude::*;
+
+use crate::constants::BPS_DENOMINATOR;
+use crate::errors::IPFlowError;
+
+/// 单个 epoch 的经营统计 PDA
+///
+/// Seeds: [b"epoch", epoch (u64 LE)]
+/// request_mint / claim / refund 累加当前 epoch 的统计，roll_epoch 结算并创建下一个
+#[account]
+#[derive(InitSpace)]
+pub struct EpochStats {
+    /// epoch 序号
+    pub epoch: u64,
+    /// 开始时间戳
+    pub start_ts: i64,
+    /// 结束时间戳 (roll_epoch 时写入，0 表示进行中)
+    pub end_ts: i64,
+    /// 收入 (micro-USD)
+    pub collected_usd: u64,
+    /// 中奖发放 (micro-USD，按发放比例折算后的金额)
+    pub paid_out_usd: u64,
+    /// 退款 (micro-USD)
+    pub refunded_usd: u64,
+    /// 售出卡片数量
+    pub cards_sold: u64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl EpochStats {
+    pub fn record_mint(&mut self, paid_usd: u64, cards: u32) -> Result<()> {
+        self.collected_usd = self
+            .collected_usd
+            .checked_add(paid_usd)
+            .ok_or(IPFlowError::MathOverflow)?;
+        self.cards_sold = self
+            .cards_sold
+            .checked_add(cards as u64)
+            .ok_or(IPFlowError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_payout(&mut self, payout_usd: u64) -> Result<()> {
+        self.paid_out_usd = self
+            .paid_out_usd
+            .checked_add(payout_usd)
+            .ok_or(IPFlowError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_refund(&mut self, refunded_usd: u64) -> Result<()> {
+        self.refunded_usd = self
+            .refunded_usd
+            .checked_add(refunded_usd)
+            .ok_or(IPFlowError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// RTP (bps) = 发放 / (收入 - 退款)，净收入为 0 时返回 0
+    pub fn rtp_bps(&self) -> u64 {
+        let net_collected = self.collected_usd.saturating_sub(self.refunded_usd);
+        if net_collected == 0 {
+            return 0;
+        }
+        let rtp = (self.paid_out_usd as u128) * (BPS_DENOMINATOR as u128) / (net_collected as u128);
+        rtp.min(u64::MAX as u128) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> EpochStats {
+        EpochStats {
+            epoch: 0,
+            start_ts: 0,
+            end_ts: 0,
+            collected_usd: 0,
+            paid_out_usd: 0,
+            refunded_usd: 0,
+            cards_sold: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn rtp_uses_net_collected() {
+        let mut s = stats();
+        assert_eq!(s.rtp_bps(), 0);
+
+        s.record_mint(100_000_000, 10).unwrap();
+        s.record_refund(20_000_000).unwrap();
+        s.record_payout(76_000_000).unwrap();
+        assert_eq!(s.cards_sold, 10);
+        // 76 / 80 = 95%
+        assert_eq!(s.rtp_bps(), 9_500);
+    }
+
+    #[test]
+    fn rtp_is_zero_when_fully_refunded() {
+        let mut s = stats();
+        s.record_mint(10_000_000, 1).unwrap();
+        s.record_refund(10_000_000).unwrap();
+        assert_eq!(s.rtp_bps(), 0);
+    }
+}
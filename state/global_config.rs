@@ -1,34 +1,1074 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::{
+    BPS_DENOMINATOR, MAX_CARDS_LIMIT, MAX_PAYOUT_BPS, MAX_REQUEST_TIMEOUT_SECONDS, MIN_PAYOUT_BPS, MAX_ORACLE_QUEUES, MAX_CARD_URI_PREFIX_LEN, MAX_PAYMENT_MINTS, MAX_ROUTE_DISCRIMINATORS, MAX_WITHDRAW_RECIPIENTS, PAUSE_ADMIN_WITHDRAW, PAUSE_CLAIM, PAUSE_MINT, PAUSE_REFUND, PAYOUT_WINDOW_SECONDS, TICKET_PRICE_MICRO_USD, CLAIM_TIMEOUT_SECONDS, CONFIG_VIEW_VERSION, PYTH_SOL_USD_FEED_ID, STATS_EMIT_INTERVAL_SECONDS,
+};
+use crate::errors::IPFlowError;
+use crate::state::{is_within_schedule, PaymentMode};
+use crate::utils::{resolve_tier_thresholds, NO_TIER_OVERRIDE};
+
 /// 奖品池最大数量
 pub const MAX_PRIZE_POOLS: usize = 50;
 
+/// 稳定币支付 Mint 白名单条目
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Default, Debug,
+)]
+pub struct PaymentMintInfo {
+    pub mint: Pubkey,
+    /// Mint 精度 (添加时从 Mint 账户读取)
+    pub decimals: u8,
+    /// 是否接受该 Mint 的新支付 (停用后已创建请求仍可退款)
+    pub enabled: bool,
+}
+
+/// 卡牌 NFT Collection 配置 (Metaplex Core)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct CollectionConfig {
+    /// Core Collection 账户 (create_card_collection 创建后写入)
+    pub collection: Pubkey,
+    /// 是否允许铸造卡牌 NFT
+    pub enabled: bool,
+    /// 元数据 URI 前缀 (前 uri_prefix_len 字节有效)
+    pub uri_prefix: [u8; MAX_CARD_URI_PREFIX_LEN],
+    pub uri_prefix_len: u8,
+}
+
+impl Default for CollectionConfig {
+    fn default() -> Self {
+        Self {
+            collection: Pubkey::default(),
+            enabled: false,
+            uri_prefix: [0u8; MAX_CARD_URI_PREFIX_LEN],
+            uri_prefix_len: 0,
+        }
+    }
+}
+
+impl CollectionConfig {
+    /// 当前元数据 URI 前缀
+    pub fn uri_prefix(&self) -> &str {
+        std::str::from_utf8(&self.uri_prefix[..self.uri_prefix_len as usize]).unwrap_or_default()
+    }
+
+    /// 是否可铸造: 已启用且 Collection 已创建
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.collection != Pubkey::default()
+    }
+}
+
 #[account]
 pub struct IPFlowState {
     pub admin: Pubkey,
     pub vault_bump: u8,
+    /// 累计已揭示请求的票款 (micro-USD)，揭示完成时累加，审核拒绝退款时扣减；用于实际 RTP 统计
     pub total_collected: u64,
     pub platform_fee_bps: u16, // 平台利润比例，例如 500 表示 5%
+    /// 旧版暂停标志，与 pause_flags 的 bit 0 (PAUSE_MINT) 保持同步，供旧客户端读取
     pub is_paused: bool,
     pub pool_count: u8,
-    /// 下一个可用索引（只增不减，用于创建新池）(Task 3.3)
+    /// 下一个可用索引（只增不减，用于创建新池，不超过 MAX_PRIZE_POOLS）(Task 3.3)
     pub prize_pool_count: u8,
     /// 当前活跃池子数量（VRF 取模基数）(Task 3.3)
     pub active_pool_count: u8,
     /// 活跃池子索引列表（有序，无空洞）(Task 3.3)
     /// 255 表示空位
     pub active_pool_indices: [u8; MAX_PRIZE_POOLS],
-    /// VRF Oracle Queue 白名单
+    /// 主 VRF Oracle Queue (与 oracle_queues[0] 保持同步，供旧客户端读取)
     pub oracle_queue: Pubkey,
     /// 退款超时时间（秒）
     pub request_timeout_seconds: i64,
+    /// 按奖品池 index 累计的待领取请求计数 (v6 起按 index 存储，此前与 active_pool_indices 按位置对应)
+    /// Revealed 时 +1，领取 / 过期 / 审核拒绝时 -1；非零时禁止删除该池，由 flush_pool_stats 同步到 PDA
+    pub pool_pending_claims: [u32; MAX_PRIZE_POOLS],
+    /// SOL 领取最小发放额 (lamports)，0 表示不限制
+    pub min_payout_lamports: u64,
+    /// 低于最小发放额时: false = 向上取整到最小值 (house 承担)，true = 累积到 UserStats 零头
+    pub dust_accumulation_enabled: bool,
+    /// 细粒度暂停位: bit 0 mint, bit 1 claim, bit 2 refund, bit 3 admin-withdraw
+    pub pause_flags: u8,
+    /// 各活跃池 Token 模式奖金上限镜像 (与 active_pool_indices 按位置对应，0 表示不限制)
+    /// VRF 回调无法预知选中的池 PDA，故由奖品池管理指令同步维护
+    pub active_pool_max_win_usd: [u64; MAX_PRIZE_POOLS],
+    /// crank_expire_pending 调用者小费 (lamports)，由 Vault 支付
+    pub crank_tip_lamports: u64,
+    /// Vault 的 USDT ATA 地址 (init_vault_token_account 创建后写入)
+    pub vault_usdt_account: Pubkey,
+    /// 是否接受 SOL 支付 (仅影响 request_mint)
+    pub sol_payments_enabled: bool,
+    /// 是否接受 USDT 支付 (仅影响 request_mint)
+    pub usdt_payments_enabled: bool,
+    /// 配置布局版本，migrate_config 据此初始化无法用零值判断的新字段
+    pub config_version: u8,
+    /// SOL 模式发放比例 (bps)，默认 9500 (95%)
+    pub sol_payout_bps: u16,
+    /// Token 模式发放比例 (bps)，默认 10000 (100%)
+    pub token_payout_bps: u16,
+    /// VRF 请求费用是否由 Vault 支付 (false 时由用户支付)
+    pub vault_pays_vrf_fee: bool,
+    /// Vault 累计支付的 VRF 费用 (lamports)
+    pub total_vrf_fees_lamports: u64,
+    /// Pyth 价格最大有效期（秒），默认 60
+    pub max_price_age_seconds: u64,
+    /// 24 小时窗口内允许发放的中奖总额上限 (micro-USD)，0 表示不限制
+    pub daily_payout_cap_usd: u64,
+    /// 当前发放统计窗口起始时间戳
+    pub payout_window_start: i64,
+    /// 当前窗口内已发放的中奖总额 (micro-USD)
+    pub payout_in_window_usd: u64,
+    /// 单笔中奖人工审核阈值 (micro-USD)，超过时需管理员 approve 后才能领取，0 表示不审核
+    pub large_win_threshold_usd: u64,
+    /// VRF Oracle Queue 白名单 (前 oracle_queue_count 个有效)，request_mint 可使用其中任意一个
+    pub oracle_queues: [Pubkey; MAX_ORACLE_QUEUES],
+    /// 白名单中有效 queue 数量
+    pub oracle_queue_count: u8,
+    /// 单次 VRF 请求费用 (lamports)，Vault 代付时用户超时退款扣除此金额，0 表示全额退款
+    pub vrf_fee_lamports: u64,
+    /// 稳定币支付 Mint 白名单 (前 payment_mint_count 个有效)
+    pub payment_mints: [PaymentMintInfo; MAX_PAYMENT_MINTS],
+    /// 白名单中有效 Mint 数量
+    pub payment_mint_count: u8,
+    /// 与 active_pool_indices 平行：对应池的生效起始时间戳 (0 表示不限制)
+    pub active_pool_active_from: [i64; MAX_PRIZE_POOLS],
+    /// 与 active_pool_indices 平行：对应池的失效时间戳 (0 表示不限制)
+    pub active_pool_active_until: [i64; MAX_PRIZE_POOLS],
+    /// 当前统计 epoch 序号
+    pub current_epoch: u64,
+    /// 当前 epoch 开始时间戳 (0 表示尚未调用 start_epoch，统计未启用)
+    pub epoch_start_ts: i64,
+    /// epoch 长度 (秒)，默认 7 天
+    pub epoch_length_seconds: u64,
+    /// 保底阈值: 连续该数量的 Tier 1/2 卡后强制下一张 Tier 3+ (0 表示关闭)
+    pub pity_threshold: u16,
+    /// 是否按 Vault 健康度动态调节开奖金额
+    pub rtp_modulation_enabled: bool,
+    /// Vault 目标余额 (lamports)，低于目标时下调、高于目标时上调开奖金额
+    pub vault_target_lamports: u64,
+    /// 动态调节幅度上限 (bps)
+    pub max_modulation_bps: u16,
+    /// Jupiter Program ID (swap CPI 目标)
+    pub jupiter_program: Pubkey,
+    /// 允许的 Jupiter 路由指令 discriminator
+    pub allowed_route_discriminators: [[u8; 8]; MAX_ROUTE_DISCRIMINATORS],
+    /// 有效的 discriminator 数量
+    pub route_discriminator_count: u8,
+    /// Raydium CPMM Program ID (按部署环境设置)
+    pub raydium_program: Pubkey,
+    /// 推荐返佣比例 (bps，按被推荐请求的 paid_usd 计入推荐人奖励余额，0 表示关闭)
+    pub referral_bps: u16,
+    /// 奖励余额最小领取额 (micro-USD)
+    pub min_rewards_claim_usd: u64,
+    /// 已揭示未领取的中奖总额 (micro-USD)，揭示时增加，claim / 拒绝审核时饱和扣减
+    /// (migrate_config 后从 0 开始计数，迁移前已揭示的请求不计入)
+    pub outstanding_liabilities_usd: u64,
+    /// 卡牌 NFT Collection 配置
+    pub card_collection: CollectionConfig,
+    /// 单笔请求卡数下限
+    pub min_cards: u32,
+    /// 单笔请求卡数上限 (不超过 MAX_CARDS_LIMIT)
+    pub max_cards: u32,
+    /// 手续费金库 PDA 的 bump (seeds = [SEED_FEE_VAULT])
+    pub fee_vault_bump: u8,
+    /// 手续费接收地址，withdraw_fees_* 只能转给该地址 (Token 提取时为接收账户的 owner)
+    pub fee_recipient: Pubkey,
+    /// withdraw_sol / withdraw_token 允许的接收地址 (前 withdraw_whitelist_count 个有效)
+    pub withdraw_whitelist: [Pubkey; MAX_WITHDRAW_RECIPIENTS],
+    /// 各白名单地址的生效时间 (添加时间 + WITHDRAW_RECIPIENT_DELAY_SECONDS)
+    pub withdraw_whitelist_active_after: [i64; MAX_WITHDRAW_RECIPIENTS],
+    /// 有效的提取白名单数量
+    pub withdraw_whitelist_count: u8,
+    /// VRF 请求签名用程序身份 PDA (seeds = [b"identity"]) 的 bump
+    pub identity_bump: u8,
+    /// 程序暂停 (is_paused / PAUSE_MINT) 期间是否仍允许领取已揭示的奖金 (默认 true)
+    pub claims_allowed_while_paused: bool,
+    /// 当前托管在各请求 escrow PDA 中的中奖金额合计 (lamports)
+    pub total_escrowed_lamports: u64,
+    /// 与 active_pool_indices 平行的 Tier 覆盖镜像 (全 0 表示使用默认阈值表)
+    /// request_mint 据此快照候选池的覆盖表，VRF 回调按选中池的概率开奖，无需传入奖品池 PDA
+    pub active_pool_tier_overrides: [[u64; 4]; MAX_PRIZE_POOLS],
+    /// 平台手续费收取时机 (FEE_POLICY_ON_MINT / FEE_POLICY_ON_CLAIM)，仅影响新创建的请求
+    pub fee_policy: u8,
+    /// 累计领取发放的奖金价值 (micro-USD，按发放比例折算、扣除领取时手续费后)，用于实际 RTP 统计
+    pub total_paid_out_usd: u64,
+    /// 按奖品池 index 累计的揭示分配奖金 (micro-USD，与最终领取方式无关)，由 flush_pool_stats 同步到 PDA
+    pub pool_assigned_usd: [u64; MAX_PRIZE_POOLS],
+    /// 按奖品池 index 累计的被选中次数，由 flush_pool_stats 同步到 PDA
+    pub pool_times_selected: [u64; MAX_PRIZE_POOLS],
+    /// Token 模式 swap 输入下限 (lamports)，低于该值的 swap 大部分被手续费消耗，0 表示不限制
+    pub min_swap_lamports: u64,
+    /// swap 输入低于 min_swap_lamports 时: true = 自动降级为 SOL 发放，false = 拒绝 (PayoutTooSmallForSwap)
+    pub dust_swap_fallback_to_sol: bool,
+    /// 奖品池 swap_pool 变更的生效延迟 (秒)，0 表示 update_prize_pool 立即生效
+    pub pool_change_delay_seconds: u64,
+    /// Vault 可用余额下限 (lamports)，低于该值时停止接受新的 request_mint，0 表示不限制
+    pub min_vault_reserve_lamports: u64,
+    /// 转化漏斗计数 (饱和累加)：创建的请求数
+    pub count_requested: u64,
+    /// 揭示完成的请求数
+    pub count_revealed: u64,
+    /// 以 SOL 领取的请求数 (含 Token 降级为 SOL)
+    pub count_claimed_sol: u64,
+    /// 以 Token / Split 领取的请求数
+    pub count_claimed_token: u64,
+    /// 退款的请求数 (用户退款、管理员协助退款、大额中奖审核拒绝)
+    pub count_refunded: u64,
+    /// 过期清理的请求数 (crank_expire_pending 与 sweep_expired)
+    pub count_expired: u64,
+    /// 最近一次 emit_stats 时间戳 (限频)
+    pub last_stats_ts: i64,
+    /// MagicBlock VRF 程序 ID (request_mint 的 CPI 目标)，默认 SDK 常量，VRF 程序升级时由管理员更新
+    pub vrf_program: Pubkey,
+    /// MagicBlock VRF 回调签名身份，默认 SDK 常量，身份轮换时由管理员更新
+    pub vrf_identity: Pubkey,
+    /// Vault 的 WSOL ATA (由 init_vault_wsol 写入)，swap 路径只接受该账户作为输入；
+    /// 零值表示尚未初始化，Jupiter 路径按扫描方式查找
+    pub vault_wsol_account: Pubkey,
+    /// 经济参数变更的确认人 (co-admin)；零值表示未配置，经济参数仍由管理员直接修改，
+    /// 配置后须经 stage_economics_change / confirm_economics_change 两步生效
+    pub co_admin: Pubkey,
+    /// 按奖品池 index 累计的待揭示请求计数 (请求创建时快照的每个候选池各 +1，
+    /// 揭示完成或退款时 -1)；非零时禁止删除该池，避免揭示选中已删除的池
+    pub pool_pending_reveals: [u32; MAX_PRIZE_POOLS],
 }
 
 impl IPFlowState {
     // 32 (admin) + 1 (vault_bump) + 8 (total_collected) + 2 (platform_fee_bps)
     // + 1 (is_paused) + 1 (pool_count) + 1 (prize_pool_count)
     // + 1 (active_pool_count) + 50 (active_pool_indices) + 32 (oracle_queue)
-    // + 8 (request_timeout_seconds)
-    pub const INIT_SPACE: usize =
-        32 + 1 + 8 + 2 + 1 + 1 + 1 + 1 + MAX_PRIZE_POOLS + 32 + 8;
+    // + 8 (request_timeout_seconds) + 4 * 50 (pool_pending_claims)
+    // + 8 (min_payout_lamports) + 1 (dust_accumulation_enabled) + 1 (pause_flags)
+    // + 8 * 50 (active_pool_max_win_usd) + 8 (crank_tip_lamports)
+    // + 32 (vault_usdt_account) + 1 (sol_payments_enabled) + 1 (usdt_payments_enabled)
+    // + 1 (config_version) + 2 (sol_payout_bps) + 2 (token_payout_bps)
+    // + 1 (vault_pays_vrf_fee) + 8 (total_vrf_fees_lamports) + 8 (max_price_age_seconds)
+    // + 8 (daily_payout_cap_usd) + 8 (payout_window_start) + 8 (payout_in_window_usd)
+    // + 8 (large_win_threshold_usd) + 32 * 4 (oracle_queues) + 1 (oracle_queue_count)
+    // + 8 (vrf_fee_lamports) + 34 * 4 (payment_mints) + 1 (payment_mint_count)
+    // + 8 * 50 (active_pool_active_from) + 8 * 50 (active_pool_active_until)
+    // + 8 (current_epoch) + 8 (epoch_start_ts) + 8 (epoch_length_seconds)
+    // + 2 (pity_threshold) + 1 (rtp_modulation_enabled) + 8 (vault_target_lamports)
+    // + 2 (max_modulation_bps) + 32 (jupiter_program)
+    // + 8 * 6 (allowed_route_discriminators) + 1 (route_discriminator_count)
+    // + 32 (raydium_program) + 2 (referral_bps) + 8 (min_rewards_claim_usd)
+    // + 8 (outstanding_liabilities_usd) + 32 + 1 + 64 + 1 (card_collection)
+    // + 4 (min_cards) + 4 (max_cards) + 1 (fee_vault_bump) + 32 (fee_recipient)
+    // + 32 * 4 (withdraw_whitelist) + 8 * 4 (withdraw_whitelist_active_after)
+    // + 1 (withdraw_whitelist_count) + 1 (identity_bump) + 1 (claims_allowed_while_paused)
+    // + 8 (total_escrowed_lamports) + 32 * 50 (active_pool_tier_overrides)
+    // + 1 (fee_policy) + 8 (total_paid_out_usd)
+    // + 8 * 50 (pool_assigned_usd) + 8 * 50 (pool_times_selected)
+    // + 8 (min_swap_lamports) + 1 (dust_swap_fallback_to_sol)
+    // + 8 (pool_change_delay_seconds) + 8 (min_vault_reserve_lamports)
+    // + 8 * 6 (count_requested .. count_expired) + 8 (last_stats_ts)
+    // + 32 (vrf_program) + 32 (vrf_identity) + 32 (vault_wsol_account)
+    // + 32 (co_admin) + 4 * 50 (pool_pending_reveals)
+    pub const INIT_SPACE: usize = 32 + 1 + 8 + 2 + 1 + 1 + 1 + 1 + MAX_PRIZE_POOLS + 32 + 8
+        + 4 * MAX_PRIZE_POOLS
+        + 8
+        + 1
+        + 1
+        + 8 * MAX_PRIZE_POOLS
+        + 8
+        + 32
+        + 1
+        + 1
+        + 1
+        + 2
+        + 2
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 32 * MAX_ORACLE_QUEUES
+        + 1
+        + 8
+        + PaymentMintInfo::INIT_SPACE * MAX_PAYMENT_MINTS
+        + 1
+        + 8 * MAX_PRIZE_POOLS
+        + 8 * MAX_PRIZE_POOLS
+        + 8
+        + 8
+        + 8
+        + 2
+        + 1
+        + 8
+        + 2
+        + 32
+        + 8 * MAX_ROUTE_DISCRIMINATORS
+        + 1
+        + 32
+        + 2
+        + 8
+        + 8
+        + CollectionConfig::INIT_SPACE
+        + 4
+        + 4
+        + 1
+        + 32
+        + 32 * MAX_WITHDRAW_RECIPIENTS
+        + 8 * MAX_WITHDRAW_RECIPIENTS
+        + 1
+        + 1
+        + 1
+        + 8
+        + 32 * MAX_PRIZE_POOLS
+        + 1
+        + 8
+        + 8 * MAX_PRIZE_POOLS
+        + 8 * MAX_PRIZE_POOLS
+        + 8
+        + 1
+        + 8
+        + 8
+        + 8 * 6
+        + 8
+        + 32
+        + 32
+        + 32
+        + 32
+        + 4 * MAX_PRIZE_POOLS;
+
+    /// 卡数上下限是否合法: 1 <= min_cards <= max_cards <= MAX_CARDS_LIMIT
+    pub fn card_limits_valid(min_cards: u32, max_cards: u32) -> bool {
+        1 <= min_cards && min_cards <= max_cards && max_cards <= MAX_CARDS_LIMIT
+    }
+
+    /// 请求卡数是否在配置的上下限内
+    pub fn check_card_amount(&self, amount_of_cards: u32) -> Result<()> {
+        require!(
+            (self.min_cards..=self.max_cards).contains(&amount_of_cards),
+            IPFlowError::InvalidCardAmount
+        );
+        Ok(())
+    }
+
+    pub fn is_payment_mode_enabled(&self, payment_mode: PaymentMode) -> bool {
+        match payment_mode {
+            PaymentMode::SOL | PaymentMode::WSOL => self.sol_payments_enabled,
+            PaymentMode::USDT => self.usdt_payments_enabled,
+        }
+    }
+
+    pub fn is_mint_paused(&self) -> bool {
+        self.pause_flags & PAUSE_MINT != 0
+    }
+
+    pub fn is_claim_paused(&self) -> bool {
+        self.pause_flags & PAUSE_CLAIM != 0
+    }
+
+    /// claim 是否被拒绝: PAUSE_CLAIM 位始终生效；程序暂停时按 claims_allowed_while_paused 决定
+    ///
+    /// 退款不受程序暂停影响，仅由 PAUSE_REFUND 位控制
+    pub fn is_claim_blocked(&self) -> bool {
+        self.is_claim_paused() || (self.is_mint_paused() && !self.claims_allowed_while_paused)
+    }
+
+    pub fn is_refund_paused(&self) -> bool {
+        self.pause_flags & PAUSE_REFUND != 0
+    }
+
+    pub fn is_withdraw_paused(&self) -> bool {
+        self.pause_flags & PAUSE_ADMIN_WITHDRAW != 0
+    }
+
+    /// 当前发放窗口结束时间戳
+    pub fn payout_window_end(&self) -> i64 {
+        self.payout_window_start.saturating_add(PAYOUT_WINDOW_SECONDS)
+    }
+
+    /// 窗口已满 24 小时则从 now 开启新窗口并清零累计
+    pub fn roll_payout_window(&mut self, now: i64) {
+        if now >= self.payout_window_end() {
+            self.payout_window_start = now;
+            self.payout_in_window_usd = 0;
+        }
+    }
+
+    /// 在 now 时刻发放 amount_usd 是否会超出 24 小时发放上限 (不修改状态)
+    pub fn exceeds_payout_cap(&self, amount_usd: u64, now: i64) -> bool {
+        if self.daily_payout_cap_usd == 0 || now >= self.payout_window_end() {
+            return self.daily_payout_cap_usd != 0 && amount_usd > self.daily_payout_cap_usd;
+        }
+        self.payout_in_window_usd.saturating_add(amount_usd) > self.daily_payout_cap_usd
+    }
+
+    /// Vault 可用余额是否低于新请求所需的储备下限 (0 表示不限制)
+    pub fn is_below_vault_reserve(&self, vault_available_lamports: u64) -> bool {
+        vault_available_lamports < self.min_vault_reserve_lamports
+    }
+
+    /// 距上次 emit_stats 是否已满 STATS_EMIT_INTERVAL_SECONDS
+    pub fn can_emit_stats(&self, now: i64) -> bool {
+        now.saturating_sub(self.last_stats_ts) >= STATS_EMIT_INTERVAL_SECONDS
+    }
+
+    /// 生效的 VRF 程序 ID (未迁移的配置为零值时回退到 SDK 常量)
+    pub fn vrf_program_id(&self) -> Pubkey {
+        if self.vrf_program == Pubkey::default() {
+            ephemeral_vrf_sdk::consts::VRF_PROGRAM_ID
+        } else {
+            self.vrf_program
+        }
+    }
+
+    /// 生效的 VRF 回调签名身份 (未迁移的配置为零值时回退到 SDK 常量)
+    pub fn vrf_identity_key(&self) -> Pubkey {
+        if self.vrf_identity == Pubkey::default() {
+            ephemeral_vrf_sdk::consts::VRF_PROGRAM_IDENTITY
+        } else {
+            self.vrf_identity
+        }
+    }
+
+    /// 已登记的 Vault WSOL 账户 (未调用 init_vault_wsol 时为 None)
+    pub fn pinned_vault_wsol_account(&self) -> Option<Pubkey> {
+        (self.vault_wsol_account != Pubkey::default()).then_some(self.vault_wsol_account)
+    }
+
+    /// 经济参数变更是否需要 co-admin 确认
+    pub fn economics_requires_co_admin(&self) -> bool {
+        self.co_admin != Pubkey::default()
+    }
+
+    /// epoch 统计是否已启用 (start_epoch 之后)
+    pub fn epochs_started(&self) -> bool {
+        self.epoch_start_ts != 0
+    }
+
+    /// 当前 epoch 是否已满 epoch_length_seconds，可被 roll
+    pub fn can_roll_epoch(&self, now: i64) -> bool {
+        now.saturating_sub(self.epoch_start_ts) >= self.epoch_length_seconds as i64
+    }
+
+    /// 配置不变量校验 (initialize / migrate_config 结束时调用)
+    ///
+    /// - admin 非默认值，platform_fee_bps <= 10000
+    /// - 各计数不超过容量，活跃池索引唯一且小于 prize_pool_count
+    /// - 退款超时与发放比例在允许范围内
+    /// - 卡数上下限满足 1 <= min_cards <= max_cards <= MAX_CARDS_LIMIT
+    pub fn validate(&self) -> Result<()> {
+        require!(self.admin != Pubkey::default(), IPFlowError::InvalidAdmin);
+        require!(
+            self.platform_fee_bps as u64 <= BPS_DENOMINATOR,
+            IPFlowError::InvalidPlatformFee
+        );
+        require!(
+            self.prize_pool_count as usize <= MAX_PRIZE_POOLS
+                && self.active_pool_count as usize <= MAX_PRIZE_POOLS
+                && (1..=MAX_ORACLE_QUEUES).contains(&(self.oracle_queue_count as usize))
+                && self.payment_mint_count as usize <= MAX_PAYMENT_MINTS
+                && self.route_discriminator_count as usize <= MAX_ROUTE_DISCRIMINATORS,
+            IPFlowError::InvalidConfigState
+        );
+
+        let active = &self.active_pool_indices[..self.active_pool_count as usize];
+        for (i, index) in active.iter().enumerate() {
+            require!(
+                *index < self.prize_pool_count && !active[..i].contains(index),
+                IPFlowError::InvalidConfigState
+            );
+        }
+
+        require!(
+            self.request_timeout_seconds > 0
+                && self.request_timeout_seconds <= MAX_REQUEST_TIMEOUT_SECONDS,
+            IPFlowError::InvalidConfigState
+        );
+        require!(
+            (MIN_PAYOUT_BPS..=MAX_PAYOUT_BPS).contains(&self.sol_payout_bps)
+                && (MIN_PAYOUT_BPS..=MAX_PAYOUT_BPS).contains(&self.token_payout_bps),
+            IPFlowError::InvalidConfigState
+        );
+        require!(
+            Self::card_limits_valid(self.min_cards, self.max_cards),
+            IPFlowError::InvalidConfigState
+        );
+        Ok(())
+    }
+
+    /// queue 是否在 VRF Oracle Queue 白名单中
+    pub fn is_oracle_queue_whitelisted(&self, queue: &Pubkey) -> bool {
+        self.oracle_queues[..self.oracle_queue_count as usize].contains(queue)
+    }
+
+    /// recipient 在提取白名单中的位置 (含未生效)
+    pub fn withdraw_recipient_position(&self, recipient: &Pubkey) -> Option<usize> {
+        self.withdraw_whitelist[..self.withdraw_whitelist_count as usize]
+            .iter()
+            .position(|r| r == recipient)
+    }
+
+    /// recipient 是否为 now 时刻已生效的提取白名单地址
+    pub fn is_withdraw_recipient_active(&self, recipient: &Pubkey, now: i64) -> bool {
+        self.withdraw_recipient_position(recipient)
+            .is_some_and(|pos| now >= self.withdraw_whitelist_active_after[pos])
+    }
+
+    /// 当前允许的 Jupiter 路由指令 discriminator
+    pub fn route_discriminators(&self) -> &[[u8; 8]] {
+        &self.allowed_route_discriminators[..self.route_discriminator_count as usize]
+    }
+
+    /// 查找白名单中的稳定币支付 Mint (含已停用)
+    pub fn find_payment_mint(&self, mint: &Pubkey) -> Option<&PaymentMintInfo> {
+        self.payment_mints[..self.payment_mint_count as usize]
+            .iter()
+            .find(|info| info.mint == *mint)
+    }
+
+    /// 中奖金额是否需要人工审核
+    pub fn requires_review(&self, total_won_usd: u64) -> bool {
+        self.large_win_threshold_usd != 0 && total_won_usd > self.large_win_threshold_usd
+    }
+
+    /// 在 now 时刻处于排期窗口内的活跃池 (保持原顺序压缩，255 填充空位) 及数量
+    ///
+    /// 窗口外的池直接跳过，其余池之间仍为等概率选择
+    pub fn scheduled_pool_indices(&self, now: i64) -> ([u8; MAX_PRIZE_POOLS], u8) {
+        let mut indices = [255u8; MAX_PRIZE_POOLS];
+        let mut count = 0usize;
+        for pos in 0..self.active_pool_count as usize {
+            if is_within_schedule(
+                self.active_pool_active_from[pos],
+                self.active_pool_active_until[pos],
+                now,
+            ) {
+                indices[count] = self.active_pool_indices[pos];
+                count += 1;
+            }
+        }
+        (indices, count as u8)
+    }
+
+    /// 查找奖品池索引在 active_pool_indices 中的位置
+    pub fn active_pool_position(&self, index: u8) -> Option<usize> {
+        self.active_pool_indices[..self.active_pool_count as usize]
+            .iter()
+            .position(|&i| i == index)
+    }
+
+    /// 选中池的待领取计数 +1 (揭示时，NO_PRIZE_POOL 哨兵忽略)
+    pub fn add_pool_pending_claim(&mut self, index: u8) -> Result<()> {
+        if let Some(count) = self.pool_pending_claims.get_mut(index as usize) {
+            *count = count.checked_add(1).ok_or(IPFlowError::MathOverflow)?;
+        }
+        Ok(())
+    }
+
+    /// 选中池的待领取计数 -1 (领取 / 过期 / 审核拒绝时；池已被强制删除时同样扣减)
+    pub fn release_pool_pending_claim(&mut self, index: u8) {
+        if let Some(count) = self.pool_pending_claims.get_mut(index as usize) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// 请求快照的候选池待揭示计数各 +1 (request_mint 时)
+    pub fn add_pool_pending_reveals(&mut self, indices: &[u8]) -> Result<()> {
+        for &index in indices {
+            if let Some(count) = self.pool_pending_reveals.get_mut(index as usize) {
+                *count = count.checked_add(1).ok_or(IPFlowError::MathOverflow)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 请求快照的候选池待揭示计数各 -1 (揭示完成 / 退款时；池已被强制删除时同样扣减)
+    pub fn release_pool_pending_reveals(&mut self, indices: &[u8]) {
+        for &index in indices {
+            if let Some(count) = self.pool_pending_reveals.get_mut(index as usize) {
+                *count = count.saturating_sub(1);
+            }
+        }
+    }
+
+    /// 奖品池的生效 Tier 阈值表 (未覆盖或不在活跃列表中时为默认表)
+    pub fn pool_tier_thresholds(&self, index: u8) -> [u64; 4] {
+        let tier_override = self
+            .active_pool_position(index)
+            .map_or(NO_TIER_OVERRIDE, |pos| self.active_pool_tier_overrides[pos]);
+        resolve_tier_thresholds(&tier_override)
+    }
+}
+
+/// get_config 写入 return data 的稳定配置视图，供 CPI 调用方反序列化，无需依赖 IPFlowState 布局
+///
+/// 兼容约定: 字段只追加在末尾并递增 view_version；view_len 为整个视图序列化后的字节数。
+/// 旧版调用方通过 `ConfigView::decode` 读取已知前缀，忽略新版本追加的尾部字段。
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ConfigView {
+    /// 视图版本 (CONFIG_VIEW_VERSION)
+    pub view_version: u8,
+    /// 视图序列化后的总字节数 (含本字段)
+    pub view_len: u16,
+    /// IPFlowState 布局版本
+    pub config_version: u8,
+    pub admin: Pubkey,
+    pub is_paused: bool,
+    pub pause_flags: u8,
+    /// 单张卡价格 (micro-USD)
+    pub card_price_usd: u64,
+    pub platform_fee_bps: u16,
+    pub sol_payout_bps: u16,
+    pub token_payout_bps: u16,
+    pub min_cards: u32,
+    pub max_cards: u32,
+    /// Pending 请求的退款超时 (秒)
+    pub request_timeout_seconds: i64,
+    /// 揭示后的默认领取期限 (秒)
+    pub claim_timeout_seconds: i64,
+    pub max_price_age_seconds: u64,
+    pub pool_count: u8,
+    pub prize_pool_count: u8,
+    pub active_pool_count: u8,
+    /// 新请求使用的主 VRF Oracle Queue
+    pub oracle_queue: Pubkey,
+    /// VRF Oracle Queue 白名单
+    pub oracle_queues: Vec<Pubkey>,
+    /// Pyth SOL/USD feed id
+    pub pyth_feed_id: [u8; 32],
+}
+
+impl ConfigView {
+    pub fn from_state(config: &IPFlowState) -> Self {
+        let mut view = Self {
+            view_version: CONFIG_VIEW_VERSION,
+            view_len: 0,
+            config_version: config.config_version,
+            admin: config.admin,
+            is_paused: config.is_paused,
+            pause_flags: config.pause_flags,
+            card_price_usd: TICKET_PRICE_MICRO_USD,
+            platform_fee_bps: config.platform_fee_bps,
+            sol_payout_bps: config.sol_payout_bps,
+            token_payout_bps: config.token_payout_bps,
+            min_cards: config.min_cards,
+            max_cards: config.max_cards,
+            request_timeout_seconds: config.request_timeout_seconds,
+            claim_timeout_seconds: CLAIM_TIMEOUT_SECONDS,
+            max_price_age_seconds: config.max_price_age_seconds,
+            pool_count: config.pool_count,
+            prize_pool_count: config.prize_pool_count,
+            active_pool_count: config.active_pool_count,
+            oracle_queue: config.oracle_queue,
+            oracle_queues: config.oracle_queues[..config.oracle_queue_count as usize].to_vec(),
+            pyth_feed_id: PYTH_SOL_USD_FEED_ID,
+        };
+        view.view_len = view.serialized_len() as u16;
+        view
+    }
+
+    /// 序列化后的字节数 (固定部分 + Vec 长度前缀 + 白名单 queue)
+    fn serialized_len(&self) -> usize {
+        1 + 2 + 1 + 32 + 1 + 1 + 8 + 2 + 2 + 2 + 4 + 4 + 8 + 8 + 8 + 1 + 1 + 1 + 32
+            + 4
+            + 32 * self.oracle_queues.len()
+            + 32
+    }
+
+    /// 从 get_config 的 return data 读取视图；新版本追加在 view_len 范围内的未知字段被忽略
+    ///
+    /// # 错误
+    /// - `InvalidConfigView`: 数据不足以解析已知字段，或短于 view_len
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let view = Self::deserialize(&mut &data[..])
+            .map_err(|_| error!(IPFlowError::InvalidConfigView))?;
+        require!(
+            data.len() >= view.view_len as usize,
+            IPFlowError::InvalidConfigView
+        );
+        Ok(view)
+    }
+}
+
+#[cfg(test)]
+impl IPFlowState {
+    /// 全零的配置 (与 init 后尚未写入的账户相同)，供单元测试在其上设置所需字段
+    pub(crate) fn zeroed() -> Self {
+        Self::try_deserialize_unchecked(&mut &[0u8; 8 + Self::INIT_SPACE][..]).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::WITHDRAW_RECIPIENT_DELAY_SECONDS;
+
+    fn config_with_cap(cap: u64, window_start: i64, in_window: u64) -> IPFlowState {
+        let mut config = IPFlowState::zeroed();
+        config.daily_payout_cap_usd = cap;
+        config.payout_window_start = window_start;
+        config.payout_in_window_usd = in_window;
+        config
+    }
+
+    #[test]
+    fn stats_emit_rate_limited_to_interval() {
+        let mut config = config_with_cap(0, 0, 0);
+        assert!(config.can_emit_stats(1_700_000_000));
+
+        config.last_stats_ts = 1_700_000_000;
+        assert!(!config.can_emit_stats(1_700_000_000 + STATS_EMIT_INTERVAL_SECONDS - 1));
+        assert!(config.can_emit_stats(1_700_000_000 + STATS_EMIT_INTERVAL_SECONDS));
+    }
+
+    #[test]
+    fn vrf_keys_default_to_sdk_constants_until_overridden() {
+        let mut config = config_with_cap(0, 0, 0);
+        assert_eq!(config.vrf_program_id(), ephemeral_vrf_sdk::consts::VRF_PROGRAM_ID);
+        assert_eq!(config.vrf_identity_key(), ephemeral_vrf_sdk::consts::VRF_PROGRAM_IDENTITY);
+
+        let (program, identity) = (Pubkey::new_unique(), Pubkey::new_unique());
+        config.vrf_program = program;
+        config.vrf_identity = identity;
+        assert_eq!(config.vrf_program_id(), program);
+        assert_eq!(config.vrf_identity_key(), identity);
+    }
+
+    #[test]
+    fn economics_single_key_until_co_admin_configured() {
+        let mut config = config_with_cap(0, 0, 0);
+        assert!(!config.economics_requires_co_admin());
+
+        config.co_admin = Pubkey::new_unique();
+        assert!(config.economics_requires_co_admin());
+    }
+
+    #[test]
+    fn vault_reserve_floor() {
+        let mut config = config_with_cap(0, 0, 0);
+        assert!(!config.is_below_vault_reserve(0));
+
+        config.min_vault_reserve_lamports = 5_000_000_000;
+        assert!(config.is_below_vault_reserve(4_999_999_999));
+        assert!(!config.is_below_vault_reserve(5_000_000_000));
+    }
+
+    #[test]
+    fn payout_cap_disabled_when_zero() {
+        let config = config_with_cap(0, 0, u64::MAX);
+        assert!(!config.exceeds_payout_cap(u64::MAX, 0));
+    }
+
+    #[test]
+    fn payout_cap_blocks_within_window() {
+        let config = config_with_cap(100_000_000, 1_000, 90_000_000);
+        assert!(!config.exceeds_payout_cap(10_000_000, 1_000 + 60));
+        assert!(config.exceeds_payout_cap(10_000_001, 1_000 + 60));
+    }
+
+    #[test]
+    fn large_win_review_threshold_is_exclusive() {
+        let mut config = config_with_cap(0, 0, 0);
+        assert!(!config.requires_review(u64::MAX));
+
+        config.large_win_threshold_usd = 500_000_000;
+        assert!(!config.requires_review(500_000_000));
+        assert!(config.requires_review(500_000_001));
+    }
+
+    #[test]
+    fn claim_pause_policy_combinations() {
+        let mut config = config_with_cap(0, 0, 0);
+        // (程序暂停, 暂停期间允许领取) -> 是否拒绝领取
+        for (paused, allowed, blocked) in [
+            (false, false, false),
+            (false, true, false),
+            (true, false, true),
+            (true, true, false),
+        ] {
+            config.pause_flags = if paused { PAUSE_MINT } else { 0 };
+            config.claims_allowed_while_paused = allowed;
+            assert_eq!(config.is_claim_blocked(), blocked, "paused={paused}, allowed={allowed}");
+            // 程序暂停不影响退款
+            assert!(!config.is_refund_paused());
+        }
+
+        // 显式的 PAUSE_CLAIM 位不受策略开关影响
+        config.pause_flags = PAUSE_CLAIM;
+        config.claims_allowed_while_paused = true;
+        assert!(config.is_claim_blocked());
+    }
+
+    #[test]
+    fn withdraw_recipient_active_only_after_delay() {
+        let mut config = config_with_cap(0, 0, 0);
+        let recipient = Pubkey::new_unique();
+        assert!(!config.is_withdraw_recipient_active(&recipient, i64::MAX));
+        // 未使用的空位不能被默认地址匹配
+        assert!(!config.is_withdraw_recipient_active(&Pubkey::default(), i64::MAX));
+
+        config.withdraw_whitelist[0] = recipient;
+        config.withdraw_whitelist_active_after[0] = 1_000 + WITHDRAW_RECIPIENT_DELAY_SECONDS;
+        config.withdraw_whitelist_count = 1;
+        assert_eq!(config.withdraw_recipient_position(&recipient), Some(0));
+        assert!(!config.is_withdraw_recipient_active(&recipient, 1_000));
+        assert!(config.is_withdraw_recipient_active(
+            &recipient,
+            1_000 + WITHDRAW_RECIPIENT_DELAY_SECONDS
+        ));
+    }
+
+    #[test]
+    fn oracle_queue_whitelist_ignores_unused_slots() {
+        let mut config = config_with_cap(0, 0, 0);
+        assert!(!config.is_oracle_queue_whitelisted(&Pubkey::default()));
+
+        let primary = Pubkey::new_unique();
+        config.oracle_queues[0] = primary;
+        config.oracle_queue_count = 1;
+        assert!(config.is_oracle_queue_whitelisted(&primary));
+        assert!(!config.is_oracle_queue_whitelisted(&Pubkey::default()));
+    }
+
+    #[test]
+    fn payment_mint_lookup_ignores_unused_slots() {
+        let mut config = config_with_cap(0, 0, 0);
+        assert!(config.find_payment_mint(&Pubkey::default()).is_none());
+
+        let usdc = Pubkey::new_unique();
+        config.payment_mints[0] = PaymentMintInfo {
+            mint: usdc,
+            decimals: 6,
+            enabled: false,
+        };
+        config.payment_mint_count = 1;
+        assert_eq!(config.find_payment_mint(&usdc).unwrap().decimals, 6);
+        assert!(!config.find_payment_mint(&usdc).unwrap().enabled);
+        assert!(config.find_payment_mint(&Pubkey::default()).is_none());
+    }
+
+    #[test]
+    fn scheduled_pools_skip_out_of_window_entries() {
+        let mut config = config_with_cap(0, 0, 0);
+        config.active_pool_count = 3;
+        config.active_pool_indices[..3].copy_from_slice(&[0, 1, 2]);
+        // 池 1 尚未生效，池 2 已过期
+        config.active_pool_active_from[1] = 2_000;
+        config.active_pool_active_until[2] = 1_500;
+
+        let (indices, count) = config.scheduled_pool_indices(1_500);
+        assert_eq!(count, 1);
+        assert_eq!(indices[0], 0);
+        assert_eq!(indices[1], 255);
+
+        let (indices, count) = config.scheduled_pool_indices(2_000);
+        assert_eq!(count, 2);
+        assert_eq!(&indices[..2], &[0, 1]);
+
+        let (_, count) = config.scheduled_pool_indices(1_499);
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn pool_tier_thresholds_fall_back_to_default() {
+        use crate::constants::{DEFAULT_TIER_THRESHOLDS, NO_PRIZE_POOL, PROB_PRECISION};
+
+        let mut config = config_with_cap(0, 0, 0);
+        config.active_pool_count = 2;
+        config.active_pool_indices[..2].copy_from_slice(&[4, 9]);
+        let boosted = [150_000, 650_000, 900_000, PROB_PRECISION];
+        config.active_pool_tier_overrides[1] = boosted;
+
+        assert_eq!(config.pool_tier_thresholds(4), DEFAULT_TIER_THRESHOLDS);
+        assert_eq!(config.pool_tier_thresholds(9), boosted);
+        // 未选中池 / 已删除的池使用默认表
+        assert_eq!(config.pool_tier_thresholds(NO_PRIZE_POOL), DEFAULT_TIER_THRESHOLDS);
+    }
+
+    #[test]
+    fn epoch_rolls_only_after_length_elapsed() {
+        let mut config = config_with_cap(0, 0, 0);
+        assert!(!config.epochs_started());
+
+        config.epoch_start_ts = 1_000;
+        config.epoch_length_seconds = 600;
+        assert!(config.epochs_started());
+        assert!(!config.can_roll_epoch(1_599));
+        assert!(config.can_roll_epoch(1_600));
+    }
+
+    #[test]
+    fn payout_window_rolls_after_24h() {
+        let mut config = config_with_cap(100_000_000, 1_000, 100_000_000);
+        let now = 1_000 + PAYOUT_WINDOW_SECONDS;
+        assert!(!config.exceeds_payout_cap(50_000_000, now));
+
+        config.roll_payout_window(now);
+        assert_eq!(config.payout_window_start, now);
+        assert_eq!(config.payout_in_window_usd, 0);
+    }
+
+    #[test]
+    fn route_discriminators_respects_count() {
+        let mut config = config_with_cap(0, 0, 0);
+        assert!(config.route_discriminators().is_empty());
+
+        config.allowed_route_discriminators[0] = [1u8; 8];
+        config.allowed_route_discriminators[1] = [2u8; 8];
+        config.route_discriminator_count = 1;
+        assert_eq!(config.route_discriminators(), &[[1u8; 8]]);
+    }
+
+    fn valid_config() -> IPFlowState {
+        let mut config = config_with_cap(0, 0, 0);
+        config.admin = Pubkey::new_unique();
+        config.platform_fee_bps = 500;
+        config.prize_pool_count = 3;
+        config.active_pool_count = 2;
+        config.active_pool_indices[0] = 0;
+        config.active_pool_indices[1] = 2;
+        config.oracle_queue_count = 1;
+        config.request_timeout_seconds = 45;
+        config.sol_payout_bps = 9_500;
+        config.token_payout_bps = 10_000;
+        config.min_cards = 1;
+        config.max_cards = 100;
+        config
+    }
+
+    #[test]
+    fn validate_accepts_sane_config() {
+        assert!(valid_config().validate().is_ok());
+    }
+
+    #[test]
+    fn card_limits_bounds() {
+        assert!(IPFlowState::card_limits_valid(1, 100));
+        assert!(IPFlowState::card_limits_valid(500, 500));
+        assert!(IPFlowState::card_limits_valid(1, MAX_CARDS_LIMIT));
+        assert!(!IPFlowState::card_limits_valid(0, 100));
+        assert!(!IPFlowState::card_limits_valid(101, 100));
+        assert!(!IPFlowState::card_limits_valid(1, MAX_CARDS_LIMIT + 1));
+
+        let mut config = valid_config();
+        config.min_cards = 5;
+        config.max_cards = 500;
+        assert!(config.check_card_amount(5).is_ok());
+        assert!(config.check_card_amount(500).is_ok());
+        assert_eq!(
+            config.check_card_amount(501).unwrap_err(),
+            error!(IPFlowError::InvalidCardAmount)
+        );
+        assert!(config.check_card_amount(4).is_err());
+
+        config.max_cards = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_bad_fee_and_admin() {
+        let mut config = valid_config();
+        config.platform_fee_bps = 10_001;
+        assert!(config.validate().is_err());
+
+        let mut config = valid_config();
+        config.admin = Pubkey::default();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_bad_active_indices() {
+        // 重复索引
+        let mut config = valid_config();
+        config.active_pool_indices[1] = 0;
+        assert!(config.validate().is_err());
+
+        // 超出 prize_pool_count
+        let mut config = valid_config();
+        config.active_pool_indices[1] = 3;
+        assert!(config.validate().is_err());
+
+        // 计数超出容量
+        let mut config = valid_config();
+        config.active_pool_count = MAX_PRIZE_POOLS as u8 + 1;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_bad_timeout_and_payout_bps() {
+        let mut config = valid_config();
+        config.request_timeout_seconds = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = valid_config();
+        config.request_timeout_seconds = MAX_REQUEST_TIMEOUT_SECONDS + 1;
+        assert!(config.validate().is_err());
+
+        let mut config = valid_config();
+        config.sol_payout_bps = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn config_view_len_matches_serialization() {
+        let mut config = valid_config();
+        config.oracle_queues[1] = Pubkey::new_unique();
+        config.oracle_queue_count = 2;
+
+        let view = ConfigView::from_state(&config);
+        let data = view.try_to_vec().unwrap();
+        assert_eq!(view.view_len as usize, data.len());
+        assert_eq!(view.oracle_queues.len(), 2);
+        assert!(data.len() <= anchor_lang::solana_program::program::MAX_RETURN_DATA);
+        assert_eq!(ConfigView::decode(&data).unwrap(), view);
+    }
+
+    #[test]
+    fn config_view_decodes_with_appended_fields() {
+        let view = ConfigView::from_state(&valid_config());
+
+        // 模拟新版本在末尾追加字段: view_len 随之增大，旧版 decode 只读取已知前缀
+        let mut newer = view.clone();
+        newer.view_version = CONFIG_VIEW_VERSION + 1;
+        newer.view_len += 9;
+        let mut data = newer.try_to_vec().unwrap();
+        data.extend_from_slice(&[0xAB; 9]);
+        assert_eq!(data.len(), newer.view_len as usize);
+        assert_eq!(ConfigView::decode(&data).unwrap(), newer);
+
+        // 截断的数据被拒绝
+        let short = &data[..data.len() - 1];
+        assert_eq!(
+            ConfigView::decode(short).unwrap_err(),
+            error!(IPFlowError::InvalidConfigView)
+        );
+        let full = view.try_to_vec().unwrap();
+        assert!(ConfigView::decode(&full[..full.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn pool_pending_claims_keyed_by_index() {
+        use crate::constants::NO_PRIZE_POOL;
+
+        let mut config = config_with_cap(0, 0, 0);
+        config.add_pool_pending_claim(7).unwrap();
+        config.add_pool_pending_claim(7).unwrap();
+        // 揭示时无活跃池的哨兵索引不计数
+        config.add_pool_pending_claim(NO_PRIZE_POOL).unwrap();
+        assert_eq!(config.pool_pending_claims[7], 2);
+
+        // 与活跃列表位置无关: 池被强制删除后领取仍正常扣减，且不会低于 0
+        config.release_pool_pending_claim(7);
+        config.release_pool_pending_claim(7);
+        config.release_pool_pending_claim(7);
+        config.release_pool_pending_claim(NO_PRIZE_POOL);
+        assert_eq!(config.pool_pending_claims[7], 0);
+    }
+
+    #[test]
+    fn pool_pending_reveals_track_snapshot_indices() {
+        let mut config = config_with_cap(0, 0, 0);
+        config.add_pool_pending_reveals(&[0, 2, 5]).unwrap();
+        config.add_pool_pending_reveals(&[2]).unwrap();
+        assert_eq!(config.pool_pending_reveals[..6], [1, 0, 2, 0, 0, 1]);
+
+        config.release_pool_pending_reveals(&[0, 2, 5]);
+        assert_eq!(config.pool_pending_reveals[2], 1);
+        config.release_pool_pending_reveals(&[2, 2]);
+        assert!(config.pool_pending_reveals.iter().all(|&count| count == 0));
+    }
 }
@@ -0,0 +1,126 @@
+// ==================== 单笔中奖排行榜状态定义 ====================
+
+use anchor_lang::prelude::*;
+
+/// 排行榜条目数量
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// 排行榜条目 (total_won_usd = 0 表示空位)
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Default, Debug,
+)]
+pub struct LeaderboardEntry {
+    pub user: Pubkey,
+    /// 单笔中奖总额 (micro-USD)
+    pub total_won_usd: u64,
+    /// 对应请求的 vrf_request_slot (request_id)
+    pub request_id: u64,
+    pub revealed_at: i64,
+}
+
+/// 本赛季单笔最大中奖 Top 10 PDA
+///
+/// Seeds: [b"leaderboard"]
+/// 由 VRF 回调更新 (按 total_won_usd 降序)，管理员 reset_leaderboard 开启新赛季
+#[account]
+#[derive(InitSpace)]
+pub struct Leaderboard {
+    /// 赛季序号 (每次 reset 递增)
+    pub season: u64,
+    pub entries: [LeaderboardEntry; LEADERBOARD_SIZE],
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl Leaderboard {
+    /// 插入排序：超过当前第 10 名时插入并返回名次 (0 起)，否则不修改并返回 None
+    ///
+    /// 最多移动 LEADERBOARD_SIZE - 1 个条目；未上榜结果仅比较一次
+    pub fn try_insert(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        if entry.total_won_usd <= self.entries[LEADERBOARD_SIZE - 1].total_won_usd {
+            return None;
+        }
+
+        // 同额时先到者排名靠前
+        let rank = self
+            .entries
+            .iter()
+            .position(|e| entry.total_won_usd > e.total_won_usd)?;
+        for i in (rank + 1..LEADERBOARD_SIZE).rev() {
+            self.entries[i] = self.entries[i - 1];
+        }
+        self.entries[rank] = entry;
+        Some(rank)
+    }
+
+    /// 清空所有条目并进入下一个赛季
+    pub fn reset(&mut self) {
+        self.entries = [LeaderboardEntry::default(); LEADERBOARD_SIZE];
+        self.season = self.season.saturating_add(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> Leaderboard {
+        Leaderboard {
+            season: 0,
+            entries: [LeaderboardEntry::default(); LEADERBOARD_SIZE],
+            bump: 255,
+        }
+    }
+
+    fn entry(won: u64, request_id: u64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            user: Pubkey::default(),
+            total_won_usd: won,
+            request_id,
+            revealed_at: 0,
+        }
+    }
+
+    #[test]
+    fn inserts_in_descending_order() {
+        let mut lb = board();
+        assert_eq!(lb.try_insert(entry(50, 1)), Some(0));
+        assert_eq!(lb.try_insert(entry(80, 2)), Some(0));
+        assert_eq!(lb.try_insert(entry(60, 3)), Some(1));
+        // 同额排在已有条目之后
+        assert_eq!(lb.try_insert(entry(60, 4)), Some(2));
+
+        let won: Vec<u64> = lb.entries[..4].iter().map(|e| e.total_won_usd).collect();
+        assert_eq!(won, vec![80, 60, 60, 50]);
+        assert_eq!(lb.entries[1].request_id, 3);
+    }
+
+    #[test]
+    fn drops_lowest_when_full_and_ignores_non_qualifying() {
+        let mut lb = board();
+        for i in 1..=LEADERBOARD_SIZE as u64 {
+            lb.try_insert(entry(i * 10, i));
+        }
+        assert_eq!(lb.entries[LEADERBOARD_SIZE - 1].total_won_usd, 10);
+
+        assert_eq!(lb.try_insert(entry(10, 99)), None);
+        assert_eq!(lb.try_insert(entry(15, 100)), Some(LEADERBOARD_SIZE - 1));
+        assert_eq!(lb.entries[LEADERBOARD_SIZE - 1].request_id, 100);
+        assert_eq!(lb.entries[0].total_won_usd, 100);
+    }
+
+    #[test]
+    fn zero_win_never_qualifies() {
+        let mut lb = board();
+        assert_eq!(lb.try_insert(entry(0, 1)), None);
+    }
+
+    #[test]
+    fn reset_clears_entries_and_bumps_season() {
+        let mut lb = board();
+        lb.try_insert(entry(50, 1));
+        lb.reset();
+        assert_eq!(lb.season, 1);
+        assert!(lb.entries.iter().all(|e| e.total_won_usd == 0));
+    }
+}
@@ -1,20 +1,28 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::{
+    BPS_DENOMINATOR, CLAIM_TIMEOUT_SECONDS, FEE_POLICY_ON_CLAIM, SWAP_FAILURE_RELIEF_ATTEMPTS,
+    SWAP_FAILURE_REPORT_INTERVAL_SECONDS,
+};
+use crate::errors::IPFlowError;
+use crate::state::global_config::{IPFlowState, MAX_PRIZE_POOLS};
+use crate::utils::{randomness_hash, resolve_tier_thresholds, RevealProgress, NO_TIER_OVERRIDE};
+
 // ==================== VRF 请求状态 ====================
 
 #[account]
 #[derive(InitSpace)]
 pub struct MintRequest {
-    /// 发起请求的用户
+    /// 奖金归属用户 (受益人，claim 需其签名；PDA 种子使用该地址)
     pub user: Pubkey, // 32 bytes
 
     /// 关联的 Randomness 账户 (用于 PDA 派生和 VRF 校验)
     pub randomness_account: Pubkey, // 32 bytes
 
-    /// 购买的周卡数量 (5U/张)
+    /// 购买的周卡数量 (每张 TICKET_PRICE_MICRO_USD，即 10U)
     pub amount_of_cards: u32, // 4 bytes
 
-    /// 请求状态 (Pending → Revealed → Claimed)
+    /// 请求状态 (Pending → Revealing → Revealed → Claiming → Claimed)
     pub status: RequestStatus, // 1 byte
 
     /// 用户选择的支付方式 (SOL 或 USDT)
@@ -32,7 +40,7 @@ pub struct MintRequest {
     /// 随机数揭示时间戳 (用于领取超时校验)
     pub revealed_at: i64, // 8 bytes
 
-    /// VRF 随机选中的奖品池索引 (0-4, Task 1.23)
+    /// VRF 随机选中的奖品池索引 (0-4, Task 1.23)；揭示时无活跃池则为 NO_PRIZE_POOL (仅可 SOL 领取)
     pub selected_pool_index: u8, // 1 byte
 
     /// VRF Commit 阶段锁定的 slot (用于防重放校验)
@@ -43,6 +51,396 @@ pub struct MintRequest {
 
     /// VRF 请求发起时的 slot (用于防重放校验和审计，兼容旧字段)
     pub vrf_request_slot: u64, // 8 bytes
+
+    /// Token 模式可领取的奖金 (micro-USD)，按选中池的 max_win_usd 截断后的值
+    /// SOL 模式始终使用 total_won_usd
+    pub token_won_usd: u64, // 8 bytes
+
+    /// VRF 请求的 caller_seed (= hash(client_seed))，用于可证明公平审计
+    pub caller_seed_hash: [u8; 32], // 32 bytes
+
+    /// 支付本金的钱包 (代付时与 user 不同)，超时退款本金及 PDA 租金退还给该地址
+    pub payer: Pubkey, // 32 bytes
+
+    /// 领取截止时间戳 (揭示时写入 revealed_at + CLAIM_TIMEOUT_SECONDS，熔断/审核/管理员可向后顺延)
+    /// 升级前揭示的请求此处为顺延秒数 (远小于 revealed_at)，由 effective_claim_deadline 换算
+    pub claim_deadline: i64, // 8 bytes
+
+    /// 大额中奖待人工审核 (为 true 时 claim 被拒绝，直到管理员 approve 或 reject)
+    pub needs_review: bool, // 1 byte
+
+    /// 发起 VRF 请求使用的 Oracle Queue (白名单中的任一 queue)
+    pub oracle_queue: Pubkey, // 32 bytes
+
+    /// 创建时 Vault 代付的 VRF 费用 (lamports)，用户超时退款时从 SOL 本金中扣除
+    pub vrf_fee_lamports: u64, // 8 bytes
+
+    /// 稳定币支付使用的 Mint (SOL 支付时为默认值)，退款按此 Mint 校验 Token 账户
+    pub payment_mint: Pubkey, // 32 bytes
+
+    /// 支付本金对应的美元价值 (micro-USD)，paid_amount 为实际转入的 raw amount
+    pub paid_usd: u64, // 8 bytes
+
+    /// 揭示时应用的 RTP 调节系数 (bps，10000 表示未调节)
+    pub rtp_modulation_bps: u16, // 2 bytes
+
+    /// VRF 随机数 (揭示时写入，用于复算逐卡结果)
+    pub randomness: [u8; 32], // 32 bytes
+
+    /// 揭示时生效的保底阈值 (0 表示未启用)；创建时按配置锁定，受益人无 UserStats 时回调置 0
+    pub reveal_pity_threshold: u16, // 2 bytes
+
+    /// 揭示前用户的保底计数器
+    pub reveal_pity_counter: u16, // 2 bytes
+
+    /// 已铸造的卡牌 NFT 数量 (分批铸造的游标)
+    pub cards_minted: u32, // 4 bytes
+
+    /// 分批揭示: 已处理卡数 (Revealing 状态下的游标)
+    pub cards_processed: u32, // 4 bytes
+
+    /// 分批揭示: 已处理卡的累计奖金 (micro-USD，RTP 调节前)
+    pub revealing_won_usd: u64, // 8 bytes
+
+    /// 分批揭示: 当前保底计数器
+    pub revealing_pity_counter: u16, // 2 bytes
+
+    /// 分批揭示: 已触发的保底提升次数
+    pub revealing_pity_upgrades: u32, // 4 bytes
+
+    /// 揭示失败原因 (FAILURE_*，仅 Failed 状态有意义)
+    pub failure_reason: u8, // 1 byte
+
+    /// SOL 支付换算所用的 Pyth SOL/USD 价格 (稳定币支付为 0)，与 paid_usd 一起用于收入核算
+    pub payment_price: i64, // 8 bytes
+
+    /// payment_price 的指数 (实际价格 = payment_price * 10^payment_price_expo)
+    pub payment_price_expo: i32, // 4 bytes
+
+    /// 揭示时托管到 escrow PDA 的中奖金额 (lamports，不含免租金额；0 表示未托管)
+    /// 领取时按 escrow_lamports / total_won_usd 的锁定汇率发放
+    pub escrow_lamports: u64, // 8 bytes
+
+    /// escrow PDA 的 bump
+    pub escrow_bump: u8, // 1 byte
+
+    /// 回调选池时锁定的 Tier 阈值表 (选中池的覆盖表或默认表)，逐卡复算使用
+    /// 全 0 表示未快照选池输入的旧请求 (按默认表开奖，揭示结算时再选池)
+    pub tier_thresholds: [u64; 4], // 32 bytes
+
+    /// 创建时锁定的平台手续费收取时机 (FEE_POLICY_ON_MINT / FEE_POLICY_ON_CLAIM)
+    /// 升级前创建的请求为 0 (铸造时收取)，切换策略不影响已创建的请求
+    pub fee_policy: u8, // 1 byte
+
+    /// 合作方标签 (如活动 ID)，程序不解析，随 MintRequested / LotteryRevealed / ClaimCompleted /
+    /// RefundCompleted 事件输出供链下聚合；未传入时全 0
+    pub memo: [u8; 32], // 32 bytes
+
+    /// 用户登记的 Token 领取 swap 失败次数 (滑点超限等)，达到 SWAP_FAILURE_RELIEF_ATTEMPTS 后
+    /// SOL 领取按 Token 发放比例计算
+    pub failed_swap_attempts: u8, // 1 byte
+
+    /// 最近一次登记 swap 失败的时间戳
+    pub last_swap_failure_at: i64, // 8 bytes
+
+    /// VRF 回调延迟 (秒，created_at 到收到随机数)，按 oracle_queue 累计到 QueueStats
+    pub reveal_latency_seconds: u64, // 8 bytes
+
+    /// 支付时收款的 Vault Token 账户 (SOL 支付及升级前创建的请求为默认值)
+    /// 退款只能从该账户转出，Vault 账户轮换后由管理员 refund_from_alternate 协助退款
+    pub vault_token_account: Pubkey, // 32 bytes
+
+    /// 可证明公平承诺 = hash(client_seed || vrf_request_slot || mint_request)，创建时写入
+    /// (升级前创建的请求为全 0)
+    pub commitment: [u8; 32], // 32 bytes
+
+    /// 是否已在创建时快照选池输入 (升级前创建的请求为 false，揭示结算时回退到实时配置)
+    pub pool_snapshot_taken: bool, // 1 byte
+
+    /// 创建时处于排期窗口内的活跃池数量 (回调据此选池，不受其后管理员增删池影响)
+    pub pool_snapshot_count: u8, // 1 byte
+
+    /// 创建时处于排期窗口内的活跃池索引 (前 pool_snapshot_count 个有效，其余为 255)
+    pub pool_snapshot_indices: [u8; MAX_PRIZE_POOLS], // 50 bytes
+
+    /// 首次处理的 VRF 随机数的 hash(randomness)，重复投递按此判断是否与已采用的随机数一致
+    /// (升级前揭示的请求为全 0，按已保存的 randomness 计算)
+    pub randomness_hash: [u8; 32], // 32 bytes
+
+    /// 用户是否通过 pre_authorize_claim 锁定了 Token 领取条款 (可覆盖或清除)
+    /// 锁定后 claim (含中继提交) 只能按下列路由、预期输出与滑点执行 Token 发放
+    pub claim_preauthorized: bool, // 1 byte
+
+    /// 预授权的 DEX 路由
+    pub preauth_router: SwapRouter, // 1 byte
+
+    /// 预授权的预期 Token 输出 (后端在揭示后按报价预先计算)
+    pub preauth_expected_output: u64, // 8 bytes
+
+    /// 预授权允许的最大滑点 (bps，不超过 MAX_CLAIM_SLIPPAGE_BPS)
+    pub preauth_max_slippage_bps: u16, // 2 bytes
+
+    /// 创建时快照的候选池 Tier 覆盖表 (与 pool_snapshot_indices 平行，全 0 表示默认表)
+    /// 覆盖表各项不超过 PROB_PRECISION，按 u32 存储
+    pub pool_snapshot_tier_overrides: [[u32; 4]; MAX_PRIZE_POOLS], // 800 bytes
+
+    /// 创建时快照的候选池 Token 模式奖金上限 (与 pool_snapshot_indices 平行，0 表示不限制)
+    pub pool_snapshot_max_win_usd: [u64; MAX_PRIZE_POOLS], // 400 bytes
+
+    /// 创建时的 VRF 回调签名身份 (回调据此校验调用者，不读取实时配置；升级前的请求为默认值)
+    pub vrf_identity: Pubkey, // 32 bytes
+
+    /// 最近一次由 record_payout_block 顺延领取期限的发放熔断窗口起点 (同一窗口只顺延一次；未登记时为 0)
+    pub payout_block_window_start: i64, // 8 bytes
+}
+
+/// 用户预授权的 Token 领取条款
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ClaimPreauthorization {
+    pub router: SwapRouter,
+    pub expected_token_output: u64,
+    pub max_slippage_bps: u16,
+}
+
+impl MintRequest {
+    /// migrate_mint_request 扩容后补齐旧版请求中零值不能表示旧语义的字段
+    ///
+    /// - payer 为默认值: 旧版请求由 user 自付
+    /// - USDT 支付且 payment_mint 为默认值: 旧版仅接受支付 Mint 白名单首位 (迁移前硬编码的 USDT)
+    /// - 已揭示、有奖金但 token_won_usd 为 0: 按选中池当前的奖金上限截断 total_won_usd
+    /// - rtp_modulation_bps 为 0: 未调节 (10000)
+    ///
+    /// 其余新增字段的零值即旧语义 (未托管、未预授权、铸造时收费等)。各条件只会由旧版布局触发，
+    /// 重复调用不改变已规范化的请求
+    pub fn normalize_legacy_fields(&mut self, config: &IPFlowState) {
+        if self.payer == Pubkey::default() {
+            self.payer = self.user;
+        }
+        if self.payment_mode == PaymentMode::USDT && self.payment_mint == Pubkey::default() {
+            self.payment_mint = config.payment_mints[0].mint;
+        }
+        if self.status == RequestStatus::Revealed
+            && self.token_won_usd == 0
+            && self.total_won_usd > 0
+        {
+            let pool_max_win_usd = config
+                .active_pool_position(self.selected_pool_index)
+                .map_or(0, |pos| config.active_pool_max_win_usd[pos]);
+            self.token_won_usd = if pool_max_win_usd > 0 {
+                self.total_won_usd.min(pool_max_win_usd)
+            } else {
+                self.total_won_usd
+            };
+        }
+        if self.rtp_modulation_bps == 0 {
+            self.rtp_modulation_bps = BPS_DENOMINATOR as u16;
+        }
+    }
+
+    /// 领取截止时间戳：优先使用揭示时写入的 claim_deadline，
+    /// 旧布局中该字段为顺延秒数时按 revealed_at + 24h + 顺延换算
+    pub fn effective_claim_deadline(&self) -> i64 {
+        if self.claim_deadline >= self.revealed_at {
+            self.claim_deadline
+        } else {
+            self.revealed_at
+                .saturating_add(CLAIM_TIMEOUT_SECONDS)
+                .saturating_add(self.claim_deadline)
+        }
+    }
+
+    /// 将领取期限顺延至 deadline (只向后推，不会缩短)
+    pub fn extend_claim_deadline_to(&mut self, deadline: i64) {
+        self.claim_deadline = self.effective_claim_deadline().max(deadline);
+    }
+
+    /// 登记发放熔断窗口 (起点 window_start，结束 window_end) 对领取的阻塞：
+    /// 在当前生效期限基础上顺延 (window_end - now)。同一窗口已登记时不再顺延，返回 false
+    pub fn credit_payout_block(&mut self, window_start: i64, window_end: i64, now: i64) -> bool {
+        if self.payout_block_window_start == window_start {
+            return false;
+        }
+        self.payout_block_window_start = window_start;
+        let blocked_for = window_end.saturating_sub(now).max(0);
+        self.extend_claim_deadline_to(self.effective_claim_deadline().saturating_add(blocked_for));
+        true
+    }
+
+    /// 当前分批揭示进度
+    pub fn reveal_progress(&self) -> RevealProgress {
+        RevealProgress {
+            cards_processed: self.cards_processed,
+            total_won_usd: self.revealing_won_usd,
+            pity_counter: self.revealing_pity_counter,
+            pity_upgrades: self.revealing_pity_upgrades,
+        }
+    }
+
+    /// 写回分批揭示进度
+    pub fn set_reveal_progress(&mut self, progress: &RevealProgress) {
+        self.cards_processed = progress.cards_processed;
+        self.revealing_won_usd = progress.total_won_usd;
+        self.revealing_pity_counter = progress.pity_counter;
+        self.revealing_pity_upgrades = progress.pity_upgrades;
+    }
+
+    /// 记录创建时的选池输入 (now 时刻处于排期窗口内的活跃池) 及各候选池的 Tier 覆盖表与奖金上限
+    pub fn snapshot_pools(&mut self, config: &IPFlowState, now: i64) {
+        let (indices, count) = config.scheduled_pool_indices(now);
+        self.pool_snapshot_taken = true;
+        self.pool_snapshot_count = count;
+        self.pool_snapshot_indices = indices;
+        self.pool_snapshot_tier_overrides = [[0u32; 4]; MAX_PRIZE_POOLS];
+        self.pool_snapshot_max_win_usd = [0u64; MAX_PRIZE_POOLS];
+        for (slot, &index) in indices[..count as usize].iter().enumerate() {
+            if let Some(pos) = config.active_pool_position(index) {
+                self.pool_snapshot_tier_overrides[slot] =
+                    config.active_pool_tier_overrides[pos].map(|threshold| threshold as u32);
+                self.pool_snapshot_max_win_usd[slot] = config.active_pool_max_win_usd[pos];
+            }
+        }
+    }
+
+    /// 快照中的候选池索引 (升级前的请求为空)
+    pub fn snapshot_pool_indices(&self) -> &[u8] {
+        if self.pool_snapshot_taken {
+            &self.pool_snapshot_indices[..self.pool_snapshot_count as usize]
+        } else {
+            &[]
+        }
+    }
+
+    /// 快照中奖品池的生效 Tier 阈值表 (不在快照中或无活跃池时为默认表)
+    pub fn snapshot_tier_thresholds(&self, index: u8) -> [u64; 4] {
+        let tier_override = self
+            .snapshot_pool_indices()
+            .iter()
+            .position(|&i| i == index)
+            .map_or(NO_TIER_OVERRIDE, |slot| {
+                self.pool_snapshot_tier_overrides[slot].map(u64::from)
+            });
+        resolve_tier_thresholds(&tier_override)
+    }
+
+    /// 选中池的 Token 模式奖金上限：快照请求使用创建时的上限，升级前的请求回退到实时配置
+    pub fn pool_max_win_usd(&self, config: &IPFlowState, index: u8) -> u64 {
+        if self.pool_snapshot_taken {
+            self.snapshot_pool_indices()
+                .iter()
+                .position(|&i| i == index)
+                .map_or(0, |slot| self.pool_snapshot_max_win_usd[slot])
+        } else {
+            config
+                .active_pool_position(index)
+                .map_or(0, |pos| config.active_pool_max_win_usd[pos])
+        }
+    }
+
+    /// VRF 回调应校验的签名身份 (升级前的请求未记录时为 SDK 常量)
+    pub fn vrf_identity_key(&self) -> Pubkey {
+        if self.vrf_identity == Pubkey::default() {
+            ephemeral_vrf_sdk::consts::VRF_PROGRAM_IDENTITY
+        } else {
+            self.vrf_identity
+        }
+    }
+
+    /// 回调选池使用的 (活跃池索引, 数量)：优先使用创建时的快照，升级前的请求回退到 now 时刻的实时配置
+    pub fn selection_pools(&self, config: &IPFlowState, now: i64) -> ([u8; MAX_PRIZE_POOLS], u8) {
+        if self.pool_snapshot_taken {
+            (self.pool_snapshot_indices, self.pool_snapshot_count)
+        } else {
+            config.scheduled_pool_indices(now)
+        }
+    }
+
+    /// 已采用随机数的 hash (升级前揭示的请求未记录时由 randomness 计算)
+    pub fn recorded_randomness_hash(&self) -> [u8; 32] {
+        if self.randomness_hash == [0u8; 32] {
+            randomness_hash(&self.randomness)
+        } else {
+            self.randomness_hash
+        }
+    }
+
+    /// 回调时是否已选定奖品池并锁定 Tier 阈值表
+    pub fn pool_selected(&self) -> bool {
+        self.tier_thresholds != NO_TIER_OVERRIDE
+    }
+
+    /// 开奖使用的 Tier 阈值表 (升级前的请求为默认表)
+    pub fn effective_tier_thresholds(&self) -> [u64; 4] {
+        resolve_tier_thresholds(&self.tier_thresholds)
+    }
+
+    /// 领取时应扣除的平台手续费比例 (bps)：仅 FEE_POLICY_ON_CLAIM 的请求按当前 platform_fee_bps 扣除
+    pub fn claim_fee_bps(&self, platform_fee_bps: u16) -> u16 {
+        if self.fee_policy == FEE_POLICY_ON_CLAIM {
+            platform_fee_bps
+        } else {
+            0
+        }
+    }
+
+    /// 登记一次 swap 失败 (距上次登记须满 SWAP_FAILURE_REPORT_INTERVAL_SECONDS)
+    pub fn record_swap_failure(&mut self, now: i64) -> std::result::Result<(), IPFlowError> {
+        if self.failed_swap_attempts > 0
+            && now < self
+                .last_swap_failure_at
+                .saturating_add(SWAP_FAILURE_REPORT_INTERVAL_SECONDS)
+        {
+            return Err(IPFlowError::SwapFailureReportTooSoon);
+        }
+        self.failed_swap_attempts = self.failed_swap_attempts.saturating_add(1);
+        self.last_swap_failure_at = now;
+        Ok(())
+    }
+
+    /// 登记的 swap 失败次数是否已达到放宽阈值
+    pub fn swap_failure_relief(&self) -> bool {
+        self.failed_swap_attempts >= SWAP_FAILURE_RELIEF_ATTEMPTS
+    }
+
+    /// SOL 部分适用的发放比例 (bps)：达到放宽阈值后取 SOL 与 Token 比例中较高者
+    pub fn sol_payout_bps(&self, sol_payout_bps: u16, token_payout_bps: u16) -> u16 {
+        if self.swap_failure_relief() {
+            sol_payout_bps.max(token_payout_bps)
+        } else {
+            sol_payout_bps
+        }
+    }
+
+    /// 已开始但未完成卡牌 NFT 铸造 (claim 后保留 PDA，由最后一批铸造关闭)
+    pub fn card_nfts_pending(&self) -> bool {
+        self.cards_minted > 0 && self.cards_minted < self.amount_of_cards
+    }
+
+    /// 已锁定的 Token 领取条款 (未预授权或已清除时为 None)
+    pub fn claim_preauthorization(&self) -> Option<ClaimPreauthorization> {
+        self.claim_preauthorized.then_some(ClaimPreauthorization {
+            router: self.preauth_router,
+            expected_token_output: self.preauth_expected_output,
+            max_slippage_bps: self.preauth_max_slippage_bps,
+        })
+    }
+
+    /// 写入 (Some) 或清除 (None) 预授权的 Token 领取条款
+    pub fn set_claim_preauthorization(&mut self, preauthorization: Option<ClaimPreauthorization>) {
+        let terms = preauthorization.unwrap_or(ClaimPreauthorization {
+            router: SwapRouter::default(),
+            expected_token_output: 0,
+            max_slippage_bps: 0,
+        });
+        self.claim_preauthorized = preauthorization.is_some();
+        self.preauth_router = terms.router;
+        self.preauth_expected_output = terms.expected_token_output;
+        self.preauth_max_slippage_bps = terms.max_slippage_bps;
+    }
+
+    /// 领取 (或卡牌 NFT 最后一批铸造) 后是否关闭 PDA：仅终态 Claimed 且卡牌 NFT 无未完成批次
+    pub fn closes_after_claim(&self) -> bool {
+        self.status.should_close_after_claim() && !self.card_nfts_pending()
+    }
 }
 
 #[derive(
@@ -58,6 +456,41 @@ pub enum RequestStatus {
     Claimed,
     /// 失败 (可退款)
     Failed,
+    /// 领取进行中 (CPI 前持久化，转账全部成功后才切换为 Claimed)
+    Claiming,
+    /// 已收到随机数，分批处理卡牌中 (continue_reveal 处理完成后切换为 Revealed)
+    Revealing,
+}
+
+impl RequestStatus {
+    /// claim 前置状态校验：仅 Revealed 可领取，其余状态返回对应的具体错误
+    pub fn check_claimable(&self) -> std::result::Result<(), IPFlowError> {
+        match self {
+            RequestStatus::Revealed => Ok(()),
+            RequestStatus::Pending => Err(IPFlowError::RequestStillPending),
+            RequestStatus::Claimed => Err(IPFlowError::RequestAlreadyClaimed),
+            RequestStatus::Failed => Err(IPFlowError::RequestFailed),
+            RequestStatus::Claiming => Err(IPFlowError::ClaimInProgress),
+            RequestStatus::Revealing => Err(IPFlowError::RevealInProgress),
+        }
+    }
+
+    /// Pending 状态校验 (VRF 回调 / 超时退款)，其余状态返回对应的具体错误
+    pub fn check_pending(&self) -> std::result::Result<(), IPFlowError> {
+        match self {
+            RequestStatus::Pending => Ok(()),
+            RequestStatus::Revealed => Err(IPFlowError::RequestAlreadyRevealed),
+            RequestStatus::Claimed => Err(IPFlowError::RequestAlreadyClaimed),
+            RequestStatus::Failed => Err(IPFlowError::RequestFailed),
+            RequestStatus::Claiming => Err(IPFlowError::ClaimInProgress),
+            RequestStatus::Revealing => Err(IPFlowError::RevealInProgress),
+        }
+    }
+
+    /// claim 结束时是否关闭 MintRequest PDA：仅最终状态为 Claimed 时关闭
+    pub fn should_close_after_claim(&self) -> bool {
+        *self == RequestStatus::Claimed
+    }
 }
 
 #[derive(
@@ -69,6 +502,8 @@ pub enum PayoutMode {
     SOL,
     /// IP 代币回购发放
     Token,
+    /// 按 sol_portion_bps 拆分: 部分 SOL 兑付，其余 swap 为奖品 Token
+    Split,
 }
 
 // ==================== 支付方式 ====================
@@ -80,8 +515,46 @@ pub enum PaymentMode {
     /// SOL 支付 (按 Pyth 实时汇率换算)
     #[default]
     SOL,
-    /// USDT 直接支付 (固定 5U/张)
+    /// 稳定币直接支付 (按 TICKET_PRICE_MICRO_USD 与 Mint 精度换算，10U/张)
     USDT,
+    /// WSOL Token 账户支付 (按 Pyth 实时汇率换算，与 SOL 支付共用开关)，WSOL 留存在 Vault 的 WSOL 账户
+    WSOL,
+}
+
+impl PaymentMode {
+    /// 是否以 lamports 计价 (SOL / WSOL)，Vault 代付的 VRF 费用仅对此类请求在超时退款时扣除
+    pub fn is_sol_denominated(self) -> bool {
+        matches!(self, PaymentMode::SOL | PaymentMode::WSOL)
+    }
+}
+
+// ==================== 退款触发来源 ====================
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RefundTrigger {
+    /// 用户在 VRF 超时后主动退款
+    Timeout,
+    /// 用户在 VRF 回调前主动取消
+    Cancel,
+    /// 揭示后超过领取期限，退还本金
+    Expired,
+    /// 揭示失败或大额中奖审核拒绝 (非用户原因)
+    Failed,
+    /// 过期 Pending 请求由 crank / sweep 清理
+    Crank,
+}
+
+impl RefundTrigger {
+    /// 是否从 SOL 计价的本金中扣除 Vault 已代付的 VRF 费用
+    ///
+    /// 用户主动发起 (超时、取消) 或已完成揭示后逾期 (领取过期) 的退款扣除，防止 mint+refund 循环消耗 Vault；
+    /// 非用户原因 (失败、审核拒绝) 与 crank 清理全额退还
+    pub fn deducts_vrf_fee(self) -> bool {
+        matches!(
+            self,
+            RefundTrigger::Timeout | RefundTrigger::Cancel | RefundTrigger::Expired
+        )
+    }
 }
 
 // ==================== Swap 路由选择 (Task 1.20) ====================
@@ -96,3 +569,408 @@ pub enum SwapRouter {
     /// Raydium CPMM 直连 (备选)
     Raydium,
 }
+
+#[cfg(test)]
+impl MintRequest {
+    /// 全零的请求 (与 init 后尚未写入的账户相同)，供单元测试在其上设置所需字段
+    pub(crate) fn zeroed() -> Self {
+        Self::try_deserialize_unchecked(&mut &[0u8; 8 + Self::INIT_SPACE][..]).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{DEFAULT_TIER_THRESHOLDS, FEE_POLICY_ON_MINT};
+
+    fn code(result: std::result::Result<(), IPFlowError>) -> Option<u32> {
+        result.err().map(|e| e as u32)
+    }
+
+    #[test]
+    fn test_check_claimable_maps_each_status() {
+        assert_eq!(code(RequestStatus::Revealed.check_claimable()), None);
+        assert_eq!(
+            code(RequestStatus::Pending.check_claimable()),
+            Some(IPFlowError::RequestStillPending as u32)
+        );
+        assert_eq!(
+            code(RequestStatus::Claimed.check_claimable()),
+            Some(IPFlowError::RequestAlreadyClaimed as u32)
+        );
+        assert_eq!(
+            code(RequestStatus::Failed.check_claimable()),
+            Some(IPFlowError::RequestFailed as u32)
+        );
+        assert_eq!(
+            code(RequestStatus::Claiming.check_claimable()),
+            Some(IPFlowError::ClaimInProgress as u32)
+        );
+    }
+
+    #[test]
+    fn test_check_pending_maps_each_status() {
+        assert_eq!(code(RequestStatus::Pending.check_pending()), None);
+        assert_eq!(
+            code(RequestStatus::Revealed.check_pending()),
+            Some(IPFlowError::RequestAlreadyRevealed as u32)
+        );
+        assert_eq!(
+            code(RequestStatus::Claimed.check_pending()),
+            Some(IPFlowError::RequestAlreadyClaimed as u32)
+        );
+        assert_eq!(
+            code(RequestStatus::Failed.check_pending()),
+            Some(IPFlowError::RequestFailed as u32)
+        );
+        assert_eq!(
+            code(RequestStatus::Claiming.check_pending()),
+            Some(IPFlowError::ClaimInProgress as u32)
+        );
+    }
+
+    #[test]
+    fn test_reentrant_claim_rejected_while_claiming() {
+        // 模拟 CPI 回调中重入 claim：首个 claim 已将状态持久化为 Claiming
+        let mut status = RequestStatus::Revealed;
+        assert!(status.check_claimable().is_ok());
+        status = RequestStatus::Claiming;
+
+        // 重入的 claim / record_payout_block / reject_large_win 均在前置校验处被拒绝
+        assert_eq!(
+            code(status.check_claimable()),
+            Some(IPFlowError::ClaimInProgress as u32)
+        );
+        // CPI 中途状态不会触发关闭，PDA 保持存在
+        assert!(!status.should_close_after_claim());
+    }
+
+    #[test]
+    fn test_claiming_status_persisted_by_exit() {
+        // claim 在 CPI 前对 Account 调用 exit：重入调用从同一账户数据重新加载时须读到 Claiming
+        let key = Pubkey::new_unique();
+        let mut lamports = 1u64;
+        let mut data = vec![0u8; 8 + MintRequest::INIT_SPACE];
+        revealed_request(1_700_000_000, 1_700_086_400)
+            .try_serialize(&mut &mut data[..])
+            .unwrap();
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &crate::ID, false, 0);
+
+        let mut request = Account::<MintRequest>::try_from(&info).unwrap();
+        assert!(request.status.check_claimable().is_ok());
+        request.status = RequestStatus::Claiming;
+        request.exit(&crate::ID).unwrap();
+
+        let reentrant = Account::<MintRequest>::try_from(&info).unwrap();
+        assert_eq!(reentrant.status, RequestStatus::Claiming);
+        assert_eq!(
+            code(reentrant.status.check_claimable()),
+            Some(IPFlowError::ClaimInProgress as u32)
+        );
+    }
+
+    #[test]
+    fn test_should_close_only_when_claimed() {
+        assert!(RequestStatus::Claimed.should_close_after_claim());
+        for status in [
+            RequestStatus::Pending,
+            RequestStatus::Revealed,
+            RequestStatus::Failed,
+            RequestStatus::Claiming,
+            RequestStatus::Revealing,
+        ] {
+            assert!(!status.should_close_after_claim());
+        }
+    }
+
+    #[test]
+    fn test_request_pda_survives_until_terminal_claim() {
+        let mut request = revealed_request(1_700_000_000, 1_700_086_400);
+        request.amount_of_cards = 10;
+
+        // 提前返回 / 降级路径停留在非终态时不关闭
+        for status in [
+            RequestStatus::Pending,
+            RequestStatus::Revealing,
+            RequestStatus::Revealed,
+            RequestStatus::Claiming,
+            RequestStatus::Failed,
+        ] {
+            request.status = status;
+            assert!(!request.closes_after_claim());
+        }
+
+        // 已领取但卡牌 NFT 尚有未铸造批次：保留给 mint_card_nfts
+        request.status = RequestStatus::Claimed;
+        request.cards_minted = 4;
+        assert!(!request.closes_after_claim());
+
+        // 终态且无未完成批次时关闭 (未铸造 NFT 或已全部铸造)
+        request.cards_minted = 0;
+        assert!(request.closes_after_claim());
+        request.cards_minted = 10;
+        assert!(request.closes_after_claim());
+    }
+
+    #[test]
+    fn test_claiming_appended_without_shifting_discriminants() {
+        // 新增变体追加在末尾，已存在账户的状态字节含义不变
+        assert_eq!(RequestStatus::Pending.try_to_vec().unwrap(), vec![0]);
+        assert_eq!(RequestStatus::Revealed.try_to_vec().unwrap(), vec![1]);
+        assert_eq!(RequestStatus::Claimed.try_to_vec().unwrap(), vec![2]);
+        assert_eq!(RequestStatus::Failed.try_to_vec().unwrap(), vec![3]);
+        assert_eq!(RequestStatus::Claiming.try_to_vec().unwrap(), vec![4]);
+        assert_eq!(RequestStatus::Revealing.try_to_vec().unwrap(), vec![5]);
+    }
+
+    fn revealed_request(revealed_at: i64, claim_deadline: i64) -> MintRequest {
+        let mut request = MintRequest::zeroed();
+        request.status = RequestStatus::Revealed;
+        request.revealed_at = revealed_at;
+        request.claim_deadline = claim_deadline;
+        request
+    }
+
+    #[test]
+    fn test_pool_selection_uses_request_time_snapshot() {
+        let mut config = IPFlowState::zeroed();
+        config.active_pool_count = 3;
+        config.active_pool_indices[..3].copy_from_slice(&[0, 2, 5]);
+
+        // 升级前的请求回退到实时配置
+        let mut request = revealed_request(0, 0);
+        assert_eq!(request.selection_pools(&config, 1_000).1, 3);
+
+        request.snapshot_pools(&config, 1_000);
+
+        // 管理员在揭示前移除池 5 并新增池 7，选池仍使用创建时的 [0, 2, 5]
+        config.active_pool_indices[..3].copy_from_slice(&[0, 2, 7]);
+        config.active_pool_count = 2;
+        let (indices, count) = request.selection_pools(&config, 2_000);
+        assert_eq!(count, 3);
+        assert_eq!(&indices[..3], &[0, 2, 5]);
+        assert!(indices[3..].iter().all(|&i| i == 255));
+    }
+
+    #[test]
+    fn test_pool_terms_use_request_time_snapshot() {
+        let mut config = IPFlowState::zeroed();
+        let tier_override = [100_000, 300_000, 600_000, 1_000_000];
+        config.active_pool_count = 2;
+        config.active_pool_indices[..2].copy_from_slice(&[1, 4]);
+        config.active_pool_tier_overrides[1] = tier_override;
+        config.active_pool_max_win_usd[1] = 50_000_000;
+
+        // 升级前的请求: 上限回退到实时配置，阈值表为默认表
+        let mut request = revealed_request(0, 0);
+        assert_eq!(request.pool_max_win_usd(&config, 4), 50_000_000);
+        assert_eq!(request.snapshot_tier_thresholds(4), DEFAULT_TIER_THRESHOLDS);
+
+        request.snapshot_pools(&config, 1_000);
+
+        // 管理员在揭示前修改池 4 的覆盖表与上限，并移除池 4
+        config.active_pool_tier_overrides[1] = NO_TIER_OVERRIDE;
+        config.active_pool_max_win_usd[1] = 0;
+        config.active_pool_count = 1;
+        assert_eq!(request.snapshot_tier_thresholds(4), tier_override);
+        assert_eq!(request.pool_max_win_usd(&config, 4), 50_000_000);
+        assert_eq!(request.snapshot_tier_thresholds(1), DEFAULT_TIER_THRESHOLDS);
+        assert_eq!(request.pool_max_win_usd(&config, 1), 0);
+        assert_eq!(request.snapshot_pool_indices(), &[1, 4]);
+    }
+
+    #[test]
+    fn test_stored_claim_deadline_used_directly() {
+        let request = revealed_request(1_000, 1_000 + CLAIM_TIMEOUT_SECONDS);
+        assert_eq!(request.effective_claim_deadline(), 1_000 + CLAIM_TIMEOUT_SECONDS);
+    }
+
+    #[test]
+    fn test_legacy_extension_converted_to_deadline() {
+        // 旧布局: 字段为熔断顺延秒数
+        let request = revealed_request(1_700_000_000, 3_600);
+        assert_eq!(
+            request.effective_claim_deadline(),
+            1_700_000_000 + CLAIM_TIMEOUT_SECONDS + 3_600
+        );
+    }
+
+    #[test]
+    fn test_extend_claim_deadline_never_shortens() {
+        let deadline = 1_000 + CLAIM_TIMEOUT_SECONDS;
+        let mut request = revealed_request(1_000, deadline);
+        request.extend_claim_deadline_to(deadline - 1);
+        assert_eq!(request.claim_deadline, deadline);
+        request.extend_claim_deadline_to(deadline + 60);
+        assert_eq!(request.claim_deadline, deadline + 60);
+    }
+
+    #[test]
+    fn test_payout_block_extends_effective_deadline_once_per_window() {
+        let revealed_at = 1_700_000_000;
+        let mut request = revealed_request(revealed_at, revealed_at + CLAIM_TIMEOUT_SECONDS);
+
+        // 审核 20 小时后通过: 期限推到 approved_at + 24h
+        let approved_at = revealed_at + 20 * 3_600;
+        request.extend_claim_deadline_to(approved_at + CLAIM_TIMEOUT_SECONDS);
+
+        // 随后被熔断阻塞 6 小时: 在审核后的期限基础上顺延，而不是 revealed_at + 24h
+        let now = approved_at + 3_600;
+        let window_start = now - 18 * 3_600;
+        assert!(request.credit_payout_block(window_start, now + 6 * 3_600, now));
+        let blocked_deadline = approved_at + CLAIM_TIMEOUT_SECONDS + 6 * 3_600;
+        assert_eq!(request.effective_claim_deadline(), blocked_deadline);
+
+        // 同一窗口重复登记不叠加
+        assert!(!request.credit_payout_block(window_start, now + 6 * 3_600, now + 60));
+        assert_eq!(request.effective_claim_deadline(), blocked_deadline);
+
+        // 管理员顺延后再次被新窗口阻塞 2 小时: 在管理员设定的期限基础上顺延
+        let admin_deadline = blocked_deadline + 48 * 3_600;
+        request.claim_deadline = admin_deadline;
+        let next_window = window_start + 24 * 3_600;
+        let later = next_window + 22 * 3_600;
+        assert!(request.credit_payout_block(next_window, later + 2 * 3_600, later));
+        assert_eq!(request.effective_claim_deadline(), admin_deadline + 2 * 3_600);
+    }
+
+    #[test]
+    fn test_claim_and_refund_rejected_while_revealing() {
+        assert_eq!(
+            code(RequestStatus::Revealing.check_claimable()),
+            Some(IPFlowError::RevealInProgress as u32)
+        );
+        assert_eq!(
+            code(RequestStatus::Revealing.check_pending()),
+            Some(IPFlowError::RevealInProgress as u32)
+        );
+        assert!(!RequestStatus::Revealing.should_close_after_claim());
+    }
+
+    #[test]
+    fn test_wsol_payment_mode_is_sol_denominated() {
+        // 追加在末尾，不改变已有变体的编码
+        assert_eq!(PaymentMode::USDT.try_to_vec().unwrap(), vec![1]);
+        assert_eq!(PaymentMode::WSOL.try_to_vec().unwrap(), vec![2]);
+        assert!(PaymentMode::SOL.is_sol_denominated());
+        assert!(PaymentMode::WSOL.is_sol_denominated());
+        assert!(!PaymentMode::USDT.is_sol_denominated());
+    }
+
+    #[test]
+    fn test_memo_survives_request_lifecycle() {
+        let memo = *b"campaign-2026-autumn\0\0\0\0\0\0\0\0\0\0\0\0";
+        let mut request = revealed_request(1_000, 1_000 + CLAIM_TIMEOUT_SECONDS);
+        request.memo = memo;
+
+        // 揭示 -> 领取 / 失败退款的状态流转与账户重新序列化均不改写 memo
+        for status in [
+            RequestStatus::Revealing,
+            RequestStatus::Revealed,
+            RequestStatus::Claiming,
+            RequestStatus::Failed,
+        ] {
+            request.status = status;
+            request.set_reveal_progress(&RevealProgress::default());
+            request.extend_claim_deadline_to(2_000 + CLAIM_TIMEOUT_SECONDS);
+
+            let mut data = Vec::new();
+            request.try_serialize(&mut data).unwrap();
+            assert_eq!(data.len(), 8 + MintRequest::INIT_SPACE);
+            request = MintRequest::try_deserialize(&mut &data[..]).unwrap();
+            assert_eq!(request.memo, memo);
+        }
+    }
+
+    #[test]
+    fn test_swap_failure_relief_after_threshold() {
+        let mut request = revealed_request(1_000, 1_000 + CLAIM_TIMEOUT_SECONDS);
+        assert_eq!(request.sol_payout_bps(9_500, 10_000), 9_500);
+
+        let mut now = 2_000;
+        for _ in 0..SWAP_FAILURE_RELIEF_ATTEMPTS {
+            assert!(!request.swap_failure_relief());
+            request.record_swap_failure(now).unwrap();
+            // 间隔不足的重复登记被拒绝，计数不变
+            assert_eq!(
+                code(request.record_swap_failure(now + 1)),
+                Some(IPFlowError::SwapFailureReportTooSoon as u32)
+            );
+            now += SWAP_FAILURE_REPORT_INTERVAL_SECONDS;
+        }
+
+        assert_eq!(request.failed_swap_attempts, SWAP_FAILURE_RELIEF_ATTEMPTS);
+        assert!(request.swap_failure_relief());
+        assert_eq!(request.sol_payout_bps(9_500, 10_000), 10_000);
+        // Token 比例更低时不降低 SOL 发放
+        assert_eq!(request.sol_payout_bps(9_500, 9_000), 9_500);
+    }
+
+    #[test]
+    fn test_claim_fee_only_for_claim_time_policy() {
+        let mut request = revealed_request(1_000, 0);
+        assert_eq!(request.fee_policy, FEE_POLICY_ON_MINT);
+        assert_eq!(request.claim_fee_bps(500), 0);
+        request.fee_policy = FEE_POLICY_ON_CLAIM;
+        assert_eq!(request.claim_fee_bps(500), 500);
+    }
+
+    #[test]
+    fn test_claim_preauthorization_set_and_clear() {
+        let mut request = revealed_request(1_700_000_000, 1_700_086_400);
+        // 升级前创建的请求未预授权
+        assert_eq!(request.claim_preauthorization(), None);
+
+        let terms = ClaimPreauthorization {
+            router: SwapRouter::Raydium,
+            expected_token_output: 1_234_567,
+            max_slippage_bps: 150,
+        };
+        request.set_claim_preauthorization(Some(terms));
+        assert_eq!(request.claim_preauthorization(), Some(terms));
+
+        request.set_claim_preauthorization(None);
+        assert_eq!(request.claim_preauthorization(), None);
+        assert_eq!(request.preauth_expected_output, 0);
+    }
+
+    #[test]
+    fn test_legacy_layout_migrates_after_zero_fill() {
+        let user = Pubkey::new_unique();
+        let mut legacy = revealed_request(1_700_000_000, 0);
+        legacy.user = user;
+        legacy.payment_mode = PaymentMode::USDT;
+        legacy.total_won_usd = 50_000_000;
+        legacy.selected_pool_index = 2;
+
+        // 升级前的账户只有基线 13 个字段 (8 + 127 bytes)，其后由 realloc 零填充
+        let mut data = Vec::new();
+        legacy.try_serialize(&mut data).unwrap();
+        data.truncate(8 + 127);
+        data.resize(8 + MintRequest::INIT_SPACE, 0);
+        let mut request = MintRequest::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(request.payer, Pubkey::default());
+
+        let mut config = IPFlowState::zeroed();
+        let usdt = Pubkey::new_unique();
+        config.payment_mints[0].mint = usdt;
+        config.active_pool_count = 1;
+        config.active_pool_indices[0] = 2;
+        config.active_pool_max_win_usd[0] = 20_000_000;
+
+        request.normalize_legacy_fields(&config);
+        assert_eq!(request.user, user);
+        assert_eq!(request.payer, user);
+        assert_eq!(request.payment_mint, usdt);
+        assert_eq!(request.token_won_usd, 20_000_000);
+        assert_eq!(request.rtp_modulation_bps, 10_000);
+        assert_eq!(request.claim_preauthorization(), None);
+
+        // 重复调用不改变已规范化的请求
+        let normalized = request.clone();
+        config.active_pool_max_win_usd[0] = 0;
+        request.normalize_legacy_fields(&config);
+        assert_eq!(request.token_won_usd, normalized.token_won_usd);
+        assert_eq!(request.payer, normalized.payer);
+    }
+}
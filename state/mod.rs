@@ -1,7 +1,23 @@
+pub mod claim_receipt;
+pub mod delegation;
+pub mod epoch_stats;
 pub mod global_config;
+pub mod leaderboard;
 pub mod mint_request;
+pub mod pending_change;
 pub mod prize_pool;
+pub mod queue_stats;
+pub mod rewards;
+pub mod user_stats;
 
+pub use claim_receipt::*;
+pub use delegation::*;
+pub use epoch_stats::*;
 pub use global_config::*;
+pub use leaderboard::*;
 pub use mint_request::*;
+pub use pending_change::*;
 pub use prize_pool::*;
+pub use queue_stats::*;
+pub use rewards::*;
+pub use user_stats::*;
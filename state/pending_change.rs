@@ -0,0 +1,121 @@
+// ==================== 待确认经济参数变更 ====================
+
+use anchor_lang::prelude::*;
+
+use crate::constants::PENDING_CHANGE_TTL_SECONDS;
+
+/// 需要 co-admin 确认的经济参数变更
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum EconomicsChange {
+    /// 对应 update_payout_bps
+    PayoutBps {
+        sol_payout_bps: u16,
+        token_payout_bps: u16,
+    },
+    /// 对应 update_prize_pool 的 tier_override (全 0 表示恢复默认概率)
+    TierOverride {
+        pool_index: u8,
+        tier_override: [u64; 4],
+    },
+    /// 对应 update_rtp_modulation
+    RtpModulation {
+        rtp_modulation_enabled: bool,
+        vault_target_lamports: u64,
+        max_modulation_bps: u16,
+    },
+    /// 对应 update_pity_threshold (0 表示关闭保底)
+    PityThreshold { pity_threshold: u16 },
+    /// 对应 update_rewards_settings 的 referral_bps
+    ReferralBps { referral_bps: u16 },
+    /// 对应 update_fee_policy
+    FeePolicy { fee_policy: u8 },
+    /// 对应 update_prize_pool 的 max_win_usd (0 表示不限制)
+    MaxWinUsd { pool_index: u8, max_win_usd: u64 },
+}
+
+impl EconomicsChange {
+    /// 变更涉及的奖品池索引 (仅 Tier 覆盖与奖金上限需要传入奖品池账户)
+    pub fn pool_index(&self) -> Option<u8> {
+        match self {
+            EconomicsChange::TierOverride { pool_index, .. }
+            | EconomicsChange::MaxWinUsd { pool_index, .. } => Some(*pool_index),
+            EconomicsChange::PayoutBps { .. }
+            | EconomicsChange::RtpModulation { .. }
+            | EconomicsChange::PityThreshold { .. }
+            | EconomicsChange::ReferralBps { .. }
+            | EconomicsChange::FeePolicy { .. } => None,
+        }
+    }
+}
+
+/// 待确认经济参数变更 PDA
+///
+/// Seeds: [b"pending_change"]
+/// 配置 co_admin 后，影响偿付能力的参数变更分两步执行: 管理员 stage_economics_change 暂存，
+/// co-admin 在 PENDING_CHANGE_TTL_SECONDS 内 confirm_economics_change 确认并原子生效。
+/// 确认或取消时关闭账户，租金退还给暂存人。
+#[account]
+#[derive(InitSpace)]
+pub struct PendingChange {
+    /// 暂存变更的管理员 (支付并回收租金)
+    pub staged_by: Pubkey,
+    /// 变更内容
+    pub change: EconomicsChange,
+    /// 暂存时间戳
+    pub staged_at: i64,
+    /// 过期时间戳 (不含)
+    pub expires_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl PendingChange {
+    /// 暂存时计算过期时间
+    pub fn expiry_for(staged_at: i64) -> i64 {
+        staged_at.saturating_add(PENDING_CHANGE_TTL_SECONDS)
+    }
+
+    /// 变更在 now 时刻是否已过期
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_change_expires_after_ttl() {
+        let staged_at = 1_700_000_000;
+        let pending = PendingChange {
+            staged_by: Pubkey::default(),
+            change: EconomicsChange::PayoutBps {
+                sol_payout_bps: 9_000,
+                token_payout_bps: 9_500,
+            },
+            staged_at,
+            expires_at: PendingChange::expiry_for(staged_at),
+            bump: 255,
+        };
+        assert!(!pending.is_expired(staged_at + PENDING_CHANGE_TTL_SECONDS - 1));
+        assert!(pending.is_expired(staged_at + PENDING_CHANGE_TTL_SECONDS));
+        assert_eq!(pending.change.pool_index(), None);
+    }
+
+    #[test]
+    fn pool_changes_carry_pool_index() {
+        assert_eq!(
+            EconomicsChange::MaxWinUsd {
+                pool_index: 3,
+                max_win_usd: 0,
+            }
+            .pool_index(),
+            Some(3)
+        );
+        assert_eq!(
+            EconomicsChange::FeePolicy { fee_policy: 0 }.pool_index(),
+            None
+        );
+    }
+}
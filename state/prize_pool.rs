@@ -31,8 +31,53 @@ pub struct PrizePoolAccount {
     pub name: String,
     /// PDA bump
     pub bump: u8,
+    /// 累计通过该池 swap 的 lamports (Token 模式 claim)
+    pub total_swapped_lamports: u64,
+    /// 累计 Token 模式 claim 次数
+    pub total_claims: u64,
+    /// 最近一次 Token 模式 claim 时间戳
+    pub last_claim_ts: i64,
+    /// Token 模式单次请求可归属该池的最大奖金 (micro-USD)，0 表示不限制
+    pub max_win_usd: u64,
+    /// 生效起始时间戳 (早于此时间不参与选池)，0 表示不限制
+    pub active_from: i64,
+    /// 失效时间戳 (达到此时间后不再参与选池)，0 表示不限制
+    pub active_until: i64,
+    /// 奖品 Token 符号 (最长 MAX_POOL_SYMBOL_LEN 字节)，未设置时为 None
+    #[max_len(10)]
+    pub symbol: Option<String>,
+    /// 奖品 Token 元数据 / logo URI (最长 MAX_POOL_METADATA_URI_LEN 字节)，未设置时为 None
+    #[max_len(100)]
+    pub metadata_uri: Option<String>,
+    /// 自定义 Tier 累积阈值表 (替换 DEFAULT_TIER_THRESHOLDS，用于合作方池的概率活动)，None 表示默认概率
+    pub tier_override: Option<[u64; 4]>,
+    /// 累计揭示时分配到该池的奖金 (micro-USD，与领取方式无关，flush_pool_stats 时同步)
+    pub total_assigned_usd: u64,
+    /// 累计被选中次数 (flush_pool_stats 时同步)
+    pub times_selected: u64,
+    /// 已暂存、尚未生效的 swap_pool 变更 (apply_prize_pool_update 到期后写入 swap_pool)
+    pub pending_swap_pool: Option<Pubkey>,
+    /// pending_swap_pool 的生效时间戳 (无暂存变更时为 0)
+    pub pending_swap_pool_effective_at: i64,
+    /// 选中该池、尚未领取的 Revealed 请求数 (flush_pool_stats 时从配置同步；
+    /// VRF 回调不含池 PDA，删除校验以配置中按 index 的计数为准)
+    pub pending_claims: u32,
+}
+
+/// 当前时间是否在 [active_from, active_until) 排期窗口内 (0 表示该端不限制)
+pub fn is_within_schedule(active_from: i64, active_until: i64, now: i64) -> bool {
+    (active_from == 0 || now >= active_from) && (active_until == 0 || now < active_until)
 }
 
 // 空间: 8 (discriminator) + 1 (index) + 32 (swap_pool) + 1 (pool_type)
-//       + 4 (String len prefix) + 16 (name max) + 1 (bump) = 63 bytes
-// 租金: ~0.00089 SOL
+//       + 4 (String len prefix) + 16 (name max) + 1 (bump)
+//       + 8 (total_swapped_lamports) + 8 (total_claims) + 8 (last_claim_ts)
+//       + 8 (max_win_usd) + 8 (active_from) + 8 (active_until)
+//       + 1 + 4 + 10 (symbol: Option<String>) + 1 + 4 + 100 (metadata_uri: Option<String>)
+//       + 1 + 32 (tier_override: Option<[u64; 4]>)
+//       + 8 (total_assigned_usd) + 8 (times_selected)
+//       + 1 + 32 (pending_swap_pool: Option<Pubkey>) + 8 (pending_swap_pool_effective_at)
+//       + 4 (pending_claims)
+//       = 325 bytes
+// 租金: ~0.0029 SOL
+// 旧版账户 (63 / 111 / 231 / 264 / 280 / 321 bytes) 通过 migrate_prize_pool 扩容，新增字段零初始化即为 None
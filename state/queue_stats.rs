@@ -0,0 +1,98 @@
+// ==================== VRF Oracle Queue 统计状态定义 ====================
+
+use anchor_lang::prelude::*;
+
+/// 单个 VRF Oracle Queue 的回调延迟统计 PDA
+///
+/// Seeds: [b"queue_stats", queue]
+/// VRF 回调按请求记录的 queue 累计 (未创建时跳过)，管理员 reset_queue_stats 创建或清零
+#[account]
+#[derive(InitSpace)]
+pub struct QueueStats {
+    /// 统计的 Oracle Queue
+    pub queue: Pubkey,
+    /// 已统计的回调次数
+    pub count: u64,
+    /// 累计回调延迟 (秒，request_mint 到 VRF 回调)
+    pub total_latency_seconds: u64,
+    /// 单次最大回调延迟 (秒)
+    pub max_latency_seconds: u64,
+    /// 最近一次清零时间戳
+    pub reset_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl QueueStats {
+    /// 累计一次回调延迟 (饱和加法，统计溢出不应阻塞开奖)
+    pub fn record(&mut self, latency_seconds: u64) {
+        self.count = self.count.saturating_add(1);
+        self.total_latency_seconds = self.total_latency_seconds.saturating_add(latency_seconds);
+        self.max_latency_seconds = self.max_latency_seconds.max(latency_seconds);
+    }
+
+    /// 清零统计
+    pub fn reset(&mut self, now: i64) {
+        self.count = 0;
+        self.total_latency_seconds = 0;
+        self.max_latency_seconds = 0;
+        self.reset_at = now;
+    }
+
+    /// 平均回调延迟 (秒)，无样本时为 0
+    pub fn average_latency_seconds(&self) -> u64 {
+        self.total_latency_seconds.checked_div(self.count).unwrap_or(0)
+    }
+}
+
+/// 回调延迟 (秒)：请求创建到收到随机数，时钟回拨时记为 0
+pub fn reveal_latency_seconds(created_at: i64, now: i64) -> u64 {
+    now.saturating_sub(created_at).max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> QueueStats {
+        QueueStats {
+            queue: Pubkey::default(),
+            count: 0,
+            total_latency_seconds: 0,
+            max_latency_seconds: 0,
+            reset_at: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn records_count_total_and_max() {
+        let mut s = stats();
+        assert_eq!(s.average_latency_seconds(), 0);
+
+        s.record(4);
+        s.record(10);
+        s.record(1);
+        assert_eq!(s.count, 3);
+        assert_eq!(s.total_latency_seconds, 15);
+        assert_eq!(s.max_latency_seconds, 10);
+        assert_eq!(s.average_latency_seconds(), 5);
+    }
+
+    #[test]
+    fn reset_clears_aggregates() {
+        let mut s = stats();
+        s.record(7);
+        s.reset(1_700_000_000);
+        assert_eq!(s.count, 0);
+        assert_eq!(s.total_latency_seconds, 0);
+        assert_eq!(s.max_latency_seconds, 0);
+        assert_eq!(s.reset_at, 1_700_000_000);
+    }
+
+    #[test]
+    fn latency_never_negative() {
+        assert_eq!(reveal_latency_seconds(1_000, 1_012), 12);
+        assert_eq!(reveal_latency_seconds(1_000, 990), 0);
+    }
+}
@@ -0,0 +1,101 @@
+// ==================== 用户奖励余额状态定义 ====================
+
+use anchor_lang::prelude::*;
+
+use crate::constants::BPS_DENOMINATOR;
+use crate::errors::IPFlowError;
+
+/// 用户奖励余额 PDA
+///
+/// Seeds: [b"rewards", user]
+/// 由用户自行初始化 (init_rewards_account)；推荐返佣、零头等以 micro-USD 累积，
+/// 通过 claim_rewards 一次性按 Pyth 价格换算为 SOL 领取
+#[account]
+#[derive(InitSpace)]
+pub struct RewardsAccount {
+    /// 所属用户
+    pub user: Pubkey,
+    /// 待领取奖励 (micro-USD)
+    pub accrued_usd: u64,
+    /// 累计已领取奖励 (micro-USD)
+    pub total_claimed_usd: u64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl RewardsAccount {
+    /// 累加奖励，返回累加后的待领取余额
+    pub fn credit(&mut self, amount_usd: u64) -> Result<u64> {
+        self.accrued_usd = self
+            .accrued_usd
+            .checked_add(amount_usd)
+            .ok_or(IPFlowError::MathOverflow)?;
+        Ok(self.accrued_usd)
+    }
+
+    /// 清空待领取余额并计入累计已领取，返回本次领取金额
+    pub fn take_accrued(&mut self) -> Result<u64> {
+        let amount_usd = self.accrued_usd;
+        self.total_claimed_usd = self
+            .total_claimed_usd
+            .checked_add(amount_usd)
+            .ok_or(IPFlowError::MathOverflow)?;
+        self.accrued_usd = 0;
+        Ok(amount_usd)
+    }
+}
+
+/// 奖励来源
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug,
+)]
+pub enum RewardSource {
+    /// 被推荐用户抽卡的返佣
+    Referral,
+    /// 低于最小发放额的 SOL 奖金零头
+    Dust,
+}
+
+/// 推荐返佣金额: paid_usd * referral_bps / 10000
+pub fn referral_reward_usd(paid_usd: u64, referral_bps: u16) -> u64 {
+    ((paid_usd as u128) * (referral_bps as u128) / (BPS_DENOMINATOR as u128)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewards() -> RewardsAccount {
+        RewardsAccount {
+            user: Pubkey::default(),
+            accrued_usd: 0,
+            total_claimed_usd: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn credit_and_take_accrued() {
+        let mut account = rewards();
+        assert_eq!(account.credit(1_000_000).unwrap(), 1_000_000);
+        assert_eq!(account.credit(500_000).unwrap(), 1_500_000);
+
+        assert_eq!(account.take_accrued().unwrap(), 1_500_000);
+        assert_eq!(account.accrued_usd, 0);
+        assert_eq!(account.total_claimed_usd, 1_500_000);
+    }
+
+    #[test]
+    fn credit_overflow_rejected() {
+        let mut account = rewards();
+        account.accrued_usd = u64::MAX;
+        assert!(account.credit(1).is_err());
+    }
+
+    #[test]
+    fn referral_reward_is_bps_of_paid() {
+        assert_eq!(referral_reward_usd(10_000_000, 500), 500_000);
+        assert_eq!(referral_reward_usd(10_000_000, 0), 0);
+        assert_eq!(referral_reward_usd(u64::MAX, 10_000), u64::MAX);
+    }
+}
@@ -0,0 +1,20 @@
+// ==================== 用户统计状态定义 ====================
+
+use anchor_lang::prelude::*;
+
+/// 用户统计 PDA
+///
+/// Seeds: [b"user_stats", user]
+/// 由用户自行初始化 (init_user_stats)，记录跨请求累积的用户级状态
+#[account]
+#[derive(InitSpace)]
+pub struct UserStats {
+    /// 所属用户
+    pub user: Pubkey,
+    /// 低于最小发放额、暂未发放的 SOL 零头 (lamports)，下次 SOL 领取时合并发放
+    pub pending_dust_lamports: u64,
+    /// 保底计数器: 连续未中 Tier 3+ 的卡数 (由 VRF 回调维护)
+    pub pity_counter: u16,
+    /// PDA bump
+    pub bump: u8,
+}
@@ -0,0 +1,81 @@
+// ==================== 程序账户手动关闭 ====================
+//
+// 替代声明式 `close = ...`：由 handler 在到达终态 (如 Claimed) 后显式调用，
+// 降级或提前返回 Ok 的路径不会误关闭账户。
+// 关闭后 owner 为 System Program 且数据长度为 0，Anchor 在指令结束时不再序列化该账户。
+
+use anchor_lang::prelude::*;
+
+use crate::errors::IPFlowError;
+
+/// 关闭本程序持有的账户：清零数据，lamports (含租金) 全部转给 destination，owner 交还 System Program
+///
+/// 账户不属于本程序 (含已关闭的账户) 时返回 `AccountAlreadyClosed`，保证同一账户只关闭一次
+pub fn close_account_to<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    require_keys_eq!(
+        *account.owner,
+        crate::ID,
+        IPFlowError::AccountAlreadyClosed
+    );
+
+    account.try_borrow_mut_data()?.fill(0);
+
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(IPFlowError::MathOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+
+    account.assign(&anchor_lang::system_program::ID);
+    account.realloc(0, false)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_moves_all_lamports_exactly_once() {
+        let (account_key, destination_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (program_owner, system_owner) = (crate::ID, anchor_lang::system_program::ID);
+        let (mut account_lamports, mut destination_lamports) = (2_000_000u64, 500u64);
+        let (mut account_data, mut destination_data) = (vec![], vec![]);
+        let account = AccountInfo::new(
+            &account_key,
+            false,
+            true,
+            &mut account_lamports,
+            &mut account_data,
+            &program_owner,
+            false,
+            0,
+        );
+        let destination = AccountInfo::new(
+            &destination_key,
+            false,
+            true,
+            &mut destination_lamports,
+            &mut destination_data,
+            &system_owner,
+            false,
+            0,
+        );
+
+        close_account_to(&account, &destination).unwrap();
+        assert_eq!(account.lamports(), 0);
+        assert_eq!(destination.lamports(), 2_000_500);
+        assert_eq!(*account.owner, anchor_lang::system_program::ID);
+
+        // 已交还 System Program 的账户不能再次关闭
+        assert_eq!(
+            close_account_to(&account, &destination).unwrap_err(),
+            error!(IPFlowError::AccountAlreadyClosed)
+        );
+        assert_eq!(destination.lamports(), 2_000_500);
+    }
+}
@@ -0,0 +1,171 @@
+// ==================== Metaplex Core CPI 集成模块 (卡牌 NFT) ====================
+//
+// 直接构造 Core 指令 (单字节 discriminator + borsh 参数)，不引入 mpl-core 依赖。
+// Core 的可选账户未传入时以 Core Program ID 占位。
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+
+use crate::constants::MPL_CORE_PROGRAM_ID;
+use crate::errors::IPFlowError;
+
+/// CreateV1 指令 discriminator
+const CREATE_V1_DISCRIMINATOR: u8 = 0;
+/// CreateCollectionV1 指令 discriminator
+const CREATE_COLLECTION_V1_DISCRIMINATOR: u8 = 1;
+/// DataState::AccountState (资产数据保存在账户中)
+const DATA_STATE_ACCOUNT: u8 = 0;
+
+/// 校验传入的 Core Program ID
+pub fn check_core_program(program: &Pubkey) -> Result<()> {
+    require_keys_eq!(*program, MPL_CORE_PROGRAM_ID, IPFlowError::InvalidCoreProgram);
+    Ok(())
+}
+
+/// 编码 CreateV1 参数: data_state, name, uri, plugins = None
+pub(crate) fn create_v1_data(name: &str, uri: &str) -> Result<Vec<u8>> {
+    let mut data = vec![CREATE_V1_DISCRIMINATOR, DATA_STATE_ACCOUNT];
+    name.to_string()
+        .serialize(&mut data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    uri.to_string()
+        .serialize(&mut data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    data.push(0); // plugins: None
+    Ok(data)
+}
+
+/// 编码 CreateCollectionV1 参数: name, uri, plugins = None
+pub(crate) fn create_collection_v1_data(name: &str, uri: &str) -> Result<Vec<u8>> {
+    let mut data = vec![CREATE_COLLECTION_V1_DISCRIMINATOR];
+    name.to_string()
+        .serialize(&mut data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    uri.to_string()
+        .serialize(&mut data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    data.push(0); // plugins: None
+    Ok(data)
+}
+
+/// 创建 Core Collection，update_authority 为程序 PDA (后续铸造由其签名)
+pub fn create_collection<'info>(
+    core_program: AccountInfo<'info>,
+    collection: AccountInfo<'info>,
+    update_authority: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    name: &str,
+    uri: &str,
+) -> Result<()> {
+    check_core_program(core_program.key)?;
+
+    let ix = Instruction {
+        program_id: core_program.key(),
+        accounts: vec![
+            AccountMeta::new(collection.key(), true),
+            AccountMeta::new_readonly(update_authority.key(), false),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(system_program.key(), false),
+        ],
+        data: create_collection_v1_data(name, uri)?,
+    };
+
+    invoke(
+        &ix,
+        &[collection, update_authority, payer, system_program, core_program],
+    )?;
+    Ok(())
+}
+
+/// 在 Collection 下铸造单个 Core 资产 (资产地址为程序 PDA，与 Collection 权限 PDA 一同签名)
+#[allow(clippy::too_many_arguments)]
+pub fn create_asset<'info>(
+    core_program: AccountInfo<'info>,
+    asset: AccountInfo<'info>,
+    collection: AccountInfo<'info>,
+    collection_authority: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    owner: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    name: &str,
+    uri: &str,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    check_core_program(core_program.key)?;
+
+    let ix = Instruction {
+        program_id: core_program.key(),
+        accounts: vec![
+            AccountMeta::new(asset.key(), true),
+            AccountMeta::new(collection.key(), false),
+            AccountMeta::new_readonly(collection_authority.key(), true),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(owner.key(), false),
+            // update_authority: 归属 Collection 时不可单独设置
+            AccountMeta::new_readonly(core_program.key(), false),
+            AccountMeta::new_readonly(system_program.key(), false),
+            // log_wrapper: 未使用
+            AccountMeta::new_readonly(core_program.key(), false),
+        ],
+        data: create_v1_data(name, uri)?,
+    };
+
+    invoke_signed(
+        &ix,
+        &[
+            asset,
+            collection,
+            collection_authority,
+            payer,
+            owner,
+            system_program,
+            core_program,
+        ],
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_v1_data_layout() {
+        let data = create_v1_data("Card #1 T3", "https://x/1").unwrap();
+        assert_eq!(data[0], CREATE_V1_DISCRIMINATOR);
+        assert_eq!(data[1], DATA_STATE_ACCOUNT);
+        // name: u32 LE 长度前缀 + 字节
+        assert_eq!(&data[2..6], &10u32.to_le_bytes());
+        assert_eq!(&data[6..16], b"Card #1 T3");
+        assert_eq!(&data[16..20], &11u32.to_le_bytes());
+        assert_eq!(&data[20..31], b"https://x/1");
+        assert_eq!(data[31], 0);
+        assert_eq!(data.len(), 32);
+    }
+
+    #[test]
+    fn test_create_collection_v1_data_layout() {
+        let data = create_collection_v1_data("Cards", "u").unwrap();
+        let expected = [
+            &[CREATE_COLLECTION_V1_DISCRIMINATOR][..],
+            &5u32.to_le_bytes(),
+            b"Cards",
+            &1u32.to_le_bytes(),
+            b"u",
+            &[0],
+        ]
+        .concat();
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn test_core_program_check() {
+        assert!(check_core_program(&MPL_CORE_PROGRAM_ID).is_ok());
+        assert_eq!(
+            check_core_program(&Pubkey::new_unique()).unwrap_err(),
+            error!(IPFlowError::InvalidCoreProgram)
+        );
+    }
+}
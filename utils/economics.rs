@@ -0,0 +1,15 @@
+// ==================== 票价与分层分布的编译期断言 ====================
+//
+// TICKET_PRICE_MICRO_USD 与分层概率配置 (TIER*_THRESHOLD / TIER*_MIN_USD / TIER*_STEPS) 分别定义，
+// 任一方单独修改都会改变经济模型。以下断言在编译期校验默认分布的期望奖金仍落在票价的设计区间内，
+// 修改票价或分层配置时须同步审计另一方，否则无法通过编译。
+
+use crate::constants::{TICKET_PRICE_MICRO_USD, TIER1_MIN_USD};
+use crate::utils::vrf_helper::expected_value_micro_usd;
+
+// 单卡期望奖金高于票价 (设计 ROI 为正)，且不超过票价的 2 倍
+const _: () = assert!(expected_value_micro_usd() > TICKET_PRICE_MICRO_USD);
+const _: () = assert!(expected_value_micro_usd() < 2 * TICKET_PRICE_MICRO_USD);
+
+// 最低档奖金不低于半张票价 (每张卡至少返还 50%)
+const _: () = assert!(2 * TIER1_MIN_USD >= TICKET_PRICE_MICRO_USD);
@@ -0,0 +1,142 @@
+// ==================== 中奖 SOL 托管 (Escrow) ====================
+//
+// 揭示时按当时的 Pyth 价格，将中奖金额对应的 lamports 从 Vault 划入每个请求独立的
+// escrow PDA (seeds = [SEED_ESCROW, mint_request])，既锁定汇率也预留兑付资金:
+// - claim: 托管资金先全部退回 Vault，再按锁定汇率 (escrow_lamports / total_won_usd) 发放
+// - 过期清理 / 拒绝审核: 托管资金退回 Vault
+// Escrow 为 0 字节 System 账户，创建时由 Vault 一并支付免租金额，释放时全部退回。
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke_signed, system_instruction};
+
+use crate::constants::SEED_ESCROW;
+use crate::errors::IPFlowError;
+use crate::state::{IPFlowState, MintRequest};
+use crate::utils::vault_available_lamports;
+
+/// 托管所需的 Vault 可用余额: 托管金额 + escrow 免租金额 (已有余额部分不再重复扣除)
+pub fn escrow_funding_lamports(amount: u64, escrow_lamports_now: u64, rent: u64) -> Option<u64> {
+    amount.checked_add(rent.saturating_sub(escrow_lamports_now))
+}
+
+/// 从 Vault 划转 amount (及 escrow 所需的免租金额) 到 escrow PDA
+///
+/// # 错误
+/// - `InsufficientVaultBalance`: Vault 可用余额不足
+pub fn fund_escrow<'info>(
+    vault: &AccountInfo<'info>,
+    vault_bump: u8,
+    escrow: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(0);
+    let transfer = escrow_funding_lamports(amount, escrow.lamports(), rent)
+        .ok_or(IPFlowError::MathOverflow)?;
+    require!(
+        transfer <= vault_available_lamports(vault)?,
+        IPFlowError::InsufficientVaultBalance
+    );
+
+    invoke_signed(
+        &system_instruction::transfer(vault.key, escrow.key, transfer),
+        &[vault.clone(), escrow.clone(), system_program.clone()],
+        &[&[b"vault".as_ref(), &[vault_bump]]],
+    )?;
+    Ok(())
+}
+
+/// 将 escrow PDA 的全部 lamports (含免租金额) 退回 Vault，返回退回金额
+///
+/// escrow 地址须为 [SEED_ESCROW, mint_request] 以 escrow_bump 派生的 PDA；余额为 0 时直接返回
+pub fn release_escrow<'info>(
+    escrow: &AccountInfo<'info>,
+    mint_request: &Pubkey,
+    escrow_bump: u8,
+    vault: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u64> {
+    let seeds: &[&[u8]] = &[SEED_ESCROW, mint_request.as_ref(), &[escrow_bump]];
+    let expected = Pubkey::create_program_address(seeds, &crate::ID)
+        .map_err(|_| error!(IPFlowError::InvalidEscrowAccount))?;
+    require_keys_eq!(escrow.key(), expected, IPFlowError::InvalidEscrowAccount);
+
+    let lamports = escrow.lamports();
+    if lamports == 0 {
+        return Ok(0);
+    }
+
+    invoke_signed(
+        &system_instruction::transfer(escrow.key, vault.key, lamports),
+        &[escrow.clone(), vault.clone(), system_program.clone()],
+        &[seeds],
+    )?;
+    Ok(lamports)
+}
+
+/// 释放请求的托管资金并扣减全局托管合计；未托管的请求 (escrow_lamports = 0) 直接返回 0
+pub fn release_request_escrow<'info>(
+    config: &mut IPFlowState,
+    mint_request: &MintRequest,
+    mint_request_key: &Pubkey,
+    escrow: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u64> {
+    if mint_request.escrow_lamports == 0 {
+        return Ok(0);
+    }
+    let released = release_escrow(
+        escrow,
+        mint_request_key,
+        mint_request.escrow_bump,
+        vault,
+        system_program,
+    )?;
+    config.total_escrowed_lamports = config
+        .total_escrowed_lamports
+        .saturating_sub(mint_request.escrow_lamports);
+    Ok(released)
+}
+
+/// 按揭示时锁定的汇率换算: escrow_lamports * usd / total_won_usd
+///
+/// 未托管 (升级前揭示或中奖为 0) 时返回 None，由调用方回退到实时价格
+pub fn locked_lamports_for_usd(escrow_lamports: u64, total_won_usd: u64, usd: u64) -> Option<u64> {
+    if escrow_lamports == 0 || total_won_usd == 0 {
+        return None;
+    }
+    let lamports = (escrow_lamports as u128)
+        .checked_mul(usd as u128)?
+        .checked_div(total_won_usd as u128)?;
+    u64::try_from(lamports).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn funding_covers_rent_only_once() {
+        assert_eq!(escrow_funding_lamports(1_000, 0, 890_880), Some(891_880));
+        // 已有余额 (例如被他人预先转入) 时只补足免租差额
+        assert_eq!(escrow_funding_lamports(1_000, 890_880, 890_880), Some(1_000));
+        assert_eq!(escrow_funding_lamports(u64::MAX, 0, 1), None);
+    }
+
+    #[test]
+    fn locked_rate_scales_with_portion() {
+        // 揭示时 10 USD = 0.1 SOL
+        let escrow = 100_000_000;
+        let total = 10_000_000;
+        assert_eq!(locked_lamports_for_usd(escrow, total, total), Some(escrow));
+        assert_eq!(locked_lamports_for_usd(escrow, total, 9_500_000), Some(95_000_000));
+        assert_eq!(locked_lamports_for_usd(escrow, total, 0), Some(0));
+    }
+
+    #[test]
+    fn unescrowed_request_falls_back() {
+        assert_eq!(locked_lamports_for_usd(0, 10_000_000, 10_000_000), None);
+        assert_eq!(locked_lamports_for_usd(100, 0, 0), None);
+    }
+}
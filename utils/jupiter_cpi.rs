@@ -4,7 +4,7 @@
 //
 // 设计说明:
 //   - 采用"透传模式"：前端获取 Jupiter swap-instructions 后直接传入
-//   - 合约校验 Jupiter Program ID 和指令 discriminator
+//   - 合约校验 Jupiter Program ID 和指令 discriminator (均由 IPFlowState 配置，可热更新)
 //   - Vault PDA 作为 signer 执行 swap
 //
 // 安全考虑 (CRITICAL):
@@ -15,35 +15,63 @@
 // remaining_accounts 说明:
 //   - 由前端从 Jupiter API /v6/swap-instructions 获取
 //   - 第一个账户必须是 Jupiter Program
-//   - 账户数量取决于路由路径 (通常 10-50 个)
+//   - 账户数量取决于路由路径 (通常 10-50 个)，上限 MAX_JUPITER_ROUTE_ACCOUNTS
+//
+// 堆内存 (32KB，bump 分配器不回收):
+//   - 账户数与 swap_data 长度在任何分配前校验 (SwapPayloadTooLarge)，避免复杂路由触发
+//     memory allocation failed 中止
+//   - AccountMeta 列表按路由账户数预分配；swap_data 以切片传入，仅在构建 Instruction 时复制一次
+//
+// Vault 支出 (CRITICAL):
+//   - CPI 只能触及 remaining_accounts 中的账户，swap 前对其中所有 Vault 持有的 Token 账户做余额快照
+//   - swap 输入先从 Vault lamports 包装到 WSOL 输入账户，快照取于包装之前: WSOL 账户中已有的余额
+//     (如旧版未解包的 WSOL 支付本金) 不得被路由消耗
+//   - swap 后只允许 WSOL 输入账户减少 (且不超过 max_input_amount)，其余 Vault Token 账户
+//     (如被夹带进路由的 USDT 账户) 任何减少均拒绝
 
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
 use anchor_lang::solana_program::program::invoke_signed;
 use anchor_spl::token::TokenAccount;
 
-use crate::constants::{JUPITER_PROGRAM_ID, NATIVE_SOL_MINT};
+use crate::constants::{MAX_JUPITER_ROUTE_ACCOUNTS, MAX_SWAP_DATA_LEN, NATIVE_SOL_MINT};
 use crate::errors::IPFlowError;
+use crate::utils::wsol_helper::wrap_sol;
 
 /// Jupiter Route 指令的 discriminator (8 字节)
 /// 来源: Jupiter V6 Program IDL
 /// 指令: route (最常用的 swap 指令)
-const JUPITER_ROUTE_DISCRIMINATOR: [u8; 8] = [229, 100, 247, 91, 30, 192, 179, 237];
+pub const JUPITER_ROUTE_DISCRIMINATOR: [u8; 8] = [229, 100, 247, 91, 30, 192, 179, 237];
 
 /// Jupiter SharedAccountsRoute 指令的 discriminator
 /// 用于共享账户的路由优化
-const JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR: [u8; 8] = [193, 32, 155, 51, 65, 214, 156, 129];
+pub const JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR: [u8; 8] = [193, 32, 155, 51, 65, 214, 156, 129];
 
 /// Jupiter ExactOutRoute 指令的 discriminator
 /// 用于精确输出金额的 swap
-const JUPITER_EXACT_OUT_ROUTE_DISCRIMINATOR: [u8; 8] = [208, 51, 239, 151, 123, 43, 237, 92];
+pub const JUPITER_EXACT_OUT_ROUTE_DISCRIMINATOR: [u8; 8] = [208, 51, 239, 151, 123, 43, 237, 92];
+
+/// Jupiter V6 自定义错误码 (IDL errors)
+const JUPITER_ERR_EMPTY_ROUTE: u32 = 6000;
+const JUPITER_ERR_SLIPPAGE_TOLERANCE_EXCEEDED: u32 = 6001;
+const JUPITER_ERR_INVALID_INPUT_INDEX: u32 = 6006;
+const JUPITER_ERR_INVALID_OUTPUT_INDEX: u32 = 6007;
+const JUPITER_ERR_NOT_ENOUGH_ACCOUNT_KEYS: u32 = 6008;
+const JUPITER_ERR_INVALID_ROUTE_PLAN: u32 = 6010;
+
+/// Jupiter V6 默认允许的路由指令 (初始化与迁移时写入配置)
+pub const DEFAULT_ROUTE_DISCRIMINATORS: [[u8; 8]; 3] = [
+    JUPITER_ROUTE_DISCRIMINATOR,
+    JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR,
+    JUPITER_EXACT_OUT_ROUTE_DISCRIMINATOR,
+];
 
 /// 通过 Jupiter 执行 Swap (带滑点保护)
 ///
 /// # 安全说明
 /// - 验证 swap_data 长度至少 8 字节
-/// - 验证 Jupiter 指令 discriminator (route/sharedAccountsRoute/exactOutRoute)
-/// - 验证 Jupiter Program ID
+/// - 验证 Jupiter 指令 discriminator (config.allowed_route_discriminators)
+/// - 验证 Jupiter Program ID (config.jupiter_program)
 /// - **CRITICAL**: swap 后验证输出金额 >= minimum_amount_out
 ///
 /// # 参数
@@ -51,113 +79,97 @@ const JUPITER_EXACT_OUT_ROUTE_DISCRIMINATOR: [u8; 8] = [208, 51, 239, 151, 123,
 ///   - 第一个账户必须是 Jupiter Program
 ///   - 后续账户为路由所需的各种账户
 /// - `swap_data`: Jupiter swap 指令的 data 字段 (由前端透传，需验证 discriminator)
+/// - `jupiter_program`: 配置中的 Jupiter Program ID
+/// - `allowed_discriminators`: 配置中允许的路由指令 discriminator
+/// - `pinned_vault_wsol`: 配置中登记的 Vault WSOL 账户 (None 时按扫描方式查找)
 /// - `vault`: Vault PDA 账户 (作为 token 持有者)
 /// - `vault_bump`: Vault PDA bump seed
+/// - `system_program` / `token_program`: 包装 swap 输入所需的 System / SPL Token Program
 /// - `user_output_token_account`: 用户输出 token 账户 (用于验证余额变化)
 /// - `minimum_amount_out`: 最小输出金额 (滑点保护)
-/// - `max_input_amount`: 允许的最大输入金额 (限制 Vault 支出)
+/// - `max_input_amount`: 允许的最大输入金额 (限制 Vault 支出，WSOL 与 Vault lamports 减少量合计；
+///   路由中其他 Vault Token 账户不得减少)
 ///
 /// # 返回
-/// - `Ok(())`: Swap 成功且输出满足最小要求
+/// - `Ok(actual_output)`: Swap 成功且输出满足最小要求，返回用户输出账户的实际到账数量
 /// - `Err(IPFlowError)`: Swap 失败、校验不通过或滑点超限
+#[allow(clippy::too_many_arguments)]
 pub fn swap_via_jupiter<'info>(
     remaining_accounts: &[AccountInfo<'info>],
-    swap_data: Vec<u8>,
+    swap_data: &[u8],
+    jupiter_program: &Pubkey,
+    allowed_discriminators: &[[u8; 8]],
+    pinned_vault_wsol: Option<Pubkey>,
     vault: &AccountInfo<'info>,
     vault_bump: u8,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
     user_output_token_account: &AccountInfo<'info>,
     minimum_amount_out: u64,
     max_input_amount: u64,
-) -> Result<()> {
-    // ==================== 校验 swap_data 安全性 (CRITICAL) ====================
-
-    // 1. 长度校验：至少需要 8 字节 discriminator
-    require!(
-        swap_data.len() >= 8,
-        IPFlowError::InvalidSwapData
-    );
-
-    // 2. Discriminator 校验：必须是已知的 Jupiter 指令
-    let discriminator: [u8; 8] = swap_data[0..8]
-        .try_into()
-        .map_err(|_| error!(IPFlowError::InvalidSwapData))?;
-
-    let is_valid_discriminator = discriminator == JUPITER_ROUTE_DISCRIMINATOR
-        || discriminator == JUPITER_SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR
-        || discriminator == JUPITER_EXACT_OUT_ROUTE_DISCRIMINATOR;
-
-    require!(
-        is_valid_discriminator,
-        IPFlowError::InvalidSwapData
-    );
-
-    // ==================== 记录 swap 前余额 (CRITICAL: 滑点保护) ====================
+) -> Result<u64> {
+    // ==================== 校验 swap_data 与路由账户 (CRITICAL) ====================
+    let vault_input_index = validate_swap_route(
+        remaining_accounts,
+        swap_data,
+        jupiter_program,
+        allowed_discriminators,
+        pinned_vault_wsol,
+        vault,
+        user_output_token_account,
+    )?;
+
+    // ==================== 记录 swap 前余额 (CRITICAL: 滑点保护 / 限制 Vault 支出) ====================
     let balance_before = get_token_amount(user_output_token_account)?;
+    let vault_balances_before = snapshot_vault_token_balances(remaining_accounts, vault.key);
+    let vault_lamports_before = vault.lamports();
 
-    // ==================== 校验输入账户 (CRITICAL: 限制 Vault 支出) ====================
-    let vault_input_token_account = find_vault_wsol_account(remaining_accounts, vault)?;
-    require!(
-        vault_input_token_account.key() != user_output_token_account.key(),
-        IPFlowError::InvalidTokenAccount
-    );
-    let input_balance_before = get_token_amount(&vault_input_token_account)?;
+    // Vault PDA 签名种子
+    let seeds = &[b"vault".as_ref(), &[vault_bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    // ==================== 包装 swap 输入 (CRITICAL: 不动用 WSOL 账户中已有的余额) ====================
+    // 包装额计入 Vault lamports 的减少量，swap 后 WSOL 输入账户不得低于包装前的余额
+    wrap_sol(
+        vault,
+        &remaining_accounts[vault_input_index],
+        system_program,
+        token_program,
+        max_input_amount,
+        signer_seeds,
+    )
+    .map_err(|e| {
+        msg!("WSOL wrap failed: {:?}", e);
+        error!(IPFlowError::WsolWrapFailed)
+    })?;
 
     msg!(
-        "Jupiter swap_data validated: len={}, discriminator={:?}, user_output={}, balance_before={}, min_out={}",
+        "Jupiter swap_data validated: len={}, user_output={}, balance_before={}, min_out={}",
         swap_data.len(),
-        discriminator,
         user_output_token_account.key(),
         balance_before,
         minimum_amount_out
     );
 
-    // ==================== 校验 remaining_accounts ====================
-
-    // 至少需要 Jupiter Program + 若干路由账户
-    require!(
-        remaining_accounts.len() >= 2,
-        IPFlowError::MissingSwapAccounts
-    );
-
-    // 第一个账户必须是 Jupiter Program
     let jupiter_program = &remaining_accounts[0];
-    require!(
-        jupiter_program.key() == JUPITER_PROGRAM_ID,
-        IPFlowError::InvalidJupiterProgram
-    );
+    let route_accounts = &remaining_accounts[1..];
 
     // ==================== 构建账户列表 ====================
 
-    // 从 remaining_accounts[1..] 构建 AccountMeta 列表
-    // 将 vault 匹配的账户标记为 signer
-    let accounts: Vec<AccountMeta> = remaining_accounts[1..]
-        .iter()
-        .map(|acc| {
-            let is_signer = acc.key == vault.key;
-            if acc.is_writable {
-                AccountMeta::new(*acc.key, is_signer)
-            } else {
-                AccountMeta::new_readonly(*acc.key, is_signer)
-            }
-        })
-        .collect();
-
-    // 收集 AccountInfo 用于 invoke_signed
-    let account_infos: Vec<AccountInfo> = remaining_accounts[1..].to_vec();
+    // 从 remaining_accounts[1..] 构建 AccountMeta 列表 (Instruction 需持有所有权)
+    // 将 vault 匹配的账户标记为 signer；AccountInfo 直接借用切片，不再克隆
+    let accounts = route_account_metas(route_accounts, vault.key);
 
     // ==================== 构建并执行指令 ====================
 
+    // Instruction 需持有 data，此处为 swap_data 唯一的一次复制 (长度已受 MAX_SWAP_DATA_LEN 限制)
     let ix = Instruction {
         program_id: jupiter_program.key(),
         accounts,
-        data: swap_data,
+        data: swap_data.to_vec(),
     };
 
-    // Vault PDA 签名种子
-    let seeds = &[b"vault".as_ref(), &[vault_bump]];
-    let signer_seeds = &[&seeds[..]];
-
-    invoke_signed(&ix, &account_infos, signer_seeds)?;
+    invoke_signed(&ix, route_accounts, signer_seeds).map_err(map_jupiter_error)?;
 
     // ==================== 验证 swap 后余额 (CRITICAL: 滑点保护) ====================
     // CPI 后账户数据已更新，直接重新读取即可
@@ -177,54 +189,265 @@ pub fn swap_via_jupiter<'info>(
     // 验证实际输出 >= 最小输出要求
     require!(
         actual_output >= minimum_amount_out,
-        IPFlowError::SlippageExceeded
+        IPFlowError::SwapReturnedTooLittle
     );
 
-    // ==================== 验证 Vault 输入不超过上限 ====================
-    let input_balance_after = get_token_amount(&vault_input_token_account)?;
-    let input_spent = input_balance_before.saturating_sub(input_balance_after);
+    // ==================== 验证 Vault 支出: 仅 WSOL 输入账户 (及 Vault 自身 lamports) 减少且合计不超过上限 ====================
+    check_vault_token_spend(
+        remaining_accounts,
+        &vault_balances_before,
+        vault_input_index,
+        max_input_amount,
+        vault,
+        vault_lamports_before,
+    )?;
+
+    msg!("Jupiter swap executed successfully with slippage protection verified");
+
+    Ok(actual_output)
+}
+
+/// 校验 Jupiter swap 指令数据与路由账户 (不执行任何 CPI)
+///
+/// - 账户数与 swap_data 长度不超过上限 (SwapPayloadTooLarge)
+/// - swap_data 长度至少 8 字节，且 discriminator 在配置的白名单中
+/// - remaining_accounts[0] 必须是配置的 Jupiter Program
+/// - Vault 输入账户: 已登记 vault_wsol_account 时必须是该账户；否则 remaining_accounts 中
+///   恰好存在一个 Vault 持有的可写 WSOL 账户。两种情况下均不能是用户输出账户
+///
+/// 返回 Vault 的 WSOL 输入账户在 remaining_accounts 中的下标，供 swap 前后余额比对
+pub fn validate_swap_route(
+    remaining_accounts: &[AccountInfo],
+    swap_data: &[u8],
+    jupiter_program: &Pubkey,
+    allowed_discriminators: &[[u8; 8]],
+    pinned_vault_wsol: Option<Pubkey>,
+    vault: &AccountInfo,
+    user_output_token_account: &AccountInfo,
+) -> Result<usize> {
+    // 0. 负载上限：在分配任何缓冲区前拒绝过长的路由
+    check_swap_payload(remaining_accounts.len(), swap_data.len())?;
+
+    // 1. 长度校验：至少需要 8 字节 discriminator
+    require!(swap_data.len() >= 8, IPFlowError::InvalidSwapData);
+
+    // 2. Discriminator 校验：必须在配置的白名单中
+    let discriminator: [u8; 8] = swap_data[0..8]
+        .try_into()
+        .map_err(|_| error!(IPFlowError::InvalidSwapData))?;
+
     require!(
-        input_spent <= max_input_amount,
-        IPFlowError::ExcessiveSwapInput
+        allowed_discriminators.contains(&discriminator),
+        IPFlowError::InvalidSwapData
     );
 
-    msg!("Jupiter swap executed successfully with slippage protection verified");
+    // 3. 至少需要 Jupiter Program + 若干路由账户
+    require!(
+        remaining_accounts.len() >= 2,
+        IPFlowError::MissingSwapAccounts
+    );
+
+    // 4. 第一个账户必须是 Jupiter Program
+    require!(
+        remaining_accounts[0].key() == *jupiter_program,
+        IPFlowError::InvalidJupiterProgram
+    );
 
+    // 5. 输入账户必须是 Vault 的 WSOL 账户 (CRITICAL: 限制 Vault 支出)
+    let vault_input_index = match pinned_vault_wsol {
+        Some(pinned) => find_pinned_vault_wsol_account(remaining_accounts, vault, &pinned)?,
+        None => find_vault_wsol_account(remaining_accounts, vault)?,
+    };
+    require!(
+        remaining_accounts[vault_input_index].key() != user_output_token_account.key(),
+        IPFlowError::InvalidTokenAccount
+    );
+
+    Ok(vault_input_index)
+}
+
+/// 路由账户数 (含 Jupiter Program) 与 swap_data 长度不超过上限
+pub(crate) fn check_swap_payload(account_count: usize, swap_data_len: usize) -> Result<()> {
+    require!(
+        account_count <= MAX_JUPITER_ROUTE_ACCOUNTS && swap_data_len <= MAX_SWAP_DATA_LEN,
+        IPFlowError::SwapPayloadTooLarge
+    );
     Ok(())
 }
 
-fn get_token_amount(account: &AccountInfo) -> Result<u64> {
+/// 将 Jupiter CPI 返回的错误映射为具体错误码
+///
+/// - SlippageToleranceExceeded → `SlippageExceeded`
+/// - 路由计划与账户不匹配 (报价过期后路由已变化) → `RouteExpired`
+/// - 其余 → `JupiterSwapFailed`
+///
+/// 注意: 运行时在被调用程序失败时通常直接中止整笔交易，仅 invoke 返回的错误会经过此映射
+pub(crate) fn map_jupiter_error(err: ProgramError) -> Error {
+    match err {
+        ProgramError::Custom(JUPITER_ERR_SLIPPAGE_TOLERANCE_EXCEEDED) => {
+            error!(IPFlowError::SlippageExceeded)
+        }
+        ProgramError::Custom(
+            JUPITER_ERR_EMPTY_ROUTE
+            | JUPITER_ERR_INVALID_INPUT_INDEX
+            | JUPITER_ERR_INVALID_OUTPUT_INDEX
+            | JUPITER_ERR_NOT_ENOUGH_ACCOUNT_KEYS
+            | JUPITER_ERR_INVALID_ROUTE_PLAN,
+        ) => error!(IPFlowError::RouteExpired),
+        other => {
+            msg!("Jupiter CPI error: {:?}", other);
+            error!(IPFlowError::JupiterSwapFailed)
+        }
+    }
+}
+
+/// 构建路由账户的 AccountMeta 列表 (按账户数一次性分配)
+fn route_account_metas(route_accounts: &[AccountInfo], vault: &Pubkey) -> Vec<AccountMeta> {
+    let mut metas = Vec::with_capacity(route_accounts.len());
+    metas.extend(route_accounts.iter().map(|acc| route_account_meta(acc, vault)));
+    metas
+}
+
+/// 构建路由账户的 AccountMeta，Vault 标记为 signer
+fn route_account_meta(acc: &AccountInfo, vault: &Pubkey) -> AccountMeta {
+    let is_signer = acc.key == vault;
+    if acc.is_writable {
+        AccountMeta::new(*acc.key, is_signer)
+    } else {
+        AccountMeta::new_readonly(*acc.key, is_signer)
+    }
+}
+
+/// 读取 SPL Token 账户余额
+pub(crate) fn get_token_amount(account: &AccountInfo) -> Result<u64> {
     let data = account.try_borrow_data()?;
     let token_account = TokenAccount::try_deserialize(&mut &data[..])?;
     Ok(token_account.amount)
 }
 
-fn find_vault_wsol_account<'info>(
-    remaining_accounts: &[AccountInfo<'info>],
-    vault: &AccountInfo<'info>,
-) -> Result<AccountInfo<'info>> {
-    let mut found: Option<AccountInfo<'info>> = None;
+/// 记录 remaining_accounts 中所有 Vault 持有的 Token 账户余额 (下标, 余额)，跳过 Jupiter Program
+fn snapshot_vault_token_balances(remaining_accounts: &[AccountInfo], vault: &Pubkey) -> Vec<(usize, u64)> {
+    remaining_accounts
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(index, acc)| vault_token_amount(acc, vault).map(|amount| (index, amount)))
+        .collect()
+}
 
-    for acc in remaining_accounts.iter().skip(1) {
-        let data = match acc.try_borrow_data() {
-            Ok(data) => data,
-            Err(_) => continue,
-        };
-        let token_account = match TokenAccount::try_deserialize(&mut &data[..]) {
-            Ok(token_account) => token_account,
-            Err(_) => continue,
+/// swap 后比对 Vault 余额
+///
+/// - Vault PDA 以签名者身份传入路由，其 lamports 减少量计入 WSOL 输入额度
+///   (防止构造的路由夹带 System transfer 直接转走 Vault SOL)
+/// - WSOL 输入账户 (vault_input_index) 的减少量与 Vault lamports 减少量合计不超过 max_input_amount
+/// - 其他 Vault Token 账户余额不得减少 (防止构造的路由从夹带的 Vault USDT 账户取款)
+///
+/// 任一不满足返回 `ExcessiveSwapInput`
+fn check_vault_token_spend(
+    remaining_accounts: &[AccountInfo],
+    balances_before: &[(usize, u64)],
+    vault_input_index: usize,
+    max_input_amount: u64,
+    vault: &AccountInfo,
+    vault_lamports_before: u64,
+) -> Result<()> {
+    let lamports_spent = vault_lamports_before.saturating_sub(vault.lamports());
+    if lamports_spent > max_input_amount {
+        msg!(
+            "Vault lamports decreased by {} (allowed {})",
+            lamports_spent,
+            max_input_amount
+        );
+        return Err(error!(IPFlowError::ExcessiveSwapInput));
+    }
+
+    for &(index, before) in balances_before {
+        let after = get_token_amount(&remaining_accounts[index])?;
+        let allowed_spend = if index == vault_input_index {
+            max_input_amount - lamports_spent
+        } else {
+            0
         };
+        let spent = before.saturating_sub(after);
+        if spent > allowed_spend {
+            msg!(
+                "Vault token account {} decreased by {} (allowed {})",
+                remaining_accounts[index].key(),
+                spent,
+                allowed_spend
+            );
+            return Err(error!(IPFlowError::ExcessiveSwapInput));
+        }
+    }
+    Ok(())
+}
 
-        if token_account.owner == *vault.key && token_account.mint == NATIVE_SOL_MINT {
-            require!(acc.is_writable, IPFlowError::InvalidTokenAccount);
+/// 查找已登记的 Vault WSOL 账户，返回其在 remaining_accounts 中的下标 (跳过 Jupiter Program)
+fn find_pinned_vault_wsol_account(
+    remaining_accounts: &[AccountInfo],
+    vault: &AccountInfo,
+    pinned: &Pubkey,
+) -> Result<usize> {
+    let index = remaining_accounts
+        .iter()
+        .skip(1)
+        .position(|acc| acc.key == pinned)
+        .map(|position| position + 1)
+        .ok_or(error!(IPFlowError::VaultWsolAccountMissing))?;
+    require_vault_wsol_account(&remaining_accounts[index], vault.key)?;
+    Ok(index)
+}
+
+/// 查找 Vault 持有的可写 WSOL 账户，返回其在 remaining_accounts 中的下标 (跳过 Jupiter Program)
+///
+/// 仅在尚未登记 vault_wsol_account 的旧部署上使用
+fn find_vault_wsol_account(remaining_accounts: &[AccountInfo], vault: &AccountInfo) -> Result<usize> {
+    let mut found: Option<usize> = None;
+
+    for (index, acc) in remaining_accounts.iter().enumerate().skip(1) {
+        if is_vault_wsol_account(acc, vault.key) {
+            require_vault_wsol_account(acc, vault.key)?;
             if found.is_some() {
                 return Err(error!(IPFlowError::InvalidTokenAccount));
             }
-            found = Some(acc.clone());
+            found = Some(index);
         }
     }
 
-    found.ok_or(error!(IPFlowError::MissingSwapAccounts))
+    found.ok_or(error!(IPFlowError::VaultWsolAccountMissing))
+}
+
+/// 校验 Vault WSOL 输入账户: 须为 Vault 持有的 WSOL Token 账户且可写 (wrap_sol 向其转入 Vault 资金)
+pub(crate) fn require_vault_wsol_account(acc: &AccountInfo, vault: &Pubkey) -> Result<()> {
+    require!(
+        is_vault_wsol_account(acc, vault),
+        IPFlowError::VaultWsolAccountMissing
+    );
+    require!(acc.is_writable, IPFlowError::VaultWsolAccountNotWritable);
+    Ok(())
+}
+
+/// 是否为 Vault 持有的 WSOL Token 账户 (须由 SPL Token 程序持有；无法按 Token 账户反序列化时返回 false)
+pub(crate) fn is_vault_wsol_account(acc: &AccountInfo, vault: &Pubkey) -> bool {
+    vault_token_account(acc, vault)
+        .map(|token_account| token_account.mint == NATIVE_SOL_MINT)
+        .unwrap_or(false)
+}
+
+/// Vault 持有的 Token 账户余额 (任意 Mint)，非 Vault Token 账户返回 None
+fn vault_token_amount(acc: &AccountInfo, vault: &Pubkey) -> Option<u64> {
+    vault_token_account(acc, vault).map(|token_account| token_account.amount)
+}
+
+/// 按 Token 账户反序列化并要求 owner 为 Vault (须由 SPL Token 程序持有)
+fn vault_token_account(acc: &AccountInfo, vault: &Pubkey) -> Option<TokenAccount> {
+    if *acc.owner != anchor_spl::token::ID {
+        return None;
+    }
+    let data = acc.try_borrow_data().ok()?;
+    TokenAccount::try_deserialize(&mut &data[..])
+        .ok()
+        .filter(|token_account| token_account.owner == *vault)
 }
 
 /// 计算最小输出量 (滑点保护)
@@ -266,4 +489,374 @@ mod tests {
             970_000_000
         );
     }
+
+    /// 构造 40 个路由账户 (模拟长路由)，wsol_positions 处为 Vault 持有的 WSOL 账户
+    fn route_fixture(
+        vault: &Pubkey,
+        wsol_positions: &[usize],
+    ) -> (Vec<Pubkey>, Vec<u64>, Vec<Vec<u8>>) {
+        route_fixture_with_len(40, vault, wsol_positions)
+    }
+
+    fn route_fixture_with_len(
+        len: usize,
+        vault: &Pubkey,
+        wsol_positions: &[usize],
+    ) -> (Vec<Pubkey>, Vec<u64>, Vec<Vec<u8>>) {
+        let keys: Vec<Pubkey> = (0..len).map(|_| Pubkey::new_unique()).collect();
+        let lamports = vec![1_000_000u64; len];
+        let data = (0..len)
+            .map(|i| {
+                if wsol_positions.contains(&i) {
+                    token_account_data(NATIVE_SOL_MINT, *vault, 1_000)
+                } else {
+                    vec![0u8; 16]
+                }
+            })
+            .collect();
+        (keys, lamports, data)
+    }
+
+    /// 打包一个已初始化的 SPL Token 账户
+    fn token_account_data(mint: Pubkey, owner: Pubkey, amount: u64) -> Vec<u8> {
+        use anchor_spl::token::spl_token::solana_program::program_pack::Pack;
+        use anchor_spl::token::spl_token::state::{Account as SplAccount, AccountState};
+
+        let mut buf = vec![0u8; SplAccount::LEN];
+        SplAccount::pack(
+            SplAccount {
+                mint,
+                owner,
+                amount,
+                state: AccountState::Initialized,
+                ..SplAccount::default()
+            },
+            &mut buf,
+        )
+        .unwrap();
+        buf
+    }
+
+    /// 模拟 CPI 后的余额变化 (SPL Token 账户布局中 amount 位于 [64, 72))
+    fn set_token_amount(acc: &AccountInfo, amount: u64) {
+        acc.try_borrow_mut_data().unwrap()[64..72].copy_from_slice(&amount.to_le_bytes());
+    }
+
+    fn route_infos<'a>(
+        keys: &'a [Pubkey],
+        lamports: &'a mut [u64],
+        data: &'a mut [Vec<u8>],
+    ) -> Vec<AccountInfo<'a>> {
+        keys.iter()
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .map(|((key, lamports), data)| {
+                AccountInfo::new(key, false, true, lamports, data, &anchor_spl::token::ID, false, 0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_vault_wsol_account_returns_index_on_long_route() {
+        let vault_key = Pubkey::new_unique();
+        let (keys, mut lamports, mut data) = route_fixture(&vault_key, &[17]);
+        let infos = route_infos(&keys, &mut lamports, &mut data);
+        let (mut vault_lamports, mut vault_data) = (0u64, vec![]);
+        let vault = AccountInfo::new(
+            &vault_key,
+            false,
+            false,
+            &mut vault_lamports,
+            &mut vault_data,
+            &anchor_lang::system_program::ID,
+            false,
+            0,
+        );
+
+        assert_eq!(find_vault_wsol_account(&infos, &vault).unwrap(), 17);
+        assert_eq!(get_token_amount(&infos[17]).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_find_vault_wsol_account_rejects_duplicates() {
+        let vault_key = Pubkey::new_unique();
+        let (keys, mut lamports, mut data) = route_fixture(&vault_key, &[5, 30]);
+        let infos = route_infos(&keys, &mut lamports, &mut data);
+        let (mut vault_lamports, mut vault_data) = (0u64, vec![]);
+        let vault = AccountInfo::new(
+            &vault_key,
+            false,
+            false,
+            &mut vault_lamports,
+            &mut vault_data,
+            &anchor_lang::system_program::ID,
+            false,
+            0,
+        );
+
+        assert!(find_vault_wsol_account(&infos, &vault).is_err());
+    }
+
+    #[test]
+    fn test_find_vault_wsol_account_skips_program_slot() {
+        // 下标 0 为 Jupiter Program 位置，即使是 WSOL 账户也不计入
+        let vault_key = Pubkey::new_unique();
+        let (keys, mut lamports, mut data) = route_fixture(&vault_key, &[0]);
+        let infos = route_infos(&keys, &mut lamports, &mut data);
+        let (mut vault_lamports, mut vault_data) = (0u64, vec![]);
+        let vault = AccountInfo::new(
+            &vault_key,
+            false,
+            false,
+            &mut vault_lamports,
+            &mut vault_data,
+            &anchor_lang::system_program::ID,
+            false,
+            0,
+        );
+
+        assert_eq!(
+            find_vault_wsol_account(&infos, &vault).unwrap_err(),
+            error!(IPFlowError::VaultWsolAccountMissing)
+        );
+        assert!(!is_vault_wsol_account(&infos[0], &vault_key));
+        assert!(!is_vault_wsol_account(&infos[1], &vault_key));
+    }
+
+    #[test]
+    fn test_jupiter_error_mapping() {
+        assert_eq!(
+            map_jupiter_error(ProgramError::Custom(JUPITER_ERR_SLIPPAGE_TOLERANCE_EXCEEDED)),
+            error!(IPFlowError::SlippageExceeded)
+        );
+        for code in [
+            JUPITER_ERR_EMPTY_ROUTE,
+            JUPITER_ERR_INVALID_INPUT_INDEX,
+            JUPITER_ERR_INVALID_OUTPUT_INDEX,
+            JUPITER_ERR_NOT_ENOUGH_ACCOUNT_KEYS,
+            JUPITER_ERR_INVALID_ROUTE_PLAN,
+        ] {
+            assert_eq!(
+                map_jupiter_error(ProgramError::Custom(code)),
+                error!(IPFlowError::RouteExpired)
+            );
+        }
+        assert_eq!(
+            map_jupiter_error(ProgramError::Custom(6016)),
+            error!(IPFlowError::JupiterSwapFailed)
+        );
+        assert_eq!(
+            map_jupiter_error(ProgramError::InvalidAccountData),
+            error!(IPFlowError::JupiterSwapFailed)
+        );
+    }
+
+    #[test]
+    fn test_swap_payload_ceiling() {
+        assert!(check_swap_payload(MAX_JUPITER_ROUTE_ACCOUNTS, MAX_SWAP_DATA_LEN).is_ok());
+        assert_eq!(
+            check_swap_payload(MAX_JUPITER_ROUTE_ACCOUNTS + 1, 8).unwrap_err(),
+            error!(IPFlowError::SwapPayloadTooLarge)
+        );
+        assert_eq!(
+            check_swap_payload(2, MAX_SWAP_DATA_LEN + 1).unwrap_err(),
+            error!(IPFlowError::SwapPayloadTooLarge)
+        );
+    }
+
+    /// CPI 路径的堆占用估算: AccountMeta 列表与 swap_data 副本，invoke_signed 内部再各复制一份
+    fn route_cpi_heap_bytes(route_account_count: usize, swap_data_len: usize) -> usize {
+        2 * (route_account_count * std::mem::size_of::<AccountMeta>() + swap_data_len)
+    }
+
+    #[test]
+    fn test_synthetic_48_account_route_stays_within_heap_budget() {
+        // 48 个账户 (Jupiter Program + 47 个路由账户) + 900 字节 swap_data
+        let vault_key = Pubkey::new_unique();
+        let (keys, mut lamports, mut data) = route_fixture_with_len(48, &vault_key, &[12]);
+        let infos = route_infos(&keys, &mut lamports, &mut data);
+        let (mut vault_lamports, mut vault_data) = (0u64, vec![]);
+        let vault = AccountInfo::new(
+            &vault_key,
+            false,
+            false,
+            &mut vault_lamports,
+            &mut vault_data,
+            &anchor_lang::system_program::ID,
+            false,
+            0,
+        );
+        let mut swap_data = vec![0u8; 900];
+        swap_data[..8].copy_from_slice(&JUPITER_ROUTE_DISCRIMINATOR);
+
+        assert!(check_swap_payload(infos.len(), swap_data.len()).is_ok());
+        assert_eq!(find_vault_wsol_account(&infos, &vault).unwrap(), 12);
+
+        // AccountMeta 列表一次分配到位，不发生扩容
+        let metas = route_account_metas(&infos[1..], &vault_key);
+        assert_eq!(metas.len(), 47);
+        assert_eq!(metas.capacity(), 47);
+
+        // 上限内的最坏情况也远低于 32KB 堆 (为 Anchor 反序列化与日志留出余量)
+        assert!(route_cpi_heap_bytes(47, 900) < 8 * 1024);
+        assert!(
+            route_cpi_heap_bytes(MAX_JUPITER_ROUTE_ACCOUNTS - 1, MAX_SWAP_DATA_LEN) < 8 * 1024
+        );
+    }
+
+    #[test]
+    fn test_pinned_vault_wsol_account_skips_scan() {
+        // 路由中出现两个 Vault WSOL 账户：扫描方式拒绝，登记后按地址直接定位
+        let vault_key = Pubkey::new_unique();
+        let (keys, mut lamports, mut data) = route_fixture(&vault_key, &[5, 30]);
+        let infos = route_infos(&keys, &mut lamports, &mut data);
+        let (mut vault_lamports, mut vault_data) = (0u64, vec![]);
+        let vault = AccountInfo::new(
+            &vault_key,
+            false,
+            false,
+            &mut vault_lamports,
+            &mut vault_data,
+            &anchor_lang::system_program::ID,
+            false,
+            0,
+        );
+
+        assert_eq!(
+            find_pinned_vault_wsol_account(&infos, &vault, &keys[30]).unwrap(),
+            30
+        );
+        // 登记的账户不在路由中，或登记账户不是 Vault WSOL 账户
+        assert_eq!(
+            find_pinned_vault_wsol_account(&infos, &vault, &Pubkey::new_unique()).unwrap_err(),
+            error!(IPFlowError::VaultWsolAccountMissing)
+        );
+        assert_eq!(
+            find_pinned_vault_wsol_account(&infos, &vault, &keys[7]).unwrap_err(),
+            error!(IPFlowError::VaultWsolAccountMissing)
+        );
+        // Jupiter Program 位置不计入
+        assert_eq!(
+            find_pinned_vault_wsol_account(&infos, &vault, &keys[0]).unwrap_err(),
+            error!(IPFlowError::VaultWsolAccountMissing)
+        );
+    }
+
+    #[test]
+    fn test_smuggled_vault_usdt_account_cannot_be_spent() {
+        // 路由中除 WSOL 输入账户 (5) 外还夹带了 Vault 的 USDT 账户 (9)
+        let vault_key = Pubkey::new_unique();
+        let usdt_mint = Pubkey::new_unique();
+        let (keys, mut lamports, mut data) = route_fixture(&vault_key, &[5]);
+        data[9] = token_account_data(usdt_mint, vault_key, 50_000_000);
+        // 非 Vault 持有的 Token 账户不纳入快照
+        data[12] = token_account_data(usdt_mint, Pubkey::new_unique(), 50_000_000);
+        let infos = route_infos(&keys, &mut lamports, &mut data);
+        let (mut vault_lamports, mut vault_data) = (10_000u64, vec![]);
+        let vault = AccountInfo::new(
+            &vault_key,
+            false,
+            true,
+            &mut vault_lamports,
+            &mut vault_data,
+            &anchor_lang::system_program::ID,
+            false,
+            0,
+        );
+
+        let before = snapshot_vault_token_balances(&infos, &vault_key);
+        assert_eq!(before, vec![(5, 1_000), (9, 50_000_000)]);
+
+        // 构造的路由从 USDT 账户取款，WSOL 余额不变
+        set_token_amount(&infos[9], 0);
+        assert_eq!(
+            check_vault_token_spend(&infos, &before, 5, 1_000, &vault, 10_000).unwrap_err(),
+            error!(IPFlowError::ExcessiveSwapInput)
+        );
+
+        // 正常路由: 仅 WSOL 减少且不超过上限
+        set_token_amount(&infos[9], 50_000_000);
+        set_token_amount(&infos[5], 200);
+        assert!(check_vault_token_spend(&infos, &before, 5, 800, &vault, 10_000).is_ok());
+        assert_eq!(
+            check_vault_token_spend(&infos, &before, 5, 799, &vault, 10_000).unwrap_err(),
+            error!(IPFlowError::ExcessiveSwapInput)
+        );
+    }
+
+    #[test]
+    fn test_vault_lamports_spend_counts_against_input_limit() {
+        let vault_key = Pubkey::new_unique();
+        let (keys, mut lamports, mut data) = route_fixture(&vault_key, &[5]);
+        let infos = route_infos(&keys, &mut lamports, &mut data);
+        let (mut vault_lamports, mut vault_data) = (10_000u64, vec![]);
+        let vault = AccountInfo::new(
+            &vault_key,
+            false,
+            true,
+            &mut vault_lamports,
+            &mut vault_data,
+            &anchor_lang::system_program::ID,
+            false,
+            0,
+        );
+        let before = snapshot_vault_token_balances(&infos, &vault_key);
+
+        // 构造的路由用 Vault 签名直接转走 SOL，WSOL 余额不变
+        **vault.try_borrow_mut_lamports().unwrap() = 4_000;
+        assert_eq!(
+            check_vault_token_spend(&infos, &before, 5, 1_000, &vault, 10_000).unwrap_err(),
+            error!(IPFlowError::ExcessiveSwapInput)
+        );
+
+        // lamports 与 WSOL 的减少量合计计入上限
+        **vault.try_borrow_mut_lamports().unwrap() = 9_700;
+        set_token_amount(&infos[5], 300);
+        assert!(check_vault_token_spend(&infos, &before, 5, 1_000, &vault, 10_000).is_ok());
+        assert_eq!(
+            check_vault_token_spend(&infos, &before, 5, 999, &vault, 10_000).unwrap_err(),
+            error!(IPFlowError::ExcessiveSwapInput)
+        );
+
+        // swap 中 Vault lamports 增加 (例如关闭临时账户返还租金) 不影响额度
+        **vault.try_borrow_mut_lamports().unwrap() = 12_000;
+        assert!(check_vault_token_spend(&infos, &before, 5, 700, &vault, 10_000).is_ok());
+    }
+
+    #[test]
+    fn test_jupiter_claim_cannot_spend_wsol_principal() {
+        // Vault WSOL 账户 (5) 中有 1_000 旧版未解包的 WSOL 支付本金，领取的 swap 输入为 400
+        let vault_key = Pubkey::new_unique();
+        let (keys, mut lamports, mut data) = route_fixture(&vault_key, &[5]);
+        let infos = route_infos(&keys, &mut lamports, &mut data);
+        let (mut vault_lamports, mut vault_data) = (10_000u64, vec![]);
+        let vault = AccountInfo::new(
+            &vault_key,
+            false,
+            true,
+            &mut vault_lamports,
+            &mut vault_data,
+            &anchor_lang::system_program::ID,
+            false,
+            0,
+        );
+
+        // 快照取于包装之前，随后从 Vault lamports 包装 swap 输入
+        let before = snapshot_vault_token_balances(&infos, &vault_key);
+        assert_eq!(before, vec![(5, 1_000)]);
+        **vault.try_borrow_mut_lamports().unwrap() = 9_600;
+        set_token_amount(&infos[5], 1_400);
+
+        // 路由消耗了包装的输入之外的 300 本金
+        set_token_amount(&infos[5], 700);
+        assert_eq!(
+            check_vault_token_spend(&infos, &before, 5, 400, &vault, 10_000).unwrap_err(),
+            error!(IPFlowError::ExcessiveSwapInput)
+        );
+
+        // 正常路由只消耗包装的输入，本金原样保留
+        set_token_amount(&infos[5], 1_000);
+        assert!(check_vault_token_spend(&infos, &before, 5, 400, &vault, 10_000).is_ok());
+        assert_eq!(get_token_amount(&infos[5]).unwrap(), 1_000);
+    }
 }
@@ -1,11 +1,20 @@
+pub mod account_close;
+pub mod core_cpi;
+pub mod economics;
+pub mod escrow;
 pub mod jupiter_cpi;
 pub mod pyth_oracle;
 pub mod raydium_cpi;
+pub mod vault;
 pub mod vrf_helper;
 pub mod wsol_helper;
 
+pub use account_close::*;
+pub use core_cpi::*;
+pub use escrow::*;
 pub use jupiter_cpi::*;
 pub use pyth_oracle::*;
 pub use raydium_cpi::*;
+pub use vault::*;
 pub use vrf_helper::*;
 pub use wsol_helper::*;
@@ -1,35 +1,127 @@
-use crate::constants::{PYTH_SOL_USD_FEED_ID, SOL_DECIMALS, USD_PRECISION};
+use crate::constants::{
+    BPS_DENOMINATOR, MAX_PRICE_CONF_BPS, PYTH_SOL_USD_FEED_ID, SOL_DECIMALS, USD_PRECISION,
+};
 use crate::errors::IPFlowError;
 use anchor_lang::prelude::*;
 use pyth_solana_receiver_sdk::price_update::{Price, PriceUpdateV2};
 
-/// 价格最大有效期（秒）- 超过此时间的价格视为陈旧
-/// NOTE: Devnet 上 Pyth 更新频率较低，设置为 1 小时
-/// 生产环境应改回 60 秒
-const MAX_PRICE_AGE_SECONDS: u64 = 3600;
+/// 获取经过完整校验的 SOL/USD 价格
+///
+/// 所有读取 Pyth 价格的指令都必须经过此函数，集中完成:
+/// 1. 账户 owner 为 Pyth Receiver 程序 (防止其他程序伪造同布局账户)
+/// 2. feed id 为 SOL/USD
+/// 3. 发布时间不早于 `max_price_age_seconds`
+/// 4. 价格为正且置信区间不超过 MAX_PRICE_CONF_BPS
+pub fn get_validated_price<'info>(
+    price_update: &Account<'info, PriceUpdateV2>,
+    max_price_age_seconds: u64,
+) -> Result<Price> {
+    check_price_account_owner(&price_update.to_account_info())?;
+    require!(
+        price_update.price_message.feed_id == PYTH_SOL_USD_FEED_ID,
+        IPFlowError::PythFeedIdMismatch
+    );
+
+    let clock = Clock::get()?;
+
+    // 使用带时效校验的价格获取方法，防止陈旧价格攻击
+    let price: Price = price_update
+        .get_price_no_older_than(&clock, max_price_age_seconds, &PYTH_SOL_USD_FEED_ID)
+        .map_err(|_| error!(IPFlowError::PythPriceStale))?;
+
+    check_price_sanity(price.price, price.conf)?;
+    Ok(price)
+}
+
+/// 校验价格账户由 Pyth Receiver 程序持有
+pub(crate) fn check_price_account_owner(info: &AccountInfo) -> Result<()> {
+    require_keys_eq!(
+        *info.owner,
+        pyth_solana_receiver_sdk::ID,
+        IPFlowError::InvalidPythAccountOwner
+    );
+    Ok(())
+}
+
+/// 校验价格为正数且置信区间在允许范围内 (conf / price <= MAX_PRICE_CONF_BPS)
+pub(crate) fn check_price_sanity(price: i64, conf: u64) -> Result<()> {
+    require!(price > 0, IPFlowError::PythPriceInvalid);
 
-/// 将 micro-USD (10^6) 换算为 Lamports (10^9)
+    let max_conf = (price as u128)
+        .checked_mul(MAX_PRICE_CONF_BPS as u128)
+        .ok_or(error!(IPFlowError::MathOverflow))?
+        / BPS_DENOMINATOR as u128;
+    require!(
+        (conf as u128) <= max_conf,
+        IPFlowError::PythPriceConfidenceTooWide
+    );
+    Ok(())
+}
+
+/// 将 micro-USD (10^6) 换算为 Lamports (10^9) 的取整方向
+///
+/// 统一约定: 用户支付向上取整 (不少付)，Vault 发放向下取整 (不多付)，取整误差始终不超过 1 lamport
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    /// 向下取整 (领取、奖励、托管等发放侧)
+    Floor,
+    /// 向上取整 (request_mint 等支付侧)
+    Ceil,
+}
+
+/// 发放侧换算: 按校验后的 Pyth 价格将 micro-USD 换算为 Lamports，向下取整
 ///
 /// 计算公式:
 /// lamports = (micro_usd / 10^6) * (1 / price_usd) * 10^9
 /// 为了防止精度丢失，先乘后除:
 /// lamports = (micro_usd * 10^9 * 10^price_expo) / (price * 10^6)
-pub fn get_lamports_for_micro_usd(
-    price_update: &PriceUpdateV2,
+///
+/// `max_price_age_seconds` 由调用方从 `IPFlowState.max_price_age_seconds` 读取，
+/// 超过该时长的价格视为陈旧
+pub fn get_lamports_floor<'info>(
+    price_update: &Account<'info, PriceUpdateV2>,
     micro_usd_amount: u64,
+    max_price_age_seconds: u64,
 ) -> Result<u64> {
-    let clock = Clock::get()?;
+    let current_price = get_validated_price(price_update, max_price_age_seconds)?;
+    convert_micro_usd_to_lamports(
+        current_price.price,
+        current_price.exponent,
+        micro_usd_amount,
+        Rounding::Floor,
+    )
+}
 
-    // 使用带时效校验的价格获取方法，防止陈旧价格攻击
-    let current_price: Price = price_update
-        .get_price_no_older_than(&clock, MAX_PRICE_AGE_SECONDS, &PYTH_SOL_USD_FEED_ID)
-        .map_err(|_| error!(IPFlowError::PythPriceStale))?;
+/// 支付侧换算: 与 `get_lamports_floor` 相同的价格校验，向上取整
+pub fn get_lamports_ceil<'info>(
+    price_update: &Account<'info, PriceUpdateV2>,
+    micro_usd_amount: u64,
+    max_price_age_seconds: u64,
+) -> Result<u64> {
+    let current_price = get_validated_price(price_update, max_price_age_seconds)?;
+    convert_micro_usd_to_lamports(
+        current_price.price,
+        current_price.exponent,
+        micro_usd_amount,
+        Rounding::Ceil,
+    )
+}
 
-    // 校验价格为正数，防止无效价格
-    require!(current_price.price > 0, IPFlowError::PythPriceInvalid);
+/// 按给定价格 (price * 10^exponent USD/SOL) 将 micro-USD 换算为 Lamports (向下取整)，不做时效校验
+pub(crate) fn micro_usd_to_lamports(price: i64, exponent: i32, micro_usd_amount: u64) -> Result<u64> {
+    convert_micro_usd_to_lamports(price, exponent, micro_usd_amount, Rounding::Floor)
+}
 
-    let price = current_price.price as u128;
-    let expo = current_price.exponent.unsigned_abs();
+/// 按给定价格与取整方向将 micro-USD 换算为 Lamports，不做时效校验
+pub(crate) fn convert_micro_usd_to_lamports(
+    price: i64,
+    exponent: i32,
+    micro_usd_amount: u64,
+    rounding: Rounding,
+) -> Result<u64> {
+    require!(price > 0, IPFlowError::PythPriceInvalid);
+    let price = price as u128;
+    let expo = exponent.unsigned_abs();
 
     // 10^expo
     let scale_factor = 10u128
@@ -53,14 +145,151 @@ pub fn get_lamports_for_micro_usd(
         .checked_mul(USD_PRECISION as u128)
         .ok_or(error!(IPFlowError::MathOverflow))?;
 
-    let lamports = numerator
-        .checked_div(denominator)
-        .ok_or(error!(IPFlowError::MathOverflow))?;
+    let lamports = match rounding {
+        Rounding::Floor => numerator / denominator,
+        Rounding::Ceil => numerator.div_ceil(denominator),
+    };
 
-    Ok(lamports as u64)
+    u64::try_from(lamports).map_err(|_| error!(IPFlowError::MathOverflow))
 }
 
-/// 保留旧接口供 request_mint 使用 (5U 支付逻辑)
-pub fn get_lamports_for_usd(price_update: &PriceUpdateV2, usd_amount: u64) -> Result<u64> {
-    get_lamports_for_micro_usd(price_update, usd_amount * USD_PRECISION)
+/// 整数美元金额换算为 Lamports 的旧接口 (支付侧向上取整)，票价请使用 TICKET_PRICE_MICRO_USD
+pub fn get_lamports_for_usd<'info>(
+    price_update: &Account<'info, PriceUpdateV2>,
+    usd_amount: u64,
+    max_price_age_seconds: u64,
+) -> Result<u64> {
+    get_lamports_ceil(
+        price_update,
+        usd_amount * USD_PRECISION,
+        max_price_age_seconds,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owner_check_with(owner: &Pubkey) -> Result<()> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 1_000_000u64;
+        let mut data = vec![0u8; 128];
+        let info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            owner,
+            false,
+            0,
+        );
+        check_price_account_owner(&info)
+    }
+
+    #[test]
+    fn test_owner_check_accepts_pyth_receiver() {
+        assert!(owner_check_with(&pyth_solana_receiver_sdk::ID).is_ok());
+    }
+
+    #[test]
+    fn test_owner_check_rejects_spoofed_account() {
+        // 其他程序创建的同布局账户 (discriminator 相同) 必须被拒绝
+        let spoofer = Pubkey::new_unique();
+        assert_eq!(
+            owner_check_with(&spoofer).unwrap_err(),
+            error!(IPFlowError::InvalidPythAccountOwner)
+        );
+    }
+
+    #[test]
+    fn test_price_sanity_rejects_non_positive() {
+        assert_eq!(
+            check_price_sanity(0, 0).unwrap_err(),
+            error!(IPFlowError::PythPriceInvalid)
+        );
+        assert_eq!(
+            check_price_sanity(-1, 0).unwrap_err(),
+            error!(IPFlowError::PythPriceInvalid)
+        );
+    }
+
+    #[test]
+    fn test_price_sanity_confidence_bound() {
+        // price = 150.00000000 (expo -8), 2% = 3.00000000
+        let price = 15_000_000_000i64;
+        assert!(check_price_sanity(price, 300_000_000).is_ok());
+        assert_eq!(
+            check_price_sanity(price, 300_000_001).unwrap_err(),
+            error!(IPFlowError::PythPriceConfidenceTooWide)
+        );
+    }
+
+    #[test]
+    fn test_micro_usd_to_lamports_conversion() {
+        // 1 SOL = 150 USD (expo -8)，1.5 USD = 0.01 SOL
+        assert_eq!(
+            micro_usd_to_lamports(15_000_000_000, -8, 1_500_000).unwrap(),
+            10_000_000
+        );
+        assert_eq!(micro_usd_to_lamports(15_000_000_000, -8, 0).unwrap(), 0);
+        assert_eq!(
+            micro_usd_to_lamports(0, -8, 1).unwrap_err(),
+            error!(IPFlowError::PythPriceInvalid)
+        );
+    }
+
+    /// lamports 按同一价格换回 micro-USD (向下取整)，仅用于往返校验
+    fn lamports_to_micro_usd(price: i64, exponent: i32, lamports: u64) -> u64 {
+        let scale = 10u128.pow(exponent.unsigned_abs());
+        let numerator = lamports as u128 * price as u128 * USD_PRECISION as u128;
+        (numerator / (10u128.pow(SOL_DECIMALS) * scale)) as u64
+    }
+
+    /// 确定性伪随机序列 (LCG)，覆盖价格与金额的组合
+    fn samples() -> impl Iterator<Item = (i64, u64)> {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        (0..2_000).map(move |_| {
+            state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            // 价格 1 ~ 10_000 USD (expo -8)，金额 0 ~ 1_000_000 USD
+            let price = (state >> 33) as i64 % 1_000_000_000_000 + 100_000_000;
+            let micro_usd = state % 1_000_000_000_000;
+            (price, micro_usd)
+        })
+    }
+
+    #[test]
+    fn test_ceil_and_floor_differ_by_at_most_one_lamport() {
+        for (price, micro_usd) in samples() {
+            let floor = micro_usd_to_lamports(price, -8, micro_usd).unwrap();
+            let ceil =
+                convert_micro_usd_to_lamports(price, -8, micro_usd, Rounding::Ceil).unwrap();
+            assert!(ceil >= floor);
+            assert!(ceil - floor <= 1);
+        }
+        // 整除时两者相等
+        assert_eq!(
+            convert_micro_usd_to_lamports(15_000_000_000, -8, 1_500_000, Rounding::Ceil).unwrap(),
+            10_000_000
+        );
+    }
+
+    #[test]
+    fn test_round_trip_never_creates_lamports() {
+        for (price, micro_usd) in samples() {
+            // 发放侧: 美元 -> lamports (floor) -> 美元不超过原值
+            let paid_out = micro_usd_to_lamports(price, -8, micro_usd).unwrap();
+            assert!(lamports_to_micro_usd(price, -8, paid_out) <= micro_usd);
+
+            // 支付侧: 用户按 ceil 支付的 lamports 至少值标价
+            let charged =
+                convert_micro_usd_to_lamports(price, -8, micro_usd, Rounding::Ceil).unwrap();
+            assert!(lamports_to_micro_usd(price, -8, charged) >= micro_usd);
+
+            // lamports -> 美元 (floor) -> lamports (floor) 不超过原 lamports
+            let back = micro_usd_to_lamports(price, -8, lamports_to_micro_usd(price, -8, charged))
+                .unwrap();
+            assert!(back <= charged);
+        }
+    }
 }
@@ -3,8 +3,11 @@ use anchor_lang::prelude::*;
 use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
 use anchor_lang::solana_program::program::invoke_signed;
 
-use crate::constants::{RAYDIUM_CP_SWAP_PROGRAM, RAYDIUM_CP_SWAP_PROGRAM_DEVNET};
 use crate::errors::IPFlowError;
+use crate::utils::jupiter_cpi::get_token_amount;
+
+/// Raydium CPMM ExceededSlippage 自定义错误码
+const RAYDIUM_ERR_EXCEEDED_SLIPPAGE: u32 = 6005;
 
 /// SwapBaseInput 指令参数
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -13,8 +16,19 @@ pub struct SwapBaseInputArgs {
     pub minimum_amount_out: u64,
 }
 
+/// 校验 Raydium CPMM Program ID 与配置 (config.raydium_program) 一致
+pub fn check_raydium_program(cp_swap_program: &Pubkey, expected_program: &Pubkey) -> Result<()> {
+    require_keys_eq!(
+        *cp_swap_program,
+        *expected_program,
+        IPFlowError::InvalidRaydiumProgram
+    );
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn swap_base_input<'info>(
+    expected_program: &Pubkey,
     cp_swap_program: AccountInfo<'info>,
     payer: AccountInfo<'info>,
     authority: AccountInfo<'info>,
@@ -34,11 +48,7 @@ pub fn swap_base_input<'info>(
     signer_seeds: &[&[&[u8]]],
 ) -> Result<()> {
     // 0. 验证 Raydium CPMM Program ID (安全检查)
-    require!(
-        cp_swap_program.key() == RAYDIUM_CP_SWAP_PROGRAM
-            || cp_swap_program.key() == RAYDIUM_CP_SWAP_PROGRAM_DEVNET,
-        IPFlowError::InvalidRaydiumProgram
-    );
+    check_raydium_program(&cp_swap_program.key(), expected_program)?;
 
     // 1. 构建指令数据
     // swap_base_input discriminator (Raydium CPMM Anchor IDL)
@@ -94,5 +104,176 @@ pub fn swap_base_input<'info>(
         ],
         signer_seeds,
     )
-    .map_err(Into::into)
+    .map_err(map_raydium_error)
+}
+
+/// 将 Raydium CPI 返回的错误映射为具体错误码 (ExceededSlippage → `SlippageExceeded`，其余 → `RaydiumSwapFailed`)
+///
+/// 注意: 运行时在被调用程序失败时通常直接中止整笔交易，仅 invoke 返回的错误会经过此映射
+pub(crate) fn map_raydium_error(err: ProgramError) -> Error {
+    match err {
+        ProgramError::Custom(RAYDIUM_ERR_EXCEEDED_SLIPPAGE) => {
+            error!(IPFlowError::SlippageExceeded)
+        }
+        other => {
+            msg!("Raydium CPI error: {:?}", other);
+            error!(IPFlowError::RaydiumSwapFailed)
+        }
+    }
+}
+
+/// Raydium swap 后余额校验 (与 Jupiter 路径的保护对齐)
+///
+/// - 用户输出账户增量必须 >= minimum_amount_out，否则 `SwapReturnedTooLittle`
+/// - Vault WSOL 账户的支出必须恰好等于包装的 amount_in，否则 `ExcessiveSwapInput`
+///
+/// `input_before` / `output_before` 须在 wrap 之后、swap CPI 之前读取。
+/// 返回用户实际到账数量
+pub fn verify_swap_balances(
+    input_token_account: &AccountInfo,
+    output_token_account: &AccountInfo,
+    input_before: u64,
+    output_before: u64,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<u64> {
+    let output_after = get_token_amount(output_token_account)?;
+    let actual_output = output_after
+        .checked_sub(output_before)
+        .ok_or(IPFlowError::MathOverflow)?;
+    require!(
+        actual_output >= minimum_amount_out,
+        IPFlowError::SwapReturnedTooLittle
+    );
+
+    let input_after = get_token_amount(input_token_account)?;
+    let input_spent = input_before.saturating_sub(input_after);
+    require!(input_spent == amount_in, IPFlowError::ExcessiveSwapInput);
+
+    msg!(
+        "Raydium swap verified: actual_output={}, minimum_required={}, input_spent={}",
+        actual_output,
+        minimum_amount_out,
+        input_spent
+    );
+
+    Ok(actual_output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{RAYDIUM_CP_SWAP_PROGRAM, RAYDIUM_CP_SWAP_PROGRAM_DEVNET};
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token;
+
+    #[test]
+    fn raydium_program_must_match_config() {
+        assert!(check_raydium_program(&RAYDIUM_CP_SWAP_PROGRAM, &RAYDIUM_CP_SWAP_PROGRAM).is_ok());
+        assert!(
+            check_raydium_program(&RAYDIUM_CP_SWAP_PROGRAM_DEVNET, &RAYDIUM_CP_SWAP_PROGRAM_DEVNET)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn raydium_other_network_program_rejected() {
+        // 主网配置拒绝 devnet 程序，反之亦然
+        assert!(check_raydium_program(&RAYDIUM_CP_SWAP_PROGRAM_DEVNET, &RAYDIUM_CP_SWAP_PROGRAM).is_err());
+        assert!(check_raydium_program(&RAYDIUM_CP_SWAP_PROGRAM, &RAYDIUM_CP_SWAP_PROGRAM_DEVNET).is_err());
+        assert!(check_raydium_program(&Pubkey::new_unique(), &RAYDIUM_CP_SWAP_PROGRAM).is_err());
+    }
+
+    fn token_account_data(amount: u64) -> Vec<u8> {
+        let account = spl_token::state::Account {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(account, &mut data).unwrap();
+        data
+    }
+
+    fn run(
+        input_before: u64,
+        input_after: u64,
+        output_before: u64,
+        output_after: u64,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<u64> {
+        let (input_key, output_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut input_lamports, mut output_lamports) = (0u64, 0u64);
+        let mut input_data = token_account_data(input_after);
+        let mut output_data = token_account_data(output_after);
+        let input = AccountInfo::new(
+            &input_key,
+            false,
+            true,
+            &mut input_lamports,
+            &mut input_data,
+            &spl_token::ID,
+            false,
+            0,
+        );
+        let output = AccountInfo::new(
+            &output_key,
+            false,
+            true,
+            &mut output_lamports,
+            &mut output_data,
+            &spl_token::ID,
+            false,
+            0,
+        );
+        verify_swap_balances(
+            &input,
+            &output,
+            input_before,
+            output_before,
+            amount_in,
+            minimum_amount_out,
+        )
+    }
+
+    #[test]
+    fn accepts_swap_meeting_minimum_output() {
+        assert_eq!(run(1_000, 0, 50, 1_050, 1_000, 970).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn rejects_output_below_minimum() {
+        let err = run(1_000, 0, 0, 900, 1_000, 970).unwrap_err();
+        assert_eq!(err, error!(IPFlowError::SwapReturnedTooLittle));
+    }
+
+    #[test]
+    fn maps_raydium_cpi_errors() {
+        assert_eq!(
+            map_raydium_error(ProgramError::Custom(RAYDIUM_ERR_EXCEEDED_SLIPPAGE)),
+            error!(IPFlowError::SlippageExceeded)
+        );
+        assert_eq!(
+            map_raydium_error(ProgramError::Custom(6000)),
+            error!(IPFlowError::RaydiumSwapFailed)
+        );
+    }
+
+    #[test]
+    fn rejects_output_balance_decrease() {
+        let err = run(1_000, 0, 100, 50, 1_000, 0).unwrap_err();
+        assert_eq!(err, error!(IPFlowError::MathOverflow));
+    }
+
+    #[test]
+    fn rejects_input_spend_mismatch() {
+        let overspent = run(2_000, 0, 0, 1_000, 1_000, 970).unwrap_err();
+        assert_eq!(overspent, error!(IPFlowError::ExcessiveSwapInput));
+
+        let underspent = run(1_000, 500, 0, 1_000, 1_000, 970).unwrap_err();
+        assert_eq!(underspent, error!(IPFlowError::ExcessiveSwapInput));
+    }
 }
@@ -0,0 +1,27 @@
+// ==================== Vault Helper Module ====================
+//
+// Vault PDA 余额相关的公共校验：
+// 1. 校验 Vault owner 为 System Program
+// 2. 按 Vault 实际数据长度计算租金保留额，返回可用 lamports
+
+use anchor_lang::prelude::*;
+
+use crate::errors::IPFlowError;
+
+/// 返回 Vault 扣除租金保留额后的可用 lamports
+///
+/// 租金保留额按 Vault 实际数据长度计算 (`Rent::minimum_balance(vault.data_len())`)，
+/// 避免硬编码 0 字节在 Vault 扩容后失效。
+///
+/// # 错误
+/// - `InvalidVaultOwner`: Vault owner 不是 System Program
+pub fn vault_available_lamports(vault: &AccountInfo) -> Result<u64> {
+    require_keys_eq!(
+        *vault.owner,
+        anchor_lang::system_program::ID,
+        IPFlowError::InvalidVaultOwner
+    );
+
+    let min_rent = Rent::get()?.minimum_balance(vault.data_len());
+    Ok(vault.lamports().saturating_sub(min_rent))
+}
@@ -1,13 +1,21 @@
+use anchor_lang::solana_program::hash::hashv;
 use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::pubkey::Pubkey;
 
 use crate::constants::{
-    PROB_PRECISION, REWARD_STEP, TIER1_MIN_USD, TIER1_STEPS, TIER1_THRESHOLD, TIER2_MIN_USD,
-    TIER2_STEPS, TIER2_THRESHOLD, TIER3_MIN_USD, TIER3_STEPS, TIER3_THRESHOLD, TIER4_MIN_USD,
-    TIER4_STEPS,
+    BPS_DENOMINATOR, DEFAULT_TIER_THRESHOLDS, NO_PRIZE_POOL, PROB_PRECISION, REWARD_STEP,
+    TIER1_MIN_USD, TIER1_STEPS, TIER2_MIN_USD, TIER2_STEPS, TIER3_MIN_USD, TIER3_STEPS,
+    TIER4_MIN_USD, TIER4_STEPS,
 };
 
 // ==================== VRF Helper: 通用随机数处理 ====================
 
+/// 每次派生覆盖的卡数: 32 字节切分为 4 个 8 字节熵块
+pub const CARDS_PER_DERIVATION: u32 = 4;
+
+/// 未设置 Tier 覆盖的奖品池 (全 0 表示使用 DEFAULT_TIER_THRESHOLDS)
+pub const NO_TIER_OVERRIDE: [u64; 4] = [0; 4];
+
 /// 抽奖结果
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct LotteryResult {
@@ -15,6 +23,10 @@ pub struct LotteryResult {
     pub total_won_usd: u64,
     /// 选中的奖品池索引
     pub selected_pool_index: u8,
+    /// 保底机制强制提升到 Tier 3+ 的卡数
+    pub pity_upgrades: u32,
+    /// 本次开奖后的保底计数器 (连续 Tier 1/2 卡数)
+    pub pity_counter: u16,
 }
 
 /// 处理 VRF 回调结果，计算抽奖奖金
@@ -33,28 +45,387 @@ pub fn process_vrf_result(
     active_pool_count: u8,
     active_pool_indices: &[u8; 50],
 ) -> std::result::Result<LotteryResult, ProgramError> {
-    let mut total_won_usd: u64 = 0;
+    process_vrf_result_with_pity(
+        randomness,
+        amount_of_cards,
+        active_pool_count,
+        active_pool_indices,
+        &[NO_TIER_OVERRIDE; 50],
+        0,
+        0,
+    )
+}
+
+/// 处理 VRF 回调结果 (含保底机制与奖品池 Tier 覆盖)
+///
+/// 先按字节 8-15 选择奖品池，再使用该池的 Tier 阈值表逐卡开奖 (未设置覆盖时为默认表)。
+/// 每张 Tier 1/2 卡使保底计数器 +1，Tier 3/4 卡将其清零；
+/// 计数器达到 `pity_threshold` 的那张卡被强制提升到 Tier 3+ 并清零计数器。
+/// `pity_threshold = 0` 表示关闭保底，行为与 `process_vrf_result` 一致。
+///
+/// # 参数
+/// - `active_pool_tier_overrides`: 与 `active_pool_indices` 平行的 Tier 覆盖表 (全 0 表示未覆盖)
+/// - `pity_threshold`: 保底阈值 (连续未中 Tier 3+ 的卡数)
+/// - `pity_counter`: 开奖前用户的保底计数器
+pub fn process_vrf_result_with_pity(
+    randomness: &[u8; 32],
+    amount_of_cards: u32,
+    active_pool_count: u8,
+    active_pool_indices: &[u8; 50],
+    active_pool_tier_overrides: &[[u64; 4]; 50],
+    pity_threshold: u16,
+    pity_counter: u16,
+) -> std::result::Result<LotteryResult, ProgramError> {
+    let position = select_active_pool_position(randomness, active_pool_count);
+    let selected_pool_index = position.map_or(NO_PRIZE_POOL, |pos| active_pool_indices[pos]);
+    let thresholds = resolve_tier_thresholds(
+        &position.map_or(NO_TIER_OVERRIDE, |pos| active_pool_tier_overrides[pos]),
+    );
+
+    let progress = advance_reveal(
+        randomness,
+        amount_of_cards,
+        pity_threshold,
+        &thresholds,
+        RevealProgress::start(pity_counter),
+        amount_of_cards,
+    )?;
+
+    Ok(LotteryResult {
+        total_won_usd: progress.total_won_usd,
+        selected_pool_index,
+        pity_upgrades: progress.pity_upgrades,
+        pity_counter: progress.pity_counter,
+    })
+}
+
+/// 可证明公平承诺: hash(client_seed || request_slot (LE) || mint_request)
+///
+/// request_mint 写入 MintRequest.commitment 并在 MintRequested 中发出，
+/// 揭示时 LotteryRevealed 同时发出该承诺与 hash(randomness)，供第三方关联同一请求
+pub fn request_commitment(client_seed: u8, request_slot: u64, mint_request: &Pubkey) -> [u8; 32] {
+    hashv(&[
+        &[client_seed],
+        &request_slot.to_le_bytes(),
+        mint_request.as_ref(),
+    ])
+    .to_bytes()
+}
+
+/// VRF 随机数摘要 hash(randomness)：LotteryRevealed 对外发出，MintRequest 据此识别重复投递
+pub fn randomness_hash(randomness: &[u8; 32]) -> [u8; 32] {
+    hashv(&[randomness]).to_bytes()
+}
+
+/// 第三方复算揭示结果 (纯函数，不依赖链上状态)
+///
+/// 按默认 Tier 阈值表、不启用保底逐卡复算基础奖金 (RTP 调节前，即 LotteryRevealed.unmodulated_won_usd)；
+/// 选中池使用了 Tier 覆盖或开启保底时，改用 `process_vrf_result_with_pity` 并传入事件中的
+/// tier_thresholds / pity 参数。返回结果的 selected_pool_index 为 NO_PRIZE_POOL，以事件为准。
+/// 全 0 的 commitment (升级前创建的请求) 无法关联，返回 InvalidArgument。
+pub fn verify_reveal(
+    commitment: &[u8; 32],
+    randomness: &[u8; 32],
+    amount_of_cards: u32,
+) -> std::result::Result<LotteryResult, ProgramError> {
+    if commitment == &[0u8; 32] {
+        return Err(ProgramError::InvalidArgument);
+    }
+    process_vrf_result(randomness, amount_of_cards, 0, &[NO_PRIZE_POOL; 50])
+}
 
-    for i in 0..amount_of_cards {
-        let card_random = derive_random_result(randomness, i);
-        let won = map_to_tiered_distribution(&card_random);
+/// 分批揭示进度 (持久化在 MintRequest 中，continue_reveal 据此续算)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RevealProgress {
+    /// 已处理卡数
+    pub cards_processed: u32,
+    /// 已处理卡的累计奖金 (micro-USD，RTP 调节前)
+    pub total_won_usd: u64,
+    /// 当前保底计数器
+    pub pity_counter: u16,
+    /// 已触发的保底提升次数
+    pub pity_upgrades: u32,
+}
+
+impl RevealProgress {
+    /// 从揭示前的用户保底计数器开始
+    pub fn start(pity_counter: u16) -> Self {
+        Self {
+            pity_counter,
+            ..Self::default()
+        }
+    }
+
+    /// 是否已处理全部卡
+    pub fn is_complete(&self, amount_of_cards: u32) -> bool {
+        self.cards_processed >= amount_of_cards
+    }
+}
+
+/// 从 `progress` 续算最多 `max_cards` 张卡，返回新的进度
+///
+/// 分批结果与一次性处理全部卡逐卡一致 (保底计数器随进度延续)；
+/// `thresholds` 为选中池的 Tier 阈值表，各批次须使用同一张表
+pub fn advance_reveal(
+    randomness: &[u8; 32],
+    amount_of_cards: u32,
+    pity_threshold: u16,
+    thresholds: &[u64; 4],
+    progress: RevealProgress,
+    max_cards: u32,
+) -> std::result::Result<RevealProgress, ProgramError> {
+    let end = progress
+        .cards_processed
+        .saturating_add(max_cards)
+        .min(amount_of_cards);
+    let mut roller = CardRoller::resume(randomness, pity_threshold, thresholds, &progress);
+    let mut total_won_usd = progress.total_won_usd;
+    for _ in progress.cards_processed..end {
+        let card = roller.next_card();
         total_won_usd = total_won_usd
-            .checked_add(won)
+            .checked_add(card.won_usd)
             .ok_or(ProgramError::ArithmeticOverflow)?;
     }
 
-    let selected_pool_index = if active_pool_count > 0 {
-        select_active_prize_pool(randomness, active_pool_count, active_pool_indices)
-    } else {
-        0
-    };
+    // 每张卡奖金均为 REWARD_STEP 的整数倍，总额亦然 (防御性断言)
+    if total_won_usd % REWARD_STEP != 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
 
-    Ok(LotteryResult {
+    Ok(RevealProgress {
+        cards_processed: roller.index,
         total_won_usd,
-        selected_pool_index,
+        pity_counter: roller.counter,
+        pity_upgrades: roller.pity_upgrades,
     })
 }
 
+/// 单张卡的开奖结果 (卡牌 NFT 元数据使用)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CardOutcome {
+    /// Tier 编号 (1-4)
+    pub tier: u8,
+    /// 该卡奖金 (micro-USD，RTP 调节前)
+    pub won_usd: u64,
+}
+
+/// 逐张开卡的状态 (process_vrf_result_with_pity 与 card_outcomes 共用，保证逐卡结果与总额一致)
+struct CardRoller<'a> {
+    randomness: &'a [u8; 32],
+    pity_threshold: u16,
+    thresholds: [u64; 4],
+    counter: u16,
+    pity_upgrades: u32,
+    card_block: [u8; 32],
+    /// 当前熵块对应的卡组 (续算时首张卡不在组首，需按组重新派生)
+    block_group: Option<u32>,
+    index: u32,
+}
+
+impl<'a> CardRoller<'a> {
+    fn new(
+        randomness: &'a [u8; 32],
+        pity_threshold: u16,
+        thresholds: &[u64; 4],
+        pity_counter: u16,
+    ) -> Self {
+        Self {
+            randomness,
+            pity_threshold,
+            thresholds: *thresholds,
+            counter: pity_counter,
+            pity_upgrades: 0,
+            card_block: [0u8; 32],
+            block_group: None,
+            index: 0,
+        }
+    }
+
+    /// 从分批揭示进度续算
+    fn resume(
+        randomness: &'a [u8; 32],
+        pity_threshold: u16,
+        thresholds: &[u64; 4],
+        progress: &RevealProgress,
+    ) -> Self {
+        Self {
+            pity_upgrades: progress.pity_upgrades,
+            index: progress.cards_processed,
+            ..Self::new(randomness, pity_threshold, thresholds, progress.pity_counter)
+        }
+    }
+
+    fn next_card(&mut self) -> CardOutcome {
+        let i = self.index;
+        self.index += 1;
+
+        // 每 4 张卡共用一次派生结果，每张卡的 8 字节同时提供 Tier 熵与步进熵
+        let slot = (i % CARDS_PER_DERIVATION) as usize;
+        let group = i / CARDS_PER_DERIVATION;
+        if self.block_group != Some(group) {
+            self.card_block = derive_card_block(self.randomness, group);
+            self.block_group = Some(group);
+        }
+        let (mut roll, step_entropy) = split_card_entropy(entropy_chunk(&self.card_block, slot));
+        if roll < self.thresholds[1] {
+            self.counter = self.counter.saturating_add(1);
+            if self.pity_threshold > 0 && self.counter >= self.pity_threshold {
+                // 保底触发较少，单独派生提升熵 (索引从 u32::MAX 向下，与卡组索引不重叠)
+                roll = pity_upgrade_roll(
+                    &derive_random_result(self.randomness, u32::MAX - i),
+                    &self.thresholds,
+                );
+                self.counter = 0;
+                self.pity_upgrades += 1;
+            }
+        } else {
+            self.counter = 0;
+        }
+
+        CardOutcome {
+            tier: tier_for_roll(roll, &self.thresholds),
+            won_usd: amount_for_tier_roll(roll, step_entropy, &self.thresholds),
+        }
+    }
+}
+
+/// 复算第 `start` 张起最多 `count` 张卡的开奖结果 (超出 amount_of_cards 的部分截断)
+///
+/// `pity_threshold` / `pity_counter` / `thresholds` 须与揭示时使用的值一致
+pub fn card_outcomes(
+    randomness: &[u8; 32],
+    amount_of_cards: u32,
+    pity_threshold: u16,
+    pity_counter: u16,
+    thresholds: &[u64; 4],
+    start: u32,
+    count: u32,
+) -> Vec<CardOutcome> {
+    let end = start.saturating_add(count).min(amount_of_cards);
+    let mut roller = CardRoller::new(randomness, pity_threshold, thresholds, pity_counter);
+    let mut outcomes = Vec::with_capacity(end.saturating_sub(start) as usize);
+    for i in 0..end {
+        let card = roller.next_card();
+        if i >= start {
+            outcomes.push(card);
+        }
+    }
+    outcomes
+}
+
+/// Tier 概率值在阈值表下对应的 Tier 编号 (1-4)
+pub fn tier_for_roll(tier_roll: u64, thresholds: &[u64; 4]) -> u8 {
+    if tier_roll < thresholds[0] {
+        1
+    } else if tier_roll < thresholds[1] {
+        2
+    } else if tier_roll < thresholds[2] {
+        3
+    } else {
+        4
+    }
+}
+
+/// 奖品池 Tier 覆盖对应的生效阈值表 (未覆盖时为 DEFAULT_TIER_THRESHOLDS)
+pub fn resolve_tier_thresholds(tier_override: &[u64; 4]) -> [u64; 4] {
+    if *tier_override == NO_TIER_OVERRIDE {
+        DEFAULT_TIER_THRESHOLDS
+    } else {
+        *tier_override
+    }
+}
+
+/// 校验 Tier 阈值表: 单调不减、末项为 PROB_PRECISION，且 Tier 3+ 区间非空 (保底提升需要)
+pub fn is_valid_tier_thresholds(thresholds: &[u64; 4]) -> bool {
+    thresholds.windows(2).all(|w| w[0] <= w[1])
+        && thresholds[3] == PROB_PRECISION
+        && thresholds[1] < PROB_PRECISION
+}
+
+/// 阈值表下单卡的期望奖金 (micro-USD，不含保底与 RTP 调节)
+///
+/// 期望 = Σ P(Tier k) * (min_k + (steps_k - 1) / 2 * REWARD_STEP)
+/// const fn: 供 utils/economics.rs 在编译期校验默认分布与票价
+pub const fn compute_expected_value(thresholds: &[u64; 4]) -> u64 {
+    let tiers = [
+        (TIER1_MIN_USD, TIER1_STEPS),
+        (TIER2_MIN_USD, TIER2_STEPS),
+        (TIER3_MIN_USD, TIER3_STEPS),
+        (TIER4_MIN_USD, TIER4_STEPS),
+    ];
+    let mut lower = 0u64;
+    let mut weighted = 0u128;
+    let mut k = 0;
+    while k < tiers.len() {
+        let (min_usd, steps) = tiers[k];
+        // 均值的 2 倍避免 (steps - 1) 为奇数时的截断
+        let double_mean = 2 * min_usd as u128 + (steps as u128 - 1) * REWARD_STEP as u128;
+        weighted += thresholds[k].saturating_sub(lower) as u128 * double_mean;
+        lower = thresholds[k];
+        k += 1;
+    }
+    (weighted / (2 * PROB_PRECISION as u128)) as u64
+}
+
+/// 奖金相对成本的回报率 (bps，10000 = 100%)，成本为 0 时返回 0
+pub fn rtp_bps(value_usd: u64, cost_usd: u64) -> u64 {
+    if cost_usd == 0 {
+        return 0;
+    }
+    let bps = value_usd as u128 * BPS_DENOMINATOR as u128 / cost_usd as u128;
+    u64::try_from(bps).unwrap_or(u64::MAX)
+}
+
+/// 默认分布下单卡的期望奖金 (micro-USD，当前为 19.4825 USD)
+pub const fn expected_value_micro_usd() -> u64 {
+    compute_expected_value(&DEFAULT_TIER_THRESHOLDS)
+}
+
+/// 派生第 `group` 组 (4 张卡) 的熵块
+pub fn derive_card_block(randomness: &[u8; 32], group: u32) -> [u8; 32] {
+    derive_random_result(randomness, group)
+}
+
+/// 将单卡的 8 字节熵拆分为 (Tier 选择值, 步进熵)
+///
+/// 余数 (取模 PROB_PRECISION) 选择 Tier，商作为步进熵 (约 44 位，对最多 500 个步进的取模偏差可忽略)
+fn split_card_entropy(chunk: u64) -> (u64, u64) {
+    (chunk % PROB_PRECISION, chunk / PROB_PRECISION)
+}
+
+/// 取熵块中第 `slot` 个 8 字节 (小端序)
+fn entropy_chunk(block: &[u8; 32], slot: usize) -> u64 {
+    u64::from_le_bytes(block[slot * 8..slot * 8 + 8].try_into().unwrap())
+}
+
+/// 开奖 `amount_of_cards` 张卡所需的派生次数 (不含保底提升)
+pub fn derivations_for_cards(amount_of_cards: u32) -> u32 {
+    amount_of_cards.div_ceil(CARDS_PER_DERIVATION)
+}
+
+/// 按 Vault 健康度计算 RTP 调节系数 (bps)
+///
+/// 系数 = 10000 + (余额 - 目标) / 目标 * 10000，限制在 ±max_modulation_bps 内；
+/// 目标为 0 时不调节
+pub fn rtp_modulation_bps(vault_lamports: u64, target_lamports: u64, max_modulation_bps: u16) -> u16 {
+    if target_lamports == 0 {
+        return BPS_DENOMINATOR as u16;
+    }
+    let deviation = (vault_lamports as i128 - target_lamports as i128) * BPS_DENOMINATOR as i128
+        / target_lamports as i128;
+    let max = max_modulation_bps.min(BPS_DENOMINATOR as u16) as i128;
+    (BPS_DENOMINATOR as i128 + deviation.clamp(-max, max)) as u16
+}
+
+/// 按调节系数缩放奖金，并向下取整到 REWARD_STEP 的整数倍
+pub fn apply_rtp_modulation(total_won_usd: u64, modulation_bps: u16) -> Option<u64> {
+    let scaled = (total_won_usd as u128)
+        .checked_mul(modulation_bps as u128)?
+        .checked_div(BPS_DENOMINATOR as u128)?;
+    let scaled = u64::try_from(scaled).ok()?;
+    Some(scaled - scaled % REWARD_STEP)
+}
+
 /// 计数器法: 从原始随机数派生特定索引的随机数
 /// 使用简单的 XOR 和位旋转实现确定性派生
 pub fn derive_random_result(raw_seed: &[u8; 32], index: u32) -> [u8; 32] {
@@ -135,28 +506,23 @@ pub fn select_active_prize_pool(
     active_pool_count: u8,
     active_pool_indices: &[u8; 50],
 ) -> u8 {
-    // 没有活跃池时返回 0 (默认值)
+    // 没有活跃池时返回哨兵索引，避免指向不存在的奖品池；否则从活跃池列表中取出实际索引
+    select_active_pool_position(random_bytes, active_pool_count)
+        .map_or(NO_PRIZE_POOL, |position| active_pool_indices[position])
+}
+
+/// 选中的活跃池位置 (active_pool_indices 下标)，没有活跃池时返回 None
+///
+/// 使用随机数的第 8-15 字节 (与 select_prize_pool 保持一致) 对活跃池数量取模
+pub fn select_active_pool_position(
+    random_bytes: &[u8; 32],
+    active_pool_count: u8,
+) -> Option<usize> {
     if active_pool_count == 0 {
-        return 0;
+        return None;
     }
-
-    // 使用随机数的第 8-15 字节 (与 select_prize_pool 保持一致)
-    let random_u64 = u64::from_le_bytes([
-        random_bytes[8],
-        random_bytes[9],
-        random_bytes[10],
-        random_bytes[11],
-        random_bytes[12],
-        random_bytes[13],
-        random_bytes[14],
-        random_bytes[15],
-    ]);
-
-    // 对活跃池数量取模，得到位置
-    let position = (random_u64 % (active_pool_count as u64)) as usize;
-
-    // 从活跃池列表中取出实际索引
-    active_pool_indices[position]
+    let random_u64 = u64::from_le_bytes(random_bytes[8..16].try_into().unwrap());
+    Some((random_u64 % (active_pool_count as u64)) as usize)
 }
 
 /// [已废弃] 原平方根反演算法，保留用于回退
@@ -181,7 +547,9 @@ pub fn map_to_linear_curve(random_bytes: &[u8; 32], min_usd: u64, max_usd: u64)
 /// 32 字节 VRF 随机数熵分配：
 /// - 字节 0-7:   选择 Tier (取模 1000000)
 /// - 字节 8-15:  Tier 内离散步进选择
-/// - 字节 16-23: 选择奖品池 (保持现有逻辑)
+///
+/// 批量开奖时 (`process_vrf_result_with_pity`) 每 4 张卡共用一次派生结果，第 k 张卡取第 k 个 8 字节:
+/// 余数选择 Tier、商作为步进熵，各 Tier 概率与离散值分布与本函数一致
 ///
 /// 分布设计 (单抽 10U):
 /// - Tier 1 (15%): 5.0 - 7.0 USDC,   21 个离散值
@@ -191,25 +559,28 @@ pub fn map_to_linear_curve(random_bytes: &[u8; 32], min_usd: u64, max_usd: u64)
 ///
 /// 精度: 0.1 USDC (100,000 micro-USDC)
 pub fn map_to_tiered_distribution(random_bytes: &[u8; 32]) -> u64 {
-    // 1. 提取熵源选择 Tier (字节 0-7)
-    let tier_entropy = u64::from_le_bytes(random_bytes[0..8].try_into().unwrap());
-    let tier_roll = tier_entropy % PROB_PRECISION;
-
-    // 2. 提取 Tier 内步进熵源 (字节 8-15)
-    let step_entropy = u64::from_le_bytes(random_bytes[8..16].try_into().unwrap());
-
-    // 3. 确定 Tier 及计算奖金
-    let (min_usd, steps) = if tier_roll < TIER1_THRESHOLD {
-        (TIER1_MIN_USD, TIER1_STEPS) // 15%: 5.0-7.0 USDC
-    } else if tier_roll < TIER2_THRESHOLD {
-        (TIER2_MIN_USD, TIER2_STEPS) // 50%: 7.0-14.0 USDC
-    } else if tier_roll < TIER3_THRESHOLD {
-        (TIER3_MIN_USD, TIER3_STEPS) // 30%: 14.0-49.9 USDC
-    } else {
-        (TIER4_MIN_USD, TIER4_STEPS) // 5%: 50.0-99.9 USDC
+    let tier_roll = entropy_chunk(random_bytes, 0) % PROB_PRECISION;
+    amount_for_tier_roll(tier_roll, entropy_chunk(random_bytes, 1), &DEFAULT_TIER_THRESHOLDS)
+}
+
+/// 保底提升: 使用独立派生结果的字节 16-23 在 Tier 3/4 区间内重新取值，
+/// 保持阈值表中 Tier 3 : Tier 4 的相对概率 (默认 30 : 5)
+fn pity_upgrade_roll(random_bytes: &[u8; 32], thresholds: &[u64; 4]) -> u64 {
+    let upgrade_entropy = u64::from_le_bytes(random_bytes[16..24].try_into().unwrap());
+    thresholds[1] + upgrade_entropy % (PROB_PRECISION - thresholds[1])
+}
+
+/// 根据 Tier 选择值、步进熵源与阈值表计算奖金
+fn amount_for_tier_roll(tier_roll: u64, step_entropy: u64, thresholds: &[u64; 4]) -> u64 {
+    // 1. 确定 Tier 及计算奖金 (默认概率见 DEFAULT_TIER_THRESHOLDS)
+    let (min_usd, steps) = match tier_for_roll(tier_roll, thresholds) {
+        1 => (TIER1_MIN_USD, TIER1_STEPS), // 默认 15%: 5.0-7.0 USDC
+        2 => (TIER2_MIN_USD, TIER2_STEPS), // 默认 50%: 7.0-14.0 USDC
+        3 => (TIER3_MIN_USD, TIER3_STEPS), // 默认 30%: 14.0-49.9 USDC
+        _ => (TIER4_MIN_USD, TIER4_STEPS), // 默认 5%: 50.0-99.9 USDC
     };
 
-    // 4. 计算离散步进索引并生成奖金
+    // 2. 计算离散步进索引并生成奖金
     let idx = step_entropy % steps;
     min_usd.saturating_add(idx.saturating_mul(REWARD_STEP))
 }
@@ -230,9 +601,39 @@ fn integer_sqrt(n: u128) -> u128 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::constants::{TIER1_MAX_USD, TIER2_MAX_USD, TIER3_MAX_USD, TIER4_MAX_USD};
+    use crate::constants::{
+        TIER1_MAX_USD, TIER1_THRESHOLD, TIER2_MAX_USD, TIER2_THRESHOLD, TIER3_MAX_USD,
+        TIER3_THRESHOLD, TIER4_MAX_USD,
+    };
     use proptest::prelude::*;
 
+    const NO_OVERRIDES: [[u64; 4]; 50] = [NO_TIER_OVERRIDE; 50];
+
+    #[test]
+    fn commitment_binds_seed_slot_and_request() {
+        let request = Pubkey::new_unique();
+        let commitment = request_commitment(7, 1_000, &request);
+        assert_eq!(commitment, request_commitment(7, 1_000, &request));
+        assert_ne!(commitment, request_commitment(8, 1_000, &request));
+        assert_ne!(commitment, request_commitment(7, 1_001, &request));
+        assert_ne!(commitment, request_commitment(7, 1_000, &Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn verify_reveal_matches_default_table_payout() {
+        let commitment = request_commitment(7, 1_000, &Pubkey::new_unique());
+        let randomness = [42u8; 32];
+        let verified = verify_reveal(&commitment, &randomness, 10).unwrap();
+        let cards = card_outcomes(&randomness, 10, 0, 0, &DEFAULT_TIER_THRESHOLDS, 0, 10);
+        assert_eq!(verified.total_won_usd, cards.iter().map(|c| c.won_usd).sum::<u64>());
+        assert_eq!(verified.selected_pool_index, NO_PRIZE_POOL);
+
+        assert_eq!(
+            verify_reveal(&[0u8; 32], &randomness, 10).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+    }
+
     fn tier_roll(random_bytes: &[u8; 32]) -> u64 {
         let tier_entropy = u64::from_le_bytes(random_bytes[0..8].try_into().unwrap());
         tier_entropy % PROB_PRECISION
@@ -274,6 +675,22 @@ mod tests {
             prop_assert!(amount <= TIER4_MAX_USD); // 最大值: 99.9 USDC
         }
 
+        #[test]
+        fn tiered_distribution_is_reward_step_multiple(random_bytes in any::<[u8; 32]>()) {
+            let amount = map_to_tiered_distribution(&random_bytes);
+            prop_assert_eq!(amount % REWARD_STEP, 0);
+        }
+
+        #[test]
+        fn process_vrf_result_total_is_reward_step_multiple(
+            random_bytes in any::<[u8; 32]>(),
+            amount_of_cards in 1u32..=100,
+        ) {
+            let indices = [255u8; 50];
+            let result = process_vrf_result(&random_bytes, amount_of_cards, 0, &indices).unwrap();
+            prop_assert_eq!(result.total_won_usd % REWARD_STEP, 0);
+        }
+
         #[test]
         fn compute_pool_index_in_range(random_bytes in any::<[u8; 32]>(), pool_count in 1u64..=255) {
             let index = compute_pool_index(&random_bytes, pool_count);
@@ -440,15 +857,15 @@ mod tests {
         }
 
         #[test]
-        fn select_active_prize_pool_empty_returns_zero(random_bytes in any::<[u8; 32]>()) {
+        fn select_active_prize_pool_empty_returns_sentinel(random_bytes in any::<[u8; 32]>()) {
             // 没有活跃池
             let active_pool_indices = [255u8; 50];
             let active_pool_count = 0u8;
 
             let result = select_active_prize_pool(&random_bytes, active_pool_count, &active_pool_indices);
 
-            // 没有活跃池时返回 0 (或使用默认值)
-            prop_assert_eq!(result, 0);
+            // 没有活跃池时返回哨兵索引
+            prop_assert_eq!(result, NO_PRIZE_POOL);
         }
 
         #[test]
@@ -500,5 +917,449 @@ mod tests {
                 prop_assert!(diff <= tolerance, "Pool {} selected {} times, expected ~{}", i, counts[i], expected);
             }
         }
+
+        // ==================== 保底机制测试 ====================
+
+        #[test]
+        fn pity_disabled_matches_plain_result(
+            seed in any::<[u8; 32]>(),
+            cards in 1u32..=10,
+            counter in any::<u16>(),
+        ) {
+            let indices = [255u8; 50];
+            let plain = process_vrf_result(&seed, cards, 0, &indices).unwrap();
+            let pity = process_vrf_result_with_pity(
+                &seed,
+                cards,
+                0,
+                &indices,
+                &NO_OVERRIDES,
+                0,
+                counter,
+            )
+            .unwrap();
+            prop_assert_eq!(plain.total_won_usd, pity.total_won_usd);
+            prop_assert_eq!(pity.pity_upgrades, 0);
+        }
+
+        #[test]
+        fn pity_upgrade_roll_is_tier3_or_above(random_bytes in any::<[u8; 32]>()) {
+            let roll = pity_upgrade_roll(&random_bytes, &DEFAULT_TIER_THRESHOLDS);
+            prop_assert!(tier_id_from_roll(roll) >= 3);
+        }
+
+        #[test]
+        fn pity_never_lowers_total(
+            seed in any::<[u8; 32]>(),
+            cards in 1u32..=10,
+            threshold in 1u16..=10,
+            counter in 0u16..10,
+        ) {
+            let indices = [255u8; 50];
+            let plain = process_vrf_result(&seed, cards, 0, &indices).unwrap();
+            let pity = process_vrf_result_with_pity(
+                &seed,
+                cards,
+                0,
+                &indices,
+                &NO_OVERRIDES,
+                threshold,
+                counter,
+            )
+            .unwrap();
+            prop_assert!(pity.total_won_usd >= plain.total_won_usd);
+            prop_assert!(pity.pity_counter < threshold);
+        }
+    }
+
+    #[test]
+    fn rtp_modulation_bps_clamps_and_scales() {
+        // 目标余额或未设置目标: 不调节
+        assert_eq!(rtp_modulation_bps(100, 100, 500), 10_000);
+        assert_eq!(rtp_modulation_bps(100, 0, 500), 10_000);
+        // 余额低于目标 2%: 系数 9800
+        assert_eq!(rtp_modulation_bps(98, 100, 500), 9_800);
+        // 偏离超出上限时截断
+        assert_eq!(rtp_modulation_bps(0, 100, 500), 9_500);
+        assert_eq!(rtp_modulation_bps(1_000, 100, 500), 10_500);
+        // 上限为 0: 不调节
+        assert_eq!(rtp_modulation_bps(0, 100, 0), 10_000);
+    }
+
+    #[test]
+    fn apply_rtp_modulation_keeps_reward_step() {
+        assert_eq!(apply_rtp_modulation(10_000_000, 10_000), Some(10_000_000));
+        assert_eq!(apply_rtp_modulation(10_000_000, 9_500), Some(9_500_000));
+        // 9.97 USDC 向下取整到 9.9
+        assert_eq!(apply_rtp_modulation(14_000_000, 7_125), Some(9_900_000));
+        assert_eq!(apply_rtp_modulation(u64::MAX, 10_500), None);
+    }
+
+    #[test]
+    fn pity_forces_card_crossing_threshold() {
+        // 构造 Tier 1 的卡: 计数器 = 阈值 - 1 时下一张必定提升
+        let indices = [255u8; 50];
+        let mut found = false;
+        for i in 0u64..1000 {
+            let mut seed = [0u8; 32];
+            seed[0..8].copy_from_slice(&i.to_le_bytes());
+            if tier_id(&derive_random_result(&seed, 0)) > 2 {
+                continue;
+            }
+            let result =
+                process_vrf_result_with_pity(&seed, 1, 0, &indices, &NO_OVERRIDES, 5, 4).unwrap();
+            assert_eq!(result.pity_upgrades, 1);
+            assert_eq!(result.pity_counter, 0);
+            assert!(result.total_won_usd >= TIER3_MIN_USD);
+            found = true;
+            break;
+        }
+        assert!(found);
+    }
+
+    /// splitmix64: 测试用确定性伪随机源
+    fn splitmix64(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    #[test]
+    fn pity_expected_value_matches_analytic() {
+        // 单卡期望 (USDC): 各 Tier 均匀步进的均值
+        let tier_mean = |min: u64, steps: u64| {
+            (min as f64 + (steps - 1) as f64 * REWARD_STEP as f64 / 2.0) / 1_000_000.0
+        };
+        let p = |t: u64| t as f64 / PROB_PRECISION as f64;
+        let (p1, p2) = (p(TIER1_THRESHOLD), p(TIER2_THRESHOLD - TIER1_THRESHOLD));
+        let (p3, p4) = (p(TIER3_THRESHOLD - TIER2_THRESHOLD), p(PROB_PRECISION - TIER3_THRESHOLD));
+        let (m1, m2) = (tier_mean(TIER1_MIN_USD, TIER1_STEPS), tier_mean(TIER2_MIN_USD, TIER2_STEPS));
+        let (m3, m4) = (tier_mean(TIER3_MIN_USD, TIER3_STEPS), tier_mean(TIER4_MIN_USD, TIER4_STEPS));
+        let base_ev = p1 * m1 + p2 * m2 + p3 * m3 + p4 * m4;
+        let low_mean = (p1 * m1 + p2 * m2) / (p1 + p2);
+        let high_mean = (p3 * m3 + p4 * m4) / (p3 + p4);
+
+        let threshold = 3u16;
+        // 更新过程: 每个周期以 Tier 3+ 结束，被强制提升的比例 = q^N * p / (1 - q^N)
+        let q = p1 + p2;
+        let q_n = q.powi(threshold as i32);
+        let upgrade_rate = q_n * (1.0 - q) / (1.0 - q_n);
+        let pity_ev = base_ev + upgrade_rate * (high_mean - low_mean);
+
+        let indices = [255u8; 50];
+        let samples = 50_000u32;
+        let mut state = 42u64;
+        let mut counter = 0u16;
+        let (mut plain_total, mut pity_total, mut upgrades) = (0u64, 0u64, 0u32);
+        for _ in 0..samples {
+            let mut seed = [0u8; 32];
+            for chunk in seed.chunks_mut(8) {
+                chunk.copy_from_slice(&splitmix64(&mut state).to_le_bytes());
+            }
+            plain_total += process_vrf_result(&seed, 1, 0, &indices).unwrap().total_won_usd;
+            let r = process_vrf_result_with_pity(
+                &seed,
+                1,
+                0,
+                &indices,
+                &NO_OVERRIDES,
+                threshold,
+                counter,
+            )
+            .unwrap();
+            pity_total += r.total_won_usd;
+            upgrades += r.pity_upgrades;
+            counter = r.pity_counter;
+        }
+
+        let to_usd = |total: u64| total as f64 / samples as f64 / 1_000_000.0;
+        let within = |actual: f64, expected: f64| (actual - expected).abs() / expected < 0.03;
+        assert!(within(to_usd(plain_total), base_ev), "plain EV {} vs {}", to_usd(plain_total), base_ev);
+        assert!(within(to_usd(pity_total), pity_ev), "pity EV {} vs {}", to_usd(pity_total), pity_ev);
+        assert!(within(upgrades as f64 / samples as f64, upgrade_rate));
+        assert!(pity_total > plain_total);
+    }
+
+    /// 卡 i 的 (Tier 选择值, 步进熵)，与批量开奖的取值方式一致
+    fn card_entropy(randomness: &[u8; 32], i: u32) -> (u64, u64) {
+        let block = derive_card_block(randomness, i / CARDS_PER_DERIVATION);
+        split_card_entropy(entropy_chunk(&block, (i % CARDS_PER_DERIVATION) as usize))
+    }
+
+    fn chi_square(observed: &[u64], expected: &[f64]) -> f64 {
+        observed
+            .iter()
+            .zip(expected)
+            .map(|(&o, &e)| (o as f64 - e).powi(2) / e)
+            .sum()
+    }
+
+    fn random_seed(state: &mut u64) -> [u8; 32] {
+        let mut seed = [0u8; 32];
+        for chunk in seed.chunks_mut(8) {
+            chunk.copy_from_slice(&splitmix64(state).to_le_bytes());
+        }
+        seed
+    }
+
+    #[test]
+    fn batched_derivation_cuts_derivations_by_three_quarters() {
+        // 逐卡派生时 n 张卡需要 n 次
+        assert_eq!(derivations_for_cards(1), 1);
+        assert_eq!(derivations_for_cards(4), 1);
+        assert_eq!(derivations_for_cards(50), 13);
+        assert_eq!(derivations_for_cards(100), 25);
+    }
+
+    #[test]
+    fn batched_total_matches_per_card_sum() {
+        let indices = [255u8; 50];
+        let mut state = 7u64;
+        for _ in 0..200 {
+            let seed = random_seed(&mut state);
+            let expected: u64 = (0..100)
+                .map(|i| {
+                    let (roll, step) = card_entropy(&seed, i);
+                    amount_for_tier_roll(roll, step, &DEFAULT_TIER_THRESHOLDS)
+                })
+                .sum();
+            let result = process_vrf_result(&seed, 100, 0, &indices).unwrap();
+            assert_eq!(result.total_won_usd, expected);
+        }
+    }
+
+    #[test]
+    fn batched_tier_distribution_chi_square_per_slot() {
+        // 每个 slot 的 Tier 分布均须符合 15/50/30/5 (df = 3，p = 0.001 临界值 16.27)
+        let probs = [
+            TIER1_THRESHOLD,
+            TIER2_THRESHOLD - TIER1_THRESHOLD,
+            TIER3_THRESHOLD - TIER2_THRESHOLD,
+            PROB_PRECISION - TIER3_THRESHOLD,
+        ];
+        let mut counts = [[0u64; 4]; CARDS_PER_DERIVATION as usize];
+        let mut state = 11u64;
+        let seeds = 5_000u32;
+        for _ in 0..seeds {
+            let seed = random_seed(&mut state);
+            for i in 0..100u32 {
+                let (roll, _) = card_entropy(&seed, i);
+                let slot = (i % CARDS_PER_DERIVATION) as usize;
+                counts[slot][(tier_id_from_roll(roll) - 1) as usize] += 1;
+            }
+        }
+        let per_slot = (seeds * 100 / CARDS_PER_DERIVATION) as f64;
+        let expected: Vec<f64> = probs
+            .iter()
+            .map(|&p| per_slot * p as f64 / PROB_PRECISION as f64)
+            .collect();
+        for (slot, observed) in counts.iter().enumerate() {
+            let chi2 = chi_square(observed, &expected);
+            assert!(chi2 < 16.27, "slot {} chi-square {}", slot, chi2);
+        }
+    }
+
+    #[test]
+    fn batched_step_distribution_chi_square() {
+        // 步进熵与 Tier 取自同一 8 字节: Tier 1 内 21 个离散值仍应均匀 (df = 20，p = 0.001 临界值 45.31)
+        let mut counts = [0u64; TIER1_STEPS as usize];
+        let mut state = 13u64;
+        for _ in 0..5_000 {
+            let seed = random_seed(&mut state);
+            for i in 0..100u32 {
+                let (roll, step) = card_entropy(&seed, i);
+                if roll < TIER1_THRESHOLD {
+                    counts[(step % TIER1_STEPS) as usize] += 1;
+                }
+            }
+        }
+        let total: u64 = counts.iter().sum();
+        let expected = vec![total as f64 / TIER1_STEPS as f64; TIER1_STEPS as usize];
+        let chi2 = chi_square(&counts, &expected);
+        assert!(chi2 < 45.31, "step chi-square {}", chi2);
+    }
+
+    #[test]
+    fn batched_adjacent_slots_independent() {
+        // 同组相邻卡的 Tier 联合分布 (4x4，已知边际概率，df = 15，p = 0.001 临界值 37.70)
+        let mut joint = [0u64; 16];
+        let mut state = 17u64;
+        let seeds = 20_000u32;
+        for _ in 0..seeds {
+            let seed = random_seed(&mut state);
+            let (a, _) = card_entropy(&seed, 0);
+            let (b, _) = card_entropy(&seed, 1);
+            joint[((tier_id_from_roll(a) - 1) * 4 + tier_id_from_roll(b) - 1) as usize] += 1;
+        }
+        let p = [0.15, 0.50, 0.30, 0.05];
+        let expected: Vec<f64> = (0..16)
+            .map(|k| seeds as f64 * p[k / 4] * p[k % 4])
+            .collect();
+        let chi2 = chi_square(&joint, &expected);
+        assert!(chi2 < 37.70, "joint chi-square {}", chi2);
+    }
+
+    #[test]
+    fn card_outcomes_sum_matches_total_with_pity() {
+        let mut state = 29u64;
+        let indices = [0u8; 50];
+        for _ in 0..200 {
+            let seed = random_seed(&mut state);
+            let result =
+                process_vrf_result_with_pity(&seed, 23, 1, &indices, &NO_OVERRIDES, 3, 1).unwrap();
+            let cards = card_outcomes(&seed, 23, 3, 1, &DEFAULT_TIER_THRESHOLDS, 0, 23);
+            assert_eq!(cards.len(), 23);
+            assert_eq!(cards.iter().map(|c| c.won_usd).sum::<u64>(), result.total_won_usd);
+            assert!(cards.iter().all(|c| (1..=4).contains(&c.tier)));
+        }
+    }
+
+    #[test]
+    fn batched_reveal_matches_single_pass() {
+        let mut state = 61u64;
+        let indices = [0u8; 50];
+        for _ in 0..100 {
+            let seed = random_seed(&mut state);
+            let full =
+                process_vrf_result_with_pity(&seed, 97, 1, &indices, &NO_OVERRIDES, 4, 3).unwrap();
+            // 批次大小不是 4 的倍数，覆盖卡组中途续算
+            for batch in [1u32, 7, 50] {
+                let mut progress = RevealProgress::start(3);
+                let mut steps = 0;
+                while !progress.is_complete(97) {
+                    progress = advance_reveal(
+                        &seed,
+                        97,
+                        4,
+                        &DEFAULT_TIER_THRESHOLDS,
+                        progress,
+                        batch,
+                    )
+                    .unwrap();
+                    steps += 1;
+                }
+                assert_eq!(steps, 97u32.div_ceil(batch));
+                assert_eq!(progress.cards_processed, 97);
+                assert_eq!(progress.total_won_usd, full.total_won_usd);
+                assert_eq!(progress.pity_counter, full.pity_counter);
+                assert_eq!(progress.pity_upgrades, full.pity_upgrades);
+            }
+        }
+    }
+
+    #[test]
+    fn advance_reveal_is_noop_when_complete() {
+        let seed = random_seed(&mut 67u64);
+        let thresholds = &DEFAULT_TIER_THRESHOLDS;
+        let done = advance_reveal(&seed, 10, 0, thresholds, RevealProgress::start(0), 10).unwrap();
+        assert!(done.is_complete(10));
+        assert_eq!(advance_reveal(&seed, 10, 0, thresholds, done, 10).unwrap(), done);
+    }
+
+    #[test]
+    fn reveal_without_active_pools_selects_sentinel() {
+        let seed = random_seed(&mut 53u64);
+        let result =
+            process_vrf_result_with_pity(&seed, 5, 0, &[255u8; 50], &NO_OVERRIDES, 0, 0).unwrap();
+        assert_eq!(result.selected_pool_index, NO_PRIZE_POOL);
+        // 奖金计算不受奖品池影响
+        let with_pool =
+            process_vrf_result_with_pity(&seed, 5, 1, &[0u8; 50], &NO_OVERRIDES, 0, 0).unwrap();
+        assert_eq!(result.total_won_usd, with_pool.total_won_usd);
+    }
+
+    #[test]
+    fn card_outcomes_window_matches_full_sequence() {
+        let seed = random_seed(&mut 41u64);
+        let full = card_outcomes(&seed, 15, 4, 2, &DEFAULT_TIER_THRESHOLDS, 0, 15);
+        // 分批复算 (游标推进) 与一次性复算逐卡一致
+        let mut batched = Vec::new();
+        let mut cursor = 0;
+        while cursor < 15 {
+            let batch = card_outcomes(&seed, 15, 4, 2, &DEFAULT_TIER_THRESHOLDS, cursor, 4);
+            cursor += batch.len() as u32;
+            batched.extend(batch);
+        }
+        assert_eq!(batched, full);
+        // 超出卡数的窗口被截断
+        assert_eq!(card_outcomes(&seed, 15, 4, 2, &DEFAULT_TIER_THRESHOLDS, 14, 10).len(), 1);
+        assert!(card_outcomes(&seed, 15, 4, 2, &DEFAULT_TIER_THRESHOLDS, 15, 10).is_empty());
+    }
+
+    #[test]
+    fn tier_threshold_validation() {
+        assert!(is_valid_tier_thresholds(&DEFAULT_TIER_THRESHOLDS));
+        assert!(is_valid_tier_thresholds(&[0, 0, 0, PROB_PRECISION]));
+        // 非单调
+        assert!(!is_valid_tier_thresholds(&[650_000, 150_000, 950_000, PROB_PRECISION]));
+        // 末项须为 PROB_PRECISION
+        assert!(!is_valid_tier_thresholds(&[150_000, 650_000, 950_000, 990_000]));
+        assert!(!is_valid_tier_thresholds(&[150_000, 650_000, 950_000, PROB_PRECISION + 1]));
+        // Tier 3+ 区间为空时保底无法提升
+        assert!(!is_valid_tier_thresholds(&[0, PROB_PRECISION, PROB_PRECISION, PROB_PRECISION]));
+        assert_eq!(resolve_tier_thresholds(&NO_TIER_OVERRIDE), DEFAULT_TIER_THRESHOLDS);
+    }
+
+    #[test]
+    fn expected_card_value_tracks_thresholds() {
+        // 默认表: 0.9 + 5.25 + 9.585 + 3.7475 = 19.4825 USDC
+        assert_eq!(compute_expected_value(&DEFAULT_TIER_THRESHOLDS), 19_482_500);
+        // Tier 4 提升到 10% (取自 Tier 3): 19.4825 - 0.05 * 31.95 + 0.05 * 74.95
+        assert_eq!(
+            compute_expected_value(&[150_000, 650_000, 900_000, PROB_PRECISION]),
+            21_632_500
+        );
+    }
+
+    #[test]
+    fn default_expected_value_matches_ticket_price_basis() {
+        use crate::constants::TICKET_PRICE_MICRO_USD;
+
+        // 分层概率配置注释中的 19.48 USDC 期望，对应 10U 票价
+        assert_eq!(expected_value_micro_usd(), 19_482_500);
+        assert_eq!(TICKET_PRICE_MICRO_USD, 10_000_000);
+        assert!(expected_value_micro_usd() < 2 * TICKET_PRICE_MICRO_USD);
+    }
+
+    #[test]
+    fn rtp_bps_is_ratio_of_value_to_cost() {
+        assert_eq!(rtp_bps(19_482_500, 10_000_000), 19_482);
+        assert_eq!(rtp_bps(5_000_000, 10_000_000), 5_000);
+        assert_eq!(rtp_bps(1, 0), 0);
+        assert_eq!(rtp_bps(u64::MAX, 1), u64::MAX);
+    }
+
+    #[test]
+    fn tier_override_applies_to_selected_pool_only() {
+        // 池 7 (位置 1) 全部开出 Tier 4，池 3 使用默认表
+        let mut indices = [255u8; 50];
+        indices[0] = 3;
+        indices[1] = 7;
+        let mut overrides = NO_OVERRIDES;
+        overrides[1] = [0, 0, 0, PROB_PRECISION];
+        let mut state = 71u64;
+        let (mut boosted, mut default) = (0, 0);
+        for _ in 0..100 {
+            let seed = random_seed(&mut state);
+            let result =
+                process_vrf_result_with_pity(&seed, 12, 2, &indices, &overrides, 0, 0).unwrap();
+            let plain = process_vrf_result(&seed, 12, 2, &indices).unwrap();
+            assert_eq!(result.selected_pool_index, plain.selected_pool_index);
+
+            let position = (result.selected_pool_index == 7) as usize;
+            let thresholds = resolve_tier_thresholds(&overrides[position]);
+            let cards = card_outcomes(&seed, 12, 0, 0, &thresholds, 0, 12);
+            assert_eq!(cards.iter().map(|c| c.won_usd).sum::<u64>(), result.total_won_usd);
+            if result.selected_pool_index == 7 {
+                assert!(cards.iter().all(|c| c.tier == 4));
+                boosted += 1;
+            } else {
+                assert_eq!(result.total_won_usd, plain.total_won_usd);
+                default += 1;
+            }
+        }
+        assert!(boosted > 0 && default > 0);
     }
 }
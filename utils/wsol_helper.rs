@@ -4,11 +4,15 @@
 // 1. 从 native SOL 转账到 WSOL Account
 // 2. 调用 sync_native 同步 WSOL 余额
 // 3. 关闭 WSOL Account 回收 rent
+// 4. 将用户的 WSOL 支付解包为 Vault 的 native SOL (经同一指令内创建并关闭的临时账户)
 
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{program::invoke_signed, system_instruction};
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::{program_pack::Pack, system_instruction};
 use anchor_spl::token::spl_token;
 
+use crate::constants::NATIVE_SOL_MINT;
+
 /// 从 Vault PDA 向目标 WSOL Token Account 包装指定数量的 SOL
 ///
 /// # 流程
@@ -96,3 +100,93 @@ pub fn close_wsol_account<'info>(
 
     Ok(())
 }
+
+/// 将用户的 WSOL 支付解包为 Vault 的 native SOL
+///
+/// WSOL 支付若留在 Vault 的 WSOL 账户中，会被 swap 路由当作输入消耗，且不计入 Vault lamports
+/// (健康度、偿付与储备校验均按 lamports 计算)。此处经临时账户在同一指令内完成解包:
+///
+/// # 流程
+/// 1. Vault 出资创建临时 Token 账户 (PDA，owner 为 Vault)
+/// 2. spl_token::transfer_checked: 用户 WSOL -> 临时账户
+/// 3. spl_token::close_account: 临时账户的全部 lamports (支付额 + 租金) 转回 Vault
+///
+/// Vault 净增加 `amount` lamports，临时账户不留存
+///
+/// # 参数
+/// - `user` / `user_token_account`: 付款用户 (signer) 及其 WSOL Token Account
+/// - `temp_account`: 临时 WSOL 账户 PDA (须为空账户)
+/// - `wsol_mint`: NATIVE_SOL_MINT
+/// - `vault`: Vault PDA (出资方、临时账户 owner 及收款方)
+/// - `amount`: 支付的 lamports 数量
+/// - `temp_signer_seeds` / `vault_signer_seeds`: 临时账户与 Vault PDA 签名种子
+#[allow(clippy::too_many_arguments)]
+pub fn unwrap_wsol_payment<'info>(
+    user: &AccountInfo<'info>,
+    user_token_account: &AccountInfo<'info>,
+    temp_account: &AccountInfo<'info>,
+    wsol_mint: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+    temp_signer_seeds: &[&[u8]],
+    vault_signer_seeds: &[&[u8]],
+) -> Result<()> {
+    // 1. Vault 出资创建临时账户 (关闭时租金连同支付额一并回到 Vault)
+    let space = spl_token::state::Account::LEN;
+    let create_ix = system_instruction::create_account(
+        vault.key,
+        temp_account.key,
+        Rent::get()?.minimum_balance(space),
+        space as u64,
+        token_program.key,
+    );
+    invoke_signed(
+        &create_ix,
+        &[vault.clone(), temp_account.clone(), system_program.clone()],
+        &[vault_signer_seeds, temp_signer_seeds],
+    )?;
+
+    let init_ix = spl_token::instruction::initialize_account3(
+        token_program.key,
+        temp_account.key,
+        &NATIVE_SOL_MINT,
+        vault.key,
+    )?;
+    invoke(&init_ix, &[temp_account.clone(), wsol_mint.clone()])?;
+
+    // 2. 用户 WSOL -> 临时账户 (用户签名随外层指令传递)
+    let transfer_ix = spl_token::instruction::transfer_checked(
+        token_program.key,
+        user_token_account.key,
+        &NATIVE_SOL_MINT,
+        temp_account.key,
+        user.key,
+        &[],
+        amount,
+        spl_token::native_mint::DECIMALS,
+    )?;
+    invoke(
+        &transfer_ix,
+        &[
+            user_token_account.clone(),
+            wsol_mint.clone(),
+            temp_account.clone(),
+            user.clone(),
+        ],
+    )?;
+
+    // 3. 关闭临时账户，lamports 全部转回 Vault
+    close_wsol_account(
+        temp_account,
+        vault,
+        vault,
+        token_program,
+        &[vault_signer_seeds],
+    )?;
+
+    msg!("WSOL payment unwrapped: {} lamports to vault", amount);
+
+    Ok(())
+}